@@ -14,6 +14,10 @@ const entries: Array<{ entry: string; outfile: string }> = [
     outfile: "./dist/preload-settings.js",
   },
   { entry: "./src/settings.tsx", outfile: "./dist/settings.js" },
+  { entry: "./src/preload-prompt.ts", outfile: "./dist/preload-prompt.js" },
+  { entry: "./src/prompt.tsx", outfile: "./dist/prompt.js" },
+  { entry: "./src/preload-palette.ts", outfile: "./dist/preload-palette.js" },
+  { entry: "./src/palette.tsx", outfile: "./dist/palette.js" },
 ];
 
 await Bun.$`mkdir -p ./dist`;
@@ -1,4 +1,4 @@
-import type { IpcRequestMessage, ProviderId } from "./contracts";
+import { BATCH_METHOD, type BatchCall, type IpcRequestMessage, type ProviderId } from "./contracts";
 
 type PendingCallback = {
   resolve: (value: unknown) => void;
@@ -48,6 +48,25 @@ export class IpcClient {
     return { id, promise };
   }
 
+  /** Sends a JSON-RPC batch; resolves with the responses in call order. */
+  requestBatch(providerId: ProviderId, calls: BatchCall[]): Promise<unknown> {
+    const id = this.nextId++;
+    return new Promise<unknown>((resolve, reject) => {
+      this.callbacks.set(id, { resolve, reject });
+      postIpc({
+        id,
+        providerId,
+        method: BATCH_METHOD,
+        params: [],
+        batch: calls.map((call) => ({
+          id: call.id ?? null,
+          method: call.method,
+          params: Array.isArray(call.params) ? call.params : [],
+        })),
+      });
+    });
+  }
+
   notify(providerId: ProviderId, method: string, params: unknown[] = []) {
     postIpc({
       id: 0,
@@ -5,6 +5,10 @@ export const PROVIDER_IDS = {
   tabbar: "vibefi-tabbar",
   settings: "vibefi-settings",
   ipfs: "vibefi-ipfs",
+  network: "vibefi-network",
+  app: "vibefi-app",
+  prompt: "vibefi-prompt",
+  palette: "vibefi-palette",
 } as const;
 
 export type ProviderId = (typeof PROVIDER_IDS)[keyof typeof PROVIDER_IDS];
@@ -14,8 +18,18 @@ export type IpcRequestMessage = {
   providerId: ProviderId;
   method: string;
   params: unknown[];
+  /** Calls of a JSON-RPC batch, sent with method `vibefi_batch`. */
+  batch?: BatchCall[];
 };
 
+export type BatchCall = {
+  id?: unknown;
+  method: string;
+  params?: unknown[];
+};
+
+export const BATCH_METHOD = "vibefi_batch";
+
 export type RpcResponsePayload = {
   id: number;
   result: unknown;
@@ -32,27 +46,65 @@ export type WalletconnectPairingPayload = {
   qrSvg?: string;
 };
 
+export type WalletconnectRelayPayload = {
+  /** Unset while on the SDK's default relay. */
+  relayUrl?: string | null;
+  state?: "connecting" | "connected" | "reconnecting" | "failed";
+  attempt?: number;
+};
+
 export type Tab = {
   id?: string;
   label?: string;
   closable?: boolean;
   clickable?: boolean;
   loading?: boolean;
+  suspended?: boolean;
+  /** Primary account of the tab; `walletAccounts` counts all of them. */
+  wallet?: TabWallet | null;
+  walletAccounts?: number;
+  walletSwitchable?: boolean;
+  /** Relay state when the primary account is a WalletConnect one. */
+  walletRelay?: WalletconnectRelayPayload | null;
+  /** Usage of a tab over the heavy-tab limits; unset for other tabs. */
+  heavy?: TabUsagePayload | null;
+  badges?: TabBadges;
+};
+
+export type TabBadges = {
+  pendingRpc?: number;
+  awaitingSignature?: number;
+  updateAvailable?: boolean;
+  crashed?: boolean;
+};
+
+export type TabUsagePayload = {
+  heapUsed?: number | null;
+  heapLimit?: number | null;
+  cpuPercent?: number | null;
+};
+
+export type TabWallet = {
+  backend: "local" | "walletconnect" | "hardware" | "smartaccount" | "remote";
+  account: string;
 };
 
 export type TabbarUpdatePayload = {
   tabs?: Tab[];
   activeIndex?: number;
+  /** Set in a `--fork` session, e.g. "Fork of mainnet.example.com @ block 19000000". */
+  forkLabel?: string;
 };
 
-export type RpcStatusPayload = {
-  webviewId?: string;
-  pendingCount?: number;
-};
+export type QrPayload =
+  | { kind: "walletconnect"; uri: string }
+  | { kind: "airGapped"; urType: string; value: string }
+  | { kind: "address"; address: string; chainId?: number }
+  | { kind: "text"; value: string };
 
 export type HostDispatchMessage =
   | { kind: "rpcResponse"; payload: RpcResponsePayload }
   | { kind: "providerEvent"; payload: ProviderEventPayload }
   | { kind: "walletconnectPairing"; payload: WalletconnectPairingPayload }
-  | { kind: "tabbarUpdate"; payload: TabbarUpdatePayload }
-  | { kind: "rpcStatus"; payload: RpcStatusPayload };
+  | { kind: "walletconnectRelay"; payload: WalletconnectRelayPayload }
+  | { kind: "tabbarUpdate"; payload: TabbarUpdatePayload };
@@ -0,0 +1,13 @@
+/** `data` the client attaches to errors it has a hint for. */
+export type ErrorHintData = {
+  code: string;
+  hint: string;
+};
+
+/** The error's message, followed by the client's hint when it has one. */
+export function errorText(err: unknown): string {
+  if (!err || typeof err !== "object") return String(err);
+  const { message, data } = err as { message?: unknown; data?: Partial<ErrorHintData> };
+  const text = typeof message === "string" ? message : String(err);
+  return typeof data?.hint === "string" ? `${text}\nHint: ${data.hint}` : text;
+}
@@ -2,17 +2,17 @@ import type {
   HostDispatchMessage,
   ProviderEventPayload,
   RpcResponsePayload,
-  RpcStatusPayload,
   TabbarUpdatePayload,
   WalletconnectPairingPayload,
+  WalletconnectRelayPayload,
 } from "./contracts";
 
 export type HostDispatchHandlers = {
   onRpcResponse?: (payload: RpcResponsePayload) => void;
   onProviderEvent?: (payload: ProviderEventPayload) => void;
   onWalletconnectPairing?: (payload: WalletconnectPairingPayload) => void;
+  onWalletconnectRelay?: (payload: WalletconnectRelayPayload) => void;
   onTabbarUpdate?: (payload: TabbarUpdatePayload) => void;
-  onRpcStatus?: (payload: RpcStatusPayload) => void;
 };
 
 export function handleHostDispatch(message: unknown, handlers: HostDispatchHandlers) {
@@ -38,12 +38,12 @@ export function handleHostDispatch(message: unknown, handlers: HostDispatchHandl
     handlers.onWalletconnectPairing?.((candidate.payload ?? {}) as WalletconnectPairingPayload);
     return;
   }
-  if (candidate.kind === "tabbarUpdate") {
-    handlers.onTabbarUpdate?.((candidate.payload ?? {}) as TabbarUpdatePayload);
+  if (candidate.kind === "walletconnectRelay") {
+    handlers.onWalletconnectRelay?.((candidate.payload ?? {}) as WalletconnectRelayPayload);
     return;
   }
-  if (candidate.kind === "rpcStatus") {
-    handlers.onRpcStatus?.((candidate.payload ?? {}) as RpcStatusPayload);
+  if (candidate.kind === "tabbarUpdate") {
+    handlers.onTabbarUpdate?.((candidate.payload ?? {}) as TabbarUpdatePayload);
     return;
   }
 
@@ -1,5 +1,6 @@
 import React, { useEffect, useMemo, useState } from "react";
 import { createRoot } from "react-dom/client";
+import { errorText } from "./ipc/errors";
 import { composeStyles, sharedStyles } from "./styles/shared";
 
 type DappInfo = {
@@ -10,8 +11,22 @@ type DappInfo = {
   description: string;
   status: string;
   rootCid: string;
+  publisher?: string;
+  reported?: boolean;
+  pinned?: boolean;
+  offline?: boolean;
+  registry?: string;
+  registryLabel?: string;
+  warnings?: string[];
+  // data: URL of the manifest icon, from the client's registry metadata cache.
+  icon?: string;
 };
 
+// Dapp ids are only unique within one registry.
+function dappKey(item: DappInfo): string {
+  return item.registry ? `${item.registry}:${item.dappId}` : item.dappId;
+}
+
 type VibefiRequest = (args: { method: string; params?: unknown[] }) => Promise<unknown>;
 type ProviderEventHandler = (...args: unknown[]) => void;
 
@@ -73,6 +88,41 @@ const localStyles = `
     color: #9a3412;
     font-weight: 600;
   }
+  .pill.offline { border-color: #cbd5e1; background: #f1f5f9; color: #475569; }
+  .pill.registry { border-color: #bfdbfe; background: #eff6ff; color: #1d4ed8; }
+  .dapp-warning { margin-top: 4px; font-size: 12px; color: #9a3412; }
+  .pin {
+    border: none;
+    background: none;
+    padding: 0 4px;
+    cursor: pointer;
+    font-size: 15px;
+    color: #cbd5e1;
+  }
+  .pin.active { color: #f59e0b; }
+  .dapp-icon {
+    width: 20px;
+    height: 20px;
+    margin-right: 6px;
+    border-radius: 4px;
+    vertical-align: middle;
+    object-fit: contain;
+  }
+  .pill.reported { border-color: #fecaca; background: #fef2f2; color: #b91c1c; }
+  .report-form {
+    display: flex;
+    flex-direction: column;
+    gap: 8px;
+    margin-bottom: 16px;
+    padding: 10px 12px;
+    border-radius: 10px;
+    border: 1px solid #fecaca;
+    background: #fef2f2;
+    font-size: 14px;
+  }
+  .report-form textarea { min-height: 60px; font: inherit; }
+  .cid-input { flex: 1; padding: 6px 10px; border: 1px solid #cbd5e1; border-radius: 8px; font: inherit; }
+  .toggle { display: flex; align-items: center; gap: 6px; font-size: 13px; color: #475569; }
   .log {
     margin-top: 16px;
     background: #0f172a;
@@ -117,15 +167,32 @@ const localStyles = `
     color: #64748b;
     font-size: 12px;
   }
+  .versions {
+    margin-bottom: 16px;
+    padding: 10px 12px;
+    border-radius: 10px;
+    border: 1px solid #cbd5e1;
+    background: #ffffff;
+    font-size: 14px;
+  }
+  .versions-head { display: flex; justify-content: space-between; align-items: center; margin-bottom: 8px; }
+  .version-row { display: flex; align-items: center; gap: 8px; padding: 4px 0; }
+  .version-row .cid { color: #64748b; font-size: 12px; }
+  .link-button { border: none; background: none; padding: 0; color: #1d4ed8; cursor: pointer; font: inherit; }
+  .update-notice {
+    margin-bottom: 14px;
+    padding: 10px 12px;
+    border-radius: 10px;
+    border: 1px solid #fde68a;
+    background: #fffbeb;
+    color: #92400e;
+    font-size: 13px;
+  }
 `;
 const styles = composeStyles(sharedStyles, localStyles);
-
-function asErrorMessage(err: unknown): string {
-  if (err && typeof err === "object" && "message" in err && typeof (err as { message?: unknown }).message === "string") {
-    return (err as { message: string }).message;
-  }
-  return String(err);
-}
+const MAX_REPORT_REASON_CHARS = 500;
+// Launch errors for bundles whose manifest needs a newer client start with this.
+const UPDATE_REQUIRED = "Update required";
 
 function parseLaunchProgress(input: unknown): LaunchProgress | null {
   if (!input || typeof input !== "object") return null;
@@ -146,11 +213,23 @@ function App() {
   const [logs, setLogs] = useState<string[]>([]);
   const [busy, setBusy] = useState(false);
   const [launchProgress, setLaunchProgress] = useState<LaunchProgress | null>(null);
+  const [showReported, setShowReported] = useState(false);
+  const [reportReason, setReportReason] = useState<string | null>(null);
+  const [updateNotice, setUpdateNotice] = useState<string | null>(null);
+  const [cidInput, setCidInput] = useState("");
+  // Every published version of one dapp, newest first, for rolling back.
+  const [history, setHistory] = useState<{ item: DappInfo; versions: DappInfo[] } | null>(null);
 
+  const reportedCount = useMemo(() => items.filter((item) => item.reported).length, [items]);
+  const visibleItems = useMemo(
+    () => (showReported ? items : items.filter((item) => !item.reported)),
+    [items, showReported]
+  );
   const selectedItem = useMemo(() => {
     if (selectedIndex === null) return null;
-    return items[selectedIndex] ?? null;
-  }, [items, selectedIndex]);
+    return visibleItems[selectedIndex] ?? null;
+  }, [visibleItems, selectedIndex]);
+  const multipleRegistries = useMemo(() => items.some((item) => item.registry), [items]);
   const pausedItems = useMemo(
     () => items.filter((item) => item.status === "Paused"),
     [items]
@@ -176,9 +255,13 @@ function App() {
       const nextItems = Array.isArray(result) ? (result as DappInfo[]) : [];
       setItems(nextItems);
       setSelectedIndex(null);
-      addLog(`Found ${nextItems.length} dapps.`);
+      addLog(
+        nextItems.some((item) => item.offline)
+          ? `Registry unreachable; showing ${nextItems.length} cached pinned dapps.`
+          : `Found ${nextItems.length} dapps.`
+      );
     } catch (err) {
-      addLog(`Error: ${asErrorMessage(err)}`);
+      addLog(`Error: ${errorText(err)}`);
     } finally {
       setBusy(false);
     }
@@ -191,17 +274,156 @@ function App() {
       return;
     }
     setBusy(true);
+    setUpdateNotice(null);
     setLaunchProgress({
       stage: "prepare",
       message: "Preparing launch...",
       percent: 0,
     });
     addLog(`Launching ${selectedItem.name || ""} ${selectedItem.version || ""} (${selectedItem.rootCid})`);
+    for (const warning of selectedItem.warnings ?? []) {
+      addLog(`Warning: ${warning}`);
+    }
     try {
       await vibefiRequest("vibefi_launchDapp", [selectedItem.rootCid, selectedItem.name || selectedItem.rootCid]);
       addLog("Launch request sent.");
     } catch (err) {
-      addLog(`Error: ${asErrorMessage(err)}`);
+      const message = errorText(err);
+      if (message.startsWith(UPDATE_REQUIRED)) {
+        setUpdateNotice(`${selectedItem.name || selectedItem.rootCid}: ${message}`);
+      }
+      addLog(`Error: ${message}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const showVersions = async (item: DappInfo) => {
+    setBusy(true);
+    addLog(`Fetching versions of ${item.name || item.dappId}...`);
+    try {
+      const result = await vibefiRequest("vibefi_listDappVersions", [item.dappId, item.registry ?? null]);
+      setHistory({ item, versions: Array.isArray(result) ? (result as DappInfo[]) : [] });
+    } catch (err) {
+      addLog(`Error: ${errorText(err)}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const launchVersion = async (version: DappInfo) => {
+    const label = `${version.name || version.dappId} ${version.version || `v${version.versionId}`}`;
+    setBusy(true);
+    setUpdateNotice(null);
+    setLaunchProgress({
+      stage: "prepare",
+      message: "Preparing launch...",
+      percent: 0,
+    });
+    addLog(`Launching ${label} (${version.rootCid})`);
+    try {
+      await vibefiRequest("vibefi_launchDapp", [
+        version.rootCid,
+        version.name || version.rootCid,
+        { dappId: version.dappId, versionId: version.versionId, registry: version.registry },
+      ]);
+      addLog("Launch request sent.");
+    } catch (err) {
+      const message = errorText(err);
+      if (message.startsWith(UPDATE_REQUIRED)) {
+        setUpdateNotice(`${label}: ${message}`);
+      }
+      addLog(`Error: ${message}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const launchCid = async () => {
+    const rootCid = cidInput.trim();
+    if (!rootCid) return;
+    setBusy(true);
+    setUpdateNotice(null);
+    setLaunchProgress({
+      stage: "prepare",
+      message: "Preparing launch...",
+      percent: 0,
+    });
+    addLog(`Launching pasted CID ${rootCid}`);
+    try {
+      const result = (await vibefiRequest("vibefi_launchCid", [rootCid])) as { name: string; unverified: boolean };
+      addLog(
+        result.unverified
+          ? `${result.name} is not listed in any registry; it runs unverified, without capabilities or saved site data.`
+          : `${result.name} is listed in a registry; launched normally.`
+      );
+      setCidInput("");
+    } catch (err) {
+      const message = errorText(err);
+      if (message.startsWith(UPDATE_REQUIRED)) {
+        setUpdateNotice(`${rootCid}: ${message}`);
+      }
+      addLog(`Error: ${message}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const togglePin = async (item: DappInfo) => {
+    const pinned = !item.pinned;
+    try {
+      await vibefiRequest("vibefi_pinDapp", [
+        { dappId: item.dappId, name: item.name, rootCid: item.rootCid, registry: item.registry, pinned },
+      ]);
+      const key = dappKey(item);
+      setItems((prev) => prev.map((entry) => (dappKey(entry) === key ? { ...entry, pinned } : entry)));
+      addLog(pinned ? `Pinned ${item.name || item.dappId}; prefetching in the background.` : `Unpinned ${item.name || item.dappId}.`);
+    } catch (err) {
+      addLog(`Error: ${errorText(err)}`);
+    }
+  };
+
+  const exportSelected = async () => {
+    if (!selectedItem) return;
+    setBusy(true);
+    try {
+      const path = await vibefiRequest("vibefi_exportBundle", [
+        { rootCid: selectedItem.rootCid, name: selectedItem.name },
+      ]);
+      if (path) addLog(`Exported ${selectedItem.name || selectedItem.rootCid} to ${String(path)}`);
+    } catch (err) {
+      addLog(`Error: ${errorText(err)}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const submitReport = async () => {
+    if (!selectedItem || reportReason === null) return;
+    const reason = reportReason.trim();
+    if (!reason) {
+      addLog("Enter a reason before reporting.");
+      return;
+    }
+    setBusy(true);
+    addLog(`Reporting ${selectedItem.name || selectedItem.rootCid} #${selectedItem.dappId}...`);
+    try {
+      const report = (await vibefiRequest("vibefi_reportDapp", [
+        {
+          dappId: selectedItem.dappId,
+          versionId: selectedItem.versionId,
+          rootCid: selectedItem.rootCid,
+          registry: selectedItem.registry,
+          reason,
+        },
+      ])) as { txHash?: string } | null;
+      addLog(report?.txHash ? `Report submitted: ${report.txHash}` : "Report saved; this dapp is now hidden.");
+      const reportedKey = dappKey(selectedItem);
+      setItems((prev) => prev.map((item) => (dappKey(item) === reportedKey ? { ...item, reported: true } : item)));
+      setSelectedIndex(null);
+      setReportReason(null);
+    } catch (err) {
+      addLog(`Error: ${errorText(err)}`);
     } finally {
       setBusy(false);
     }
@@ -220,6 +442,21 @@ function App() {
     window.ethereum?.on?.("vibefiLaunchProgress", handler);
   }, []);
 
+  useEffect(() => {
+    // Icons fetched in the background after a listing, keyed by dappKey.
+    const handler: ProviderEventHandler = (payload) => {
+      if (!payload || typeof payload !== "object") return;
+      const icons = payload as Record<string, unknown>;
+      setItems((prev) =>
+        prev.map((item) => {
+          const icon = icons[dappKey(item)];
+          return typeof icon === "string" ? { ...item, icon } : item;
+        })
+      );
+    };
+    window.ethereum?.on?.("vibefiDappIcons", handler);
+  }, []);
+
   return (
     <>
       <style>{styles}</style>
@@ -236,8 +473,97 @@ function App() {
           >
             Launch selected
           </button>
+          <button
+            onClick={() => setReportReason("")}
+            disabled={busy || !selectedItem || selectedItem.reported || reportReason !== null}
+          >
+            Report selected
+          </button>
+          <button onClick={() => void exportSelected()} disabled={busy || !selectedItem}>
+            Export selected
+          </button>
           <button onClick={() => void vibefiRequest("vibefi_openSettings")}>Settings</button>
+          {reportedCount > 0 ? (
+            <label className="toggle">
+              <input
+                type="checkbox"
+                checked={showReported}
+                onChange={(event) => {
+                  setShowReported(event.target.checked);
+                  setSelectedIndex(null);
+                }}
+              />
+              Show reported ({reportedCount})
+            </label>
+          ) : null}
+        </div>
+        {reportReason !== null && selectedItem ? (
+          <div className="report-form">
+            <span>
+              Report {selectedItem.name || "(unnamed)"} #{selectedItem.dappId} as abusive. Reported dapps are
+              hidden from this list.
+            </span>
+            <textarea
+              value={reportReason}
+              maxLength={MAX_REPORT_REASON_CHARS}
+              placeholder="What is wrong with this dapp?"
+              onChange={(event) => setReportReason(event.target.value)}
+            />
+            <div className="row">
+              <button className="primary" onClick={() => void submitReport()} disabled={busy}>
+                Submit report
+              </button>
+              <button onClick={() => setReportReason(null)} disabled={busy}>
+                Cancel
+              </button>
+            </div>
+          </div>
+        ) : null}
+        <div className="row">
+          <input
+            className="cid-input"
+            value={cidInput}
+            placeholder="Paste a bundle root CID (bafy… or ipfs://…)"
+            onChange={(event) => setCidInput(event.target.value)}
+            onKeyDown={(event) => {
+              if (event.key === "Enter") void launchCid();
+            }}
+          />
+          <button onClick={() => void launchCid()} disabled={busy || !cidInput.trim()}>
+            Open unlisted CID
+          </button>
         </div>
+        {cidInput.trim() ? (
+          <div className="notice">
+            Bundles opened by CID are unverified unless a registry lists them. They get no capabilities from their
+            manifest, no remembered permissions, and site data that is discarded when the tab closes.
+          </div>
+        ) : null}
+        {updateNotice ? <div className="update-notice">{updateNotice}</div> : null}
+        {history ? (
+          <div className="versions">
+            <div className="versions-head">
+              <strong>Versions of {history.item.name || "(unnamed)"} #{history.item.dappId}</strong>
+              <button onClick={() => setHistory(null)}>Close</button>
+            </div>
+            {history.versions.map((version) => (
+              <div className="version-row" key={version.versionId}>
+                <button
+                  onClick={() => void launchVersion(version)}
+                  disabled={busy || !version.rootCid || version.status === "Paused"}
+                >
+                  Launch
+                </button>
+                <span>
+                  {version.version || `v${version.versionId}`}
+                  {version.versionId === history.item.versionId ? " (latest)" : ""}
+                </span>
+                {version.status !== "Published" && <span className="pill">{version.status}</span>}
+                <span className="cid">{version.rootCid}</span>
+              </div>
+            ))}
+          </div>
+        ) : null}
         {busy && launchProgress ? (
           <div className="progress-card">
             <div className="progress-head">
@@ -267,14 +593,14 @@ function App() {
             </tr>
           </thead>
           <tbody>
-            {items.length === 0 ? (
+            {visibleItems.length === 0 ? (
               <tr>
                 <td colSpan={4}>No dapps found.</td>
               </tr>
             ) : (
-              items.map((item, idx) => (
+              visibleItems.map((item, idx) => (
                 <tr
-                  key={`${item.dappId}:${item.versionId}:${item.rootCid}`}
+                  key={`${dappKey(item)}:${item.versionId}:${item.rootCid}`}
                   className={`dapp-row ${item.status === "Published" ? "" : "unavailable"}`}
                 >
                   <td>
@@ -282,15 +608,44 @@ function App() {
                       type="radio"
                       name="select"
                       checked={selectedIndex === idx}
-                      onChange={() => setSelectedIndex(idx)}
+                      onChange={() => {
+                        setSelectedIndex(idx);
+                        setReportReason(null);
+                      }}
                       disabled={item.status !== "Published"}
                     />
                   </td>
                   <td>
+                    <button
+                      className={`pin${item.pinned ? " active" : ""}`}
+                      title={item.pinned ? "Unpin" : "Pin: keep the latest bundle downloaded"}
+                      onClick={() => void togglePin(item)}
+                      disabled={item.offline}
+                    >
+                      {item.pinned ? "\u2605" : "\u2606"}
+                    </button>
+                    {item.icon && <img className="dapp-icon" src={item.icon} alt="" />}
                     {item.name || "(unnamed)"} #{item.dappId}
                     {item.status === "Paused" && <span className="pill">Paused</span>}
+                    {item.reported && <span className="pill reported">Reported</span>}
+                    {item.offline && <span className="pill offline">Offline</span>}
+                    {multipleRegistries && item.registryLabel && (
+                      <span className="pill registry" title={item.registry ?? "Configured registry"}>
+                        {item.registryLabel}
+                      </span>
+                    )}
+                    {(item.warnings ?? []).map((warning) => (
+                      <div className="dapp-warning" key={warning}>{warning}</div>
+                    ))}
+                  </td>
+                  <td>
+                    {item.version || `v${item.versionId || ""}`}{" "}
+                    {!item.offline && (
+                      <button className="link-button" onClick={() => void showVersions(item)} disabled={busy}>
+                        history
+                      </button>
+                    )}
                   </td>
-                  <td>{item.version || `v${item.versionId || ""}`}</td>
                   <td>{item.rootCid || ""}</td>
                 </tr>
               ))
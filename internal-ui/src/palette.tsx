@@ -0,0 +1,195 @@
+import React, { useEffect, useMemo, useRef, useState } from "react";
+import { createRoot } from "react-dom/client";
+import { IpcClient } from "./ipc/client";
+import { PROVIDER_IDS } from "./ipc/contracts";
+import {
+  composeStyles,
+  sharedFeedbackStyles,
+  sharedFormFieldStyles,
+  sharedStyles,
+  sharedSurfaceStyles,
+} from "./styles/shared";
+
+declare global {
+  interface Window {
+    __WryEthereumResolve?: (id: number, result: unknown, error: unknown) => void;
+    __WryEthereumEmit?: (event: string, payload: unknown) => void;
+  }
+}
+
+type PaletteCommand = {
+  id: string;
+  title: string;
+  group: string;
+  shortcut?: string;
+  keywords?: string;
+};
+
+const localStyles = `
+  body { background: rgba(15, 23, 42, 0.45); }
+  .palette { max-width: 560px; margin: 60px auto 0; padding: 12px; }
+  .field input { font-size: 15px; padding: 10px 12px; }
+  .results { max-height: 60vh; overflow-y: auto; margin-top: 8px; }
+  .group { font-size: 11px; color: #64748b; text-transform: uppercase; margin: 10px 8px 4px; }
+  .command {
+    display: flex;
+    justify-content: space-between;
+    gap: 12px;
+    padding: 8px;
+    border-radius: 8px;
+    font-size: 13px;
+    cursor: pointer;
+  }
+  .command.selected { background: #f1f5f9; }
+  .shortcut { color: #64748b; font-size: 12px; white-space: nowrap; }
+  .empty { padding: 12px 8px; }
+`;
+
+const styles = composeStyles(
+  sharedStyles,
+  sharedFormFieldStyles,
+  sharedFeedbackStyles,
+  sharedSurfaceStyles,
+  localStyles
+);
+
+const paletteClient = new IpcClient();
+
+window.__WryEthereumResolve = (id: number, result: unknown, error: unknown) => {
+  paletteClient.resolve(id, result, error);
+};
+
+function paletteIpc(method: string, params: unknown[] = []): Promise<unknown> {
+  return paletteClient.request(PROVIDER_IDS.palette, method, params);
+}
+
+function closePalette() {
+  void paletteIpc("vibefi_closePalette").catch(() => {});
+}
+
+/**
+ * Scores `text` against `query` as a subsequence match, or returns null when
+ * some query character is missing. Consecutive matches and matches at word
+ * starts score higher, so "sw eth" ranks "Switch to Ethereum" first.
+ */
+function fuzzyScore(query: string, text: string): number | null {
+  const haystack = text.toLowerCase();
+  let score = 0;
+  let position = 0;
+  let previous = -2;
+  for (const char of query.toLowerCase()) {
+    if (char === " ") continue;
+    const found = haystack.indexOf(char, position);
+    if (found < 0) return null;
+    score += found === previous + 1 ? 3 : 1;
+    if (found === 0 || haystack[found - 1] === " ") score += 2;
+    previous = found;
+    position = found + 1;
+  }
+  return score;
+}
+
+function filterCommands(commands: PaletteCommand[], query: string): PaletteCommand[] {
+  if (!query.trim()) return commands;
+  return commands
+    .map((command) => {
+      const title = fuzzyScore(query, command.title);
+      const keywords = command.keywords ? fuzzyScore(query, command.keywords) : null;
+      // A title match beats one on keywords alone.
+      const score = title !== null ? title + 1 : keywords;
+      return { command, score };
+    })
+    .filter((entry): entry is { command: PaletteCommand; score: number } => entry.score !== null)
+    .sort((a, b) => b.score - a.score)
+    .map((entry) => entry.command);
+}
+
+function App() {
+  const [commands, setCommands] = useState<PaletteCommand[]>([]);
+  const [query, setQuery] = useState("");
+  const [selected, setSelected] = useState(0);
+  const [error, setError] = useState("");
+  const selectedRef = useRef<HTMLDivElement | null>(null);
+
+  useEffect(() => {
+    paletteIpc("vibefi_listCommands")
+      .then((value) => setCommands(Array.isArray(value) ? (value as PaletteCommand[]) : []))
+      .catch((err: any) => setError(err?.message || String(err)));
+  }, []);
+
+  const matches = useMemo(() => filterCommands(commands, query), [commands, query]);
+
+  useEffect(() => setSelected(0), [query]);
+  useEffect(() => selectedRef.current?.scrollIntoView({ block: "nearest" }), [selected]);
+
+  const run = async (command: PaletteCommand | undefined) => {
+    if (!command) return;
+    try {
+      await paletteIpc("vibefi_runCommand", [command.id]);
+    } catch (err: any) {
+      setError(err?.message || String(err));
+    }
+  };
+
+  const onKeyDown = (event: React.KeyboardEvent) => {
+    if (event.key === "Escape") {
+      closePalette();
+    } else if (event.key === "ArrowDown") {
+      event.preventDefault();
+      setSelected((index) => Math.min(index + 1, matches.length - 1));
+    } else if (event.key === "ArrowUp") {
+      event.preventDefault();
+      setSelected((index) => Math.max(index - 1, 0));
+    } else if (event.key === "Enter") {
+      void run(matches[selected]);
+    }
+  };
+
+  return (
+    <>
+      <style>{styles}</style>
+      <div className="palette surface-card" onKeyDown={onKeyDown}>
+        <div className="field">
+          <input
+            autoFocus
+            placeholder="Type a command, dapp or tab"
+            value={query}
+            onChange={(event) => setQuery(event.target.value)}
+            onBlur={(event) => event.target.focus()}
+          />
+        </div>
+        {error && <div className="error">{error}</div>}
+        <div className="results">
+          {matches.length === 0 && <div className="empty">No matching commands</div>}
+          {matches.map((command, index) => (
+            <React.Fragment key={command.id}>
+              {!query.trim() && matches[index - 1]?.group !== command.group && (
+                <div className="group">{command.group}</div>
+              )}
+              <div
+                ref={index === selected ? selectedRef : undefined}
+                className={`command${index === selected ? " selected" : ""}`}
+                onMouseMove={() => setSelected(index)}
+                onMouseDown={(event) => event.preventDefault()}
+                onClick={() => void run(command)}
+              >
+                <span>{command.title}</span>
+                {command.shortcut && <span className="shortcut">{command.shortcut}</span>}
+              </div>
+            </React.Fragment>
+          ))}
+        </div>
+      </div>
+    </>
+  );
+}
+
+// Clicking outside the palette dismisses it.
+document.addEventListener("mousedown", (event) => {
+  if (!(event.target as HTMLElement).closest(".palette")) closePalette();
+});
+
+const rootEl = document.getElementById("root");
+if (rootEl) {
+  createRoot(rootEl).render(<App />);
+}
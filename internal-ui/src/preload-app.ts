@@ -1,14 +1,45 @@
 import { IpcClient } from "./ipc/client";
-import { PROVIDER_IDS } from "./ipc/contracts";
+import { PROVIDER_IDS, type QrPayload } from "./ipc/contracts";
 import { handleHostDispatch } from "./ipc/host-dispatch";
+import { decodeQrFile, scanQr, type QrDecodeSource } from "./qr-scanner";
 
 type Eip1193RequestArgs = {
   method: string;
   params?: unknown[];
 };
+type Eip1193BatchCall = Eip1193RequestArgs & { id?: unknown; jsonrpc?: string };
 
 type Listener = (...args: unknown[]) => void;
 type IpfsListener = (payload: unknown) => void;
+type OpenedFile = {
+  name: string;
+  size: number;
+  mimeType: string;
+  data: string;
+};
+type SaveFileInput = { name: string; data?: string; text?: string };
+type DappMessage = {
+  id: string;
+  from: { dappId: string; name: string };
+  replyTo?: string | null;
+  data: unknown;
+};
+type MessageHandler = (message: DappMessage) => unknown;
+// Square VibeFi mark for EIP-6963 wallet pickers.
+const PROVIDER_ICON =
+  "data:image/svg+xml," +
+  encodeURIComponent(
+    '<svg xmlns="http://www.w3.org/2000/svg" width="96" height="96" viewBox="0 0 96 96">' +
+      '<rect width="96" height="96" rx="20" fill="#111827"/>' +
+      '<path d="M24 28h12l12 30 12-30h12L54 70H42z" fill="#a78bfa"/>' +
+      "</svg>"
+  );
+
+type HttpFetchInit = {
+  method?: "GET" | "POST";
+  headers?: Record<string, string>;
+  body?: string;
+};
 
 declare global {
   interface Window {
@@ -23,7 +54,7 @@ declare global {
     ethereum?: {
       isWry: boolean;
       isMetaMask: boolean;
-      request: (args: Eip1193RequestArgs) => Promise<unknown>;
+      request: (args: Eip1193RequestArgs | Eip1193BatchCall[]) => Promise<unknown>;
       on: (event: string, handler: Listener) => void;
       removeListener: (event: string, handler: Listener) => void;
       off: (event: string, handler: Listener) => void;
@@ -31,6 +62,8 @@ declare global {
     };
     vibefi?: {
       request: (args: Eip1193RequestArgs) => Promise<unknown>;
+      getClientInfo: () => Promise<unknown>;
+      resolveEns: (nameOrAddress: string) => Promise<unknown>;
     };
     vibefiIpfs?: {
       request: (args: Eip1193RequestArgs) => Promise<unknown>;
@@ -41,12 +74,39 @@ declare global {
       off: (event: "progress", handler: IpfsListener) => void;
       removeListener: (event: "progress", handler: IpfsListener) => void;
     };
+    vibefiNetwork?: {
+      request: (args: Eip1193RequestArgs) => Promise<unknown>;
+      fetch: (url: string, init?: HttpFetchInit) => Promise<unknown>;
+    };
+    vibefiQr?: {
+      scan: () => Promise<QrPayload>;
+      decodeImage: (file: Blob) => Promise<QrPayload[]>;
+    };
+    vibefiNotifications?: {
+      notify: (title: string, body?: string) => Promise<unknown>;
+    };
+    vibefiFiles?: {
+      open: () => Promise<OpenedFile | null>;
+      save: (file: SaveFileInput) => Promise<boolean>;
+    };
+    vibefiMessages?: {
+      send: (to: string, data: unknown) => Promise<string>;
+      request: (to: string, data: unknown, timeoutMs?: number) => Promise<unknown>;
+      onMessage: (handler: MessageHandler) => () => void;
+    };
+    vibefiEvents?: {
+      subscribe: (topic: string, handler: Listener) => Promise<string>;
+      unsubscribe: (subscription: string) => Promise<boolean>;
+    };
     updateTabs?: (tabs: unknown[], activeIndex: number) => void;
   };
 
   const ipc = new IpcClient();
   const listeners = new Map<string, Set<Listener>>();
   const ipfsListeners = new Map<string, Set<IpfsListener>>();
+  const subscriptionHandlers = new Map<string, Listener>();
+  const messageHandlers = new Set<MessageHandler>();
+  const pendingReplies = new Map<string, (data: unknown) => void>();
 
   function on(event: string, handler: Listener) {
     if (typeof handler !== "function") return;
@@ -99,7 +159,11 @@ declare global {
     }
   }
 
-  async function request(args: Eip1193RequestArgs) {
+  async function request(args: Eip1193RequestArgs | Eip1193BatchCall[]) {
+    // Batch transports send an array of JSON-RPC requests.
+    if (Array.isArray(args)) {
+      return await ipc.requestBatch(PROVIDER_IDS.provider, args);
+    }
     const method = args?.method;
     const params = Array.isArray(args?.params) ? args.params : [];
     return await ipc.request(PROVIDER_IDS.provider, method, params);
@@ -120,6 +184,84 @@ declare global {
     return { ipcId: id, response: promise };
   }
 
+  function requestNetwork(args: Eip1193RequestArgs): Promise<unknown> {
+    const method = args?.method;
+    const params = Array.isArray(args?.params) ? args.params : [];
+    return ipc.request(PROVIDER_IDS.network, method, params);
+  }
+
+  function requestApp(method: string, params: unknown[] = []): Promise<unknown> {
+    return ipc.request(PROVIDER_IDS.app, method, params);
+  }
+
+  function decodeQr(source: QrDecodeSource): Promise<QrPayload[]> {
+    return requestApp("vibefi_decodeQr", [source]) as Promise<QrPayload[]>;
+  }
+
+  // Route programmatic clipboard writes through the host, which shows the
+  // user any address being copied before it lands on the clipboard. Every
+  // other way for script to write the clipboard is closed.
+  function copyToClipboard(text: string): Promise<unknown> {
+    return requestApp("vibefi_copyToClipboard", [String(text)]);
+  }
+
+  function lockMethod(target: object, name: string, value: unknown) {
+    try {
+      Object.defineProperty(target, name, { value, configurable: false, writable: false });
+    } catch (error) {
+      console.warn(`[vibefi:preload] failed to intercept ${name}`, error);
+    }
+  }
+
+  if (typeof Clipboard !== "undefined") {
+    lockMethod(Clipboard.prototype, "writeText", (text: string) =>
+      copyToClipboard(text).then(() => undefined),
+    );
+    lockMethod(Clipboard.prototype, "write", () =>
+      Promise.reject(new DOMException("use navigator.clipboard.writeText", "NotAllowedError")),
+    );
+  }
+
+  // A copy handler can replace the copied content through clipboardData. This
+  // capture listener runs before any the dapp registers and sends plain text
+  // through the host instead; other formats are dropped.
+  for (const type of ["copy", "cut"]) {
+    window.addEventListener(
+      type,
+      (event) => {
+        const data = (event as ClipboardEvent).clipboardData;
+        if (!data) return;
+        lockMethod(data, "setData", (format: string, value: string) => {
+          if (format !== "text/plain" && format !== "text") return;
+          event.preventDefault();
+          copyToClipboard(value).catch((error) => {
+            console.warn("[vibefi:preload] clipboard write rejected", error);
+          });
+        });
+      },
+      { capture: true },
+    );
+  }
+
+  const originalExecCommand = Document.prototype.execCommand;
+  lockMethod(
+    Document.prototype,
+    "execCommand",
+    function (this: Document, commandId: string, showUI?: boolean, value?: string) {
+      const command = commandId.toLowerCase();
+      if (command === "copy" || command === "cut") {
+        const selected = window.getSelection()?.toString() ?? "";
+        if (selected) {
+          copyToClipboard(selected).catch((error) => {
+            console.warn("[vibefi:preload] clipboard write rejected", error);
+          });
+        }
+        return true;
+      }
+      return originalExecCommand.call(this, commandId, showUI, value);
+    },
+  );
+
   globalWindow.__WryEthereumEmit = (event: string, payload: unknown) => {
     emit(event, payload);
   };
@@ -138,6 +280,20 @@ declare global {
           emitIpfs("progress", payload.value);
           return;
         }
+        if (payload.event === "vibefiMessage") {
+          handleDappMessage(payload.value as DappMessage);
+          return;
+        }
+        if (payload.event === "vibefiSubscription") {
+          const delivery = (payload.value ?? {}) as { subscription?: string; data?: unknown };
+          const handler = subscriptionHandlers.get(String(delivery.subscription));
+          try {
+            handler?.(delivery.data);
+          } catch (error) {
+            console.warn("[vibefi:preload] subscription handler threw", delivery.subscription, error);
+          }
+          return;
+        }
         emit(payload.event, payload.value);
       },
       onWalletconnectPairing: (payload) => {
@@ -172,11 +328,43 @@ declare global {
     });
   }
 
+  // randomUUID needs a secure context, which custom schemes are not everywhere.
+  function randomUuid(): string {
+    if (typeof crypto.randomUUID === "function") return crypto.randomUUID();
+    const bytes = crypto.getRandomValues(new Uint8Array(16));
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    const hex = Array.from(bytes, (byte) => byte.toString(16).padStart(2, "0")).join("");
+    return `${hex.slice(0, 8)}-${hex.slice(8, 12)}-${hex.slice(12, 16)}-${hex.slice(16, 20)}-${hex.slice(20)}`;
+  }
+
+  // EIP-6963: announce the provider to dapps that discover wallets by event
+  // instead of reading window.ethereum. The uuid is fresh for each page load.
+  const providerInfo = Object.freeze({
+    uuid: randomUuid(),
+    name: "VibeFi",
+    icon: PROVIDER_ICON,
+    rdns: "dev.vibefi",
+  });
+
+  function announceProvider() {
+    window.dispatchEvent(
+      new CustomEvent("eip6963:announceProvider", {
+        detail: Object.freeze({ info: providerInfo, provider: ethereum }),
+      })
+    );
+  }
+
+  window.addEventListener("eip6963:requestProvider", announceProvider);
+  announceProvider();
+
   globalWindow.vibefi = {
     request: ({ method, params }: Eip1193RequestArgs) => {
       const list = Array.isArray(params) ? params : [];
       return ipc.request(PROVIDER_IDS.launcher, method, list);
     },
+    getClientInfo: () => requestApp("vibefi_getClientInfo"),
+    resolveEns: (nameOrAddress: string) => requestApp("vibefi_resolveEns", [String(nameOrAddress)]),
   };
 
   globalWindow.vibefiIpfs = {
@@ -187,6 +375,83 @@ declare global {
     removeListener: offIpfs,
   };
 
+  globalWindow.vibefiNetwork = {
+    request: requestNetwork,
+    fetch: (url: string, init?: HttpFetchInit) =>
+      requestNetwork({ method: "vibefi_httpFetch", params: [url, init ?? {}] }),
+  };
+
+  globalWindow.vibefiQr = {
+    scan: () => scanQr(decodeQr),
+    decodeImage: (file: Blob) => decodeQrFile(file, decodeQr),
+  };
+
+  globalWindow.vibefiNotifications = {
+    notify: (title: string, body?: string) =>
+      requestApp("vibefi_notify", [String(title), body === undefined ? "" : String(body)]),
+  };
+
+  globalWindow.vibefiFiles = {
+    open: () => requestApp("vibefi_openFile") as Promise<OpenedFile | null>,
+    save: async (file: SaveFileInput) => (await requestApp("vibefi_saveFile", [file])) === true,
+  };
+
+  function postMessage(to: string, data: unknown, replyTo?: string): Promise<string> {
+    return requestApp("vibefi_postMessage", [{ to: String(to), data, replyTo }]).then(String);
+  }
+
+  // Replies settle the matching request; other messages go to onMessage
+  // handlers, and the first defined value one returns is sent back as the reply.
+  function handleDappMessage(message: DappMessage) {
+    if (!message || typeof message.id !== "string") return;
+    if (message.replyTo) {
+      pendingReplies.get(message.replyTo)?.(message.data);
+      pendingReplies.delete(message.replyTo);
+      return;
+    }
+    for (const handler of Array.from(messageHandlers)) {
+      Promise.resolve()
+        .then(() => handler(message))
+        .then((reply) => {
+          if (reply !== undefined) return postMessage(message.from.dappId, reply, message.id);
+        })
+        .catch((error) => console.warn("[vibefi:preload] message handler failed", message.id, error));
+    }
+  }
+
+  globalWindow.vibefiMessages = {
+    send: (to: string, data: unknown) => postMessage(to, data),
+    request: async (to: string, data: unknown, timeoutMs = 30_000) => {
+      const id = await postMessage(to, data);
+      return new Promise((resolve, reject) => {
+        const timer = setTimeout(() => {
+          pendingReplies.delete(id);
+          reject(new Error(`no reply from dapp ${to}`));
+        }, timeoutMs);
+        pendingReplies.set(id, (reply) => {
+          clearTimeout(timer);
+          resolve(reply);
+        });
+      });
+    },
+    onMessage: (handler: MessageHandler) => {
+      messageHandlers.add(handler);
+      return () => messageHandlers.delete(handler);
+    },
+  };
+
+  globalWindow.vibefiEvents = {
+    subscribe: async (topic: string, handler: Listener) => {
+      const subscription = String(await requestApp("vibefi_subscribe", [topic]));
+      subscriptionHandlers.set(subscription, handler);
+      return subscription;
+    },
+    unsubscribe: async (subscription: string) => {
+      subscriptionHandlers.delete(subscription);
+      return (await requestApp("vibefi_unsubscribe", [subscription])) === true;
+    },
+  };
+
   Promise.resolve().then(async () => {
     try {
       const chainId = await request({ method: "eth_chainId", params: [] });
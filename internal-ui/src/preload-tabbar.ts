@@ -3,8 +3,7 @@ import { handleHostDispatch } from "./ipc/host-dispatch";
 declare global {
   interface Window {
     __VibefiTabbarState?: unknown;
-    updateTabs?: (tabs: unknown[], activeIndex: number) => void;
-    updateRpcStatus?: (webviewId: string, pendingCount: number) => void;
+    updateTabs?: (tabs: unknown[], activeIndex: number, forkLabel?: string) => void;
     __VibefiHostDispatch?: (message: unknown) => void;
   }
 }
@@ -18,12 +17,7 @@ declare global {
         onTabbarUpdate: (payload) => {
           window.__VibefiTabbarState = payload;
           if (typeof window.updateTabs === "function") {
-            window.updateTabs(payload.tabs ?? [], payload.activeIndex ?? 0);
-          }
-        },
-        onRpcStatus: (payload) => {
-          if (typeof window.updateRpcStatus === "function") {
-            window.updateRpcStatus(payload.webviewId ?? "", payload.pendingCount ?? 0);
+            window.updateTabs(payload.tabs ?? [], payload.activeIndex ?? 0, payload.forkLabel);
           }
         },
       });
@@ -33,6 +33,11 @@ declare global {
             new CustomEvent("vibefi:walletconnect-pairing", { detail: payload ?? {} })
           );
         },
+        onWalletconnectRelay: (payload) => {
+          window.dispatchEvent(
+            new CustomEvent("vibefi:walletconnect-relay", { detail: payload ?? {} })
+          );
+        },
       });
     };
 })();
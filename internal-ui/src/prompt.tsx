@@ -0,0 +1,324 @@
+import React, { useEffect, useState } from "react";
+import { createRoot } from "react-dom/client";
+import { IpcClient } from "./ipc/client";
+import { PROVIDER_IDS } from "./ipc/contracts";
+import {
+  composeStyles,
+  sharedFeedbackStyles,
+  sharedPageStyles,
+  sharedStyles,
+  sharedSurfaceStyles,
+  sharedUtilityStyles,
+} from "./styles/shared";
+
+declare global {
+  interface Window {
+    __WryEthereumResolve?: (id: number, result: unknown, error: unknown) => void;
+    __WryEthereumEmit?: (event: string, payload: unknown) => void;
+  }
+}
+
+type DappIdentity = {
+  name: string;
+  dappId?: string;
+  rootCid?: string;
+  publisher?: string;
+  registry?: string;
+};
+type PromptRequest = {
+  id: number;
+  kind: string;
+  title: string;
+  message: string;
+  requester?: DappIdentity;
+  details: unknown;
+  rememberable: boolean;
+  queued: number;
+};
+
+const PROMPT_EVENT = "vibefiPrompt";
+// The lock screen: answered with a passphrase and never denied.
+const UNLOCK_PROMPT = "unlock";
+
+const localStyles = `
+  body { background: rgba(15, 23, 42, 0.45); }
+  .prompt { padding: 24px; }
+  .requester { margin-bottom: 16px; padding: 10px 12px; font-size: 13px; color: #334155; }
+  .requester .meta, .details dd {
+    font-family: ui-monospace, Menlo, Monaco, Consolas, monospace;
+    font-size: 11px;
+    color: #64748b;
+    overflow: hidden;
+    text-overflow: ellipsis;
+    white-space: nowrap;
+  }
+  .requester .meta { margin-top: 2px; }
+  .details { margin: 0 0 16px; padding: 10px 12px; font-size: 12px; }
+  .details dt { color: #475569; margin-top: 6px; }
+  .details dt:first-child { margin-top: 0; }
+  .details dd { margin: 2px 0 0; }
+  .remember { display: flex; align-items: center; gap: 6px; font-size: 13px; margin-bottom: 16px; }
+  .actions { display: flex; gap: 8px; justify-content: flex-end; }
+  .queued { color: #64748b; font-size: 12px; margin-top: 12px; text-align: right; }
+  .passphrase { width: 100%; margin-bottom: 16px; }
+`;
+
+const styles = composeStyles(
+  sharedStyles,
+  sharedPageStyles,
+  sharedFeedbackStyles,
+  sharedSurfaceStyles,
+  sharedUtilityStyles,
+  localStyles
+);
+
+const promptClient = new IpcClient();
+
+window.__WryEthereumResolve = (id: number, result: unknown, error: unknown) => {
+  promptClient.resolve(id, result, error);
+};
+
+function promptIpc(method: string, params: unknown[] = []): Promise<unknown> {
+  return promptClient.request(PROVIDER_IDS.prompt, method, params);
+}
+
+function asPrompt(value: unknown): PromptRequest | null {
+  if (!value || typeof value !== "object") return null;
+  const candidate = value as PromptRequest;
+  return typeof candidate.id === "number" && typeof candidate.kind === "string" ? candidate : null;
+}
+
+function formatValue(value: unknown): string {
+  return typeof value === "string" ? value : JSON.stringify(value);
+}
+
+function DetailsList({ details }: { details: unknown }) {
+  if (!details || typeof details !== "object") return null;
+  const entries = Object.entries(details as Record<string, unknown>);
+  if (entries.length === 0) return null;
+  return (
+    <dl className="details surface-card">
+      {entries.map(([key, value]) => (
+        <React.Fragment key={key}>
+          <dt>{key}</dt>
+          <dd title={formatValue(value)}>{formatValue(value)}</dd>
+        </React.Fragment>
+      ))}
+    </dl>
+  );
+}
+
+type TouchedContract = { address: string; storageSlots: number };
+
+type FiatValues = {
+  currency: string;
+  value?: number;
+  token?: { address: string; symbol: string; amount: string; value?: number };
+};
+
+function formatFiat(value: number, currency: string) {
+  try {
+    return new Intl.NumberFormat(undefined, { style: "currency", currency: currency.toUpperCase() }).format(value);
+  } catch {
+    return `${value.toFixed(2)} ${currency.toUpperCase()}`;
+  }
+}
+
+type DecodedCall = {
+  function: string;
+  signature: string;
+  args: { name: string; type: string; value: unknown }[];
+  source: "bundle" | "signatureDatabase";
+};
+
+function DecodedCallDetails({ call }: { call: DecodedCall }) {
+  return (
+    <dl className="details surface-card">
+      <dt>Function</dt>
+      <dd title={call.signature}>
+        {call.signature}
+        {call.source === "signatureDatabase" && " (guessed from a public signature database)"}
+      </dd>
+      {call.args.map((arg, index) => (
+        <React.Fragment key={index}>
+          <dt>
+            {arg.name || `#${index}`} ({arg.type})
+          </dt>
+          <dd title={formatValue(arg.value)}>{formatValue(arg.value)}</dd>
+        </React.Fragment>
+      ))}
+    </dl>
+  );
+}
+
+function TransactionDetails({ details }: { details: unknown }) {
+  const { touches, fiat, decoded, toEns, ...rest } = (details ?? {}) as {
+    touches?: TouchedContract[] | null;
+    fiat?: FiatValues | null;
+    decoded?: DecodedCall | null;
+    toEns?: string;
+    to?: string;
+  };
+  // The ENS name is only sent when it resolves back to the recipient.
+  if (toEns && rest.to) rest.to = `${toEns} (${rest.to})`;
+  return (
+    <>
+      {decoded && <DecodedCallDetails call={decoded} />}
+      {fiat && (
+        <dl className="details surface-card">
+          {fiat.value !== undefined && (
+            <>
+              <dt>Value</dt>
+              <dd>≈ {formatFiat(fiat.value, fiat.currency)}</dd>
+            </>
+          )}
+          {fiat.token && (
+            <>
+              <dt>Token</dt>
+              <dd title={fiat.token.address}>
+                {fiat.token.amount} {fiat.token.symbol || fiat.token.address}
+                {fiat.token.value !== undefined && ` ≈ ${formatFiat(fiat.token.value, fiat.currency)}`}
+              </dd>
+            </>
+          )}
+        </dl>
+      )}
+      <DetailsList details={rest} />
+      {touches && (
+        <dl className="details surface-card">
+          <dt>Interacts with</dt>
+          {touches.length === 0 ? (
+            <dd>No other contracts or storage reported</dd>
+          ) : (
+            touches.map((contract) => (
+              <dd key={contract.address} title={contract.address}>
+                {contract.address} ({contract.storageSlots} storage slot{contract.storageSlots === 1 ? "" : "s"})
+              </dd>
+            ))
+          )}
+        </dl>
+      )}
+    </>
+  );
+}
+
+// Kinds with a dedicated layout; anything else lists its details.
+const detailRenderers: Record<string, (details: unknown) => React.ReactNode> = {
+  transaction: (details) => <TransactionDetails details={details} />,
+  remoteTransaction: (details) => <TransactionDetails details={details} />,
+};
+
+function Requester({ identity }: { identity: DappIdentity }) {
+  return (
+    <div className="requester surface-card">
+      Requested by <strong>{identity.name}</strong>
+      {identity.dappId ? ` #${identity.dappId}` : ""}
+      {identity.registry && <div className="meta">Registry {identity.registry}</div>}
+      {identity.rootCid && <div className="meta">CID {identity.rootCid}</div>}
+      {identity.publisher && <div className="meta">Publisher {identity.publisher}</div>}
+    </div>
+  );
+}
+
+function App() {
+  const [prompt, setPrompt] = useState<PromptRequest | null>(null);
+  const [remember, setRemember] = useState(false);
+  const [passphrase, setPassphrase] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState("");
+
+  useEffect(() => {
+    window.__WryEthereumEmit = (event: string, payload: unknown) => {
+      if (event === PROMPT_EVENT) setPrompt(asPrompt(payload));
+    };
+    promptIpc("vibefi_getPrompt")
+      .then((value) => setPrompt(asPrompt(value)))
+      .catch((err) => console.warn("[vibefi:prompt] failed to load prompt", err));
+  }, []);
+
+  useEffect(() => {
+    setRemember(false);
+    setPassphrase("");
+    setError("");
+  }, [prompt?.id]);
+
+  const answer = async (approved: boolean) => {
+    if (!prompt || busy) return;
+    setBusy(true);
+    setError("");
+    try {
+      await promptIpc("vibefi_resolvePrompt", [
+        {
+          id: prompt.id,
+          approved,
+          remember: prompt.rememberable && remember,
+          ...(prompt.kind === UNLOCK_PROMPT ? { passphrase } : {}),
+        },
+      ]);
+    } catch (err: any) {
+      setError(err?.message || String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  if (!prompt) return <style>{styles}</style>;
+  const renderDetails = detailRenderers[prompt.kind];
+  const unlocking = prompt.kind === UNLOCK_PROMPT;
+
+  return (
+    <>
+      <style>{styles}</style>
+      <div className="page-container compact prompt surface-card">
+        <h1 className="page-title">{prompt.title}</h1>
+        {prompt.message && <div className="subtitle">{prompt.message}</div>}
+        {prompt.requester && <Requester identity={prompt.requester} />}
+        {renderDetails ? renderDetails(prompt.details) : <DetailsList details={prompt.details} />}
+        {unlocking && (
+          <input
+            className="passphrase"
+            type="password"
+            autoFocus
+            placeholder="Passphrase"
+            value={passphrase}
+            onChange={(event) => setPassphrase(event.target.value)}
+            onKeyDown={(event) => {
+              if (event.key === "Enter") void answer(true);
+            }}
+          />
+        )}
+        {prompt.rememberable && (
+          <label className="remember">
+            <input
+              type="checkbox"
+              checked={remember}
+              onChange={(event) => setRemember(event.target.checked)}
+            />
+            Remember my choice
+          </label>
+        )}
+        {error && <div className="error mt-0 mb-12">{error}</div>}
+        <div className="actions">
+          {!unlocking && (
+            <button className="secondary" disabled={busy} onClick={() => void answer(false)}>
+              Deny
+            </button>
+          )}
+          <button className="primary" disabled={busy} onClick={() => void answer(true)}>
+            {unlocking ? "Unlock" : "Approve"}
+          </button>
+        </div>
+        {prompt.queued > 0 && (
+          <div className="queued">
+            {prompt.queued} more {prompt.queued === 1 ? "request" : "requests"} waiting
+          </div>
+        )}
+      </div>
+    </>
+  );
+}
+
+const rootEl = document.getElementById("root");
+if (rootEl) {
+  createRoot(rootEl).render(<App />);
+}
@@ -0,0 +1,159 @@
+import type { QrPayload } from "./ipc/contracts";
+
+export type QrDecodeSource =
+  | { width: number; height: number; luma: string }
+  | { image: string };
+
+export type QrDecoder = (source: QrDecodeSource) => Promise<QrPayload[]>;
+
+// Frames are downscaled before decoding; QR finder patterns survive this easily
+// and it keeps each IPC payload small.
+const MAX_FRAME_WIDTH = 640;
+const FRAME_INTERVAL_MS = 250;
+
+function bytesToBase64(bytes: Uint8Array): string {
+  let binary = "";
+  const chunk = 0x8000;
+  for (let i = 0; i < bytes.length; i += chunk) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + chunk));
+  }
+  return btoa(binary);
+}
+
+function frameToLuma(ctx: CanvasRenderingContext2D, width: number, height: number): Uint8Array {
+  const { data } = ctx.getImageData(0, 0, width, height);
+  const luma = new Uint8Array(width * height);
+  for (let i = 0, p = 0; i < luma.length; i += 1, p += 4) {
+    luma[i] = (data[p] * 77 + data[p + 1] * 150 + data[p + 2] * 29) >> 8;
+  }
+  return luma;
+}
+
+export async function decodeQrFile(file: Blob, decode: QrDecoder): Promise<QrPayload[]> {
+  const bytes = new Uint8Array(await file.arrayBuffer());
+  return decode({ image: bytesToBase64(bytes) });
+}
+
+function el<K extends keyof HTMLElementTagNameMap>(
+  tag: K,
+  style: Partial<CSSStyleDeclaration>,
+  text?: string
+): HTMLElementTagNameMap[K] {
+  const node = document.createElement(tag);
+  Object.assign(node.style, style);
+  if (text) node.textContent = text;
+  return node;
+}
+
+/**
+ * Opens a full-page scanner overlay and resolves with the first decoded QR
+ * payload. Falls back to image-file import when the camera is unavailable.
+ */
+export function scanQr(decode: QrDecoder): Promise<QrPayload> {
+  return new Promise((resolve, reject) => {
+    const overlay = el("div", {
+      position: "fixed",
+      inset: "0",
+      zIndex: "2147483647",
+      background: "rgba(15, 23, 42, 0.85)",
+      display: "flex",
+      flexDirection: "column",
+      alignItems: "center",
+      justifyContent: "center",
+      gap: "12px",
+      fontFamily: "system-ui, sans-serif",
+      color: "#f8fafc",
+    });
+    const video = el("video", { maxWidth: "min(90vw, 480px)", borderRadius: "12px", background: "#000" });
+    video.muted = true;
+    video.playsInline = true;
+    const status = el("div", { fontSize: "14px" }, "Point the camera at a QR code");
+    const actions = el("div", { display: "flex", gap: "8px" });
+    const fileInput = el("input", { display: "none" });
+    fileInput.type = "file";
+    fileInput.accept = "image/png,image/jpeg";
+    const importButton = el("button", {}, "Import image");
+    const cancelButton = el("button", {}, "Cancel");
+    actions.append(importButton, cancelButton);
+    overlay.append(video, status, actions, fileInput);
+    document.body.appendChild(overlay);
+
+    const canvas = document.createElement("canvas");
+    const ctx = canvas.getContext("2d", { willReadFrequently: true });
+    let stream: MediaStream | null = null;
+    let timer: number | null = null;
+    let busy = false;
+    let settled = false;
+
+    const cleanup = () => {
+      settled = true;
+      if (timer !== null) window.clearInterval(timer);
+      stream?.getTracks().forEach((track) => track.stop());
+      overlay.remove();
+    };
+    const finish = (payloads: QrPayload[]) => {
+      if (settled || payloads.length === 0) return false;
+      cleanup();
+      resolve(payloads[0]);
+      return true;
+    };
+
+    cancelButton.onclick = () => {
+      cleanup();
+      reject(new Error("QR scan cancelled"));
+    };
+    importButton.onclick = () => fileInput.click();
+    fileInput.onchange = async () => {
+      const file = fileInput.files?.[0];
+      fileInput.value = "";
+      if (!file) return;
+      try {
+        if (!finish(await decodeQrFile(file, decode))) {
+          status.textContent = "No QR code found in that image";
+        }
+      } catch (error) {
+        status.textContent = error instanceof Error ? error.message : String(error);
+      }
+    };
+
+    const tick = async () => {
+      if (busy || settled || !ctx || video.readyState < 2) return;
+      const scale = Math.min(1, MAX_FRAME_WIDTH / video.videoWidth);
+      const width = Math.floor(video.videoWidth * scale);
+      const height = Math.floor(video.videoHeight * scale);
+      if (!width || !height) return;
+      canvas.width = width;
+      canvas.height = height;
+      ctx.drawImage(video, 0, 0, width, height);
+      busy = true;
+      try {
+        finish(await decode({ width, height, luma: bytesToBase64(frameToLuma(ctx, width, height)) }));
+      } catch (error) {
+        console.debug("[vibefi:qr] frame decode failed", error);
+      } finally {
+        busy = false;
+      }
+    };
+
+    if (!navigator.mediaDevices?.getUserMedia) {
+      status.textContent = "Camera unavailable; import an image instead";
+      return;
+    }
+    navigator.mediaDevices
+      .getUserMedia({ video: { facingMode: "environment" }, audio: false })
+      .then(async (media) => {
+        if (settled) {
+          media.getTracks().forEach((track) => track.stop());
+          return;
+        }
+        stream = media;
+        video.srcObject = media;
+        await video.play();
+        timer = window.setInterval(() => void tick(), FRAME_INTERVAL_MS);
+      })
+      .catch((error) => {
+        console.warn("[vibefi:qr] camera unavailable", error);
+        status.textContent = "Camera unavailable; import an image instead";
+      });
+  });
+}
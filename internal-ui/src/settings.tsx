@@ -21,14 +21,301 @@ declare global {
 type RpcEndpoint = {
   url: string;
   label?: string;
+  chainId?: number;
 };
 
+type EndpointTestResult = {
+  url: string;
+  latencyMs: number;
+  chainId: number;
+  expectedChainId: number;
+  chainMismatch: boolean;
+  blockNumber: number;
+};
+
+type IpfsCheck = {
+  service: "api" | "gateway" | "heliaGateway" | "heliaRouter";
+  url: string;
+  inUse: boolean;
+  reachable: boolean;
+  ok: boolean;
+  latencyMs: number | null;
+  detail: string;
+};
+
+const IPFS_SERVICE_LABELS: Record<IpfsCheck["service"], string> = {
+  api: "Local node API",
+  gateway: "Local node gateway",
+  heliaGateway: "Helia gateway",
+  heliaRouter: "Helia router",
+};
+
+type EndpointCheck = { result: EndpointTestResult } | { error: string };
+
 type IpfsFetchBackend = "helia" | "localnode";
 
+type FiatPriceSettings = {
+  enabled: boolean;
+  endpoint?: string | null;
+  currency?: string | null;
+};
+
+type TransactionSettings = {
+  accessLists: boolean;
+  offlineSigning: boolean;
+  fiatPrices: FiatPriceSettings;
+};
+
+type SignedTransaction = {
+  hash: string;
+  chainId?: number | null;
+  from: string;
+  to?: string | null;
+  nonce: number;
+  value: string;
+  signedAt: number;
+};
+
+type BroadcastResult = {
+  hash: string;
+  sent: boolean;
+  error?: string;
+};
+
+type LocalAccounts = {
+  accounts: string[];
+  connected: boolean;
+  canDerive: boolean;
+  active?: string | null;
+};
+
+type ApprovalRule = {
+  chainId: number;
+  dapp?: string | null;
+  dappName?: string | null;
+  maxValue: string;
+  allowCalldata?: boolean;
+};
+
+type ApprovalRules = {
+  rules: ApprovalRule[];
+  chains: number[];
+  dapps: { subject: string; name: string }[];
+};
+
+type NftSettings = {
+  indexer?: string | null;
+  accounts: string[];
+};
+
+type SpendTotals = {
+  transactions: number;
+  confirmed: number;
+  failed: number;
+  gasUsed: number;
+  fees: string;
+  value: string;
+};
+
+type SpendReport = {
+  since?: number | null;
+  byChain: (SpendTotals & { chainId: number })[];
+  byDapp: (SpendTotals & { dapp: string; dappId?: string; chainId: number; lastSentAt: number })[];
+};
+
+type LaunchReport = {
+  rootCid: string;
+  dapp: string;
+  startedAt: number;
+  backend?: string;
+  stages: { stage: string; durationMs: number }[];
+  bytesDownloaded: number;
+  buildMs?: number;
+  durationMs: number;
+  result: "opened" | "failed";
+  error?: string;
+};
+
+function describeLaunch(report: LaunchReport) {
+  const parts = [
+    `${(report.durationMs / 1000).toFixed(1)}s`,
+    ...report.stages.map((stage) => `${stage.stage} ${(stage.durationMs / 1000).toFixed(1)}s`),
+    report.bytesDownloaded > 0 ? `${formatMb(report.bytesDownloaded)} downloaded` : "cached bundle",
+  ];
+  if (report.backend) parts.push(report.backend);
+  return parts.join(" \u00b7 ");
+}
+
+const SPEND_RANGES = [
+  { value: "24h", label: "Last 24 hours" },
+  { value: "7d", label: "Last 7 days" },
+  { value: "30d", label: "Last 30 days" },
+  { value: "365d", label: "Last year" },
+  { value: "all", label: "All time" },
+];
+
+// Native amounts arrive with 18 decimals; a few are enough to read.
+function formatMb(bytes: number) {
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+function formatTabUsage(usage: TabUsage | null | undefined) {
+  if (!usage) return "Not sampled yet";
+  const parts = [
+    usage.heapUsed != null ? `${formatMb(usage.heapUsed)} JS heap` : "JS heap not reported",
+    usage.cpuPercent != null ? `${usage.cpuPercent.toFixed(0)}% busy` : "load not reported",
+  ];
+  return parts.join(" \u00b7 ");
+}
+
+function formatNative(amount: string) {
+  const [whole, fraction = ""] = amount.split(".");
+  const trimmed = fraction.slice(0, 6).replace(/0+$/, "");
+  return trimmed ? `${whole}.${trimmed}` : whole;
+}
+
+function describeSpend(totals: SpendTotals) {
+  const failed = totals.failed > 0 ? `, ${totals.failed} failed` : "";
+  return `${totals.transactions} tx${failed} \u00b7 fees ${formatNative(totals.fees)} \u00b7 sent ${formatNative(totals.value)}`;
+}
+
+type Profile = {
+  name: string;
+  active: boolean;
+};
+
+type LockSettings = {
+  enabled: boolean;
+  locked: boolean;
+  autoLockMinutes?: number | null;
+};
+
+type NftItem = {
+  contract: string;
+  tokenId: string;
+  standard: "erc721" | "erc1155";
+  balance: string;
+  collection?: string | null;
+  name?: string | null;
+  description?: string | null;
+  image?: string | null;
+  error?: string | null;
+};
+
+type NftListing = {
+  nfts: NftItem[];
+  truncated: boolean;
+};
+
+type BackgroundRefreshSettings = {
+  enabled: boolean;
+  intervalMins: number;
+  pauseOnBattery: boolean;
+};
+
+type PrefetchSettings = {
+  meteredConnection: boolean;
+  bundleCacheMaxMb?: number | null;
+  iconPrefetchKb?: number | null;
+  backgroundRefresh?: BackgroundRefreshSettings;
+  pinnedCount?: number;
+};
+
+const DEFAULT_BACKGROUND_REFRESH: BackgroundRefreshSettings = {
+  enabled: false,
+  intervalMins: 30,
+  pauseOnBattery: true,
+};
+
+type IntegrityReport = {
+  finishedAt: number;
+  checked: number;
+  skipped: number;
+  quarantined: { rootCid: string; error: string }[];
+};
+
+type CommunityRegistry = {
+  address: string;
+  chainId: number;
+  label: string;
+  trustedPublishers: string[];
+  deployBlock?: number | null;
+  active?: boolean;
+};
+
+type Registries = {
+  defaultRegistry: string;
+  defaultLabel: string;
+  chainId: number;
+  community: CommunityRegistry[];
+};
+
+type SitePartition = {
+  key: string;
+  name: string;
+  dappId?: string;
+  registry?: string;
+  usedBytes: number;
+};
+
+type CachedBundle = {
+  rootCid: string;
+  name?: string;
+  bytes: number;
+  lastUsed: number;
+  pinned: boolean;
+  open: boolean;
+};
+
+type CacheUsage = {
+  bundles: CachedBundle[];
+  totalBytes: number;
+  maxBytes?: number | null;
+};
+
+type SiteData = {
+  quotaMb: number;
+  defaultQuotaMb: number;
+  partitions: SitePartition[];
+};
+
+type TabUsage = {
+  heapUsed?: number | null;
+  heapLimit?: number | null;
+  cpuPercent?: number | null;
+  heavy: boolean;
+};
+
+type HeavyTabSettings = {
+  enabled: boolean;
+  heapMb: number;
+  cpuPercent: number;
+};
+
+type TabUsageReport = {
+  tabs: { id: string; label: string; suspended: boolean; usage?: TabUsage | null }[];
+  process?: { processes: number; rssBytes: number; cpuPercent?: number | null } | null;
+  heavyTab: HeavyTabSettings;
+};
+
+type DappGrant = {
+  kind: "accounts" | "ipfs" | "network" | "notifications" | "camera";
+  value: string;
+  label: string;
+};
+
+type DappGrants = {
+  subject: string;
+  name: string;
+  openTabs: number;
+  grants: DappGrant[];
+};
+
 type IpfsSettings = {
   fetchBackend: IpfsFetchBackend;
   gatewayEndpoint: string;
   defaultGatewayEndpoint: string;
+  downloadConcurrency: number;
 };
 
 const DEFAULT_MAX_CONCURRENT_RPC = 10;
@@ -37,7 +324,9 @@ const DEFAULT_IPFS_SETTINGS: IpfsSettings = {
   fetchBackend: "helia",
   gatewayEndpoint: "http://127.0.0.1:8080",
   defaultGatewayEndpoint: "http://127.0.0.1:8080",
+  downloadConcurrency: 6,
 };
+const MAX_DOWNLOAD_CONCURRENCY = 16;
 
 const localStyles = `
   .section { margin-bottom: 28px; }
@@ -68,6 +357,9 @@ const localStyles = `
     white-space: nowrap;
   }
   .endpoint-item .lbl { font-size: 11px; color: #94a3b8; }
+  .endpoint-item .check { font-size: 11px; color: #16a34a; }
+  .endpoint-item .check.warn { color: #b45309; }
+  .endpoint-item .check.error { color: #dc2626; }
   .endpoint-actions { display: flex; gap: 4px; }
   .endpoint-actions button {
     width: 26px; height: 26px;
@@ -99,6 +391,19 @@ const localStyles = `
   .muted { font-size: 12px; color: #64748b; margin-top: 6px; }
   .ipfs-actions { margin-top: 12px; display: flex; gap: 8px; }
   button.primary:disabled { opacity: 0.5; cursor: default; }
+  .nft-grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(140px, 1fr)); gap: 10px; margin-top: 12px; }
+  .nft-card { padding: 8px; min-width: 0; }
+  .nft-card .media {
+    aspect-ratio: 1;
+    border-radius: 6px;
+    background: #f1f5f9;
+    display: flex; align-items: center; justify-content: center;
+    font-size: 11px; color: #94a3b8;
+    overflow: hidden;
+  }
+  .nft-card img { width: 100%; height: 100%; object-fit: contain; }
+  .nft-card .label { font-size: 12px; font-weight: 600; color: #1e293b; margin-top: 6px; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+  .nft-card .desc { font-size: 11px; color: #64748b; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
 `;
 const styles = composeStyles(
   sharedStyles,
@@ -132,10 +437,14 @@ function parseIpfsSettings(value: unknown): IpfsSettings {
     typeof record.gatewayEndpoint === "string" && record.gatewayEndpoint.trim()
       ? record.gatewayEndpoint.trim()
       : defaultGateway;
+  const concurrency = Number(record.downloadConcurrency);
   return {
     fetchBackend: backend,
     gatewayEndpoint: gateway,
     defaultGatewayEndpoint: defaultGateway,
+    downloadConcurrency: Number.isInteger(concurrency) && concurrency >= 1
+      ? concurrency
+      : DEFAULT_IPFS_SETTINGS.downloadConcurrency,
   };
 }
 
@@ -145,10 +454,51 @@ function parseMaxConcurrentRpc(value: unknown): number {
   return Math.max(1, Math.floor(parsed));
 }
 
+function NftCard({ nft }: { nft: NftItem }) {
+  const [image, setImage] = useState<{ src?: string; error?: string } | null>(null);
+
+  useEffect(() => {
+    if (!nft.image) return;
+    let cancelled = false;
+    settingsIpc("vibefi_nftImage", [nft.image])
+      .then((result) => {
+        if (!cancelled) setImage({ src: (result as { dataUrl: string }).dataUrl });
+      })
+      .catch((err: any) => {
+        if (!cancelled) setImage({ error: err?.message || String(err) });
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [nft.image]);
+
+  const title = nft.name || `#${nft.tokenId}`;
+  return (
+    <div className="nft-card surface-card" title={nft.error || nft.description || undefined}>
+      <div className="media">
+        {image?.src ? (
+          <img src={image.src} alt={title} />
+        ) : (
+          <span>{image?.error ? "Image unavailable" : nft.image ? "Loading..." : "No image"}</span>
+        )}
+      </div>
+      <div className="label">{title}</div>
+      <div className="desc">
+        {nft.collection || `${nft.contract.slice(0, 6)}…${nft.contract.slice(-4)}`}
+        {nft.standard === "erc1155" && nft.balance !== "1" ? ` · ×${nft.balance}` : ""}
+      </div>
+    </div>
+  );
+}
+
 function App() {
   const [endpoints, setEndpoints] = useState<RpcEndpoint[]>([]);
   const [newUrl, setNewUrl] = useState("");
   const [newLabel, setNewLabel] = useState("");
+  const [editingIdx, setEditingIdx] = useState<number | null>(null);
+  const [endpointChecks, setEndpointChecks] = useState<Record<string, EndpointCheck>>({});
+  const [testingUrl, setTestingUrl] = useState<string | null>(null);
+  const [unverifiedUrl, setUnverifiedUrl] = useState<string | null>(null);
   const [status, setStatus] = useState<{ text: string; ok: boolean } | null>(null);
   const [loadingEndpoints, setLoadingEndpoints] = useState(true);
   const [loadingIpfs, setLoadingIpfs] = useState(true);
@@ -156,264 +506,2030 @@ function App() {
   const [maxConcurrentRpcInput, setMaxConcurrentRpcInput] = useState("");
   const [loadingMaxConcurrentRpc, setLoadingMaxConcurrentRpc] = useState(true);
   const [savingRpcAndIpfs, setSavingRpcAndIpfs] = useState(false);
+  const [ipfsChecks, setIpfsChecks] = useState<IpfsCheck[] | null>(null);
+  const [testingIpfs, setTestingIpfs] = useState(false);
   const [openingLogs, setOpeningLogs] = useState(false);
+  const [prefetch, setPrefetch] = useState<PrefetchSettings | null>(null);
+  const [iconBudgetInput, setIconBudgetInput] = useState("");
+  const [cacheLimitInput, setCacheLimitInput] = useState("");
+  const [savingPrefetch, setSavingPrefetch] = useState(false);
+  const [txSettings, setTxSettings] = useState<TransactionSettings | null>(null);
+  const [savingTxSettings, setSavingTxSettings] = useState(false);
+  const [signedTxs, setSignedTxs] = useState<SignedTransaction[] | null>(null);
+  const [localAccounts, setLocalAccounts] = useState<LocalAccounts | null>(null);
+  const [accountPassword, setAccountPassword] = useState("");
+  const [addingAccount, setAddingAccount] = useState(false);
+  const [approvalRules, setApprovalRules] = useState<ApprovalRules | null>(null);
+  const [ruleDraft, setRuleDraft] = useState({ chainId: "", dapp: "", maxValue: "", allowCalldata: false });
+  const [nftSettings, setNftSettings] = useState<NftSettings | null>(null);
+  const [nftAccount, setNftAccount] = useState("");
+  const [nftListing, setNftListing] = useState<NftListing | null>(null);
+  const [loadingNfts, setLoadingNfts] = useState(false);
+  const [spendRange, setSpendRange] = useState("30d");
+  const [spendReport, setSpendReport] = useState<SpendReport | null>(null);
+  const [launchHistory, setLaunchHistory] = useState<LaunchReport[] | null>(null);
+  const [lockSettings, setLockSettings] = useState<LockSettings | null>(null);
+  const [profiles, setProfiles] = useState<Profile[] | null>(null);
+  const [profileDraft, setProfileDraft] = useState("");
+  const [passphraseDraft, setPassphraseDraft] = useState({ current: "", next: "" });
+  const [autoLockInput, setAutoLockInput] = useState("");
+  const [savingLock, setSavingLock] = useState(false);
+  const [registries, setRegistries] = useState<Registries | null>(null);
+  const [registryDraft, setRegistryDraft] = useState({ address: "", chainId: "", label: "", publishers: "" });
+  const [savingRegistry, setSavingRegistry] = useState(false);
+  const [rememberedPrompts, setRememberedPrompts] = useState<Record<string, boolean> | null>(null);
+  const [dappGrants, setDappGrants] = useState<DappGrants[] | null>(null);
+  const [siteData, setSiteData] = useState<SiteData | null>(null);
+  const [cacheUsage, setCacheUsage] = useState<CacheUsage | null>(null);
+  const [clearingCache, setClearingCache] = useState(false);
+  const [tabUsage, setTabUsage] = useState<TabUsageReport | null>(null);
+  const [heavyTab, setHeavyTab] = useState<HeavyTabSettings | null>(null);
+  const [savingHeavyTab, setSavingHeavyTab] = useState(false);
+  const [integrity, setIntegrity] = useState<IntegrityReport | null>(null);
+  const [quotaInput, setQuotaInput] = useState("");
+  const [savingQuota, setSavingQuota] = useState(false);
 
   useEffect(() => {
-    void Promise.all([loadEndpoints(), loadIpfsSettings(), loadMaxConcurrentRpc()]);
+    void Promise.all([
+      loadEndpoints(),
+      loadIpfsSettings(),
+      loadMaxConcurrentRpc(),
+      loadPrefetchSettings(),
+      loadTransactionSettings(),
+      loadSignedTransactions(),
+      loadLocalAccounts(),
+      loadApprovalRules(),
+      loadNftSettings(),
+      loadLockSettings(),
+      loadProfiles(),
+      loadSpendReport("30d"),
+      loadLaunchHistory(),
+      loadRegistries(),
+      loadRememberedPrompts(),
+      loadDappGrants(),
+      loadSiteData(),
+      loadIntegrityReport(),
+      loadCacheUsage(),
+      loadTabUsage(),
+    ]);
   }, []);
 
-  const loadEndpoints = async () => {
-    setLoadingEndpoints(true);
+  useEffect(() => {
+    // Tabs are sampled every few seconds; keep the table current while open.
+    const timer = window.setInterval(() => void loadTabUsage(), 5000);
+    return () => window.clearInterval(timer);
+  }, []);
+
+  const loadRememberedPrompts = async () => {
     try {
-      const result = await settingsIpc("vibefi_getEndpoints");
-      setEndpoints(Array.isArray(result) ? (result as RpcEndpoint[]) : []);
+      setRememberedPrompts((await settingsIpc("vibefi_getRememberedPrompts")) as Record<string, boolean>);
     } catch (error) {
-      console.warn("[vibefi:settings] failed to load rpc endpoints", error);
-      setEndpoints([]);
-    } finally {
-      setLoadingEndpoints(false);
+      console.warn("[vibefi:settings] failed to load remembered choices", error);
     }
   };
 
-  const loadIpfsSettings = async () => {
-    setLoadingIpfs(true);
+  const forgetPrompt = async (key?: string) => {
     try {
-      const result = await settingsIpc("vibefi_getIpfsSettings");
-      setIpfsDraft(parseIpfsSettings(result));
+      await settingsIpc("vibefi_forgetPrompt", key ? [key] : []);
+      await loadRememberedPrompts();
+      setStatus({ text: key ? "Choice forgotten" : "All remembered choices forgotten", ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to forget remembered choice", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const loadDappGrants = async () => {
+    try {
+      setDappGrants((await settingsIpc("vibefi_getDappPermissions")) as DappGrants[]);
     } catch (error) {
-      console.warn("[vibefi:settings] failed to load ipfs settings", error);
-      setIpfsDraft(DEFAULT_IPFS_SETTINGS);
-    } finally {
-      setLoadingIpfs(false);
+      console.warn("[vibefi:settings] failed to load dapp permissions", error);
     }
   };
 
-  const loadMaxConcurrentRpc = async () => {
-    setLoadingMaxConcurrentRpc(true);
+  const revokeGrant = async (dapp: DappGrants, grant: DappGrant) => {
     try {
-      const result = await settingsIpc("vibefi_getMaxConcurrentRpc");
-      setMaxConcurrentRpcInput(String(parseMaxConcurrentRpc(result)));
+      await settingsIpc("vibefi_revokeDappPermission", [dapp.subject, grant.kind, grant.value]);
+      await Promise.all([loadDappGrants(), loadRememberedPrompts()]);
+      setStatus({ text: `Revoked ${grant.label} from ${dapp.name}`, ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to revoke dapp permission", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const resetDapp = async (dapp: DappGrants) => {
+    try {
+      await settingsIpc("vibefi_resetDappPermissions", [dapp.subject]);
+      await Promise.all([loadDappGrants(), loadRememberedPrompts()]);
+      setStatus({ text: `Reset permissions of ${dapp.name}`, ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to reset dapp permissions", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const loadIntegrityReport = async () => {
+    try {
+      setIntegrity((await settingsIpc("vibefi_getCacheIntegrity")) as IntegrityReport | null);
     } catch (error) {
-      console.warn("[vibefi:settings] failed to load rpc max concurrency", error);
-      setMaxConcurrentRpcInput(String(DEFAULT_MAX_CONCURRENT_RPC));
+      console.warn("[vibefi:settings] failed to load cache integrity report", error);
+    }
+  };
+
+  const loadCacheUsage = async () => {
+    try {
+      setCacheUsage((await settingsIpc("vibefi_getCacheUsage")) as CacheUsage);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load bundle cache usage", error);
+    }
+  };
+
+  const clearCache = async (bundle?: CachedBundle) => {
+    setClearingCache(true);
+    try {
+      const result = (await settingsIpc("vibefi_clearCache", bundle ? [bundle.rootCid] : [])) as {
+        removed: number;
+        freedBytes: number;
+        skipped: number;
+      };
+      await loadCacheUsage();
+      const kept = result.skipped > 0 ? `, ${result.skipped} open in tabs kept` : "";
+      setStatus({ text: `Removed ${result.removed} cached bundles (${formatMb(result.freedBytes)})${kept}`, ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to clear bundle cache", err);
+      setStatus({ text: err?.message || String(err), ok: false });
     } finally {
-      setLoadingMaxConcurrentRpc(false);
+      setClearingCache(false);
     }
   };
 
-  const saveEndpoints = async (next: RpcEndpoint[]) => {
+  const loadSiteData = async () => {
     try {
-      await settingsIpc("vibefi_setEndpoints", [next]);
-      setEndpoints(next);
+      const result = (await settingsIpc("vibefi_getSiteData")) as SiteData;
+      setSiteData(result);
+      setQuotaInput(String(result.quotaMb));
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load site data", error);
+    }
+  };
+
+  const saveStorageQuota = async () => {
+    const quota = Number.parseInt(quotaInput.trim(), 10);
+    if (!Number.isFinite(quota) || quota < 1) {
+      setStatus({ text: "Storage quota must be a whole number of MB >= 1", ok: false });
+      return;
+    }
+    setSavingQuota(true);
+    try {
+      await settingsIpc("vibefi_setStorageQuota", [quota]);
+      await loadSiteData();
       setStatus({ text: "Saved", ok: true });
     } catch (err: any) {
-      console.warn("[vibefi:settings] failed to save rpc endpoints", err);
+      console.warn("[vibefi:settings] failed to save storage quota", err);
       setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      setSavingQuota(false);
     }
   };
 
-  const saveRpcAndIpfsSettings = async () => {
-    const max = Number.parseInt(maxConcurrentRpcInput.trim(), 10);
-    if (!Number.isFinite(max) || max < 1) {
-      setStatus({ text: "Max concurrent RPC must be a whole number >= 1", ok: false });
-      return;
+  const clearSiteData = async (partition: SitePartition) => {
+    try {
+      await settingsIpc("vibefi_clearSiteData", [partition.key]);
+      await loadSiteData();
+      setStatus({ text: `Cleared site data of ${partition.name || partition.key}`, ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to clear site data", err);
+      setStatus({ text: err?.message || String(err), ok: false });
     }
+  };
 
-    setSavingRpcAndIpfs(true);
+  const loadTabUsage = async () => {
     try {
-      await settingsIpc("vibefi_saveSettings", [{
-        maxConcurrentRpc: max,
-        fetchBackend: ipfsDraft.fetchBackend,
-        gatewayEndpoint: ipfsDraft.fetchBackend === "localnode" ? ipfsDraft.gatewayEndpoint.trim() : undefined,
-      }]);
-      setMaxConcurrentRpcInput(String(max));
+      const result = (await settingsIpc("vibefi_getTabUsage")) as TabUsageReport;
+      setTabUsage(result);
+      setHeavyTab((curr) => curr ?? result.heavyTab);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load tab usage", error);
+    }
+  };
+
+  const saveHeavyTabSettings = async () => {
+    if (!heavyTab) return;
+    if (!Number.isInteger(heavyTab.heapMb) || heavyTab.heapMb < 1) {
+      setStatus({ text: "Heavy tab heap limit must be a whole number of MB >= 1", ok: false });
+      return;
+    }
+    if (!Number.isInteger(heavyTab.cpuPercent) || heavyTab.cpuPercent < 1 || heavyTab.cpuPercent > 100) {
+      setStatus({ text: "Heavy tab CPU limit must be a whole percentage between 1 and 100", ok: false });
+      return;
+    }
+    setSavingHeavyTab(true);
+    try {
+      await settingsIpc("vibefi_setHeavyTabSettings", [heavyTab]);
       setStatus({ text: "Saved", ok: true });
     } catch (err: any) {
-      console.warn("[vibefi:settings] failed to save rpc/ipfs settings", err);
+      console.warn("[vibefi:settings] failed to save heavy tab settings", err);
       setStatus({ text: err?.message || String(err), ok: false });
     } finally {
-      setSavingRpcAndIpfs(false);
+      setSavingHeavyTab(false);
     }
   };
 
-  const openLogDirectory = async () => {
-    setOpeningLogs(true);
+  const loadRegistries = async () => {
     try {
-      const result = await settingsIpc("vibefi_openLogDirectory");
-      const path = typeof result === "string" && result.trim() ? result.trim() : null;
-      setStatus({ text: path ? `Opened log directory: ${path}` : "Opened log directory", ok: true });
+      setRegistries((await settingsIpc("vibefi_getRegistries")) as Registries);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load registries", error);
+    }
+  };
+
+  const saveRegistry = async () => {
+    const chainId = Number.parseInt(registryDraft.chainId.trim() || String(registries?.chainId ?? ""), 10);
+    if (!Number.isFinite(chainId) || chainId < 1) {
+      setStatus({ text: "Registry chain ID must be a whole number >= 1", ok: false });
+      return;
+    }
+    setSavingRegistry(true);
+    try {
+      await settingsIpc("vibefi_saveRegistry", [{
+        address: registryDraft.address.trim(),
+        chainId,
+        label: registryDraft.label.trim(),
+        trustedPublishers: registryDraft.publishers.split(/[\s,]+/).filter(Boolean),
+      }]);
+      setRegistryDraft({ address: "", chainId: "", label: "", publishers: "" });
+      await loadRegistries();
+      setStatus({ text: "Registry saved; refresh the launcher to list its dapps", ok: true });
     } catch (err: any) {
-      console.warn("[vibefi:settings] failed to open log directory", err);
+      console.warn("[vibefi:settings] failed to save registry", err);
       setStatus({ text: err?.message || String(err), ok: false });
     } finally {
-      setOpeningLogs(false);
+      setSavingRegistry(false);
     }
   };
 
-  const addEndpoint = () => {
-    const url = newUrl.trim();
-    if (!url) return;
-    const next = [...endpoints, { url, label: newLabel.trim() || undefined }];
-    setNewUrl("");
-    setNewLabel("");
-    void saveEndpoints(next);
+  const removeRegistry = async (registry: CommunityRegistry) => {
+    try {
+      await settingsIpc("vibefi_removeRegistry", [registry.address, registry.chainId]);
+      await loadRegistries();
+      setStatus({ text: `Removed ${registry.label}`, ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to remove registry", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
   };
 
-  const removeEndpoint = (idx: number) => {
-    if (endpoints.length <= 1) {
-      setStatus({ text: "At least one RPC endpoint is required.", ok: false });
+  const loadPrefetchSettings = async () => {
+    try {
+      const result = (await settingsIpc("vibefi_getPrefetchSettings")) as PrefetchSettings;
+      setPrefetch(result);
+      setCacheLimitInput(result.bundleCacheMaxMb ? String(result.bundleCacheMaxMb) : "");
+      setIconBudgetInput(result.iconPrefetchKb != null ? String(result.iconPrefetchKb) : "");
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load prefetch settings", error);
+      setPrefetch({ meteredConnection: false });
+    }
+  };
+
+  const savePrefetchSettings = async () => {
+    if (!prefetch) return;
+    const trimmed = cacheLimitInput.trim();
+    const limit = trimmed ? Number.parseInt(trimmed, 10) : null;
+    if (limit !== null && (!Number.isFinite(limit) || limit < 1)) {
+      setStatus({ text: "Bundle cache limit must be a whole number of MB >= 1", ok: false });
       return;
     }
-    const next = endpoints.filter((_, i) => i !== idx);
-    void saveEndpoints(next);
+    const iconTrimmed = iconBudgetInput.trim();
+    const iconBudget = iconTrimmed ? Number.parseInt(iconTrimmed, 10) : null;
+    if (iconBudget !== null && (!Number.isFinite(iconBudget) || iconBudget < 0)) {
+      setStatus({ text: "Icon prefetch budget must be a whole number of KB >= 0", ok: false });
+      return;
+    }
+    setSavingPrefetch(true);
+    try {
+      await settingsIpc("vibefi_setPrefetchSettings", [{
+        meteredConnection: prefetch.meteredConnection,
+        bundleCacheMaxMb: limit,
+        iconPrefetchKb: iconBudget,
+        backgroundRefresh: prefetch.backgroundRefresh ?? DEFAULT_BACKGROUND_REFRESH,
+      }]);
+      void loadCacheUsage();
+      setPrefetch((curr) => (curr ? { ...curr, bundleCacheMaxMb: limit, iconPrefetchKb: iconBudget } : curr));
+      setStatus({ text: "Saved", ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to save prefetch settings", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      setSavingPrefetch(false);
+    }
   };
 
-  const moveUp = (idx: number) => {
-    if (idx <= 0) return;
-    const next = [...endpoints];
-    [next[idx - 1], next[idx]] = [next[idx], next[idx - 1]];
-    void saveEndpoints(next);
+  const loadTransactionSettings = async () => {
+    try {
+      const result = (await settingsIpc("vibefi_getTransactionSettings")) as TransactionSettings;
+      setTxSettings(result);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load transaction settings", error);
+      setTxSettings({ accessLists: false, offlineSigning: false, fiatPrices: { enabled: false } });
+    }
   };
 
-  const moveDown = (idx: number) => {
-    if (idx >= endpoints.length - 1) return;
-    const next = [...endpoints];
-    [next[idx], next[idx + 1]] = [next[idx + 1], next[idx]];
-    void saveEndpoints(next);
+  const saveTransactionSettings = async (next: TransactionSettings) => {
+    const previous = txSettings;
+    setTxSettings(next);
+    setSavingTxSettings(true);
+    try {
+      await settingsIpc("vibefi_setTransactionSettings", [next]);
+      setStatus({ text: "Saved", ok: true });
+      // The client trims and lowercases the price fields.
+      await loadTransactionSettings();
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to save transaction settings", err);
+      setTxSettings(previous);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      setSavingTxSettings(false);
+    }
   };
 
-  return (
-    <>
-      <style>{styles}</style>
-      <div className="page-container wide">
-        <h1 className="page-title">Settings</h1>
-        <div className="subtitle">Manage RPC endpoints and IPFS retrieval preferences.</div>
+  const editFiatPrices = (patch: Partial<FiatPriceSettings>) =>
+    setTxSettings((curr) => (curr ? { ...curr, fiatPrices: { ...curr.fiatPrices, ...patch } } : curr));
+
+  const loadLocalAccounts = async () => {
+    try {
+      setLocalAccounts((await settingsIpc("vibefi_getLocalAccounts")) as LocalAccounts);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load local accounts", error);
+      setLocalAccounts({ accounts: [], connected: false, canDerive: false });
+    }
+  };
+
+  const selectAccount = async (account: string) => {
+    try {
+      await settingsIpc("vibefi_selectAccount", [account]);
+      setStatus({ text: "Active account changed", ok: true });
+      await loadLocalAccounts();
+    } catch (err: any) {
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const addLocalAccount = async () => {
+    setAddingAccount(true);
+    try {
+      const account = (await settingsIpc("vibefi_addLocalAccount", [accountPassword])) as string;
+      setAccountPassword("");
+      setStatus({ text: `Added ${account}`, ok: true });
+      await loadLocalAccounts();
+    } catch (err: any) {
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      setAddingAccount(false);
+    }
+  };
+
+  const loadApprovalRules = async () => {
+    try {
+      setApprovalRules((await settingsIpc("vibefi_getApprovalRules")) as ApprovalRules);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load approval rules", error);
+      setApprovalRules({ rules: [], chains: [], dapps: [] });
+    }
+  };
+
+  const saveApprovalRules = async (rules: ApprovalRule[]) => {
+    try {
+      await settingsIpc("vibefi_setApprovalRules", [rules]);
+      setStatus({ text: "Saved", ok: true });
+      await loadApprovalRules();
+      return true;
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to save approval rules", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+      return false;
+    }
+  };
+
+  const addApprovalRule = async () => {
+    if (!approvalRules) return;
+    const chainId = Number(ruleDraft.chainId || approvalRules.chains[0]);
+    const dapp = approvalRules.dapps.find((d) => d.subject === ruleDraft.dapp);
+    const rule: ApprovalRule = {
+      chainId,
+      dapp: dapp?.subject ?? null,
+      dappName: dapp?.name ?? null,
+      maxValue: ruleDraft.maxValue.trim(),
+      allowCalldata: ruleDraft.allowCalldata,
+    };
+    if (await saveApprovalRules([...approvalRules.rules, rule])) {
+      setRuleDraft({ chainId: "", dapp: "", maxValue: "", allowCalldata: false });
+    }
+  };
+
+  const loadNftSettings = async () => {
+    try {
+      const result = (await settingsIpc("vibefi_getNftSettings")) as NftSettings;
+      setNftSettings(result);
+      setNftAccount((curr) => curr || result.accounts[0] || "");
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load NFT settings", error);
+      setNftSettings({ accounts: [] });
+    }
+  };
+
+  const saveNftSettings = async () => {
+    if (!nftSettings) return;
+    try {
+      await settingsIpc("vibefi_setNftSettings", [{ indexer: nftSettings.indexer ?? null }]);
+      setStatus({ text: "Saved", ok: true });
+      await loadNftSettings();
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to save NFT settings", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const loadNfts = async () => {
+    if (!nftAccount.trim()) return;
+    setLoadingNfts(true);
+    setNftListing(null);
+    try {
+      setNftListing((await settingsIpc("vibefi_listNfts", [{ account: nftAccount.trim() }])) as NftListing);
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to list NFTs", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      setLoadingNfts(false);
+    }
+  };
+
+  const loadSpendReport = async (range: string) => {
+    setSpendRange(range);
+    try {
+      setSpendReport((await settingsIpc("vibefi_getSpendReport", [range])) as SpendReport);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load spend report", error);
+      setSpendReport({ byChain: [], byDapp: [] });
+    }
+  };
+
+  const loadLaunchHistory = async () => {
+    try {
+      setLaunchHistory((await settingsIpc("vibefi_getLaunchHistory")) as LaunchReport[]);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load launch history", error);
+      setLaunchHistory([]);
+    }
+  };
+
+  const loadSignedTransactions = async () => {
+    try {
+      setSignedTxs((await settingsIpc("vibefi_getSignedTransactions")) as SignedTransaction[]);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load signed transactions", error);
+      setSignedTxs([]);
+    }
+  };
+
+  const exportSignedTransactions = async (format: "json" | "raw") => {
+    try {
+      const path = await settingsIpc("vibefi_exportSignedTransactions", [format]);
+      if (path) setStatus({ text: `Exported to ${path}`, ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to export signed transactions", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      await loadSignedTransactions();
+    }
+  };
+
+  const discardSignedTransactions = async () => {
+    try {
+      await settingsIpc("vibefi_discardSignedTransactions");
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to discard signed transactions", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      await loadSignedTransactions();
+    }
+  };
+
+  const importSignedTransactions = async () => {
+    try {
+      const results = (await settingsIpc("vibefi_importSignedTransactions")) as BroadcastResult[] | null;
+      if (!results) return;
+      const failed = results.filter((result) => !result.sent);
+      setStatus(
+        failed.length === 0
+          ? { text: `Broadcast ${results.length} transaction${results.length === 1 ? "" : "s"}`, ok: true }
+          : { text: `${failed.length} of ${results.length} failed: ${failed[0].error}`, ok: false }
+      );
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to import signed transactions", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      await loadSignedTransactions();
+    }
+  };
+
+  const exportSpendReport = async () => {
+    try {
+      const path = await settingsIpc("vibefi_exportSpendReport", [spendRange]);
+      if (path) setStatus({ text: `Exported to ${path}`, ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to export spend report", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const applyLockSettings = (result: LockSettings) => {
+    setLockSettings(result);
+    setAutoLockInput(result.autoLockMinutes ? String(result.autoLockMinutes) : "");
+  };
+
+  const loadLockSettings = async () => {
+    try {
+      applyLockSettings((await settingsIpc("vibefi_getLockSettings")) as LockSettings);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load lock settings", error);
+    }
+  };
+
+  const loadProfiles = async () => {
+    try {
+      setProfiles((await settingsIpc("vibefi_listProfiles")) as Profile[]);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load profiles", error);
+      setProfiles([]);
+    }
+  };
+
+  const createProfile = async () => {
+    try {
+      const name = await settingsIpc("vibefi_createProfile", [profileDraft]);
+      setProfileDraft("");
+      setStatus({ text: `Created profile ${name}`, ok: true });
+      await loadProfiles();
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to create profile", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const switchProfile = async (name: string) => {
+    try {
+      await settingsIpc("vibefi_switchProfile", [name]);
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to switch profile", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const savePassphrase = async (remove: boolean) => {
+    setSavingLock(true);
+    try {
+      const result = await settingsIpc("vibefi_setLockPassphrase", [
+        {
+          currentPassphrase: passphraseDraft.current || null,
+          passphrase: remove ? null : passphraseDraft.next,
+        },
+      ]);
+      applyLockSettings(result as LockSettings);
+      setPassphraseDraft({ current: "", next: "" });
+      setStatus({ text: remove ? "Passphrase removed" : "Passphrase saved", ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to save lock passphrase", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      setSavingLock(false);
+    }
+  };
+
+  const saveAutoLock = async () => {
+    const trimmed = autoLockInput.trim();
+    const minutes = trimmed ? Number(trimmed) : null;
+    if (minutes !== null && (!Number.isInteger(minutes) || minutes <= 0)) {
+      setStatus({ text: "Auto-lock must be a whole number of minutes", ok: false });
+      return;
+    }
+    try {
+      applyLockSettings((await settingsIpc("vibefi_setAutoLock", [minutes])) as LockSettings);
+      setStatus({ text: "Saved", ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to save auto-lock", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const lockNow = async () => {
+    try {
+      await settingsIpc("vibefi_lockNow");
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to lock", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const loadEndpoints = async () => {
+    setLoadingEndpoints(true);
+    try {
+      const result = await settingsIpc("vibefi_getEndpoints");
+      setEndpoints(Array.isArray(result) ? (result as RpcEndpoint[]) : []);
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load rpc endpoints", error);
+      setEndpoints([]);
+    } finally {
+      setLoadingEndpoints(false);
+    }
+  };
+
+  const loadIpfsSettings = async () => {
+    setLoadingIpfs(true);
+    try {
+      const result = await settingsIpc("vibefi_getIpfsSettings");
+      setIpfsDraft(parseIpfsSettings(result));
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load ipfs settings", error);
+      setIpfsDraft(DEFAULT_IPFS_SETTINGS);
+    } finally {
+      setLoadingIpfs(false);
+    }
+  };
+
+  const loadMaxConcurrentRpc = async () => {
+    setLoadingMaxConcurrentRpc(true);
+    try {
+      const result = await settingsIpc("vibefi_getMaxConcurrentRpc");
+      setMaxConcurrentRpcInput(String(parseMaxConcurrentRpc(result)));
+    } catch (error) {
+      console.warn("[vibefi:settings] failed to load rpc max concurrency", error);
+      setMaxConcurrentRpcInput(String(DEFAULT_MAX_CONCURRENT_RPC));
+    } finally {
+      setLoadingMaxConcurrentRpc(false);
+    }
+  };
+
+  const saveEndpoints = async (next: RpcEndpoint[]) => {
+    try {
+      await settingsIpc("vibefi_setEndpoints", [next]);
+      setEndpoints(next);
+      setStatus({ text: "Saved", ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to save rpc endpoints", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    }
+  };
+
+  const saveRpcAndIpfsSettings = async () => {
+    const max = Number.parseInt(maxConcurrentRpcInput.trim(), 10);
+    if (!Number.isFinite(max) || max < 1) {
+      setStatus({ text: "Max concurrent RPC must be a whole number >= 1", ok: false });
+      return;
+    }
+    const downloads = ipfsDraft.downloadConcurrency;
+    if (!Number.isInteger(downloads) || downloads < 1 || downloads > MAX_DOWNLOAD_CONCURRENCY) {
+      setStatus({ text: `Parallel downloads must be between 1 and ${MAX_DOWNLOAD_CONCURRENCY}`, ok: false });
+      return;
+    }
+
+    setSavingRpcAndIpfs(true);
+    try {
+      await settingsIpc("vibefi_saveSettings", [{
+        maxConcurrentRpc: max,
+        fetchBackend: ipfsDraft.fetchBackend,
+        gatewayEndpoint: ipfsDraft.fetchBackend === "localnode" ? ipfsDraft.gatewayEndpoint.trim() : undefined,
+        downloadConcurrency: downloads,
+      }]);
+      setMaxConcurrentRpcInput(String(max));
+      setStatus({ text: "Saved", ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to save rpc/ipfs settings", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      setSavingRpcAndIpfs(false);
+    }
+  };
+
+  const testIpfs = async () => {
+    setTestingIpfs(true);
+    try {
+      const result = (await settingsIpc("vibefi_testIpfs")) as IpfsCheck[];
+      setIpfsChecks(result);
+      const failing = result.filter((check) => check.inUse && !check.ok).length;
+      setStatus(
+        failing === 0
+          ? { text: "IPFS services in use are reachable", ok: true }
+          : { text: `${failing} IPFS service(s) in use did not answer`, ok: false }
+      );
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to test ipfs connectivity", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      setTestingIpfs(false);
+    }
+  };
+
+  const openLogDirectory = async () => {
+    setOpeningLogs(true);
+    try {
+      const result = await settingsIpc("vibefi_openLogDirectory");
+      const path = typeof result === "string" && result.trim() ? result.trim() : null;
+      setStatus({ text: path ? `Opened log directory: ${path}` : "Opened log directory", ok: true });
+    } catch (err: any) {
+      console.warn("[vibefi:settings] failed to open log directory", err);
+      setStatus({ text: err?.message || String(err), ok: false });
+    } finally {
+      setOpeningLogs(false);
+    }
+  };
+
+  const testEndpoint = async (url: string): Promise<EndpointCheck> => {
+    setTestingUrl(url);
+    let check: EndpointCheck;
+    try {
+      check = { result: (await settingsIpc("vibefi_testEndpoint", [url])) as EndpointTestResult };
+    } catch (err: any) {
+      check = { error: err?.message || String(err) };
+    } finally {
+      setTestingUrl(null);
+    }
+    setEndpointChecks((prev) => ({ ...prev, [url]: check }));
+    return check;
+  };
+
+  const describeCheck = (check: EndpointCheck): { text: string; level: "ok" | "warn" | "error" } => {
+    if ("error" in check) {
+      return { text: `Test failed: ${check.error}`, level: "error" };
+    }
+    const { latencyMs, chainId, expectedChainId, chainMismatch, blockNumber } = check.result;
+    if (chainMismatch) {
+      return {
+        text: `${latencyMs} ms · reports chain ${chainId}, but this profile uses chain ${expectedChainId}`,
+        level: "warn",
+      };
+    }
+    return { text: `${latencyMs} ms · chain ${chainId} · block ${blockNumber}`, level: "ok" };
+  };
+
+  const resetEndpointForm = () => {
+    setNewUrl("");
+    setNewLabel("");
+    setEditingIdx(null);
+    setUnverifiedUrl(null);
+  };
+
+  // Endpoints are tested before they are saved; a failed test or a chain
+  // mismatch needs a second click to save anyway.
+  const submitEndpoint = async () => {
+    const url = newUrl.trim();
+    if (!url) return;
+    if (unverifiedUrl !== url) {
+      const check = await testEndpoint(url);
+      const { text, level } = describeCheck(check);
+      if (level !== "ok") {
+        setUnverifiedUrl(url);
+        setStatus({ text: `${text}. Click "Save anyway" to keep it.`, ok: false });
+        return;
+      }
+    }
+    const endpoint = { url, label: newLabel.trim() || undefined };
+    const next =
+      editingIdx === null
+        ? [...endpoints, endpoint]
+        : endpoints.map((ep, i) => (i === editingIdx ? endpoint : ep));
+    resetEndpointForm();
+    void saveEndpoints(next);
+  };
+
+  const editEndpoint = (idx: number) => {
+    setEditingIdx(idx);
+    setNewUrl(endpoints[idx].url);
+    setNewLabel(endpoints[idx].label ?? "");
+    setUnverifiedUrl(null);
+  };
+
+  const removeEndpoint = (idx: number) => {
+    if (endpoints.length <= 1) {
+      setStatus({ text: "At least one RPC endpoint is required.", ok: false });
+      return;
+    }
+    const next = endpoints.filter((_, i) => i !== idx);
+    if (editingIdx !== null) resetEndpointForm();
+    void saveEndpoints(next);
+  };
+
+  const moveUp = (idx: number) => {
+    if (idx <= 0) return;
+    const next = [...endpoints];
+    [next[idx - 1], next[idx]] = [next[idx], next[idx - 1]];
+    void saveEndpoints(next);
+  };
+
+  const moveDown = (idx: number) => {
+    if (idx >= endpoints.length - 1) return;
+    const next = [...endpoints];
+    [next[idx], next[idx + 1]] = [next[idx + 1], next[idx]];
+    void saveEndpoints(next);
+  };
+
+  return (
+    <>
+      <style>{styles}</style>
+      <div className="page-container wide">
+        <h1 className="page-title">Settings</h1>
+        <div className="subtitle">Manage RPC endpoints and IPFS retrieval preferences.</div>
+
+        <div className="section">
+          <h2>RPC Endpoints</h2>
+          {loadingEndpoints ? (
+            <div className="empty">Loading...</div>
+          ) : endpoints.length === 0 ? (
+            <div className="empty">No endpoints configured.</div>
+          ) : (
+            <div className="endpoint-list">
+              {endpoints.map((ep, idx) => {
+                const check = endpointChecks[ep.url];
+                const described = check ? describeCheck(check) : null;
+                return (
+                  <div className="endpoint-item surface-card" key={`${idx}-${ep.url}`}>
+                    <div className="index">{idx + 1}</div>
+                    <div className="info">
+                      <div className="url">{ep.url}</div>
+                      {ep.label && <div className="lbl">{ep.label}</div>}
+                      {testingUrl === ep.url ? (
+                        <div className="lbl">Testing...</div>
+                      ) : described && (
+                        <div className={`check ${described.level === "ok" ? "" : described.level}`}>{described.text}</div>
+                      )}
+                    </div>
+                    <div className="endpoint-actions">
+                      <button onClick={() => void testEndpoint(ep.url)} disabled={testingUrl !== null} title="Test latency and chain">&#x21BB;</button>
+                      <button onClick={() => editEndpoint(idx)} title="Edit">&#x270E;</button>
+                      <button onClick={() => moveUp(idx)} disabled={idx <= 0} title="Move up">&#x25B2;</button>
+                      <button onClick={() => moveDown(idx)} disabled={idx >= endpoints.length - 1} title="Move down">&#x25BC;</button>
+                      <button onClick={() => removeEndpoint(idx)} disabled={endpoints.length <= 1} title="Remove">&#x2715;</button>
+                    </div>
+                  </div>
+                );
+              })}
+            </div>
+          )}
+
+          <div className="add-form">
+            <div className="field flex-2">
+              <label>URL</label>
+              <input
+                type="text"
+                placeholder="https://rpc.example.com"
+                value={newUrl}
+                onChange={(e) => { setNewUrl(e.target.value); setUnverifiedUrl(null); }}
+                onKeyDown={(e) => { if (e.key === "Enter") void submitEndpoint(); }}
+              />
+            </div>
+            <div className="field flex-1">
+              <label>Label (optional)</label>
+              <input
+                type="text"
+                placeholder="My RPC"
+                value={newLabel}
+                onChange={(e) => setNewLabel(e.target.value)}
+                onKeyDown={(e) => { if (e.key === "Enter") void submitEndpoint(); }}
+              />
+            </div>
+            <button className="secondary mb-0" onClick={() => void submitEndpoint()} disabled={testingUrl !== null}>
+              {testingUrl !== null && testingUrl === newUrl.trim()
+                ? "Testing..."
+                : unverifiedUrl !== null && unverifiedUrl === newUrl.trim()
+                  ? "Save anyway"
+                  : editingIdx === null ? "Add" : "Save"}
+            </button>
+            {editingIdx !== null && (
+              <button className="secondary mb-0" onClick={resetEndpointForm}>Cancel</button>
+            )}
+          </div>
+          <div className="muted">New and edited endpoints are tested for reachability, latency and chain ID before they are saved.</div>
+
+          <div className="field mt-3">
+            <label>Max Concurrent RPC</label>
+            {loadingMaxConcurrentRpc ? (
+              <div className="empty">Loading...</div>
+            ) : (
+              <>
+                <input
+                  type="number"
+                  min={1}
+                  step={1}
+                  value={maxConcurrentRpcInput}
+                  onChange={(e) => setMaxConcurrentRpcInput(e.target.value)}
+                  onKeyDown={(e) => { if (e.key === "Enter") void saveRpcAndIpfsSettings(); }}
+                />
+              </>
+            )}
+          </div>
+        </div>
+
+        <div className="section">
+          <h2>IPFS Retrieval</h2>
+          {loadingIpfs ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              <div className="radio-group">
+                <label className="radio-option surface-card">
+                  <input
+                    type="radio"
+                    name="ipfs-backend"
+                    checked={ipfsDraft.fetchBackend === "helia"}
+                    onChange={() => setIpfsDraft((curr) => ({ ...curr, fetchBackend: "helia" }))}
+                  />
+                  <div>
+                    <div className="label">Helia Verified Fetch (Recommended)</div>
+                    <div className="desc">Fetches via trustless gateways with local cryptographic verification. No local node needed.</div>
+                  </div>
+                </label>
+                <label className="radio-option surface-card">
+                  <input
+                    type="radio"
+                    name="ipfs-backend"
+                    checked={ipfsDraft.fetchBackend === "localnode"}
+                    onChange={() => setIpfsDraft((curr) => ({ ...curr, fetchBackend: "localnode" }))}
+                  />
+                  <div>
+                    <div className="label">Local IPFS Node</div>
+                    <div className="desc">For advanced users running their own IPFS daemon. Implicitly trusted.</div>
+                  </div>
+                </label>
+              </div>
+
+              {ipfsDraft.fetchBackend === "localnode" ? (
+                <div className="field">
+                  <label>Local Node Endpoint</label>
+                  <input
+                    type="text"
+                    placeholder="http://127.0.0.1:8080"
+                    value={ipfsDraft.gatewayEndpoint}
+                    onChange={(e) => setIpfsDraft((curr) => ({ ...curr, gatewayEndpoint: e.target.value }))}
+                  />
+                </div>
+              ) : null}
+
+              <div className="field">
+                <label>Parallel downloads</label>
+                <input
+                  type="number"
+                  min={1}
+                  max={MAX_DOWNLOAD_CONCURRENCY}
+                  value={ipfsDraft.downloadConcurrency}
+                  onChange={(e) =>
+                    setIpfsDraft((curr) => ({ ...curr, downloadConcurrency: Number.parseInt(e.target.value, 10) }))
+                  }
+                />
+                <div className="muted">Bundle files fetched at once. Each file is tried up to 3 times.</div>
+              </div>
+
+              <div className="ipfs-actions">
+                <button
+                  className="primary"
+                  onClick={() => void saveRpcAndIpfsSettings()}
+                  disabled={savingRpcAndIpfs || loadingIpfs || loadingMaxConcurrentRpc}
+                >
+                  {savingRpcAndIpfs ? "Saving..." : "Save"}
+                </button>
+                <button className="secondary" onClick={() => void testIpfs()} disabled={testingIpfs}>
+                  {testingIpfs ? "Testing..." : "Test connectivity"}
+                </button>
+              </div>
+              {ipfsChecks && (
+                <div className="endpoint-list mt-3">
+                  {ipfsChecks.map((check, idx) => (
+                    <div className="endpoint-item surface-card" key={`${idx}-${check.service}-${check.url}`}>
+                      <div className="info">
+                        <div className="url">{check.url}</div>
+                        <div className="lbl">
+                          {IPFS_SERVICE_LABELS[check.service]}
+                          {check.inUse ? "" : " (not used by the current backend)"}
+                        </div>
+                        <div className={`check ${check.ok ? "" : check.reachable ? "warn" : "error"}`}>
+                          {check.latencyMs !== null ? `${check.latencyMs} ms · ` : ""}
+                          {check.detail}
+                        </div>
+                      </div>
+                    </div>
+                  ))}
+                </div>
+              )}
+              <div className="muted">Connectivity tests use the saved settings.</div>
+            </>
+          )}
+          {status && <div className={`status ${status.ok ? "ok" : "err"}`}>{status.text}</div>}
+        </div>
+
+        <div className="section">
+          <h2>Pinned Dapps</h2>
+          {prefetch === null ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              <div className="muted">
+                The latest bundle of each pinned dapp ({prefetch.pinnedCount ?? 0} pinned) is downloaded and verified in
+                the background so it launches instantly, even offline.
+              </div>
+              <label className="radio-option surface-card mt-3">
+                <input
+                  type="checkbox"
+                  checked={prefetch.meteredConnection}
+                  onChange={(e) => setPrefetch((curr) => (curr ? { ...curr, meteredConnection: e.target.checked } : curr))}
+                />
+                <div>
+                  <div className="label">Metered connection</div>
+                  <div className="desc">Pause background downloads until this is turned off.</div>
+                </div>
+              </label>
+              <label className="radio-option surface-card mt-3">
+                <input
+                  type="checkbox"
+                  checked={prefetch.backgroundRefresh?.enabled ?? false}
+                  onChange={(e) =>
+                    setPrefetch((curr) =>
+                      curr
+                        ? {
+                            ...curr,
+                            backgroundRefresh: {
+                              ...(curr.backgroundRefresh ?? DEFAULT_BACKGROUND_REFRESH),
+                              enabled: e.target.checked,
+                            },
+                          }
+                        : curr
+                    )
+                  }
+                />
+                <div>
+                  <div className="label">Refresh in the background</div>
+                  <div className="desc">
+                    While the client is idle and online, refresh the dapp list, check pinned dapps for new versions and
+                    refresh ENS names and prices.
+                  </div>
+                </div>
+              </label>
+              {prefetch.backgroundRefresh?.enabled && (
+                <>
+                  <div className="field mt-3">
+                    <label>Refresh every (minutes, at least 5)</label>
+                    <input
+                      type="number"
+                      min={5}
+                      value={prefetch.backgroundRefresh.intervalMins}
+                      onChange={(e) => {
+                        const mins = Number.parseInt(e.target.value, 10);
+                        setPrefetch((curr) =>
+                          curr
+                            ? {
+                                ...curr,
+                                backgroundRefresh: {
+                                  ...(curr.backgroundRefresh ?? DEFAULT_BACKGROUND_REFRESH),
+                                  intervalMins: Number.isFinite(mins) ? mins : 30,
+                                },
+                              }
+                            : curr
+                        );
+                      }}
+                    />
+                  </div>
+                  <label className="radio-option surface-card mt-3">
+                    <input
+                      type="checkbox"
+                      checked={prefetch.backgroundRefresh.pauseOnBattery}
+                      onChange={(e) =>
+                        setPrefetch((curr) =>
+                          curr
+                            ? {
+                                ...curr,
+                                backgroundRefresh: {
+                                  ...(curr.backgroundRefresh ?? DEFAULT_BACKGROUND_REFRESH),
+                                  pauseOnBattery: e.target.checked,
+                                },
+                              }
+                            : curr
+                        )
+                      }
+                    />
+                    <div>
+                      <div className="label">Pause on battery</div>
+                      <div className="desc">Skip background refreshes while the computer runs on battery.</div>
+                    </div>
+                  </label>
+                </>
+              )}
+              <div className="field mt-3">
+                <label>Bundle cache limit (MB, empty for unlimited)</label>
+                <input
+                  type="number"
+                  min={1}
+                  value={cacheLimitInput}
+                  onChange={(e) => setCacheLimitInput(e.target.value)}
+                />
+              </div>
+              <div className="field mt-3">
+                <label>Launcher icon downloads per listing (KB, empty for 1024, 0 turns them off)</label>
+                <input
+                  type="number"
+                  min={0}
+                  value={iconBudgetInput}
+                  onChange={(e) => setIconBudgetInput(e.target.value)}
+                />
+              </div>
+              <div className="ipfs-actions">
+                <button className="primary" onClick={() => void savePrefetchSettings()} disabled={savingPrefetch}>
+                  {savingPrefetch ? "Saving..." : "Save"}
+                </button>
+              </div>
+              <div className="muted mt-3">
+                {integrity
+                  ? `Last cache check ${new Date(integrity.finishedAt * 1000).toLocaleString()}: ${integrity.checked} bundles verified, ${integrity.quarantined.length} quarantined.`
+                  : "Cached bundles are re-verified in the background shortly after startup and daily."}
+              </div>
+              {integrity?.quarantined.map((bundle) => (
+                <div className="lbl" key={bundle.rootCid} title={bundle.error}>
+                  Quarantined {bundle.rootCid}: {bundle.error}
+                </div>
+              ))}
+            </>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>Bundle Cache</h2>
+          {cacheUsage === null ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              <div className="muted">
+                {formatMb(cacheUsage.totalBytes)} of{" "}
+                {cacheUsage.maxBytes ? formatMb(cacheUsage.maxBytes) : "unlimited"} used. Past the limit set under
+                Pinned Dapps, the least recently launched bundles are removed; pinned bundles and bundles open in a tab
+                are kept.
+              </div>
+              <div className="endpoint-list mt-3">
+                {cacheUsage.bundles.length === 0 && <div className="empty">No bundles cached.</div>}
+                {cacheUsage.bundles.map((bundle) => (
+                  <div className="endpoint-item surface-card" key={bundle.rootCid}>
+                    <div className="info">
+                      <div className="url">{bundle.name || bundle.rootCid}</div>
+                      <div className="lbl" title={bundle.rootCid}>
+                        {formatMb(bundle.bytes)} · last used {new Date(bundle.lastUsed * 1000).toLocaleString()}
+                        {bundle.pinned ? " · pinned" : ""}
+                        {bundle.open ? " · open" : ""}
+                      </div>
+                    </div>
+                    <div className="endpoint-actions">
+                      <button
+                        onClick={() => void clearCache(bundle)}
+                        disabled={clearingCache || bundle.open}
+                        title="Remove cached bundle"
+                      >
+                        Remove
+                      </button>
+                    </div>
+                  </div>
+                ))}
+              </div>
+              <div className="ipfs-actions">
+                <button
+                  className="secondary"
+                  onClick={() => void clearCache()}
+                  disabled={clearingCache || cacheUsage.bundles.length === 0}
+                >
+                  {clearingCache ? "Clearing..." : "Clear cache"}
+                </button>
+              </div>
+            </>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>Profiles</h2>
+          <div className="muted">
+            Each profile has its own settings, keys, permissions, tabs and caches. Switching restarts VibeFi with the
+            other profile.
+          </div>
+          {profiles === null ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              <div className="endpoint-list mt-3">
+                {profiles.map((profile) => (
+                  <div className="endpoint-item surface-card" key={profile.name}>
+                    <div className="info">
+                      <div className="url">{profile.name}</div>
+                      {profile.active && <div className="lbl">Active</div>}
+                    </div>
+                    {!profile.active && (
+                      <div className="endpoint-actions">
+                        <button onClick={() => void switchProfile(profile.name)}>Switch</button>
+                      </div>
+                    )}
+                  </div>
+                ))}
+              </div>
+              <div className="add-form">
+                <div className="field flex-2">
+                  <label>New profile</label>
+                  <input
+                    type="text"
+                    placeholder="work"
+                    value={profileDraft}
+                    onChange={(e) => setProfileDraft(e.target.value)}
+                  />
+                </div>
+                <button className="secondary" onClick={() => void createProfile()} disabled={!profileDraft.trim()}>
+                  Create
+                </button>
+              </div>
+            </>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>Security</h2>
+          {lockSettings === null ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              <div className="muted">
+                {lockSettings.enabled
+                  ? "VibeFi starts locked and asks for the passphrase. Signing requests are refused while locked."
+                  : "Set a passphrase to lock VibeFi at startup, after inactivity or on demand."}
+              </div>
+              {lockSettings.enabled && (
+                <div className="field mt-3">
+                  <label>Current passphrase</label>
+                  <input
+                    type="password"
+                    value={passphraseDraft.current}
+                    onChange={(e) => setPassphraseDraft({ ...passphraseDraft, current: e.target.value })}
+                  />
+                </div>
+              )}
+              <div className="field">
+                <label>{lockSettings.enabled ? "New passphrase" : "Passphrase"}</label>
+                <input
+                  type="password"
+                  value={passphraseDraft.next}
+                  placeholder="At least 8 characters"
+                  onChange={(e) => setPassphraseDraft({ ...passphraseDraft, next: e.target.value })}
+                />
+              </div>
+              <div className="ipfs-actions">
+                <button
+                  className="primary"
+                  onClick={() => void savePassphrase(false)}
+                  disabled={savingLock || !passphraseDraft.next}
+                >
+                  {savingLock ? "Saving..." : lockSettings.enabled ? "Change passphrase" : "Set passphrase"}
+                </button>
+                {lockSettings.enabled && (
+                  <button
+                    className="secondary"
+                    onClick={() => void savePassphrase(true)}
+                    disabled={savingLock || !passphraseDraft.current}
+                  >
+                    Remove passphrase
+                  </button>
+                )}
+              </div>
+              {lockSettings.enabled && (
+                <>
+                  <div className="add-form mt-3">
+                    <div className="field" style={{ flex: 1 }}>
+                      <label>Auto-lock after (minutes idle, blank for never)</label>
+                      <input
+                        value={autoLockInput}
+                        placeholder="Never"
+                        onChange={(e) => setAutoLockInput(e.target.value)}
+                      />
+                    </div>
+                    <button className="primary" onClick={() => void saveAutoLock()}>
+                      Save
+                    </button>
+                  </div>
+                  <div className="ipfs-actions">
+                    <button className="secondary" onClick={() => void lockNow()}>
+                      Lock now
+                    </button>
+                  </div>
+                </>
+              )}
+            </>
+          )}
+        </div>
 
         <div className="section">
-          <h2>RPC Endpoints</h2>
-          {loadingEndpoints ? (
+          <h2>Transactions</h2>
+          {txSettings === null ? (
             <div className="empty">Loading...</div>
-          ) : endpoints.length === 0 ? (
-            <div className="empty">No endpoints configured.</div>
+          ) : (
+            <>
+              <label className="radio-option surface-card">
+                <input
+                  type="checkbox"
+                  checked={txSettings.accessLists}
+                  disabled={savingTxSettings}
+                  onChange={(e) => void saveTransactionSettings({ ...txSettings, accessLists: e.target.checked })}
+                />
+                <div>
+                  <div className="label">Generate access lists</div>
+                  <div className="desc">
+                    Ask the RPC which contracts and storage a transaction touches before sending it. The list is
+                    attached when it lowers the gas estimate and shown in the confirmation prompt.
+                  </div>
+                </div>
+              </label>
+              <label className="radio-option surface-card mt-3">
+                <input
+                  type="checkbox"
+                  checked={txSettings.offlineSigning}
+                  disabled={savingTxSettings}
+                  onChange={(e) => void saveTransactionSettings({ ...txSettings, offlineSigning: e.target.checked })}
+                />
+                <div>
+                  <div className="label">Sign without broadcasting</div>
+                  <div className="desc">
+                    Keep signed transactions here instead of sending them, to export for an online machine or a
+                    private relay. Dapps get the transaction hash as usual.
+                  </div>
+                </div>
+              </label>
+              <label className="radio-option surface-card mt-3">
+                <input
+                  type="checkbox"
+                  checked={txSettings.fiatPrices.enabled}
+                  disabled={savingTxSettings}
+                  onChange={(e) =>
+                    void saveTransactionSettings({
+                      ...txSettings,
+                      fiatPrices: { ...txSettings.fiatPrices, enabled: e.target.checked },
+                    })
+                  }
+                />
+                <div>
+                  <div className="label">Show fiat values</div>
+                  <div className="desc">
+                    Price the native value and recognized token amounts in confirmation prompts. Each priced
+                    transaction sends its chain and token address to the price service.
+                  </div>
+                </div>
+              </label>
+              {txSettings.fiatPrices.enabled && (
+                <>
+                  <div className="field mt-3">
+                    <label>Currency</label>
+                    <input
+                      value={txSettings.fiatPrices.currency ?? ""}
+                      placeholder="USD"
+                      onChange={(e) => editFiatPrices({ currency: e.target.value })}
+                    />
+                  </div>
+                  <div className="field">
+                    <label>Price service (CoinGecko-compatible API)</label>
+                    <input
+                      value={txSettings.fiatPrices.endpoint ?? ""}
+                      placeholder="https://api.coingecko.com/api/v3"
+                      onChange={(e) => editFiatPrices({ endpoint: e.target.value })}
+                    />
+                  </div>
+                  <div className="ipfs-actions">
+                    <button
+                      className="primary"
+                      onClick={() => void saveTransactionSettings(txSettings)}
+                      disabled={savingTxSettings}
+                    >
+                      {savingTxSettings ? "Saving..." : "Save"}
+                    </button>
+                  </div>
+                </>
+              )}
+            </>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>Signed transactions</h2>
+          <div className="muted">
+            Transactions signed while signing without broadcasting is on. Importing an export asks before sending
+            anything to the active chain.
+          </div>
+          {signedTxs === null ? (
+            <div className="empty">Loading...</div>
+          ) : signedTxs.length === 0 ? (
+            <div className="empty">No signed transactions waiting for export.</div>
           ) : (
             <div className="endpoint-list">
-              {endpoints.map((ep, idx) => (
-                <div className="endpoint-item surface-card" key={`${idx}-${ep.url}`}>
-                  <div className="index">{idx + 1}</div>
+              {signedTxs.map((tx) => (
+                <div className="endpoint-item surface-card" key={tx.hash}>
                   <div className="info">
-                    <div className="url">{ep.url}</div>
-                    {ep.label && <div className="lbl">{ep.label}</div>}
-                  </div>
-                  <div className="endpoint-actions">
-                    <button onClick={() => moveUp(idx)} disabled={idx <= 0} title="Move up">&#x25B2;</button>
-                    <button onClick={() => moveDown(idx)} disabled={idx >= endpoints.length - 1} title="Move down">&#x25BC;</button>
-                    <button onClick={() => removeEndpoint(idx)} disabled={endpoints.length <= 1} title="Remove">&#x2715;</button>
+                    <div className="url">{tx.hash}</div>
+                    <div className="lbl">
+                      From {tx.from} · nonce {tx.nonce} · to {tx.to ?? "new contract"}
+                      {tx.chainId != null && ` · chain ${tx.chainId}`}
+                    </div>
                   </div>
                 </div>
               ))}
             </div>
           )}
-
-          <div className="add-form">
-            <div className="field flex-2">
-              <label>URL</label>
-              <input
-                type="text"
-                placeholder="https://rpc.example.com"
-                value={newUrl}
-                onChange={(e) => setNewUrl(e.target.value)}
-                onKeyDown={(e) => { if (e.key === "Enter") addEndpoint(); }}
-              />
-            </div>
-            <div className="field flex-1">
-              <label>Label (optional)</label>
-              <input
-                type="text"
-                placeholder="My RPC"
-                value={newLabel}
-                onChange={(e) => setNewLabel(e.target.value)}
-                onKeyDown={(e) => { if (e.key === "Enter") addEndpoint(); }}
-              />
-            </div>
-            <button className="secondary mb-0" onClick={addEndpoint}>Add</button>
+          <div className="ipfs-actions">
+            <button
+              className="secondary"
+              onClick={() => void exportSignedTransactions("json")}
+              disabled={!signedTxs?.length}
+            >
+              Export JSON
+            </button>
+            <button
+              className="secondary"
+              onClick={() => void exportSignedTransactions("raw")}
+              disabled={!signedTxs?.length}
+            >
+              Export raw
+            </button>
+            <button
+              className="secondary"
+              onClick={() => void discardSignedTransactions()}
+              disabled={!signedTxs?.length}
+            >
+              Discard
+            </button>
+            <button className="primary" onClick={() => void importSignedTransactions()}>
+              Import & broadcast
+            </button>
           </div>
+        </div>
 
-          <div className="field mt-3">
-            <label>Max Concurrent RPC</label>
-            {loadingMaxConcurrentRpc ? (
-              <div className="empty">Loading...</div>
-            ) : (
-              <>
-                <input
-                  type="number"
-                  min={1}
-                  step={1}
-                  value={maxConcurrentRpcInput}
-                  onChange={(e) => setMaxConcurrentRpcInput(e.target.value)}
-                  onKeyDown={(e) => { if (e.key === "Enter") void saveRpcAndIpfsSettings(); }}
-                />
-              </>
-            )}
+        <div className="section">
+          <h2>Accounts</h2>
+          <div className="muted">
+            Accounts of the local signer. Dapps see the active account first. Accounts derived from an imported recovery
+            phrase are added here with the keystore password.
           </div>
+          {localAccounts === null ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              {localAccounts.accounts.length === 0 ? (
+                <div className="empty">No local keys. Import one from the wallet selector.</div>
+              ) : (
+                <div className="endpoint-list">
+                  {localAccounts.accounts.map((account, index) => (
+                    <div className="endpoint-item surface-card" key={account}>
+                      <div className="info">
+                        <div className="url">{account}</div>
+                        <div className="lbl">
+                          Account {index + 1}
+                          {account === localAccounts.active ? " \u00b7 active" : ""}
+                        </div>
+                      </div>
+                      {localAccounts.connected && account !== localAccounts.active && (
+                        <div className="endpoint-actions">
+                          <button onClick={() => void selectAccount(account)}>Use</button>
+                        </div>
+                      )}
+                    </div>
+                  ))}
+                </div>
+              )}
+              {localAccounts.canDerive && (
+                <div className="add-form">
+                  <div className="field" style={{ flex: 1 }}>
+                    <label>Keystore password</label>
+                    <input
+                      type="password"
+                      value={accountPassword}
+                      onChange={(e) => setAccountPassword(e.target.value)}
+                    />
+                  </div>
+                  <button
+                    className="primary"
+                    onClick={() => void addLocalAccount()}
+                    disabled={addingAccount || !accountPassword}
+                  >
+                    {addingAccount ? "Deriving..." : "Add account"}
+                  </button>
+                </div>
+              )}
+            </>
+          )}
         </div>
 
         <div className="section">
-          <h2>IPFS Retrieval</h2>
-          {loadingIpfs ? (
+          <h2>Approval rules</h2>
+          <div className="muted">
+            Approve transactions without a prompt on local devnets and this profile's test network. Every other
+            chain always prompts, and so does the remote signer.
+          </div>
+          {approvalRules === null ? (
             <div className="empty">Loading...</div>
           ) : (
             <>
-              <div className="radio-group">
-                <label className="radio-option surface-card">
+              {approvalRules.rules.length === 0 ? (
+                <div className="empty">No rules. Every transaction prompts.</div>
+              ) : (
+                <div className="endpoint-list">
+                  {approvalRules.rules.map((rule, index) => (
+                    <div className="endpoint-item surface-card" key={`${rule.chainId}:${rule.dapp ?? ""}:${index}`}>
+                      <div className="info">
+                        <div className="url">Up to {rule.maxValue} ETH</div>
+                        <div className="lbl">
+                          Chain {rule.chainId}
+                          {" \u00b7 "}
+                          {rule.dapp ? (rule.dappName ?? rule.dapp) : "any dapp"}
+                          {" \u00b7 "}
+                          {rule.allowCalldata ? "contract calls too" : "plain transfers only"}
+                        </div>
+                      </div>
+                      <div className="endpoint-actions">
+                        <button
+                          onClick={() => void saveApprovalRules(approvalRules.rules.filter((_, i) => i !== index))}
+                          title="Remove"
+                        >
+                          &#x2715;
+                        </button>
+                      </div>
+                    </div>
+                  ))}
+                </div>
+              )}
+              {approvalRules.chains.length > 0 && (
+                <div className="add-form">
+                  <div className="field">
+                    <label>Chain</label>
+                    <select
+                      value={ruleDraft.chainId}
+                      onChange={(e) => setRuleDraft({ ...ruleDraft, chainId: e.target.value })}
+                    >
+                      {approvalRules.chains.map((chainId) => (
+                        <option key={chainId} value={String(chainId)}>
+                          {chainId}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                  <div className="field" style={{ flex: 1 }}>
+                    <label>Dapp</label>
+                    <select value={ruleDraft.dapp} onChange={(e) => setRuleDraft({ ...ruleDraft, dapp: e.target.value })}>
+                      <option value="">Any dapp</option>
+                      {approvalRules.dapps.map((dapp) => (
+                        <option key={dapp.subject} value={dapp.subject}>
+                          {dapp.name}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                  <div className="field">
+                    <label>Max value (ETH)</label>
+                    <input
+                      value={ruleDraft.maxValue}
+                      placeholder="0.01"
+                      onChange={(e) => setRuleDraft({ ...ruleDraft, maxValue: e.target.value })}
+                    />
+                  </div>
+                  <button className="primary" onClick={() => void addApprovalRule()} disabled={!ruleDraft.maxValue.trim()}>
+                    Add
+                  </button>
+                </div>
+              )}
+              {approvalRules.chains.length > 0 && (
+                <label className="radio-option surface-card mt-3">
                   <input
-                    type="radio"
-                    name="ipfs-backend"
-                    checked={ipfsDraft.fetchBackend === "helia"}
-                    onChange={() => setIpfsDraft((curr) => ({ ...curr, fetchBackend: "helia" }))}
+                    type="checkbox"
+                    checked={ruleDraft.allowCalldata}
+                    onChange={(e) => setRuleDraft({ ...ruleDraft, allowCalldata: e.target.checked })}
                   />
                   <div>
-                    <div className="label">Helia Verified Fetch (Recommended)</div>
-                    <div className="desc">Fetches via trustless gateways with local cryptographic verification. No local node needed.</div>
+                    <div className="label">Include contract calls</div>
+                    <div className="desc">
+                      Also approve transactions with calldata. The value limit doesn't cover token amounts in a transfer or
+                      approval.
+                    </div>
                   </div>
                 </label>
-                <label className="radio-option surface-card">
+              )}
+            </>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>Spending</h2>
+          <div className="add-form">
+            <div className="field" style={{ flex: 1 }}>
+              <label>Range</label>
+              <select value={spendRange} onChange={(e) => void loadSpendReport(e.target.value)}>
+                {SPEND_RANGES.map((range) => (
+                  <option key={range.value} value={range.value}>
+                    {range.label}
+                  </option>
+                ))}
+              </select>
+            </div>
+            <button className="secondary" onClick={() => void exportSpendReport()}>
+              Export CSV
+            </button>
+          </div>
+          {spendReport === null ? (
+            <div className="empty">Loading...</div>
+          ) : spendReport.byChain.length === 0 ? (
+            <div className="empty">No transactions in this range.</div>
+          ) : (
+            <>
+              <div className="endpoint-list">
+                {spendReport.byChain.map((chain) => (
+                  <div className="endpoint-item surface-card" key={chain.chainId}>
+                    <div className="info">
+                      <div className="url">{describeSpend(chain)}</div>
+                      <div className="lbl">
+                        Chain {chain.chainId}
+                        {" \u00b7 "}
+                        {chain.gasUsed.toLocaleString()} gas
+                      </div>
+                    </div>
+                  </div>
+                ))}
+              </div>
+              <div className="muted">By dapp</div>
+              <div className="endpoint-list">
+                {spendReport.byDapp.map((dapp) => (
+                  <div className="endpoint-item surface-card" key={`${dapp.dapp}:${dapp.dappId ?? ""}:${dapp.chainId}`}>
+                    <div className="info">
+                      <div className="url">
+                        {dapp.dapp}
+                        {dapp.dappId ? ` #${dapp.dappId}` : ""}
+                      </div>
+                      <div className="lbl">
+                        Chain {dapp.chainId}
+                        {" \u00b7 "}
+                        {describeSpend(dapp)}
+                        {" \u00b7 last "}
+                        {new Date(dapp.lastSentAt * 1000).toLocaleDateString()}
+                      </div>
+                    </div>
+                  </div>
+                ))}
+              </div>
+            </>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>Launch history</h2>
+          <div className="muted">Recent dapp launches, with how long each stage took and why a launch failed.</div>
+          <div className="ipfs-actions">
+            <button className="secondary" onClick={() => void loadLaunchHistory()}>
+              Refresh
+            </button>
+          </div>
+          {launchHistory === null ? (
+            <div className="empty">Loading...</div>
+          ) : launchHistory.length === 0 ? (
+            <div className="empty">No launches recorded yet.</div>
+          ) : (
+            <div className="endpoint-list">
+              {launchHistory.slice(0, 50).map((report) => (
+                <div className="endpoint-item surface-card" key={`${report.rootCid}:${report.startedAt}:${report.durationMs}`}>
+                  <div className="info">
+                    <div className="url">
+                      {report.result === "failed" ? "\u26a0 " : ""}
+                      {report.dapp}
+                      {" \u00b7 "}
+                      {new Date(report.startedAt * 1000).toLocaleString()}
+                    </div>
+                    <div className="lbl">{describeLaunch(report)}</div>
+                    {report.error && <div className="lbl">{report.error}</div>}
+                  </div>
+                </div>
+              ))}
+            </div>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>NFTs</h2>
+          {nftSettings === null ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              <div className="add-form">
+                <div className="field" style={{ flex: 1 }}>
+                  <label>Account</label>
                   <input
-                    type="radio"
-                    name="ipfs-backend"
-                    checked={ipfsDraft.fetchBackend === "localnode"}
-                    onChange={() => setIpfsDraft((curr) => ({ ...curr, fetchBackend: "localnode" }))}
+                    list="nft-accounts"
+                    value={nftAccount}
+                    placeholder="0x…"
+                    onChange={(e) => setNftAccount(e.target.value)}
                   />
-                  <div>
-                    <div className="label">Local IPFS Node</div>
-                    <div className="desc">For advanced users running their own IPFS daemon. Implicitly trusted.</div>
-                  </div>
-                </label>
+                  <datalist id="nft-accounts">
+                    {nftSettings.accounts.map((account) => (
+                      <option key={account} value={account} />
+                    ))}
+                  </datalist>
+                </div>
+                <button className="primary" onClick={() => void loadNfts()} disabled={loadingNfts || !nftAccount.trim()}>
+                  {loadingNfts ? "Loading..." : "Load NFTs"}
+                </button>
+              </div>
+              {nftListing && nftListing.nfts.length === 0 && <div className="empty">No NFTs found</div>}
+              {nftListing && nftListing.nfts.length > 0 && (
+                <div className="nft-grid">
+                  {nftListing.nfts.map((nft) => (
+                    <NftCard key={`${nft.contract}:${nft.tokenId}`} nft={nft} />
+                  ))}
+                </div>
+              )}
+              {nftListing?.truncated && <div className="muted">Showing the first {nftListing.nfts.length} tokens.</div>}
+              <div className="field mt-3">
+                <label>Indexer (optional)</label>
+                <input
+                  value={nftSettings.indexer ?? ""}
+                  placeholder="https://indexer.example/{chainId}/{account}/nfts"
+                  onChange={(e) => setNftSettings({ ...nftSettings, indexer: e.target.value })}
+                />
+              </div>
+              <div className="muted">
+                Without an indexer, recent Transfer logs are scanned over RPC, which only finds tokens received in
+                roughly the last 200,000 blocks.
+              </div>
+              <div className="ipfs-actions">
+                <button className="primary" onClick={() => void saveNftSettings()}>
+                  Save
+                </button>
               </div>
+            </>
+          )}
+        </div>
 
-              {ipfsDraft.fetchBackend === "localnode" ? (
-                <div className="field">
-                  <label>Local Node Endpoint</label>
+        <div className="section">
+          <h2>Registries</h2>
+          {registries === null ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              <div className="muted">
+                Dapps from {registries.defaultLabel} ({registries.defaultRegistry}) are always listed. Community
+                registries add their own dapps, badged with their label; each registry's publisher allowlist only
+                applies to its own dapps.
+              </div>
+              <div className="endpoint-list mt-3">
+                {registries.community.map((registry) => (
+                  <div className="endpoint-item surface-card" key={`${registry.chainId}-${registry.address}`}>
+                    <div className="info">
+                      <div className="url">{registry.label}</div>
+                      <div className="lbl">
+                        {registry.address} on chain {registry.chainId}
+                        {registry.active ? "" : " (inactive on this network)"}
+                        {" \u00b7 "}
+                        {registry.trustedPublishers.length
+                          ? `${registry.trustedPublishers.length} trusted publishers`
+                          : "no trusted publishers"}
+                      </div>
+                    </div>
+                    <div className="endpoint-actions">
+                      <button onClick={() => void removeRegistry(registry)} title="Remove">&#x2715;</button>
+                    </div>
+                  </div>
+                ))}
+              </div>
+              <div className="add-form">
+                <div className="field flex-2">
+                  <label>Registry address</label>
                   <input
                     type="text"
-                    placeholder="http://127.0.0.1:8080"
-                    value={ipfsDraft.gatewayEndpoint}
-                    onChange={(e) => setIpfsDraft((curr) => ({ ...curr, gatewayEndpoint: e.target.value }))}
+                    placeholder="0x..."
+                    value={registryDraft.address}
+                    onChange={(e) => setRegistryDraft((curr) => ({ ...curr, address: e.target.value }))}
                   />
                 </div>
-              ) : null}
+                <div className="field flex-1">
+                  <label>Chain ID</label>
+                  <input
+                    type="number"
+                    min={1}
+                    placeholder={String(registries.chainId)}
+                    value={registryDraft.chainId}
+                    onChange={(e) => setRegistryDraft((curr) => ({ ...curr, chainId: e.target.value }))}
+                  />
+                </div>
+                <div className="field flex-1">
+                  <label>Label</label>
+                  <input
+                    type="text"
+                    placeholder="Community picks"
+                    value={registryDraft.label}
+                    onChange={(e) => setRegistryDraft((curr) => ({ ...curr, label: e.target.value }))}
+                  />
+                </div>
+              </div>
+              <div className="field mt-3">
+                <label>Trusted publishers (addresses, optional)</label>
+                <input
+                  type="text"
+                  placeholder="0x..., 0x..."
+                  value={registryDraft.publishers}
+                  onChange={(e) => setRegistryDraft((curr) => ({ ...curr, publishers: e.target.value }))}
+                />
+              </div>
+              <div className="ipfs-actions">
+                <button className="primary" onClick={() => void saveRegistry()} disabled={savingRegistry}>
+                  {savingRegistry ? "Saving..." : "Add registry"}
+                </button>
+              </div>
+            </>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>Dapp Permissions</h2>
+          {dappGrants === null ? (
+            <div className="empty">Loading...</div>
+          ) : dappGrants.length === 0 ? (
+            <div className="empty">No dapp holds accounts, capabilities or notification access.</div>
+          ) : (
+            dappGrants.map((dapp) => (
+              <div key={dapp.subject}>
+                <div className="muted">
+                  {dapp.name}
+                  {dapp.openTabs > 0 ? ` \u00b7 ${dapp.openTabs} open tab${dapp.openTabs === 1 ? "" : "s"}` : ""}
+                </div>
+                <div className="endpoint-list">
+                  {dapp.grants.map((grant) => (
+                    <div className="endpoint-item surface-card" key={`${grant.kind}:${grant.value}`}>
+                      <div className="info">
+                        <div className="url">{grant.label}</div>
+                        <div className="lbl">{grant.kind}</div>
+                      </div>
+                      <div className="endpoint-actions">
+                        <button onClick={() => void revokeGrant(dapp, grant)} title="Revoke">&#x2715;</button>
+                      </div>
+                    </div>
+                  ))}
+                </div>
+                <div className="ipfs-actions">
+                  <button className="secondary" onClick={() => void resetDapp(dapp)}>
+                    Reset dapp
+                  </button>
+                </div>
+              </div>
+            ))
+          )}
+        </div>
 
+        <div className="section">
+          <h2>Permissions</h2>
+          {rememberedPrompts === null ? (
+            <div className="empty">Loading...</div>
+          ) : Object.keys(rememberedPrompts).length === 0 ? (
+            <div className="empty">No remembered choices. Tick "Remember my choice" on a prompt to add one.</div>
+          ) : (
+            <>
+              <div className="endpoint-list">
+                {Object.entries(rememberedPrompts).map(([key, approved]) => {
+                  const [subject, kind, scope] = key.split("|");
+                  return (
+                    <div className="endpoint-item surface-card" key={key}>
+                      <div className="info">
+                        <div className="url">{subject}</div>
+                        <div className="lbl">
+                          {kind} {scope} {"\u00b7"} {approved ? "always allowed" : "always denied"}
+                        </div>
+                      </div>
+                      <div className="endpoint-actions">
+                        <button onClick={() => void forgetPrompt(key)} title="Forget">&#x2715;</button>
+                      </div>
+                    </div>
+                  );
+                })}
+              </div>
               <div className="ipfs-actions">
-                <button
-                  className="primary"
-                  onClick={() => void saveRpcAndIpfsSettings()}
-                  disabled={savingRpcAndIpfs || loadingIpfs || loadingMaxConcurrentRpc}
-                >
-                  {savingRpcAndIpfs ? "Saving..." : "Save"}
+                <button className="secondary" onClick={() => void forgetPrompt()}>
+                  Forget all
+                </button>
+              </div>
+            </>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>Site Data</h2>
+          {siteData === null ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              <div className="muted">
+                Each dapp keeps its cookies and local storage in its own partition. A dapp over the quota asks to clear
+                its data before it launches. Close a dapp's tabs before clearing its data.
+              </div>
+              <div className="endpoint-list mt-3">
+                {siteData.partitions.length === 0 && <div className="empty">No site data stored yet.</div>}
+                {siteData.partitions.map((partition) => (
+                  <div className="endpoint-item surface-card" key={partition.key}>
+                    <div className="info">
+                      <div className="url">
+                        {partition.name || partition.key}
+                        {partition.dappId ? ` #${partition.dappId}` : ""}
+                      </div>
+                      <div className="lbl">
+                        {(partition.usedBytes / (1024 * 1024)).toFixed(1)} MB
+                        {partition.registry ? ` \u00b7 ${partition.registry}` : ""}
+                      </div>
+                    </div>
+                    <div className="endpoint-actions">
+                      <button onClick={() => void clearSiteData(partition)} title="Clear site data">
+                        Clear
+                      </button>
+                    </div>
+                  </div>
+                ))}
+              </div>
+              <div className="field mt-3">
+                <label>Storage quota per dapp (MB, default {siteData.defaultQuotaMb})</label>
+                <input type="number" min={1} value={quotaInput} onChange={(e) => setQuotaInput(e.target.value)} />
+              </div>
+              <div className="ipfs-actions">
+                <button className="primary" onClick={() => void saveStorageQuota()} disabled={savingQuota}>
+                  {savingQuota ? "Saving..." : "Save"}
+                </button>
+              </div>
+            </>
+          )}
+        </div>
+
+        <div className="section">
+          <h2>Tab Usage</h2>
+          {tabUsage === null || heavyTab === null ? (
+            <div className="empty">Loading...</div>
+          ) : (
+            <>
+              <div className="muted">
+                JS heap and main thread load of each open tab, where its webview reports them. Suspended tabs use
+                neither.
+              </div>
+              <div className="endpoint-list mt-3">
+                {tabUsage.tabs.map((tab) => (
+                  <div className="endpoint-item surface-card" key={tab.id}>
+                    <div className="info">
+                      <div className="url">
+                        {tab.label || tab.id}
+                        {tab.usage?.heavy ? " \u26a0" : ""}
+                      </div>
+                      <div className="lbl">{tab.suspended ? "Suspended" : formatTabUsage(tab.usage)}</div>
+                    </div>
+                  </div>
+                ))}
+              </div>
+              {tabUsage.process && (
+                <div className="muted mt-3">
+                  Client and its web processes ({tabUsage.process.processes}):{" "}
+                  {formatMb(tabUsage.process.rssBytes)} resident
+                  {tabUsage.process.cpuPercent != null ? `, ${tabUsage.process.cpuPercent.toFixed(0)}% CPU` : ""}
+                </div>
+              )}
+              <label className="radio-option surface-card mt-3">
+                <input
+                  type="checkbox"
+                  checked={heavyTab.enabled}
+                  onChange={(e) => setHeavyTab((curr) => (curr ? { ...curr, enabled: e.target.checked } : curr))}
+                />
+                <div>
+                  <div className="label">Heavy tab warning</div>
+                  <div className="desc">Mark tabs over these limits in the tab bar.</div>
+                </div>
+              </label>
+              <div className="field mt-3">
+                <label>JS heap limit (MB)</label>
+                <input
+                  type="number"
+                  min={1}
+                  value={heavyTab.heapMb}
+                  onChange={(e) => setHeavyTab((curr) => (curr ? { ...curr, heapMb: Number(e.target.value) } : curr))}
+                />
+              </div>
+              <div className="field">
+                <label>Main thread busy limit (%)</label>
+                <input
+                  type="number"
+                  min={1}
+                  max={100}
+                  value={heavyTab.cpuPercent}
+                  onChange={(e) =>
+                    setHeavyTab((curr) => (curr ? { ...curr, cpuPercent: Number(e.target.value) } : curr))
+                  }
+                />
+              </div>
+              <div className="ipfs-actions">
+                <button className="primary" onClick={() => void saveHeavyTabSettings()} disabled={savingHeavyTab}>
+                  {savingHeavyTab ? "Saving..." : "Save"}
                 </button>
               </div>
             </>
           )}
-          {status && <div className={`status ${status.ok ? "ok" : "err"}`}>{status.text}</div>}
         </div>
 
         <div className="section">
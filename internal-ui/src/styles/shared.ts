@@ -81,6 +81,7 @@ export const sharedFeedbackStyles = `
     color: #dc2626;
     font-size: 13px;
     margin-top: 8px;
+    white-space: pre-line;
   }
   .empty {
     color: #94a3b8;
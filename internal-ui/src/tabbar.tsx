@@ -1,13 +1,19 @@
 import React, { useEffect, useState } from "react";
 import { createRoot } from "react-dom/client";
 import { IpcClient } from "./ipc/client";
-import { PROVIDER_IDS, type Tab } from "./ipc/contracts";
+import {
+  PROVIDER_IDS,
+  type Tab,
+  type TabBadges,
+  type TabUsagePayload,
+  type TabWallet,
+  type WalletconnectRelayPayload,
+} from "./ipc/contracts";
 import { composeStyles, sharedStyles } from "./styles/shared";
 
 declare global {
   interface Window {
-    updateTabs?: (tabs: unknown[], activeIndex: number) => void;
-    updateRpcStatus?: (webviewId: string, pendingCount: number) => void;
+    updateTabs?: (tabs: unknown[], activeIndex: number, forkLabel?: string) => void;
     __VibefiTabbarState?: unknown;
   }
 }
@@ -53,7 +59,46 @@ html, body {
 .tab.active { background: #1e293b; color: #e2e8f0; border-color: #334155; }
 .tab.disabled { cursor: default; opacity: 0.9; }
 .tab.disabled:hover { background: transparent; color: #94a3b8; }
+.tab.suspended .tab-label { font-style: italic; opacity: 0.7; }
 .tab-label { overflow: hidden; text-overflow: ellipsis; }
+.tab-wallet {
+  flex-shrink: 0;
+  padding: 1px 5px;
+  border-radius: 4px;
+  font-size: 10px;
+  line-height: 14px;
+  background: #1e293b;
+  border: 1px solid #334155;
+  color: #94a3b8;
+  cursor: pointer;
+}
+.tab-wallet:hover { color: #e2e8f0; border-color: #64748b; }
+.tab-wallet.hardware { color: #6ee7b7; }
+.tab-wallet.walletconnect { color: #c4b5fd; }
+.tab-wallet.smartaccount { color: #fcd34d; }
+.tab-wallet.remote { color: #93c5fd; }
+.tab-wallet.relay-down { color: #fbbf24; border-color: #92400e; }
+.tab-wallet-add { padding: 1px 4px; }
+.tab-heavy { flex-shrink: 0; font-size: 11px; color: #fbbf24; }
+.tab-badge {
+  flex-shrink: 0;
+  min-width: 14px;
+  padding: 0 4px;
+  border-radius: 7px;
+  font-size: 10px;
+  line-height: 14px;
+  text-align: center;
+}
+.tab-badge.pending { background: #1e293b; color: #94a3b8; }
+.tab-badge.signature { background: #4c1d95; color: #ddd6fe; }
+.tab-badge.crashed { background: #7f1d1d; color: #fecaca; }
+.tab-update-dot {
+  flex-shrink: 0;
+  width: 6px;
+  height: 6px;
+  border-radius: 50%;
+  background: #38bdf8;
+}
 .tab-close {
   display: flex;
   align-items: center;
@@ -91,6 +136,17 @@ html, body {
 .rpc-status.active {
   opacity: 1;
 }
+.fork-label {
+  flex-shrink: 0;
+  margin-right: 6px;
+  padding: 1px 8px;
+  border-radius: 4px;
+  font-size: 11px;
+  line-height: 16px;
+  background: #7c2d12;
+  border: 1px solid #c2410c;
+  color: #fed7aa;
+}
 .rpc-status-spinner {
   width: 10px;
   height: 10px;
@@ -103,48 +159,99 @@ html, body {
 `;
 const styles = composeStyles(sharedStyles, localStyles);
 
-function postTabbarCommand(method: "switchTab" | "closeTab", index: number) {
+function postTabbarCommand(
+  method: "switchTab" | "closeTab" | "switchWallet" | "addWallet",
+  index: number
+) {
   tabbarClient.notify(PROVIDER_IDS.tabbar, method, [index]);
 }
 
+const BACKEND_LABELS: Record<TabWallet["backend"], string> = {
+  local: "Local",
+  walletconnect: "WC",
+  hardware: "HW",
+  smartaccount: "4337",
+  remote: "Remote",
+};
+
+function relayTitle(relay: WalletconnectRelayPayload | null | undefined): string {
+  if (!relay?.state) return "";
+  const host = relay.relayUrl ? relay.relayUrl.replace(/^wss?:\/\//, "") : "default relay";
+  if (relay.state === "reconnecting") {
+    return `\nRelay ${host}: reconnecting (attempt ${relay.attempt ?? 1})`;
+  }
+  return `\nRelay ${host}: ${relay.state}`;
+}
+
+function heavyTitle(usage: TabUsagePayload): string {
+  const parts: string[] = [];
+  if (usage.heapUsed != null) parts.push(`${Math.round(usage.heapUsed / (1024 * 1024))} MB JS heap`);
+  if (usage.cpuPercent != null) parts.push(`main thread ${Math.round(usage.cpuPercent)}% busy`);
+  return `Heavy tab: ${parts.join(", ")}. See Settings \u2192 Tab Usage.`;
+}
+
+function TabBadgeList({ badges, active }: { badges: TabBadges | undefined; active: boolean }) {
+  if (!badges) return null;
+  const pending = badges.pendingRpc ?? 0;
+  const signatures = badges.awaitingSignature ?? 0;
+  return (
+    <>
+      {badges.crashed ? (
+        <span className="tab-badge crashed" title="Not responding; the page may have crashed. Reload or close the tab.">
+          !
+        </span>
+      ) : null}
+      {signatures > 0 ? (
+        <span
+          className="tab-badge signature"
+          title={`${signatures} signing request${signatures === 1 ? "" : "s"} waiting for approval`}
+        >
+          {"\u270e"}
+          {signatures > 1 ? ` ${signatures}` : ""}
+        </span>
+      ) : null}
+      {/* The active tab's count is shown at the end of the bar. */}
+      {!active && pending > 0 ? (
+        <span className="tab-badge pending" title={`${pending} RPC request${pending === 1 ? "" : "s"} in flight`}>
+          {pending}
+        </span>
+      ) : null}
+      {badges.updateAvailable ? (
+        <span className="tab-update-dot" title="A newer version of this dapp is published" />
+      ) : null}
+    </>
+  );
+}
+
+function shortAccount(account: string): string {
+  return account.length > 10 ? `${account.slice(0, 6)}…${account.slice(-4)}` : account;
+}
+
 function App() {
   const [tabs, setTabs] = useState<Tab[]>([]);
   const [activeIndex, setActiveIndex] = useState(0);
-  const [pendingCounts, setPendingCounts] = useState<Map<string, number>>(new Map());
+  const [forkLabel, setForkLabel] = useState<string | undefined>(undefined);
 
   useEffect(() => {
-    window.updateTabs = (nextTabs: unknown[], nextActiveIndex: number) => {
+    window.updateTabs = (nextTabs: unknown[], nextActiveIndex: number, nextForkLabel?: string) => {
       setTabs(Array.isArray(nextTabs) ? (nextTabs as Tab[]) : []);
       setActiveIndex(Number.isFinite(nextActiveIndex) ? nextActiveIndex : 0);
-    };
-
-    window.updateRpcStatus = (webviewId: string, count: number) => {
-      setPendingCounts((prev) => {
-        const next = new Map(prev);
-        if (count === 0) {
-          next.delete(webviewId);
-        } else {
-          next.set(webviewId, count);
-        }
-        return next;
-      });
+      setForkLabel(typeof nextForkLabel === "string" ? nextForkLabel : undefined);
     };
 
     const initial = window.__VibefiTabbarState as
-      | { tabs?: unknown[]; activeIndex?: number }
+      | { tabs?: unknown[]; activeIndex?: number; forkLabel?: string }
       | undefined;
     if (initial && typeof window.updateTabs === "function") {
-      window.updateTabs(initial.tabs ?? [], initial.activeIndex ?? 0);
+      window.updateTabs(initial.tabs ?? [], initial.activeIndex ?? 0, initial.forkLabel);
     }
 
     return () => {
       delete window.updateTabs;
-      delete window.updateRpcStatus;
     };
   }, []);
 
-  const activeTabId = tabs[activeIndex]?.id;
-  const pendingCount = activeTabId ? (pendingCounts.get(activeTabId) ?? 0) : 0;
+  const pendingCount = tabs[activeIndex]?.badges?.pendingRpc ?? 0;
 
   return (
     <>
@@ -153,13 +260,44 @@ function App() {
         {tabs.map((tab, index) => (
           <div
             key={`${tab.id ?? "tab"}:${index}`}
-            className={`tab${index === activeIndex ? " active" : ""}${tab.clickable === false ? " disabled" : ""}`}
+            className={`tab${index === activeIndex ? " active" : ""}${tab.clickable === false ? " disabled" : ""}${tab.suspended ? " suspended" : ""}`}
             onClick={() => {
               if (tab.clickable === false) return;
               postTabbarCommand("switchTab", index);
             }}
           >
             <span className="tab-label">{tab.label || tab.id || "Tab"}</span>
+            <TabBadgeList badges={tab.badges} active={index === activeIndex} />
+            {tab.heavy ? (
+              <span className="tab-heavy" title={heavyTitle(tab.heavy)}>
+                {"\u26a0"}
+              </span>
+            ) : null}
+            {tab.wallet && tab.walletSwitchable !== false ? (
+              <span
+                className={`tab-wallet ${tab.wallet.backend}${tab.walletRelay && tab.walletRelay.state !== "connected" ? " relay-down" : ""}`}
+                title={`${tab.wallet.account} — click to switch wallet for this tab${relayTitle(tab.walletRelay)}`}
+                onClick={(event) => {
+                  event.stopPropagation();
+                  postTabbarCommand("switchWallet", index);
+                }}
+              >
+                {BACKEND_LABELS[tab.wallet.backend] ?? tab.wallet.backend} {shortAccount(tab.wallet.account)}
+                {(tab.walletAccounts ?? 1) > 1 ? ` +${(tab.walletAccounts ?? 1) - 1}` : ""}
+              </span>
+            ) : null}
+            {tab.wallet && tab.walletSwitchable !== false ? (
+              <span
+                className="tab-wallet tab-wallet-add"
+                title="Connect another wallet to this tab"
+                onClick={(event) => {
+                  event.stopPropagation();
+                  postTabbarCommand("addWallet", index);
+                }}
+              >
+                +
+              </span>
+            ) : null}
             {tab.loading ? <span className="tab-spinner" aria-label="loading" /> : null}
             {tabs.length > 1 && tab.closable !== false ? (
               <span
@@ -178,6 +316,14 @@ function App() {
           <span className="rpc-status-spinner" />
           <span>{pendingCount}</span>
         </div>
+        {forkLabel ? (
+          <span
+            className="fork-label"
+            title="Transactions in this session go to a local fork, not the real chain."
+          >
+            {forkLabel}
+          </span>
+        ) : null}
       </div>
     </>
   );
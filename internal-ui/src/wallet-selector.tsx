@@ -1,7 +1,12 @@
 import React, { useEffect, useState } from "react";
 import { createRoot } from "react-dom/client";
 import { IpcClient } from "./ipc/client";
-import { PROVIDER_IDS, type WalletconnectPairingPayload } from "./ipc/contracts";
+import { errorText } from "./ipc/errors";
+import {
+  PROVIDER_IDS,
+  type WalletconnectPairingPayload,
+  type WalletconnectRelayPayload,
+} from "./ipc/contracts";
 import {
   composeStyles,
   sharedFeedbackStyles,
@@ -17,10 +22,26 @@ declare global {
   }
 }
 
-type Phase = "select" | "localKey" | "connecting" | "done";
+type Phase = "select" | "localImport" | "localUnlock" | "latticeDevice" | "ledgerBle" | "connecting" | "done";
+type DappIdentity = {
+  name: string;
+  dappId?: string;
+  rootCid?: string;
+  publisher?: string;
+};
 type SelectorCapabilities = {
   localSignerAvailable: boolean;
-  localSignerRequiresPrivateKey: boolean;
+  localDeveloperKey?: boolean;
+  localKeystoreAddress?: string | null;
+  requestedBy?: DappIdentity | null;
+  ledgerBleDevice?: BleDevice | null;
+  smartAccountAvailable?: boolean;
+  smartAccountOwner?: "local" | "hardware" | null;
+  remoteSignerUrl?: string | null;
+};
+type BleDevice = {
+  id: string;
+  name: string;
 };
 
 const localStyles = `
@@ -44,6 +65,12 @@ const localStyles = `
   .option-icon.local { background: #dbeafe; }
   .option-icon.wc { background: #ede9fe; }
   .option-icon.hw { background: #d1fae5; }
+  .option-icon.lattice { background: #fef3c7; }
+  .option-icon.ble { background: #e0f2fe; }
+  .option-icon.aa { background: #fef9c3; }
+  .option-icon.remote { background: #e0e7ff; }
+  .option.disabled { cursor: default; opacity: 0.6; }
+  .option.disabled:hover { border-color: inherit; box-shadow: none; }
   .option-text strong { display: block; font-size: 15px; margin-bottom: 2px; }
   .option-text span { font-size: 13px; color: #64748b; }
 
@@ -85,6 +112,8 @@ const localStyles = `
     margin-bottom: 8px;
   }
   .actions { display: flex; gap: 8px; justify-content: center; }
+  .relay-status { font-size: 12px; color: #64748b; margin-bottom: 12px; }
+  .relay-status.reconnecting, .relay-status.failed { color: #b45309; }
 
   .done-view { text-align: center; padding-top: 40px; }
   .done-view .check { font-size: 48px; margin-bottom: 12px; }
@@ -104,6 +133,36 @@ const localStyles = `
     margin-bottom: 12px;
   }
   .local-key-actions { display: flex; gap: 8px; justify-content: flex-end; }
+
+  .modal-backdrop {
+    position: fixed;
+    inset: 0;
+    background: rgba(15, 23, 42, 0.45);
+    display: flex;
+    align-items: center;
+    justify-content: center;
+    padding: 16px;
+  }
+  .modal {
+    width: 100%;
+    max-width: 360px;
+    padding: 20px;
+    border-radius: 12px;
+  }
+  .modal h2 { font-size: 17px; margin-bottom: 6px; }
+  .modal .desc { color: #475569; font-size: 13px; margin-bottom: 14px; }
+  .pairing-input { text-transform: uppercase; letter-spacing: 0.2em; text-align: center; font-size: 16px; }
+
+  .requester { margin-bottom: 16px; padding: 10px 12px; font-size: 13px; color: #334155; }
+  .requester .meta {
+    margin-top: 2px;
+    font-family: ui-monospace, Menlo, Monaco, Consolas, monospace;
+    font-size: 11px;
+    color: #64748b;
+    overflow: hidden;
+    text-overflow: ellipsis;
+    white-space: nowrap;
+  }
 `;
 const styles = composeStyles(
   sharedStyles,
@@ -146,14 +205,41 @@ async function copyText(value: string) {
   document.execCommand("copy");
 }
 
+function RelayStatusLine({ relay }: { relay: WalletconnectRelayPayload | null }) {
+  if (!relay?.state) return null;
+  const host = relay.relayUrl ? relay.relayUrl.replace(/^wss?:\/\//, "") : "default relay";
+  const state =
+    relay.state === "reconnecting" ? `reconnecting (attempt ${relay.attempt ?? 1})` : relay.state;
+  return (
+    <div className={`relay-status ${relay.state}`}>
+      Relay {host}: {state}
+    </div>
+  );
+}
+
 function App() {
   const [phase, setPhase] = useState<Phase>("select");
   const [error, setError] = useState("");
   const [uri, setUri] = useState("");
   const [qrSvg, setQrSvg] = useState("");
+  const [relay, setRelay] = useState<WalletconnectRelayPayload | null>(null);
   const [localSignerAvailable, setLocalSignerAvailable] = useState(false);
-  const [localSignerRequiresPrivateKey, setLocalSignerRequiresPrivateKey] = useState(false);
-  const [localPrivateKey, setLocalPrivateKey] = useState("");
+  const [localDeveloperKey, setLocalDeveloperKey] = useState(false);
+  const [localKeystoreAddress, setLocalKeystoreAddress] = useState<string | null>(null);
+  const [localSecret, setLocalSecret] = useState("");
+  const [localPassword, setLocalPassword] = useState("");
+  const [localPasswordConfirm, setLocalPasswordConfirm] = useState("");
+  const [requestedBy, setRequestedBy] = useState<DappIdentity | null>(null);
+  const [latticeDeviceId, setLatticeDeviceId] = useState("");
+  const [latticePairing, setLatticePairing] = useState(false);
+  const [latticeCode, setLatticeCode] = useState("");
+  const [latticeBusy, setLatticeBusy] = useState(false);
+  const [rememberedLedger, setRememberedLedger] = useState<BleDevice | null>(null);
+  const [ledgerDevices, setLedgerDevices] = useState<BleDevice[]>([]);
+  const [ledgerScanning, setLedgerScanning] = useState(false);
+  const [smartAccountAvailable, setSmartAccountAvailable] = useState(false);
+  const [smartAccountOwner, setSmartAccountOwner] = useState<"local" | "hardware" | null>(null);
+  const [remoteSignerUrl, setRemoteSignerUrl] = useState<string | null>(null);
 
   useEffect(() => {
     const onPairing = (event: Event) => {
@@ -162,9 +248,15 @@ function App() {
       if (typeof detail.uri === "string") setUri(detail.uri);
       if (typeof detail.qrSvg === "string") setQrSvg(detail.qrSvg);
     };
+    const onRelay = (event: Event) => {
+      const custom = event as CustomEvent<WalletconnectRelayPayload>;
+      setRelay(custom.detail?.state ? custom.detail : null);
+    };
     window.addEventListener("vibefi:walletconnect-pairing", onPairing);
+    window.addEventListener("vibefi:walletconnect-relay", onRelay);
     return () => {
       window.removeEventListener("vibefi:walletconnect-pairing", onPairing);
+      window.removeEventListener("vibefi:walletconnect-relay", onRelay);
     };
   }, []);
 
@@ -178,21 +270,32 @@ function App() {
           typeof capabilities === "object" &&
           typeof (capabilities as SelectorCapabilities).localSignerAvailable === "boolean" &&
           (capabilities as SelectorCapabilities).localSignerAvailable;
-        const requiresPrivateKey =
-          !!capabilities &&
-          typeof capabilities === "object" &&
-          typeof (capabilities as SelectorCapabilities).localSignerRequiresPrivateKey ===
-            "boolean" &&
-          (capabilities as SelectorCapabilities).localSignerRequiresPrivateKey;
+        const requester =
+          !!capabilities && typeof capabilities === "object"
+            ? (capabilities as SelectorCapabilities).requestedBy ?? null
+            : null;
+        const ledger =
+          !!capabilities && typeof capabilities === "object"
+            ? (capabilities as SelectorCapabilities).ledgerBleDevice ?? null
+            : null;
+        const smartAccount =
+          !!capabilities && typeof capabilities === "object"
+            ? (capabilities as SelectorCapabilities)
+            : null;
         if (!cancelled) {
+          setSmartAccountAvailable(smartAccount?.smartAccountAvailable === true);
+          setSmartAccountOwner(smartAccount?.smartAccountOwner ?? null);
+          setRemoteSignerUrl(smartAccount?.remoteSignerUrl ?? null);
+          setRememberedLedger(ledger && typeof ledger.id === "string" ? ledger : null);
           setLocalSignerAvailable(available);
-          setLocalSignerRequiresPrivateKey(available && requiresPrivateKey);
+          setLocalDeveloperKey(available && smartAccount?.localDeveloperKey === true);
+          setLocalKeystoreAddress(smartAccount?.localKeystoreAddress ?? null);
+          setRequestedBy(requester && typeof requester.name === "string" ? requester : null);
         }
       } catch (err) {
         console.warn("[vibefi:wallet-selector] failed to load capabilities", err);
         if (!cancelled) {
           setLocalSignerAvailable(false);
-          setLocalSignerRequiresPrivateKey(false);
         }
       }
     };
@@ -202,36 +305,62 @@ function App() {
     };
   }, []);
 
-  const connectLocalWithKey = async (privateKey?: string) => {
+  const runLocalConnect = async (method: string, params: unknown[], retryPhase: Phase) => {
     setPhase("connecting");
     setError("");
     try {
-      await walletIpc("vibefi_connectLocal", privateKey ? [privateKey] : []);
+      await walletIpc(method, params);
+      setLocalSecret("");
+      setLocalPassword("");
+      setLocalPasswordConfirm("");
       setPhase("done");
     } catch (err: any) {
       console.warn("[vibefi:wallet-selector] local connect failed", err);
-      setError(err?.message || String(err));
-      setPhase("select");
+      setError(errorText(err));
+      setPhase(retryPhase);
     }
   };
 
   const connectLocal = async () => {
-    if (localSignerRequiresPrivateKey) {
-      setError("");
-      setLocalPrivateKey("");
-      setPhase("localKey");
-      return;
+    setError("");
+    setLocalPassword("");
+    if (localDeveloperKey) {
+      await runLocalConnect("vibefi_connectLocal", [], "select");
+    } else if (localKeystoreAddress) {
+      setPhase("localUnlock");
+    } else {
+      setLocalSecret("");
+      setLocalPasswordConfirm("");
+      setPhase("localImport");
     }
-    await connectLocalWithKey();
   };
 
-  const submitLocalPrivateKey = async () => {
-    const privateKey = localPrivateKey.trim();
-    if (!privateKey) {
-      setError("Enter a private key to continue.");
+  const submitLocalImport = async () => {
+    if (!localSecret.trim()) {
+      setError("Enter a private key or recovery phrase to continue.");
+      return;
+    }
+    if (localPassword !== localPasswordConfirm) {
+      setError("The passwords do not match.");
       return;
     }
-    await connectLocalWithKey(privateKey);
+    await runLocalConnect(
+      "vibefi_importLocalKey",
+      [{ secret: localSecret.trim(), password: localPassword }],
+      "localImport"
+    );
+  };
+
+  const forgetLocalKey = async () => {
+    setError("");
+    try {
+      await walletIpc("vibefi_forgetLocalKey", [localPassword]);
+      setLocalKeystoreAddress(null);
+      setLocalPassword("");
+      setPhase("localImport");
+    } catch (err: any) {
+      setError(errorText(err));
+    }
   };
 
   const connectWalletConnect = async () => {
@@ -242,7 +371,7 @@ function App() {
       setPhase("done");
     } catch (err: any) {
       console.warn("[vibefi:wallet-selector] walletconnect connect failed", err);
-      setError(err?.message || String(err));
+      setError(errorText(err));
       setPhase("select");
     }
   };
@@ -255,11 +384,117 @@ function App() {
       setPhase("done");
     } catch (err: any) {
       console.warn("[vibefi:wallet-selector] hardware connect failed", err);
-      setError(err?.message || String(err));
+      setError(errorText(err));
+      setPhase("select");
+    }
+  };
+
+  const connectSmartAccount = async () => {
+    if (!smartAccountOwner) return;
+    setPhase("connecting");
+    setError("");
+    try {
+      await walletIpc("vibefi_connectSmartAccount");
+      setPhase("done");
+    } catch (err: any) {
+      console.warn("[vibefi:wallet-selector] smart account connect failed", err);
+      setError(errorText(err));
       setPhase("select");
     }
   };
 
+  const connectRemoteSigner = async () => {
+    setPhase("connecting");
+    setError("");
+    try {
+      await walletIpc("vibefi_connectRemoteSigner");
+      setPhase("done");
+    } catch (err: any) {
+      console.warn("[vibefi:wallet-selector] remote signer connect failed", err);
+      setError(errorText(err));
+      setPhase("select");
+    }
+  };
+
+  const scanLedgerBle = async () => {
+    setLedgerScanning(true);
+    setError("");
+    try {
+      const devices = await walletIpc("vibefi_scanLedgerBle");
+      setLedgerDevices(Array.isArray(devices) ? (devices as BleDevice[]) : []);
+    } catch (err: any) {
+      console.warn("[vibefi:wallet-selector] ledger bluetooth scan failed", err);
+      setError(errorText(err));
+    } finally {
+      setLedgerScanning(false);
+    }
+  };
+
+  const openLedgerBle = () => {
+    setError("");
+    setLedgerDevices([]);
+    setPhase("ledgerBle");
+    void scanLedgerBle();
+  };
+
+  const connectLedgerBle = async (device: BleDevice) => {
+    setPhase("connecting");
+    setError("");
+    try {
+      await walletIpc("vibefi_connectLedgerBle", [device]);
+      setPhase("done");
+    } catch (err: any) {
+      console.warn("[vibefi:wallet-selector] ledger bluetooth connect failed", err);
+      setError(errorText(err));
+      setPhase("ledgerBle");
+    }
+  };
+
+  const connectLattice = async () => {
+    const deviceId = latticeDeviceId.trim();
+    if (!deviceId) {
+      setError("Enter the device ID shown on your Lattice.");
+      return;
+    }
+    setPhase("connecting");
+    setError("");
+    try {
+      const result = await walletIpc("vibefi_connectLattice", [{ deviceId }]);
+      if (result === "needsPairing") {
+        setLatticeCode("");
+        setLatticePairing(true);
+        setPhase("latticeDevice");
+        return;
+      }
+      setPhase("done");
+    } catch (err: any) {
+      console.warn("[vibefi:wallet-selector] lattice connect failed", err);
+      setError(errorText(err));
+      setPhase("latticeDevice");
+    }
+  };
+
+  const submitLatticeCode = async () => {
+    const code = latticeCode.trim();
+    if (!code) {
+      setError("Enter the pairing code shown on your Lattice.");
+      return;
+    }
+    setLatticeBusy(true);
+    setError("");
+    try {
+      await walletIpc("vibefi_pairLattice", [code]);
+      setLatticePairing(false);
+      setPhase("done");
+    } catch (err: any) {
+      console.warn("[vibefi:wallet-selector] lattice pairing failed", err);
+      setError(errorText(err));
+    } finally {
+      setLatticeBusy(false);
+      setLatticeCode("");
+    }
+  };
+
   if (phase === "done") {
     return (
       <>
@@ -281,6 +516,7 @@ function App() {
           <div className="spinner" />
           <h2>Connecting...</h2>
           <div className="desc">Setting up wallet connection</div>
+          <RelayStatusLine relay={relay} />
           {error && <div className="error">{error}</div>}
         </div>
       </>
@@ -300,6 +536,7 @@ function App() {
             </div>
           )}
           <textarea id="uri" value={uri} readOnly />
+          <RelayStatusLine relay={relay} />
           <div className="actions">
             <button onClick={() => void copyText(uri)}>Copy URI</button>
             <button onClick={() => { setPhase("select"); setUri(""); setQrSvg(""); setError(""); }}>Back</button>
@@ -310,19 +547,78 @@ function App() {
     );
   }
 
-  if (phase === "localKey") {
+  if (phase === "localImport") {
+    return (
+      <>
+        <style>{styles}</style>
+        <div className="page-container compact local-key-view">
+          <h2>Import a Key</h2>
+          <div className="desc">
+            Paste a private key or a 12 to 24 word recovery phrase. It is stored encrypted with your password, and
+            recovery phrases use their first account (m/44'/60'/0'/0/0).
+          </div>
+          <textarea
+            className="key-input"
+            value={localSecret}
+            onChange={(event) => setLocalSecret(event.currentTarget.value)}
+            placeholder="0x... or recovery phrase"
+            autoComplete="off"
+            spellCheck={false}
+            autoFocus
+          />
+          <input
+            className="key-input"
+            type="password"
+            value={localPassword}
+            onChange={(event) => setLocalPassword(event.currentTarget.value)}
+            placeholder="Password (8 characters or more)"
+          />
+          <input
+            className="key-input"
+            type="password"
+            value={localPasswordConfirm}
+            onChange={(event) => setLocalPasswordConfirm(event.currentTarget.value)}
+            onKeyDown={(event) => {
+              if (event.key === "Enter") void submitLocalImport();
+            }}
+            placeholder="Repeat password"
+          />
+          {error && <div className="error mb-12">{error}</div>}
+          <div className="local-key-actions">
+            <button
+              onClick={() => {
+                setPhase("select");
+                setLocalSecret("");
+                setLocalPassword("");
+                setLocalPasswordConfirm("");
+                setError("");
+              }}
+            >
+              Back
+            </button>
+            <button onClick={() => void submitLocalImport()}>Import and connect</button>
+          </div>
+        </div>
+      </>
+    );
+  }
+
+  if (phase === "localUnlock") {
     return (
       <>
         <style>{styles}</style>
         <div className="page-container compact local-key-view">
-          <h2>Enter Testnet Private Key</h2>
-          <div className="desc">No local signer is configured for this test network.</div>
+          <h2>Unlock Local Key</h2>
+          <div className="desc">Enter the password for {localKeystoreAddress}.</div>
           <input
             className="key-input"
             type="password"
-            value={localPrivateKey}
-            onChange={(event) => setLocalPrivateKey(event.currentTarget.value)}
-            placeholder="0x..."
+            value={localPassword}
+            onChange={(event) => setLocalPassword(event.currentTarget.value)}
+            onKeyDown={(event) => {
+              if (event.key === "Enter") void runLocalConnect("vibefi_unlockLocalKey", [localPassword], "localUnlock");
+            }}
+            placeholder="Password"
             autoFocus
           />
           {error && <div className="error mb-12">{error}</div>}
@@ -330,25 +626,155 @@ function App() {
             <button
               onClick={() => {
                 setPhase("select");
-                setLocalPrivateKey("");
+                setLocalPassword("");
                 setError("");
               }}
             >
               Back
             </button>
-            <button onClick={() => void submitLocalPrivateKey()}>Connect</button>
+            <button title="Delete the stored key; needs its password" onClick={() => void forgetLocalKey()}>
+              Forget key
+            </button>
+            <button onClick={() => void runLocalConnect("vibefi_unlockLocalKey", [localPassword], "localUnlock")}>Unlock</button>
           </div>
         </div>
       </>
     );
   }
 
+  if (phase === "ledgerBle") {
+    const devices = [
+      ...(rememberedLedger ? [rememberedLedger] : []),
+      ...ledgerDevices.filter((device) => device.id !== rememberedLedger?.id),
+    ];
+    return (
+      <>
+        <style>{styles}</style>
+        <div className="page-container compact local-key-view">
+          <h2>Connect Ledger over Bluetooth</h2>
+          <div className="desc">
+            Unlock your Nano X, Stax or Flex and open the Ethereum app. The first connection asks
+            you to confirm pairing on the device.
+          </div>
+          {error && <div className="error mb-12">{error}</div>}
+          <div className="options mb-12">
+            {devices.map((device) => (
+              <div
+                key={device.id}
+                className="option surface-card"
+                onClick={() => void connectLedgerBle(device)}
+              >
+                <div className="option-icon ble">&#x1F4F6;</div>
+                <div className="option-text">
+                  <strong>{device.name}</strong>
+                  <span>{device.id === rememberedLedger?.id ? "Paired" : "Nearby"}</span>
+                </div>
+              </div>
+            ))}
+            {!ledgerScanning && devices.length === 0 && (
+              <div className="desc">No Ledger found nearby.</div>
+            )}
+          </div>
+          <div className="local-key-actions">
+            <button
+              onClick={() => {
+                setPhase("select");
+                setError("");
+              }}
+            >
+              Back
+            </button>
+            <button disabled={ledgerScanning} onClick={() => void scanLedgerBle()}>
+              {ledgerScanning ? "Scanning..." : "Scan again"}
+            </button>
+          </div>
+        </div>
+      </>
+    );
+  }
+
+  if (phase === "latticeDevice") {
+    return (
+      <>
+        <style>{styles}</style>
+        <div className="page-container compact local-key-view">
+          <h2>Connect GridPlus Lattice1</h2>
+          <div className="desc">Enter the device ID from Settings &gt; Device Info on your Lattice.</div>
+          <input
+            className="key-input"
+            value={latticeDeviceId}
+            onChange={(event) => setLatticeDeviceId(event.currentTarget.value)}
+            placeholder="Device ID"
+            autoFocus={!latticePairing}
+          />
+          {error && !latticePairing && <div className="error mb-12">{error}</div>}
+          <div className="local-key-actions">
+            <button
+              onClick={() => {
+                setPhase("select");
+                setError("");
+              }}
+            >
+              Back
+            </button>
+            <button onClick={() => void connectLattice()}>Connect</button>
+          </div>
+        </div>
+        {latticePairing && (
+          <div className="modal-backdrop" role="dialog" aria-modal="true">
+            <div className="modal surface-card">
+              <h2>Pair with Lattice</h2>
+              <div className="desc">Enter the pairing code now shown on your Lattice screen.</div>
+              <input
+                className="key-input pairing-input"
+                type="password"
+                autoComplete="off"
+                value={latticeCode}
+                onChange={(event) => setLatticeCode(event.currentTarget.value)}
+                onKeyDown={(event) => {
+                  if (event.key === "Enter" && !latticeBusy) void submitLatticeCode();
+                }}
+                maxLength={8}
+                disabled={latticeBusy}
+                autoFocus
+              />
+              {error && <div className="error mb-12">{error}</div>}
+              <div className="local-key-actions">
+                <button
+                  disabled={latticeBusy}
+                  onClick={() => {
+                    setLatticePairing(false);
+                    setLatticeCode("");
+                    setError("");
+                  }}
+                >
+                  Cancel
+                </button>
+                <button disabled={latticeBusy} onClick={() => void submitLatticeCode()}>
+                  {latticeBusy ? "Pairing..." : "Pair"}
+                </button>
+              </div>
+            </div>
+          </div>
+        )}
+      </>
+    );
+  }
+
   return (
     <>
       <style>{styles}</style>
       <div className="page-container compact">
         <h1 className="page-title">Connect Wallet</h1>
         <div className="subtitle">Choose how you want to connect to this dapp.</div>
+        {requestedBy && (
+          <div className="requester surface-card">
+            Requested by <strong>{requestedBy.name}</strong>
+            {requestedBy.dappId ? ` #${requestedBy.dappId}` : ""}
+            {requestedBy.rootCid && <div className="meta">CID {requestedBy.rootCid}</div>}
+            {requestedBy.publisher && <div className="meta">Publisher {requestedBy.publisher}</div>}
+          </div>
+        )}
         {error && <div className="error mt-0 mb-12">{error}</div>}
         <div className="options">
           {localSignerAvailable && (
@@ -357,9 +783,11 @@ function App() {
               <div className="option-text">
                 <strong>Local Signer</strong>
                 <span>
-                  {localSignerRequiresPrivateKey
-                    ? "Enter a private key to sign on this test network."
-                    : "Use the configured local signer for transactions."}
+                  {localDeveloperKey
+                    ? "Use the configured developer key on this test network."
+                    : localKeystoreAddress
+                      ? `Unlock the stored key for ${localKeystoreAddress}.`
+                      : "Import a private key or recovery phrase, stored encrypted."}
                 </span>
               </div>
             </div>
@@ -378,6 +806,58 @@ function App() {
               <span>Connect a Ledger or Trezor device via USB.</span>
             </div>
           </div>
+          <div className="option surface-card" onClick={openLedgerBle}>
+            <div className="option-icon ble">&#x1F4F6;</div>
+            <div className="option-text">
+              <strong>Ledger (Bluetooth)</strong>
+              <span>
+                {rememberedLedger
+                  ? `Reconnect ${rememberedLedger.name} without a cable.`
+                  : "Connect a Nano X, Stax or Flex without a cable."}
+              </span>
+            </div>
+          </div>
+          <div
+            className="option surface-card"
+            onClick={() => {
+              setError("");
+              setLatticePairing(false);
+              setPhase("latticeDevice");
+            }}
+          >
+            <div className="option-icon lattice">&#x1F6E1;</div>
+            <div className="option-text">
+              <strong>GridPlus Lattice1</strong>
+              <span>Connect a Lattice1 over its cloud connection.</span>
+            </div>
+          </div>
+          {smartAccountAvailable && (
+            <div
+              className={`option surface-card${smartAccountOwner ? "" : " disabled"}`}
+              onClick={() => void connectSmartAccount()}
+            >
+              <div className="option-icon aa">&#x1F9E9;</div>
+              <div className="option-text">
+                <strong>Smart Account (ERC-4337)</strong>
+                <span>
+                  {smartAccountOwner === "hardware"
+                    ? "Use an account abstraction wallet owned by your hardware wallet."
+                    : smartAccountOwner === "local"
+                      ? "Use an account abstraction wallet owned by the local signer."
+                      : "Connect a hardware wallet or local signer first to own the account."}
+                </span>
+              </div>
+            </div>
+          )}
+          {remoteSignerUrl && (
+            <div className="option surface-card" onClick={() => void connectRemoteSigner()}>
+              <div className="option-icon remote">&#x1F510;</div>
+              <div className="option-text">
+                <strong>Remote Signer</strong>
+                <span>Sign with the service at {remoteSignerUrl}. You confirm every request here.</span>
+              </div>
+            </div>
+          )}
         </div>
       </div>
     </>
@@ -0,0 +1,235 @@
+#!/usr/bin/env node
+
+import process from "node:process";
+import fs from "node:fs";
+import path from "node:path";
+import os from "node:os";
+import crypto from "node:crypto";
+import readline from "node:readline";
+import { Client, Constants } from "gridplus-sdk";
+
+const appName = process.env.VIBEFI_LATTICE_APP_NAME || "VibeFi Desktop";
+const baseUrl = process.env.VIBEFI_LATTICE_BASE_URL || undefined;
+const requestTimeoutMs = Number.parseInt(process.env.VIBEFI_LATTICE_TIMEOUT_MS || "120000", 10);
+
+// The pairing secret is bound to this client key; losing it means re-pairing
+// the device, so it is persisted next to the other helper state.
+const clientStatePath = path.join(os.homedir(), ".vibefi", "lattice-client.json");
+
+// m/44'/60'/0'/0/0
+const HARDENED = 0x80000000;
+const SIGNER_PATH = [HARDENED + 44, HARDENED + 60, HARDENED, 0, 0];
+
+let client = null;
+let paired = false;
+
+function writeMessage(payload) {
+  process.stdout.write(`${JSON.stringify(payload)}\n`);
+}
+
+function writeResponse(payload) {
+  writeMessage(payload);
+}
+
+function log(message) {
+  process.stderr.write(`[lattice-helper] ${message}\n`);
+}
+
+function withTimeout(promise, timeoutMs, label) {
+  let timer = null;
+  return Promise.race([
+    promise,
+    new Promise((_, reject) => {
+      timer = setTimeout(() => reject(new Error(`${label} timeout after ${timeoutMs}ms`)), timeoutMs);
+    })
+  ]).finally(() => {
+    if (timer) clearTimeout(timer);
+  });
+}
+
+function loadClientState() {
+  try {
+    const raw = JSON.parse(fs.readFileSync(clientStatePath, "utf8"));
+    if (typeof raw?.privKey === "string" && raw.privKey.length === 64) {
+      return raw;
+    }
+  } catch {}
+  return null;
+}
+
+function saveClientState(state) {
+  fs.mkdirSync(path.dirname(clientStatePath), { recursive: true });
+  fs.writeFileSync(clientStatePath, JSON.stringify(state), { encoding: "utf8", mode: 0o600 });
+}
+
+function clientPrivKey() {
+  const existing = loadClientState();
+  if (existing) return existing.privKey;
+  const privKey = crypto.randomBytes(32).toString("hex");
+  saveClientState({ privKey });
+  return privKey;
+}
+
+function stripHex(value) {
+  if (typeof value !== "string") {
+    throw new Error("payload must be a hex string");
+  }
+  return value.startsWith("0x") ? value.slice(2) : value;
+}
+
+function toHex(value) {
+  if (value === undefined || value === null) return null;
+  if (typeof value === "number") return `0x${value.toString(16)}`;
+  if (typeof value === "bigint") return `0x${value.toString(16)}`;
+  if (typeof value === "string") return value.startsWith("0x") ? value : `0x${value}`;
+  return `0x${Buffer.from(value).toString("hex")}`;
+}
+
+function normalizeSig(response) {
+  const sig = response?.sig;
+  if (!sig?.r || !sig?.s || sig.v === undefined) {
+    throw new Error("device returned an incomplete signature");
+  }
+  return { r: toHex(sig.r), s: toHex(sig.s), v: toHex(sig.v) };
+}
+
+function requireClient() {
+  if (!client) throw new Error("Lattice is not connected");
+  if (!paired) throw new Error("Lattice is not paired with this app");
+  return client;
+}
+
+async function connect(deviceId) {
+  if (typeof deviceId !== "string" || !deviceId.trim()) {
+    throw new Error("deviceId is required");
+  }
+  client = new Client({ name: appName, privKey: clientPrivKey(), baseUrl });
+  log(`connecting device=${deviceId.trim()}`);
+  paired = Boolean(await withTimeout(client.connect(deviceId.trim()), requestTimeoutMs, "connect"));
+  return { paired };
+}
+
+async function pair(code) {
+  if (!client) throw new Error("Lattice is not connected");
+  if (typeof code !== "string" || !code.trim()) {
+    throw new Error("pairing code is required");
+  }
+  paired = Boolean(await withTimeout(client.pair(code.trim().toUpperCase()), requestTimeoutMs, "pair"));
+  if (!paired) throw new Error("Lattice rejected the pairing code");
+  return { paired };
+}
+
+async function getAddress() {
+  const addresses = await withTimeout(
+    requireClient().getAddresses({ startPath: SIGNER_PATH, n: 1 }),
+    requestTimeoutMs,
+    "getAddresses"
+  );
+  const address = Array.isArray(addresses) ? addresses[0] : null;
+  if (!address) throw new Error("device returned no address");
+  return { address: toHex(address) };
+}
+
+async function signPersonal(payload) {
+  const response = await withTimeout(
+    requireClient().sign({
+      currency: "ETH_MSG",
+      data: {
+        signerPath: SIGNER_PATH,
+        protocol: "signPersonal",
+        payload: `0x${stripHex(payload)}`
+      }
+    }),
+    requestTimeoutMs,
+    "signPersonal"
+  );
+  return normalizeSig(response);
+}
+
+async function signTransaction(payload) {
+  const response = await withTimeout(
+    requireClient().sign({
+      data: {
+        signerPath: SIGNER_PATH,
+        curveType: Constants.SIGNING.CURVES.SECP256K1,
+        hashType: Constants.SIGNING.HASHES.KECCAK256,
+        encodingType: Constants.SIGNING.ENCODINGS.EVM,
+        payload: Buffer.from(stripHex(payload), "hex")
+      }
+    }),
+    requestTimeoutMs,
+    "signTransaction"
+  );
+  return normalizeSig(response);
+}
+
+async function handleCommand(msg) {
+  const { id, method, params } = msg || {};
+  if (typeof id !== "number") {
+    throw new Error("Command is missing numeric id");
+  }
+  if (typeof method !== "string") {
+    throw new Error("Command is missing method");
+  }
+  if (method === "ping") {
+    return { id, result: { ok: true } };
+  }
+  if (method === "connect") {
+    return { id, result: await connect(params?.deviceId) };
+  }
+  if (method === "pair") {
+    return { id, result: await pair(params?.code) };
+  }
+  if (method === "getAddress") {
+    return { id, result: await getAddress() };
+  }
+  if (method === "signPersonal") {
+    return { id, result: await signPersonal(params?.payload) };
+  }
+  if (method === "signTransaction") {
+    return { id, result: await signTransaction(params?.payload) };
+  }
+  throw new Error(`Unknown helper method: ${method}`);
+}
+
+const rl = readline.createInterface({
+  input: process.stdin,
+  crlfDelay: Infinity
+});
+
+rl.on("line", async (line) => {
+  const trimmed = line.trim();
+  if (!trimmed) return;
+  let msg;
+  try {
+    msg = JSON.parse(trimmed);
+  } catch (error) {
+    writeResponse({
+      id: 0,
+      error: {
+        code: -32700,
+        message: `Invalid JSON: ${String(error)}`
+      }
+    });
+    return;
+  }
+
+  const id = typeof msg?.id === "number" ? msg.id : 0;
+  try {
+    const response = await handleCommand(msg);
+    writeResponse(response);
+  } catch (error) {
+    log(`cmd error id=${id}: ${error instanceof Error ? error.message : String(error)}`);
+    writeResponse({
+      id,
+      error: {
+        code: -32000,
+        message: error instanceof Error ? error.message : String(error)
+      }
+    });
+  }
+});
+
+rl.on("close", () => {
+  process.exit(0);
+});
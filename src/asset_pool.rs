@@ -0,0 +1,109 @@
+//! Threads that read and hash `app://` assets. They are kept apart from the
+//! RPC runtime, whose blocking pool also resolves host names, so a page
+//! loading many large assets neither waits behind RPC work nor delays it.
+//! Reads queue until a thread is free instead of being refused.
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Threads reading assets at once.
+const ASSET_THREADS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct AssetPool {
+    /// `None` when no thread could be started; reads then get one each.
+    jobs: Option<Sender<Job>>,
+}
+
+static POOL: OnceLock<AssetPool> = OnceLock::new();
+
+fn start(threads: usize) -> AssetPool {
+    let (tx, rx) = channel::<Job>();
+    let rx = Arc::new(Mutex::new(rx));
+    let mut started = 0;
+    for index in 0..threads {
+        let rx = Arc::clone(&rx);
+        match std::thread::Builder::new()
+            .name(format!("vibefi-assets-{index}"))
+            .spawn(move || run_jobs(&rx))
+        {
+            Ok(_) => started += 1,
+            Err(err) => tracing::warn!(error = %err, "failed to start asset reader thread"),
+        }
+    }
+    AssetPool {
+        jobs: (started > 0).then_some(tx),
+    }
+}
+
+fn run_jobs(rx: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = rx
+            .lock()
+            .expect("poisoned asset queue lock while waiting for a read")
+            .recv();
+        let Ok(job) = job else {
+            return;
+        };
+        // A read that panics must not take the thread, and the reads queued
+        // behind it, down with it.
+        if catch_unwind(AssertUnwindSafe(job)).is_err() {
+            tracing::error!("asset read panicked");
+        }
+    }
+}
+
+impl AssetPool {
+    fn spawn(&self, job: Job) {
+        let job = match &self.jobs {
+            Some(jobs) => match jobs.send(job) {
+                Ok(()) => return,
+                Err(err) => err.0,
+            },
+            None => job,
+        };
+        std::thread::spawn(job);
+    }
+}
+
+/// Runs `read` on an asset thread once one is free.
+pub fn spawn(read: impl FnOnce() + Send + 'static) {
+    POOL.get_or_init(|| start(ASSET_THREADS))
+        .spawn(Box::new(read));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::start;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    #[test]
+    fn reads_beyond_the_thread_count_wait_their_turn() {
+        let pool = start(2);
+        let (done_tx, done_rx) = channel();
+        for read in 0..50 {
+            let done_tx = done_tx.clone();
+            pool.spawn(Box::new(move || {
+                std::thread::sleep(Duration::from_millis(1));
+                done_tx.send(read).unwrap();
+            }));
+        }
+        let mut done: Vec<i32> = (0..50)
+            .map(|_| done_rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        done.sort();
+        assert_eq!(done, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn a_panicking_read_does_not_stop_the_pool() {
+        let pool = start(1);
+        pool.spawn(Box::new(|| panic!("read failed")));
+        let (done_tx, done_rx) = channel();
+        pool.spawn(Box::new(move || done_tx.send(()).unwrap()));
+        done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+}
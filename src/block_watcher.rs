@@ -0,0 +1,188 @@
+use anyhow::{Context, Result, anyhow};
+use serde_json::json;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::state::AppState;
+
+/// How often the head is polled while anything is subscribed.
+pub const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The chain head, polled once for every feature that needs to react to new
+/// blocks. Polling only runs while at least one [`BlockSubscription`] is
+/// alive.
+#[derive(Debug, Clone, Default)]
+pub struct BlockWatcher {
+    shared: Arc<(Mutex<Head>, Condvar)>,
+}
+
+#[derive(Debug, Default)]
+struct Head {
+    latest: Option<u64>,
+    seen_at: Option<Instant>,
+    subscribers: usize,
+    polling: bool,
+}
+
+/// A handle that wakes on each new block. Dropping it unsubscribes.
+#[derive(Debug)]
+pub struct BlockSubscription {
+    watcher: BlockWatcher,
+    seen: Option<u64>,
+}
+
+impl BlockWatcher {
+    /// Latest block number seen, if any.
+    pub fn latest(&self) -> Option<u64> {
+        self.head().latest
+    }
+
+    /// Latest block number, if it was seen within `max_age`.
+    pub fn recent(&self, max_age: Duration) -> Option<u64> {
+        let head = self.head();
+        head.seen_at
+            .filter(|seen_at| seen_at.elapsed() <= max_age)
+            .and(head.latest)
+    }
+
+    /// Records a block number learned elsewhere, such as a registry query,
+    /// waking subscribers when it is newer than the current head.
+    pub fn record(&self, block: u64) {
+        let (lock, changed) = &*self.shared;
+        let mut head = lock.lock().expect("block watcher");
+        head.seen_at = Some(Instant::now());
+        if head.latest.is_none_or(|latest| block > latest) {
+            head.latest = Some(block);
+            changed.notify_all();
+        }
+    }
+
+    /// Subscribes to new blocks, starting the poller if it is not running.
+    pub fn subscribe(&self, state: &AppState) -> BlockSubscription {
+        let subscription = self.attach();
+        let start = {
+            let mut head = self.head();
+            !std::mem::replace(&mut head.polling, true)
+        };
+        if start {
+            spawn_poller(state.clone(), self.clone());
+        }
+        subscription
+    }
+
+    fn attach(&self) -> BlockSubscription {
+        let mut head = self.head();
+        head.subscribers += 1;
+        BlockSubscription {
+            watcher: self.clone(),
+            seen: head.latest,
+        }
+    }
+
+    fn head(&self) -> std::sync::MutexGuard<'_, Head> {
+        self.shared.0.lock().expect("block watcher")
+    }
+}
+
+impl BlockSubscription {
+    /// Blocks until a block newer than the last one returned arrives, or
+    /// `timeout` passes.
+    pub fn next_block(&mut self, timeout: Duration) -> Option<u64> {
+        let (lock, changed) = &*self.watcher.shared;
+        let guard = lock.lock().expect("block watcher");
+        let seen = self.seen;
+        let (head, _) = changed
+            .wait_timeout_while(guard, timeout, |head| head.latest <= seen)
+            .expect("block watcher");
+        let latest = head.latest.filter(|latest| Some(*latest) > seen)?;
+        self.seen = Some(latest);
+        Some(latest)
+    }
+}
+
+impl Drop for BlockSubscription {
+    fn drop(&mut self) {
+        self.watcher.head().subscribers -= 1;
+    }
+}
+
+/// Polls `eth_blockNumber` until the last subscription is dropped. Polls are
+/// held while offline; the connectivity monitor probes in the meantime.
+fn spawn_poller(state: AppState, watcher: BlockWatcher) {
+    std::thread::spawn(move || {
+        tracing::debug!("block watcher started");
+        loop {
+            {
+                let mut head = watcher.head();
+                if head.subscribers == 0 {
+                    head.polling = false;
+                    break;
+                }
+            }
+            if crate::connectivity::wait_until_online(BLOCK_POLL_INTERVAL) {
+                match fetch_block_number(&state) {
+                    Ok(block) => watcher.record(block),
+                    Err(err) => {
+                        tracing::debug!(error = %format!("{err:#}"), "block number poll failed")
+                    }
+                }
+                std::thread::sleep(BLOCK_POLL_INTERVAL);
+            }
+        }
+        tracing::debug!("block watcher stopped; no subscribers");
+    });
+}
+
+fn fetch_block_number(state: &AppState) -> Result<u64> {
+    let value = crate::ipc::rpc_request(state, "eth_blockNumber", json!([]))
+        .context("eth_blockNumber failed")?;
+    let raw = value
+        .as_str()
+        .ok_or_else(|| anyhow!("eth_blockNumber returned non-string quantity"))?;
+    u64::from_str_radix(raw.trim_start_matches("0x"), 16)
+        .context("eth_blockNumber returned invalid quantity")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockWatcher;
+    use std::time::Duration;
+
+    #[test]
+    fn subscribers_wake_once_per_new_block() {
+        let watcher = BlockWatcher::default();
+        watcher.record(5);
+        let mut sub = watcher.attach();
+        assert_eq!(sub.next_block(Duration::from_millis(10)), None);
+
+        let writer = watcher.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            writer.record(7);
+        });
+        assert_eq!(sub.next_block(Duration::from_secs(5)), Some(7));
+        handle.join().unwrap();
+        assert_eq!(sub.next_block(Duration::from_millis(10)), None);
+    }
+
+    #[test]
+    fn older_heads_from_a_lagging_endpoint_are_ignored() {
+        let watcher = BlockWatcher::default();
+        watcher.record(5);
+        let mut sub = watcher.attach();
+        watcher.record(4);
+        assert_eq!(sub.next_block(Duration::from_millis(10)), None);
+        watcher.record(7);
+        assert_eq!(sub.next_block(Duration::from_millis(10)), Some(7));
+        assert_eq!(watcher.recent(Duration::from_secs(60)), Some(7));
+    }
+
+    #[test]
+    fn counts_attached_subscribers() {
+        let watcher = BlockWatcher::default();
+        let sub = watcher.attach();
+        assert_eq!(watcher.head().subscribers, 1);
+        drop(sub);
+        assert_eq!(watcher.head().subscribers, 0);
+    }
+}
@@ -1,40 +1,93 @@
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
+use sha2::{Digest, Sha256};
 use std::{
+    collections::HashMap,
     fs,
     path::{Component, Path, PathBuf},
     process::Command,
+    time::UNIX_EPOCH,
 };
 
+use crate::manifest::{self, BundleManifest};
 use crate::runtime_paths::resolve_bun_binary;
 
 #[derive(Debug, Clone)]
 pub struct BundleConfig {
     pub dist_dir: PathBuf,
+    /// Built here from a project directory or a bundle without build output.
+    pub local_project: bool,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
-pub struct BundleManifest {
-    pub files: Vec<BundleManifestFile>,
-    #[serde(default)]
-    pub layout: Option<String>,
-    #[serde(default)]
-    pub constraints: Option<BundleConstraints>,
+/// Version of the running client, compared against `minClientVersion`.
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Start of the error returned for bundles needing a newer client; the
+/// launcher keys its update notice on it.
+pub const UPDATE_REQUIRED: &str = "Update required";
+
+/// Digests confirmed by earlier `verify_manifest` runs. Kept under `.vibefi/`
+/// next to the build output so it is never mistaken for a bundle file.
+const VERIFIED_STAMPS_PATH: &str = ".vibefi/verified.json";
+/// Digests of every file a build wrote, next to the dist directory. Built
+/// output is not covered by the manifest, so this is what `app://` enforces
+/// for bundles that are not static-html.
+const DIST_HASHES_FILE: &str = "dist-hashes.json";
+
+/// Size and mtime of a file at the time its digest was last checked. A cached
+/// file whose stamp is unchanged is not re-hashed.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+struct VerifiedStamp {
+    bytes: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+    sha256: String,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
-pub struct BundleManifestFile {
-    pub path: String,
-    pub bytes: u64,
+impl VerifiedStamp {
+    fn new(meta: &fs::Metadata, sha256: &str) -> Option<Self> {
+        let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            bytes: meta.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            sha256: sha256.to_string(),
+        })
+    }
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
-pub struct BundleConstraints {
-    #[serde(rename = "type", default)]
-    pub kind: Option<String>,
+fn load_verified_stamps(bundle_dir: &Path) -> HashMap<String, VerifiedStamp> {
+    fs::read_to_string(bundle_dir.join(VERIFIED_STAMPS_PATH))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_verified_stamps(bundle_dir: &Path, stamps: &HashMap<String, VerifiedStamp>) {
+    let path = bundle_dir.join(VERIFIED_STAMPS_PATH);
+    let result = (|| -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_vec(stamps)?)?;
+        Ok(())
+    })();
+    if let Err(err) = result {
+        // Only costs a re-hash on the next run.
+        tracing::debug!(path = %path.display(), error = %err, "failed to save verified stamps");
+    }
 }
 
 pub fn verify_manifest(bundle_dir: &Path) -> Result<()> {
-    tracing::info!(bundle_dir = %bundle_dir.display(), "verifying bundle manifest");
+    verify_manifest_with(bundle_dir, true)
+}
+
+/// Like [`verify_manifest`], but re-hashes every file even when its verified
+/// stamp is unchanged, catching corruption that kept size and mtime.
+pub fn reverify_manifest(bundle_dir: &Path) -> Result<()> {
+    verify_manifest_with(bundle_dir, false)
+}
+
+fn verify_manifest_with(bundle_dir: &Path, trust_stamps: bool) -> Result<()> {
+    tracing::info!(bundle_dir = %bundle_dir.display(), trust_stamps, "verifying bundle manifest");
     let manifest_path = bundle_dir.join("manifest.json");
     if !manifest_path.exists() {
         tracing::warn!(
@@ -43,9 +96,16 @@ pub fn verify_manifest(bundle_dir: &Path) -> Result<()> {
         );
         return Err(anyhow!("manifest.json missing in bundle"));
     }
-    let content = fs::read_to_string(&manifest_path).context("read manifest.json")?;
-    let manifest: BundleManifest = serde_json::from_str(&content).context("parse manifest.json")?;
-    tracing::debug!(files = manifest.files.len(), "bundle manifest parsed");
+    let manifest = manifest::load(bundle_dir)?;
+    tracing::debug!(
+        files = manifest.files.len(),
+        schema_version = manifest.schema_version,
+        "bundle manifest parsed"
+    );
+    check_manifest_references(&manifest)?;
+    let previous_stamps = load_verified_stamps(bundle_dir);
+    let mut stamps = HashMap::new();
+    let mut hashed = 0usize;
     for entry in manifest.files {
         let file_path = bundle_dir.join(&entry.path);
         if !file_path.exists() {
@@ -67,26 +127,191 @@ pub fn verify_manifest(bundle_dir: &Path) -> Result<()> {
                 meta.len()
             ));
         }
+        let Some(expected) = entry.sha256.as_deref().map(normalize_sha256) else {
+            continue;
+        };
+        let stamp = VerifiedStamp::new(&meta, &expected);
+        if trust_stamps && stamp.is_some() && previous_stamps.get(&entry.path) == stamp.as_ref() {
+            stamps.insert(entry.path, stamp.expect("checked is_some"));
+            continue;
+        }
+        let bytes = fs::read(&file_path).context("read bundle file")?;
+        hashed += 1;
+        let actual = sha256_hex(&bytes);
+        if actual != expected {
+            tracing::warn!(
+                path = %entry.path,
+                expected = %expected,
+                actual = %actual,
+                "bundle file hash mismatch"
+            );
+            return Err(anyhow!("bundle file hash mismatch {}", entry.path));
+        }
+        if let Some(stamp) = stamp {
+            stamps.insert(entry.path, stamp);
+        }
     }
-    tracing::info!(bundle_dir = %bundle_dir.display(), "bundle manifest verified");
+    if stamps != previous_stamps {
+        save_verified_stamps(bundle_dir, &stamps);
+    }
+    tracing::info!(
+        bundle_dir = %bundle_dir.display(),
+        hashed,
+        "bundle manifest verified"
+    );
     Ok(())
 }
 
-fn load_manifest(bundle_dir: &Path) -> Result<BundleManifest> {
-    let manifest_path = bundle_dir.join("manifest.json");
-    let content = fs::read_to_string(&manifest_path).context("read manifest.json")?;
-    serde_json::from_str(&content).context("parse manifest.json")
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Normalizes a manifest digest for comparison (optional `0x` prefix, any case).
+pub fn normalize_sha256(raw: &str) -> String {
+    let trimmed = raw.trim();
+    trimmed
+        .strip_prefix("0x")
+        .unwrap_or(trimmed)
+        .to_ascii_lowercase()
+}
+
+pub(crate) fn normalize_manifest_path(path: &str) -> String {
+    path.trim_start_matches("./")
+        .trim_start_matches('/')
+        .replace('\\', "/")
+}
+
+/// Expected SHA-256 digests for files served from `dist_dir`, keyed by
+/// dist-relative path; files missing from the map are not served. Static-html
+/// bundles serve manifest files verbatim and use the manifest's digests, which
+/// the root CID covers. Other layouts use the digests recorded next to the
+/// build when it finished; the manifest lists sources, not build output, so
+/// nothing published vouches for them. They only catch build output that was
+/// truncated or changed by accident since the build, not a local attacker who
+/// can rewrite the digests file along with the dist.
+pub fn served_file_hashes(dist_dir: &Path) -> Result<HashMap<String, String>> {
+    let bundle_dir = dist_dir
+        .parent()
+        .and_then(Path::parent)
+        .ok_or_else(|| anyhow!("dist dir {} is not inside a bundle", dist_dir.display()))?;
+    let manifest = manifest::load(bundle_dir)?;
+    if manifest.is_static_html() {
+        return Ok(manifest
+            .files
+            .iter()
+            .filter_map(|entry| {
+                let digest = entry.sha256.as_deref()?;
+                Some((
+                    normalize_manifest_path(&entry.path),
+                    normalize_sha256(digest),
+                ))
+            })
+            .collect());
+    }
+    let path = dist_dir.with_file_name(DIST_HASHES_FILE);
+    let raw = fs::read_to_string(&path).with_context(|| {
+        format!(
+            "build output in {} has no recorded hashes; rebuild the bundle",
+            dist_dir.display()
+        )
+    })?;
+    serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))
+}
+
+/// Hashes every file under `dist_dir` and records the digests for
+/// [`served_file_hashes`]. Run once the build output is complete.
+pub fn record_dist_hashes(dist_dir: &Path) -> Result<()> {
+    let mut hashes = HashMap::new();
+    for path in walk_files(dist_dir)? {
+        let rel = path
+            .strip_prefix(dist_dir)
+            .context("dist file outside dist dir")?;
+        let key = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+        hashes.insert(key, sha256_hex(&bytes));
+    }
+    let path = dist_dir.with_file_name(DIST_HASHES_FILE);
+    fs::write(&path, serde_json::to_vec(&hashes)?)
+        .with_context(|| format!("write {}", path.display()))?;
+    tracing::debug!(files = hashes.len(), "recorded dist hashes");
+    Ok(())
+}
+
+/// Whether `dist_dir` holds a finished build whose files can be verified.
+pub fn has_verified_build(dist_dir: &Path) -> bool {
+    dist_dir.join("index.html").exists() && served_file_hashes(dist_dir).is_ok()
+}
+
+/// Checks that icons and the entrypoint point at files the manifest lists.
+fn check_manifest_references(manifest: &BundleManifest) -> Result<()> {
+    let listed = |path: &str| {
+        let path = normalize_manifest_path(path);
+        manifest
+            .files
+            .iter()
+            .any(|entry| normalize_manifest_path(&entry.path) == path)
+    };
+    if let Some(icon) = manifest.icons.iter().find(|icon| !listed(&icon.src)) {
+        bail!("manifest icon {} is not listed in files", icon.src);
+    }
+    // Dapps are always served from `app://index.html`.
+    if normalize_manifest_path(&manifest.entrypoint) != "index.html" {
+        bail!(
+            "manifest entrypoint {} is not supported; dapps must start at index.html",
+            manifest.entrypoint
+        );
+    }
+    if manifest.is_static_html() && !listed(&manifest.entrypoint) {
+        bail!(
+            "static-html manifest does not list its entrypoint {}",
+            manifest.entrypoint
+        );
+    }
+    Ok(())
 }
 
-fn is_static_html_layout(manifest: &BundleManifest) -> bool {
-    if manifest.layout.as_deref() == Some("static-html") {
-        return true;
+/// Refuses bundles whose manifest asks for a newer client than this one, so
+/// they fail before building instead of breaking on missing APIs.
+pub fn ensure_client_supported(bundle_dir: &Path) -> Result<()> {
+    let manifest = manifest::load(bundle_dir)?;
+    let Some(required) = manifest.min_client_version.as_deref() else {
+        return Ok(());
+    };
+    let wanted = parse_version(required)
+        .ok_or_else(|| anyhow!("invalid minClientVersion {required:?} in manifest.json"))?;
+    let running = parse_version(CLIENT_VERSION).expect("CARGO_PKG_VERSION is semver");
+    if running < wanted {
+        tracing::warn!(
+            bundle_dir = %bundle_dir.display(),
+            required,
+            running = CLIENT_VERSION,
+            "bundle requires a newer client"
+        );
+        bail!(
+            "{UPDATE_REQUIRED}: this dapp needs VibeFi {required} or newer, but this is {CLIENT_VERSION}. Update VibeFi and launch it again."
+        );
     }
-    manifest
-        .constraints
-        .as_ref()
-        .and_then(|c| c.kind.as_deref())
-        == Some("static-html")
+    Ok(())
+}
+
+/// Parses `major[.minor[.patch]]`, ignoring a leading `v` and any
+/// pre-release or build suffix.
+fn parse_version(raw: &str) -> Option<(u64, u64, u64)> {
+    let raw = raw.trim();
+    let core = raw.strip_prefix('v').unwrap_or(raw);
+    let core = core.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
 }
 
 fn validate_static_html_bundle_path(path: &Path) -> Result<()> {
@@ -207,14 +432,17 @@ fn write_standard_build_files(bundle_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn build_bundle(bundle_dir: &Path, dist_dir: &Path) -> Result<()> {
+/// Builds `bundle_dir` into `dist_dir`. `source_maps` is for local projects
+/// under the dev profile; published bundles never ship them.
+pub fn build_bundle(bundle_dir: &Path, dist_dir: &Path, source_maps: bool) -> Result<()> {
+    let _span = tracing::info_span!("bundle_build", bundle_dir = %bundle_dir.display()).entered();
     tracing::info!(
         bundle_dir = %bundle_dir.display(),
         dist_dir = %dist_dir.display(),
         "building bundle"
     );
-    let manifest = load_manifest(bundle_dir)?;
-    if is_static_html_layout(&manifest) {
+    let manifest = manifest::load(bundle_dir)?;
+    if manifest.is_static_html() {
         tracing::info!("static-html layout detected; skipping Vite build");
         copy_static_html_bundle(bundle_dir, dist_dir, &manifest)?;
         tracing::info!(dist_dir = %dist_dir.display(), "static-html bundle copy completed");
@@ -231,12 +459,14 @@ pub fn build_bundle(bundle_dir: &Path, dist_dir: &Path) -> Result<()> {
     let node_modules = bundle_dir.join("node_modules");
     if !node_modules.exists() {
         tracing::info!("bundle dependencies missing; running bun install");
-        let output = Command::new(&bun_bin)
-            .arg("install")
-            .arg("--no-save")
-            .current_dir(bundle_dir)
-            .output()
-            .with_context(|| format!("bun install failed (runtime: {bun_bin})"))?;
+        let output = crate::shutdown::output_tracked(
+            Command::new(&bun_bin)
+                .arg("install")
+                .arg("--no-save")
+                .current_dir(bundle_dir),
+            "bun install",
+        )
+        .with_context(|| format!("bun install failed (runtime: {bun_bin})"))?;
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
             let stdout = String::from_utf8_lossy(&output.stdout);
@@ -258,8 +488,9 @@ pub fn build_bundle(bundle_dir: &Path, dist_dir: &Path) -> Result<()> {
     fs::create_dir_all(dist_dir).context("create dist dir")?;
     // Use relative path from bundle_dir for vite's outDir since vite runs in bundle_dir
     let relative_dist = PathBuf::from(".vibefi").join("dist");
-    tracing::info!(out_dir = %relative_dist.display(), "running vite build for bundle");
-    let output = Command::new(&bun_bin)
+    tracing::info!(out_dir = %relative_dist.display(), source_maps, "running vite build for bundle");
+    let mut command = Command::new(&bun_bin);
+    command
         .arg("x")
         .arg("--bun")
         .arg("vite")
@@ -267,8 +498,11 @@ pub fn build_bundle(bundle_dir: &Path, dist_dir: &Path) -> Result<()> {
         .arg("--emptyOutDir")
         .arg("--outDir")
         .arg(&relative_dist)
-        .current_dir(bundle_dir)
-        .output()
+        .current_dir(bundle_dir);
+    if source_maps {
+        command.arg("--sourcemap");
+    }
+    let output = crate::shutdown::output_tracked(&mut command, "vite build")
         .with_context(|| format!("bun vite build failed (runtime: {bun_bin})"))?;
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -285,6 +519,7 @@ pub fn build_bundle(bundle_dir: &Path, dist_dir: &Path) -> Result<()> {
             output.status
         ));
     }
+    record_dist_hashes(dist_dir)?;
     tracing::info!(dist_dir = %dist_dir.display(), "bundle build completed");
     Ok(())
 }
@@ -316,3 +551,546 @@ pub fn walk_files(root: &Path) -> Result<Vec<PathBuf>> {
     }
     Ok(out)
 }
+
+/// Chunk size and links per node of the default UnixFS importer, which both
+/// `ipfs add` and Helia use.
+const UNIXFS_CHUNK_SIZE: usize = 262_144;
+const UNIXFS_MAX_LINKS: usize = 174;
+/// Directories whose links add up to more than this are sharded by the
+/// importer, which [`unixfs_root_cid`] does not reproduce.
+const UNIXFS_SHARDING_THRESHOLD: usize = 262_144;
+const UNIXFS_RAW: u64 = 0;
+const UNIXFS_DIRECTORY: u64 = 1;
+const UNIXFS_FILE: u64 = 2;
+const CODEC_RAW: u64 = 0x55;
+const CODEC_DAG_PB: u64 = 0x70;
+const MULTIHASH_SHA2_256: u64 = 0x12;
+
+/// CID version of a bundle. CIDv0 bundles wrap file chunks in dag-pb
+/// nodes; CIDv1 ones store them as raw blocks, the importer's default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CidVersion {
+    V0,
+    V1,
+}
+
+impl CidVersion {
+    /// Version of a CID as registries store it: base58 `Qm…` for CIDv0,
+    /// base32 `b…` for CIDv1. Other multibases are not recomputed.
+    pub(crate) fn of(root_cid: &str) -> Result<Self> {
+        if root_cid.len() == 46 && root_cid.starts_with("Qm") {
+            Ok(Self::V0)
+        } else if root_cid.starts_with('b') {
+            Ok(Self::V1)
+        } else {
+            bail!("cannot verify root CID {root_cid}: only CIDv0 and base32 CIDv1 are supported")
+        }
+    }
+}
+
+/// Recomputes the root CID of `manifest.json` plus the files it lists,
+/// laid out the way publishing adds them, and fails unless it is
+/// `root_cid`. Run on every downloaded bundle, so a gateway can't serve
+/// anything but what the registry points at.
+pub fn verify_root_cid(bundle_dir: &Path, root_cid: &str) -> Result<()> {
+    let version = CidVersion::of(root_cid)?;
+    let manifest = manifest::load(bundle_dir)?;
+    let paths = manifest
+        .files
+        .iter()
+        .map(|entry| normalize_manifest_path(&entry.path))
+        .chain(std::iter::once("manifest.json".to_string()));
+    let computed = unixfs_root_cid(paths, version, &|path| {
+        fs::read(bundle_dir.join(path)).with_context(|| format!("read bundle file {path}"))
+    })?;
+    let expected = match version {
+        CidVersion::V0 => root_cid.to_string(),
+        CidVersion::V1 => root_cid.to_ascii_lowercase(),
+    };
+    if computed != expected {
+        tracing::warn!(
+            bundle_dir = %bundle_dir.display(),
+            expected = %root_cid,
+            computed = %computed,
+            "bundle root CID mismatch"
+        );
+        bail!("bundle content hashes to {computed}, not to the registry's root CID {root_cid}");
+    }
+    tracing::info!(root_cid, "bundle root CID verified");
+    Ok(())
+}
+
+/// UnixFS CID of a directory holding `paths`, read through `read`, as the
+/// default importer computes it with sha2-256 and no sharding.
+pub(crate) fn unixfs_root_cid(
+    paths: impl IntoIterator<Item = String>,
+    version: CidVersion,
+    read: &dyn Fn(&str) -> Result<Vec<u8>>,
+) -> Result<String> {
+    unixfs_dag(paths, version, read, &mut |_, _| {})
+}
+
+/// [`unixfs_root_cid`], handing every block and its binary CID to `sink`.
+pub(crate) fn unixfs_dag(
+    paths: impl IntoIterator<Item = String>,
+    version: CidVersion,
+    read: &dyn Fn(&str) -> Result<Vec<u8>>,
+    sink: &mut BlockSink<'_>,
+) -> Result<String> {
+    let mut root = UnixfsDir::default();
+    for path in paths {
+        root.insert(&path)?;
+    }
+    Ok(cid_string(&root.build(version, read, sink)?.cid, version))
+}
+
+/// Receives each block of a DAG as it is built: binary CID, then bytes.
+pub(crate) type BlockSink<'a> = dyn FnMut(&[u8], &[u8]) + 'a;
+
+#[derive(Default)]
+struct UnixfsDir {
+    entries: std::collections::BTreeMap<String, UnixfsEntry>,
+}
+
+enum UnixfsEntry {
+    /// Path of the file within the bundle.
+    File(String),
+    Dir(UnixfsDir),
+}
+
+/// A node's CID, its size with everything below it (a link's `Tsize`) and
+/// the file bytes it covers.
+struct DagNode {
+    cid: Vec<u8>,
+    tree_size: u64,
+    file_size: u64,
+}
+
+impl UnixfsDir {
+    fn insert(&mut self, path: &str) -> Result<()> {
+        let mut segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let Some(file_name) = segments.pop() else {
+            bail!("empty bundle path");
+        };
+        let mut dir = self;
+        for segment in segments {
+            let entry = dir
+                .entries
+                .entry(segment.to_string())
+                .or_insert_with(|| UnixfsEntry::Dir(UnixfsDir::default()));
+            dir = match entry {
+                UnixfsEntry::Dir(sub) => sub,
+                UnixfsEntry::File(_) => bail!("bundle path {path} runs through a file"),
+            };
+        }
+        if let Some(UnixfsEntry::Dir(_)) = dir.entries.get(file_name) {
+            bail!("bundle path {path} is also a directory");
+        }
+        dir.entries
+            .insert(file_name.to_string(), UnixfsEntry::File(path.to_string()));
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        version: CidVersion,
+        read: &dyn Fn(&str) -> Result<Vec<u8>>,
+        sink: &mut BlockSink<'_>,
+    ) -> Result<DagNode> {
+        let mut children = Vec::with_capacity(self.entries.len());
+        // BTreeMap order is byte order, which dag-pb requires for links.
+        for (name, entry) in &self.entries {
+            let node = match entry {
+                UnixfsEntry::File(path) => unixfs_file(&read(path)?, version, sink),
+                UnixfsEntry::Dir(dir) => dir.build(version, read, sink)?,
+            };
+            children.push((name.as_str(), node));
+        }
+        let estimated: usize = children
+            .iter()
+            .map(|(name, node)| name.len() + node.cid.len())
+            .sum();
+        if estimated > UNIXFS_SHARDING_THRESHOLD {
+            bail!("bundle directory is too large to verify; IPFS shards directories this size");
+        }
+        let links: Vec<(&str, &DagNode)> =
+            children.iter().map(|(name, node)| (*name, node)).collect();
+        Ok(dag_pb_node(
+            &links,
+            &unixfs_data(UNIXFS_DIRECTORY, &[], None, &[]),
+            0,
+            version,
+            sink,
+        ))
+    }
+}
+
+/// A file as the balanced layout builds it: one leaf while it fits a chunk,
+/// otherwise a tree of at most [`UNIXFS_MAX_LINKS`] links per node that
+/// grows a level whenever the root is full.
+fn unixfs_file(data: &[u8], version: CidVersion, sink: &mut BlockSink<'_>) -> DagNode {
+    let mut chunks = data.chunks(UNIXFS_CHUNK_SIZE).peekable();
+    let Some(first) = chunks.next() else {
+        return unixfs_leaf(&[], UNIXFS_FILE, version, sink);
+    };
+    let mut root = unixfs_leaf(first, UNIXFS_FILE, version, sink);
+    let mut depth = 1;
+    while chunks.peek().is_some() {
+        root = fill_file_node(vec![root], depth, &mut chunks, version, sink);
+        depth += 1;
+    }
+    root
+}
+
+fn fill_file_node(
+    mut children: Vec<DagNode>,
+    depth: u32,
+    chunks: &mut std::iter::Peekable<std::slice::Chunks<'_, u8>>,
+    version: CidVersion,
+    sink: &mut BlockSink<'_>,
+) -> DagNode {
+    while children.len() < UNIXFS_MAX_LINKS {
+        let Some(chunk) = chunks.peek().copied() else {
+            break;
+        };
+        let child = if depth == 1 {
+            chunks.next();
+            unixfs_leaf(chunk, UNIXFS_RAW, version, sink)
+        } else {
+            fill_file_node(Vec::new(), depth - 1, chunks, version, sink)
+        };
+        children.push(child);
+    }
+    let block_sizes: Vec<u64> = children.iter().map(|child| child.file_size).collect();
+    let file_size = block_sizes.iter().sum();
+    let links: Vec<(&str, &DagNode)> = children.iter().map(|child| ("", child)).collect();
+    dag_pb_node(
+        &links,
+        &unixfs_data(UNIXFS_FILE, &[], Some(file_size), &block_sizes),
+        file_size,
+        version,
+        sink,
+    )
+}
+
+fn unixfs_leaf(chunk: &[u8], kind: u64, version: CidVersion, sink: &mut BlockSink<'_>) -> DagNode {
+    let len = chunk.len() as u64;
+    match version {
+        CidVersion::V1 => {
+            let cid = cid_bytes(chunk, CODEC_RAW, version);
+            sink(&cid, chunk);
+            DagNode {
+                cid,
+                tree_size: len,
+                file_size: len,
+            }
+        }
+        CidVersion::V0 => dag_pb_node(
+            &[],
+            &unixfs_data(kind, chunk, Some(len), &[]),
+            len,
+            version,
+            sink,
+        ),
+    }
+}
+
+/// The UnixFS `Data` message.
+fn unixfs_data(kind: u64, data: &[u8], file_size: Option<u64>, block_sizes: &[u64]) -> Vec<u8> {
+    let mut out = vec![0x08];
+    put_varint(&mut out, kind);
+    if !data.is_empty() {
+        put_bytes(&mut out, 0x12, data);
+    }
+    if let Some(file_size) = file_size {
+        out.push(0x18);
+        put_varint(&mut out, file_size);
+    }
+    for size in block_sizes {
+        out.push(0x20);
+        put_varint(&mut out, *size);
+    }
+    out
+}
+
+/// A dag-pb node: its links, then its data.
+fn dag_pb_node(
+    links: &[(&str, &DagNode)],
+    data: &[u8],
+    file_size: u64,
+    version: CidVersion,
+    sink: &mut BlockSink<'_>,
+) -> DagNode {
+    let mut block = Vec::new();
+    for (name, child) in links {
+        let mut link = Vec::new();
+        put_bytes(&mut link, 0x0a, &child.cid);
+        put_bytes(&mut link, 0x12, name.as_bytes());
+        link.push(0x18);
+        put_varint(&mut link, child.tree_size);
+        put_bytes(&mut block, 0x12, &link);
+    }
+    put_bytes(&mut block, 0x0a, data);
+    let cid = cid_bytes(&block, CODEC_DAG_PB, version);
+    sink(&cid, &block);
+    DagNode {
+        cid,
+        tree_size: block.len() as u64 + links.iter().map(|(_, child)| child.tree_size).sum::<u64>(),
+        file_size,
+    }
+}
+
+/// Binary CID of `block`: the bare multihash for CIDv0.
+fn cid_bytes(block: &[u8], codec: u64, version: CidVersion) -> Vec<u8> {
+    let mut out = Vec::with_capacity(36);
+    if version == CidVersion::V1 {
+        put_varint(&mut out, 1);
+        put_varint(&mut out, codec);
+    }
+    put_varint(&mut out, MULTIHASH_SHA2_256);
+    put_bytes_raw(&mut out, &Sha256::digest(block));
+    out
+}
+
+fn cid_string(cid: &[u8], version: CidVersion) -> String {
+    match version {
+        CidVersion::V0 => base58btc(cid),
+        CidVersion::V1 => format!("b{}", base32_lower(cid)),
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// A length-delimited protobuf field.
+fn put_bytes(out: &mut Vec<u8>, tag: u8, bytes: &[u8]) {
+    out.push(tag);
+    put_bytes_raw(out, bytes);
+}
+
+/// Length prefix and bytes.
+fn put_bytes_raw(out: &mut Vec<u8>, bytes: &[u8]) {
+    put_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn base58btc(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    // Little-endian base-58 digits.
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = u32::from(byte);
+        for digit in &mut digits {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|digit| ALPHABET[*digit as usize] as char),
+        )
+        .collect()
+}
+
+/// RFC 4648 base32, lowercase and unpadded, as multibase `b` uses it.
+pub(crate) fn base32_lower(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | u64::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        out.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CidVersion, VERIFIED_STAMPS_PATH, cid_string, parse_version, record_dist_hashes,
+        served_file_hashes, sha256_hex, unixfs_file, unixfs_root_cid, verify_manifest,
+        verify_root_cid,
+    };
+    use std::fs;
+
+    /// A bundle dir holding `index.html` and a manifest that lists it.
+    fn manifest_fixture(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("vibefi-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("index.html"), b"<html>ok</html>").unwrap();
+        let manifest = serde_json::json!({
+            "files": [{
+                "path": "index.html",
+                "bytes": 15,
+                "sha256": sha256_hex(b"<html>ok</html>"),
+            }],
+        });
+        fs::write(dir.join("manifest.json"), manifest.to_string()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn verify_manifest_records_stamps_for_unchanged_files() {
+        let dir = manifest_fixture("verify-manifest-stamps");
+        verify_manifest(&dir).unwrap();
+        assert!(dir.join(VERIFIED_STAMPS_PATH).exists());
+        // Unchanged files take the fast path.
+        verify_manifest(&dir).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn verify_manifest_catches_changes_of_the_same_length() {
+        let dir = manifest_fixture("verify-manifest-changed");
+        verify_manifest(&dir).unwrap();
+        // Same length, different contents: only the digest catches it.
+        fs::write(dir.join("index.html"), b"<html>no</html>").unwrap();
+        // Pin the mtime so the check does not depend on filesystem timestamp granularity.
+        fs::File::options()
+            .write(true)
+            .open(dir.join("index.html"))
+            .unwrap()
+            .set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000))
+            .unwrap();
+        assert!(verify_manifest(&dir).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn built_output_is_served_against_recorded_hashes() {
+        let dir = std::env::temp_dir().join(format!("vibefi-dist-hashes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let dist = dir.join(".vibefi").join("dist");
+        fs::create_dir_all(dist.join("assets")).unwrap();
+        fs::write(dir.join("manifest.json"), r#"{ "files": [] }"#).unwrap();
+        fs::write(dist.join("index.html"), b"<html>ok</html>").unwrap();
+        fs::write(dist.join("assets").join("app.js"), b"app").unwrap();
+
+        // A build without recorded hashes is not served at all.
+        assert!(served_file_hashes(&dist).is_err());
+        record_dist_hashes(&dist).unwrap();
+        let hashes = served_file_hashes(&dist).unwrap();
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(hashes["assets/app.js"], sha256_hex(b"app"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn parses_client_versions_for_comparison() {
+        assert_eq!(parse_version("1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("v0.4"), Some((0, 4, 0)));
+        assert_eq!(parse_version("2.0.0-beta.1+abc"), Some((2, 0, 0)));
+        assert_eq!(parse_version("1.2.3.4"), None);
+        assert_eq!(parse_version("latest"), None);
+        assert!(parse_version("0.10.0") > parse_version("0.9.9"));
+    }
+
+    fn file_cid(data: &[u8], version: CidVersion) -> String {
+        cid_string(&unixfs_file(data, version, &mut |_, _| {}).cid, version)
+    }
+
+    #[test]
+    fn small_file_cids_match_the_ipfs_importer() {
+        assert_eq!(
+            file_cid(b"", CidVersion::V0),
+            "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH"
+        );
+        assert_eq!(
+            file_cid(b"hello world\n", CidVersion::V0),
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"
+        );
+        assert_eq!(
+            file_cid(b"", CidVersion::V1),
+            "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"
+        );
+    }
+
+    #[test]
+    fn chunked_file_cids_match_the_ipfs_importer() {
+        // Three chunks under one root.
+        let big: Vec<u8> = (0..600_000u32).map(|i| (i % 251) as u8).collect();
+        assert_eq!(
+            file_cid(&big, CidVersion::V0),
+            "QmP4enSvvE7Poduq89K4gXzXgdawZnmvHFEugsLNB5iwnR"
+        );
+        assert_eq!(
+            file_cid(&big, CidVersion::V1),
+            "bafybeicp64het67shnhxiyl3sg5mylxqop6pnqsqpfecb6pmni2ghoxzom"
+        );
+    }
+
+    #[test]
+    fn directory_root_cids_match_the_ipfs_importer() {
+        let files = std::collections::HashMap::from([
+            ("index.html", b"<html></html>".as_slice()),
+            ("src/main.js", b"console.log(1)".as_slice()),
+            ("manifest.json", br#"{"files":[]}"#.as_slice()),
+        ]);
+        let read = |path: &str| -> anyhow::Result<Vec<u8>> { Ok(files[path].to_vec()) };
+        let paths = || files.keys().map(|path| path.to_string());
+        assert_eq!(
+            unixfs_root_cid(std::iter::empty(), CidVersion::V0, &read).unwrap(),
+            "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"
+        );
+        assert_eq!(
+            unixfs_root_cid(paths(), CidVersion::V0, &read).unwrap(),
+            "QmfXsStwnWs1ddXzwz3sVhtgoiK2q2oAJTcHPSkrGzDfAc"
+        );
+        assert_eq!(
+            unixfs_root_cid(paths(), CidVersion::V1, &read).unwrap(),
+            "bafybeico2izalpcq3563hopudj6bpcwwz3hgrfhme6quj3fp4i6vrlxlwq"
+        );
+    }
+
+    #[test]
+    fn unsupported_cid_encodings_are_refused() {
+        assert!(CidVersion::of("zdj7WWeQ43G6JJvLWQWZpyHuAMq6uYWRjkBXFad11vE2LHhQ7").is_err());
+    }
+
+    #[test]
+    fn root_cid_check_catches_changed_files() {
+        let dir = std::env::temp_dir().join(format!("vibefi-root-cid-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("index.html"), b"<html></html>").unwrap();
+        let manifest = serde_json::json!({
+            "files": [{ "path": "index.html", "bytes": 13, "sha256": sha256_hex(b"<html></html>") }],
+        });
+        fs::write(dir.join("manifest.json"), manifest.to_string()).unwrap();
+        let root_cid = unixfs_root_cid(
+            ["index.html".to_string(), "manifest.json".to_string()],
+            CidVersion::V1,
+            &|path| Ok(fs::read(dir.join(path))?),
+        )
+        .unwrap();
+        verify_root_cid(&dir, &root_cid).unwrap();
+        fs::write(dir.join("index.html"), b"<html>!!</html>").unwrap();
+        assert!(verify_root_cid(&dir, &root_cid).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
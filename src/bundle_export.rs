@@ -0,0 +1,260 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Serialize;
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::bundle::{normalize_manifest_path, walk_files};
+
+/// Describes an export; written next to `source/` and `dist/`.
+pub(crate) const EXPORT_RECORD: &str = "vibefi-export.json";
+
+/// Which parts of a cached bundle to export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportParts {
+    All,
+    Source,
+    Dist,
+}
+
+impl ExportParts {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "all" => Some(Self::All),
+            "source" => Some(Self::Source),
+            "dist" => Some(Self::Dist),
+            _ => None,
+        }
+    }
+
+    fn source(self) -> bool {
+        matches!(self, Self::All | Self::Source)
+    }
+
+    fn dist(self) -> bool {
+        matches!(self, Self::All | Self::Dist)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportRecord<'a> {
+    root_cid: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    exported_at: u64,
+    client_version: &'static str,
+    source: bool,
+    dist: bool,
+}
+
+/// Exports the cached bundle `root_cid` to `dest`: a zip archive when `dest`
+/// ends in `.zip`, otherwise a new directory. The source is re-verified
+/// against its manifest first, so only files the manifest vouches for are
+/// exported.
+pub fn export_bundle(
+    cache_dir: &Path,
+    root_cid: &str,
+    name: Option<&str>,
+    parts: ExportParts,
+    dest: &Path,
+) -> Result<PathBuf> {
+    if root_cid.is_empty() || root_cid.contains(['/', '\\', '.']) {
+        bail!("invalid root CID: {root_cid}");
+    }
+    let bundle_dir = cache_dir.join(root_cid);
+    if !bundle_dir.join("manifest.json").exists() {
+        bail!("bundle {root_cid} is not cached; launch it first");
+    }
+    let _guard = crate::registry::PrepareGuard::acquire(root_cid);
+
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    if parts.source() {
+        crate::bundle::reverify_manifest(&bundle_dir)
+            .with_context(|| format!("cached bundle {root_cid} failed verification"))?;
+        files.push((
+            "source/manifest.json".to_string(),
+            bundle_dir.join("manifest.json"),
+        ));
+        for entry in crate::manifest::load(&bundle_dir)?.files {
+            let path = normalize_manifest_path(&entry.path);
+            if path.split('/').any(|part| part == "..") {
+                bail!("manifest path escapes the bundle: {}", entry.path);
+            }
+            files.push((format!("source/{path}"), bundle_dir.join(&path)));
+        }
+    }
+    if parts.dist() {
+        let dist_dir = bundle_dir.join(".vibefi").join("dist");
+        if !dist_dir.join("index.html").exists() {
+            bail!("bundle {root_cid} has not been built yet; launch it first");
+        }
+        for path in walk_files(&dist_dir)? {
+            let rel = path
+                .strip_prefix(&dist_dir)
+                .context("dist file outside dist dir")?
+                .to_string_lossy()
+                .replace('\\', "/");
+            files.push((format!("dist/{rel}"), path));
+        }
+    }
+    let record = serde_json::to_vec_pretty(&ExportRecord {
+        root_cid,
+        name,
+        exported_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        client_version: env!("CARGO_PKG_VERSION"),
+        source: parts.source(),
+        dist: parts.dist(),
+    })?;
+
+    let is_zip = dest
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    if is_zip {
+        write_zip(dest, &files, &record)?;
+    } else {
+        write_dir(dest, &files, &record)?;
+    }
+    tracing::info!(root_cid, dest = %dest.display(), files = files.len(), "exported cached bundle");
+    Ok(dest.to_path_buf())
+}
+
+fn write_zip(dest: &Path, files: &[(String, PathBuf)], record: &[u8]) -> Result<()> {
+    let file =
+        fs::File::create(dest).with_context(|| format!("failed to create {}", dest.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(EXPORT_RECORD, options)?;
+    zip.write_all(record)?;
+    for (name, path) in files {
+        zip.start_file(name.as_str(), options)?;
+        let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        zip.write_all(&bytes)?;
+    }
+    zip.finish().context("finish zip archive")?;
+    Ok(())
+}
+
+fn write_dir(dest: &Path, files: &[(String, PathBuf)], record: &[u8]) -> Result<()> {
+    let occupied = fs::read_dir(dest)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    if occupied {
+        return Err(anyhow!(
+            "{} already exists and is not empty",
+            dest.display()
+        ));
+    }
+    fs::create_dir_all(dest).with_context(|| format!("failed to create {}", dest.display()))?;
+    fs::write(dest.join(EXPORT_RECORD), record)?;
+    for (name, path) in files {
+        let target = dest.join(name);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(path, &target).with_context(|| format!("failed to copy {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportParts, export_bundle};
+    use crate::bundle::sha256_hex;
+    use std::fs;
+
+    /// A bundle cache under a fresh temp dir holding `bafytest`, with one
+    /// manifest file, a stray file and build output. Returns the temp dir.
+    fn export_fixture(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("vibefi-export-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let bundle = dir.join("cache").join("bafytest");
+        fs::create_dir_all(bundle.join(".vibefi/dist/assets")).unwrap();
+        fs::write(bundle.join("app.js"), b"app").unwrap();
+        fs::write(bundle.join("stray.txt"), b"not in manifest").unwrap();
+        let manifest = serde_json::json!({
+            "files": [{ "path": "./app.js", "bytes": 3, "sha256": sha256_hex(b"app") }]
+        });
+        fs::write(bundle.join("manifest.json"), manifest.to_string()).unwrap();
+        fs::write(bundle.join(".vibefi/dist/index.html"), b"<html>").unwrap();
+        fs::write(bundle.join(".vibefi/dist/assets/app.js"), b"built").unwrap();
+        dir
+    }
+
+    #[test]
+    fn exports_verified_source_and_dist() {
+        let dir = export_fixture("all");
+        let out = dir.join("out");
+        export_bundle(
+            &dir.join("cache"),
+            "bafytest",
+            Some("Swap"),
+            ExportParts::All,
+            &out,
+        )
+        .unwrap();
+        assert!(out.join("source/app.js").exists());
+        assert!(out.join("source/manifest.json").exists());
+        assert!(!out.join("source/stray.txt").exists());
+        assert!(out.join("dist/assets/app.js").exists());
+        let record = fs::read_to_string(out.join("vibefi-export.json")).unwrap();
+        assert!(record.contains("\"rootCid\": \"bafytest\""));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn exports_never_overwrite() {
+        let dir = export_fixture("overwrite");
+        let out = dir.join("out");
+        export_bundle(
+            &dir.join("cache"),
+            "bafytest",
+            None,
+            ExportParts::Dist,
+            &out,
+        )
+        .unwrap();
+        assert!(
+            export_bundle(
+                &dir.join("cache"),
+                "bafytest",
+                None,
+                ExportParts::Dist,
+                &out
+            )
+            .is_err()
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn tampered_sources_are_refused() {
+        let dir = export_fixture("tampered");
+        fs::write(dir.join("cache/bafytest/app.js"), b"APP").unwrap();
+        assert!(
+            export_bundle(
+                &dir.join("cache"),
+                "bafytest",
+                None,
+                ExportParts::Source,
+                &dir.join("out")
+            )
+            .is_err()
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn root_cids_that_leave_the_cache_are_refused() {
+        let dir = export_fixture("escape");
+        assert!(export_bundle(&dir, "../cache", None, ExportParts::All, &dir.join("x")).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
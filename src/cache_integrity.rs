@@ -0,0 +1,221 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::state::AppState;
+
+/// Let launches and the prefetch pass go first after startup.
+const STARTUP_DELAY: Duration = Duration::from_secs(120);
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Pause between bundles so the check never competes with a launch for disk.
+const BUNDLE_PAUSE: Duration = Duration::from_millis(500);
+/// Corrupted bundles are moved here, out of the way of launches and eviction.
+pub const QUARANTINE_DIR: &str = ".quarantine";
+const QUARANTINE_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const REPORT_FILE: &str = "integrity-check.json";
+
+/// Outcome of the last cache integrity check, kept in the cache dir for
+/// Settings and bug reports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub finished_at: u64,
+    pub checked: usize,
+    /// Bundles left alone because a tab had them open.
+    pub skipped: usize,
+    pub quarantined: Vec<QuarantinedBundle>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedBundle {
+    pub root_cid: String,
+    pub error: String,
+}
+
+pub fn spawn_integrity_worker(state: AppState) {
+    let Some(cache_dir) = state.resolved.as_ref().map(|r| r.cache_dir.clone()) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        std::thread::sleep(STARTUP_DELAY);
+        loop {
+            match check_cache(&cache_dir, &state.bundles_in_use()) {
+                Ok(report) => {
+                    tracing::info!(
+                        checked = report.checked,
+                        skipped = report.skipped,
+                        quarantined = report.quarantined.len(),
+                        "bundle cache integrity check finished"
+                    );
+                    if let Err(err) = save_report(&cache_dir, &report) {
+                        tracing::warn!(error = %format!("{err:#}"), "failed to save integrity report");
+                    }
+                }
+                Err(err) => {
+                    tracing::warn!(error = %format!("{err:#}"), "bundle cache integrity check failed")
+                }
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+pub fn load_report(cache_dir: &Path) -> Option<IntegrityReport> {
+    let raw = fs::read(cache_dir.join(REPORT_FILE)).ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+fn save_report(cache_dir: &Path, report: &IntegrityReport) -> Result<()> {
+    fs::write(
+        cache_dir.join(REPORT_FILE),
+        serde_json::to_vec_pretty(report)?,
+    )
+    .context("write integrity report")
+}
+
+/// Re-hashes every cached bundle against its manifest, ignoring the verified
+/// stamps, and quarantines the ones that no longer match. Bundles in
+/// `in_use` are skipped; a bad one is caught on the next run.
+fn check_cache(cache_dir: &Path, in_use: &[String]) -> Result<IntegrityReport> {
+    purge_quarantine(&cache_dir.join(QUARANTINE_DIR));
+    let entries = match fs::read_dir(cache_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(IntegrityReport::default());
+        }
+        Err(err) => return Err(err).context("read bundle cache dir"),
+    };
+    let mut report = IntegrityReport::default();
+    for entry in entries {
+        let entry = entry.context("read bundle cache entry")?;
+        let root_cid = entry.file_name().to_string_lossy().into_owned();
+        let bundle_dir = entry.path();
+        if root_cid.starts_with('.') || !bundle_dir.join("manifest.json").exists() {
+            continue;
+        }
+        if in_use.contains(&root_cid) {
+            report.skipped += 1;
+            continue;
+        }
+        // Waits for a launch or prefetch of the same bundle to finish.
+        let _guard = crate::registry::PrepareGuard::acquire(&root_cid);
+        if !bundle_dir.join("manifest.json").exists() {
+            continue;
+        }
+        report.checked += 1;
+        if let Err(err) = crate::bundle::reverify_manifest(&bundle_dir) {
+            let error = format!("{err:#}");
+            match quarantine(cache_dir, &root_cid, &bundle_dir) {
+                Ok(dest) => tracing::warn!(
+                    root_cid,
+                    error,
+                    quarantine = %dest.display(),
+                    "quarantined corrupted cached bundle"
+                ),
+                Err(move_err) => tracing::warn!(
+                    root_cid,
+                    error,
+                    move_error = %format!("{move_err:#}"),
+                    "failed to quarantine corrupted cached bundle"
+                ),
+            }
+            report
+                .quarantined
+                .push(QuarantinedBundle { root_cid, error });
+        }
+        std::thread::sleep(BUNDLE_PAUSE);
+    }
+    report.finished_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    Ok(report)
+}
+
+fn quarantine(cache_dir: &Path, root_cid: &str, bundle_dir: &Path) -> Result<PathBuf> {
+    let quarantine_dir = cache_dir.join(QUARANTINE_DIR);
+    fs::create_dir_all(&quarantine_dir).context("create quarantine dir")?;
+    let dest = quarantine_dir.join(root_cid);
+    if dest.exists() {
+        fs::remove_dir_all(&dest).context("replace quarantined bundle")?;
+    }
+    fs::rename(bundle_dir, &dest).context("move bundle to quarantine")?;
+    Ok(dest)
+}
+
+fn purge_quarantine(quarantine_dir: &Path) {
+    let Ok(entries) = fs::read_dir(quarantine_dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let expired = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > QUARANTINE_RETENTION);
+        if !expired {
+            continue;
+        }
+        if let Err(err) = fs::remove_dir_all(entry.path()) {
+            tracing::debug!(path = %entry.path().display(), error = %err, "failed to purge quarantined bundle");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QUARANTINE_DIR, check_cache};
+    use crate::bundle::sha256_hex;
+    use std::fs;
+
+    /// A bundle cache holding `cids`, each with one file listed in its
+    /// manifest. `corrupt` ones have that file changed after the fact.
+    fn cache_fixture(name: &str, cids: &[&str], corrupt: &[&str]) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("vibefi-integrity-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for cid in cids {
+            let bundle = dir.join(cid);
+            fs::create_dir_all(&bundle).unwrap();
+            fs::write(bundle.join("index.html"), b"hello").unwrap();
+            let manifest = serde_json::json!({
+                "files": [{ "path": "index.html", "bytes": 5, "sha256": sha256_hex(b"hello") }]
+            });
+            fs::write(bundle.join("manifest.json"), manifest.to_string()).unwrap();
+        }
+        for cid in corrupt {
+            // Same size, different contents: only a full re-hash notices.
+            fs::write(dir.join(cid).join("index.html"), b"HELLO").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn corrupted_bundles_are_quarantined() {
+        let dir = cache_fixture("corrupt", &["good", "bad"], &["bad"]);
+        let report = check_cache(&dir, &[]).unwrap();
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.quarantined.len(), 1);
+        assert_eq!(report.quarantined[0].root_cid, "bad");
+        assert!(!dir.join("bad").exists());
+        assert!(dir.join(QUARANTINE_DIR).join("bad/index.html").exists());
+        assert!(dir.join("good").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn bundles_in_use_are_skipped() {
+        let dir = cache_fixture("open", &["good", "open"], &["open"]);
+        let report = check_cache(&dir, &["open".to_string()]).unwrap();
+        assert_eq!((report.checked, report.skipped), (1, 1));
+        assert!(report.quarantined.is_empty());
+        assert!(dir.join("open").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
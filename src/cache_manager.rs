@@ -0,0 +1,355 @@
+//! Bundle cache size limits. `bundleCacheMaxMb` is enforced after every
+//! launch and prefetch by deleting the least recently launched bundles that
+//! no tab has open and no pin needs. Settings lists what each cached bundle
+//! takes and can clear the cache.
+
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::settings::{UserSettings, load_settings};
+use crate::state::AppState;
+
+/// Marker touched whenever a cached bundle is prepared for launch.
+pub const LAST_USED_MARKER: &str = ".vibefi/last-used";
+
+/// One cached bundle, as the settings tab lists it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleUsage {
+    pub root_cid: String,
+    /// Registry name when the bundle was listed or pinned this session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub bytes: u64,
+    /// Unix time of the last launch, or of the download when never launched.
+    pub last_used: u64,
+    pub pinned: bool,
+    /// A tab has it open; eviction and clearing leave it alone.
+    pub open: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheUsage {
+    /// Most recently used first.
+    pub bundles: Vec<BundleUsage>,
+    pub total_bytes: u64,
+    pub max_bytes: Option<u64>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearResult {
+    pub removed: usize,
+    pub freed_bytes: u64,
+    /// Bundles kept because a tab has them open.
+    pub skipped: usize,
+}
+
+struct CachedBundle {
+    path: PathBuf,
+    root_cid: String,
+    bytes: u64,
+    last_used: SystemTime,
+}
+
+/// The configured budget in bytes; `None` when the cache is unbounded.
+pub fn budget_bytes(settings: &UserSettings) -> Option<u64> {
+    settings
+        .bundle_cache_max_mb
+        .map(|mb| mb.saturating_mul(1024 * 1024))
+}
+
+/// Evicts down to the configured budget, keeping open and pinned bundles.
+/// Returns the cache size afterwards.
+pub fn enforce_budget(state: &AppState) -> Result<Option<u64>> {
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("Network not configured"))?;
+    let Some(config_path) = resolved.config_path.as_deref() else {
+        return Ok(None);
+    };
+    let settings = load_settings(config_path);
+    let Some(budget) = budget_bytes(&settings) else {
+        return Ok(None);
+    };
+    let mut keep = pinned_bundles(&settings);
+    keep.extend(state.bundles_in_use());
+    evict_to_budget(&resolved.cache_dir, budget, &keep).map(Some)
+}
+
+/// Every cached bundle with its size, for the settings tab.
+pub fn usage(state: &AppState) -> Result<CacheUsage> {
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("Network not configured"))?;
+    let settings = resolved
+        .config_path
+        .as_deref()
+        .map(load_settings)
+        .unwrap_or_default();
+    let pinned = pinned_bundles(&settings);
+    let open: HashSet<String> = state.bundles_in_use().into_iter().collect();
+    let known = state.known_dapps.lock().expect("known_dapps").clone();
+
+    let mut cached = cached_bundles(&resolved.cache_dir)?;
+    cached.sort_by_key(|bundle| std::cmp::Reverse(bundle.last_used));
+    let bundles: Vec<BundleUsage> = cached
+        .into_iter()
+        .map(|bundle| {
+            let name = known
+                .get(&bundle.root_cid)
+                .map(|identity| identity.name.clone())
+                .or_else(|| {
+                    settings
+                        .pinned_dapps
+                        .iter()
+                        .find(|pin| pin.root_cid == bundle.root_cid)
+                        .map(|pin| pin.name.clone())
+                })
+                .filter(|name| !name.is_empty());
+            BundleUsage {
+                name,
+                bytes: bundle.bytes,
+                last_used: bundle
+                    .last_used
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_secs())
+                    .unwrap_or_default(),
+                pinned: pinned.contains(&bundle.root_cid),
+                open: open.contains(&bundle.root_cid),
+                root_cid: bundle.root_cid,
+            }
+        })
+        .collect();
+    Ok(CacheUsage {
+        total_bytes: bundles.iter().map(|bundle| bundle.bytes).sum(),
+        bundles,
+        max_bytes: budget_bytes(&settings),
+    })
+}
+
+/// Deletes `root_cid`, or every cached bundle when `None`, except those a
+/// tab has open. Pinned dapps are fetched again by the next prefetch pass.
+pub fn clear(
+    cache_dir: &Path,
+    root_cid: Option<&str>,
+    open: &HashSet<String>,
+) -> Result<ClearResult> {
+    let mut result = ClearResult::default();
+    for bundle in cached_bundles(cache_dir)? {
+        if root_cid.is_some_and(|cid| cid != bundle.root_cid) {
+            continue;
+        }
+        if open.contains(&bundle.root_cid) {
+            result.skipped += 1;
+            continue;
+        }
+        fs::remove_dir_all(&bundle.path)
+            .with_context(|| format!("remove cached bundle {}", bundle.root_cid))?;
+        result.removed += 1;
+        result.freed_bytes += bundle.bytes;
+    }
+    Ok(result)
+}
+
+/// Root CIDs the pins were last resolved to.
+fn pinned_bundles(settings: &UserSettings) -> HashSet<String> {
+    settings
+        .pinned_dapps
+        .iter()
+        .filter(|pin| !pin.root_cid.is_empty())
+        .map(|pin| pin.root_cid.clone())
+        .collect()
+}
+
+/// Bundle dirs in `cache_dir`. Anything without a manifest, such as the
+/// quarantine, registry caches or other profiles, is not a bundle.
+fn cached_bundles(cache_dir: &Path) -> Result<Vec<CachedBundle>> {
+    let entries = match fs::read_dir(cache_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).context("read bundle cache dir"),
+    };
+    let mut bundles = Vec::new();
+    for entry in entries {
+        let entry = entry.context("read bundle cache entry")?;
+        let path = entry.path();
+        if !entry.file_type()?.is_dir() || !path.join("manifest.json").exists() {
+            continue;
+        }
+        bundles.push(CachedBundle {
+            root_cid: entry.file_name().to_string_lossy().into_owned(),
+            bytes: dir_size(&path),
+            last_used: last_used(&path),
+            path,
+        });
+    }
+    Ok(bundles)
+}
+
+/// Deletes least recently used bundles outside `keep` until the cache fits in
+/// `budget` bytes. Returns the cache size afterwards, which stays above the
+/// budget when the kept bundles alone exceed it.
+pub fn evict_to_budget(cache_dir: &Path, budget: u64, keep: &HashSet<String>) -> Result<u64> {
+    let mut bundles = cached_bundles(cache_dir)?;
+    let mut used: u64 = bundles.iter().map(|bundle| bundle.bytes).sum();
+    bundles.sort_by_key(|bundle| bundle.last_used);
+    for bundle in bundles {
+        if used <= budget {
+            break;
+        }
+        if keep.contains(&bundle.root_cid) {
+            continue;
+        }
+        match fs::remove_dir_all(&bundle.path) {
+            Ok(()) => {
+                used = used.saturating_sub(bundle.bytes);
+                tracing::info!(
+                    root_cid = %bundle.root_cid,
+                    bytes = bundle.bytes,
+                    "evicted cached bundle"
+                );
+            }
+            Err(err) => {
+                tracing::warn!(root_cid = %bundle.root_cid, error = %err, "failed to evict bundle")
+            }
+        }
+    }
+    Ok(used)
+}
+
+fn last_used(bundle_dir: &Path) -> SystemTime {
+    [
+        bundle_dir.join(LAST_USED_MARKER),
+        bundle_dir.join("manifest.json"),
+    ]
+    .iter()
+    .find_map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+    .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+pub(crate) fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let meta = entry.path().symlink_metadata().ok()?;
+            Some(if meta.is_dir() {
+                dir_size(&entry.path())
+            } else {
+                meta.len()
+            })
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClearResult, clear, evict_to_budget};
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vibefi-cache-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Bundles of 100 bytes each, last used three, two and one hours ago.
+    fn aged_bundles(dir: &Path) {
+        let now = SystemTime::now();
+        for (idx, cid) in ["old", "pinned", "recent"].iter().enumerate() {
+            let bundle = dir.join(cid);
+            fs::create_dir_all(&bundle).unwrap();
+            let manifest = bundle.join("manifest.json");
+            fs::write(&manifest, vec![b' '; 100]).unwrap();
+            fs::File::options()
+                .write(true)
+                .open(&manifest)
+                .unwrap()
+                .set_modified(now - Duration::from_secs(3600 * (3 - idx as u64)))
+                .unwrap();
+        }
+    }
+
+    fn pinned() -> HashSet<String> {
+        ["pinned".to_string()].into()
+    }
+
+    #[test]
+    fn evicts_least_recently_used_unpinned_bundles() {
+        let dir = cache_dir("evict");
+        aged_bundles(&dir);
+        // "pinned" is older than "recent" but kept; "old" goes first.
+        assert_eq!(evict_to_budget(&dir, 250, &pinned()).unwrap(), 200);
+        assert!(!dir.join("old").exists());
+        assert!(dir.join("pinned").exists());
+        assert!(dir.join("recent").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn kept_bundles_alone_can_exceed_the_budget() {
+        let dir = cache_dir("over-budget");
+        aged_bundles(&dir);
+        assert_eq!(evict_to_budget(&dir, 50, &pinned()).unwrap(), 100);
+        assert!(dir.join("pinned").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Bundles "a", "b" and "open" of 10 bytes each, and a quarantined one.
+    fn small_bundles(dir: &Path) {
+        for cid in ["a", "b", "open"] {
+            fs::create_dir_all(dir.join(cid)).unwrap();
+            fs::write(dir.join(cid).join("manifest.json"), vec![b' '; 10]).unwrap();
+        }
+        fs::create_dir_all(dir.join(".quarantine").join("c")).unwrap();
+    }
+
+    fn open() -> HashSet<String> {
+        ["open".to_string()].into()
+    }
+
+    #[test]
+    fn clears_a_single_bundle() {
+        let dir = cache_dir("clear-one");
+        small_bundles(&dir);
+        let one = clear(&dir, Some("a"), &open()).unwrap();
+        assert_eq!(
+            one,
+            ClearResult {
+                removed: 1,
+                freed_bytes: 10,
+                skipped: 0
+            }
+        );
+        assert!(!dir.join("a").exists());
+        assert!(dir.join("b").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn clearing_keeps_open_bundles_and_other_cache_data() {
+        let dir = cache_dir("clear-all");
+        small_bundles(&dir);
+        let all = clear(&dir, None, &open()).unwrap();
+        assert_eq!((all.removed, all.skipped), (2, 1));
+        assert!(!dir.join("b").exists());
+        assert!(dir.join("open").exists());
+        assert!(dir.join(".quarantine").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,132 @@
+//! What this client is and supports, so dapps and Studio can feature-detect
+//! instead of calling a method and catching the failure.
+
+use serde::Serialize;
+
+use crate::config::ResolvedConfig;
+use crate::state::{AppState, WalletBackend};
+
+/// Version of the dapp-facing API: provider methods, `window.vibefi*` objects
+/// and their events. Bumped on incompatible changes only; additions show up
+/// in [`CAPABILITIES`].
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Features dapps can rely on in this client. Those gated by the manifest
+/// still need their capability granted there.
+const CAPABILITIES: &[&str] = &[
+    "eip1193",
+    "eip6963",
+    "eip712",
+    "ipfs",
+    "network.rpc",
+    "network.httpFetch",
+    "clipboard",
+    "qr",
+    "notifications",
+    "files",
+    "messages",
+    "events",
+    "clientInfo",
+    "ens",
+];
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientInfo {
+    pub client_version: &'static str,
+    pub protocol_version: u32,
+    pub capabilities: &'static [&'static str],
+    pub network: NetworkProfile,
+    /// Backends the wallet selector can connect with this config.
+    pub wallet_backends: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkProfile {
+    /// Name of the network config, e.g. `sepolia` for `config/sepolia.json`.
+    pub name: Option<String>,
+    pub chain_id: String,
+    pub test_network: bool,
+    /// Requests go to a local fork of the chain (`--fork`), not the chain itself.
+    pub fork: bool,
+    pub online: bool,
+}
+
+pub fn client_info(state: &AppState) -> ClientInfo {
+    let resolved = state.resolved.as_deref();
+    ClientInfo {
+        client_version: crate::bundle::CLIENT_VERSION,
+        protocol_version: PROTOCOL_VERSION,
+        capabilities: CAPABILITIES,
+        network: NetworkProfile {
+            name: resolved
+                .and_then(|r| r.config_path.as_deref())
+                .and_then(|path| path.file_stem())
+                .map(|stem| stem.to_string_lossy().into_owned()),
+            chain_id: state.chain_id_hex(),
+            test_network: resolved.is_some_and(|r| r.test_network),
+            fork: resolved.is_some_and(|r| r.fork.is_some()),
+            online: crate::connectivity::is_online(),
+        },
+        wallet_backends: wallet_backends(resolved)
+            .into_iter()
+            .map(WalletBackend::as_str)
+            .collect(),
+    }
+}
+
+fn wallet_backends(resolved: Option<&ResolvedConfig>) -> Vec<WalletBackend> {
+    let mut backends = Vec::new();
+    // Keys are imported into a keystore next to the config; test networks
+    // can also sign with the configured developer key.
+    if resolved.is_some_and(|r| {
+        r.config_path.is_some() || (r.test_network && r.developer_private_key.is_some())
+    }) {
+        backends.push(WalletBackend::Local);
+    }
+    if resolved.is_some_and(|r| r.walletconnect_project_id.is_some()) {
+        backends.push(WalletBackend::WalletConnect);
+    }
+    backends.push(WalletBackend::Hardware);
+    if resolved.is_some_and(|r| r.smart_account.is_some()) {
+        backends.push(WalletBackend::SmartAccount);
+    }
+    if resolved.is_some_and(|r| r.remote_signer.is_some()) {
+        backends.push(WalletBackend::Remote);
+    }
+    backends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wallet_backends;
+    use crate::config::{AppConfig, ConfigBuilder};
+    use crate::state::WalletBackend;
+    use serde_json::json;
+
+    #[test]
+    fn only_hardware_wallets_without_a_config() {
+        assert_eq!(wallet_backends(None), [WalletBackend::Hardware]);
+    }
+
+    #[test]
+    fn lists_the_backends_the_config_enables() {
+        let config: AppConfig = serde_json::from_value(json!({
+            "chainId": 31337,
+            "testNetwork": true,
+            "developerPrivateKey": "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+            "walletConnect": { "projectId": "test-project" },
+        }))
+        .unwrap();
+        let resolved = ConfigBuilder::new(config, None).build();
+        assert_eq!(
+            wallet_backends(Some(&resolved)),
+            [
+                WalletBackend::Local,
+                WalletBackend::WalletConnect,
+                WalletBackend::Hardware,
+            ]
+        );
+    }
+}
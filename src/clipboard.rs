@@ -0,0 +1,63 @@
+use anyhow::{Context, Result, bail};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn pipe_to_command(program: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("failed to run '{program}'"))?;
+    child
+        .stdin
+        .take()
+        .context("clipboard helper stdin unavailable")?
+        .write_all(text.as_bytes())
+        .with_context(|| format!("failed to write to '{program}'"))?;
+    let status = child
+        .wait()
+        .with_context(|| format!("failed to wait for '{program}'"))?;
+    if !status.success() {
+        bail!("'{program}' exited with status {}", status);
+    }
+    Ok(())
+}
+
+/// Writes `text` to the system clipboard using the platform's clipboard tool.
+pub fn write_text(text: &str) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        return pipe_to_command("pbcopy", &[], text);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        return pipe_to_command("clip", &[], text);
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let candidates: [(&str, &[&str]); 3] = [
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ];
+        let mut last_err = None;
+        for (program, args) in candidates {
+            match pipe_to_command(program, args, text) {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    tracing::debug!(program, error = %err, "clipboard helper failed");
+                    last_err = Some(err);
+                }
+            }
+        }
+        return Err(last_err.expect("at least one clipboard helper attempted"))
+            .context("no clipboard helper available (install wl-clipboard or xclip)");
+    }
+
+    #[allow(unreachable_code)]
+    Ok(())
+}
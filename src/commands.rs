@@ -0,0 +1,226 @@
+//! The command palette (Cmd/Ctrl+K): an overlay over the app area listing
+//! what can be done right now, searched by name. Every fixed menu command is
+//! listed, so a new menu entry shows up in the palette without more work; the
+//! other entries come from the session: open tabs, dapps the registry listed,
+//! the active tab's accounts and the known chains.
+
+use serde::Serialize;
+
+use crate::menu::MenuCommand;
+use crate::state::AppState;
+use crate::webview_manager::WebViewManager;
+
+/// Id of the palette overlay webview. It covers the app area but is not a tab.
+pub const PALETTE_WEBVIEW_ID: &str = "palette";
+
+/// One palette entry. The palette sends `id` back to run it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteCommand {
+    pub id: String,
+    pub title: String,
+    /// Heading the entry is listed under.
+    pub group: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shortcut: Option<String>,
+    /// More words the search matches, such as a dapp id or a full address.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub keywords: String,
+}
+
+/// What running a [`PaletteCommand`] does, as encoded in its id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+    Menu(MenuCommand),
+    /// Switches to the tab with this webview id.
+    SwitchTab(String),
+    /// Opens the dapp with this root CID, switching to its tab if open.
+    LaunchDapp(String),
+    CopyAddress(String),
+    SwitchChain(u64),
+}
+
+impl PaletteAction {
+    pub fn id(&self) -> String {
+        match self {
+            Self::Menu(command) => format!("menu:{}", command.id()),
+            Self::SwitchTab(webview_id) => format!("tab:{webview_id}"),
+            Self::LaunchDapp(root_cid) => format!("dapp:{root_cid}"),
+            Self::CopyAddress(address) => format!("copy:{address}"),
+            Self::SwitchChain(chain_id) => format!("chain:{chain_id}"),
+        }
+    }
+
+    pub fn parse(id: &str) -> Option<Self> {
+        let (kind, arg) = id.split_once(':')?;
+        match kind {
+            "menu" => MenuCommand::from_id(arg).map(Self::Menu),
+            "tab" => Some(Self::SwitchTab(arg.to_string())),
+            "dapp" => Some(Self::LaunchDapp(arg.to_string())),
+            "copy" => Some(Self::CopyAddress(arg.to_string())),
+            "chain" => arg.parse().ok().map(Self::SwitchChain),
+            _ => None,
+        }
+    }
+
+    fn command(&self, title: String, group: &'static str) -> PaletteCommand {
+        PaletteCommand {
+            id: self.id(),
+            title,
+            group,
+            shortcut: None,
+            keywords: String::new(),
+        }
+    }
+}
+
+/// Everything the palette offers at the moment. Only ids in this list are
+/// run, so the palette can't ask for a tab, dapp or chain it wasn't shown.
+pub fn available(state: &AppState, manager: &WebViewManager) -> Vec<PaletteCommand> {
+    let mut commands: Vec<PaletteCommand> = MenuCommand::FIXED
+        .into_iter()
+        .filter(|command| *command != MenuCommand::CommandPalette)
+        .map(|command| PaletteCommand {
+            shortcut: command.shortcut_label(),
+            ..PaletteAction::Menu(command).command(
+                command.title().trim_end_matches('…').to_string(),
+                "Commands",
+            )
+        })
+        .collect();
+
+    for (index, entry) in manager.apps.iter().enumerate() {
+        if !entry.selectable || Some(index) == manager.active_app_index {
+            continue;
+        }
+        commands.push(
+            PaletteAction::SwitchTab(entry.id.clone())
+                .command(format!("Switch to {}", entry.label), "Tabs"),
+        );
+    }
+
+    let mut dapps: Vec<_> = state
+        .known_dapps
+        .lock()
+        .expect("known_dapps")
+        .values()
+        .filter(|identity| !identity.name.is_empty())
+        .cloned()
+        .collect();
+    dapps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    for identity in dapps {
+        let Some(root_cid) = identity.root_cid.clone() else {
+            continue;
+        };
+        let keywords = [identity.dapp_id.as_deref(), identity.registry.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        commands.push(PaletteCommand {
+            keywords,
+            ..PaletteAction::LaunchDapp(root_cid)
+                .command(format!("Launch {}", identity.name), "Dapps")
+        });
+    }
+
+    let active_id = manager
+        .active_app_index
+        .and_then(|index| manager.apps.get(index))
+        .map(|entry| entry.id.clone());
+    for address in active_id
+        .map(|id| state.accounts_for(&id))
+        .unwrap_or_default()
+    {
+        commands.push(PaletteCommand {
+            keywords: address.clone(),
+            ..PaletteAction::CopyAddress(address.clone()).command(
+                format!("Copy address {}", short_address(&address)),
+                "Accounts",
+            )
+        });
+    }
+
+    let active_chain = state.wallet.lock().expect("wallet").chain.chain_id;
+    if let Some(resolved) = state.resolved.as_deref() {
+        let added = resolved
+            .config_path
+            .as_deref()
+            .map(crate::settings::load_networks)
+            .unwrap_or_default();
+        let chains = std::iter::once((resolved.chain_id, None)).chain(
+            added
+                .into_iter()
+                .map(|network| (network.chain_id, Some(network.name))),
+        );
+        for (chain_id, name) in chains.filter(|(chain_id, _)| *chain_id != active_chain) {
+            let title = match name {
+                Some(name) => format!("Switch to {name} (chain {chain_id})"),
+                None => format!("Switch to chain {chain_id}"),
+            };
+            commands.push(PaletteAction::SwitchChain(chain_id).command(title, "Chains"));
+        }
+    }
+    commands
+}
+
+fn short_address(address: &str) -> String {
+    match (
+        address.get(..6),
+        address.get(address.len().saturating_sub(4)..),
+    ) {
+        (Some(head), Some(tail)) if address.len() > 12 => format!("{head}…{tail}"),
+        _ => address.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PaletteAction, short_address};
+    use crate::menu::MenuCommand;
+
+    #[test]
+    fn ids_round_trip() {
+        for action in [
+            PaletteAction::Menu(MenuCommand::OpenSettings),
+            PaletteAction::SwitchTab("app-3".to_string()),
+            PaletteAction::LaunchDapp("bafybeigdyrzt".to_string()),
+            PaletteAction::CopyAddress("0xd8da6bf26964af9d7eed9e03e53415d37aa96045".to_string()),
+            PaletteAction::SwitchChain(11155111),
+        ] {
+            assert_eq!(PaletteAction::parse(&action.id()), Some(action));
+        }
+    }
+
+    #[test]
+    fn unknown_ids_are_ignored() {
+        assert_eq!(PaletteAction::parse("menu:file.unknown"), None);
+        assert_eq!(PaletteAction::parse("chain:mainnet"), None);
+        assert_eq!(PaletteAction::parse("launch"), None);
+    }
+
+    #[test]
+    fn the_palette_is_a_fixed_menu_command() {
+        assert!(MenuCommand::FIXED.contains(&MenuCommand::CommandPalette));
+    }
+
+    #[test]
+    fn labels_shortcuts_for_the_platform() {
+        assert_eq!(
+            MenuCommand::ShowLauncher.shortcut_label().as_deref(),
+            Some(if cfg!(target_os = "macos") {
+                "Cmd+Shift+L"
+            } else {
+                "Ctrl+Shift+L"
+            })
+        );
+    }
+
+    #[test]
+    fn shortens_addresses() {
+        assert_eq!(
+            short_address("0xd8da6bf26964af9d7eed9e03e53415d37aa96045"),
+            "0xd8da…6045"
+        );
+    }
+}
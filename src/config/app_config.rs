@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IpfsFetchBackend {
@@ -23,6 +24,33 @@ impl IpfsFetchBackend {
     }
 }
 
+/// Event layout of a registry contract. Each schema keeps every event of the
+/// ones before it, so an upgraded registry's older logs still decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RegistrySchema {
+    #[serde(rename = "v1")]
+    V1,
+    /// Adds `DappPublishedV2` and `DappUpgradedV2`, which carry the manifest
+    /// hash and the publisher's signature.
+    #[serde(rename = "v2")]
+    V2,
+}
+
+impl Default for RegistrySchema {
+    fn default() -> Self {
+        Self::V1
+    }
+}
+
+impl RegistrySchema {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+            Self::V2 => "v2",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[allow(non_snake_case)]
 pub struct AppConfig {
@@ -34,9 +62,23 @@ pub struct AppConfig {
     #[serde(default)]
     pub dappRegistry: String,
 
+    /// Most blocks one `eth_getLogs` request spans. Hosted RPCs cap the
+    /// range; ranges the node still refuses are split further.
+    #[serde(default = "default_logs_block_range")]
+    pub logsBlockRange: u64,
+
     #[serde(default)]
     pub studioDappId: Option<u64>,
 
+    /// Event schema per registry contract address, for `dappRegistry` and
+    /// community registries alike. Registries not listed use v1.
+    #[serde(default)]
+    pub registrySchemas: HashMap<String, RegistrySchema>,
+
+    /// Contract receiving "report dapp" submissions; reports stay local when unset.
+    #[serde(default)]
+    pub reportRegistry: Option<String>,
+
     #[serde(default)]
     pub developerPrivateKey: Option<String>,
 
@@ -46,6 +88,16 @@ pub struct AppConfig {
     #[serde(default)]
     pub testNetwork: bool,
 
+    /// Lets dapps call `debug_*`, `trace_*`, `anvil_*` and `eth_getProof`;
+    /// only for local devnets and forks.
+    #[serde(default)]
+    pub devnetRpc: bool,
+
+    /// Looks up calldata selectors the dapp ships no ABI for in a public
+    /// signature database, for the transaction prompt; see `decoder`.
+    #[serde(default)]
+    pub signatureLookup: bool,
+
     #[serde(default)]
     pub ipfsApi: Option<String>,
 
@@ -67,8 +119,23 @@ pub struct AppConfig {
     #[serde(default)]
     pub cacheDir: Option<String>,
 
+    /// OTLP/HTTP collector receiving trace spans, e.g.
+    /// `http://collector:4318/v1/traces`; export is off when unset.
+    #[serde(default)]
+    pub otlpEndpoint: Option<String>,
+
     #[serde(default)]
     pub walletConnect: Option<WalletConnectConfig>,
+
+    /// ERC-4337 smart account backend; offered in the wallet selector when a
+    /// bundler is configured.
+    #[serde(default)]
+    pub smartAccount: Option<SmartAccountConfig>,
+
+    /// External signing service (web3signer or any JSON-RPC signer) offered
+    /// as a wallet backend.
+    #[serde(default)]
+    pub remoteSigner: Option<RemoteSignerConfig>,
 }
 
 fn default_rpc_url() -> String {
@@ -97,6 +164,10 @@ fn default_ipfs_helia_timeout_ms() -> u64 {
     15_000
 }
 
+fn default_logs_block_range() -> u64 {
+    50_000
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[allow(non_snake_case)]
 pub struct WalletConnectConfig {
@@ -104,4 +175,45 @@ pub struct WalletConnectConfig {
     pub projectId: Option<String>,
     #[serde(default)]
     pub relayUrl: Option<String>,
+    /// Fallback relays, tried in order after `relayUrl` when it is unreachable.
+    #[serde(default)]
+    pub relayUrls: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[allow(non_snake_case)]
+pub struct SmartAccountConfig {
+    /// ERC-4337 bundler JSON-RPC endpoint.
+    #[serde(default)]
+    pub bundlerUrl: Option<String>,
+    /// EntryPoint contract; defaults to the canonical v0.7 deployment.
+    #[serde(default)]
+    pub entryPoint: Option<String>,
+    /// `SimpleAccountFactory`-compatible factory deriving the account address.
+    #[serde(default)]
+    pub factory: Option<String>,
+    /// Salt passed to the factory, so one owner key can hold several accounts.
+    #[serde(default)]
+    pub salt: u64,
+    /// ERC-7677 paymaster service sponsoring gas; the account pays when unset.
+    #[serde(default)]
+    pub paymasterUrl: Option<String>,
+    /// Opaque `context` passed to the paymaster, e.g. a sponsorship policy id.
+    #[serde(default)]
+    pub paymasterContext: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[allow(non_snake_case)]
+pub struct RemoteSignerConfig {
+    /// JSON-RPC endpoint of the signer, e.g. web3signer's `http://host:9000`.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Sent as `Authorization: Bearer <token>`; prefer `VIBEFI_REMOTE_SIGNER_TOKEN`
+    /// over committing it to a config file.
+    #[serde(default)]
+    pub authToken: Option<String>,
+    /// Extra headers sent with every request, e.g. an API key header.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
 }
@@ -3,7 +3,12 @@ use std::path::PathBuf;
 
 use super::app_config::{AppConfig, default_ipfs_helia_gateways, default_ipfs_helia_routers};
 use super::env::{parse_bool_env, parse_string_env, parse_u64_env};
-use super::resolved::ResolvedConfig;
+use super::resolved::{RemoteSignerSettings, ResolvedConfig, SmartAccountSettings};
+
+/// Canonical ERC-4337 EntryPoint v0.7 deployment.
+const DEFAULT_ENTRY_POINT: &str = "0x0000000071727De22E5E9d8BAf0edAc6f37da032";
+/// eth-infinitism `SimpleAccountFactory` for EntryPoint v0.7.
+const DEFAULT_ACCOUNT_FACTORY: &str = "0x91E60e0613810449d098b0b5Ec8b51A0FE8c8985";
 
 fn embedded_walletconnect_project_id() -> Option<String> {
     option_env!("VIBEFI_EMBEDDED_WC_PROJECT_ID")
@@ -33,6 +38,10 @@ impl ConfigBuilder {
         // -- RPC URL: env override takes precedence --
         let rpc_url = parse_string_env("VIBEFI_RPC_URL").unwrap_or_else(|| config.rpcUrl.clone());
         let studio_dapp_id = parse_u64_env("VIBEFI_STUDIO_DAPP_ID").or(config.studioDappId);
+        let report_registry =
+            parse_string_env("VIBEFI_REPORT_REGISTRY").or_else(|| config.reportRegistry.clone());
+        let otlp_endpoint =
+            parse_string_env("VIBEFI_OTLP_ENDPOINT").or_else(|| config.otlpEndpoint.clone());
 
         // -- IPFS --
         let ipfs_api = config
@@ -63,11 +72,69 @@ impl ConfigBuilder {
             .and_then(|wc| wc.projectId.clone())
             .or_else(|| parse_string_env("VIBEFI_WC_PROJECT_ID"))
             .or_else(embedded_walletconnect_project_id);
-        let walletconnect_relay_url = config
-            .walletConnect
-            .as_ref()
-            .and_then(|wc| wc.relayUrl.clone())
-            .or_else(|| parse_string_env("VIBEFI_WC_RELAY_URL"));
+        let mut walletconnect_relay_urls: Vec<String> = Vec::new();
+        if let Some(wc) = config.walletConnect.as_ref() {
+            for url in wc.relayUrl.iter().chain(&wc.relayUrls) {
+                let url = url.trim();
+                if !url.is_empty() && !walletconnect_relay_urls.iter().any(|u| u == url) {
+                    walletconnect_relay_urls.push(url.to_string());
+                }
+            }
+        }
+        if walletconnect_relay_urls.is_empty() {
+            walletconnect_relay_urls = parse_string_env("VIBEFI_WC_RELAY_URL")
+                .map(|raw| {
+                    raw.split(',')
+                        .map(str::trim)
+                        .filter(|url| !url.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        // -- Smart account: config, bundler/paymaster env overrides --
+        let smart_account_config = config.smartAccount.clone();
+        let bundler_url = parse_string_env("VIBEFI_BUNDLER_URL").or_else(|| {
+            smart_account_config
+                .as_ref()
+                .and_then(|sa| sa.bundlerUrl.clone())
+        });
+        let smart_account = bundler_url.map(|bundler_url| {
+            let sa = smart_account_config.unwrap_or_default();
+            SmartAccountSettings {
+                bundler_url,
+                entry_point: sa
+                    .entryPoint
+                    .unwrap_or_else(|| DEFAULT_ENTRY_POINT.to_string()),
+                factory: sa
+                    .factory
+                    .unwrap_or_else(|| DEFAULT_ACCOUNT_FACTORY.to_string()),
+                salt: sa.salt,
+                paymaster_url: parse_string_env("VIBEFI_PAYMASTER_URL").or(sa.paymasterUrl),
+                paymaster_context: sa.paymasterContext,
+            }
+        });
+
+        // -- Remote signer: config, URL/token env overrides --
+        let remote_signer_config = config.remoteSigner.clone().unwrap_or_default();
+        let remote_signer = parse_string_env("VIBEFI_REMOTE_SIGNER_URL")
+            .or(remote_signer_config.url)
+            .filter(
+                |url| match super::validation::check_remote_signer_url(url) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        tracing::warn!(error = %err, "remote signer disabled");
+                        false
+                    }
+                },
+            )
+            .map(|url| RemoteSignerSettings {
+                url,
+                auth_token: parse_string_env("VIBEFI_REMOTE_SIGNER_TOKEN")
+                    .or(remote_signer_config.authToken),
+                headers: remote_signer_config.headers,
+            });
 
         // -- Cache dir --
         let cache_dir = config
@@ -91,9 +158,19 @@ impl ConfigBuilder {
             chain_id: config.chainId,
             deploy_block: config.deployBlock,
             dapp_registry: config.dappRegistry.clone(),
+            logs_block_range: config.logsBlockRange,
             studio_dapp_id,
+            report_registry,
+            registry_schemas: config
+                .registrySchemas
+                .iter()
+                .map(|(address, schema)| (address.to_ascii_lowercase(), *schema))
+                .collect(),
             test_network: config.testNetwork,
+            devnet_rpc: config.devnetRpc,
             rpc_url,
+            fork: None,
+            signature_lookup: config.signatureLookup,
             ipfs_api,
             ipfs_gateway,
             ipfs_fetch_backend,
@@ -101,10 +178,15 @@ impl ConfigBuilder {
             ipfs_helia_routers,
             ipfs_helia_timeout_ms,
             walletconnect_project_id,
-            walletconnect_relay_url,
+            walletconnect_relay_urls,
+            smart_account,
+            remote_signer,
             developer_private_key: config.developerPrivateKey.clone(),
+            dev_profile: parse_bool_env("VIBEFI_DEV_PROFILE").unwrap_or(false),
             cache_dir,
             config_path: self.config_path,
+            profile: None,
+            otlp_endpoint,
             enable_devtools,
             http_client: HttpClient::new(),
         }
@@ -5,9 +5,10 @@ use std::path::PathBuf;
 #[derive(Debug, Parser)]
 #[command(name = "vibefi", about)]
 pub struct CliArgs {
-    /// Path to a local dapp project directory to bundle and serve.
-    #[arg(long)]
-    pub bundle: Option<PathBuf>,
+    /// Dapp to bundle and serve: a project directory, a `.zip` or `.tar.gz`
+    /// archive, `ipfs://<root CID>`, or an `https://` URL of an archive.
+    #[arg(long, value_name = "SOURCE")]
+    pub bundle: Option<String>,
 
     /// Path to a local studio bundle directory used for Studio tab dev loading.
     #[arg(long = "studio-bundle")]
@@ -21,6 +22,50 @@ pub struct CliArgs {
     #[arg(long)]
     pub no_build: bool,
 
+    /// Let tabs of locally built projects connect to localhost and build
+    /// them with source maps. Same as `VIBEFI_DEV_PROFILE=1`.
+    #[arg(long = "dev-profile")]
+    pub dev_profile: bool,
+
+    /// User profile to run with. Each profile keeps its own settings, keys,
+    /// permissions, tabs and caches. Same as `VIBEFI_PROFILE=<NAME>`.
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Log how long each startup phase takes (target `vibefi::startup`).
+    #[arg(long = "profile-startup")]
+    pub profile_startup: bool,
+
+    /// Run against a local anvil fork of RPC_URL (the config's `rpcUrl` when
+    /// omitted), to rehearse transactions without touching the real chain.
+    #[arg(
+        long,
+        value_name = "RPC_URL",
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
+    pub fork: Option<String>,
+
+    /// Block --fork starts from; the latest block when omitted.
+    #[arg(long = "fork-block", value_name = "BLOCK", requires = "fork")]
+    pub fork_block: Option<u64>,
+
+    /// Export the cached bundle with this root CID and exit.
+    #[arg(
+        long = "export-bundle",
+        value_name = "ROOT_CID",
+        requires = "export_to"
+    )]
+    pub export_bundle: Option<String>,
+
+    /// Destination of --export-bundle: a `.zip` file or a new directory.
+    #[arg(long = "export-to", value_name = "PATH")]
+    pub export_to: Option<PathBuf>,
+
+    /// What --export-bundle includes: `all`, `source` or `dist`.
+    #[arg(long = "export-parts", default_value = "all")]
+    pub export_parts: String,
+
     /// Enable automation mode (NDJSON commands on stdin, results on stdout).
     #[arg(long)]
     pub automation: bool,
@@ -16,10 +16,10 @@ mod env;
 mod resolved;
 mod validation;
 
-pub use app_config::{AppConfig, IpfsFetchBackend};
+pub use app_config::{AppConfig, IpfsFetchBackend, RegistrySchema};
 pub use builder::ConfigBuilder;
 pub use cli::CliArgs;
-pub use resolved::ResolvedConfig;
+pub use resolved::{RemoteSignerSettings, ResolvedConfig, SmartAccountSettings};
 
 use anyhow::{Context, Result, anyhow};
 use std::path::Path;
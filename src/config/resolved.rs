@@ -1,7 +1,9 @@
 use reqwest::blocking::Client as HttpClient;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
-use super::app_config::IpfsFetchBackend;
+use super::app_config::{IpfsFetchBackend, RegistrySchema};
+use crate::fork::ForkSource;
 
 /// Single resolved configuration built once at startup.
 ///
@@ -16,9 +18,20 @@ pub struct ResolvedConfig {
     pub chain_id: u64,
     pub deploy_block: Option<u64>,
     pub dapp_registry: String,
+    /// Most blocks one `eth_getLogs` request spans.
+    pub logs_block_range: u64,
     pub studio_dapp_id: Option<u64>,
+    pub report_registry: Option<String>,
+    /// Event schema per lowercase registry address; see [`Self::registry_schema`].
+    pub registry_schemas: HashMap<String, RegistrySchema>,
     pub test_network: bool,
+    /// Passes devnet-only RPC namespaces through to dapps.
+    pub devnet_rpc: bool,
     pub rpc_url: String,
+    /// Set in `--fork` mode, where `rpc_url` is the local fork node.
+    pub fork: Option<ForkSource>,
+    /// Decodes unknown selectors through a public signature database.
+    pub signature_lookup: bool,
 
     // -- IPFS (deploy + client override) --
     pub ipfs_api: String,
@@ -30,14 +43,35 @@ pub struct ResolvedConfig {
 
     // -- WalletConnect (deploy + env override) --
     pub walletconnect_project_id: Option<String>,
-    pub walletconnect_relay_url: Option<String>,
+    /// Relays in the order they are tried; empty uses the SDK default.
+    pub walletconnect_relay_urls: Vec<String>,
 
-    // -- Developer (deploy) --
+    // -- Smart account (deploy + env override) --
+    /// Set when a bundler is configured; enables the smart account backend.
+    pub smart_account: Option<SmartAccountSettings>,
+
+    // -- Remote signer (deploy + env override) --
+    /// Set when a signer URL is configured; enables the remote signer backend.
+    pub remote_signer: Option<RemoteSignerSettings>,
+
+    // -- Developer (deploy + env/CLI override) --
     pub developer_private_key: Option<String>,
+    /// Tabs of locally built projects get a CSP that lets them reach
+    /// localhost, source maps and devtools. Registry dapps are unaffected.
+    pub dev_profile: bool,
 
     // -- Paths (client) --
     pub cache_dir: PathBuf,
+    /// User files (`settings.json`, the keystore, the session, storage
+    /// partitions) sit next to this path. With a [`Self::profile`] it points
+    /// into the profile's directory instead of at the network config.
     pub config_path: Option<PathBuf>,
+    /// User profile set with `--profile`; `None` is the default profile. See
+    /// [`crate::profiles`].
+    pub profile: Option<String>,
+
+    // -- Telemetry (deploy + env override) --
+    pub otlp_endpoint: Option<String>,
 
     // -- UI (client) --
     pub enable_devtools: bool,
@@ -46,20 +80,71 @@ pub struct ResolvedConfig {
     pub http_client: HttpClient,
 }
 
+/// ERC-4337 smart account settings with defaults applied.
+#[derive(Debug, Clone)]
+pub struct SmartAccountSettings {
+    pub bundler_url: String,
+    pub entry_point: String,
+    pub factory: String,
+    pub salt: u64,
+    pub paymaster_url: Option<String>,
+    pub paymaster_context: Option<serde_json::Value>,
+}
+
+/// Remote signer endpoint and the credentials sent with each request.
+#[derive(Clone)]
+pub struct RemoteSignerSettings {
+    pub url: String,
+    pub auth_token: Option<String>,
+    pub headers: HashMap<String, String>,
+}
+
+impl std::fmt::Debug for RemoteSignerSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Keep credentials out of logs.
+        f.debug_struct("RemoteSignerSettings")
+            .field("url", &self.url)
+            .field(
+                "auth_token",
+                &self.auth_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
 impl ResolvedConfig {
+    /// Event schema of the registry at `address`; v1 unless configured.
+    pub fn registry_schema(&self, address: &str) -> RegistrySchema {
+        self.registry_schemas
+            .get(&address.to_ascii_lowercase())
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Log a summary of the resolved configuration at startup.
     pub fn log_startup_summary(&self) {
         tracing::info!(
             chain_id = self.chain_id,
             rpc_url = %self.rpc_url,
+            fork = ?self.fork.as_ref().map(ForkSource::label),
             test_network = self.test_network,
+            devnet_rpc = self.devnet_rpc,
+            signature_lookup = self.signature_lookup,
             dapp_registry = %self.dapp_registry,
+            logs_block_range = self.logs_block_range,
             studio_dapp_id = ?self.studio_dapp_id,
+            report_registry = ?self.report_registry,
             ipfs_backend = self.ipfs_fetch_backend.as_str(),
             ipfs_gateway = %self.ipfs_gateway,
             cache_dir = %self.cache_dir.display(),
+            profile = ?self.profile,
             enable_devtools = self.enable_devtools,
+            dev_profile = self.dev_profile,
             walletconnect = self.walletconnect_project_id.is_some(),
+            smart_account = self.smart_account.is_some(),
+            remote_signer = ?self.remote_signer.as_ref().map(|signer| &signer.url),
+            otlp_endpoint = ?self.otlp_endpoint,
             "resolved configuration"
         );
     }
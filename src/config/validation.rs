@@ -6,13 +6,28 @@ use super::app_config::AppConfig;
 ///
 /// Returns an error if:
 /// - `chainId` is 0
+/// - `logsBlockRange` is 0
 /// - `dappRegistry` is non-empty but not valid hex (with optional 0x prefix)
+/// - `reportRegistry` is set but not a 20-byte hex address
+/// - a `registrySchemas` key is not a 20-byte hex address
 /// - `rpcUrl` is not a valid URL scheme (http/https/ws/wss)
+/// - `devnetRpc` is enabled for a chain that is neither a local devnet nor
+///   marked `testNetwork`
+/// - `otlpEndpoint` is set but not an http(s) URL
+/// - a WalletConnect relay is not a ws(s) URL
+/// - a `smartAccount` bundler or paymaster URL is not http(s), or its
+///   `entryPoint`/`factory` is not a 20-byte hex address
+/// - `remoteSigner.url` is set but not an https URL, or an http URL on a
+///   loopback host
 pub fn validate_app_config(config: &AppConfig) -> Result<()> {
     if config.chainId == 0 {
         bail!("chainId must not be 0");
     }
 
+    if config.logsBlockRange == 0 {
+        bail!("logsBlockRange must not be 0");
+    }
+
     if !config.dappRegistry.is_empty() {
         let hex_str = config
             .dappRegistry
@@ -23,6 +38,25 @@ pub fn validate_app_config(config: &AppConfig) -> Result<()> {
         }
     }
 
+    if let Some(report_registry) = &config.reportRegistry {
+        let hex_str = report_registry
+            .strip_prefix("0x")
+            .unwrap_or(report_registry);
+        if hex_str.len() != 40 || hex::decode(hex_str).is_err() {
+            bail!(
+                "reportRegistry is not a valid address: {:?}",
+                report_registry
+            );
+        }
+    }
+
+    for address in config.registrySchemas.keys() {
+        let hex_str = address.strip_prefix("0x").unwrap_or(address);
+        if hex_str.len() != 40 || hex::decode(hex_str).is_err() {
+            bail!("registrySchemas key is not a valid address: {:?}", address);
+        }
+    }
+
     if !config.rpcUrl.is_empty() {
         let lower = config.rpcUrl.to_ascii_lowercase();
         if !lower.starts_with("http://")
@@ -37,23 +71,118 @@ pub fn validate_app_config(config: &AppConfig) -> Result<()> {
         }
     }
 
+    if let Some(endpoint) = &config.otlpEndpoint {
+        let lower = endpoint.to_ascii_lowercase();
+        if !lower.starts_with("http://") && !lower.starts_with("https://") {
+            bail!(
+                "otlpEndpoint must start with http:// or https://: {:?}",
+                endpoint
+            );
+        }
+    }
+
+    if let Some(wc) = &config.walletConnect {
+        for url in wc.relayUrl.iter().chain(&wc.relayUrls) {
+            let lower = url.trim().to_ascii_lowercase();
+            if !lower.starts_with("wss://") && !lower.starts_with("ws://") {
+                bail!(
+                    "walletConnect relays must start with ws:// or wss://: {:?}",
+                    url
+                );
+            }
+        }
+    }
+
+    if let Some(sa) = &config.smartAccount {
+        for url in sa.bundlerUrl.iter().chain(&sa.paymasterUrl) {
+            let lower = url.trim().to_ascii_lowercase();
+            if !lower.starts_with("http://") && !lower.starts_with("https://") {
+                bail!(
+                    "smartAccount bundler and paymaster URLs must start with http:// or https://: {:?}",
+                    url
+                );
+            }
+        }
+        for address in sa.entryPoint.iter().chain(&sa.factory) {
+            let hex_str = address.strip_prefix("0x").unwrap_or(address);
+            if hex_str.len() != 40 || hex::decode(hex_str).is_err() {
+                bail!(
+                    "smartAccount contract is not a valid address: {:?}",
+                    address
+                );
+            }
+        }
+    }
+
+    if let Some(url) = config.remoteSigner.as_ref().and_then(|rs| rs.url.as_ref()) {
+        check_remote_signer_url(url)?;
+    }
+
+    if config.devnetRpc
+        && !config.testNetwork
+        && !crate::policy::DEVNET_CHAIN_IDS.contains(&config.chainId)
+    {
+        bail!(
+            "devnetRpc is only allowed on a local devnet ({:?}) or a testNetwork config, not chainId {}",
+            crate::policy::DEVNET_CHAIN_IDS,
+            config.chainId
+        );
+    }
+
     Ok(())
 }
 
+/// The remote signer gets the auth token and every signing request, so it
+/// must be reached over HTTPS unless it runs on this machine.
+pub(crate) fn check_remote_signer_url(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url.trim()).ok();
+    let secure = parsed.as_ref().is_some_and(|parsed| match parsed.scheme() {
+        "https" => true,
+        "http" => is_loopback(parsed),
+        _ => false,
+    });
+    if !secure {
+        bail!(
+            "remoteSigner.url must use https:// (http:// only for localhost): {:?}",
+            url
+        );
+    }
+    Ok(())
+}
+
+fn is_loopback(url: &reqwest::Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::app_config::IpfsFetchBackend;
+    use crate::config::app_config::{
+        IpfsFetchBackend, RemoteSignerConfig, SmartAccountConfig, WalletConnectConfig,
+    };
 
     fn minimal_config() -> AppConfig {
         AppConfig {
             chainId: 1,
             deployBlock: None,
             dappRegistry: String::new(),
+            logsBlockRange: 50_000,
             studioDappId: None,
+            registrySchemas: Default::default(),
+            reportRegistry: None,
             developerPrivateKey: None,
             rpcUrl: "http://127.0.0.1:8546".to_string(),
             testNetwork: false,
+            devnetRpc: false,
+            signatureLookup: false,
             ipfsApi: None,
             ipfsGateway: None,
             ipfsFetchBackend: IpfsFetchBackend::default(),
@@ -61,7 +190,10 @@ mod tests {
             ipfsHeliaRouters: Vec::new(),
             ipfsHeliaTimeoutMs: 15_000,
             cacheDir: None,
+            otlpEndpoint: None,
             walletConnect: None,
+            smartAccount: None,
+            remoteSigner: None,
         }
     }
 
@@ -77,6 +209,13 @@ mod tests {
         assert!(validate_app_config(&cfg).is_err());
     }
 
+    #[test]
+    fn zero_logs_block_range_rejected() {
+        let mut cfg = minimal_config();
+        cfg.logsBlockRange = 0;
+        assert!(validate_app_config(&cfg).is_err());
+    }
+
     #[test]
     fn invalid_dapp_registry_rejected() {
         let mut cfg = minimal_config();
@@ -91,6 +230,137 @@ mod tests {
         assert!(validate_app_config(&cfg).is_ok());
     }
 
+    #[test]
+    fn report_registry_must_be_an_address() {
+        let mut cfg = minimal_config();
+        cfg.reportRegistry = Some("0xaabbccdd".to_string());
+        assert!(validate_app_config(&cfg).is_err());
+        cfg.reportRegistry = Some("0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string());
+        assert!(validate_app_config(&cfg).is_ok());
+    }
+
+    #[test]
+    fn registry_schemas_are_keyed_by_address() {
+        let cfg: AppConfig = serde_json::from_value(serde_json::json!({
+            "chainId": 1,
+            "registrySchemas": { "0x5FbDB2315678afecb367f032d93F642f64180aa3": "v2" },
+        }))
+        .unwrap();
+        assert!(validate_app_config(&cfg).is_ok());
+
+        let cfg: AppConfig = serde_json::from_value(serde_json::json!({
+            "chainId": 1,
+            "registrySchemas": { "registry": "v2" },
+        }))
+        .unwrap();
+        assert!(validate_app_config(&cfg).is_err());
+    }
+
+    #[test]
+    fn devnet_rpc_rejected_on_mainnet() {
+        let mut cfg = minimal_config();
+        cfg.devnetRpc = true;
+        assert!(validate_app_config(&cfg).is_err());
+    }
+
+    #[test]
+    fn devnet_rpc_rejected_on_other_mainnets() {
+        let mut cfg = minimal_config();
+        cfg.devnetRpc = true;
+        for chain_id in [10, 137, 8453, 42161] {
+            cfg.chainId = chain_id;
+            assert!(validate_app_config(&cfg).is_err(), "chain {chain_id}");
+        }
+    }
+
+    #[test]
+    fn devnet_rpc_allowed_on_devnets() {
+        let mut cfg = minimal_config();
+        cfg.devnetRpc = true;
+        for chain_id in [31337, 1337] {
+            cfg.chainId = chain_id;
+            assert!(validate_app_config(&cfg).is_ok(), "chain {chain_id}");
+        }
+    }
+
+    #[test]
+    fn devnet_rpc_allowed_on_test_networks() {
+        let mut cfg = minimal_config();
+        cfg.devnetRpc = true;
+        cfg.chainId = 11155111;
+        cfg.testNetwork = true;
+        assert!(validate_app_config(&cfg).is_ok());
+    }
+
+    #[test]
+    fn otlp_endpoint_must_be_http() {
+        let mut cfg = minimal_config();
+        cfg.otlpEndpoint = Some("grpc://collector:4317".to_string());
+        assert!(validate_app_config(&cfg).is_err());
+        cfg.otlpEndpoint = Some("http://collector:4318/v1/traces".to_string());
+        assert!(validate_app_config(&cfg).is_ok());
+    }
+
+    #[test]
+    fn walletconnect_relays_must_be_websockets() {
+        let mut cfg = minimal_config();
+        cfg.walletConnect = Some(WalletConnectConfig {
+            projectId: None,
+            relayUrl: Some("wss://relay.walletconnect.org".to_string()),
+            relayUrls: vec!["https://relay.example".to_string()],
+        });
+        assert!(validate_app_config(&cfg).is_err());
+        cfg.walletConnect.as_mut().unwrap().relayUrls = vec!["ws://127.0.0.1:5555".to_string()];
+        assert!(validate_app_config(&cfg).is_ok());
+    }
+
+    #[test]
+    fn smart_account_urls_and_contracts_are_checked() {
+        let mut cfg = minimal_config();
+        cfg.smartAccount = Some(SmartAccountConfig {
+            bundlerUrl: Some("wss://bundler.example".to_string()),
+            ..Default::default()
+        });
+        assert!(validate_app_config(&cfg).is_err());
+        let sa = cfg.smartAccount.as_mut().unwrap();
+        sa.bundlerUrl = Some("https://bundler.example/rpc".to_string());
+        sa.factory = Some("0x1234".to_string());
+        assert!(validate_app_config(&cfg).is_err());
+        cfg.smartAccount.as_mut().unwrap().factory =
+            Some("0x91E60e0613810449d098b0b5Ec8b51A0FE8c8985".to_string());
+        assert!(validate_app_config(&cfg).is_ok());
+    }
+
+    #[test]
+    fn remote_signer_url_must_be_http() {
+        let mut cfg = minimal_config();
+        cfg.remoteSigner = Some(RemoteSignerConfig {
+            url: Some("unix:///run/signer.sock".to_string()),
+            ..Default::default()
+        });
+        assert!(validate_app_config(&cfg).is_err());
+        cfg.remoteSigner.as_mut().unwrap().url = Some("https://signer.example:9000".to_string());
+        assert!(validate_app_config(&cfg).is_ok());
+    }
+
+    #[test]
+    fn remote_signer_plain_http_only_on_loopback() {
+        let mut cfg = minimal_config();
+        cfg.remoteSigner = Some(RemoteSignerConfig {
+            url: Some("http://signer.internal:9000".to_string()),
+            ..Default::default()
+        });
+        assert!(validate_app_config(&cfg).is_err());
+        for url in [
+            "http://127.0.0.1:9000",
+            "http://localhost:9000",
+            "http://[::1]:9000",
+        ] {
+            cfg.remoteSigner.as_mut().unwrap().url = Some(url.to_string());
+            assert!(validate_app_config(&cfg).is_ok(), "{url}");
+        }
+    }
+
     #[test]
     fn invalid_rpc_url_rejected() {
         let mut cfg = minimal_config();
@@ -0,0 +1,155 @@
+use anyhow::{Result, bail};
+use serde_json::json;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::state::{AppState, UserEvent};
+
+/// Error message for requests refused while offline. Responses carrying it
+/// use the EIP-1193 "Disconnected" code.
+pub const OFFLINE_ERROR: &str = "VibeFi is offline: the RPC endpoint is unreachable";
+/// EIP-1193 provider error code for a provider disconnected from all chains.
+pub const DISCONNECTED_CODE: i64 = 4900;
+
+const PROBE_MIN_BACKOFF: Duration = Duration::from_secs(2);
+const PROBE_MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Queued background work gives up after being offline this long.
+const MAX_QUEUE_TIME: Duration = Duration::from_secs(30 * 60);
+
+static ONLINE: Mutex<bool> = Mutex::new(true);
+static CHANGED: Condvar = Condvar::new();
+
+pub fn is_online() -> bool {
+    *ONLINE.lock().expect("connectivity")
+}
+
+/// Records whether the last RPC round trip reached an endpoint.
+pub fn report(reachable: bool) {
+    let mut online = ONLINE.lock().expect("connectivity");
+    if *online != reachable {
+        *online = reachable;
+        CHANGED.notify_all();
+    }
+}
+
+/// Fails fast instead of letting an interactive request run into timeouts.
+pub fn ensure_online() -> Result<()> {
+    if !is_online() {
+        bail!(OFFLINE_ERROR);
+    }
+    Ok(())
+}
+
+pub fn error_code(message: &str) -> i64 {
+    if message.starts_with(OFFLINE_ERROR) {
+        DISCONNECTED_CODE
+    } else if message.starts_with(crate::lock::LOCKED_ERROR) {
+        crate::lock::UNAUTHORIZED_CODE
+    } else {
+        crate::error_hints::classify(message)
+            .and_then(|hint| hint.rpc_code)
+            .unwrap_or(-32601)
+    }
+}
+
+/// Blocks until the client is online or `timeout` passes. Returns whether it
+/// is online.
+pub fn wait_until_online(timeout: Duration) -> bool {
+    let guard = ONLINE.lock().expect("connectivity");
+    let (online, _) = CHANGED
+        .wait_timeout_while(guard, timeout, |online| !*online)
+        .expect("connectivity");
+    *online
+}
+
+fn wait_until_offline() {
+    let guard = ONLINE.lock().expect("connectivity");
+    drop(
+        CHANGED
+            .wait_while(guard, |online| *online)
+            .expect("connectivity"),
+    );
+}
+
+/// Runs non-urgent RPC work (receipt polling, registry refresh), holding it
+/// while offline and retrying with backoff when it fails for lack of a
+/// connection. Errors while online are returned as is.
+pub fn retry_when_online<T>(what: &str, mut work: impl FnMut() -> Result<T>) -> Result<T> {
+    let started = Instant::now();
+    let mut backoff = PROBE_MIN_BACKOFF;
+    loop {
+        let remaining = MAX_QUEUE_TIME.saturating_sub(started.elapsed());
+        if !is_online() {
+            tracing::debug!(what, "offline; queueing until the RPC is reachable");
+            if !wait_until_online(remaining) {
+                bail!("{what}: {OFFLINE_ERROR}");
+            }
+        }
+        match work() {
+            Ok(value) => return Ok(value),
+            Err(err) if !is_online() && !remaining.is_zero() => {
+                tracing::debug!(what, error = %format!("{err:#}"), ?backoff, "retrying once online");
+                std::thread::sleep(backoff);
+                backoff = (backoff * 2).min(PROBE_MAX_BACKOFF);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Watches for the RPC becoming unreachable, tells dapp tabs, and probes with
+/// backoff until it answers again.
+pub fn spawn_monitor(state: AppState) {
+    if state.resolved.is_none() {
+        return;
+    }
+    std::thread::spawn(move || {
+        loop {
+            wait_until_offline();
+            tracing::warn!("rpc unreachable; client is offline");
+            let _ = state
+                .proxy
+                .send_event(UserEvent::ConnectivityChanged { online: false });
+            let mut backoff = PROBE_MIN_BACKOFF;
+            while !wait_until_online(backoff) {
+                // A successful probe reports the client back online.
+                if let Err(err) = crate::ipc::rpc_request(&state, "eth_blockNumber", json!([])) {
+                    tracing::debug!(error = %format!("{err:#}"), ?backoff, "offline probe failed");
+                }
+                backoff = (backoff * 2).min(PROBE_MAX_BACKOFF);
+            }
+            tracing::info!("rpc reachable again; client is online");
+            let _ = state
+                .proxy
+                .send_event(UserEvent::ConnectivityChanged { online: true });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DISCONNECTED_CODE, OFFLINE_ERROR, error_code};
+
+    #[test]
+    fn offline_errors_use_the_disconnected_code() {
+        assert_eq!(error_code(OFFLINE_ERROR), DISCONNECTED_CODE);
+    }
+
+    #[test]
+    fn lock_errors_use_the_unauthorized_code() {
+        assert_eq!(
+            error_code(crate::lock::LOCKED_ERROR),
+            crate::lock::UNAUTHORIZED_CODE
+        );
+    }
+
+    #[test]
+    fn rejections_use_the_user_rejected_code() {
+        assert_eq!(error_code("Request rejected on the Ledger"), 4001);
+    }
+
+    #[test]
+    fn other_errors_keep_the_generic_code() {
+        assert_eq!(error_code("rpc error: execution reverted"), -32601);
+    }
+}
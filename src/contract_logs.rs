@@ -0,0 +1,327 @@
+//! Contract logs on a devnet, for Studio's log panel. Studio sends the
+//! project's `abis/*.json`; each one that names its deployed `address` (a
+//! Hardhat deployment, or an artifact with the field added) is tailed with
+//! `eth_getLogs`, and its logs are decoded with that file's ABI.
+
+use alloy_primitives::Address;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::decoder::AbiIndex;
+use crate::state::AppState;
+use crate::studio_context::ContextFile;
+
+/// Blocks a first request looks back over when it names no `fromBlock`.
+const DEFAULT_LOOKBACK_BLOCKS: u64 = 100;
+/// Most blocks one request scans; a panel that fell behind catches up over
+/// several polls.
+const MAX_BLOCK_SPAN: u64 = 2_000;
+
+/// What Studio sends to read the next page of its contracts' logs.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsRequest {
+    /// The project's ABI files; only those under `abis/` are read.
+    pub abis: Vec<ContextFile>,
+    /// First block to read, the previous page's `nextBlock` when tailing.
+    #[serde(default)]
+    pub from_block: Option<u64>,
+    /// Only logs of this contract, by name or address.
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Only logs decoded as this event.
+    #[serde(default)]
+    pub event: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsPage {
+    /// Oldest first, as the node returns them.
+    pub logs: Vec<Value>,
+    /// Every contract the ABIs name, for the panel's filters.
+    pub contracts: Vec<WatchedContract>,
+    /// Where the next poll starts.
+    pub next_block: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WatchedContract {
+    pub name: String,
+    pub address: String,
+    pub events: Vec<String>,
+}
+
+struct Contract {
+    name: String,
+    address: Address,
+    abi: AbiIndex,
+}
+
+impl Contract {
+    fn matches(&self, filter: &str) -> bool {
+        self.name == filter
+            || filter
+                .parse::<Address>()
+                .is_ok_and(|address| address == self.address)
+    }
+}
+
+/// Reads the contracts the project's ABI files name. Files without an
+/// address have nothing to tail and are skipped.
+fn contracts(files: &[ContextFile]) -> Vec<Contract> {
+    let mut contracts = Vec::new();
+    for file in files {
+        let path = file.path.trim_start_matches("./");
+        let Some(file_name) = path.strip_prefix("abis/") else {
+            continue;
+        };
+        let Some(stem) = file_name.strip_suffix(".json") else {
+            continue;
+        };
+        let parsed = serde_json::from_str::<Value>(&file.content)
+            .context("parse ABI")
+            .and_then(|abi| {
+                let address = abi
+                    .get("address")
+                    .and_then(Value::as_str)
+                    .map(|address| address.parse::<Address>().context("invalid address"))
+                    .transpose()?;
+                Ok((abi, address))
+            });
+        let (abi, address) = match parsed {
+            Ok((abi, Some(address))) => (abi, address),
+            Ok((_, None)) => {
+                tracing::debug!(path, "project ABI names no address; not tailing it");
+                continue;
+            }
+            Err(err) => {
+                tracing::warn!(path, error = %format!("{err:#}"), "skipping project ABI");
+                continue;
+            }
+        };
+        let abi_index = match AbiIndex::from_abi(&abi) {
+            Ok(index) => index,
+            Err(err) => {
+                tracing::warn!(path, error = %format!("{err:#}"), "skipping project ABI");
+                continue;
+            }
+        };
+        let name = abi
+            .get("contractName")
+            .and_then(Value::as_str)
+            .unwrap_or(stem)
+            .to_string();
+        contracts.push(Contract {
+            name,
+            address,
+            abi: abi_index,
+        });
+    }
+    contracts
+}
+
+/// One log for the panel: where it came from, and its event and arguments
+/// when the contract's ABI decodes it. Logs of another event than `event`
+/// are dropped.
+fn panel_log(contracts: &[&Contract], log: &Value, event: Option<&str>) -> Option<Value> {
+    let address: Address = log.get("address")?.as_str()?.parse().ok()?;
+    let contract = contracts.iter().find(|c| c.address == address)?;
+    let decoded = contract.abi.decode_log(log);
+    let name = decoded
+        .as_ref()
+        .and_then(|decoded| decoded.get("event"))
+        .and_then(Value::as_str);
+    if event.is_some_and(|wanted| name != Some(wanted)) {
+        return None;
+    }
+    Some(json!({
+        "contract": contract.name,
+        "address": address.to_string(),
+        "blockNumber": log.get("blockNumber"),
+        "transactionHash": log.get("transactionHash"),
+        "logIndex": log.get("logIndex"),
+        "event": name,
+        "args": decoded.as_ref().and_then(|decoded| decoded.get("args")),
+        "topics": log.get("topics"),
+        "data": log.get("data"),
+    }))
+}
+
+fn quantity(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.trim_start_matches("0x"), 16).ok()
+}
+
+/// The next page of logs for the project's contracts, at most
+/// [`MAX_BLOCK_SPAN`] blocks from `fromBlock`. Only for devnets, where the
+/// node is local and scanning it on every poll is cheap.
+pub fn tail(state: &AppState, request: &LogsRequest) -> Result<LogsPage> {
+    if !state.resolved.as_ref().is_some_and(|r| r.devnet_rpc) {
+        bail!("contract logs are only available with devnetRpc enabled");
+    }
+    let contracts = contracts(&request.abis);
+    let summaries = contracts
+        .iter()
+        .map(|contract| WatchedContract {
+            name: contract.name.clone(),
+            address: contract.address.to_string(),
+            events: contract.abi.event_names(),
+        })
+        .collect();
+    let watched: Vec<&Contract> = contracts
+        .iter()
+        .filter(|contract| {
+            request
+                .contract
+                .as_deref()
+                .is_none_or(|filter| contract.matches(filter))
+        })
+        .collect();
+
+    let latest = crate::ipc::rpc_request(state, "eth_blockNumber", json!([]))
+        .context("eth_blockNumber failed")?;
+    let latest = quantity(&latest).context("eth_blockNumber returned no block number")?;
+    let from = request
+        .from_block
+        .unwrap_or_else(|| latest.saturating_sub(DEFAULT_LOOKBACK_BLOCKS - 1));
+    if from > latest || watched.is_empty() {
+        return Ok(LogsPage {
+            logs: Vec::new(),
+            contracts: summaries,
+            next_block: from.max(latest + 1),
+        });
+    }
+    let to = latest.min(from + MAX_BLOCK_SPAN - 1);
+    let addresses: Vec<String> = watched.iter().map(|c| c.address.to_string()).collect();
+    let logs = crate::ipc::rpc_request(
+        state,
+        "eth_getLogs",
+        json!([{
+            "address": addresses,
+            "fromBlock": format!("{from:#x}"),
+            "toBlock": format!("{to:#x}"),
+        }]),
+    )
+    .context("eth_getLogs failed")?;
+    let logs = logs
+        .as_array()
+        .context("eth_getLogs returned no log list")?
+        .iter()
+        .filter_map(|log| panel_log(&watched, log, request.event.as_deref()))
+        .collect();
+    Ok(LogsPage {
+        logs,
+        contracts: summaries,
+        next_block: to + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contracts, panel_log};
+    use crate::studio_context::ContextFile;
+    use serde_json::json;
+
+    fn file(path: &str, content: serde_json::Value) -> ContextFile {
+        ContextFile {
+            path: path.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    const TOKEN: &str = "0x5fbdb2315678afecb367f032d93f642f64180aa3";
+
+    fn transfer_event() -> serde_json::Value {
+        json!({
+            "type": "event",
+            "name": "Transfer",
+            "inputs": [
+                { "name": "from", "type": "address", "indexed": true },
+                { "name": "to", "type": "address", "indexed": true },
+                { "name": "value", "type": "uint256", "indexed": false },
+            ],
+        })
+    }
+
+    fn token_abi() -> ContextFile {
+        file(
+            "abis/Token.json",
+            json!({ "address": TOKEN, "abi": [transfer_event()] }),
+        )
+    }
+
+    /// A `Transfer` of 1000000 from the token.
+    fn transfer_log() -> serde_json::Value {
+        json!({
+            "address": TOKEN,
+            "blockNumber": "0x2",
+            "topics": [
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                "0x000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045",
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+            ],
+            "data": "0x00000000000000000000000000000000000000000000000000000000000f4240",
+        })
+    }
+
+    fn unknown_log() -> serde_json::Value {
+        let mut unknown = transfer_log();
+        unknown["topics"][0] = json!("0x".to_string() + &"11".repeat(32));
+        unknown
+    }
+
+    #[test]
+    fn tails_only_abi_files_with_an_address() {
+        let files = [
+            token_abi(),
+            file("abis/Undeployed.json", json!([transfer_event()])),
+            file("src/Token.json", json!({ "address": TOKEN, "abi": [] })),
+            file(
+                "abis/Broken.json",
+                json!({ "address": "not an address", "abi": [] }),
+            ),
+        ];
+        let contracts = contracts(&files);
+        assert_eq!(contracts.len(), 1);
+        assert_eq!(contracts[0].name, "Token");
+        assert_eq!(contracts[0].abi.event_names(), ["Transfer"]);
+    }
+
+    #[test]
+    fn contracts_match_by_name_or_address() {
+        let contracts = contracts(&[token_abi()]);
+        assert!(contracts[0].matches("Token"));
+        assert!(contracts[0].matches("0x5FbDB2315678afecb367f032d93F642f64180aa3"));
+        assert!(!contracts[0].matches("Other"));
+    }
+
+    #[test]
+    fn decodes_logs_with_the_contract_abi() {
+        let contracts = contracts(&[token_abi()]);
+        let watched: Vec<_> = contracts.iter().collect();
+        let shown = panel_log(&watched, &transfer_log(), None).unwrap();
+        assert_eq!(shown["contract"], "Token");
+        assert_eq!(shown["event"], "Transfer");
+        assert_eq!(shown["args"][2]["value"], "1000000");
+    }
+
+    #[test]
+    fn filters_logs_by_event() {
+        let contracts = contracts(&[token_abi()]);
+        let watched: Vec<_> = contracts.iter().collect();
+        assert!(panel_log(&watched, &transfer_log(), Some("Transfer")).is_some());
+        assert!(panel_log(&watched, &transfer_log(), Some("Approval")).is_none());
+    }
+
+    #[test]
+    fn shows_undecodable_logs_unless_filtered_by_event() {
+        let contracts = contracts(&[token_abi()]);
+        let watched: Vec<_> = contracts.iter().collect();
+        assert_eq!(
+            panel_log(&watched, &unknown_log(), None).unwrap()["event"],
+            json!(null)
+        );
+        assert!(panel_log(&watched, &unknown_log(), Some("Transfer")).is_none());
+    }
+}
@@ -0,0 +1,650 @@
+//! Transaction calldata spelled out for the approval prompt. Dapps may ship
+//! JSON ABIs under `abis/` in their bundle; selectors none of them cover are
+//! looked up in a public signature database when `signatureLookup` is set in
+//! the config. Database matches are guesses, and are labelled as such.
+//! Bundle ABIs also name the calls a dapp reads through the RPC log and the
+//! calls and events in its transaction history, and Studio's project ABIs
+//! decode the contract logs it tails on a devnet.
+
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_primitives::{B256, keccak256};
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::state::AppState;
+
+/// Text signatures by selector, from the openchain.xyz database.
+const SIGNATURE_DATABASE: &str = "https://api.openchain.xyz/signature-database/v1/lookup";
+/// A slow database must not hold up the confirmation prompt for long.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// ABIs of each open dapp tab, keyed by webview id.
+static TAB_ABIS: Mutex<Option<HashMap<String, Arc<AbiIndex>>>> = Mutex::new(None);
+/// Database answers for this session; `None` when it knows no signature.
+static LOOKUPS: Mutex<Option<HashMap<[u8; 4], Option<Vec<String>>>>> = Mutex::new(None);
+
+/// One function input: its name, possibly empty, and canonical type.
+#[derive(Debug, Clone, PartialEq)]
+struct Input {
+    name: String,
+    ty: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Function {
+    name: String,
+    inputs: Vec<Input>,
+}
+
+impl Function {
+    fn signature(&self) -> String {
+        let types: Vec<&str> = self.inputs.iter().map(|input| input.ty.as_str()).collect();
+        format!("{}({})", self.name, types.join(","))
+    }
+
+    fn selector(&self) -> [u8; 4] {
+        let hash = keccak256(self.signature());
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// Parses a text signature such as `transfer(address,uint256)`.
+    fn parse(signature: &str) -> Option<Self> {
+        let open = signature.find('(')?;
+        let params = signature.get(open + 1..)?.strip_suffix(')')?;
+        Some(Self {
+            name: signature[..open].to_string(),
+            inputs: split_top_level(params)
+                .into_iter()
+                .map(|ty| Input {
+                    name: String::new(),
+                    ty,
+                })
+                .collect(),
+        })
+    }
+
+    /// Decodes the arguments after the selector; fails when the data does
+    /// not fit the inputs.
+    fn decode(&self, args: &[u8]) -> Result<Vec<Value>> {
+        let types = self
+            .inputs
+            .iter()
+            .map(|input| DynSolType::parse(&input.ty))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| anyhow!("unsupported type: {err}"))?;
+        let decoded = DynSolType::Tuple(types)
+            .abi_decode_params(args)
+            .map_err(|err| anyhow!("calldata does not match: {err}"))?;
+        let DynSolValue::Tuple(values) = decoded else {
+            return Err(anyhow!("calldata does not match"));
+        };
+        Ok(self
+            .inputs
+            .iter()
+            .zip(&values)
+            .map(|(input, value)| {
+                json!({
+                    "name": input.name,
+                    "type": input.ty,
+                    "value": display_value(value),
+                })
+            })
+            .collect())
+    }
+}
+
+/// One event input; indexed ones come from the log's topics.
+#[derive(Debug, Clone, PartialEq)]
+struct EventInput {
+    name: String,
+    ty: String,
+    indexed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Event {
+    name: String,
+    inputs: Vec<EventInput>,
+}
+
+impl Event {
+    fn signature(&self) -> String {
+        let types: Vec<&str> = self.inputs.iter().map(|input| input.ty.as_str()).collect();
+        format!("{}({})", self.name, types.join(","))
+    }
+
+    /// Decodes the arguments of a log with this event's topic. Indexed
+    /// arguments of other than value types are only stored as their hash,
+    /// which is shown as is.
+    fn decode(&self, topics: &[B256], data: &[u8]) -> Result<Vec<Value>> {
+        let indexed_count = self.inputs.iter().filter(|input| input.indexed).count();
+        if topics.len() != indexed_count + 1 {
+            return Err(anyhow!("log does not match the event's indexed inputs"));
+        }
+        let body_types = self
+            .inputs
+            .iter()
+            .filter(|input| !input.indexed)
+            .map(|input| DynSolType::parse(&input.ty))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| anyhow!("unsupported type: {err}"))?;
+        let DynSolValue::Tuple(body) = DynSolType::Tuple(body_types)
+            .abi_decode_params(data)
+            .map_err(|err| anyhow!("log data does not match: {err}"))?
+        else {
+            return Err(anyhow!("log data does not match"));
+        };
+        let mut topics = topics[1..].iter();
+        let mut body = body.iter();
+        self.inputs
+            .iter()
+            .map(|input| {
+                let value = if input.indexed {
+                    let topic = topics.next().ok_or_else(|| anyhow!("missing topic"))?;
+                    let ty = DynSolType::parse(&input.ty)
+                        .map_err(|err| anyhow!("unsupported type: {err}"))?;
+                    match ty {
+                        DynSolType::Address
+                        | DynSolType::Bool
+                        | DynSolType::Int(_)
+                        | DynSolType::Uint(_)
+                        | DynSolType::FixedBytes(_) => display_value(
+                            &ty.abi_decode(topic.as_slice())
+                                .map_err(|err| anyhow!("topic does not match: {err}"))?,
+                        ),
+                        _ => json!(format!("{topic:#x}")),
+                    }
+                } else {
+                    display_value(body.next().ok_or_else(|| anyhow!("missing log data"))?)
+                };
+                Ok(json!({
+                    "name": input.name,
+                    "type": input.ty,
+                    "indexed": input.indexed,
+                    "value": value,
+                }))
+            })
+            .collect()
+    }
+}
+
+/// Functions of a bundle's ABIs by selector, and events by topic.
+#[derive(Debug, Default)]
+pub struct AbiIndex {
+    functions: HashMap<[u8; 4], Function>,
+    events: HashMap<B256, Event>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AbiEntry {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    inputs: Vec<AbiParam>,
+    #[serde(default)]
+    anonymous: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AbiParam {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default)]
+    indexed: bool,
+    #[serde(default)]
+    components: Vec<AbiParam>,
+}
+
+impl AbiParam {
+    /// The type as it appears in a signature, with tuples spelled out.
+    fn canonical_type(&self) -> String {
+        match self.ty.strip_prefix("tuple") {
+            Some(suffix) => {
+                let components: Vec<String> =
+                    self.components.iter().map(Self::canonical_type).collect();
+                format!("({}){suffix}", components.join(","))
+            }
+            None => self.ty.clone(),
+        }
+    }
+}
+
+impl AbiIndex {
+    /// Reads every `abis/*.json` of a bundle. Files that are not ABIs are
+    /// skipped with a warning.
+    fn load(bundle_root: &Path) -> Self {
+        let mut index = Self::default();
+        let Ok(entries) = std::fs::read_dir(bundle_root.join("abis")) else {
+            return index;
+        };
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let added = std::fs::read_to_string(&path)
+                .context("read ABI")
+                .and_then(|raw| serde_json::from_str(&raw).context("parse ABI"))
+                .and_then(|abi| index.add(&abi));
+            if let Err(err) = added {
+                tracing::warn!(path = %path.display(), error = %format!("{err:#}"), "skipping bundle ABI");
+            }
+        }
+        index
+    }
+
+    /// Index of one JSON ABI, in any form [`Self::add`] takes.
+    pub fn from_abi(abi: &Value) -> Result<Self> {
+        let mut index = Self::default();
+        index.add(abi)?;
+        Ok(index)
+    }
+
+    /// Adds the functions and events of a JSON ABI, given as the entry list
+    /// or as a Foundry/Hardhat artifact holding it under `abi`. Anonymous
+    /// events have no topic to find them by and are left out.
+    fn add(&mut self, abi: &Value) -> Result<()> {
+        let entries = abi.get("abi").unwrap_or(abi).clone();
+        let entries: Vec<AbiEntry> = serde_json::from_value(entries).context("not a JSON ABI")?;
+        for entry in entries {
+            match entry.kind.as_str() {
+                "function" => {
+                    let function = Function {
+                        name: entry.name,
+                        inputs: entry
+                            .inputs
+                            .iter()
+                            .map(|param| Input {
+                                name: param.name.clone(),
+                                ty: param.canonical_type(),
+                            })
+                            .collect(),
+                    };
+                    self.functions.insert(function.selector(), function);
+                }
+                "event" if !entry.anonymous => {
+                    let event = Event {
+                        name: entry.name,
+                        inputs: entry
+                            .inputs
+                            .iter()
+                            .map(|param| EventInput {
+                                name: param.name.clone(),
+                                ty: param.canonical_type(),
+                                indexed: param.indexed,
+                            })
+                            .collect(),
+                    };
+                    self.events.insert(keccak256(event.signature()), event);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.events.is_empty()
+    }
+
+    /// Names of the events these ABIs decode, sorted and without repeats.
+    pub fn event_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.events.values().map(|e| e.name.clone()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Function name and arguments of `input`, if these ABIs cover it.
+    pub fn decode_call(&self, input: &[u8]) -> Option<Value> {
+        let selector: [u8; 4] = input.get(..4)?.try_into().ok()?;
+        let function = self.functions.get(&selector)?;
+        match function.decode(&input[4..]) {
+            Ok(args) => Some(describe(function, args, "bundle")),
+            Err(err) => {
+                tracing::debug!(error = %format!("{err:#}"), "bundle ABI does not decode calldata");
+                None
+            }
+        }
+    }
+
+    /// Event name and arguments of a log in `eth_getLogs` or receipt form,
+    /// if these ABIs cover it.
+    pub fn decode_log(&self, log: &Value) -> Option<Value> {
+        let topics = log
+            .get("topics")?
+            .as_array()?
+            .iter()
+            .map(|topic| topic.as_str()?.parse::<B256>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let event = self.events.get(topics.first()?)?;
+        let data = log.get("data").and_then(Value::as_str).unwrap_or("0x");
+        let data = hex::decode(data.trim_start_matches("0x")).ok()?;
+        match event.decode(&topics, &data) {
+            Ok(args) => Some(json!({
+                "event": event.name,
+                "signature": event.signature(),
+                "address": log.get("address"),
+                "args": args,
+            })),
+            Err(err) => {
+                tracing::debug!(error = %format!("{err:#}"), "bundle ABI does not decode log");
+                None
+            }
+        }
+    }
+}
+
+/// Loads the ABIs shipped with the bundle a tab serves from `dist_dir`.
+pub fn register_tab(webview_id: &str, dist_dir: &Path) {
+    let Some(bundle_root) = dist_dir.parent().and_then(|p| p.parent()) else {
+        return;
+    };
+    let index = AbiIndex::load(bundle_root);
+    if index.is_empty() {
+        return;
+    }
+    tracing::debug!(
+        webview_id,
+        functions = index.functions.len(),
+        events = index.events.len(),
+        "loaded bundle ABIs"
+    );
+    TAB_ABIS
+        .lock()
+        .expect("tab_abis")
+        .get_or_insert_with(HashMap::new)
+        .insert(webview_id.to_string(), Arc::new(index));
+}
+
+/// The ABIs of the bundle a tab runs. They stay usable after the tab closes,
+/// e.g. for a transaction it sent that is still being mined.
+pub fn tab_abis(webview_id: &str) -> Option<Arc<AbiIndex>> {
+    TAB_ABIS
+        .lock()
+        .expect("tab_abis")
+        .as_ref()?
+        .get(webview_id)
+        .cloned()
+}
+
+/// Drops the ABIs of a closed tab.
+pub fn forget_webview(webview_id: &str) {
+    if let Some(tabs) = TAB_ABIS.lock().expect("tab_abis").as_mut() {
+        tabs.remove(webview_id);
+    }
+}
+
+/// Function name and arguments of `input`, from the requesting tab's ABIs or
+/// the signature database. `None` when neither knows the selector or the
+/// data does not decode.
+pub fn decode_call(state: &AppState, webview_id: Option<&str>, input: &[u8]) -> Option<Value> {
+    let selector: [u8; 4] = input.get(..4)?.try_into().ok()?;
+    let args = &input[4..];
+    let bundled = webview_id
+        .and_then(tab_abis)
+        .and_then(|abis| abis.decode_call(input));
+    if bundled.is_some() {
+        return bundled;
+    }
+
+    let resolved = state.resolved.as_deref()?;
+    if !resolved.signature_lookup || !crate::connectivity::is_online() {
+        return None;
+    }
+    lookup(&resolved.http_client, selector)
+        .into_iter()
+        .filter_map(|signature| Function::parse(&signature))
+        .filter(|function| function.selector() == selector)
+        .find_map(|function| {
+            let args = function.decode(args).ok()?;
+            Some(describe(&function, args, "signatureDatabase"))
+        })
+}
+
+fn describe(function: &Function, args: Vec<Value>, source: &str) -> Value {
+    json!({
+        "function": function.name,
+        "signature": function.signature(),
+        "args": args,
+        "source": source,
+    })
+}
+
+/// Candidate signatures for `selector`; empty when the lookup fails.
+fn lookup(http: &reqwest::blocking::Client, selector: [u8; 4]) -> Vec<String> {
+    let cached = LOOKUPS
+        .lock()
+        .expect("signature lookups")
+        .as_ref()
+        .and_then(|lookups| lookups.get(&selector).cloned());
+    if let Some(signatures) = cached {
+        return signatures.unwrap_or_default();
+    }
+    let key = format!("0x{}", hex::encode(selector));
+    let result = http
+        .get(SIGNATURE_DATABASE)
+        .query(&[("function", key.as_str()), ("filter", "true")])
+        .timeout(LOOKUP_TIMEOUT)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json::<Value>());
+    let body = match result {
+        Ok(body) => body,
+        Err(err) => {
+            // Not cached, so the next prompt tries again.
+            tracing::debug!(error = %err, selector = key, "signature lookup failed");
+            return Vec::new();
+        }
+    };
+    let signatures: Option<Vec<String>> = body
+        .pointer(&format!("/result/function/{key}"))
+        .and_then(Value::as_array)
+        .map(|matches| {
+            matches
+                .iter()
+                .filter_map(|entry| entry.get("name").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect()
+        });
+    LOOKUPS
+        .lock()
+        .expect("signature lookups")
+        .get_or_insert_with(HashMap::new)
+        .insert(selector, signatures.clone());
+    signatures.unwrap_or_default()
+}
+
+/// Splits a parameter list at commas outside parentheses.
+fn split_top_level(params: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in params.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(params[start..i].trim().to_string());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = params[start..].trim();
+    if !last.is_empty() {
+        parts.push(last.to_string());
+    }
+    parts
+}
+
+fn display_value(value: &DynSolValue) -> Value {
+    match value {
+        DynSolValue::Address(address) => json!(format!("{address:#x}")),
+        DynSolValue::Bool(value) => json!(value),
+        DynSolValue::Int(value, _) => json!(value.to_string()),
+        DynSolValue::Uint(value, _) => json!(value.to_string()),
+        DynSolValue::FixedBytes(word, size) => json!(format!("0x{}", hex::encode(&word[..*size]))),
+        DynSolValue::Bytes(bytes) => json!(format!("0x{}", hex::encode(bytes))),
+        DynSolValue::String(value) => json!(value),
+        DynSolValue::Array(values)
+        | DynSolValue::FixedArray(values)
+        | DynSolValue::Tuple(values) => Value::Array(values.iter().map(display_value).collect()),
+        other => json!(format!("{other:?}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AbiIndex, Function};
+    use serde_json::json;
+
+    fn function_index() -> AbiIndex {
+        let mut index = AbiIndex::default();
+        index
+            .add(&json!({ "abi": [
+                { "type": "constructor", "inputs": [] },
+                {
+                    "type": "function",
+                    "name": "transfer",
+                    "inputs": [
+                        { "name": "to", "type": "address" },
+                        { "name": "amount", "type": "uint256" },
+                    ],
+                },
+                {
+                    "type": "function",
+                    "name": "fill",
+                    "inputs": [{
+                        "name": "orders",
+                        "type": "tuple[]",
+                        "components": [
+                            { "name": "maker", "type": "address" },
+                            { "name": "amounts", "type": "uint128[2]" },
+                        ],
+                    }],
+                },
+            ]}))
+            .unwrap();
+        index
+    }
+
+    /// `transfer` arguments: vitalik.eth and 1000000.
+    fn transfer_args() -> Vec<u8> {
+        hex::decode(concat!(
+            "000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045",
+            "00000000000000000000000000000000000000000000000000000000000f4240",
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn indexes_functions_with_tuple_signatures() {
+        let index = function_index();
+        assert_eq!(index.functions.len(), 2);
+        let fill = index.functions.values().find(|f| f.name == "fill").unwrap();
+        assert_eq!(fill.signature(), "fill((address,uint128[2])[])");
+    }
+
+    #[test]
+    fn decodes_calldata_with_bundle_abis() {
+        let index = function_index();
+        let transfer = &index.functions[&[0xa9, 0x05, 0x9c, 0xbb]];
+        assert_eq!(
+            transfer.decode(&transfer_args()).unwrap(),
+            [
+                json!({ "name": "to", "type": "address", "value": "0xd8da6bf26964af9d7eed9e03e53415d37aa96045" }),
+                json!({ "name": "amount", "type": "uint256", "value": "1000000" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_calldata_is_not_decoded() {
+        let index = function_index();
+        let transfer = &index.functions[&[0xa9, 0x05, 0x9c, 0xbb]];
+        assert!(transfer.decode(&transfer_args()[..32]).is_err());
+    }
+
+    #[test]
+    fn parses_function_signatures() {
+        let parsed = Function::parse("fill((address,uint128[2])[],bytes)").unwrap();
+        assert_eq!(parsed.inputs.len(), 2);
+        assert_eq!(parsed.inputs[0].ty, "(address,uint128[2])[]");
+    }
+
+    fn event_index() -> AbiIndex {
+        let mut index = AbiIndex::default();
+        index
+            .add(&json!([
+                {
+                    "type": "event",
+                    "name": "Transfer",
+                    "inputs": [
+                        { "name": "from", "type": "address", "indexed": true },
+                        { "name": "to", "type": "address", "indexed": true },
+                        { "name": "value", "type": "uint256", "indexed": false },
+                    ],
+                },
+                {
+                    "type": "event",
+                    "name": "Noted",
+                    "anonymous": true,
+                    "inputs": [{ "name": "note", "type": "string", "indexed": true }],
+                },
+            ]))
+            .unwrap();
+        index
+    }
+
+    fn transfer_log() -> serde_json::Value {
+        json!({
+            "address": "0x6b175474e89094c44da98b954eedeac495271d0f",
+            "topics": [
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                "0x000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045",
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+            ],
+            "data": "0x00000000000000000000000000000000000000000000000000000000000f4240",
+        })
+    }
+
+    #[test]
+    fn anonymous_events_are_not_indexed() {
+        assert_eq!(event_index().events.len(), 1);
+    }
+
+    #[test]
+    fn decodes_logs_with_bundle_abis() {
+        let decoded = event_index().decode_log(&transfer_log()).unwrap();
+        assert_eq!(decoded["event"], "Transfer");
+        assert_eq!(decoded["signature"], "Transfer(address,address,uint256)");
+        assert_eq!(
+            decoded["args"][0],
+            json!({ "name": "from", "type": "address", "indexed": true, "value": "0xd8da6bf26964af9d7eed9e03e53415d37aa96045" })
+        );
+        assert_eq!(decoded["args"][2]["value"], "1000000");
+    }
+
+    #[test]
+    fn logs_with_other_indexed_inputs_are_not_decoded() {
+        // An ERC-721 Transfer indexes the token id too, so it doesn't fit.
+        let mut nft = transfer_log();
+        nft["topics"].as_array_mut().unwrap().push(json!(
+            "0x0000000000000000000000000000000000000000000000000000000000000007"
+        ));
+        nft["data"] = json!("0x");
+        assert!(event_index().decode_log(&nft).is_none());
+    }
+}
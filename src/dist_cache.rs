@@ -0,0 +1,321 @@
+//! Memory cache for dist files served over `app://`. Contents are keyed by
+//! their SHA-256, so reloads, tab switches and every tab of a bundle are
+//! served from one in-memory copy instead of reading the file each time.
+//! The least recently served files are dropped past the byte budget.
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+/// Memory held by cached file contents across all tabs.
+const MAX_CACHE_BYTES: usize = 64 * 1024 * 1024;
+/// Larger files are read from disk each time rather than crowd out the rest.
+const MAX_ENTRY_BYTES: usize = 8 * 1024 * 1024;
+/// Paths whose hash is remembered; the map is cleared past this many.
+const MAX_TRACKED_FILES: usize = 4096;
+/// Large files are also hashed in chunks of this size, so a range can be
+/// checked without hashing the whole file again.
+const DIGEST_CHUNK_BYTES: u64 = 1024 * 1024;
+
+static SHARED: LazyLock<Arc<DistCache>> =
+    LazyLock::new(|| Arc::new(DistCache::new(MAX_CACHE_BYTES)));
+
+/// The cache every app webview serves from.
+pub fn shared() -> Arc<DistCache> {
+    Arc::clone(&SHARED)
+}
+
+struct Entry {
+    data: Vec<u8>,
+    last_used: u64,
+}
+
+/// What a file looked like when it was hashed. A file whose size and
+/// modification time still match is taken to have the same contents.
+#[derive(Clone, PartialEq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> std::io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        Ok(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// SHA-256 of a whole file and of each [`DIGEST_CHUNK_BYTES`] chunk of it,
+/// all taken from the same read.
+#[derive(Clone)]
+pub struct FileDigests {
+    pub sha256: String,
+    len: u64,
+    chunks: Vec<[u8; 32]>,
+}
+
+impl FileDigests {
+    /// Bytes `start..=end` of `path`, read from disk and checked against the
+    /// chunk digests. Returns `Ok(None)` when any chunk touched by the range
+    /// no longer matches, and no bytes past the end of the file.
+    pub fn read_range(
+        &self,
+        path: &Path,
+        start: u64,
+        end: u64,
+    ) -> std::io::Result<Option<Vec<u8>>> {
+        if start >= self.len || start > end {
+            return Ok(Some(Vec::new()));
+        }
+        let end = end.min(self.len - 1);
+        let first = start / DIGEST_CHUNK_BYTES;
+        let last = end / DIGEST_CHUNK_BYTES;
+        let mut file = fs::File::open(path)?;
+        file.seek(SeekFrom::Start(first * DIGEST_CHUNK_BYTES))?;
+        let mut data = Vec::new();
+        for index in first..=last {
+            let offset = index * DIGEST_CHUNK_BYTES;
+            let mut chunk = vec![0u8; DIGEST_CHUNK_BYTES.min(self.len - offset) as usize];
+            match file.read_exact(&mut chunk) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(err) => return Err(err),
+            }
+            if Sha256::digest(&chunk).as_slice() != self.chunks[index as usize] {
+                return Ok(None);
+            }
+            data.extend_from_slice(&chunk);
+        }
+        let skip = (start - first * DIGEST_CHUNK_BYTES) as usize;
+        let take = (end - start + 1) as usize;
+        Ok(Some(data[skip..skip + take].to_vec()))
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    files: HashMap<PathBuf, (FileStamp, FileDigests)>,
+    bytes: usize,
+    clock: u64,
+}
+
+pub struct DistCache {
+    max_bytes: usize,
+    inner: Mutex<Inner>,
+}
+
+impl DistCache {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// Contents with SHA-256 `hash`, if cached.
+    pub fn get(&self, hash: &str) -> Option<Vec<u8>> {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("poisoned dist cache lock while reading a cached file");
+        inner.clock += 1;
+        let clock = inner.clock;
+        let entry = inner.entries.get_mut(hash)?;
+        entry.last_used = clock;
+        Some(entry.data.clone())
+    }
+
+    /// Largest file kept in memory.
+    pub fn max_entry_bytes(&self) -> usize {
+        MAX_ENTRY_BYTES.min(self.max_bytes)
+    }
+
+    /// Caches `data`, whose SHA-256 the caller has checked is `hash`.
+    pub fn insert(&self, hash: &str, data: &[u8]) {
+        if data.len() > self.max_entry_bytes() {
+            return;
+        }
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("poisoned dist cache lock while caching a file");
+        if inner.entries.contains_key(hash) {
+            return;
+        }
+        while inner.bytes + data.len() > self.max_bytes {
+            let Some(oldest) = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(hash, _)| hash.clone())
+            else {
+                break;
+            };
+            if let Some(evicted) = inner.entries.remove(&oldest) {
+                inner.bytes -= evicted.data.len();
+                tracing::trace!(hash = %oldest, bytes = evicted.data.len(), "evicted dist asset");
+            }
+        }
+        inner.clock += 1;
+        let last_used = inner.clock;
+        inner.bytes += data.len();
+        inner.entries.insert(
+            hash.to_string(),
+            Entry {
+                data: data.to_vec(),
+                last_used,
+            },
+        );
+    }
+
+    /// Digests of a file, hashed in chunks so large media never sits in
+    /// memory whole. They are remembered until the file's size or
+    /// modification time changes; bytes served through
+    /// [`FileDigests::read_range`] are checked again regardless.
+    pub fn file_digests(&self, path: &Path) -> std::io::Result<FileDigests> {
+        let stamp = FileStamp::of(path)?;
+        if let Some(digests) = self.known_digests(path, &stamp) {
+            return Ok(digests);
+        }
+        let mut file = fs::File::open(path)?;
+        let mut whole = Sha256::new();
+        let mut chunks = Vec::new();
+        let mut len = 0u64;
+        let mut buf = vec![0u8; DIGEST_CHUNK_BYTES as usize];
+        loop {
+            let n = read_full(&mut file, &mut buf)?;
+            if n == 0 {
+                break;
+            }
+            whole.update(&buf[..n]);
+            chunks.push(Sha256::digest(&buf[..n]).into());
+            len += n as u64;
+        }
+        let digests = FileDigests {
+            sha256: hex::encode(whole.finalize()),
+            len,
+            chunks,
+        };
+        self.remember(path, stamp, digests.clone());
+        Ok(digests)
+    }
+
+    fn known_digests(&self, path: &Path, stamp: &FileStamp) -> Option<FileDigests> {
+        self.inner
+            .lock()
+            .expect("poisoned dist cache lock while reading known digests")
+            .files
+            .get(path)
+            .filter(|(known, _)| known == stamp)
+            .map(|(_, digests)| digests.clone())
+    }
+
+    fn remember(&self, path: &Path, stamp: FileStamp, digests: FileDigests) {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("poisoned dist cache lock while remembering file digests");
+        if inner.files.len() >= MAX_TRACKED_FILES {
+            inner.files.clear();
+        }
+        inner.files.insert(path.to_path_buf(), (stamp, digests));
+    }
+}
+
+/// Fills `buf` unless the file ends first; returns the bytes read.
+fn read_full(file: &mut fs::File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DIGEST_CHUNK_BYTES, DistCache};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn evicts_least_recently_served() {
+        let cache = DistCache::new(10);
+        cache.insert("a", b"aaaa");
+        cache.insert("b", b"bbbb");
+        assert!(cache.get("a").is_some());
+        // "b" was served longest ago, so it makes room.
+        cache.insert("c", b"cccc");
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.get("a").as_deref(), Some(&b"aaaa"[..]));
+        assert_eq!(cache.get("c").as_deref(), Some(&b"cccc"[..]));
+    }
+
+    const CHUNK: usize = DIGEST_CHUNK_BYTES as usize;
+
+    /// Writes two and a bit chunks of data to a file of its own under `name`.
+    fn video(name: &str) -> (PathBuf, Vec<u8>) {
+        let dir =
+            std::env::temp_dir().join(format!("vibefi-dist-digests-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("video.mp4");
+        let data: Vec<u8> = (0..CHUNK * 2 + 10).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&file, &data).unwrap();
+        (file, data)
+    }
+
+    fn remove(file: &Path) {
+        let _ = std::fs::remove_dir_all(file.parent().unwrap());
+    }
+
+    #[test]
+    fn digests_cover_the_whole_file() {
+        let (file, data) = video("whole");
+        let digests = DistCache::new(1024).file_digests(&file).unwrap();
+        assert_eq!(digests.sha256, crate::bundle::sha256_hex(&data));
+        remove(&file);
+    }
+
+    #[test]
+    fn ranges_across_chunks_are_served() {
+        let (file, data) = video("across");
+        let digests = DistCache::new(1024).file_digests(&file).unwrap();
+        let start = CHUNK as u64 - 2;
+        let range = digests.read_range(&file, start, start + 4).unwrap();
+        assert_eq!(range.as_deref(), Some(&data[CHUNK - 2..CHUNK + 3]));
+        remove(&file);
+    }
+
+    #[test]
+    fn ranges_past_the_end_stop_at_the_end() {
+        let (file, data) = video("tail");
+        let digests = DistCache::new(1024).file_digests(&file).unwrap();
+        let tail = digests
+            .read_range(&file, 2 * CHUNK as u64, u64::MAX - 1)
+            .unwrap();
+        assert_eq!(tail.as_deref(), Some(&data[2 * CHUNK..]));
+        remove(&file);
+    }
+
+    #[test]
+    fn ranges_over_changed_chunks_are_refused() {
+        let (file, mut data) = video("changed");
+        let digests = DistCache::new(1024).file_digests(&file).unwrap();
+        // Same size, different bytes: the stamp may still match, the chunk does not.
+        data[CHUNK + 1] ^= 0xff;
+        std::fs::write(&file, &data).unwrap();
+        let start = CHUNK as u64 - 2;
+        assert_eq!(digests.read_range(&file, start, start + 4).unwrap(), None);
+        assert!(digests.read_range(&file, 0, 3).unwrap().is_some());
+        remove(&file);
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::{Context, Result, anyhow, bail};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::bundle::normalize_sha256;
+
+const CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct StreamedFile {
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+fn partial_path(dest: &Path) -> Result<PathBuf> {
+    let name = dest
+        .file_name()
+        .ok_or_else(|| anyhow!("download destination has no file name"))?;
+    Ok(dest.with_file_name(format!(".{}.part", name.to_string_lossy())))
+}
+
+fn copy_to_file(
+    mut reader: impl Read,
+    file: &mut fs::File,
+    max_bytes: u64,
+) -> Result<StreamedFile> {
+    let mut hasher = Sha256::new();
+    let mut total = 0u64;
+    let mut buf = vec![0u8; CHUNK_BYTES];
+    loop {
+        let n = reader.read(&mut buf).context("read download body")?;
+        if n == 0 {
+            break;
+        }
+        total += n as u64;
+        if total > max_bytes {
+            bail!("download exceeds {max_bytes} bytes");
+        }
+        hasher.update(&buf[..n]);
+        file.write_all(&buf[..n]).context("write download chunk")?;
+    }
+    file.flush().context("flush download")?;
+    Ok(StreamedFile {
+        bytes: total,
+        sha256: hex::encode(hasher.finalize()),
+    })
+}
+
+/// Streams `reader` into `dest` through a sibling `.part` file, hashing as it
+/// goes. The file is renamed into place only once the size limit and the
+/// optional digest check pass, so a failed download never leaves a partial
+/// file at `dest`.
+pub fn stream_to_file(
+    reader: impl Read,
+    dest: &Path,
+    max_bytes: u64,
+    expected_sha256: Option<&str>,
+) -> Result<StreamedFile> {
+    let partial = partial_path(dest)?;
+    let result = (|| -> Result<StreamedFile> {
+        let mut file =
+            fs::File::create(&partial).with_context(|| format!("create {}", partial.display()))?;
+        let streamed = copy_to_file(reader, &mut file, max_bytes)?;
+        if let Some(expected) = expected_sha256.map(normalize_sha256) {
+            if streamed.sha256 != expected {
+                bail!(
+                    "sha256 mismatch for {}: expected {expected} got {}",
+                    dest.display(),
+                    streamed.sha256
+                );
+            }
+        }
+        drop(file);
+        fs::rename(&partial, dest).with_context(|| format!("move into {}", dest.display()))?;
+        Ok(streamed)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result
+}
+
+/// Reads at most `max_bytes` from `reader`, failing as soon as the limit is
+/// crossed instead of buffering an oversized body first.
+pub fn read_limited(reader: impl Read, max_bytes: u64) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader
+        .take(max_bytes + 1)
+        .read_to_end(&mut bytes)
+        .context("read response body")?;
+    if bytes.len() as u64 > max_bytes {
+        bail!("payload exceeds {max_bytes} bytes");
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_limited, stream_to_file};
+    use crate::bundle::sha256_hex;
+
+    const BODY: &[u8] = b"console.log('hi');";
+
+    fn download_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("vibefi-download-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn stream_to_file_enforces_the_size_limit() {
+        let dir = download_dir("size");
+        let dest = dir.join("app.js");
+        assert!(stream_to_file(BODY, &dest, 4, None).is_err());
+        assert!(!dest.exists());
+        assert!(!dir.join(".app.js.part").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stream_to_file_enforces_the_digest() {
+        let dir = download_dir("digest");
+        let dest = dir.join("app.js");
+        assert!(stream_to_file(BODY, &dest, 1024, Some(&sha256_hex(b"other"))).is_err());
+        assert!(!dest.exists());
+        assert!(!dir.join(".app.js.part").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stream_to_file_writes_matching_downloads() {
+        let dir = download_dir("ok");
+        let dest = dir.join("app.js");
+        let streamed = stream_to_file(BODY, &dest, 1024, Some(&sha256_hex(BODY))).unwrap();
+        assert_eq!(streamed.bytes, BODY.len() as u64);
+        assert_eq!(std::fs::read(&dest).unwrap(), BODY);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn read_limited_stops_at_the_limit() {
+        assert_eq!(read_limited(&b"abc"[..], 3).unwrap(), b"abc");
+        assert!(read_limited(&b"abcd"[..], 3).is_err());
+    }
+}
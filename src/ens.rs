@@ -0,0 +1,232 @@
+//! ENS names through the configured RPC: forward resolution for dapps and
+//! reverse records to label addresses in prompts and the audit log. Reverse
+//! names are only trusted when they resolve back to the same address.
+
+use alloy_primitives::{Address, B256, address, keccak256};
+use alloy_sol_types::{SolCall, sol};
+use anyhow::{Result, anyhow, bail};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::multicall::{ReadCall, read_many};
+use crate::state::AppState;
+
+sol! {
+    function resolver(bytes32 node) external view returns (address);
+    function addr(bytes32 node) external view returns (address);
+    function name(bytes32 node) external view returns (string);
+}
+
+/// The ENS registry, at the same address on every chain ENS is deployed to.
+const ENS_REGISTRY: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+/// Lookups, found or not, are reused for this long.
+const LOOKUP_TTL: Duration = Duration::from_secs(10 * 60);
+const MAX_CACHED_LOOKUPS: usize = 1024;
+
+/// Lookups keyed by chain and `name:<name>` or `addr:<address>`, with the
+/// time they were made. `None` records that nothing was found.
+static LOOKUPS: Mutex<Option<HashMap<(u64, String), (Instant, Option<String>)>>> = Mutex::new(None);
+
+/// Mainnet and the public testnets; elsewhere there is nothing to resolve.
+fn has_ens(chain_id: u64) -> bool {
+    matches!(chain_id, 1 | 11155111 | 17000)
+}
+
+/// ENS normalization for plain ASCII names: lowercased, with no empty
+/// labels. Full UTS-46 normalization is not implemented, so other names are
+/// refused rather than resolved to the wrong node.
+pub fn normalize(name: &str) -> Result<String> {
+    let name = name.trim().to_ascii_lowercase();
+    if !name.is_ascii() {
+        bail!("only ASCII ENS names are supported");
+    }
+    if name.is_empty() || name.split('.').any(str::is_empty) {
+        bail!("invalid ENS name: {name:?}");
+    }
+    Ok(name)
+}
+
+/// EIP-137 namehash of a normalized name.
+pub fn namehash(name: &str) -> B256 {
+    name.rsplit('.')
+        .filter(|label| !label.is_empty())
+        .fold(B256::ZERO, |node, label| {
+            let mut buf = [0u8; 64];
+            buf[..32].copy_from_slice(node.as_slice());
+            buf[32..].copy_from_slice(keccak256(label.as_bytes()).as_slice());
+            keccak256(buf)
+        })
+}
+
+/// The address `name` resolves to on the active chain.
+pub fn resolve_name(state: &AppState, name: &str) -> Result<Option<Address>> {
+    let name = normalize(name)?;
+    let found = cached(state, format!("name:{name}"), |state| {
+        let node = namehash(&name);
+        let Some(resolver) = resolver_of(state, node)? else {
+            return Ok(None);
+        };
+        let resolved = call(state, resolver, addrCall { node })?;
+        Ok((!resolved.is_zero()).then(|| format!("{resolved:#x}")))
+    })?;
+    found
+        .map(|address| address.parse().map_err(|_| anyhow!("bad cached address")))
+        .transpose()
+}
+
+/// The primary name of `address` on the active chain, if its reverse record
+/// resolves back to it.
+pub fn lookup_address(state: &AppState, address: Address) -> Result<Option<String>> {
+    cached(state, format!("addr:{address:#x}"), |state| {
+        let node = namehash(&format!("{}.addr.reverse", hex::encode(address)));
+        let Some(resolver) = resolver_of(state, node)? else {
+            return Ok(None);
+        };
+        let claimed = call(state, resolver, nameCall { node })?;
+        let Ok(claimed) = normalize(&claimed) else {
+            return Ok(None);
+        };
+        let verified = resolve_name(state, &claimed)? == Some(address);
+        if !verified {
+            tracing::debug!(address = %format!("{address:#x}"), name = %claimed, "reverse record does not resolve back");
+        }
+        Ok(verified.then_some(claimed))
+    })
+}
+
+/// Best-effort [`lookup_address`] for labels; failures are logged and
+/// treated as no name.
+pub fn label(state: &AppState, address: Address) -> Option<String> {
+    lookup_address(state, address).unwrap_or_else(|err| {
+        tracing::debug!(address = %format!("{address:#x}"), error = %format!("{err:#}"), "ens reverse lookup failed");
+        None
+    })
+}
+
+/// Looks up again what is cached for the active chain and past half its
+/// lifetime, so prompts find fresh labels; run by the background scheduler.
+pub fn refresh(state: &AppState) {
+    let chain_id = state.wallet.lock().expect("wallet").chain.chain_id;
+    let stale: Vec<String> = LOOKUPS
+        .lock()
+        .expect("ens lookups")
+        .as_mut()
+        .map(|lookups| {
+            let stale: Vec<String> = lookups
+                .iter()
+                .filter(|((chain, _), (fetched, _))| {
+                    *chain == chain_id && fetched.elapsed() > LOOKUP_TTL / 2
+                })
+                .map(|((_, key), _)| key.clone())
+                .collect();
+            for key in &stale {
+                lookups.remove(&(chain_id, key.clone()));
+            }
+            stale
+        })
+        .unwrap_or_default();
+    for key in &stale {
+        let result = if let Some(name) = key.strip_prefix("name:") {
+            resolve_name(state, name).map(drop)
+        } else if let Some(address) = key
+            .strip_prefix("addr:")
+            .and_then(|a| a.parse::<Address>().ok())
+        {
+            lookup_address(state, address).map(drop)
+        } else {
+            Ok(())
+        };
+        if let Err(err) = result {
+            tracing::debug!(key, error = %format!("{err:#}"), "ens refresh failed");
+        }
+    }
+    tracing::debug!(chain_id, refreshed = stale.len(), "ens lookups refreshed");
+}
+
+fn resolver_of(state: &AppState, node: B256) -> Result<Option<Address>> {
+    let resolver = call(state, ENS_REGISTRY, resolverCall { node })?;
+    Ok((!resolver.is_zero()).then_some(resolver))
+}
+
+fn call<C: SolCall>(state: &AppState, to: Address, call: C) -> Result<C::Return> {
+    let raw = read_many(
+        state,
+        &[ReadCall {
+            to,
+            data: call.abi_encode().into(),
+        }],
+    )
+    .pop()
+    .ok_or_else(|| anyhow!("no result for ENS call"))?
+    .map_err(|err| anyhow!(err))?;
+    C::abi_decode_returns(&raw).map_err(|err| anyhow!("invalid ENS response: {err}"))
+}
+
+/// Runs `lookup` unless a recent answer for `key` on the active chain is
+/// cached. Errors are not cached, so the next call tries again.
+fn cached(
+    state: &AppState,
+    key: String,
+    lookup: impl FnOnce(&AppState) -> Result<Option<String>>,
+) -> Result<Option<String>> {
+    let chain_id = state.wallet.lock().expect("wallet").chain.chain_id;
+    if !has_ens(chain_id) {
+        return Ok(None);
+    }
+    let key = (chain_id, key);
+    if let Some(found) = LOOKUPS
+        .lock()
+        .expect("ens lookups")
+        .as_ref()
+        .and_then(|lookups| lookups.get(&key))
+        .filter(|(fetched, _)| fetched.elapsed() < LOOKUP_TTL)
+        .map(|(_, found)| found.clone())
+    {
+        return Ok(found);
+    }
+    let found = lookup(state)?;
+    let mut lookups = LOOKUPS.lock().expect("ens lookups");
+    let lookups = lookups.get_or_insert_with(HashMap::new);
+    if lookups.len() >= MAX_CACHED_LOOKUPS {
+        lookups.retain(|_, (fetched, _)| fetched.elapsed() < LOOKUP_TTL);
+        if lookups.len() >= MAX_CACHED_LOOKUPS {
+            lookups.clear();
+        }
+    }
+    lookups.insert(key, (Instant::now(), found.clone()));
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{namehash, normalize};
+    use alloy_primitives::{B256, b256};
+
+    #[test]
+    fn hashes_names_per_eip137() {
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("eth"),
+            b256!("93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+        );
+    }
+
+    #[test]
+    fn normalizes_case_and_whitespace_before_hashing() {
+        assert_eq!(
+            namehash(&normalize(" Foo.ETH ").unwrap()),
+            b256!("de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+        );
+    }
+
+    #[test]
+    fn refuses_empty_labels() {
+        assert!(normalize("foo..eth").is_err());
+    }
+
+    #[test]
+    fn refuses_non_ascii_names() {
+        assert!(normalize("ｆoo.eth").is_err());
+    }
+}
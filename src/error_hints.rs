@@ -0,0 +1,212 @@
+//! Stable codes and user hints for failures people can act on. Matching is on
+//! the error message, since these errors come from nodes, wallets, devices
+//! and gateways that each word them their own way. Dapps get the result in
+//! the EIP-1193 error's `data`; the client's own pages show the hint.
+
+use serde_json::{Value, json};
+
+/// EIP-1193 provider error code for a request the user rejected.
+const USER_REJECTED_CODE: i64 = 4001;
+/// EIP-3326 error code for switching to a chain the wallet does not know.
+const UNRECOGNIZED_CHAIN_CODE: i64 = 4902;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorHint {
+    /// Machine-readable, e.g. `INSUFFICIENT_FUNDS`. Never changes once shipped.
+    pub code: &'static str,
+    pub hint: &'static str,
+    /// EIP-1193 error code when it should differ from the default.
+    pub rpc_code: Option<i64>,
+}
+
+/// Message fragments, lowercased, and what they mean. The first match wins.
+const RULES: &[(&[&str], ErrorHint)] = &[
+    (
+        &[
+            "apdu_code_conditions_not_satisfied",
+            "request rejected on the ledger",
+            "action cancelled by user",
+            "rejected on device",
+            "declined on device",
+        ],
+        ErrorHint {
+            code: "USER_REJECTED_ON_DEVICE",
+            hint: "The request was declined on the hardware wallet. If you did not decline it, check that the Ethereum app is open and blind signing is enabled, then try again.",
+            rpc_code: Some(USER_REJECTED_CODE),
+        },
+    ),
+    (
+        &["user rejected the request"],
+        ErrorHint {
+            code: "USER_REJECTED",
+            hint: "The request was declined in VibeFi.",
+            rpc_code: Some(USER_REJECTED_CODE),
+        },
+    ),
+    (
+        &[
+            "apdu_code_invalid_data",
+            "apdu_code_command_not_allowed",
+            "apdu_code_ins_not_supported",
+            "enable blind signing",
+        ],
+        ErrorHint {
+            code: "BLIND_SIGNING_REQUIRED",
+            hint: "On Ledger, open the Ethereum app and enable 'Blind signing' in Settings, then approve the transaction on device.",
+            rpc_code: None,
+        },
+    ),
+    (
+        &["insufficient funds"],
+        ErrorHint {
+            code: "INSUFFICIENT_FUNDS",
+            hint: "The account cannot pay for the value plus gas. Add funds on this network or lower the amount.",
+            rpc_code: None,
+        },
+    ),
+    (
+        &["nonce too low", "nonce has already been used"],
+        ErrorHint {
+            code: "NONCE_TOO_LOW",
+            hint: "Another transaction from this account was confirmed first. Wait for pending transactions to settle, then send again.",
+            rpc_code: None,
+        },
+    ),
+    (
+        &["unrecognized chain id"],
+        ErrorHint {
+            code: "UNRECOGNIZED_CHAIN",
+            hint: "The client does not know this network. The dapp can add it with wallet_addEthereumChain, which asks you first.",
+            rpc_code: Some(UNRECOGNIZED_CHAIN_CODE),
+        },
+    ),
+    (
+        &[
+            "invalid chain id",
+            "invalid chainid",
+            "chain id mismatch",
+            "chainid mismatch",
+            "unrecognized chain",
+            "does not support chain",
+        ],
+        ErrorHint {
+            code: "INVALID_CHAIN",
+            hint: "The wallet and the request are on different networks. Switch to the network the dapp expects and try again.",
+            rpc_code: None,
+        },
+    ),
+    (
+        &["gateway timeout", "gateway time-out", "gateway timed out"],
+        ErrorHint {
+            code: "GATEWAY_TIMEOUT",
+            hint: "A gateway took too long to answer. Try again, or pick other gateways in Settings \u{2192} IPFS Retrieval.",
+            rpc_code: None,
+        },
+    ),
+    (
+        &["held for export"],
+        ErrorHint {
+            code: "TRANSACTION_HELD",
+            hint: "Signing without broadcasting is on. The transaction waits in Settings \u{2192} Signed transactions until it is exported and sent from a connected machine.",
+            rpc_code: None,
+        },
+    ),
+];
+
+pub fn classify(message: &str) -> Option<ErrorHint> {
+    let message = message.to_lowercase();
+    RULES
+        .iter()
+        .find(|(needles, _)| needles.iter().any(|needle| message.contains(needle)))
+        .map(|(_, hint)| *hint)
+}
+
+/// `data` of the EIP-1193 error for `message`, when it has a hint.
+pub fn error_data(message: &str) -> Option<Value> {
+    classify(message).map(|hint| json!({ "code": hint.code, "hint": hint.hint }))
+}
+
+/// `message` with its hint appended, for surfaces that only show text.
+pub fn with_hint(message: &str) -> String {
+    match classify(message) {
+        Some(hint) => format!("{message}\nHint: {}", hint.hint),
+        None => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, with_hint};
+
+    fn code(message: &str) -> Option<&'static str> {
+        classify(message).map(|hint| hint.code)
+    }
+
+    fn rpc_code(message: &str) -> Option<i64> {
+        classify(message).and_then(|hint| hint.rpc_code)
+    }
+
+    #[test]
+    fn maps_transaction_failures() {
+        assert_eq!(
+            code("rpc error: insufficient funds for gas * price + value"),
+            Some("INSUFFICIENT_FUNDS")
+        );
+        assert_eq!(
+            code("nonce too low: next nonce 7, tx nonce 5"),
+            Some("NONCE_TOO_LOW")
+        );
+        assert_eq!(
+            code("transaction 0xab was signed and held for export; it was not broadcast"),
+            Some("TRANSACTION_HELD")
+        );
+    }
+
+    #[test]
+    fn maps_chain_failures() {
+        assert_eq!(
+            code("Swap does not support chain 10; its manifest declares 1"),
+            Some("INVALID_CHAIN")
+        );
+        assert_eq!(rpc_code("Unrecognized chain ID 0x2105."), Some(4902));
+    }
+
+    #[test]
+    fn maps_hardware_wallet_failures() {
+        assert_eq!(
+            code("Ledger error: APDU_CODE_CONDITIONS_NOT_SATISFIED"),
+            Some("USER_REJECTED_ON_DEVICE")
+        );
+        assert_eq!(rpc_code("Request rejected on the Ledger"), Some(4001));
+        assert_eq!(
+            code("Enable blind signing in the Ethereum app settings"),
+            Some("BLIND_SIGNING_REQUIRED")
+        );
+    }
+
+    #[test]
+    fn maps_gateway_timeouts() {
+        assert_eq!(
+            code(
+                "no gateway served block bafkrei… (https://ipfs.io: gateway timed out after 15000ms)"
+            ),
+            Some("GATEWAY_TIMEOUT")
+        );
+    }
+
+    #[test]
+    fn maps_user_rejections() {
+        assert_eq!(rpc_code("User rejected the request"), Some(4001));
+    }
+
+    #[test]
+    fn leaves_unknown_failures_alone() {
+        assert_eq!(code("execution reverted"), None);
+        assert_eq!(with_hint("execution reverted"), "execution reverted");
+    }
+
+    #[test]
+    fn appends_hints_to_known_failures() {
+        assert!(with_hint("insufficient funds").contains("\nHint: "));
+    }
+}
@@ -1,15 +1,28 @@
 use serde::Deserialize;
 use std::path::PathBuf;
 use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
 use std::{fs, path::Path};
 use tao::event_loop::EventLoopProxy;
 
+use crate::commands::{PALETTE_WEBVIEW_ID, PaletteAction};
 use crate::ipc;
 use crate::ipc_contract::{IpcRequest, KnownProviderId, TabbarMethod};
+use crate::menu::{AppMenu, MenuCommand};
+use crate::prompt::{PROMPT_EVENT, PROMPT_WEBVIEW_ID, PendingPrompt, PromptAnswer, PromptRequest};
+use crate::settings::{load_settings, save_settings};
 use crate::state::lock_or_err;
-use crate::state::{AppRuntimeCapabilities, AppState, IpfsCapabilityRule, TabAction, UserEvent};
+use crate::state::{
+    AppRuntimeCapabilities, AppState, DEFAULT_FILE_MAX_BYTES, DappIdentity, FileCapability,
+    IpfsCapabilityRule, MAX_FILE_BYTES, PendingConnect, TabAction, TabBadges, UserEvent,
+    WalletBackend,
+};
+use crate::subscriptions::Topic;
 use crate::ui_bridge;
-use crate::webview::{EmbeddedContent, WebViewHost, build_app_webview};
+use crate::webview::{
+    EmbeddedContent, TabSnapshot, WebViewHost, WebViewSource, build_app_webview,
+    restore_app_webview,
+};
 use crate::webview_manager::{AppWebViewEntry, AppWebViewKind, WebViewManager};
 
 fn lock_or_log<'a, T>(mutex: &'a Mutex<T>, name: &str) -> Option<MutexGuard<'a, T>> {
@@ -23,15 +36,72 @@ fn lock_or_log<'a, T>(mutex: &'a Mutex<T>, name: &str) -> Option<MutexGuard<'a,
 }
 
 #[derive(Debug, Deserialize)]
-struct BundleManifest {
+struct BundleCapabilities {
+    #[serde(default)]
+    ipfs: Option<BundleIpfsCapabilities>,
+    #[serde(default)]
+    network: Option<BundleNetworkCapabilities>,
+    #[serde(default)]
+    clipboard: Option<BundleClipboardCapabilities>,
+    #[serde(default)]
+    qr: Option<BundleQrCapabilities>,
     #[serde(default)]
-    capabilities: Option<BundleCapabilities>,
+    events: Option<BundleEventCapabilities>,
+    #[serde(default)]
+    notifications: Option<BundleNotificationCapabilities>,
+    #[serde(default)]
+    files: Option<BundleFileCapabilities>,
+    #[serde(default)]
+    messaging: Option<BundleMessagingCapabilities>,
 }
 
 #[derive(Debug, Deserialize)]
-struct BundleCapabilities {
+struct BundleMessagingCapabilities {
     #[serde(default)]
-    ipfs: Option<BundleIpfsCapabilities>,
+    peers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleFileCapabilities {
+    #[serde(default)]
+    open: bool,
+    #[serde(default)]
+    save: bool,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    max_bytes: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BundleNotificationCapabilities {
+    #[serde(default)]
+    send: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BundleEventCapabilities {
+    #[serde(default)]
+    subscribe: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BundleQrCapabilities {
+    #[serde(default)]
+    scan: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BundleClipboardCapabilities {
+    #[serde(default)]
+    write: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BundleNetworkCapabilities {
+    #[serde(default)]
+    allow: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -57,18 +127,93 @@ pub(crate) fn load_app_capabilities_from_dist(dist_dir: &Path) -> AppRuntimeCapa
     let Some(bundle_root) = dist_dir.parent().and_then(|p| p.parent()) else {
         return AppRuntimeCapabilities::default();
     };
-    let manifest_path = bundle_root.join("manifest.json");
-    let raw = match fs::read_to_string(&manifest_path) {
-        Ok(raw) => raw,
-        Err(_) => return AppRuntimeCapabilities::default(),
+    let Ok(manifest) = crate::manifest::load(bundle_root) else {
+        return AppRuntimeCapabilities::default();
     };
-    let parsed: BundleManifest = match serde_json::from_str(&raw) {
-        Ok(parsed) => parsed,
-        Err(_) => return AppRuntimeCapabilities::default(),
+    let chains = manifest.chains;
+    let capabilities: Option<BundleCapabilities> = match manifest.capabilities {
+        Some(raw) => match serde_json::from_value(raw) {
+            Ok(capabilities) => Some(capabilities),
+            Err(_) => {
+                return AppRuntimeCapabilities {
+                    chains,
+                    ..AppRuntimeCapabilities::default()
+                };
+            }
+        },
+        None => None,
     };
-
-    let rules = parsed
-        .capabilities
+    let network_allow = capabilities
+        .as_ref()
+        .and_then(|caps| caps.network.as_ref())
+        .map(|network| {
+            network
+                .allow
+                .iter()
+                .filter_map(|origin| crate::ipc::normalize_network_origin(origin))
+                .collect()
+        })
+        .unwrap_or_default();
+    let clipboard_write = capabilities
+        .as_ref()
+        .and_then(|caps| caps.clipboard.as_ref())
+        .is_some_and(|clipboard| clipboard.write);
+    let qr_scan = capabilities
+        .as_ref()
+        .and_then(|caps| caps.qr.as_ref())
+        .is_some_and(|qr| qr.scan);
+    let notifications = capabilities
+        .as_ref()
+        .and_then(|caps| caps.notifications.as_ref())
+        .is_some_and(|notifications| notifications.send);
+    let files = capabilities
+        .as_ref()
+        .and_then(|caps| caps.files.as_ref())
+        .map(|files| FileCapability {
+            open: files.open,
+            save: files.save,
+            extensions: files
+                .extensions
+                .iter()
+                .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            max_bytes: files
+                .max_bytes
+                .unwrap_or(DEFAULT_FILE_MAX_BYTES)
+                .min(MAX_FILE_BYTES),
+        })
+        .unwrap_or_default();
+    let message_peers = capabilities
+        .as_ref()
+        .and_then(|caps| caps.messaging.as_ref())
+        .map(|messaging| {
+            messaging
+                .peers
+                .iter()
+                .map(|peer| peer.trim().to_string())
+                .filter(|peer| !peer.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let event_topics = capabilities
+        .as_ref()
+        .and_then(|caps| caps.events.as_ref())
+        .map(|events| {
+            events
+                .subscribe
+                .iter()
+                .filter_map(|topic| {
+                    let parsed = Topic::parse(topic);
+                    if parsed.is_none() {
+                        tracing::warn!(topic, "ignoring unknown event topic in manifest");
+                    }
+                    parsed
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let rules = capabilities
         .and_then(|caps| caps.ipfs)
         .map(|ipfs| ipfs.allow)
         .unwrap_or_default()
@@ -94,7 +239,17 @@ pub(crate) fn load_app_capabilities_from_dist(dist_dir: &Path) -> AppRuntimeCapa
         })
         .collect();
 
-    AppRuntimeCapabilities { ipfs_allow: rules }
+    AppRuntimeCapabilities {
+        ipfs_allow: rules,
+        network_allow,
+        clipboard_write,
+        qr_scan,
+        notifications,
+        event_topics,
+        files,
+        message_peers,
+        chains,
+    }
 }
 
 pub fn handle_ipc_event(
@@ -107,6 +262,7 @@ pub fn handle_ipc_event(
         // Parse tab bar IPC
         if let Ok(req) = serde_json::from_str::<IpcRequest>(&msg) {
             if req.provider() == Some(KnownProviderId::Tabbar) {
+                state.lock.touch();
                 match req.tabbar_method() {
                     Some(TabbarMethod::SwitchTab) => {
                         if let Some(idx) = req.params.get(0).and_then(|v| v.as_u64()) {
@@ -115,46 +271,27 @@ pub fn handle_ipc_event(
                     }
                     Some(TabbarMethod::CloseTab) => {
                         if let Some(idx) = req.params.get(0).and_then(|v| v.as_u64()) {
-                            let idx = idx as usize;
-                            if let Some(entry) = manager.apps.get(idx) {
-                                if !entry.kind.is_closeable() {
-                                    tracing::debug!(
-                                        index = idx,
-                                        kind = ?entry.kind,
-                                        "ignoring close request for non-closeable tab"
-                                    );
-                                    return;
-                                }
-                                {
-                                    if let Some(mut caps) =
-                                        lock_or_log(&state.app_capabilities, "app_capabilities")
-                                    {
-                                        caps.remove(&entry.id);
-                                    }
-                                }
-                                if entry.kind == AppWebViewKind::Settings {
-                                    if let Some(mut sel) = lock_or_log(
-                                        &state.settings_webview_id,
-                                        "settings_webview_id",
-                                    ) {
-                                        *sel = None;
-                                    }
-                                } else if entry.kind == AppWebViewKind::WalletSelector {
-                                    if let Some(mut sel) = lock_or_log(
-                                        &state.selector_webview_id,
-                                        "selector_webview_id",
-                                    ) {
-                                        *sel = None;
-                                    }
-                                }
-                            }
-                            manager.close_app(idx);
+                            close_tab(state, manager, idx as usize);
+                        }
+                    }
+                    Some(TabbarMethod::SwitchWallet) => {
+                        if let Some(idx) = req.params.get(0).and_then(|v| v.as_u64()) {
+                            request_tab_wallet(state, manager, idx as usize, true);
+                        }
+                    }
+                    Some(TabbarMethod::AddWallet) => {
+                        if let Some(idx) = req.params.get(0).and_then(|v| v.as_u64()) {
+                            request_tab_wallet(state, manager, idx as usize, false);
                         }
                     }
                     None => {}
                 }
             }
         }
+    } else if webview_id == PROMPT_WEBVIEW_ID {
+        handle_prompt_ipc(state, manager, msg);
+    } else if webview_id == PALETTE_WEBVIEW_ID {
+        handle_palette_ipc(state, manager, msg);
     } else if let Some(wv) = manager.webview_for_id(webview_id) {
         if let Err(e) = ipc::handle_ipc(wv, manager, state, webview_id, msg) {
             tracing::error!(error = ?e, webview_id, "ipc error");
@@ -162,6 +299,171 @@ pub fn handle_ipc_event(
     }
 }
 
+/// Reopens the selector on the tab's behalf; the accounts of whichever backend
+/// the user picks are added to the tab. With `replace` the tab's current
+/// accounts are dropped first.
+fn close_tab(state: &AppState, manager: &mut WebViewManager, idx: usize) {
+    if let Some(entry) = manager.apps.get(idx) {
+        if !entry.kind.is_closeable() {
+            tracing::debug!(
+                index = idx,
+                kind = ?entry.kind,
+                "ignoring close request for non-closeable tab"
+            );
+            return;
+        }
+        {
+            if let Some(mut caps) = lock_or_log(&state.app_capabilities, "app_capabilities") {
+                caps.remove(&entry.id);
+            }
+        }
+        state.unbind_tab_wallet(&entry.id);
+        if let Some(mut subs) = lock_or_log(&state.subscriptions, "subscriptions") {
+            subs.remove_webview(&entry.id);
+        }
+        crate::notifications::forget_webview(&entry.id);
+        crate::decoder::forget_webview(&entry.id);
+        if entry.kind == AppWebViewKind::Settings {
+            if let Some(mut sel) = lock_or_log(&state.settings_webview_id, "settings_webview_id") {
+                *sel = None;
+            }
+        } else if entry.kind == AppWebViewKind::WalletSelector {
+            if let Some(mut sel) = lock_or_log(&state.selector_webview_id, "selector_webview_id") {
+                *sel = None;
+            }
+        }
+    }
+    manager.close_app(idx);
+}
+
+pub fn handle_menu_command(
+    state: &AppState,
+    manager: &mut WebViewManager,
+    menu: &AppMenu,
+    command: MenuCommand,
+) {
+    tracing::debug!(?command, "menu command");
+    let active = manager.active_app_index;
+    let launcher = manager.index_of_kind(AppWebViewKind::Launcher);
+    match command {
+        // Dapps open from the launcher, so a new tab starts there.
+        MenuCommand::NewTab | MenuCommand::ShowLauncher => {
+            if let Some(idx) = launcher {
+                manager.switch_to(idx);
+            }
+        }
+        MenuCommand::CloseTab => {
+            if let Some(idx) = active {
+                close_tab(state, manager, idx);
+            }
+        }
+        MenuCommand::OpenRecent(index) => {
+            if let Some(identity) = menu.recent(index).cloned() {
+                open_or_launch(state, manager, identity);
+            }
+        }
+        MenuCommand::CommandPalette => {
+            let _ = state.proxy.send_event(UserEvent::OpenPalette);
+        }
+        MenuCommand::OpenSettings => {
+            let _ = state.proxy.send_event(UserEvent::OpenSettings);
+        }
+        MenuCommand::ConnectWallet | MenuCommand::SwitchAccount => {
+            if let Some(idx) = active {
+                request_tab_wallet(state, manager, idx, command == MenuCommand::SwitchAccount);
+            }
+        }
+        MenuCommand::ToggleDevtools => {
+            #[cfg(debug_assertions)]
+            if let Some(webview) = manager.active_app_webview() {
+                if webview.is_devtools_open() {
+                    webview.close_devtools();
+                } else {
+                    webview.open_devtools();
+                }
+            }
+            #[cfg(not(debug_assertions))]
+            tracing::info!("developer tools are only available in debug builds");
+        }
+        MenuCommand::OpenLogs => {
+            let log_dir = crate::runtime_paths::resolve_log_dir();
+            let opened = fs::create_dir_all(&log_dir)
+                .map_err(anyhow::Error::from)
+                .and_then(|()| crate::ipc::open_directory_in_file_manager(&log_dir));
+            if let Err(err) = opened {
+                tracing::warn!(error = %format!("{err:#}"), "failed to open log directory");
+            }
+        }
+    }
+}
+
+/// Switches to the tab running `identity`, or launches it from the registry
+/// when no tab does.
+fn open_or_launch(state: &AppState, manager: &mut WebViewManager, identity: DappIdentity) {
+    let Some(root_cid) = identity.root_cid.clone() else {
+        return;
+    };
+    let open = manager.apps.iter().position(|entry| {
+        entry
+            .identity
+            .as_ref()
+            .is_some_and(|id| id.root_cid.as_deref() == Some(root_cid.as_str()))
+    });
+    if let Some(idx) = open {
+        manager.switch_to(idx);
+        return;
+    }
+    // Launch progress shows in the launcher, like a launch from there.
+    let launcher = manager.index_of_kind(AppWebViewKind::Launcher);
+    let progress_id = launcher
+        .map(|idx| manager.apps[idx].id.clone())
+        .unwrap_or_default();
+    if let Some(idx) = launcher {
+        manager.switch_to(idx);
+    }
+    let state = state.clone();
+    std::thread::spawn(move || {
+        if let Err(err) =
+            crate::registry::launch_dapp(&state, &progress_id, &root_cid, &identity.name)
+        {
+            tracing::warn!(root_cid, error = %format!("{err:#}"), "failed to launch dapp");
+        }
+    });
+}
+
+fn request_tab_wallet(state: &AppState, manager: &mut WebViewManager, index: usize, replace: bool) {
+    let Some(entry) = manager.apps.get(index) else {
+        return;
+    };
+    if !entry.kind.binds_wallet() {
+        tracing::debug!(index, kind = ?entry.kind, "ignoring wallet switch for non-dapp tab");
+        return;
+    }
+    let webview_id = entry.id.clone();
+    if !replace {
+        tracing::info!(webview_id, "tab wallet add requested");
+    } else if let Some(previous) = state.unbind_tab_wallet(&webview_id) {
+        tracing::info!(
+            webview_id,
+            accounts = previous.accounts.len(),
+            "tab wallet switch requested"
+        );
+        if let Some(wv) = manager.webview_for_id(&webview_id) {
+            ipc::emit_accounts_changed(wv, Vec::new());
+        }
+    }
+    if let Some(mut pending) = lock_or_log(&state.pending_connect, "pending_connect") {
+        if !pending.iter().any(|pc| pc.webview_id == webview_id) {
+            pending.push_back(PendingConnect {
+                webview_id,
+                ipc_id: None,
+            });
+        }
+    }
+    manager.update_tab_bar();
+    let _ = state.proxy.send_event(UserEvent::OpenWalletSelector);
+}
+
 pub fn handle_open_wallet_selector(
     host: Option<&WebViewHost>,
     state: &AppState,
@@ -191,6 +493,7 @@ pub fn handle_open_wallet_selector(
             EmbeddedContent::WalletSelector,
             AppWebViewKind::WalletSelector,
             "Connect Wallet".to_string(),
+            None,
         ) {
             Ok(id) => {
                 if let Some(mut sel) =
@@ -221,6 +524,23 @@ pub fn handle_walletconnect_pairing(
     }
 }
 
+pub fn handle_walletconnect_relay_status(
+    state: &AppState,
+    manager: &mut WebViewManager,
+    status: crate::walletconnect::RelayStatus,
+) {
+    let sel_id = lock_or_log(&state.selector_webview_id, "selector_webview_id")
+        .map(|id| id.clone())
+        .flatten();
+    if let Some(sel_id) = sel_id {
+        if let Some(wv) = manager.webview_for_id(&sel_id) {
+            ui_bridge::emit_walletconnect_relay(wv, &status);
+        }
+    }
+    manager.walletconnect_relay = Some(status);
+    manager.update_tab_bar();
+}
+
 pub fn handle_walletconnect_result(
     state: &AppState,
     manager: &mut WebViewManager,
@@ -236,30 +556,83 @@ pub fn handle_walletconnect_result(
         ipc::handle_walletconnect_connect_result(wv, state, ipc_id, result.clone());
     }
 
-    // If there is a pending eth_requestAccounts from a dapp,
-    // resolve it now that the wallet is connected.
-    if let Ok(ref session) = result {
-        let pending: Vec<_> = match lock_or_log(&state.pending_connect, "pending_connect") {
-            Some(mut guard) => guard.drain(..).collect(),
-            None => Vec::new(),
-        };
-        for pc in pending {
-            if pc.webview_id == webview_id && pc.ipc_id == ipc_id {
-                continue;
-            }
-            if let Some(dapp_wv) = manager.webview_for_id(&pc.webview_id) {
-                let accounts: Vec<serde_json::Value> = session
-                    .accounts
-                    .iter()
-                    .map(|a| serde_json::Value::String(a.clone()))
-                    .collect();
-                let _ = ipc::respond_ok(dapp_wv, pc.ipc_id, serde_json::Value::Array(accounts));
+    // Bind the requesting dapp tab (not the selector) and any tabs still
+    // waiting on the selector to the WalletConnect session.
+    if let Ok(session) = result {
+        let is_dapp_tab = manager
+            .app_kind_for_id(&webview_id)
+            .is_some_and(|kind| kind.binds_wallet());
+        if is_dapp_tab {
+            state.bind_tab_wallet(&webview_id, WalletBackend::WalletConnect, &session.accounts);
+        }
+        if let Some(mut pending) = lock_or_log(&state.pending_connect, "pending_connect") {
+            pending.retain(|pc| !(pc.webview_id == webview_id && pc.ipc_id == Some(ipc_id)));
+        }
+        handle_wallet_connected(
+            state,
+            manager,
+            WalletBackend::WalletConnect,
+            session.accounts,
+        );
+    }
+}
+
+/// Adds `backend`'s accounts to every tab waiting in `pending_connect`,
+/// answering parked `eth_requestAccounts` calls with the tab's full account
+/// list. Tabs already using `backend` follow its new accounts.
+pub fn handle_wallet_connected(
+    state: &AppState,
+    manager: &mut WebViewManager,
+    backend: WalletBackend,
+    accounts: Vec<String>,
+) {
+    if accounts.is_empty() {
+        tracing::warn!(?backend, "wallet connected without accounts");
+        return;
+    }
+    let pending: Vec<_> = match lock_or_log(&state.pending_connect, "pending_connect") {
+        Some(mut guard) => guard.drain(..).collect(),
+        None => Vec::new(),
+    };
+    let mut notify: Vec<String> = Vec::new();
+    if let Some(mut bindings) = lock_or_log(&state.tab_wallets, "tab_wallets") {
+        for (webview_id, binding) in bindings.iter_mut() {
+            if binding.has_backend(backend) && binding.set_backend_accounts(backend, &accounts) {
+                notify.push(webview_id.clone());
             }
         }
     }
+    for pc in &pending {
+        if state.bind_tab_wallet(&pc.webview_id, backend, &accounts)
+            && !notify.contains(&pc.webview_id)
+        {
+            notify.push(pc.webview_id.clone());
+        }
+    }
+    for webview_id in &notify {
+        if let Some(wv) = manager.webview_for_id(webview_id) {
+            ipc::emit_accounts_changed(wv, state.accounts_for(webview_id));
+        }
+    }
+    for pc in pending {
+        let (Some(ipc_id), Some(wv)) = (pc.ipc_id, manager.webview_for_id(&pc.webview_id)) else {
+            continue;
+        };
+        let value = serde_json::Value::Array(
+            state
+                .accounts_for(&pc.webview_id)
+                .into_iter()
+                .map(serde_json::Value::String)
+                .collect(),
+        );
+        if let Err(err) = ipc::respond_ok(wv, ipc_id, value) {
+            tracing::warn!(error = %err, webview_id = %pc.webview_id, "failed to resolve pending connect");
+        }
+    }
+    manager.update_tab_bar();
 }
 
-pub fn handle_hardware_sign_result(
+pub fn handle_signer_result(
     manager: &WebViewManager,
     webview_id: String,
     ipc_id: u64,
@@ -270,9 +643,9 @@ pub fn handle_hardware_sign_result(
         let mapped = result.map(serde_json::Value::String);
         if let Err(e) = ipc::respond_value_result(wv, ipc_id, mapped) {
             if is_ok {
-                tracing::error!(error = %e, "hardware: failed to send ok response");
+                tracing::error!(error = %e, "signer: failed to send ok response");
             } else {
-                tracing::error!(error = %e, "hardware: failed to send error response");
+                tracing::error!(error = %e, "signer: failed to send error response");
             }
         }
     }
@@ -308,6 +681,7 @@ pub fn handle_open_settings(
             EmbeddedContent::Settings,
             AppWebViewKind::Settings,
             "Settings".to_string(),
+            None,
         ) {
             Ok(id) => {
                 if let Some(mut sel) =
@@ -321,14 +695,6 @@ pub fn handle_open_settings(
     }
 }
 
-pub fn handle_rpc_pending_changed(manager: &WebViewManager, webview_id: &str, count: u32) {
-    if let Some(tb) = manager.tab_bar.as_ref() {
-        if let Err(err) = ui_bridge::update_rpc_status(tb, webview_id, count) {
-            tracing::warn!(error = %err, "failed to dispatch rpc status update");
-        }
-    }
-}
-
 pub fn handle_rpc_result(
     manager: &WebViewManager,
     webview_id: String,
@@ -356,6 +722,37 @@ pub fn handle_provider_event(
     if let Some(wv) = manager.webview_for_id(&webview_id) {
         ui_bridge::emit_provider_event(wv, &event, value);
     }
+    if event == "accountsChanged" {
+        // The tab's wallet binding moved; refresh its tab bar badge.
+        manager.update_tab_bar();
+    }
+}
+
+/// Tells dapp tabs the provider disconnected or reconnected (EIP-1193
+/// `disconnect` / `connect`).
+pub fn handle_connectivity_changed(state: &AppState, manager: &WebViewManager, online: bool) {
+    let (event, value) = if online {
+        (
+            "connect",
+            serde_json::json!({ "chainId": state.chain_id_hex() }),
+        )
+    } else {
+        (
+            "disconnect",
+            serde_json::json!({
+                "code": crate::connectivity::DISCONNECTED_CODE,
+                "message": crate::connectivity::OFFLINE_ERROR,
+            }),
+        )
+    };
+    for entry in &manager.apps {
+        if !entry.kind.binds_wallet() {
+            continue;
+        }
+        if let Some(webview) = entry.webview.as_ref() {
+            ui_bridge::emit_provider_event(webview, event, value.clone());
+        }
+    }
 }
 
 pub fn handle_close_wallet_selector(state: &AppState, manager: &mut WebViewManager) {
@@ -365,6 +762,326 @@ pub fn handle_close_wallet_selector(state: &AppState, manager: &mut WebViewManag
     manager.close_by_kind(AppWebViewKind::WalletSelector);
 }
 
+/// Queues a prompt for the overlay, building the overlay if none is open.
+/// A decision the user asked to remember is answered without showing it.
+pub fn handle_show_prompt(
+    host: Option<&WebViewHost>,
+    state: &AppState,
+    manager: &mut WebViewManager,
+    proxy: &EventLoopProxy<UserEvent>,
+    mut request: PromptRequest,
+    reply: std::sync::mpsc::Sender<bool>,
+) {
+    if let Some(webview_id) = &request.webview_id {
+        request.requester = Some(manager.dapp_identity_for(webview_id));
+    }
+    let remember_key = request.remember_key();
+    request.rememberable = remember_key.is_some();
+    let config_path = state
+        .resolved
+        .as_ref()
+        .and_then(|resolved| resolved.config_path.as_deref());
+    let remembered = remember_key.as_ref().and_then(|key| {
+        config_path.and_then(|path| load_settings(path).remembered_prompts.get(key).copied())
+    });
+    if let Some(approved) = remembered {
+        tracing::info!(
+            target: "vibefi::audit",
+            id = request.id,
+            kind = %request.kind,
+            requester = %prompt_requester(&request),
+            approved,
+            "prompt answered from remembered choice"
+        );
+        let _ = reply.send(approved);
+        return;
+    }
+    if let Some(rule) = crate::policy::approving_rule(state, &request) {
+        tracing::info!(
+            target: "vibefi::audit",
+            id = request.id,
+            kind = %request.kind,
+            requester = %prompt_requester(&request),
+            chain_id = rule.chain_id,
+            max_value = %rule.max_value,
+            "prompt approved by approval rule"
+        );
+        let _ = reply.send(true);
+        return;
+    }
+
+    tracing::info!(id = request.id, kind = %request.kind, "prompt queued");
+    manager.close_palette();
+    // The lock screen goes in front of whatever was waiting when the client locked.
+    if request.kind == crate::lock::UNLOCK_PROMPT {
+        manager.prompts.push_front(PendingPrompt { request, reply });
+    } else {
+        manager.prompts.push_back(PendingPrompt { request, reply });
+    }
+    if manager.prompt.is_some() {
+        emit_current_prompt(manager);
+        return;
+    }
+    let Some(host) = host else {
+        tracing::warn!("no window for the prompt overlay; denying queued prompts");
+        deny_pending_prompts(manager);
+        return;
+    };
+    let size = host.window.inner_size();
+    let bounds = manager.app_rect(size.width, size.height);
+    match build_app_webview(
+        host,
+        PROMPT_WEBVIEW_ID,
+        None,
+        EmbeddedContent::Prompt,
+        state,
+        proxy.clone(),
+        bounds,
+    ) {
+        Ok(webview) => manager.show_prompt(webview),
+        Err(err) => {
+            tracing::error!(error = ?err, "failed to build prompt overlay");
+            deny_pending_prompts(manager);
+        }
+    }
+}
+
+fn deny_pending_prompts(manager: &mut WebViewManager) {
+    for pending in manager.prompts.drain(..) {
+        let _ = pending.reply.send(false);
+    }
+}
+
+fn prompt_requester(request: &PromptRequest) -> String {
+    match &request.requester {
+        Some(identity) => identity.to_string(),
+        None => "VibeFi".to_string(),
+    }
+}
+
+/// The prompt at the head of the queue, with the number waiting behind it.
+fn current_prompt(manager: &WebViewManager) -> serde_json::Value {
+    let Some(pending) = manager.prompts.front() else {
+        return serde_json::Value::Null;
+    };
+    let mut request = pending.request.clone();
+    request.queued = manager.prompts.len() - 1;
+    serde_json::to_value(request).unwrap_or(serde_json::Value::Null)
+}
+
+fn emit_current_prompt(manager: &WebViewManager) {
+    if let Some(webview) = &manager.prompt {
+        ui_bridge::emit_provider_event(webview, PROMPT_EVENT, current_prompt(manager));
+    }
+}
+
+fn handle_prompt_ipc(state: &AppState, manager: &mut WebViewManager, msg: String) {
+    let req = match serde_json::from_str::<IpcRequest>(&msg) {
+        Ok(req) if req.provider() == Some(KnownProviderId::Prompt) => req,
+        Ok(req) => {
+            tracing::warn!(method = %req.method, "ignoring non-prompt ipc from prompt overlay");
+            return;
+        }
+        Err(err) => {
+            tracing::warn!(error = %err, "invalid ipc from prompt overlay");
+            return;
+        }
+    };
+    let result = match req.method.as_str() {
+        "vibefi_getPrompt" => Ok(current_prompt(manager)),
+        "vibefi_resolvePrompt" => resolve_prompt(state, manager, &req.params)
+            .map(|()| serde_json::Value::Bool(true))
+            .map_err(|err| err.to_string()),
+        other => Err(format!("Unsupported prompt method: {other}")),
+    };
+    // Answering the last prompt closes the overlay; there is nobody left to reply to.
+    if let Some(webview) = &manager.prompt {
+        if let Err(err) = ipc::respond_value_result(webview, req.id, result) {
+            tracing::warn!(error = ?err, "failed to respond to prompt overlay");
+        }
+    }
+}
+
+fn resolve_prompt(
+    state: &AppState,
+    manager: &mut WebViewManager,
+    params: &serde_json::Value,
+) -> anyhow::Result<()> {
+    let answer: PromptAnswer = serde_json::from_value(
+        params
+            .get(0)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("missing prompt answer"))?,
+    )?;
+    match manager.prompts.front() {
+        Some(pending) if pending.request.id == answer.id => {}
+        _ => anyhow::bail!("prompt {} is no longer open", answer.id),
+    }
+    state.lock.touch();
+    if manager.prompts[0].request.kind == crate::lock::UNLOCK_PROMPT {
+        // Stays on screen until the passphrase is accepted.
+        if !answer.approved {
+            anyhow::bail!("VibeFi stays locked until it is unlocked");
+        }
+        let passphrase = params[0]
+            .get("passphrase")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+        crate::lock::unlock(state, passphrase)?;
+    }
+    let pending = manager.prompts.pop_front().expect("prompt queue head");
+    let remember_key = pending.request.remember_key().filter(|_| answer.remember);
+    tracing::info!(
+        target: "vibefi::audit",
+        id = pending.request.id,
+        kind = %pending.request.kind,
+        requester = %prompt_requester(&pending.request),
+        approved = answer.approved,
+        remembered = remember_key.is_some(),
+        "prompt answered"
+    );
+    if let (Some(key), Some(config_path)) = (
+        remember_key,
+        state
+            .resolved
+            .as_ref()
+            .and_then(|resolved| resolved.config_path.as_deref()),
+    ) {
+        let mut settings = load_settings(config_path);
+        settings.remembered_prompts.insert(key, answer.approved);
+        if let Err(err) = save_settings(config_path, &settings) {
+            tracing::warn!(error = ?err, "failed to remember prompt decision");
+        }
+    }
+    let _ = pending.reply.send(answer.approved);
+
+    if manager.prompts.is_empty() {
+        manager.close_prompt();
+    } else {
+        emit_current_prompt(manager);
+    }
+    Ok(())
+}
+
+/// Opens the command palette over the app area. Not while a prompt waits
+/// for an answer, and only one at a time.
+pub fn handle_open_palette(
+    host: Option<&WebViewHost>,
+    state: &AppState,
+    manager: &mut WebViewManager,
+    proxy: &EventLoopProxy<UserEvent>,
+) {
+    if manager.prompt.is_some() || state.lock.is_locked() {
+        return;
+    }
+    if let Some(palette) = &manager.palette {
+        let _ = palette.focus();
+        return;
+    }
+    let Some(host) = host else {
+        return;
+    };
+    let size = host.window.inner_size();
+    let bounds = manager.app_rect(size.width, size.height);
+    match build_app_webview(
+        host,
+        PALETTE_WEBVIEW_ID,
+        None,
+        EmbeddedContent::Palette,
+        state,
+        proxy.clone(),
+        bounds,
+    ) {
+        Ok(webview) => {
+            if let Err(err) = webview.focus() {
+                tracing::debug!(error = %err, "failed to focus command palette");
+            }
+            manager.show_palette(webview);
+        }
+        Err(err) => tracing::error!(error = ?err, "failed to build command palette"),
+    }
+}
+
+fn handle_palette_ipc(state: &AppState, manager: &mut WebViewManager, msg: String) {
+    let req = match serde_json::from_str::<IpcRequest>(&msg) {
+        Ok(req) if req.provider() == Some(KnownProviderId::Palette) => req,
+        Ok(req) => {
+            tracing::warn!(method = %req.method, "ignoring non-palette ipc from command palette");
+            return;
+        }
+        Err(err) => {
+            tracing::warn!(error = %err, "invalid ipc from command palette");
+            return;
+        }
+    };
+    state.lock.touch();
+    let result = match req.method.as_str() {
+        "vibefi_listCommands" => serde_json::to_value(crate::commands::available(state, manager))
+            .map_err(|err| err.to_string()),
+        "vibefi_runCommand" => {
+            let id = req.params.get(0).and_then(serde_json::Value::as_str);
+            let offered = id.is_some_and(|id| {
+                crate::commands::available(state, manager)
+                    .iter()
+                    .any(|command| command.id == id)
+            });
+            match id.and_then(PaletteAction::parse).filter(|_| offered) {
+                Some(action) => {
+                    manager.close_palette();
+                    run_palette_action(state, manager, action);
+                    return;
+                }
+                None => Err(format!("Unknown command: {}", id.unwrap_or_default())),
+            }
+        }
+        "vibefi_closePalette" => {
+            manager.close_palette();
+            return;
+        }
+        other => Err(format!("Unsupported palette method: {other}")),
+    };
+    if let Some(webview) = &manager.palette {
+        if let Err(err) = ipc::respond_value_result(webview, req.id, result) {
+            tracing::warn!(error = ?err, "failed to respond to command palette");
+        }
+    }
+}
+
+fn run_palette_action(state: &AppState, manager: &mut WebViewManager, action: PaletteAction) {
+    tracing::debug!(?action, "palette command");
+    match action {
+        PaletteAction::Menu(command) => {
+            let _ = state.proxy.send_event(UserEvent::Menu(command));
+        }
+        PaletteAction::SwitchTab(webview_id) => {
+            if let Some(idx) = manager.index_of_id(&webview_id) {
+                manager.switch_to(idx);
+            }
+        }
+        PaletteAction::LaunchDapp(root_cid) => {
+            let identity = state
+                .known_dapps
+                .lock()
+                .expect("known_dapps")
+                .get(&root_cid)
+                .cloned();
+            if let Some(identity) = identity {
+                open_or_launch(state, manager, identity);
+            }
+        }
+        PaletteAction::CopyAddress(address) => {
+            if let Err(err) = crate::clipboard::write_text(&address) {
+                tracing::warn!(error = %format!("{err:#}"), "failed to copy address");
+            }
+        }
+        PaletteAction::SwitchChain(chain_id) => {
+            let from = state.wallet.lock().expect("wallet").chain.chain_id;
+            crate::registry::set_chain(state, chain_id);
+            tracing::info!(from, to = chain_id, "switched chain from command palette");
+        }
+    }
+}
+
 pub fn handle_tab_action(
     host: Option<&WebViewHost>,
     state: &AppState,
@@ -373,7 +1090,7 @@ pub fn handle_tab_action(
     action: TabAction,
 ) {
     match action {
-        TabAction::OpenApp { name, dist_dir } => {
+        TabAction::OpenApp { identity, dist_dir } => {
             if let Some(host) = host {
                 if let Err(e) = open_app_tab(
                     host,
@@ -383,20 +1100,24 @@ pub fn handle_tab_action(
                     Some(dist_dir),
                     EmbeddedContent::Default,
                     AppWebViewKind::Standard,
-                    name,
+                    identity.name.clone(),
+                    Some(identity),
                 ) {
                     tracing::error!(error = ?e, "failed to open app tab");
                 }
             }
         }
+        TabAction::Activate { id } => {
+            if let Some(host) = host {
+                activate_tab(host, state, manager, proxy, &id);
+            }
+        }
     }
 }
 
 pub fn handle_studio_bundle_resolved(
-    host: Option<&WebViewHost>,
     state: &AppState,
     manager: &mut WebViewManager,
-    proxy: &EventLoopProxy<UserEvent>,
     placeholder_id: String,
     result: Result<PathBuf, String>,
 ) {
@@ -406,55 +1127,32 @@ pub fn handle_studio_bundle_resolved(
 
     match result {
         Ok(dist_dir) => {
-            let Some(host) = host else {
-                return;
-            };
-            let size = host.window.inner_size();
-            let bounds = manager.app_rect(size.width, size.height);
-            let studio_webview_id = manager.next_app_id();
-            match build_app_webview(
-                host,
-                &studio_webview_id,
-                Some(dist_dir.clone()),
-                EmbeddedContent::Default,
-                state,
-                proxy.clone(),
-                bounds,
-            ) {
-                Ok(webview) => {
-                    if let Err(err) = webview.set_visible(false) {
-                        tracing::warn!(error = %err, "failed to hide loaded studio webview");
-                    }
-                    if let Some(mut caps) = lock_or_log(&state.app_capabilities, "app_capabilities")
-                    {
-                        let studio_caps = load_app_capabilities_from_dist(&dist_dir);
-                        caps.remove(&placeholder_id);
-                        caps.insert(studio_webview_id.clone(), studio_caps);
-                    }
-                    manager.apps[index] = AppWebViewEntry {
-                        webview,
-                        id: studio_webview_id.clone(),
-                        label: "Studio".to_string(),
-                        kind: AppWebViewKind::Studio,
-                        selectable: true,
-                        loading: false,
-                    };
-                    if state.automation {
-                        crate::automation::emit_webview_created(
-                            &studio_webview_id,
-                            &format!("{:?}", AppWebViewKind::Studio),
-                            "Studio",
-                        );
-                    }
-                }
-                Err(err) => {
-                    tracing::error!(error = ?err, "failed to build loaded studio webview");
-                    if let Some(entry) = manager.apps.get_mut(index) {
-                        entry.label = "Studio (unavailable)".to_string();
-                        entry.selectable = false;
-                        entry.loading = false;
-                    }
-                }
+            // The Studio webview is only built once the tab is first activated.
+            if let Some(mut caps) = lock_or_log(&state.app_capabilities, "app_capabilities") {
+                let identity = manager.apps.get(index).and_then(|e| e.identity.as_ref());
+                caps.insert(
+                    placeholder_id.clone(),
+                    crate::permissions::restrict_capabilities(
+                        state,
+                        identity,
+                        load_app_capabilities_from_dist(&dist_dir),
+                    ),
+                );
+            }
+            if let Some(entry) = manager.apps.get_mut(index) {
+                let storage = entry
+                    .identity
+                    .as_ref()
+                    .and_then(|identity| crate::storage::partition_dir(state, identity));
+                entry.source = Some(WebViewSource {
+                    dist_dir: Some(dist_dir),
+                    embedded: EmbeddedContent::Default,
+                    storage,
+                    ephemeral: false,
+                    local_project: false,
+                });
+                entry.selectable = true;
+                entry.loading = false;
             }
         }
         Err(err) => {
@@ -470,6 +1168,141 @@ pub fn handle_studio_bundle_resolved(
     manager.update_tab_bar();
 }
 
+/// Opens a bundle dropped onto the window: as a dapp tab when it comes with
+/// build output, otherwise as a Studio project. Without Studio it is built the
+/// way `--bundle` builds. Unpacking, verification and builds run off the event
+/// loop.
+pub fn handle_bundle_dropped(state: &AppState, manager: &WebViewManager, path: PathBuf) {
+    let studio_ready = manager
+        .index_of_kind(AppWebViewKind::Studio)
+        .is_some_and(|index| manager.apps[index].selectable);
+    tracing::info!(path = %path.display(), studio_ready, "bundle dropped onto window");
+    let state = state.clone();
+    std::thread::spawn(move || {
+        let result = (|| -> anyhow::Result<()> {
+            let bundle = crate::local_bundle::open(&path)?;
+            let dist_dir = match bundle.dist_dir.clone() {
+                Some(dist_dir) => dist_dir,
+                None if studio_ready => {
+                    let project = crate::local_bundle::studio_project(&bundle)?;
+                    *lock_or_err(&state.dropped_project, "dropped_project")? = Some(project);
+                    let _ = state.proxy.send_event(UserEvent::StudioProjectDropped);
+                    return Ok(());
+                }
+                None => crate::local_bundle::build(&state, &bundle)?,
+            };
+            crate::local_bundle::open_tab(&state, bundle, dist_dir);
+            Ok(())
+        })();
+        if let Err(err) = result {
+            let message = format!("{err:#}");
+            tracing::warn!(path = %path.display(), error = %message, "failed to open dropped bundle");
+            if let Err(err) = crate::notifications::show(
+                "Could not open bundle",
+                &crate::error_hints::with_hint(&message),
+            ) {
+                tracing::debug!(error = %err, "failed to show dropped bundle error");
+            }
+        }
+    });
+}
+
+/// Switches to Studio and tells it a dropped project is waiting. A Studio
+/// webview built just now asks for the project once its page has loaded.
+pub fn handle_studio_project_dropped(
+    host: Option<&WebViewHost>,
+    state: &AppState,
+    manager: &mut WebViewManager,
+    proxy: &EventLoopProxy<UserEvent>,
+) {
+    let Some(index) = manager.index_of_kind(AppWebViewKind::Studio) else {
+        return;
+    };
+    let id = manager.apps[index].id.clone();
+    if let Some(host) = host {
+        activate_tab(host, state, manager, proxy, &id);
+    }
+    if let Some(webview) = manager.webview_for_id(&id) {
+        ui_bridge::emit_provider_event(
+            webview,
+            crate::local_bundle::STUDIO_PROJECT_EVENT,
+            serde_json::Value::Null,
+        );
+    }
+}
+
+/// Builds the webview for a tab created lazily or suspended while idle, then shows it.
+fn activate_tab(
+    host: &WebViewHost,
+    state: &AppState,
+    manager: &mut WebViewManager,
+    proxy: &EventLoopProxy<UserEvent>,
+    id: &str,
+) {
+    let Some(index) = manager.index_of_id(id) else {
+        return;
+    };
+    let entry = &manager.apps[index];
+    if entry.webview.is_none() {
+        let Some(source) = entry.source.clone() else {
+            tracing::debug!(id, "activation ignored for tab without a source");
+            return;
+        };
+        let size = host.window.inner_size();
+        let bounds = manager.app_rect(size.width, size.height);
+        let snapshot = manager.apps[index].snapshot.take();
+        match restore_app_webview(
+            host,
+            id,
+            source,
+            snapshot.as_ref(),
+            state,
+            proxy.clone(),
+            bounds,
+        ) {
+            Ok(webview) => {
+                let entry = &mut manager.apps[index];
+                entry.webview = Some(webview);
+                tracing::debug!(
+                    id,
+                    restored = snapshot.is_some(),
+                    "built deferred tab webview"
+                );
+                if state.automation {
+                    crate::automation::emit_webview_created(
+                        &entry.id,
+                        &format!("{:?}", entry.kind),
+                        &entry.label,
+                    );
+                }
+            }
+            Err(err) => {
+                tracing::error!(error = ?err, id, "failed to build tab webview");
+                manager.apps[index].snapshot = snapshot;
+                return;
+            }
+        }
+    }
+    manager.switch_to(index);
+}
+
+pub fn handle_suspend_idle_tabs(
+    state: &AppState,
+    manager: &WebViewManager,
+    idle_for: std::time::Duration,
+) {
+    manager.request_idle_snapshots(idle_for, |id| state.has_pending_work(id));
+}
+
+pub fn handle_tab_snapshot(manager: &mut WebViewManager, webview_id: &str, snapshot: &str) {
+    match serde_json::from_str::<TabSnapshot>(snapshot) {
+        Ok(snapshot) => manager.suspend(webview_id, snapshot),
+        Err(err) => {
+            tracing::warn!(webview_id, error = %err, "discarding unreadable tab snapshot");
+        }
+    }
+}
+
 fn open_app_tab(
     host: &WebViewHost,
     state: &AppState,
@@ -479,6 +1312,7 @@ fn open_app_tab(
     embedded: EmbeddedContent,
     kind: AppWebViewKind,
     label: String,
+    identity: Option<DappIdentity>,
 ) -> anyhow::Result<String> {
     let size = host.window.inner_size();
     let id = manager.next_app_id();
@@ -486,23 +1320,66 @@ fn open_app_tab(
     let app_capabilities = dist_dir
         .as_deref()
         .map(load_app_capabilities_from_dist)
+        .map(|caps| crate::permissions::restrict_capabilities(state, identity.as_ref(), caps))
         .unwrap_or_default();
-    let webview = build_app_webview(host, &id, dist_dir, embedded, state, proxy.clone(), bounds)?;
+    let storage = identity
+        .as_ref()
+        .and_then(|identity| crate::storage::partition_dir(state, identity));
+    let source = WebViewSource {
+        dist_dir,
+        embedded,
+        storage,
+        ephemeral: identity
+            .as_ref()
+            .is_some_and(|identity| identity.unverified),
+        local_project: identity
+            .as_ref()
+            .is_some_and(|identity| identity.local_project),
+    };
+    let webview = restore_app_webview(
+        host,
+        &id,
+        source.clone(),
+        None,
+        state,
+        proxy.clone(),
+        bounds,
+    )?;
 
     if let Some(active) = manager.active_app_webview() {
         let _ = active.set_visible(false);
     }
+    if manager.prompt.is_some() {
+        // Stay behind the open prompt; `close_prompt` reveals the new tab.
+        let _ = webview.set_visible(false);
+    }
+    if let Some(previous) = manager
+        .active_app_index
+        .and_then(|i| manager.apps.get_mut(i))
+    {
+        previous.last_active = Instant::now();
+    }
     let idx = manager.apps.len();
     if let Some(mut caps) = lock_or_log(&state.app_capabilities, "app_capabilities") {
         caps.insert(id.clone(), app_capabilities);
     }
+    if let Some(dist_dir) = source.dist_dir.as_deref() {
+        crate::decoder::register_tab(&id, dist_dir);
+    }
     manager.apps.push(AppWebViewEntry {
-        webview,
+        webview: Some(webview),
+        source: Some(source),
+        snapshot: None,
+        last_active: Instant::now(),
         id,
         label,
         kind,
         selectable: true,
         loading: false,
+        usage: None,
+        unanswered_samples: 0,
+        badges: TabBadges::default(),
+        identity,
     });
     manager.active_app_index = Some(idx);
     manager.update_tab_bar();
@@ -0,0 +1,167 @@
+use anyhow::{Context, Result, bail};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Runs a dialog helper and returns the path it printed, or `None` when the
+/// user cancelled. Titles and names are passed as arguments or environment
+/// variables, never spliced into a script.
+fn run_dialog(program: &str, args: &[&str], envs: &[(&str, &str)]) -> Result<Option<PathBuf>> {
+    let output = Command::new(program)
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("failed to run '{program}'"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // zenity exits 1 on cancel; osascript reports error -128.
+        if output.status.code() == Some(1) && (program != "osascript" || stderr.contains("-128")) {
+            return Ok(None);
+        }
+        bail!(
+            "'{program}' exited with status {}: {}",
+            output.status,
+            stderr.trim()
+        );
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Some(PathBuf::from(path)).filter(|path| !path.as_os_str().is_empty()))
+}
+
+/// Asks the user to pick an existing file. `extensions` (lowercase, without
+/// the dot) narrow the dialog where the platform supports it; callers must
+/// still check the extension of the returned path.
+pub fn pick_open(title: &str, extensions: &[String]) -> Result<Option<PathBuf>> {
+    #[cfg(target_os = "macos")]
+    {
+        let _ = extensions;
+        return run_dialog(
+            "osascript",
+            &[
+                "-e",
+                "on run argv",
+                "-e",
+                "POSIX path of (choose file with prompt (item 1 of argv))",
+                "-e",
+                "end run",
+                title,
+            ],
+            &[],
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        const SCRIPT: &str = "Add-Type -AssemblyName System.Windows.Forms; \
+            $d = New-Object System.Windows.Forms.OpenFileDialog; \
+            $d.Title = $env:VIBEFI_DIALOG_TITLE; \
+            $d.Filter = $env:VIBEFI_DIALOG_FILTER; \
+            if ($d.ShowDialog() -eq 'OK') { $d.FileName }";
+        return run_dialog(
+            "powershell",
+            &["-NoProfile", "-NonInteractive", "-STA", "-Command", SCRIPT],
+            &[
+                ("VIBEFI_DIALOG_TITLE", title),
+                ("VIBEFI_DIALOG_FILTER", windows_filter(extensions).as_str()),
+            ],
+        );
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let title_arg = format!("--title={title}");
+        let filter = zenity_filter(extensions);
+        let mut args = vec!["--file-selection", title_arg.as_str()];
+        if let Some(filter) = &filter {
+            args.push(filter);
+        }
+        return run_dialog("zenity", &args, &[]).context("zenity unavailable (install zenity)");
+    }
+
+    #[allow(unreachable_code)]
+    {
+        bail!("file dialogs are not supported on this platform")
+    }
+}
+
+/// Asks the user where to save a file, suggesting `file_name`.
+pub fn pick_save(title: &str, file_name: &str, extensions: &[String]) -> Result<Option<PathBuf>> {
+    #[cfg(target_os = "macos")]
+    {
+        let _ = extensions;
+        return run_dialog(
+            "osascript",
+            &[
+                "-e",
+                "on run argv",
+                "-e",
+                "POSIX path of (choose file name with prompt (item 1 of argv) default name (item 2 of argv))",
+                "-e",
+                "end run",
+                title,
+                file_name,
+            ],
+            &[],
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        const SCRIPT: &str = "Add-Type -AssemblyName System.Windows.Forms; \
+            $d = New-Object System.Windows.Forms.SaveFileDialog; \
+            $d.Title = $env:VIBEFI_DIALOG_TITLE; \
+            $d.Filter = $env:VIBEFI_DIALOG_FILTER; \
+            $d.FileName = $env:VIBEFI_DIALOG_NAME; \
+            if ($d.ShowDialog() -eq 'OK') { $d.FileName }";
+        return run_dialog(
+            "powershell",
+            &["-NoProfile", "-NonInteractive", "-STA", "-Command", SCRIPT],
+            &[
+                ("VIBEFI_DIALOG_TITLE", title),
+                ("VIBEFI_DIALOG_FILTER", windows_filter(extensions).as_str()),
+                ("VIBEFI_DIALOG_NAME", file_name),
+            ],
+        );
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let title_arg = format!("--title={title}");
+        let name_arg = format!("--filename={file_name}");
+        let filter = zenity_filter(extensions);
+        let mut args = vec![
+            "--file-selection",
+            "--save",
+            "--confirm-overwrite",
+            title_arg.as_str(),
+            name_arg.as_str(),
+        ];
+        if let Some(filter) = &filter {
+            args.push(filter);
+        }
+        return run_dialog("zenity", &args, &[]).context("zenity unavailable (install zenity)");
+    }
+
+    #[allow(unreachable_code)]
+    {
+        bail!("file dialogs are not supported on this platform")
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn zenity_filter(extensions: &[String]) -> Option<String> {
+    if extensions.is_empty() {
+        return None;
+    }
+    let patterns: Vec<String> = extensions.iter().map(|ext| format!("*.{ext}")).collect();
+    Some(format!("--file-filter={}", patterns.join(" ")))
+}
+
+#[cfg(target_os = "windows")]
+fn windows_filter(extensions: &[String]) -> String {
+    if extensions.is_empty() {
+        return "All files (*.*)|*.*".to_string();
+    }
+    let patterns: Vec<String> = extensions.iter().map(|ext| format!("*.{ext}")).collect();
+    format!("Allowed files|{}", patterns.join(";"))
+}
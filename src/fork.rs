@@ -0,0 +1,247 @@
+//! `--fork` mode: a local anvil node forked from a real chain stands in for
+//! the profile's RPC, so risky transactions can be rehearsed in the client
+//! against real contracts and balances without sending anything for real.
+
+use anyhow::{Context, Result, anyhow, bail};
+use reqwest::blocking::Client as HttpClient;
+use serde_json::{Value, json};
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::runtime_paths;
+use crate::state::WalletBackend;
+
+/// Forking fetches the starting block from the upstream node first.
+const READY_TIMEOUT: Duration = Duration::from_secs(60);
+const READY_POLL: Duration = Duration::from_millis(250);
+
+/// The running fork, stopped on shutdown.
+static NODE: Mutex<Option<ForkNode>> = Mutex::new(None);
+
+/// Where a fork session forks from.
+#[derive(Debug, Clone)]
+pub struct ForkSource {
+    pub upstream: String,
+    /// Latest block when `None`.
+    pub block: Option<u64>,
+}
+
+impl ForkSource {
+    /// Session label for the window title and tab bar. Only the upstream
+    /// host is shown, since RPC URLs often carry an API key in the path.
+    pub fn label(&self) -> String {
+        let host = reqwest::Url::parse(&self.upstream)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| "custom RPC".to_string());
+        match self.block {
+            Some(block) => format!("Fork of {host} @ block {block}"),
+            None => format!("Fork of {host} @ latest block"),
+        }
+    }
+}
+
+/// Whether a fork session can use `backend`. A WalletConnect wallet sends
+/// transactions through its own RPC, and the bundler and remote signer submit
+/// to the real chain, so none of them would stay on the fork.
+fn keeps_to_fork(backend: WalletBackend) -> bool {
+    matches!(backend, WalletBackend::Local | WalletBackend::Hardware)
+}
+
+/// Refuses wallets that would send a fork session's transactions for real.
+pub fn ensure_backend_allowed(fork: Option<&ForkSource>, backend: WalletBackend) -> Result<()> {
+    if fork.is_some() && !keeps_to_fork(backend) {
+        bail!(
+            "the {} wallet would send transactions to the real chain, not the fork; use the local signer or a hardware wallet in --fork sessions",
+            backend.as_str()
+        );
+    }
+    Ok(())
+}
+
+struct ForkNode {
+    child: Child,
+}
+
+impl Drop for ForkNode {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn anvil_args(source: &ForkSource, port: u16) -> Vec<String> {
+    let mut args = vec![
+        "--fork-url".to_string(),
+        source.upstream.clone(),
+        "--host".to_string(),
+        "127.0.0.1".to_string(),
+        "--port".to_string(),
+        port.to_string(),
+        "--silent".to_string(),
+    ];
+    if let Some(block) = source.block {
+        args.push("--fork-block-number".to_string());
+        args.push(block.to_string());
+    }
+    args
+}
+
+/// Starts anvil forked from `source` and waits until it serves
+/// `expected_chain_id`. Returns the fork's RPC URL.
+pub fn start(source: &ForkSource, expected_chain_id: u64, http: &HttpClient) -> Result<String> {
+    let anvil = runtime_paths::resolve_anvil_binary()?;
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .context("failed to pick a port for the fork")?
+        .port();
+    let rpc_url = format!("http://127.0.0.1:{port}");
+    tracing::info!(
+        anvil = %anvil,
+        port,
+        block = ?source.block,
+        "starting fork node"
+    );
+    let child = Command::new(&anvil)
+        .args(anvil_args(source, port))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("failed to start {anvil}"))?;
+    let mut node = ForkNode { child };
+
+    let started = Instant::now();
+    let chain_id = loop {
+        if let Some(status) = node.child.try_wait()? {
+            bail!("anvil exited with {status} before the fork was ready; check the fork RPC URL");
+        }
+        if let Ok(chain_id) = fork_chain_id(http, &rpc_url) {
+            break chain_id;
+        }
+        if started.elapsed() > READY_TIMEOUT {
+            bail!("fork was not ready after {}s", READY_TIMEOUT.as_secs());
+        }
+        std::thread::sleep(READY_POLL);
+    };
+    if chain_id != expected_chain_id {
+        bail!(
+            "the fork RPC serves chain {chain_id}, but the network config is for chain {expected_chain_id}"
+        );
+    }
+    tracing::info!(
+        rpc_url = %rpc_url,
+        chain_id,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "fork node ready"
+    );
+    *NODE.lock().expect("poisoned fork node lock") = Some(node);
+    Ok(rpc_url)
+}
+
+fn fork_chain_id(http: &HttpClient, rpc_url: &str) -> Result<u64> {
+    let response: Value = http
+        .post(rpc_url)
+        .timeout(Duration::from_secs(2))
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": [] }))
+        .send()?
+        .json()?;
+    let hex = response
+        .get("result")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("eth_chainId returned no result"))?;
+    u64::from_str_radix(hex.trim_start_matches("0x"), 16).context("invalid eth_chainId result")
+}
+
+/// Kills the fork node, if one is running; called on shutdown.
+pub fn stop() {
+    drop(NODE.lock().expect("poisoned fork node lock").take());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ForkSource, anvil_args, ensure_backend_allowed};
+    use crate::state::WalletBackend;
+
+    fn pinned_mainnet() -> ForkSource {
+        ForkSource {
+            upstream: "https://eth-mainnet.example.com/v2/secret-key".to_string(),
+            block: Some(19_000_000),
+        }
+    }
+
+    fn latest_local() -> ForkSource {
+        ForkSource {
+            upstream: "http://127.0.0.1:8545".to_string(),
+            block: None,
+        }
+    }
+
+    #[test]
+    fn forks_the_upstream_on_the_given_port() {
+        let source = pinned_mainnet();
+        let args = anvil_args(&source, 8645);
+        assert_eq!(args[..2], ["--fork-url", source.upstream.as_str()]);
+        assert!(args.windows(2).any(|pair| pair == ["--port", "8645"]));
+    }
+
+    #[test]
+    fn forks_from_the_given_block() {
+        assert!(
+            anvil_args(&pinned_mainnet(), 8645)
+                .windows(2)
+                .any(|pair| pair == ["--fork-block-number", "19000000"])
+        );
+    }
+
+    #[test]
+    fn forks_from_the_latest_block_by_default() {
+        assert!(
+            !anvil_args(&latest_local(), 1)
+                .iter()
+                .any(|arg| arg == "--fork-block-number")
+        );
+    }
+
+    #[test]
+    fn labels_forks_by_host_and_block() {
+        assert_eq!(
+            pinned_mainnet().label(),
+            "Fork of eth-mainnet.example.com @ block 19000000"
+        );
+        assert_eq!(latest_local().label(), "Fork of 127.0.0.1 @ latest block");
+    }
+
+    #[test]
+    fn labels_leave_out_the_upstream_path() {
+        assert!(!pinned_mainnet().label().contains("secret-key"));
+    }
+
+    #[test]
+    fn fork_sessions_refuse_wallets_that_leave_the_fork() {
+        let fork = ForkSource {
+            upstream: "https://eth-mainnet.example.com".to_string(),
+            block: None,
+        };
+        for backend in [
+            WalletBackend::WalletConnect,
+            WalletBackend::SmartAccount,
+            WalletBackend::Remote,
+        ] {
+            assert!(ensure_backend_allowed(Some(&fork), backend).is_err());
+            assert!(ensure_backend_allowed(None, backend).is_ok());
+        }
+    }
+
+    #[test]
+    fn fork_sessions_keep_local_and_hardware_wallets() {
+        let fork = ForkSource {
+            upstream: "https://eth-mainnet.example.com".to_string(),
+            block: None,
+        };
+        assert!(ensure_backend_allowed(Some(&fork), WalletBackend::Local).is_ok());
+        assert!(ensure_backend_allowed(Some(&fork), WalletBackend::Hardware).is_ok());
+    }
+}
@@ -3,10 +3,12 @@ use alloy_primitives::{B256, Signature};
 use alloy_signer::Signer;
 use anyhow::{Context, Result, anyhow};
 
-/// Wraps either a Ledger or Trezor hardware signer.
+/// Wraps a Ledger (USB or Bluetooth), Trezor or GridPlus Lattice1 hardware signer.
 pub enum HardwareDevice {
     Ledger(alloy_signer_ledger::LedgerSigner),
+    LedgerBle(crate::ledger_ble::LedgerBleSigner),
     Trezor(alloy_signer_trezor::TrezorSigner),
+    Lattice(crate::lattice::LatticeSigner),
 }
 
 // HardwareDevice is not Send because the underlying HID transports aren't,
@@ -18,7 +20,9 @@ impl std::fmt::Debug for HardwareDevice {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             HardwareDevice::Ledger(_) => f.write_str("HardwareDevice::Ledger"),
+            HardwareDevice::LedgerBle(_) => f.write_str("HardwareDevice::LedgerBle"),
             HardwareDevice::Trezor(_) => f.write_str("HardwareDevice::Trezor"),
+            HardwareDevice::Lattice(_) => f.write_str("HardwareDevice::Lattice"),
         }
     }
 }
@@ -72,7 +76,9 @@ pub async fn detect_and_connect(chain_id: u64) -> Result<HardwareDevice> {
 pub fn get_address(device: &HardwareDevice) -> String {
     match device {
         HardwareDevice::Ledger(s) => format!("0x{:x}", s.address()),
+        HardwareDevice::LedgerBle(s) => format!("0x{:x}", s.address()),
         HardwareDevice::Trezor(s) => format!("0x{:x}", s.address()),
+        HardwareDevice::Lattice(s) => format!("0x{:x}", s.address()),
     }
 }
 
@@ -83,10 +89,14 @@ pub async fn sign_message(device: &HardwareDevice, msg: &[u8]) -> Result<String>
             .sign_message(msg)
             .await
             .context("Ledger sign_message failed")?,
+        HardwareDevice::LedgerBle(s) => s
+            .sign_message(msg)
+            .context("Ledger Bluetooth sign_message failed")?,
         HardwareDevice::Trezor(s) => s
             .sign_message(msg)
             .await
             .context("Trezor sign_message failed")?,
+        HardwareDevice::Lattice(s) => s.sign_message(msg).context("Lattice sign_message failed")?,
     };
     Ok(format!("0x{}", hex::encode(sig.as_bytes())))
 }
@@ -101,10 +111,16 @@ pub async fn sign_hash(device: &HardwareDevice, hash: B256) -> Result<String> {
             .sign_message(hash.as_slice())
             .await
             .context("Ledger sign_hash failed")?,
+        HardwareDevice::LedgerBle(s) => s
+            .sign_message(hash.as_slice())
+            .context("Ledger Bluetooth sign_hash failed")?,
         HardwareDevice::Trezor(s) => s
             .sign_message(hash.as_slice())
             .await
             .context("Trezor sign_hash failed")?,
+        HardwareDevice::Lattice(s) => s
+            .sign_message(hash.as_slice())
+            .context("Lattice sign_hash failed")?,
     };
     Ok(format!("0x{}", hex::encode(sig.as_bytes())))
 }
@@ -118,9 +134,15 @@ pub async fn sign_transaction(
         HardwareDevice::Ledger(s) => alloy_network::TxSigner::sign_transaction(s, tx)
             .await
             .context("Ledger sign_transaction failed")?,
+        HardwareDevice::LedgerBle(s) => s
+            .sign_transaction(tx)
+            .context("Ledger Bluetooth sign_transaction failed")?,
         HardwareDevice::Trezor(s) => alloy_network::TxSigner::sign_transaction(s, tx)
             .await
             .context("Trezor sign_transaction failed")?,
+        HardwareDevice::Lattice(s) => s
+            .sign_transaction(tx)
+            .context("Lattice sign_transaction failed")?,
     };
     Ok(sig)
 }
@@ -0,0 +1,131 @@
+//! Settings methods for the local accounts: listing the keystore's accounts,
+//! deriving another one and choosing which account dapps see first.
+
+use anyhow::{Result, anyhow, bail};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::ipc_contract::IpcRequest;
+use crate::state::{AppState, WalletBackend, lock_or_err};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LocalAccountsResponse {
+    /// Connected local accounts, or the keystore's while it is closed.
+    accounts: Vec<String>,
+    connected: bool,
+    /// Whether the keystore holds a recovery phrase to derive more from.
+    can_derive: bool,
+    /// Account the local signer uses first.
+    active: Option<String>,
+}
+
+/// `vibefi_getLocalAccounts`.
+pub(super) fn local_accounts(state: &AppState) -> Result<Value> {
+    let config_path = state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.config_path.as_deref());
+    let connected = state.local_accounts();
+    let accounts = if connected.is_empty() {
+        config_path
+            .map(crate::keystore::stored_accounts)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|address| format!("0x{address:x}"))
+            .collect()
+    } else {
+        connected.clone()
+    };
+    Ok(serde_json::to_value(LocalAccountsResponse {
+        accounts,
+        connected: !connected.is_empty(),
+        can_derive: config_path.is_some_and(crate::keystore::can_derive),
+        active: connected.first().cloned(),
+    })?)
+}
+
+/// `vibefi_selectAccount [address]`: makes `address` the active account. The
+/// local signer moves it first, and every tab holding it gets it as its
+/// primary account and an `accountsChanged`.
+pub(super) fn select_account(state: &AppState, req: &IpcRequest) -> Result<Value> {
+    state.lock.ensure_unlocked()?;
+    let account = req
+        .params
+        .get(0)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|account| !account.is_empty())
+        .ok_or_else(|| anyhow!("missing account"))?;
+    let mut found = false;
+    {
+        let mut signers = lock_or_err(&state.signer, "signer")?;
+        if let Some(at) = signers
+            .iter()
+            .position(|signer| format!("0x{:x}", signer.address()).eq_ignore_ascii_case(account))
+        {
+            let selected = signers.remove(at);
+            signers.insert(0, selected);
+            found = true;
+        }
+    }
+    let mut changed = Vec::new();
+    for (webview_id, binding) in lock_or_err(&state.tab_wallets, "tab_wallets")?.iter_mut() {
+        if binding.owner_of(account).is_none() {
+            continue;
+        }
+        found = true;
+        if binding.select(account) {
+            changed.push(webview_id.clone());
+        }
+    }
+    if !found {
+        bail!("No connected wallet holds {account}");
+    }
+    tracing::info!(account, tabs = changed.len(), "selected active account");
+    super::walletconnect::notify_accounts_changed(state, changed);
+    Ok(Value::Bool(true))
+}
+
+/// `vibefi_addLocalAccount [password]`: derives the next account from the
+/// keystore's recovery phrase. When the keystore is connected, the account
+/// joins the local signer and the tabs using it.
+pub(super) fn add_local_account(state: &AppState, req: &IpcRequest) -> Result<Value> {
+    state.lock.ensure_unlocked()?;
+    let config_path = state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.config_path.as_deref())
+        .ok_or_else(|| anyhow!("No config loaded"))?;
+    let password = req
+        .params
+        .get(0)
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let stored = crate::keystore::stored_accounts(config_path);
+    let signer = crate::keystore::add_account(config_path, password)?;
+    let account = format!("0x{:x}", signer.address());
+    {
+        let mut signers = lock_or_err(&state.signer, "signer")?;
+        // Only the keystore's keys grow, not a developer key.
+        if !signers
+            .first()
+            .is_some_and(|first| stored.contains(&first.address()))
+        {
+            return Ok(Value::String(account));
+        }
+        signers.push(Arc::new(signer));
+    }
+    let accounts = state.local_accounts();
+    let mut changed = Vec::new();
+    for (webview_id, binding) in lock_or_err(&state.tab_wallets, "tab_wallets")?.iter_mut() {
+        if binding.has_backend(WalletBackend::Local)
+            && binding.set_backend_accounts(WalletBackend::Local, &accounts)
+        {
+            changed.push(webview_id.clone());
+        }
+    }
+    super::walletconnect::notify_accounts_changed(state, changed);
+    Ok(Value::String(account))
+}
@@ -0,0 +1,444 @@
+use alloy_primitives::Address;
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+
+use crate::ipc_contract::IpcRequest;
+use crate::prompt::PromptRequest;
+use crate::state::{AppState, DappIdentity, FileCapability};
+use crate::subscriptions::Topic;
+
+const MAX_CLIPBOARD_CHARS: usize = 4096;
+const MAX_NOTIFY_TITLE_CHARS: usize = 80;
+const MAX_NOTIFY_BODY_CHARS: usize = 300;
+
+/// Extracts every `0x`-prefixed 20-byte hex token from `text`.
+fn find_addresses(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::new();
+    let mut idx = 0;
+    while let Some(pos) = text[idx..].find("0x") {
+        let start = idx + pos;
+        let hex_len = bytes[start + 2..]
+            .iter()
+            .take_while(|b| b.is_ascii_hexdigit())
+            .count();
+        if hex_len == 40 {
+            out.push(&text[start..start + 42]);
+        }
+        idx = start + 2 + hex_len;
+    }
+    out
+}
+
+/// The confirmation shown before a dapp copies text carrying an address. The
+/// page controls everything it renders, so only the host can show the user
+/// what is really about to land on the clipboard.
+fn copy_prompt(dapp: &DappIdentity, text: &str) -> Option<PromptRequest> {
+    let addresses = find_addresses(text);
+    if addresses.is_empty() {
+        return None;
+    }
+    Some(
+        PromptRequest::new(
+            "clipboard",
+            "Copy address?",
+            format!(
+                "{} wants to copy an address to your clipboard. Check it is the one you expect before pasting it anywhere.",
+                dapp.name
+            ),
+        )
+        .with_details(json!({ "addresses": addresses.join("\n"), "text": text })),
+    )
+}
+
+/// Writes dapp text to the clipboard. Text carrying an address is shown to the
+/// user first; blocks on that prompt, so run it off the event loop.
+fn handle_copy_to_clipboard(
+    state: &AppState,
+    webview_id: &str,
+    dapp: &DappIdentity,
+    req: &IpcRequest,
+) -> Result<Value> {
+    let caps = state
+        .app_capabilities_for(webview_id)
+        .ok_or_else(|| anyhow!("clipboard capability is not available for this webview"))?;
+    if !caps.clipboard_write {
+        bail!("clipboard capability denied");
+    }
+    let text = req
+        .params
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("text is required"))?;
+    if text.chars().count() > MAX_CLIPBOARD_CHARS {
+        bail!("clipboard text exceeds {MAX_CLIPBOARD_CHARS} characters");
+    }
+    if let Some(request) = copy_prompt(dapp, text) {
+        if !crate::prompt::ask(state, request.from_webview(webview_id)) {
+            tracing::warn!(webview_id, %dapp, "clipboard write with an address declined");
+            bail!("clipboard write denied by the user");
+        }
+    }
+    crate::clipboard::write_text(text)?;
+    tracing::debug!(webview_id, len = text.len(), "clipboard write");
+    Ok(json!(true))
+}
+
+/// Decodes a QR code from either a camera frame (`{width, height, luma}`) or an
+/// imported image file (`{image}`), both base64-encoded.
+fn handle_decode_qr(state: &AppState, webview_id: &str, req: &IpcRequest) -> Result<Value> {
+    let caps = state
+        .app_capabilities_for(webview_id)
+        .ok_or_else(|| anyhow!("qr capability is not available for this webview"))?;
+    if !caps.qr_scan {
+        bail!("qr capability denied");
+    }
+    let source = req
+        .params
+        .get(0)
+        .ok_or_else(|| anyhow!("qr source is required"))?;
+    let decode_b64 = |field: &str| -> Result<Vec<u8>> {
+        let raw = source
+            .get(field)
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("{field} is required"))?;
+        base64::engine::general_purpose::STANDARD
+            .decode(raw)
+            .map_err(|_| anyhow!("{field} is not valid base64"))
+    };
+    let texts = if source.get("image").is_some() {
+        crate::qr::decode_image(&decode_b64("image")?)?
+    } else {
+        let dimension = |field: &str| {
+            source
+                .get(field)
+                .and_then(Value::as_u64)
+                .map(|v| v as usize)
+                .ok_or_else(|| anyhow!("{field} is required"))
+        };
+        crate::qr::decode_luma(
+            dimension("width")?,
+            dimension("height")?,
+            &decode_b64("luma")?,
+        )?
+    };
+    tracing::debug!(webview_id, count = texts.len(), "qr decode");
+    let payloads: Vec<_> = texts.iter().map(|t| crate::qr::classify(t)).collect();
+    Ok(serde_json::to_value(payloads)?)
+}
+
+/// Shows a desktop notification attributed to `dapp`. The first notification
+/// of a tab asks the user; blocks on that prompt, so run it off the event loop.
+fn handle_notify(
+    state: &AppState,
+    webview_id: &str,
+    dapp: &DappIdentity,
+    req: &IpcRequest,
+) -> Result<Value> {
+    let caps = state
+        .app_capabilities_for(webview_id)
+        .ok_or_else(|| anyhow!("notifications capability is not available for this webview"))?;
+    if !caps.notifications {
+        bail!("notifications capability denied");
+    }
+    let text = |idx: usize| req.params.get(idx).and_then(Value::as_str).map(str::trim);
+    let title = text(0)
+        .filter(|title| !title.is_empty())
+        .ok_or_else(|| anyhow!("title is required"))?;
+    let body = text(1).unwrap_or_default();
+    if title.chars().count() > MAX_NOTIFY_TITLE_CHARS {
+        bail!("notification title exceeds {MAX_NOTIFY_TITLE_CHARS} characters");
+    }
+    if body.chars().count() > MAX_NOTIFY_BODY_CHARS {
+        bail!("notification body exceeds {MAX_NOTIFY_BODY_CHARS} characters");
+    }
+
+    let granted = match crate::notifications::session_permission(webview_id) {
+        Some(granted) => granted,
+        None => {
+            let request = PromptRequest::new(
+                "notifications",
+                "Allow notifications?",
+                format!("{} wants to show desktop notifications.", dapp.name),
+            )
+            .from_webview(webview_id)
+            .with_details(json!({ "title": title, "body": body }))
+            .rememberable("notifications");
+            let granted = crate::prompt::ask(state, request);
+            crate::notifications::set_session_permission(webview_id, granted);
+            granted
+        }
+    };
+    if !granted {
+        bail!("notifications denied by the user");
+    }
+    crate::notifications::reserve_slot(webview_id)?;
+    crate::notifications::show(&format!("{title} (from {})", dapp.name), body)?;
+    tracing::info!(webview_id, %dapp, "dapp notification shown");
+    Ok(json!(true))
+}
+
+fn file_capability(state: &AppState, webview_id: &str) -> Result<FileCapability> {
+    state
+        .app_capabilities_for(webview_id)
+        .map(|caps| caps.files)
+        .ok_or_else(|| anyhow!("file capability is not available for this webview"))
+}
+
+/// Reduces a dapp-suggested file name to a bare name without directories.
+fn sanitize_file_name(raw: &str) -> Option<String> {
+    let name = raw.rsplit(['/', '\\']).next()?.trim();
+    let name: String = name.chars().filter(|c| !c.is_control()).collect();
+    Some(name).filter(|name| !name.is_empty() && name != "." && name != "..")
+}
+
+/// Lets the user pick a file for the dapp to read. Resolves to `null` when
+/// cancelled. Blocks on the dialog, so run it off the event loop.
+fn handle_open_file(state: &AppState, webview_id: &str, dapp: &DappIdentity) -> Result<Value> {
+    let files = file_capability(state, webview_id)?;
+    if !files.open {
+        bail!("file open capability denied");
+    }
+    let title = format!("{} wants to open a file", dapp.name);
+    let Some(path) = crate::file_dialog::pick_open(&title, &files.extensions)? else {
+        return Ok(Value::Null);
+    };
+    if !files.allows_path(&path) {
+        bail!(
+            "file type not allowed; expected {}",
+            files.extensions.join(", ")
+        );
+    }
+    let size = fs::metadata(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?
+        .len();
+    if size > files.max_bytes as u64 {
+        bail!("file exceeds the {} byte limit", files.max_bytes);
+    }
+    let bytes = fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    tracing::info!(webview_id, %dapp, size = bytes.len(), "dapp opened a file");
+    Ok(json!({
+        "name": path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default(),
+        "size": bytes.len(),
+        "mimeType": mime_guess::from_path(&path).first_or_octet_stream().essence_str(),
+        "data": base64::engine::general_purpose::STANDARD.encode(&bytes),
+    }))
+}
+
+/// Writes dapp-provided contents (`{name, data}` base64 or `{name, text}`) to
+/// a location the user picks. Resolves to `false` when cancelled. Blocks on
+/// the dialog, so run it off the event loop.
+fn handle_save_file(
+    state: &AppState,
+    webview_id: &str,
+    dapp: &DappIdentity,
+    req: &IpcRequest,
+) -> Result<Value> {
+    let files = file_capability(state, webview_id)?;
+    if !files.save {
+        bail!("file save capability denied");
+    }
+    let file = req
+        .params
+        .get(0)
+        .ok_or_else(|| anyhow!("file is required"))?;
+    let name = file
+        .get("name")
+        .and_then(Value::as_str)
+        .and_then(sanitize_file_name)
+        .ok_or_else(|| anyhow!("file name is required"))?;
+    let bytes = match (
+        file.get("data").and_then(Value::as_str),
+        file.get("text").and_then(Value::as_str),
+    ) {
+        (Some(data), _) => base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|_| anyhow!("data is not valid base64"))?,
+        (None, Some(text)) => text.as_bytes().to_vec(),
+        (None, None) => bail!("data or text is required"),
+    };
+    if bytes.len() > files.max_bytes {
+        bail!("file exceeds the {} byte limit", files.max_bytes);
+    }
+    if !files.allows_path(Path::new(&name)) {
+        bail!(
+            "file type not allowed; expected {}",
+            files.extensions.join(", ")
+        );
+    }
+    let title = format!("{} wants to save {name}", dapp.name);
+    let Some(path) = crate::file_dialog::pick_save(&title, &name, &files.extensions)? else {
+        return Ok(Value::Bool(false));
+    };
+    if !files.allows_path(&path) {
+        bail!(
+            "file type not allowed; expected {}",
+            files.extensions.join(", ")
+        );
+    }
+    fs::write(&path, &bytes).with_context(|| format!("failed to write {}", path.display()))?;
+    tracing::info!(webview_id, %dapp, size = bytes.len(), "dapp saved a file");
+    Ok(Value::Bool(true))
+}
+
+/// App methods that block on the user (prompts, dialogs) or the node. The
+/// router runs them on a worker thread.
+pub(super) fn is_blocking_method(method: &str) -> bool {
+    matches!(
+        method,
+        "vibefi_notify"
+            | "vibefi_openFile"
+            | "vibefi_saveFile"
+            | "vibefi_resolveEns"
+            | "vibefi_copyToClipboard"
+    )
+}
+
+pub(super) fn handle_blocking_app_ipc(
+    state: &AppState,
+    webview_id: &str,
+    dapp: &DappIdentity,
+    req: &IpcRequest,
+) -> Result<Value> {
+    match req.method.as_str() {
+        "vibefi_notify" => handle_notify(state, webview_id, dapp, req),
+        "vibefi_openFile" => handle_open_file(state, webview_id, dapp),
+        "vibefi_saveFile" => handle_save_file(state, webview_id, dapp, req),
+        "vibefi_resolveEns" => handle_resolve_ens(state, req),
+        "vibefi_copyToClipboard" => handle_copy_to_clipboard(state, webview_id, dapp, req),
+        _ => Err(anyhow!("unsupported app method: {}", req.method)),
+    }
+}
+
+/// Resolves an ENS name to its address, or an address to its verified
+/// primary name. Both fields are set, `null` when nothing was found.
+fn handle_resolve_ens(state: &AppState, req: &IpcRequest) -> Result<Value> {
+    let query = req
+        .params
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("a name or address is required"))?;
+    if let Ok(address) = query.trim().parse::<Address>() {
+        let name = crate::ens::lookup_address(state, address)?;
+        return Ok(json!({ "name": name, "address": format!("{address:#x}") }));
+    }
+    let name = crate::ens::normalize(query)?;
+    let address = crate::ens::resolve_name(state, &name)?;
+    Ok(json!({ "name": name, "address": address.map(|a| format!("{a:#x}")) }))
+}
+
+fn handle_subscribe(state: &AppState, webview_id: &str, req: &IpcRequest) -> Result<Value> {
+    let raw = req
+        .params
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("topic is required"))?;
+    let topic = Topic::parse(raw).ok_or_else(|| anyhow!("unknown event topic: {raw}"))?;
+    let caps = state
+        .app_capabilities_for(webview_id)
+        .ok_or_else(|| anyhow!("event capability is not available for this webview"))?;
+    if !caps.event_topics.contains(&topic) {
+        bail!("event capability denied for {raw}");
+    }
+    let id = state
+        .subscriptions
+        .lock()
+        .expect("subscriptions")
+        .subscribe(webview_id, topic);
+    tracing::debug!(webview_id, topic = raw, subscription = %id, "event subscribe");
+    Ok(Value::String(id))
+}
+
+fn handle_unsubscribe(state: &AppState, webview_id: &str, req: &IpcRequest) -> Result<Value> {
+    let id = req
+        .params
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("subscription id is required"))?;
+    let removed = state
+        .subscriptions
+        .lock()
+        .expect("subscriptions")
+        .unsubscribe(webview_id, id);
+    Ok(Value::Bool(removed))
+}
+
+pub(super) fn handle_app_ipc(
+    state: &AppState,
+    webview_id: &str,
+    req: &IpcRequest,
+) -> Result<Value> {
+    match req.method.as_str() {
+        "vibefi_decodeQr" => handle_decode_qr(state, webview_id, req),
+        "vibefi_subscribe" => handle_subscribe(state, webview_id, req),
+        "vibefi_unsubscribe" => handle_unsubscribe(state, webview_id, req),
+        "vibefi_getClientInfo" => Ok(serde_json::to_value(crate::client_info::client_info(
+            state,
+        ))?),
+        _ => Err(anyhow!("unsupported app method: {}", req.method)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copy_prompt, find_addresses, sanitize_file_name};
+    use crate::state::{DappIdentity, FileCapability};
+    use std::path::Path;
+
+    #[test]
+    fn finds_only_full_length_addresses() {
+        let text = "send to 0x70997970C51812dc3A010C7d01b50e0d17dc79C8 not 0x1234";
+        assert_eq!(
+            find_addresses(text),
+            vec!["0x70997970C51812dc3A010C7d01b50e0d17dc79C8"]
+        );
+    }
+
+    #[test]
+    fn copying_an_address_asks_the_user() {
+        let dapp = DappIdentity {
+            name: "Swap".to_string(),
+            ..DappIdentity::default()
+        };
+        let prompt = copy_prompt(&dapp, "pay 0x70997970C51812dc3A010C7d01b50e0d17dc79C8")
+            .expect("address copies are confirmed");
+        assert_eq!(
+            prompt.details["addresses"],
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+        );
+        assert!(copy_prompt(&dapp, "plain text").is_none());
+    }
+
+    #[test]
+    fn saved_files_keep_only_their_name() {
+        assert_eq!(
+            sanitize_file_name("../../.bashrc").as_deref(),
+            Some(".bashrc")
+        );
+        assert_eq!(
+            sanitize_file_name("C:\\Users\\me\\export.csv").as_deref(),
+            Some("export.csv")
+        );
+        assert_eq!(sanitize_file_name("backups/.."), None);
+    }
+
+    #[test]
+    fn saved_files_need_an_allowed_extension() {
+        let files = FileCapability {
+            save: true,
+            extensions: vec!["csv".to_string()],
+            ..FileCapability::default()
+        };
+        assert!(files.allows_path(Path::new("/tmp/Export.CSV")));
+        assert!(!files.allows_path(Path::new("/tmp/export.csv.sh")));
+        assert!(!files.allows_path(Path::new("/tmp/export")));
+    }
+
+    #[test]
+    fn any_extension_is_allowed_when_none_are_listed() {
+        assert!(FileCapability::default().allows_path(Path::new("notes.txt")));
+    }
+}
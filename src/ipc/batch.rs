@@ -0,0 +1,178 @@
+//! JSON-RPC batches from dapps, such as viem's batch transport. The calls
+//! run concurrently and come back as one array in the order they were sent.
+
+use anyhow::{Result, anyhow, bail};
+use futures::future::{self, BoxFuture, FutureExt};
+use serde_json::{Value, json};
+
+use crate::ipc_contract::{BatchCall, IpcRequest};
+use crate::state::AppState;
+
+/// Most calls one batch may carry.
+const MAX_BATCH_CALLS: usize = 100;
+
+/// Starts answering the batch in `req`. Read-only RPC calls go to the node,
+/// the chain id and accounts are answered locally, and anything else (signing,
+/// wallet methods, raw transactions) gets an error entry, since those need the
+/// user or the chain checks of a single request.
+pub(super) fn spawn_batch(state: &AppState, webview_id: &str, req: &IpcRequest) -> Result<()> {
+    let calls = req.batch.as_deref().unwrap_or_default();
+    if calls.is_empty() {
+        bail!("empty JSON-RPC batch");
+    }
+    if calls.len() > MAX_BATCH_CALLS {
+        bail!(
+            "JSON-RPC batch of {} calls exceeds the limit of {MAX_BATCH_CALLS}",
+            calls.len()
+        );
+    }
+    let ids: Vec<Value> = calls.iter().map(|call| call.id.clone()).collect();
+    let pending: Vec<_> = calls
+        .iter()
+        .map(|call| dispatch(state, webview_id, req.id, call))
+        .collect();
+    tracing::debug!(
+        webview_id,
+        ipc_id = req.id,
+        calls = calls.len(),
+        "rpc batch"
+    );
+    super::spawn_rpc_task(
+        state,
+        webview_id,
+        req.id,
+        format!("batch of {}", calls.len()),
+        async move { Ok(responses(ids, future::join_all(pending).await)) },
+    );
+    Ok(())
+}
+
+fn dispatch(
+    state: &AppState,
+    webview_id: &str,
+    ipc_id: u64,
+    call: &BatchCall,
+) -> BoxFuture<'static, Result<Value>> {
+    if let Some(value) = super::network_identity_response(state, &call.method) {
+        return future::ready(Ok(value)).boxed();
+    }
+    if call.method == "eth_accounts" {
+        let accounts = state
+            .tab_wallet(webview_id)
+            .map(|binding| binding.addresses())
+            .unwrap_or_default();
+        return future::ready(Ok(json!(accounts))).boxed();
+    }
+    let devnet = state.resolved.as_ref().is_some_and(|r| r.devnet_rpc);
+    if !is_batchable(&call.method, devnet) {
+        let method = call.method.clone();
+        return future::ready(Err(anyhow!("{method} cannot be sent in a batch"))).boxed();
+    }
+    super::rpc::trace_decoded_call(webview_id, &call.method, &call.params);
+    let req = IpcRequest {
+        id: ipc_id,
+        provider_id: None,
+        method: call.method.clone(),
+        params: if call.params.is_null() {
+            json!([])
+        } else {
+            call.params.clone()
+        },
+        batch: None,
+    };
+    super::rpc::proxy_rpc_async(state, &req).boxed()
+}
+
+/// Whether `method` may run inside a batch. Transactions and signatures are
+/// bound to the active chain, which the router checks against the dapp's
+/// manifest for single requests only, so they must be sent on their own.
+fn is_batchable(method: &str, devnet: bool) -> bool {
+    super::rpc::is_rpc_passthrough(method, devnet) && !super::router::is_chain_bound_method(method)
+}
+
+/// JSON-RPC response objects for `results`, each with its call's id.
+fn responses(ids: Vec<Value>, results: Vec<Result<Value>>) -> Value {
+    ids.into_iter()
+        .zip(results)
+        .map(|(id, result)| match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => {
+                let message = format!("{err:#}");
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": crate::connectivity::error_code(&message),
+                        "message": message,
+                    },
+                })
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_batchable, responses};
+    use crate::ipc_contract::IpcRequest;
+    use anyhow::anyhow;
+    use serde_json::json;
+
+    #[test]
+    fn parses_batches_and_answers_in_call_order() {
+        let req: IpcRequest = serde_json::from_value(json!({
+            "id": 7,
+            "providerId": "vibefi-provider",
+            "method": "vibefi_batch",
+            "batch": [
+                { "id": 1, "method": "eth_blockNumber" },
+                { "id": "b", "method": "eth_sendTransaction", "params": [{}] },
+            ],
+        }))
+        .unwrap();
+        let calls = req.batch.unwrap();
+        assert_eq!(calls[0].params, serde_json::Value::Null);
+        assert_eq!(calls[1].id, json!("b"));
+
+        let answered = responses(
+            calls.iter().map(|call| call.id.clone()).collect(),
+            vec![
+                Ok(json!("0x10")),
+                Err(anyhow!("eth_sendTransaction cannot be sent in a batch")),
+            ],
+        );
+        assert_eq!(
+            answered[0],
+            json!({ "jsonrpc": "2.0", "id": 1, "result": "0x10" })
+        );
+        assert_eq!(answered[1]["id"], "b");
+        assert_eq!(
+            answered[1]["error"]["message"],
+            "eth_sendTransaction cannot be sent in a batch"
+        );
+    }
+
+    #[test]
+    fn read_only_calls_are_batchable() {
+        assert!(is_batchable("eth_call", false));
+        assert!(is_batchable("eth_getLogs", false));
+        assert!(is_batchable("debug_traceTransaction", true));
+    }
+
+    #[test]
+    fn raw_transactions_are_refused_in_batches() {
+        assert!(!is_batchable("eth_sendRawTransaction", false));
+        assert!(!is_batchable("eth_sendRawTransaction", true));
+    }
+
+    #[test]
+    fn chain_bound_methods_are_refused_in_batches() {
+        for method in [
+            "eth_sendTransaction",
+            "eth_signTransaction",
+            "eth_signTypedData_v4",
+        ] {
+            assert!(!is_batchable(method, true), "{method}");
+        }
+    }
+}
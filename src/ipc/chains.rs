@@ -0,0 +1,258 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::ipc_contract::IpcRequest;
+use crate::settings::CustomNetwork;
+use crate::state::{AppState, UserEvent};
+
+use super::rpc::parse_hex_u64;
+
+/// EIP-3085 `wallet_addEthereumChain` parameter.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddChainParams {
+    chain_id: String,
+    chain_name: String,
+    rpc_urls: Vec<String>,
+    native_currency: NativeCurrency,
+    #[serde(default)]
+    block_explorer_urls: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NativeCurrency {
+    symbol: String,
+    decimals: u8,
+}
+
+/// Reads and checks the chain a dapp asks to add. RPC and explorer URLs must
+/// be HTTPS, or plain HTTP on loopback.
+fn parse_add_chain(params: &Value) -> Result<CustomNetwork> {
+    let params: AddChainParams = params
+        .get(0)
+        .cloned()
+        .ok_or_else(|| anyhow!("invalid params for wallet_addEthereumChain"))
+        .and_then(|value| {
+            serde_json::from_value(value).context("invalid wallet_addEthereumChain parameter")
+        })?;
+    let chain_id = parse_hex_u64(&params.chain_id)
+        .filter(|id| *id > 0)
+        .ok_or_else(|| anyhow!("invalid chainId: {}", params.chain_id))?;
+    let name = params.chain_name.trim();
+    if name.is_empty() {
+        bail!("chainName is required");
+    }
+    let rpc_url = params
+        .rpc_urls
+        .iter()
+        .map(|url| url.trim())
+        .find(|url| super::normalize_network_origin(url).is_some())
+        .ok_or_else(|| anyhow!("rpcUrls must contain an https URL"))?;
+    let symbol = params.native_currency.symbol.trim();
+    if !(2..=6).contains(&symbol.chars().count()) {
+        bail!("nativeCurrency.symbol must be 2 to 6 characters");
+    }
+    if params.native_currency.decimals != 18 {
+        bail!("nativeCurrency.decimals must be 18");
+    }
+    let explorer_url = params
+        .block_explorer_urls
+        .unwrap_or_default()
+        .into_iter()
+        .map(|url| url.trim().to_string())
+        .find(|url| super::normalize_network_origin(url).is_some());
+    Ok(CustomNetwork {
+        chain_id,
+        name: name.to_string(),
+        rpc_url: rpc_url.to_string(),
+        currency_symbol: symbol.to_string(),
+        explorer_url,
+    })
+}
+
+/// Chains a dapp may switch to: the configured one and those added with
+/// `wallet_addEthereumChain`.
+pub(super) fn is_known_chain(state: &AppState, chain_id: u64) -> bool {
+    let Some(resolved) = state.resolved.as_deref() else {
+        return true;
+    };
+    chain_id == resolved.chain_id
+        || resolved.config_path.as_deref().is_some_and(|path| {
+            crate::settings::load_networks(path)
+                .iter()
+                .any(|network| network.chain_id == chain_id)
+        })
+}
+
+/// Chains a tab may switch to as reported by `wallet_getProviderInfo`: the
+/// active one, the configured one, then added networks.
+pub(super) fn permitted_chains(state: &AppState) -> Vec<String> {
+    let active = state.wallet.lock().expect("wallet").chain.chain_id;
+    let mut chains = vec![active];
+    if let Some(resolved) = state.resolved.as_deref() {
+        let added = resolved
+            .config_path
+            .as_deref()
+            .map(crate::settings::load_networks)
+            .unwrap_or_default();
+        for chain_id in std::iter::once(resolved.chain_id).chain(added.iter().map(|n| n.chain_id)) {
+            if !chains.contains(&chain_id) {
+                chains.push(chain_id);
+            }
+        }
+    }
+    chains.into_iter().map(|id| format!("0x{id:x}")).collect()
+}
+
+/// Added network for the active chain, when it is not the configured one.
+pub(super) fn active_custom_network(state: &AppState) -> Option<CustomNetwork> {
+    let resolved = state.resolved.as_deref()?;
+    let chain_id = state.wallet.lock().expect("wallet").chain.chain_id;
+    if chain_id == resolved.chain_id {
+        return None;
+    }
+    crate::settings::load_networks(resolved.config_path.as_deref()?)
+        .into_iter()
+        .find(|network| network.chain_id == chain_id)
+}
+
+/// Error for switching to a chain the client does not know; carries the
+/// EIP-3326 "unrecognized chain" code via [`crate::error_hints`].
+pub(super) fn unrecognized_chain(chain_id: u64) -> anyhow::Error {
+    anyhow!(
+        "Unrecognized chain ID 0x{chain_id:x}. Try adding the chain using wallet_addEthereumChain first."
+    )
+}
+
+/// Handles `wallet_addEthereumChain` for every backend. The chain's RPC must
+/// report the chain id asked for, and the user approves before it is stored.
+/// Adding a chain does not switch to it.
+pub(super) fn handle_add_chain(
+    state: &AppState,
+    webview_id: &str,
+    req: &IpcRequest,
+) -> Result<Option<Value>> {
+    let network = parse_add_chain(&req.params)?;
+    let resolved = state
+        .resolved
+        .clone()
+        .ok_or_else(|| anyhow!("no network config loaded"))?;
+    if network.chain_id == resolved.chain_id {
+        return Ok(Some(Value::Null));
+    }
+    let config_path = resolved
+        .config_path
+        .clone()
+        .ok_or_else(|| anyhow!("adding networks needs a config file"))?;
+    if crate::settings::load_networks(&config_path).contains(&network) {
+        return Ok(Some(Value::Null));
+    }
+
+    let state = state.clone();
+    let webview_id = webview_id.to_string();
+    let ipc_id = req.id;
+    std::thread::spawn(move || {
+        let result = (|| -> Result<Value> {
+            let reported = resolved
+                .http_client
+                .post(&network.rpc_url)
+                .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": [] }))
+                .send()
+                .and_then(|res| res.json::<Value>())
+                .context("the chain's RPC did not answer")?
+                .get("result")
+                .and_then(Value::as_str)
+                .and_then(parse_hex_u64);
+            if reported != Some(network.chain_id) {
+                bail!(
+                    "the RPC at {} is not on chain {}",
+                    network.rpc_url,
+                    network.chain_id
+                );
+            }
+            let request = crate::prompt::PromptRequest::new(
+                "addChain",
+                "Add network",
+                format!(
+                    "Allow this dapp to add {} (chain {})?",
+                    network.name, network.chain_id
+                ),
+            )
+            .from_webview(&webview_id)
+            .with_details(json!({
+                "chainId": network.chain_id,
+                "name": network.name,
+                "rpcUrl": network.rpc_url,
+                "currencySymbol": network.currency_symbol,
+                "explorerUrl": network.explorer_url,
+            }));
+            if !crate::prompt::ask(&state, request) {
+                bail!("adding the network was declined by the user");
+            }
+            tracing::info!(
+                webview_id,
+                chain_id = network.chain_id,
+                rpc_url = %network.rpc_url,
+                "added custom network"
+            );
+            crate::settings::save_network(&config_path, network)?;
+            Ok(Value::Null)
+        })()
+        .map_err(|err| format!("{err:#}"));
+        let _ = state.proxy.send_event(UserEvent::RpcResult {
+            webview_id,
+            ipc_id,
+            result,
+        });
+    });
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_add_chain;
+    use serde_json::json;
+
+    fn base_params() -> serde_json::Value {
+        json!([{
+            "chainId": "0x2105",
+            "chainName": "Base",
+            "rpcUrls": ["http://rpc.example", "https://mainnet.base.org"],
+            "nativeCurrency": { "name": "Ether", "symbol": "ETH", "decimals": 18 },
+            "blockExplorerUrls": ["https://basescan.org"],
+        }])
+    }
+
+    #[test]
+    fn reads_eip3085_chains_with_the_first_https_rpc() {
+        let network = parse_add_chain(&base_params()).unwrap();
+        assert_eq!(network.chain_id, 8453);
+        assert_eq!(network.rpc_url, "https://mainnet.base.org");
+        assert_eq!(
+            network.explorer_url.as_deref(),
+            Some("https://basescan.org")
+        );
+    }
+
+    #[test]
+    fn refuses_plain_http_rpcs() {
+        let mut params = base_params();
+        params[0]["rpcUrls"] = json!(["http://rpc.example"]);
+        assert!(parse_add_chain(&params).is_err());
+    }
+
+    #[test]
+    fn accepts_plain_http_on_localhost() {
+        let mut params = base_params();
+        params[0]["rpcUrls"] = json!(["http://localhost:8545"]);
+        assert!(parse_add_chain(&params).is_ok());
+    }
+
+    #[test]
+    fn refuses_native_currencies_without_18_decimals() {
+        let mut params = base_params();
+        params[0]["nativeCurrency"]["decimals"] = json!(6);
+        assert!(parse_add_chain(&params).is_err());
+    }
+}
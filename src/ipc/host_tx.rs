@@ -0,0 +1,108 @@
+use alloy_primitives::{Address, Bytes, TxKind};
+use alloy_rpc_types_eth::{TransactionInput, TransactionRequest};
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+
+use crate::state::{AppState, WalletBackend};
+
+use super::signer::{SignContext, wallet_signer};
+
+/// Sends a transaction on behalf of the host UI (not a dapp tab) from the first
+/// connected backend, preferring a smart account, then hardware, then a
+/// remote signer, then WalletConnect, then the local signer. Returns the
+/// transaction hash.
+pub(crate) fn send_host_transaction(state: &AppState, to: Address, data: Bytes) -> Result<String> {
+    state.lock.ensure_unlocked()?;
+    let backend = host_backend(state)
+        .ok_or_else(|| anyhow!("Connect a wallet before submitting this transaction"))?;
+    tracing::info!(
+        ?backend,
+        to = format!("{:#x}", to),
+        "sending host transaction"
+    );
+    let mut tx = TransactionRequest {
+        to: Some(TxKind::Call(to)),
+        input: TransactionInput::new(data),
+        ..Default::default()
+    };
+
+    let Some(signer) = wallet_signer(backend) else {
+        let hash = send_walletconnect_transaction(state, tx)?;
+        crate::tx_history::record(state, None, &hash);
+        return Ok(hash);
+    };
+    let sender = signer
+        .accounts(state)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("The {} wallet has no accounts", backend.as_str()))?;
+    tx.from = Some(
+        sender
+            .parse()
+            .with_context(|| format!("invalid {} account address", backend.as_str()))?,
+    );
+    let ctx = SignContext {
+        state,
+        webview_id: None,
+        account: sender,
+    };
+    let hash = signer.send_transaction(&ctx, tx)?;
+    crate::tx_history::record(state, None, &hash);
+    Ok(hash)
+}
+
+fn send_walletconnect_transaction(state: &AppState, tx: TransactionRequest) -> Result<String> {
+    let sender = state
+        .wallet
+        .lock()
+        .expect("wallet")
+        .walletconnect_accounts
+        .first()
+        .cloned()
+        .ok_or_else(|| anyhow!("WalletConnect session has no accounts"))?;
+    let bridge = state
+        .walletconnect
+        .lock()
+        .expect("walletconnect")
+        .as_ref()
+        .ok_or_else(|| anyhow!("walletconnect bridge unavailable"))?
+        .clone();
+    let mut tx = serde_json::to_value(&tx)?;
+    tx["from"] = Value::String(sender);
+    let (result, _events) = bridge
+        .lock()
+        .expect("walletconnect_bridge")
+        .request("eth_sendTransaction", Value::Array(vec![tx]))?;
+    result
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("eth_sendTransaction returned non-string hash"))
+}
+
+fn host_backend(state: &AppState) -> Option<WalletBackend> {
+    if state.smart_account.lock().expect("smart_account").is_some() {
+        return Some(WalletBackend::SmartAccount);
+    }
+    if state
+        .hardware_signer
+        .lock()
+        .expect("hardware_signer")
+        .is_some()
+    {
+        return Some(WalletBackend::Hardware);
+    }
+    if state.remote_signer.lock().expect("remote_signer").is_some() {
+        return Some(WalletBackend::Remote);
+    }
+    let walletconnect_connected = state.walletconnect.lock().expect("walletconnect").is_some()
+        && !state
+            .wallet
+            .lock()
+            .expect("wallet")
+            .walletconnect_accounts
+            .is_empty();
+    if walletconnect_connected {
+        return Some(WalletBackend::WalletConnect);
+    }
+    state.local_signer().map(|_| WalletBackend::Local)
+}
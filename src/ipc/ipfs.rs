@@ -1,29 +1,19 @@
 use anyhow::{Result, anyhow, bail};
-use serde::Deserialize;
 use serde_json::{Value, json};
 use std::cmp::{max, min};
 
 use crate::config::IpfsFetchBackend;
+use crate::download::read_limited;
 use crate::ipc_contract::IpcRequest;
-use crate::ipfs_helper::{IpfsHelperBridge, IpfsHelperConfig};
+use crate::manifest::{self, BundleManifest};
 use crate::state::{AppRuntimeCapabilities, AppState, IpfsCapabilityRule, UserEvent};
+use crate::verified_fetch::VerifiedFetcher;
 
-const DEFAULT_MAX_BYTES: usize = 512 * 1024;
+pub(super) const DEFAULT_MAX_BYTES: usize = 512 * 1024;
+const MAX_MANIFEST_BYTES: u64 = 4 * 1024 * 1024;
 const MAX_SNIPPET_LINES_DEFAULT: usize = 200;
 const IPFS_PROGRESS_EVENT: &str = "vibefiIpfsProgress";
 
-#[derive(Debug, Deserialize)]
-struct ManifestFileEntry {
-    path: String,
-    bytes: usize,
-}
-
-#[derive(Debug, Deserialize)]
-struct BundleManifest {
-    #[serde(default)]
-    files: Vec<ManifestFileEntry>,
-}
-
 fn normalize_gateway(gateway: &str) -> String {
     gateway.trim_end_matches('/').to_string()
 }
@@ -75,7 +65,7 @@ fn resolve_effective_ipfs_fetch_config(state: &AppState) -> Result<(IpfsFetchBac
     ))
 }
 
-fn guess_mime_from_path(path: &str) -> Option<String> {
+pub(super) fn guess_mime_from_path(path: &str) -> Option<String> {
     let lower = path.to_ascii_lowercase();
     if lower.ends_with(".png") {
         return Some("image/png".to_string());
@@ -92,6 +82,14 @@ fn guess_mime_from_path(path: &str) -> Option<String> {
     None
 }
 
+/// Image reads return raster formats only; SVG can carry script.
+pub(super) fn ensure_raster_image(mime: &str) -> Result<()> {
+    if !mime.starts_with("image/") || mime.contains("svg") {
+        bail!("image reads only support raster image payloads");
+    }
+    Ok(())
+}
+
 fn normalize_path(input: Option<&str>) -> Result<String> {
     let raw = input.unwrap_or_default().trim();
     let mut path = raw.trim_start_matches('/').to_string();
@@ -308,33 +306,24 @@ fn load_manifest_listing(
                 let body = res.text().unwrap_or_default();
                 bail!("failed to fetch manifest: {}", body);
             }
-            res.bytes()?.to_vec()
+            read_limited(res, MAX_MANIFEST_BYTES)?
         }
         IpfsFetchBackend::Helia => {
-            let mut helper = IpfsHelperBridge::spawn(IpfsHelperConfig {
-                gateways: resolved.ipfs_helia_gateways.clone(),
-                routers: resolved.ipfs_helia_routers.clone(),
-            })?;
-            let url = format!("ipfs://{cid}/manifest.json");
-            let result = helper.fetch(&url, Some(resolved.ipfs_helia_timeout_ms))?;
-            if !(200..300).contains(&result.status) {
-                bail!("failed to fetch manifest with status {}", result.status);
-            }
-            result.body
+            VerifiedFetcher::new(resolved).fetch(cid, "manifest.json", MAX_MANIFEST_BYTES)?
         }
     };
     on_progress(58, "Parsing manifest.json...");
-    let manifest: BundleManifest = serde_json::from_slice(&raw)?;
-    Ok(manifest)
+    manifest::parse(&raw)
 }
 
-fn fetch_ipfs_bytes(
+pub(super) fn fetch_ipfs_bytes(
     state: &AppState,
     cid: &str,
     path: &str,
     max_bytes: usize,
     mut on_progress: impl FnMut(u8, &str),
 ) -> Result<(Vec<u8>, Option<String>)> {
+    let _span = tracing::info_span!("ipfs_fetch", cid, path).entered();
     let resolved = state
         .resolved
         .as_ref()
@@ -365,32 +354,19 @@ fn fetch_ipfs_bytes(
                 .get(reqwest::header::CONTENT_TYPE)
                 .and_then(|v| v.to_str().ok())
                 .map(|s| s.to_string());
-            let bytes = res.bytes()?.to_vec();
+            // Enforced while streaming so an oversized body is never fully buffered.
+            let bytes = read_limited(res, max_bytes as u64)?;
             on_progress(82, "Validating payload constraints...");
-            if bytes.len() > max_bytes {
-                bail!("payload exceeds maxBytes");
-            }
             Ok((bytes, content_type))
         }
         IpfsFetchBackend::Helia => {
-            let mut helper = IpfsHelperBridge::spawn(IpfsHelperConfig {
-                gateways: resolved.ipfs_helia_gateways.clone(),
-                routers: resolved.ipfs_helia_routers.clone(),
-            })?;
-            let url = if path.is_empty() {
-                format!("ipfs://{cid}")
-            } else {
-                format!("ipfs://{cid}/{path}")
-            };
-            let result = helper.fetch(&url, Some(resolved.ipfs_helia_timeout_ms))?;
-            if !(200..300).contains(&result.status) {
-                bail!("ipfs fetch failed with status {}", result.status);
-            }
+            // Blocks are verified as they arrive and the size is enforced
+            // while the file is put together.
+            let body = VerifiedFetcher::new(resolved)
+                .fetch(cid, path, max_bytes as u64)
+                .map_err(|err| anyhow!("ipfs fetch failed: {err:#}"))?;
             on_progress(74, "Validating payload constraints...");
-            if result.body.len() > max_bytes {
-                bail!("payload exceeds maxBytes");
-            }
-            Ok((result.body, guess_mime_from_path(path)))
+            Ok((body, guess_mime_from_path(path)))
         }
     }
 }
@@ -618,9 +594,7 @@ fn handle_read(
         "image" => {
             emit("decode", 90, "Validating image payload...");
             let mime = content_type.unwrap_or_else(|| "application/octet-stream".to_string());
-            if !mime.starts_with("image/") || mime.contains("svg") {
-                bail!("image reads only support raster image payloads");
-            }
+            ensure_raster_image(&mime)?;
             emit("done", 100, "Image read complete.");
             Ok(Some(json!({
                 "kind": "image",
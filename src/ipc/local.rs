@@ -1,216 +1,69 @@
 use alloy_network::TxSignerSync;
-use alloy_primitives::{B256, Signature};
+use alloy_primitives::Signature;
 use alloy_signer::SignerSync;
+use alloy_signer_local::PrivateKeySigner;
 use anyhow::{Result, anyhow};
-use serde_json::Value;
-use wry::WebView;
+use std::sync::Arc;
 
-use crate::ipc_contract::IpcRequest;
-use crate::state::{AppState, ProviderInfo, UserEvent};
+use crate::state::{AppState, SignerCapabilities, WalletBackend};
 
-use super::rpc::{
-    build_filled_tx_request, build_typed_tx, decode_0x_hex, encode_signed_typed_tx_hex,
-    parse_hex_u64, send_raw_transaction,
-};
-use super::{emit_accounts_changed, emit_chain_changed, try_spawn_rpc_passthrough};
+use super::rpc::{FilledTransaction, build_typed_tx, encode_signed_typed_tx_hex};
+use super::signer::{SignContext, WalletSigner, typed_data_hash};
 
-pub(super) fn handle_local_ipc(
-    webview: &WebView,
-    state: &AppState,
-    webview_id: &str,
-    req: &IpcRequest,
-) -> Result<Option<Value>> {
-    if let Some(value) = super::network_identity_response(state, req.method.as_str()) {
-        return Ok(Some(value));
-    }
+/// The developer key from config, or the keys unlocked from the keystore.
+pub(super) struct LocalWallet;
 
-    match req.method.as_str() {
-        "eth_accounts" => {
-            let ws = state
-                .wallet
-                .lock()
-                .expect("poisoned wallet lock while handling local eth_accounts");
-            if ws.authorized {
-                if let Some(account) = ws.account.clone().or_else(|| state.local_signer_address()) {
-                    Ok(Some(Value::Array(vec![Value::String(account)])))
-                } else {
-                    Ok(Some(Value::Array(vec![])))
-                }
-            } else {
-                Ok(Some(Value::Array(vec![])))
-            }
-        }
-        "eth_requestAccounts" => {
-            let account = state
-                .local_signer_address()
-                .ok_or_else(|| anyhow!("Local signer unavailable"))?;
-            {
-                let mut ws = state
-                    .wallet
-                    .lock()
-                    .expect("poisoned wallet lock while authorizing local wallet");
-                ws.authorized = true;
-                ws.account = Some(account.clone());
-            }
-            emit_accounts_changed(webview, vec![account.clone()]);
-            tracing::info!(webview_id, account, "local wallet authorized account");
-            Ok(Some(Value::Array(vec![Value::String(account)])))
-        }
-        "wallet_switchEthereumChain" => {
-            let chain_id_hex = req
-                .params
-                .get(0)
-                .and_then(|v| v.get("chainId"))
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow!("invalid params for wallet_switchEthereumChain"))?;
-            let chain_id = parse_hex_u64(chain_id_hex).ok_or_else(|| anyhow!("invalid chainId"))?;
+impl WalletSigner for LocalWallet {
+    fn backend(&self) -> WalletBackend {
+        WalletBackend::Local
+    }
 
-            {
-                let mut ws = state
-                    .wallet
-                    .lock()
-                    .expect("poisoned wallet lock while switching local chain");
-                ws.chain.chain_id = chain_id;
-            }
-            let chain_hex = format!("0x{:x}", chain_id);
-            emit_chain_changed(webview, chain_hex);
-            tracing::info!(
-                webview_id,
-                chain_id = format!("0x{:x}", chain_id),
-                "local wallet switched chain"
-            );
-            Ok(Some(Value::Null))
-        }
-        "personal_sign" => {
-            let msg = req
-                .params
-                .get(0)
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow!("invalid params for personal_sign"))?;
-            let bytes = if let Some(b) = decode_0x_hex(msg) {
-                b
-            } else {
-                msg.as_bytes().to_vec()
-            };
+    fn provider_name(&self) -> &'static str {
+        "vibefi-local-wallet"
+    }
 
-            let signer = state
-                .local_signer()
-                .ok_or_else(|| anyhow!("Local signer unavailable"))?;
-            let sig = signer
-                .sign_message_sync(&bytes)
-                .map_err(|e| anyhow!("sign_message failed: {e}"))?;
-            Ok(Some(Value::String(format!(
-                "0x{}",
-                hex::encode(sig.as_bytes())
-            ))))
+    fn capabilities(&self) -> SignerCapabilities {
+        SignerCapabilities {
+            switch_chain: true,
+            raw_transactions: true,
+            client_confirmation: false,
         }
-        "eth_signTypedData_v4" => {
-            let typed_data_json = req
-                .params
-                .get(1)
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow!("invalid params for eth_signTypedData_v4"))?;
-            let hash = alloy_primitives::keccak256(typed_data_json.as_bytes());
-            let signer = state
-                .local_signer()
-                .ok_or_else(|| anyhow!("Local signer unavailable"))?;
-            let sig = signer
-                .sign_hash_sync(&B256::from(hash))
-                .map_err(|e| anyhow!("sign_hash failed: {e}"))?;
-            Ok(Some(Value::String(format!(
-                "0x{}",
-                hex::encode(sig.as_bytes())
-            ))))
-        }
-        "eth_sendTransaction" => {
-            let ws = state
-                .wallet
-                .lock()
-                .expect("poisoned wallet lock while handling local eth_sendTransaction");
-            if !ws.authorized {
-                return Err(anyhow!("Unauthorized: call eth_requestAccounts first"));
-            }
-            drop(ws);
+    }
 
-            let tx_obj = req
-                .params
-                .get(0)
-                .cloned()
-                .ok_or_else(|| anyhow!("invalid params for eth_sendTransaction"))?;
+    fn accounts(&self, state: &AppState) -> Result<Vec<String>> {
+        Ok(state.local_accounts())
+    }
 
-            let proxy = state.proxy.clone();
-            let state_clone = state.clone();
-            let ipc_id = req.id;
-            let wv_id = webview_id.to_string();
-            tracing::info!(
-                webview_id,
-                ipc_id,
-                "local wallet spawning eth_sendTransaction worker"
-            );
+    fn sign_message(&self, ctx: &SignContext, message: &[u8]) -> Result<String> {
+        let signer = local_signer(ctx)?;
+        let sig = signer
+            .sign_message_sync(message)
+            .map_err(|e| anyhow!("sign_message failed: {e}"))?;
+        Ok(format!("0x{}", hex::encode(sig.as_bytes())))
+    }
 
-            std::thread::spawn(move || {
-                let result = (|| -> Result<Value> {
-                    let tx_request = build_filled_tx_request(&state_clone, tx_obj)?;
-                    let mut tx = build_typed_tx(tx_request)?;
-                    let signer = state_clone
-                        .local_signer()
-                        .ok_or_else(|| anyhow!("Local signer unavailable"))?;
-                    let sig: Signature = signer
-                        .sign_transaction_sync(&mut tx)
-                        .map_err(|e| anyhow!("sign_transaction failed: {e}"))?;
-                    let raw_tx_hex = encode_signed_typed_tx_hex(tx, sig);
-                    let tx_hash = send_raw_transaction(&state_clone, raw_tx_hex)?;
-                    Ok(Value::String(tx_hash))
-                })()
-                .map_err(|e| e.to_string());
-                if let Err(err) = &result {
-                    tracing::warn!(
-                        webview_id = %wv_id,
-                        ipc_id,
-                        error = %err,
-                        "local wallet eth_sendTransaction worker failed"
-                    );
-                } else {
-                    tracing::debug!(
-                        webview_id = %wv_id,
-                        ipc_id,
-                        "local wallet eth_sendTransaction worker succeeded"
-                    );
-                }
-                if let Err(err) = proxy.send_event(UserEvent::RpcResult {
-                    webview_id: wv_id,
-                    ipc_id,
-                    result,
-                }) {
-                    tracing::warn!(
-                        error = %err,
-                        "failed to send local wallet RpcResult event"
-                    );
-                }
-            });
+    fn sign_typed_data(&self, ctx: &SignContext, typed_data_json: &str) -> Result<String> {
+        let hash = typed_data_hash(typed_data_json)?;
+        let signer = local_signer(ctx)?;
+        let sig = signer
+            .sign_hash_sync(&hash)
+            .map_err(|e| anyhow!("sign_hash failed: {e}"))?;
+        Ok(format!("0x{}", hex::encode(sig.as_bytes())))
+    }
 
-            Ok(None)
-        }
-        "wallet_getProviderInfo" => {
-            let ws = state
-                .wallet
-                .lock()
-                .expect("poisoned wallet lock while building local provider info");
-            let info = ProviderInfo {
-                name: "vibefi-local-wallet".to_string(),
-                chain_id: state.chain_id_hex(),
-                backend: "local",
-                account: ws.account.clone().or_else(|| state.local_signer_address()),
-                walletconnect_uri: None,
-            };
-            Ok(Some(serde_json::to_value(info)?))
-        }
-        _ => {
-            if try_spawn_rpc_passthrough(state, webview_id, req) {
-                Ok(None)
-            } else {
-                Err(anyhow!("Unsupported method: {}", req.method))
-            }
-        }
+    fn sign_transaction(&self, ctx: &SignContext, tx: FilledTransaction) -> Result<String> {
+        let mut tx = build_typed_tx(tx.tx)?;
+        let signer = local_signer(ctx)?;
+        let sig: Signature = signer
+            .sign_transaction_sync(&mut tx)
+            .map_err(|e| anyhow!("sign_transaction failed: {e}"))?;
+        Ok(encode_signed_typed_tx_hex(tx, sig))
     }
 }
+
+/// The key for the account the request names.
+fn local_signer(ctx: &SignContext) -> Result<Arc<PrivateKeySigner>> {
+    ctx.state
+        .local_signer_for(&ctx.account)
+        .ok_or_else(|| anyhow!("Local signer unavailable"))
+}
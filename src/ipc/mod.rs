@@ -1,20 +1,39 @@
+mod accounts;
+mod app;
+mod batch;
+mod chains;
 mod hardware;
+mod host_tx;
 mod ipfs;
 mod local;
+mod network;
+mod nft;
+mod reconnect;
+mod remote_signer;
 mod router;
 mod rpc;
 mod selector;
 mod settings;
+mod signer;
+mod smart_account;
 mod walletconnect;
 
 use anyhow::Result;
 use serde_json::Value;
+use std::future::Future;
 use wry::WebView;
 
 use crate::ipc_contract::IpcRequest;
-use crate::state::{AppState, UserEvent};
+use crate::state::{
+    AppState, ProviderCapabilities, ProviderInfo, TabBadge, UserEvent, WalletBackend,
+};
 
+pub(crate) use host_tx::send_host_transaction;
+pub use network::{is_loopback_origin, normalize_network_origin};
+pub use reconnect::spawn_reconnect;
 pub use router::handle_ipc;
+pub(crate) use rpc::rpc_request;
+pub(crate) use settings::open_directory_in_file_manager;
 pub use walletconnect::handle_walletconnect_connect_result;
 
 pub fn respond_ok(webview: &WebView, id: u64, value: Value) -> Result<()> {
@@ -48,6 +67,36 @@ pub fn respond_value_result(
     }
 }
 
+/// Methods that ask the user to authorize something with their wallet.
+pub(crate) fn is_wallet_approval_method(method: &str) -> bool {
+    matches!(
+        method,
+        "eth_requestAccounts"
+            | "eth_sendTransaction"
+            | "eth_signTransaction"
+            | "eth_sign"
+            | "personal_sign"
+            | "eth_signTypedData"
+            | "eth_signTypedData_v3"
+            | "eth_signTypedData_v4"
+            | "wallet_switchEthereumChain"
+            | "wallet_addEthereumChain"
+    )
+}
+
+/// Address a signing request names as its signer, if any.
+pub(crate) fn request_signer(req: &IpcRequest) -> Option<String> {
+    let address = match req.method.as_str() {
+        "eth_sendTransaction" | "eth_signTransaction" => req.params.get(0)?.get("from")?,
+        "personal_sign" => req.params.get(1)?,
+        "eth_sign" | "eth_signTypedData" | "eth_signTypedData_v3" | "eth_signTypedData_v4" => {
+            req.params.get(0)?
+        }
+        _ => return None,
+    };
+    address.as_str().map(str::to_string)
+}
+
 pub fn network_identity_response(state: &AppState, method: &str) -> Option<Value> {
     match method {
         "eth_chainId" => Some(Value::String(state.chain_id_hex())),
@@ -64,44 +113,92 @@ pub fn network_identity_response(state: &AppState, method: &str) -> Option<Value
     }
 }
 
+/// `wallet_getProviderInfo` for a tab, in the same shape whichever backend
+/// (if any) it is bound to.
+fn provider_info(
+    state: &AppState,
+    webview_id: &str,
+    name: &str,
+    backend: Option<WalletBackend>,
+    account: Option<String>,
+    capabilities: ProviderCapabilities,
+) -> ProviderInfo {
+    let (chain_id, walletconnect_uri) = {
+        let wallet = state.wallet.lock().expect("wallet");
+        let uri = wallet
+            .walletconnect_uri
+            .clone()
+            .filter(|_| backend == Some(WalletBackend::WalletConnect));
+        (format!("0x{:x}", wallet.chain.chain_id), uri)
+    };
+    ProviderInfo {
+        name: name.to_string(),
+        chain_id,
+        backend: backend.map_or("none", WalletBackend::as_str),
+        account,
+        accounts: state.accounts_for(webview_id),
+        chains: chains::permitted_chains(state),
+        walletconnect_uri,
+        capabilities,
+        protocol_version: crate::client_info::PROTOCOL_VERSION,
+    }
+}
+
 pub fn try_spawn_rpc_passthrough(state: &AppState, webview_id: &str, req: &IpcRequest) -> bool {
-    if state.resolved.is_none() || !rpc::is_rpc_passthrough(req.method.as_str()) {
+    let Some(resolved) = state.resolved.as_ref() else {
+        return false;
+    };
+    if !rpc::is_rpc_passthrough(req.method.as_str(), resolved.devnet_rpc) {
         return false;
     }
+    rpc::trace_decoded_call(webview_id, &req.method, &req.params);
+    spawn_rpc_task(
+        state,
+        webview_id,
+        req.id,
+        req.method.clone(),
+        rpc::proxy_rpc_async(state, req),
+    );
+    true
+}
 
+/// Runs `task` on the shared RPC runtime and answers `ipc_id` with its
+/// result through `RpcResult`, counting it as pending on the tab meanwhile.
+fn spawn_rpc_task(
+    state: &AppState,
+    webview_id: &str,
+    ipc_id: u64,
+    method: String,
+    task: impl Future<Output = Result<Value>> + Send + 'static,
+) {
     let new_count = state.increment_rpc_pending(webview_id);
-    if let Err(err) = state.proxy.send_event(UserEvent::RpcPendingChanged {
+    if let Err(err) = state.proxy.send_event(UserEvent::TabBadgeChanged {
         webview_id: webview_id.to_string(),
-        count: new_count,
+        badge: TabBadge::PendingRpc(new_count),
     }) {
-        tracing::warn!(error = %err, "failed to send RpcPendingChanged on spawn");
+        tracing::warn!(error = %err, "failed to send pending RPC badge on spawn");
     }
 
     let proxy = state.proxy.clone();
-    let state_clone = state.clone();
-    let ipc_id = req.id;
-    let method = req.method.clone();
-    let params = req.params.clone();
     let wv_id = webview_id.to_string();
     tracing::debug!(
         webview_id,
         ipc_id = ipc_id,
         method = %method,
-        "spawning rpc passthrough worker"
+        "queueing rpc passthrough request"
     );
-    std::thread::spawn(move || {
-        let request = IpcRequest {
-            id: ipc_id,
-            provider_id: None,
-            method,
-            params,
-        };
-        let result = rpc::proxy_rpc(&state_clone, &request).map_err(|e| e.to_string());
+    let spawned = state.rpc_client.spawn(move |permit| async move {
+        let result = match crate::connectivity::ensure_online() {
+            Ok(()) => task.await,
+            Err(e) => Err(e),
+        }
+        .map_err(|e| e.to_string());
+        drop(permit);
         if let Err(err) = &result {
             tracing::warn!(
                 webview_id = %wv_id,
                 ipc_id,
-                method = %request.method,
+                method = %method,
                 error = %err,
                 "rpc passthrough worker failed"
             );
@@ -109,7 +206,7 @@ pub fn try_spawn_rpc_passthrough(state: &AppState, webview_id: &str, req: &IpcRe
             tracing::debug!(
                 webview_id = %wv_id,
                 ipc_id,
-                method = %request.method,
+                method = %method,
                 "rpc passthrough worker succeeded"
             );
         }
@@ -121,8 +218,17 @@ pub fn try_spawn_rpc_passthrough(state: &AppState, webview_id: &str, req: &IpcRe
             tracing::warn!(error = %err, "failed to send RpcResult event from passthrough worker");
         }
     });
-
-    true
+    if let Err(err) = spawned {
+        // Answer through the usual path so the pending count drops again.
+        tracing::warn!(webview_id, ipc_id, error = %err, "rpc passthrough queue full");
+        if let Err(err) = state.proxy.send_event(UserEvent::RpcResult {
+            webview_id: webview_id.to_string(),
+            ipc_id,
+            result: Err(err.to_string()),
+        }) {
+            tracing::warn!(error = %err, "failed to send RpcResult event for a rejected request");
+        }
+    }
 }
 
 pub fn emit_accounts_changed(webview: &WebView, addrs: Vec<String>) {
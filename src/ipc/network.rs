@@ -0,0 +1,332 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Value, json};
+use std::future::Future;
+use std::time::Duration;
+
+use crate::ipc_contract::IpcRequest;
+use crate::state::AppState;
+
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+const MAX_REQUEST_BODY_BYTES: usize = 64 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Request headers a dapp may set; anything else (cookies, auth, host overrides) is dropped.
+const FORWARDED_REQUEST_HEADERS: &[&str] = &["accept", "content-type"];
+
+/// Response media types handed to dapps, besides structured `+json` types.
+/// Markup, scripts and binaries are refused.
+const ALLOWED_RESPONSE_TYPES: &[&str] = &["application/json", "text/plain", "text/csv"];
+
+/// Normalizes a manifest `network.allow` entry to `scheme://host[:port]`.
+/// Plain HTTP is only accepted for loopback hosts, which only local project
+/// tabs are granted (see [`is_loopback_origin`]).
+pub fn normalize_network_origin(raw: &str) -> Option<String> {
+    let url = reqwest::Url::parse(raw.trim()).ok()?;
+    let host = url.host_str()?;
+    match url.scheme() {
+        "https" => {}
+        "http" if is_loopback_host(host) => {}
+        _ => {
+            tracing::warn!(origin = raw, "ignoring unsupported network.allow origin");
+            return None;
+        }
+    }
+    Some(url.origin().ascii_serialization())
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host.to_ascii_lowercase().ends_with(".localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback() || ip.is_unspecified())
+}
+
+/// Whether a normalized origin points at this machine. Fetching it would let
+/// a dapp reach local services, so it is only granted to tabs built from a
+/// local project.
+pub fn is_loopback_origin(origin: &str) -> bool {
+    reqwest::Url::parse(origin)
+        .ok()
+        .and_then(|url| url.host_str().map(is_loopback_host))
+        .unwrap_or(false)
+}
+
+fn origin_allowed(allow: &[String], url: &reqwest::Url) -> bool {
+    let origin = url.origin().ascii_serialization();
+    allow.iter().any(|allowed| allowed == &origin)
+}
+
+/// GETs `url` for the client itself under the same rules as dapp fetches:
+/// HTTPS (or loopback HTTP), no redirects, and a size cap. Returns the body
+/// and its content type.
+pub(super) fn get_limited(
+    url: &reqwest::Url,
+    max_bytes: usize,
+) -> Result<(Vec<u8>, Option<String>)> {
+    if normalize_network_origin(url.as_str()).is_none() {
+        bail!("only https URLs can be fetched: {url}");
+    }
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .context("build http client")?;
+    let res = client
+        .get(url.clone())
+        .send()
+        .context("http request failed")?
+        .error_for_status()
+        .context("http request failed")?;
+    let content_type = res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    if res
+        .content_length()
+        .is_some_and(|len| len > max_bytes as u64)
+    {
+        bail!("response exceeds {max_bytes} bytes");
+    }
+    let bytes = crate::download::read_limited(res, max_bytes as u64)?;
+    Ok((bytes, content_type))
+}
+
+/// Media type of a response dapps may read, without parameters.
+fn allowed_content_type(content_type: Option<&str>) -> Option<String> {
+    let essence = content_type?.split(';').next()?.trim().to_ascii_lowercase();
+    (ALLOWED_RESPONSE_TYPES.contains(&essence.as_str()) || essence.ends_with("+json"))
+        .then_some(essence)
+}
+
+/// Returns JSON re-serialized, so only well-formed JSON reaches the dapp,
+/// and text without a byte-order mark or control characters other than tabs
+/// and line breaks.
+fn sanitize_body(media_type: &str, body: &str) -> Result<String> {
+    let body = body.strip_prefix('\u{feff}').unwrap_or(body);
+    if media_type == "application/json" || media_type.ends_with("+json") {
+        let value: Value = serde_json::from_str(body).context("response is not valid JSON")?;
+        return Ok(value.to_string());
+    }
+    Ok(body
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect())
+}
+
+fn parse_fetch_request(req: &IpcRequest) -> Result<(reqwest::Url, reqwest::Method, Value)> {
+    let params = req
+        .params
+        .as_array()
+        .ok_or_else(|| anyhow!("params must be an array"))?;
+    let raw_url = params
+        .first()
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("url is required"))?;
+    let url = reqwest::Url::parse(raw_url.trim()).context("invalid url")?;
+    let options = params.get(1).cloned().unwrap_or(Value::Null);
+    let method = match options
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or("GET")
+        .to_ascii_uppercase()
+        .as_str()
+    {
+        "GET" => reqwest::Method::GET,
+        "POST" => reqwest::Method::POST,
+        other => bail!("unsupported http method: {other}"),
+    };
+    Ok((url, method, options))
+}
+
+/// Checks a dapp fetch against its capabilities and returns the request,
+/// ready to run on the shared RPC runtime.
+fn http_fetch(
+    state: &AppState,
+    webview_id: &str,
+    req: &IpcRequest,
+) -> Result<impl Future<Output = Result<Value>> + Send + 'static> {
+    let caps = state
+        .app_capabilities_for(webview_id)
+        .ok_or_else(|| anyhow!("network capability is not available for this webview"))?;
+    let (url, method, options) = parse_fetch_request(req)?;
+    if !origin_allowed(&caps.network_allow, &url) {
+        tracing::warn!(webview_id, url = %url, "network capability denied");
+        bail!("network capability denied");
+    }
+
+    // The shared fetch client does not follow redirects, so a permitted
+    // origin cannot bounce us elsewhere.
+    let mut builder = state
+        .rpc_client
+        .fetch_http()
+        .request(method.clone(), url.clone())
+        .timeout(REQUEST_TIMEOUT);
+    if let Some(headers) = options.get("headers").and_then(Value::as_object) {
+        for (name, value) in headers {
+            let lower = name.to_ascii_lowercase();
+            if !FORWARDED_REQUEST_HEADERS.contains(&lower.as_str()) {
+                continue;
+            }
+            if let Some(value) = value.as_str() {
+                builder = builder.header(lower, value);
+            }
+        }
+    }
+    if let Some(body) = options.get("body").and_then(Value::as_str) {
+        if method != reqwest::Method::POST {
+            bail!("request body is only allowed for POST");
+        }
+        if body.len() > MAX_REQUEST_BODY_BYTES {
+            bail!("request body exceeds {MAX_REQUEST_BODY_BYTES} bytes");
+        }
+        builder = builder.body(body.to_string());
+    }
+
+    tracing::debug!(webview_id, %method, url = %url, "vibefi_httpFetch");
+    let webview_id = webview_id.to_string();
+    Ok(async move {
+        let res = builder.send().await.context("http request failed")?;
+        read_fetch_response(&webview_id, &url, res).await
+    })
+}
+
+async fn read_fetch_response(
+    webview_id: &str,
+    url: &reqwest::Url,
+    mut res: reqwest::Response,
+) -> Result<Value> {
+    let status = res.status().as_u16();
+    let content_type = res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let Some(media_type) = allowed_content_type(content_type.as_deref()) else {
+        tracing::warn!(webview_id, url = %url, ?content_type, "refused http fetch response type");
+        bail!(
+            "response type {} is not allowed (HTTP {status})",
+            content_type.as_deref().unwrap_or("(none)")
+        );
+    };
+    if let Some(len) = res.content_length() {
+        if len > MAX_RESPONSE_BYTES as u64 {
+            bail!("response exceeds {MAX_RESPONSE_BYTES} bytes");
+        }
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = res.chunk().await.context("read response body")? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_RESPONSE_BYTES {
+            bail!("response exceeds {MAX_RESPONSE_BYTES} bytes");
+        }
+    }
+    let body = String::from_utf8(bytes).map_err(|_| anyhow!("response body is not UTF-8"))?;
+    let body = sanitize_body(&media_type, &body)?;
+
+    Ok(json!({
+        "status": status,
+        "contentType": content_type,
+        "body": body,
+    }))
+}
+
+/// Starts a network request from a dapp on the shared RPC runtime; the
+/// answer arrives as an `RpcResult` event.
+pub(super) fn spawn_network_ipc(
+    state: &AppState,
+    webview_id: &str,
+    req: &IpcRequest,
+) -> Result<()> {
+    match req.method.as_str() {
+        "vibefi_httpFetch" => {
+            let task = http_fetch(state, webview_id, req)?;
+            super::spawn_rpc_task(state, webview_id, req.id, req.method.clone(), task);
+            Ok(())
+        }
+        _ => Err(anyhow!("unsupported network method: {}", req.method)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        allowed_content_type, is_loopback_origin, normalize_network_origin, origin_allowed,
+        sanitize_body,
+    };
+
+    #[test]
+    fn normalizes_allowed_origins() {
+        assert_eq!(
+            normalize_network_origin("https://api.example.com/v1/prices").as_deref(),
+            Some("https://api.example.com")
+        );
+        assert_eq!(
+            normalize_network_origin("http://localhost:8000").as_deref(),
+            Some("http://localhost:8000")
+        );
+        assert_eq!(normalize_network_origin("http://api.example.com"), None);
+        assert_eq!(normalize_network_origin("file:///etc/passwd"), None);
+    }
+
+    #[test]
+    fn recognizes_loopback_origins() {
+        for origin in [
+            "http://localhost:8000",
+            "http://127.0.0.1:8545",
+            "http://127.1.2.3",
+            "http://[::1]:3000",
+            "http://0.0.0.0:8080",
+            "https://api.localhost",
+        ] {
+            assert!(is_loopback_origin(origin), "{origin}");
+        }
+        assert!(!is_loopback_origin("https://api.example.com"));
+        assert!(!is_loopback_origin("https://localhost.example.com"));
+    }
+
+    #[test]
+    fn origin_match_is_exact() {
+        let allow = vec!["https://api.example.com".to_string()];
+        let ok = reqwest::Url::parse("https://api.example.com/graphql").unwrap();
+        let other_port = reqwest::Url::parse("https://api.example.com:8443/").unwrap();
+        let lookalike = reqwest::Url::parse("https://api.example.com.evil.tld/").unwrap();
+        assert!(origin_allowed(&allow, &ok));
+        assert!(!origin_allowed(&allow, &other_port));
+        assert!(!origin_allowed(&allow, &lookalike));
+    }
+
+    #[test]
+    fn only_data_content_types_are_returned() {
+        assert_eq!(
+            allowed_content_type(Some("application/json; charset=utf-8")).as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(
+            allowed_content_type(Some("application/problem+json")).as_deref(),
+            Some("application/problem+json")
+        );
+        assert_eq!(allowed_content_type(Some("text/html")), None);
+        assert_eq!(allowed_content_type(Some("application/javascript")), None);
+        assert_eq!(allowed_content_type(None), None);
+    }
+
+    #[test]
+    fn json_bodies_are_reserialized() {
+        assert_eq!(
+            sanitize_body("application/json", "\u{feff}{ \"a\": [1, 2] }").unwrap(),
+            r#"{"a":[1,2]}"#
+        );
+        assert!(sanitize_body("application/json", "<html>").is_err());
+    }
+
+    #[test]
+    fn text_bodies_lose_control_characters() {
+        assert_eq!(
+            sanitize_body("text/csv", "a,b\r\n1,\u{0}2\u{1b}[0m\n").unwrap(),
+            "a,b\r\n1,2[0m\n"
+        );
+    }
+}
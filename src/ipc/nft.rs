@@ -0,0 +1,591 @@
+use alloy_primitives::{Address, B256, Bytes, LogData, U256};
+use alloy_sol_types::{SolCall, SolEvent, sol};
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::ipc_contract::IpcRequest;
+use crate::multicall::{ReadCall, read_many};
+use crate::state::AppState;
+
+use super::ipfs::{DEFAULT_MAX_BYTES, ensure_raster_image, fetch_ipfs_bytes, guess_mime_from_path};
+use super::network::get_limited;
+use super::rpc_request;
+
+sol! {
+    event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+    event TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value);
+    event TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values);
+    function ownerOf(uint256 tokenId) external view returns (address);
+    function balanceOf(address account, uint256 id) external view returns (uint256);
+    function tokenURI(uint256 tokenId) external view returns (string);
+    function uri(uint256 id) external view returns (string);
+    function name() external view returns (string);
+}
+
+/// Blocks scanned back from the head when no indexer is configured.
+const SCAN_BLOCKS: u64 = 200_000;
+const SCAN_CHUNK: u64 = 50_000;
+/// Tokens described per listing; the rest are reported as truncated.
+const MAX_NFTS: usize = 60;
+const MAX_METADATA_BYTES: usize = 256 * 1024;
+/// Metadata documents fetched at once.
+const METADATA_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum NftStandard {
+    Erc721,
+    Erc1155,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct NftId {
+    contract: Address,
+    token_id: U256,
+    standard: NftStandard,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NftItem {
+    contract: String,
+    token_id: String,
+    standard: NftStandard,
+    balance: String,
+    collection: Option<String>,
+    name: Option<String>,
+    description: Option<String>,
+    /// Image URI from the metadata, loaded separately with `vibefi_nftImage`.
+    image: Option<String>,
+    /// Why the metadata could not be read, if it could not.
+    error: Option<String>,
+}
+
+/// Shape an indexer answers with: `{ "nfts": [{ "contract", "tokenId", "standard" }] }`.
+#[derive(Debug, Deserialize)]
+struct IndexerResponse {
+    nfts: Vec<IndexerNft>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexerNft {
+    contract: Address,
+    token_id: String,
+    standard: NftStandard,
+}
+
+/// Where a token URI points.
+#[derive(Debug, PartialEq)]
+enum TokenUri {
+    Ipfs { cid: String, path: String },
+    Http(reqwest::Url),
+    Data { mime: String, bytes: Vec<u8> },
+}
+
+/// Accounts bound to any open tab, for the gallery's account picker.
+pub(super) fn connected_accounts(state: &AppState) -> Vec<String> {
+    let mut accounts: Vec<String> = Vec::new();
+    let wallets = state.tab_wallets.lock().expect("tab_wallets");
+    for account in wallets.values().flat_map(|wallet| wallet.addresses()) {
+        let account = account.to_ascii_lowercase();
+        if !accounts.contains(&account) {
+            accounts.push(account);
+        }
+    }
+    accounts
+}
+
+/// `vibefi_listNfts`: tokens the account holds, with their metadata.
+pub(super) fn list_nfts(state: &AppState, req: &IpcRequest) -> Result<Value> {
+    let account: Address = req
+        .params
+        .get(0)
+        .and_then(|v| v.get("account"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("missing account"))?
+        .parse()
+        .context("invalid account address")?;
+    let indexer = state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.config_path.as_deref())
+        .and_then(|path| crate::settings::load_settings(path).nft_indexer);
+    let candidates = match indexer {
+        Some(template) => indexer_candidates(state, &template, account)?,
+        None => scan_candidates(state, account)?,
+    };
+
+    let owned = owned_tokens(state, account, candidates);
+    let truncated = owned.len() > MAX_NFTS;
+    let mut items = Vec::new();
+    for batch in owned[..owned.len().min(MAX_NFTS)].chunks(METADATA_CONCURRENCY) {
+        let described: Vec<NftItem> = std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|token| {
+                    let state = state.clone();
+                    scope.spawn(move || describe(&state, token))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("nft metadata thread panicked"))
+                .collect()
+        });
+        items.extend(described);
+    }
+    tracing::info!(
+        account = %format!("{account:#x}"),
+        tokens = items.len(),
+        truncated,
+        "nft listing complete"
+    );
+    Ok(json!({ "nfts": items, "truncated": truncated }))
+}
+
+/// `vibefi_nftImage`: a token image as a data URL, under the same size and
+/// raster-only limits as dapp IPFS image reads.
+pub(super) fn nft_image(state: &AppState, req: &IpcRequest) -> Result<Value> {
+    let raw = req
+        .params
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("missing image URI"))?;
+    let (bytes, content_type) = fetch_uri(state, &parse_token_uri(raw)?, DEFAULT_MAX_BYTES)?;
+    let mime = content_type
+        .map(|mime| {
+            mime.split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        })
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    ensure_raster_image(&mime)?;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    Ok(json!({ "dataUrl": format!("data:{mime};base64,{encoded}") }))
+}
+
+fn indexer_candidates(state: &AppState, template: &str, account: Address) -> Result<Vec<NftId>> {
+    let chain_id = state.wallet.lock().expect("wallet").chain.chain_id;
+    let url = template
+        .replace("{account}", &format!("{account:#x}"))
+        .replace("{chainId}", &chain_id.to_string());
+    let url = reqwest::Url::parse(&url).context("invalid NFT indexer URL")?;
+    let (body, _) =
+        get_limited(&url, MAX_METADATA_BYTES * 4).context("NFT indexer request failed")?;
+    let response: IndexerResponse =
+        serde_json::from_slice(&body).context("NFT indexer returned an unexpected response")?;
+    response
+        .nfts
+        .into_iter()
+        .map(|nft| {
+            Ok(NftId {
+                contract: nft.contract,
+                token_id: U256::from_str(&nft.token_id)
+                    .with_context(|| format!("invalid tokenId {:?}", nft.token_id))?,
+                standard: nft.standard,
+            })
+        })
+        .collect()
+}
+
+/// Tokens the account received in recent blocks, newest first. Ownership is
+/// checked afterwards, so tokens sent away again drop out there.
+fn scan_candidates(state: &AppState, account: Address) -> Result<Vec<NftId>> {
+    let latest = match state
+        .blocks
+        .recent(crate::block_watcher::BLOCK_POLL_INTERVAL)
+    {
+        Some(block) => block,
+        None => {
+            let raw = rpc_request(state, "eth_blockNumber", json!([]))?;
+            let raw = raw
+                .as_str()
+                .ok_or_else(|| anyhow!("eth_blockNumber returned non-string quantity"))?;
+            u64::from_str_radix(raw.trim_start_matches("0x"), 16)
+                .context("eth_blockNumber returned invalid quantity")?
+        }
+    };
+    let floor = latest.saturating_sub(SCAN_BLOCKS);
+    let recipient = format!("{:#x}", account.into_word());
+    let single_or_batch = json!([
+        format!("{:#x}", TransferSingle::SIGNATURE_HASH),
+        format!("{:#x}", TransferBatch::SIGNATURE_HASH),
+    ]);
+
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    let mut to_block = latest;
+    loop {
+        let from_block = floor.max(to_block.saturating_sub(SCAN_CHUNK - 1));
+        let range = |topics: Value| {
+            json!([{
+                "fromBlock": format!("0x{from_block:x}"),
+                "toBlock": format!("0x{to_block:x}"),
+                "topics": topics,
+            }])
+        };
+        let mut logs = get_logs(
+            state,
+            range(json!([
+                format!("{:#x}", Transfer::SIGNATURE_HASH),
+                null,
+                recipient
+            ])),
+        )?;
+        logs.extend(get_logs(
+            state,
+            range(json!([single_or_batch, null, null, recipient])),
+        )?);
+        logs.sort_by_key(|log| std::cmp::Reverse(log_position(log)));
+        for token in logs.iter().flat_map(tokens_in_log) {
+            if seen.insert(token.clone()) {
+                candidates.push(token);
+            }
+        }
+        if from_block == floor {
+            break;
+        }
+        to_block = from_block - 1;
+    }
+    Ok(candidates)
+}
+
+fn get_logs(state: &AppState, params: Value) -> Result<Vec<Value>> {
+    match rpc_request(state, "eth_getLogs", params).context("eth_getLogs failed")? {
+        Value::Array(logs) => Ok(logs),
+        other => bail!("eth_getLogs returned {other}"),
+    }
+}
+
+fn log_position(log: &Value) -> (u64, u64) {
+    let quantity = |key: &str| {
+        log.get(key)
+            .and_then(Value::as_str)
+            .and_then(|raw| u64::from_str_radix(raw.trim_start_matches("0x"), 16).ok())
+            .unwrap_or(0)
+    };
+    (quantity("blockNumber"), quantity("logIndex"))
+}
+
+/// NFTs moved by one `eth_getLogs` entry. ERC-20 `Transfer` logs share the
+/// signature but index only three topics, so they are skipped.
+fn tokens_in_log(log: &Value) -> Vec<NftId> {
+    let Some((contract, data)) = log_data(log) else {
+        return Vec::new();
+    };
+    let token = |token_id, standard| NftId {
+        contract,
+        token_id,
+        standard,
+    };
+    let Some(topic) = data.topics().first().copied() else {
+        return Vec::new();
+    };
+    if topic == Transfer::SIGNATURE_HASH && data.topics().len() == 4 {
+        Transfer::decode_log_data(&data)
+            .map(|event| vec![token(event.tokenId, NftStandard::Erc721)])
+            .unwrap_or_default()
+    } else if topic == TransferSingle::SIGNATURE_HASH {
+        TransferSingle::decode_log_data(&data)
+            .map(|event| vec![token(event.id, NftStandard::Erc1155)])
+            .unwrap_or_default()
+    } else if topic == TransferBatch::SIGNATURE_HASH {
+        TransferBatch::decode_log_data(&data)
+            .map(|event| {
+                event
+                    .ids
+                    .into_iter()
+                    .map(|id| token(id, NftStandard::Erc1155))
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+fn log_data(log: &Value) -> Option<(Address, LogData)> {
+    let contract: Address = log.get("address")?.as_str()?.parse().ok()?;
+    let topics = log
+        .get("topics")?
+        .as_array()?
+        .iter()
+        .map(|topic| topic.as_str()?.parse::<B256>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let data: Bytes = log.get("data")?.as_str()?.parse().ok()?;
+    Some((contract, LogData::new_unchecked(topics, data)))
+}
+
+/// Keeps the tokens `account` still holds, with its balance, in one batch of
+/// reads.
+fn owned_tokens(state: &AppState, account: Address, candidates: Vec<NftId>) -> Vec<(NftId, U256)> {
+    let calls: Vec<ReadCall> = candidates
+        .iter()
+        .map(|token| ReadCall {
+            to: token.contract,
+            data: match token.standard {
+                NftStandard::Erc721 => ownerOfCall {
+                    tokenId: token.token_id,
+                }
+                .abi_encode(),
+                NftStandard::Erc1155 => balanceOfCall {
+                    account,
+                    id: token.token_id,
+                }
+                .abi_encode(),
+            }
+            .into(),
+        })
+        .collect();
+    let results = read_many(state, &calls);
+    candidates
+        .into_iter()
+        .zip(results)
+        .filter_map(|(token, result)| {
+            let raw = result.ok()?;
+            let balance = match token.standard {
+                NftStandard::Erc721 => {
+                    let owner = ownerOfCall::abi_decode_returns(&raw).ok()?;
+                    (owner == account).then_some(U256::from(1))?
+                }
+                NftStandard::Erc1155 => balanceOfCall::abi_decode_returns(&raw).ok()?,
+            };
+            (!balance.is_zero()).then_some((token, balance))
+        })
+        .collect()
+}
+
+fn describe(state: &AppState, (token, balance): &(NftId, U256)) -> NftItem {
+    let uri_call: Bytes = match token.standard {
+        NftStandard::Erc721 => tokenURICall {
+            tokenId: token.token_id,
+        }
+        .abi_encode(),
+        NftStandard::Erc1155 => uriCall { id: token.token_id }.abi_encode(),
+    }
+    .into();
+    let reads = read_many(
+        state,
+        &[
+            ReadCall {
+                to: token.contract,
+                data: uri_call,
+            },
+            ReadCall {
+                to: token.contract,
+                data: nameCall {}.abi_encode().into(),
+            },
+        ],
+    );
+    let mut item = NftItem {
+        contract: format!("{:#x}", token.contract),
+        token_id: token.token_id.to_string(),
+        standard: token.standard,
+        balance: balance.to_string(),
+        collection: reads[1]
+            .as_ref()
+            .ok()
+            .and_then(|raw| nameCall::abi_decode_returns(raw).ok()),
+        name: None,
+        description: None,
+        image: None,
+        error: None,
+    };
+    let metadata = reads[0]
+        .as_ref()
+        .map_err(|err| anyhow!("token URI call failed: {err}"))
+        .and_then(|raw| {
+            // tokenURI and uri share the `string` return.
+            tokenURICall::abi_decode_returns(raw).context("token URI is not a string")
+        })
+        .map(|raw| substitute_token_id(&raw, token.token_id))
+        .and_then(|raw| parse_token_uri(&raw))
+        .and_then(|uri| fetch_uri(state, &uri, MAX_METADATA_BYTES))
+        .and_then(|(bytes, _)| {
+            serde_json::from_slice::<Value>(&bytes).context("metadata is not JSON")
+        });
+    match metadata {
+        Ok(metadata) => {
+            let text = |key: &str| {
+                metadata
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            };
+            item.name = text("name");
+            item.description = text("description");
+            item.image = text("image").or_else(|| text("image_url"));
+        }
+        Err(err) => item.error = Some(format!("{err:#}")),
+    }
+    item
+}
+
+/// ERC-1155 URIs carry `{id}`, replaced by the zero-padded hex token ID.
+fn substitute_token_id(uri: &str, token_id: U256) -> String {
+    uri.replace("{id}", &format!("{token_id:064x}"))
+}
+
+fn parse_token_uri(raw: &str) -> Result<TokenUri> {
+    let raw = raw.trim();
+    if let Some(rest) = raw.strip_prefix("ipfs://") {
+        let rest = rest.strip_prefix("ipfs/").unwrap_or(rest);
+        let (cid, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if cid.is_empty() {
+            bail!("IPFS URI has no CID: {raw}");
+        }
+        return Ok(TokenUri::Ipfs {
+            cid: cid.to_string(),
+            path: path.to_string(),
+        });
+    }
+    if let Some(rest) = raw.strip_prefix("data:") {
+        let (header, payload) = rest
+            .split_once(',')
+            .ok_or_else(|| anyhow!("malformed data URI"))?;
+        let (mime, base64) = match header.strip_suffix(";base64") {
+            Some(mime) => (mime, true),
+            None => (header, false),
+        };
+        let bytes = if base64 {
+            base64::engine::general_purpose::STANDARD
+                .decode(payload)
+                .context("data URI is not valid base64")?
+        } else {
+            payload.as_bytes().to_vec()
+        };
+        return Ok(TokenUri::Data {
+            mime: mime.to_string(),
+            bytes,
+        });
+    }
+    let url = reqwest::Url::parse(raw).with_context(|| format!("unsupported token URI {raw:?}"))?;
+    Ok(TokenUri::Http(url))
+}
+
+fn fetch_uri(
+    state: &AppState,
+    uri: &TokenUri,
+    max_bytes: usize,
+) -> Result<(Vec<u8>, Option<String>)> {
+    match uri {
+        TokenUri::Ipfs { cid, path } => {
+            let (bytes, content_type) = fetch_ipfs_bytes(state, cid, path, max_bytes, |_, _| {})?;
+            Ok((bytes, content_type.or_else(|| guess_mime_from_path(path))))
+        }
+        TokenUri::Http(url) => get_limited(url, max_bytes),
+        TokenUri::Data { mime, bytes } => {
+            if bytes.len() > max_bytes {
+                bail!("payload exceeds {max_bytes} bytes");
+            }
+            Ok((bytes.clone(), Some(mime.clone())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        NftStandard, TokenUri, Transfer, TransferBatch, parse_token_uri, substitute_token_id,
+        tokens_in_log,
+    };
+    use alloy_primitives::{Address, U256};
+    use alloy_sol_types::SolEvent;
+    use serde_json::json;
+
+    fn log_json(address: Address, data: alloy_primitives::LogData) -> serde_json::Value {
+        json!({
+            "address": format!("{address:#x}"),
+            "topics": data.topics().iter().map(|t| format!("{t:#x}")).collect::<Vec<_>>(),
+            "data": format!("0x{}", hex::encode(&data.data)),
+        })
+    }
+
+    fn erc721_transfer() -> Transfer {
+        Transfer {
+            from: Address::ZERO,
+            to: Address::repeat_byte(0x01),
+            tokenId: U256::from(9),
+        }
+    }
+
+    #[test]
+    fn reads_erc721_transfers() {
+        let contract = Address::repeat_byte(0x42);
+        let tokens = tokens_in_log(&log_json(contract, erc721_transfer().encode_log_data()));
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token_id, U256::from(9));
+        assert_eq!(tokens[0].standard, NftStandard::Erc721);
+    }
+
+    #[test]
+    fn skips_erc20_transfers() {
+        // Same signature, but the amount is in data rather than a fourth topic.
+        let contract = Address::repeat_byte(0x42);
+        let mut erc20 = erc721_transfer().encode_log_data();
+        let topics = erc20.topics()[..3].to_vec();
+        erc20.set_topics_unchecked(topics);
+        assert!(tokens_in_log(&log_json(contract, erc20)).is_empty());
+    }
+
+    #[test]
+    fn reads_every_token_in_an_erc1155_batch() {
+        let contract = Address::repeat_byte(0x42);
+        let batch = TransferBatch {
+            operator: Address::repeat_byte(0x02),
+            from: Address::ZERO,
+            to: Address::repeat_byte(0x01),
+            ids: vec![U256::from(1), U256::from(2)],
+            values: vec![U256::from(5), U256::from(1)],
+        };
+        let tokens = tokens_in_log(&log_json(contract, batch.encode_log_data()));
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens.iter().all(|t| t.standard == NftStandard::Erc1155));
+    }
+
+    #[test]
+    fn parses_ipfs_token_uris() {
+        assert_eq!(
+            parse_token_uri("ipfs://ipfs/bafymeta/7.json").unwrap(),
+            TokenUri::Ipfs {
+                cid: "bafymeta".to_string(),
+                path: "7.json".to_string()
+            }
+        );
+        assert!(parse_token_uri("ipfs://").is_err());
+    }
+
+    #[test]
+    fn parses_data_token_uris() {
+        assert_eq!(
+            parse_token_uri("data:application/json;base64,e30=").unwrap(),
+            TokenUri::Data {
+                mime: "application/json".to_string(),
+                bytes: b"{}".to_vec()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_http_token_uris() {
+        assert!(matches!(
+            parse_token_uri("https://meta.example/7").unwrap(),
+            TokenUri::Http(_)
+        ));
+    }
+
+    #[test]
+    fn substitutes_erc1155_token_ids_as_padded_hex() {
+        assert_eq!(
+            substitute_token_id("https://meta.example/{id}.json", U256::from(255)),
+            format!("https://meta.example/{}ff.json", "0".repeat(62))
+        );
+    }
+}
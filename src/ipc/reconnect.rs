@@ -0,0 +1,319 @@
+//! Reconnects the wallet the selector connected last when the client starts,
+//! so dapps asking for accounts get it back without the selector. The
+//! selector only opens when there is nothing to reconnect or it failed.
+
+use anyhow::{Context, Result, anyhow, bail};
+use std::sync::Mutex;
+
+use crate::hardware::HardwareDevice;
+use crate::settings::LastWallet;
+use crate::state::{AppState, PendingConnect, UserEvent, WalletBackend, lock_or_err};
+use crate::walletconnect::{WalletConnectBridge, WalletConnectConfig};
+
+#[derive(Debug, Clone)]
+enum Status {
+    /// Nothing was reconnected; connects go through the selector.
+    Idle,
+    Running,
+    Connected(WalletBackend, Vec<String>),
+}
+
+static STATUS: Mutex<Status> = Mutex::new(Status::Idle);
+
+/// Saves the wallet the selector just connected so the next start
+/// reconnects it. `None` forgets the previous one, for wallets that cannot
+/// be reconnected.
+pub(super) fn remember(state: &AppState, wallet: Option<LastWallet>) {
+    let Some(config_path) = state.resolved.as_ref().and_then(|r| r.config_path.as_ref()) else {
+        return;
+    };
+    let mut settings = crate::settings::load_settings(config_path);
+    if settings.last_wallet == wallet {
+        return;
+    }
+    settings.last_wallet = wallet;
+    if let Err(e) = crate::settings::save_settings(config_path, &settings) {
+        tracing::warn!(error = %format!("{e:#}"), "failed to remember last wallet");
+    }
+}
+
+pub(super) fn remembered(state: &AppState) -> Option<LastWallet> {
+    let config_path = state.resolved.as_ref()?.config_path.as_ref()?;
+    crate::settings::load_settings(config_path).last_wallet
+}
+
+/// A smart account remembers the wallet that owns it, which is the one
+/// connected before it.
+pub(super) fn smart_account_wallet(state: &AppState) -> Option<LastWallet> {
+    remembered(state)
+        .filter(|owner| {
+            !matches!(
+                owner,
+                LastWallet::WalletConnect | LastWallet::SmartAccount { .. } | LastWallet::Remote
+            )
+        })
+        .map(|owner| LastWallet::SmartAccount {
+            owner: Box::new(owner),
+        })
+}
+
+/// What a hardware device is remembered as.
+pub(super) fn hardware_wallet(device: &HardwareDevice) -> Option<LastWallet> {
+    match device {
+        HardwareDevice::Ledger(_) | HardwareDevice::Trezor(_) => Some(LastWallet::Hardware),
+        HardwareDevice::LedgerBle(_) => Some(LastWallet::LedgerBle),
+        // Without a device id there is nothing to reconnect to.
+        HardwareDevice::Lattice(signer) => {
+            signer.device_id().map(|device_id| LastWallet::Lattice {
+                device_id: device_id.to_string(),
+            })
+        }
+    }
+}
+
+/// Reconnects the remembered wallet in the background and shows a
+/// notification naming the account once it is back.
+pub fn spawn_reconnect(state: &AppState) {
+    let Some(wallet) = remembered(state) else {
+        return;
+    };
+    *STATUS.lock().expect("reconnect status") = Status::Running;
+    let state = state.clone();
+    std::thread::spawn(move || {
+        let result = reconnect(&state, &wallet);
+        // Decide under the pending_connect lock so a tab queued meanwhile
+        // either sees the outcome or is answered by it.
+        let Ok(pending) = lock_or_err(&state.pending_connect, "pending_connect") else {
+            return;
+        };
+        let waiting = !pending.is_empty();
+        match result {
+            Ok((backend, accounts)) => {
+                tracing::info!(
+                    backend = backend.as_str(),
+                    accounts = accounts.len(),
+                    "reconnected last wallet"
+                );
+                *STATUS.lock().expect("reconnect status") =
+                    Status::Connected(backend, accounts.clone());
+                drop(pending);
+                let body = format!(
+                    "Connected as {} ({})",
+                    accounts.first().map(String::as_str).unwrap_or_default(),
+                    backend.as_str()
+                );
+                if let Err(e) = crate::notifications::show("Wallet connected", &body) {
+                    tracing::debug!(error = %format!("{e:#}"), "wallet notification not shown");
+                }
+                let _ = state
+                    .proxy
+                    .send_event(UserEvent::WalletConnected { backend, accounts });
+            }
+            Err(e) => {
+                tracing::warn!(
+                    error = %format!("{e:#}"),
+                    ?wallet,
+                    "could not reconnect last wallet; using the wallet selector"
+                );
+                *STATUS.lock().expect("reconnect status") = Status::Idle;
+                drop(pending);
+                if waiting {
+                    let _ = state.proxy.send_event(UserEvent::OpenWalletSelector);
+                }
+            }
+        }
+    });
+}
+
+/// Parks an `eth_requestAccounts` from a tab without a wallet. It is answered
+/// by the reconnected wallet, by the reconnect still running, or else by the
+/// selector, which is opened here.
+pub(super) fn queue_connect(state: &AppState, pending: PendingConnect) -> Result<()> {
+    let mut queue = lock_or_err(&state.pending_connect, "pending_connect")?;
+    queue.push_back(pending);
+    let status = STATUS.lock().expect("reconnect status").clone();
+    drop(queue);
+    let event = match status {
+        Status::Running => return Ok(()),
+        Status::Connected(backend, accounts) if is_live(state, backend) => {
+            let accounts = if backend == WalletBackend::WalletConnect {
+                lock_or_err(&state.wallet, "wallet")?
+                    .walletconnect_accounts
+                    .clone()
+            } else {
+                accounts
+            };
+            UserEvent::WalletConnected { backend, accounts }
+        }
+        _ => UserEvent::OpenWalletSelector,
+    };
+    state
+        .proxy
+        .send_event(event)
+        .map_err(|_| anyhow!("event loop closed"))
+}
+
+/// Whether the reconnected backend is still there; locking the client drops
+/// the local signer, for one.
+fn is_live(state: &AppState, backend: WalletBackend) -> bool {
+    match backend {
+        WalletBackend::Local => !state.signer.lock().expect("signer").is_empty(),
+        WalletBackend::WalletConnect => {
+            state.walletconnect.lock().expect("walletconnect").is_some()
+        }
+        WalletBackend::Hardware => state
+            .hardware_signer
+            .lock()
+            .expect("hardware_signer")
+            .is_some(),
+        WalletBackend::SmartAccount => state.smart_account.lock().expect("smart_account").is_some(),
+        WalletBackend::Remote => state.remote_signer.lock().expect("remote_signer").is_some(),
+    }
+}
+
+fn reconnect(state: &AppState, wallet: &LastWallet) -> Result<(WalletBackend, Vec<String>)> {
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("no network config loaded"))?;
+    let chain_id = lock_or_err(&state.wallet, "wallet")?.chain.chain_id;
+    match wallet {
+        LastWallet::Local => {
+            state.lock.ensure_unlocked()?;
+            if !resolved.test_network {
+                bail!("the local signer is only available on test networks");
+            }
+            let signer: alloy_signer_local::PrivateKeySigner = resolved
+                .developer_private_key
+                .as_deref()
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .ok_or_else(|| anyhow!("no developer key configured"))?
+                .parse()
+                .context("failed to parse signing private key")?;
+            let account = format!("0x{:x}", signer.address());
+            *lock_or_err(&state.signer, "signer")? = vec![std::sync::Arc::new(signer)];
+            Ok((WalletBackend::Local, vec![account]))
+        }
+        LastWallet::Hardware => {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .context("failed to create tokio runtime")?;
+            let device = rt.block_on(crate::hardware::detect_and_connect(chain_id))?;
+            Ok(install_hardware(state, device))
+        }
+        LastWallet::LedgerBle => {
+            let device = crate::settings::load_settings(
+                resolved
+                    .config_path
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("no config file"))?,
+            )
+            .ledger_ble_device
+            .ok_or_else(|| anyhow!("no bluetooth Ledger remembered"))?;
+            let signer = crate::ledger_ble::connect(&device)?;
+            Ok(install_hardware(state, HardwareDevice::LedgerBle(signer)))
+        }
+        LastWallet::Lattice { device_id } => {
+            let signer = crate::lattice::connect(device_id)?
+                .ok_or_else(|| anyhow!("the Lattice needs pairing again"))?;
+            Ok(install_hardware(state, HardwareDevice::Lattice(signer)))
+        }
+        LastWallet::WalletConnect => {
+            crate::fork::ensure_backend_allowed(
+                resolved.fork.as_ref(),
+                WalletBackend::WalletConnect,
+            )?;
+            let project_id = resolved
+                .walletconnect_project_id
+                .clone()
+                .ok_or_else(|| anyhow!("WalletConnect is not configured"))?;
+            let mut bridge = WalletConnectBridge::spawn(WalletConnectConfig {
+                project_id,
+                relay_urls: resolved.walletconnect_relay_urls.clone(),
+                store_path: crate::profiles::walletconnect_store(Some(resolved)),
+            })
+            .context("failed to initialize WalletConnect bridge")?;
+            let session = bridge.restore_session(chain_id)?;
+            if session.accounts.is_empty() {
+                bail!("the stored WalletConnect session has no accounts");
+            }
+            if let Some(chain_id) = super::rpc::parse_hex_u64(&session.chain_id_hex) {
+                lock_or_err(&state.wallet, "wallet")?.chain.chain_id = chain_id;
+            }
+            state.apply_walletconnect_accounts(&session.accounts);
+            let bridge = std::sync::Arc::new(Mutex::new(bridge));
+            *lock_or_err(&state.walletconnect, "walletconnect")? = Some(bridge.clone());
+            crate::walletconnect::spawn_relay_monitor(
+                std::sync::Arc::downgrade(&bridge),
+                state.proxy.clone(),
+            );
+            Ok((WalletBackend::WalletConnect, session.accounts))
+        }
+        LastWallet::SmartAccount { owner } => {
+            crate::fork::ensure_backend_allowed(
+                resolved.fork.as_ref(),
+                WalletBackend::SmartAccount,
+            )?;
+            reconnect(state, owner).context("failed to reconnect the smart account owner")?;
+            let account = crate::smart_account::connect(state)?;
+            let address = format!("{:#x}", account.address);
+            *lock_or_err(&state.smart_account, "smart_account")? = Some(account);
+            Ok((WalletBackend::SmartAccount, vec![address]))
+        }
+        LastWallet::Remote => {
+            crate::fork::ensure_backend_allowed(resolved.fork.as_ref(), WalletBackend::Remote)?;
+            let accounts = super::remote_signer::connect(state)?;
+            Ok((WalletBackend::Remote, accounts))
+        }
+    }
+}
+
+fn install_hardware(state: &AppState, device: HardwareDevice) -> (WalletBackend, Vec<String>) {
+    let account = crate::hardware::get_address(&device);
+    *state.hardware_signer.lock().expect("hardware_signer") = Some(device);
+    (WalletBackend::Hardware, vec![account])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::settings::LastWallet;
+    use serde_json::json;
+
+    fn smart_account_on_lattice() -> LastWallet {
+        LastWallet::SmartAccount {
+            owner: Box::new(LastWallet::Lattice {
+                device_id: "abc123".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn stores_the_last_wallet_by_backend() {
+        assert_eq!(
+            serde_json::to_value(smart_account_on_lattice()).unwrap(),
+            json!({
+                "backend": "smartAccount",
+                "owner": { "backend": "lattice", "deviceId": "abc123" },
+            })
+        );
+    }
+
+    #[test]
+    fn stored_wallets_round_trip() {
+        let value = serde_json::to_value(smart_account_on_lattice()).unwrap();
+        assert_eq!(
+            serde_json::from_value::<LastWallet>(value).unwrap(),
+            smart_account_on_lattice()
+        );
+    }
+
+    #[test]
+    fn reads_wallets_without_details() {
+        assert_eq!(
+            serde_json::from_value::<LastWallet>(json!({ "backend": "walletConnect" })).unwrap(),
+            LastWallet::WalletConnect
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Value, json};
+
+use crate::prompt::PromptRequest;
+use crate::remote_signer::RemoteSigner;
+use crate::state::{AppState, SignerCapabilities, WalletBackend};
+
+use super::rpc::FilledTransaction;
+use super::signer::{SignContext, WalletSigner, transaction_details};
+
+/// An external signing service. It signs whatever it is sent, so every
+/// request is confirmed in a client prompt first.
+pub(super) struct RemoteWallet;
+
+impl WalletSigner for RemoteWallet {
+    fn backend(&self) -> WalletBackend {
+        WalletBackend::Remote
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "vibefi-remote-signer"
+    }
+
+    fn capabilities(&self) -> SignerCapabilities {
+        SignerCapabilities {
+            switch_chain: false,
+            raw_transactions: true,
+            client_confirmation: true,
+        }
+    }
+
+    fn accounts(&self, state: &AppState) -> Result<Vec<String>> {
+        match connected_signer(state) {
+            Some(signer) => signer.accounts(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn sign_message(&self, ctx: &SignContext, message: &[u8]) -> Result<String> {
+        let prompt = PromptRequest::new(
+            "remoteSignature",
+            "Sign message?",
+            "The remote signer will sign this message.",
+        )
+        .with_details(json!({
+            "account": ctx.account,
+            "message": String::from_utf8(message.to_vec())
+                .unwrap_or_else(|_| format!("0x{}", hex::encode(message))),
+        }));
+        let signer = confirm(ctx, "personal_sign", prompt)?;
+        signer.sign_message(&ctx.account, message)
+    }
+
+    fn sign_typed_data(&self, ctx: &SignContext, typed_data_json: &str) -> Result<String> {
+        let typed_data: Value =
+            serde_json::from_str(typed_data_json).context("invalid typed data JSON")?;
+        let prompt = PromptRequest::new(
+            "remoteSignature",
+            "Sign typed data?",
+            "The remote signer will sign this structured data.",
+        )
+        .with_details(json!({
+            "account": ctx.account,
+            "domain": typed_data.get("domain"),
+            "primaryType": typed_data.get("primaryType"),
+            "message": typed_data.get("message"),
+        }));
+        let signer = confirm(ctx, "eth_signTypedData_v4", prompt)?;
+        signer.sign_typed_data(&ctx.account, typed_data)
+    }
+
+    fn sign_transaction(&self, ctx: &SignContext, tx: FilledTransaction) -> Result<String> {
+        // Called with the filled transaction, so the prompt shows the final
+        // nonce, gas and fees.
+        let details = transaction_details(ctx.state, ctx.webview_id, &tx.tx, tx.touched.as_ref());
+        let tx = serde_json::to_value(&tx.tx)?;
+        let prompt = PromptRequest::new(
+            "remoteTransaction",
+            "Send transaction?",
+            "The remote signer will sign this transaction and the client will broadcast it.",
+        )
+        .with_details(details);
+        let signer = confirm(ctx, "eth_signTransaction", prompt)?;
+        signer.sign_transaction(tx)
+    }
+}
+
+fn connected_signer(state: &AppState) -> Option<Arc<RemoteSigner>> {
+    state.remote_signer.lock().expect("remote_signer").clone()
+}
+
+/// Asks the user to approve a request before it leaves the client and
+/// records the decision in the audit log.
+fn confirm(ctx: &SignContext, method: &str, prompt: PromptRequest) -> Result<Arc<RemoteSigner>> {
+    let signer =
+        connected_signer(ctx.state).ok_or_else(|| anyhow!("Remote signer not connected"))?;
+    let prompt = match ctx.webview_id {
+        Some(webview_id) => prompt.from_webview(webview_id),
+        None => prompt,
+    };
+    let approved = crate::prompt::ask(ctx.state, prompt);
+    tracing::info!(
+        target: "vibefi::audit",
+        webview_id = ctx.webview_id.unwrap_or("host"),
+        method,
+        account = %ctx.account,
+        signer = signer.url(),
+        approved,
+        "remote signer request answered"
+    );
+    if !approved {
+        bail!("User rejected the request");
+    }
+    Ok(signer)
+}
+
+/// Connects to the configured service and returns the accounts it signs for.
+pub(super) fn connect(state: &AppState) -> Result<Vec<String>> {
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("No config loaded"))?;
+    let settings = resolved
+        .remote_signer
+        .clone()
+        .ok_or_else(|| anyhow!("Remote signing requires remoteSigner.url in config"))?;
+    let signer = RemoteSigner::new(settings, resolved.http_client.clone());
+    let accounts = signer.accounts()?;
+    if accounts.is_empty() {
+        bail!("Remote signer at {} has no accounts", signer.url());
+    }
+    tracing::info!(
+        target: "vibefi::audit",
+        signer = signer.url(),
+        ?accounts,
+        "remote signer connected"
+    );
+    *state.remote_signer.lock().expect("remote_signer") = Some(Arc::new(signer));
+    Ok(accounts)
+}
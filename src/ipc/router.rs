@@ -2,14 +2,14 @@ use anyhow::{Context, Result, anyhow, bail};
 use serde_json::Value;
 use wry::WebView;
 
-use crate::ipc_contract::{IpcRequest, KnownProviderId};
+use crate::ipc_contract::{BATCH_METHOD, IpcRequest, KnownProviderId};
 use crate::registry::handle_launcher_ipc;
-use crate::state::lock_or_err;
-use crate::state::{AppState, PendingConnect, ProviderInfo, UserEvent, WalletBackend};
+use crate::state::{AppState, PendingConnect, ProviderCapabilities, UserEvent, WalletBackend};
 use crate::webview_manager::{AppWebViewKind, WebViewManager};
 
 use super::{
-    hardware, ipfs, local, respond_option_result, respond_value_result, selector, walletconnect,
+    app, chains, ipfs, network, respond_option_result, respond_value_result, selector, signer,
+    walletconnect,
 };
 
 pub fn handle_ipc(
@@ -21,6 +21,7 @@ pub fn handle_ipc(
 ) -> Result<()> {
     let req: IpcRequest = serde_json::from_str(&msg).context("invalid IPC JSON")?;
     let provider = req.provider();
+    let _span = tracing::info_span!("ipc", webview_id, method = %req.method).entered();
     tracing::debug!(
         webview_id,
         provider = ?provider,
@@ -29,6 +30,15 @@ pub fn handle_ipc(
         "ipc request received"
     );
 
+    // Client surfaces are driven by the user; dapp traffic does not hold off
+    // the auto-lock.
+    if matches!(
+        provider,
+        Some(KnownProviderId::Wallet | KnownProviderId::Settings | KnownProviderId::Launcher)
+    ) {
+        state.lock.touch();
+    }
+
     // Handle vibefi-wallet IPC from the wallet selector tab.
     if provider == Some(KnownProviderId::Wallet) {
         let result =
@@ -38,26 +48,35 @@ pub fn handle_ipc(
     }
 
     if provider == Some(KnownProviderId::Settings) {
-        let settings_write_method = matches!(
-            req.method.as_str(),
-            "vibefi_setEndpoints"
-                | "vibefi_setIpfsSettings"
-                | "vibefi_setMaxConcurrentRpc"
-                | "vibefi_setRpcAndIpfsSettings"
-                | "vibefi_saveSettings"
-                | "vibefi_openLogDirectory"
-        );
-        if settings_write_method {
-            if manager.app_kind_for_id(webview_id) != Some(AppWebViewKind::Settings) {
-                tracing::warn!(
+        // Settings methods read or change client-wide state, other dapps'
+        // data and held signed transactions included. No other webview may
+        // call any of them, so new methods are covered without being listed.
+        if manager.app_kind_for_id(webview_id) != Some(AppWebViewKind::Settings) {
+            tracing::warn!(
+                webview_id,
+                method = %req.method,
+                "settings ipc request rejected for non-settings webview"
+            );
+            bail!("settings IPC is only available to the settings webview");
+        }
+        if super::settings::is_blocking_method(&req.method) {
+            let state_clone = state.clone();
+            let webview_id = webview_id.to_string();
+            let req_clone = req.clone();
+            std::thread::spawn(move || {
+                let result =
+                    super::settings::handle_blocking_settings_ipc(&state_clone, &req_clone)
+                        .map_err(|err| format!("{err:#}"));
+                let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
                     webview_id,
-                    method = %req.method,
-                    "settings write attempt from non-settings webview"
-                );
-                bail!("settings write methods are only available to the settings webview");
-            }
+                    ipc_id: req_clone.id,
+                    result,
+                });
+            });
+            return Ok(());
         }
-        let result = super::settings::handle_settings_ipc(state, &req).map_err(|e| e.to_string());
+        let result =
+            super::settings::handle_settings_ipc(state, manager, &req).map_err(|e| e.to_string());
         respond_value_result(webview, req.id, result)?;
         return Ok(());
     }
@@ -75,6 +94,34 @@ pub fn handle_ipc(
             );
             bail!("launcher IPC is only available to launcher/studio webviews");
         }
+        if req.method == "vibefi_publishDapp"
+            && manager.app_kind_for_id(webview_id) != Some(AppWebViewKind::Studio)
+        {
+            bail!("only Studio can publish dapps");
+        }
+        if req.method == "vibefi_contractLogs"
+            && manager.app_kind_for_id(webview_id) != Some(AppWebViewKind::Studio)
+        {
+            bail!("only Studio can read contract logs");
+        }
+        if req.method == "vibefi_evalInPreview" {
+            if manager.app_kind_for_id(webview_id) != Some(AppWebViewKind::Studio) {
+                bail!("only Studio can evaluate in the preview");
+            }
+            let request: crate::preview_eval::EvalRequest = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing eval request"))?,
+            )
+            .context("invalid eval request")?;
+            if let Err(err) =
+                crate::preview_eval::start(state, manager, webview_id, req.id, &request)
+            {
+                respond_option_result(webview, req.id, Err(err))?;
+            }
+            return Ok(());
+        }
         let result = handle_launcher_ipc(state, webview_id, &req);
         respond_option_result(webview, req.id, result)?;
         return Ok(());
@@ -105,36 +152,158 @@ pub fn handle_ipc(
         return Ok(());
     }
 
-    let backend = state.get_wallet_backend();
-
-    // If no wallet backend is chosen yet and the dapp calls eth_requestAccounts,
-    // open the wallet selector tab and park the request.
-    if backend.is_none() && req.method == "eth_requestAccounts" {
-        {
-            let mut pending = lock_or_err(&state.pending_connect, "pending_connect")?;
-            pending.push_back(PendingConnect {
-                webview_id: webview_id.to_string(),
-                ipc_id: req.id,
+    if provider == Some(KnownProviderId::App) && app::is_blocking_method(&req.method) {
+        let dapp = manager.dapp_identity_for(webview_id);
+        let state_clone = state.clone();
+        let webview_id = webview_id.to_string();
+        let req_clone = req.clone();
+        std::thread::spawn(move || {
+            let result = app::handle_blocking_app_ipc(&state_clone, &webview_id, &dapp, &req_clone)
+                .map_err(|err| err.to_string());
+            let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
+                webview_id,
+                ipc_id: req_clone.id,
+                result,
             });
+        });
+        return Ok(());
+    }
+
+    if provider == Some(KnownProviderId::App) && req.method == crate::preview_eval::RESULT_METHOD {
+        crate::preview_eval::finish(state, webview_id, &req.params);
+        return Ok(());
+    }
+
+    if provider == Some(KnownProviderId::App) && req.method == "vibefi_postMessage" {
+        let result =
+            crate::messaging::post(state, manager, webview_id, &req).map_err(|e| e.to_string());
+        respond_value_result(webview, req.id, result)?;
+        return Ok(());
+    }
+
+    if provider == Some(KnownProviderId::App) {
+        let result = app::handle_app_ipc(state, webview_id, &req).map_err(|e| e.to_string());
+        respond_value_result(webview, req.id, result)?;
+        return Ok(());
+    }
+
+    if provider == Some(KnownProviderId::Network) {
+        return match network::spawn_network_ipc(state, webview_id, &req) {
+            Ok(()) => Ok(()),
+            Err(err) => respond_option_result(webview, req.id, Err(err)),
+        };
+    }
+
+    if req.method == BATCH_METHOD {
+        return match super::batch::spawn_batch(state, webview_id, &req) {
+            Ok(()) => Ok(()),
+            Err(err) => respond_option_result(webview, req.id, Err(err)),
+        };
+    }
+
+    if super::is_wallet_approval_method(req.method.as_str()) && state.lock.is_locked() {
+        tracing::info!(webview_id, method = %req.method, "wallet request refused while locked");
+        return respond_option_result(webview, req.id, Err(anyhow!(crate::lock::LOCKED_ERROR)));
+    }
+
+    if req.method == "wallet_switchEthereumChain" {
+        if let Err(err) = ensure_chain_allowed(state, manager, webview_id, &req) {
+            return respond_option_result(webview, req.id, Err(err));
+        }
+    }
+
+    if is_chain_bound_method(req.method.as_str()) {
+        if let Err(err) = ensure_active_chain_allowed(state, manager, webview_id, &req) {
+            return respond_option_result(webview, req.id, Err(err));
         }
+    }
+
+    // Added networks belong to the client, whichever backend the tab uses.
+    if req.method == "wallet_addEthereumChain" {
+        let result = chains::handle_add_chain(state, webview_id, &req);
+        return respond_option_result(webview, req.id, result);
+    }
+
+    let binding = state.tab_wallet(webview_id);
+
+    // Signing requests go to the backend that owns the named signer; anything
+    // else goes to the backend of the tab's primary account.
+    let backend = match (&binding, super::request_signer(&req)) {
+        (Some(binding), Some(signer)) => match binding.owner_of(&signer) {
+            Some(backend) => Some(backend),
+            None => {
+                tracing::warn!(
+                    webview_id,
+                    method = %req.method,
+                    signer,
+                    "signing request for an account not connected to this tab"
+                );
+                return respond_option_result(
+                    webview,
+                    req.id,
+                    Err(anyhow!(
+                        "Unauthorized: {signer} is not connected to this dapp"
+                    )),
+                );
+            }
+        },
+        (Some(binding), None) => binding.primary().map(|a| a.backend),
+        (None, _) => None,
+    };
+
+    if super::is_wallet_approval_method(req.method.as_str()) {
+        let dapp = manager.dapp_identity_for(webview_id);
+        tracing::info!(
+            target: "vibefi::audit",
+            webview_id,
+            %dapp,
+            method = %req.method,
+            backend = ?backend,
+            "wallet approval requested"
+        );
+    }
+
+    // If this tab has no wallet bound yet and the dapp calls eth_requestAccounts,
+    // park the request until the reconnected wallet or the selector answers it.
+    if backend.is_none() && req.method == "eth_requestAccounts" {
         tracing::info!(
             webview_id,
             ipc_id = req.id,
-            "queued pending eth_requestAccounts and opening wallet selector"
+            "queued pending eth_requestAccounts"
         );
-        if let Err(err) = state.proxy.send_event(UserEvent::OpenWalletSelector) {
-            tracing::warn!(error = %err, "failed to send OpenWalletSelector event");
+        let pending = PendingConnect {
+            webview_id: webview_id.to_string(),
+            ipc_id: Some(req.id),
+        };
+        if let Err(err) = super::reconnect::queue_connect(state, pending) {
+            tracing::warn!(error = %err, "failed to queue pending connect");
         }
-        // Response will be sent later once the user picks a wallet.
+        // Response will be sent later once a wallet is connected.
         return Ok(());
     }
 
+    // Accounts are aggregated across every backend the tab is connected to.
+    if let Some(binding) = &binding {
+        if matches!(req.method.as_str(), "eth_accounts" | "eth_requestAccounts") {
+            let accounts = binding.addresses().into_iter().map(Value::String).collect();
+            return respond_option_result(webview, req.id, Ok(Some(Value::Array(accounts))));
+        }
+    }
+
+    let sends_transaction = matches!(
+        req.method.as_str(),
+        "eth_sendTransaction" | "eth_sendRawTransaction"
+    );
+    if sends_transaction {
+        crate::subscriptions::expect_transaction(state, webview_id, req.id);
+        crate::tx_history::expect(webview_id, req.id, manager.dapp_identity_for(webview_id));
+    }
+
     let result = match backend {
-        Some(WalletBackend::Local) => local::handle_local_ipc(webview, state, webview_id, &req),
         Some(WalletBackend::WalletConnect) => {
             walletconnect::handle_walletconnect_ipc(webview, state, webview_id, &req)
         }
-        Some(WalletBackend::Hardware) => hardware::handle_hardware_ipc(state, webview_id, &req),
+        Some(backend) => signer::handle_signer_ipc(webview, state, webview_id, &req, backend),
         None => {
             if let Some(value) = super::network_identity_response(state, req.method.as_str()) {
                 return respond_option_result(webview, req.id, Ok(Some(value)));
@@ -145,13 +314,14 @@ pub fn handle_ipc(
             match req.method.as_str() {
                 "eth_accounts" => Ok(Some(Value::Array(vec![]))),
                 "wallet_getProviderInfo" => {
-                    let info = ProviderInfo {
-                        name: "vibefi".to_string(),
-                        chain_id: state.chain_id_hex(),
-                        backend: "none",
-                        account: None,
-                        walletconnect_uri: None,
-                    };
+                    let info = super::provider_info(
+                        state,
+                        webview_id,
+                        "vibefi",
+                        None,
+                        None,
+                        ProviderCapabilities::default(),
+                    );
                     Ok(Some(serde_json::to_value(info)?))
                 }
                 _ => {
@@ -167,7 +337,113 @@ pub fn handle_ipc(
         }
     };
 
+    if sends_transaction {
+        // Deferred results arrive through RpcResult/SignerResult instead.
+        let hash = match &result {
+            Ok(Some(value)) => Some(value.as_str()),
+            Ok(None) => None,
+            Err(_) => Some(None),
+        };
+        if let Some(hash) = hash {
+            crate::subscriptions::on_transaction_result(state, webview_id, req.id, hash);
+            crate::tx_history::on_result(state, webview_id, req.id, hash);
+        }
+    }
     respond_option_result(webview, req.id, result)?;
 
     Ok(())
 }
+
+/// Refuses `wallet_switchEthereumChain` to a chain the dapp's manifest does not
+/// declare, so it cannot end up sending transactions on the wrong network.
+fn ensure_chain_allowed(
+    state: &AppState,
+    manager: &WebViewManager,
+    webview_id: &str,
+    req: &IpcRequest,
+) -> Result<()> {
+    let Some(caps) = state.app_capabilities_for(webview_id) else {
+        return Ok(());
+    };
+    let chain_id = req
+        .params
+        .get(0)
+        .and_then(|v| v.get("chainId"))
+        .and_then(Value::as_str)
+        .and_then(super::rpc::parse_hex_u64)
+        .ok_or_else(|| anyhow!("invalid params for wallet_switchEthereumChain"))?;
+    if caps.chains.allows(chain_id) {
+        return Ok(());
+    }
+    let dapp = manager.dapp_identity_for(webview_id);
+    tracing::warn!(
+        webview_id,
+        %dapp,
+        chain_id,
+        "chain switch to a chain the dapp does not declare"
+    );
+    bail!(
+        "{} does not support chain {chain_id}; its manifest declares {}",
+        dapp.name,
+        describe_chains(&caps.chains)
+    )
+}
+
+/// Requests whose result only means something on the chain they are made on.
+pub(super) fn is_chain_bound_method(method: &str) -> bool {
+    matches!(
+        method,
+        "eth_sendTransaction"
+            | "eth_signTransaction"
+            | "eth_sendRawTransaction"
+            | "eth_signTypedData"
+            | "eth_signTypedData_v3"
+            | "eth_signTypedData_v4"
+    )
+}
+
+/// Refuses signing while the client is on a chain the dapp's manifest does
+/// not declare. The active chain is shared by every tab, so launching another
+/// dapp can move it after this one checked it at launch.
+fn ensure_active_chain_allowed(
+    state: &AppState,
+    manager: &WebViewManager,
+    webview_id: &str,
+    req: &IpcRequest,
+) -> Result<()> {
+    let Some(caps) = state.app_capabilities_for(webview_id) else {
+        return Ok(());
+    };
+    let chain_id = state
+        .wallet
+        .lock()
+        .expect("poisoned wallet lock while checking the tab's chains")
+        .chain
+        .chain_id;
+    if caps.chains.allows(chain_id) {
+        return Ok(());
+    }
+    let dapp = manager.dapp_identity_for(webview_id);
+    tracing::warn!(
+        webview_id,
+        %dapp,
+        chain_id,
+        method = %req.method,
+        "signing refused on a chain the dapp does not declare"
+    );
+    bail!(
+        "{} does not support chain {chain_id}; its manifest declares {}. Switch networks and try again",
+        dapp.name,
+        describe_chains(&caps.chains)
+    )
+}
+
+fn describe_chains(chains: &crate::manifest::ChainRequirements) -> String {
+    chains
+        .required
+        .iter()
+        .chain(chains.supported.iter())
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,36 +1,103 @@
 use alloy_consensus::TypedTransaction;
 use alloy_eips::eip2718::Encodable2718;
+use alloy_eips::eip2930::{AccessList, AccessListResult};
 use alloy_primitives::{Address, Signature};
 use alloy_rpc_types_eth::TransactionRequest;
 use anyhow::{Context, Result, anyhow, bail};
-use serde_json::Value;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
 
 use crate::ipc_contract::IpcRequest;
+use crate::rpc_manager::RpcEndpointManager;
 use crate::state::AppState;
 
-pub(super) fn is_rpc_passthrough(method: &str) -> bool {
-    matches!(
+pub(super) fn is_rpc_passthrough(method: &str, devnet: bool) -> bool {
+    (devnet && is_devnet_method(method))
+        || matches!(
+            method,
+            "eth_blockNumber"
+                | "eth_getBlockByNumber"
+                | "eth_getBlockByHash"
+                | "eth_getBalance"
+                | "eth_getCode"
+                | "eth_getLogs"
+                | "eth_call"
+                | "eth_estimateGas"
+                | "eth_gasPrice"
+                | "eth_feeHistory"
+                | "eth_maxPriorityFeePerGas"
+                | "eth_getTransactionReceipt"
+                | "eth_getTransactionByHash"
+                | "eth_getStorageAt"
+                | "eth_getTransactionCount"
+                | "eth_sendRawTransaction"
+        )
+}
+
+/// Tracing and node-control methods, passed through only with `devnetRpc`.
+fn is_devnet_method(method: &str) -> bool {
+    method == "eth_getProof"
+        || ["debug_", "trace_", "anvil_"]
+            .iter()
+            .any(|prefix| method.starts_with(prefix))
+}
+
+/// Logs the function a dapp's `eth_call` or `eth_estimateGas` invokes, when
+/// its bundle ABIs cover it, so the RPC log reads as more than hex.
+pub(super) fn trace_decoded_call(webview_id: &str, method: &str, params: &Value) {
+    if !matches!(method, "eth_call" | "eth_estimateGas")
+        || !tracing::enabled!(tracing::Level::DEBUG)
+    {
+        return;
+    }
+    let Some(tx) = params.get(0) else {
+        return;
+    };
+    let Some(input) = tx
+        .get("input")
+        .or_else(|| tx.get("data"))
+        .and_then(Value::as_str)
+        .and_then(|input| hex::decode(input.trim_start_matches("0x")).ok())
+    else {
+        return;
+    };
+    let Some(decoded) =
+        crate::decoder::tab_abis(webview_id).and_then(|abis| abis.decode_call(&input))
+    else {
+        return;
+    };
+    tracing::debug!(
+        webview_id,
         method,
-        "eth_blockNumber"
-            | "eth_getBlockByNumber"
-            | "eth_getBlockByHash"
-            | "eth_getBalance"
-            | "eth_getCode"
-            | "eth_getLogs"
-            | "eth_call"
-            | "eth_estimateGas"
-            | "eth_gasPrice"
-            | "eth_feeHistory"
-            | "eth_maxPriorityFeePerGas"
-            | "eth_getTransactionReceipt"
-            | "eth_getTransactionByHash"
-            | "eth_getStorageAt"
-            | "eth_getTransactionCount"
-            | "eth_sendRawTransaction"
-    )
+        to = %tx.get("to").and_then(Value::as_str).unwrap_or_default(),
+        call = %decoded,
+        "decoded rpc call"
+    );
 }
 
+/// Blocking form of [`proxy_rpc_async`] for callers on their own threads.
 pub(super) fn proxy_rpc(state: &AppState, req: &IpcRequest) -> Result<Value> {
+    state.rpc_client.block_on(proxy_rpc_async(state, req))
+}
+
+/// Where a request goes, picked up front so the returned future owns
+/// everything it needs and can run on the shared RPC runtime.
+enum Route {
+    /// Added chains are served by their own RPC, not the configured endpoints.
+    Direct(String),
+    Managed(RpcEndpointManager),
+}
+
+pub(super) fn proxy_rpc_async(
+    state: &AppState,
+    req: &IpcRequest,
+) -> impl Future<Output = Result<Value>> + Send + 'static {
+    let span = tracing::info_span!("rpc", method = %req.method);
+    let method = req.method.clone();
     let payload = serde_json::json!({
         "jsonrpc": "2.0",
         "id": 1,
@@ -39,6 +106,7 @@ pub(super) fn proxy_rpc(state: &AppState, req: &IpcRequest) -> Result<Value> {
     });
 
     tracing::debug!(
+        parent: &span,
         method = %req.method,
         params = %serde_json::to_string(&req.params).unwrap_or_default(),
         "rpc request"
@@ -52,45 +120,138 @@ pub(super) fn proxy_rpc(state: &AppState, req: &IpcRequest) -> Result<Value> {
         .expect("poisoned rpc_manager lock while proxying RPC request")
         .as_ref()
         .cloned();
-
-    let v = if let Some(m) = mgr_clone {
-        m.send_rpc(&payload)?
+    let route = if let Some(network) = super::chains::active_custom_network(state) {
+        Ok(Route::Direct(network.rpc_url))
+    } else if let Some(m) = mgr_clone {
+        Ok(Route::Managed(m))
     } else {
         // Fallback: use resolved config directly
-        let resolved = state.resolved.as_ref().ok_or_else(|| {
-            anyhow!("No RPC endpoint configured. Provide a config file with rpcUrl.")
-        })?;
-        let res = resolved
-            .http_client
-            .post(&resolved.rpc_url)
-            .json(&payload)
-            .send()
-            .context("rpc request failed")?;
-        res.json().context("rpc decode failed")?
+        state
+            .resolved
+            .as_ref()
+            .map(|resolved| Route::Direct(resolved.rpc_url.clone()))
+            .ok_or_else(|| {
+                anyhow!("No RPC endpoint configured. Provide a config file with rpcUrl.")
+            })
     };
+    let http = state.rpc_client.http().clone();
+    let chain_id = state.wallet.lock().expect("wallet").chain.chain_id;
+    let cache_key = read_cache_key(chain_id, &req.method, &req.params);
+    let cached = cache_key.as_deref().and_then(cached_read);
+    if method.starts_with("anvil_") {
+        // Node control can rewind or reset the chain under cached reads.
+        clear_read_cache();
+    }
 
-    let result_str = v
-        .get("result")
-        .map(|r| {
-            let s = r.to_string();
-            if s.len() > 200 {
-                format!("{}...", &s[..200])
-            } else {
-                s
+    async move {
+        if let Some(hit) = cached {
+            tracing::debug!(method = %method, "rpc read cache hit");
+            return Ok(hit);
+        }
+        let v: Value = match route? {
+            Route::Direct(url) => {
+                let res = http
+                    .post(&url)
+                    .json(&payload)
+                    .send()
+                    .await
+                    .context("rpc request failed")?;
+                res.json().await.context("rpc decode failed")?
             }
-        })
-        .unwrap_or_else(|| "null".to_string());
+            Route::Managed(m) => m.send(&payload).await?,
+        };
+
+        let result_str = v
+            .get("result")
+            .map(|r| {
+                let s = r.to_string();
+                if s.len() > 200 {
+                    format!("{}...", &s[..200])
+                } else {
+                    s
+                }
+            })
+            .unwrap_or_else(|| "null".to_string());
 
-    if let Some(err) = v.get("error") {
-        tracing::warn!(method = %req.method, error = %err, "rpc error response");
-        bail!("rpc error: {}", err);
+        if let Some(err) = v.get("error") {
+            tracing::warn!(method = %method, error = %err, "rpc error response");
+            bail!("rpc error: {}", err);
+        }
+
+        tracing::debug!(method = %method, result = %result_str, "rpc success response");
+        let result = v.get("result").cloned().unwrap_or(Value::Null);
+        if let Some(key) = cache_key.filter(|_| is_settled_read(&method, &result)) {
+            cache_read(key, &result);
+        }
+        Ok(result)
     }
+    .instrument(span)
+}
+
+/// How long a cached read is served before asking the node again. Bounds
+/// how long a reorged receipt or block can linger.
+const READ_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+/// Cached reads across all tabs; expired ones are dropped past this many.
+const READ_CACHE_MAX_ENTRIES: usize = 2048;
+
+/// Answers to reads that do not change once the node has them, keyed by
+/// chain, method and params. Dapps repeat these on every render.
+static READ_CACHE: Mutex<Option<HashMap<String, (Instant, Value)>>> = Mutex::new(None);
 
-    tracing::debug!(method = %req.method, result = %result_str, "rpc success response");
-    Ok(v.get("result").cloned().unwrap_or(Value::Null))
+fn read_cache_key(chain_id: u64, method: &str, params: &Value) -> Option<String> {
+    matches!(
+        method,
+        "eth_getTransactionReceipt" | "eth_getCode" | "eth_chainId" | "eth_getBlockByHash"
+    )
+    .then(|| format!("{chain_id}:{method}:{params}"))
 }
 
-fn rpc_request(state: &AppState, method: &str, params: Value) -> Result<Value> {
+/// Whether `result` is final: a receipt only once the transaction is mined,
+/// a block only once the node has it.
+fn is_settled_read(method: &str, result: &Value) -> bool {
+    match method {
+        "eth_getTransactionReceipt" => result.get("blockNumber").is_some_and(|n| !n.is_null()),
+        "eth_getBlockByHash" => !result.is_null(),
+        _ => true,
+    }
+}
+
+fn cached_read(key: &str) -> Option<Value> {
+    let mut cache = READ_CACHE.lock().expect("poisoned rpc read cache lock");
+    let entries = cache.as_mut()?;
+    match entries.get(key) {
+        Some((stored, value)) if stored.elapsed() < READ_CACHE_TTL => Some(value.clone()),
+        Some(_) => {
+            entries.remove(key);
+            None
+        }
+        None => None,
+    }
+}
+
+fn cache_read(key: String, value: &Value) {
+    let mut cache = READ_CACHE.lock().expect("poisoned rpc read cache lock");
+    let entries = cache.get_or_insert_with(HashMap::new);
+    if entries.len() >= READ_CACHE_MAX_ENTRIES {
+        entries.retain(|_, (stored, _)| stored.elapsed() < READ_CACHE_TTL);
+        if entries.len() >= READ_CACHE_MAX_ENTRIES {
+            entries.clear();
+        }
+    }
+    entries.insert(key, (Instant::now(), value.clone()));
+}
+
+fn clear_read_cache() {
+    if let Some(entries) = READ_CACHE
+        .lock()
+        .expect("poisoned rpc read cache lock")
+        .as_mut()
+    {
+        entries.clear();
+    }
+}
+
+pub(crate) fn rpc_request(state: &AppState, method: &str, params: Value) -> Result<Value> {
     if state.resolved.is_none() {
         bail!("No RPC endpoint configured. Provide a config file with rpcUrl.");
     }
@@ -100,6 +261,7 @@ fn rpc_request(state: &AppState, method: &str, params: Value) -> Result<Value> {
         provider_id: None,
         method: method.to_string(),
         params,
+        batch: None,
     };
     proxy_rpc(state, &req)
 }
@@ -120,35 +282,56 @@ fn rpc_quantity_u128(state: &AppState, method: &str, params: Value) -> Result<u1
     parse_hex_u128(s).ok_or_else(|| anyhow!("{} returned invalid quantity", method))
 }
 
-fn connected_sender(state: &AppState) -> Result<Address> {
+fn connected_sender(state: &AppState, webview_id: &str) -> Result<Address> {
     let account = state
-        .account()
+        .account_for(webview_id)
         .ok_or_else(|| anyhow!("No connected account available for transaction sending"))?;
     account
         .parse::<Address>()
         .with_context(|| format!("invalid connected account address: {account}"))
 }
 
-pub(super) fn build_filled_tx_request(
+fn is_authorized_sender(state: &AppState, webview_id: &str, from: Address) -> bool {
+    state
+        .tab_wallet(webview_id)
+        .and_then(|binding| binding.owner_of(&format!("0x{:x}", from)))
+        .is_some()
+}
+
+/// The account a dapp transaction is sent from: its `from` when the tab holds
+/// it, else the tab's primary account.
+pub(super) fn authorized_sender(
     state: &AppState,
-    tx_obj: Value,
-) -> Result<TransactionRequest> {
-    let mut tx: TransactionRequest =
-        serde_json::from_value(tx_obj).context("invalid eth_sendTransaction object")?;
-    let sender = connected_sender(state)?;
-
-    // Enforce backend account ownership for signing.
-    if let Some(from) = tx.from {
-        if from != sender {
-            bail!(
-                "Transaction 'from' ({:#x}) does not match connected account ({:#x})",
-                from,
-                sender
-            );
-        }
-    } else {
-        tx.from = Some(sender);
+    webview_id: &str,
+    tx: &TransactionRequest,
+) -> Result<Address> {
+    // Enforce account ownership for signing; the router already picked the
+    // backend that owns `from`.
+    match tx.from {
+        Some(from) if !is_authorized_sender(state, webview_id, from) => bail!(
+            "Transaction 'from' ({:#x}) is not an account connected to this dapp",
+            from
+        ),
+        Some(from) => Ok(from),
+        None => connected_sender(state, webview_id),
     }
+}
+
+/// Fills chain id, nonce, gas and fee defaults for a transaction sent by `sender`.
+/// A transaction ready to sign.
+pub(super) struct FilledTransaction {
+    pub tx: TransactionRequest,
+    /// Contracts and storage slots the transaction touches, when access list
+    /// generation is enabled. Attached to `tx` only if that lowers its gas.
+    pub touched: Option<AccessList>,
+}
+
+pub(super) fn fill_tx_request(
+    state: &AppState,
+    mut tx: TransactionRequest,
+    sender: Address,
+) -> Result<FilledTransaction> {
+    tx.from = Some(sender);
 
     if tx.chain_id.is_none() {
         tx.chain_id = Some(
@@ -172,7 +355,8 @@ pub(super) fn build_filled_tx_request(
         )?);
     }
 
-    if tx.gas.is_none() {
+    let gas_estimated = tx.gas.is_none();
+    if gas_estimated {
         let estimate_obj =
             serde_json::to_value(&tx).context("failed to encode tx for estimateGas")?;
         tx.gas = Some(rpc_quantity_u64(
@@ -182,6 +366,18 @@ pub(super) fn build_filled_tx_request(
         )?);
     }
 
+    let touched = if tx.access_list.is_none() && access_lists_enabled(state) {
+        match generate_access_list(state, &mut tx, gas_estimated) {
+            Ok(list) => Some(list),
+            Err(err) => {
+                tracing::warn!(error = %err, "access list generation failed; sending without one");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     // Fill fee defaults when omitted by dapp.
     let has_legacy_fee = tx.gas_price.is_some();
     let has_1559_fee = tx.max_fee_per_gas.is_some() || tx.max_priority_fee_per_gas.is_some();
@@ -212,7 +408,59 @@ pub(super) fn build_filled_tx_request(
         tx.max_priority_fee_per_gas = None;
     }
 
-    Ok(tx)
+    Ok(FilledTransaction { tx, touched })
+}
+
+fn access_lists_enabled(state: &AppState) -> bool {
+    state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.config_path.as_ref())
+        .is_some_and(|path| crate::settings::load_settings(path).access_lists)
+}
+
+/// Asks the node which contracts and storage slots `tx` touches. When the
+/// client estimated the gas itself and the list lowers that estimate, the
+/// list is attached and the gas re-estimated with it.
+fn generate_access_list(
+    state: &AppState,
+    tx: &mut TransactionRequest,
+    gas_estimated: bool,
+) -> Result<AccessList> {
+    let request = serde_json::to_value(&*tx).context("failed to encode tx for createAccessList")?;
+    let result: AccessListResult = serde_json::from_value(rpc_request(
+        state,
+        "eth_createAccessList",
+        json!([request]),
+    )?)
+    .context("invalid eth_createAccessList response")?;
+    if let Some(err) = result.error {
+        bail!("eth_createAccessList: {err}");
+    }
+    let list = result.access_list;
+    let Some(gas_without) = tx.gas.filter(|_| gas_estimated && !list.is_empty()) else {
+        return Ok(list);
+    };
+
+    let mut with_list = tx.clone();
+    with_list.gas = None;
+    with_list.access_list = Some(list.clone());
+    let estimate_obj =
+        serde_json::to_value(&with_list).context("failed to encode tx for estimateGas")?;
+    let gas_with = rpc_quantity_u64(state, "eth_estimateGas", json!([estimate_obj]))?;
+    let attached = gas_with < gas_without;
+    if attached {
+        tx.access_list = Some(list.clone());
+        tx.gas = Some(gas_with);
+    }
+    tracing::info!(
+        entries = list.len(),
+        gas_without,
+        gas_with,
+        attached,
+        "generated access list"
+    );
+    Ok(list)
 }
 
 pub(super) fn build_typed_tx(mut tx: TransactionRequest) -> Result<TypedTransaction> {
@@ -262,3 +510,50 @@ pub(super) fn decode_0x_hex(s: &str) -> Option<Vec<u8>> {
     }
     hex::decode(s).ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{cache_read, cached_read, is_settled_read, read_cache_key};
+    use serde_json::{Value, json};
+
+    #[test]
+    fn only_immutable_reads_have_a_cache_key() {
+        assert!(read_cache_key(1, "eth_blockNumber", &Value::Null).is_none());
+        assert!(read_cache_key(1, "eth_getTransactionReceipt", &json!(["0xabc"])).is_some());
+    }
+
+    #[test]
+    fn cache_keys_are_per_chain() {
+        let params = json!(["0xabc"]);
+        assert_ne!(
+            read_cache_key(1, "eth_getTransactionReceipt", &params),
+            read_cache_key(31_337_001, "eth_getTransactionReceipt", &params)
+        );
+    }
+
+    #[test]
+    fn pending_or_missing_results_are_not_settled() {
+        assert!(!is_settled_read("eth_getTransactionReceipt", &Value::Null));
+        assert!(!is_settled_read(
+            "eth_getTransactionReceipt",
+            &json!({ "blockNumber": null })
+        ));
+        assert!(!is_settled_read("eth_getBlockByHash", &Value::Null));
+    }
+
+    #[test]
+    fn mined_receipts_are_settled() {
+        let receipt = json!({ "blockNumber": "0x10", "status": "0x1" });
+        assert!(is_settled_read("eth_getTransactionReceipt", &receipt));
+    }
+
+    #[test]
+    fn cached_reads_are_served_back() {
+        let key =
+            read_cache_key(31_337_001, "eth_getTransactionReceipt", &json!(["0xabc"])).unwrap();
+        let receipt = json!({ "blockNumber": "0x10", "status": "0x1" });
+        assert!(cached_read(&key).is_none());
+        cache_read(key.clone(), &receipt);
+        assert_eq!(cached_read(&key), Some(receipt));
+    }
+}
@@ -2,12 +2,16 @@ use anyhow::{Context, Result, anyhow, bail};
 use serde_json::Value;
 use wry::WebView;
 
+use crate::hardware::HardwareDevice;
 use crate::ipc_contract::{IpcRequest, WalletSelectorMethod};
+use crate::settings::LastWallet;
 use crate::state::lock_or_err;
 use crate::state::{AppState, UserEvent, WalletBackend};
-use crate::walletconnect::{WalletConnectBridge, WalletConnectConfig, WalletConnectSession};
+use crate::walletconnect::{WalletConnectBridge, WalletConnectConfig};
 use crate::webview_manager::{AppWebViewKind, WebViewManager};
 
+const LEDGER_BLE_SCAN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Handle IPC from the wallet selector tab.
 pub(super) fn handle_wallet_selector_ipc(
     _webview: &WebView,
@@ -22,56 +26,100 @@ pub(super) fn handle_wallet_selector_ipc(
     }
 
     match req.wallet_selector_method() {
-        Some(WalletSelectorMethod::GetCapabilities) => Ok(Some(serde_json::json!({
-            "localSignerAvailable": local_signer_available(state),
-            "localSignerRequiresPrivateKey": local_signer_requires_private_key(state),
-        }))),
+        Some(WalletSelectorMethod::GetCapabilities) => {
+            let requested_by = lock_or_err(&state.pending_connect, "pending_connect")?
+                .front()
+                .map(|pending| manager.dapp_identity_for(&pending.webview_id));
+            Ok(Some(serde_json::json!({
+                "localSignerAvailable": local_signer_available(state),
+                "localDeveloperKey": has_developer_key(state),
+                "localKeystoreAddress": keystore_config_path(state)
+                    .ok()
+                    .and_then(|path| crate::keystore::stored_accounts(path).first().copied())
+                    .map(|address| format!("0x{address:x}")),
+                "requestedBy": requested_by,
+                "ledgerBleDevice": remembered_ledger_ble(state),
+                "smartAccountAvailable": state
+                    .resolved
+                    .as_ref()
+                    .is_some_and(|r| r.smart_account.is_some() && r.fork.is_none()),
+                "smartAccountOwner": crate::smart_account::available_owner(state)
+                    .map(|(owner_key, _)| owner_key),
+                "remoteSignerUrl": state
+                    .resolved
+                    .as_ref()
+                    .filter(|r| r.fork.is_none())
+                    .and_then(|r| r.remote_signer.as_ref())
+                    .map(|settings| settings.url.clone()),
+                "forkSession": fork_source(state).is_some(),
+            })))
+        }
         Some(WalletSelectorMethod::ConnectLocal) => {
             tracing::info!("wallet-selector connecting local signer");
-            let signer_hex = resolve_local_signer_hex(state, req)?;
-            let signer: alloy_signer_local::PrivateKeySigner = signer_hex
+            state.lock.ensure_unlocked()?;
+            let signer: alloy_signer_local::PrivateKeySigner = developer_private_key(state)?
                 .parse()
                 .context("failed to parse signing private key")?;
-            let account = format!("0x{:x}", signer.address());
-
-            // Store signer
-            {
-                let mut s = lock_or_err(&state.signer, "signer")?;
-                *s = Some(std::sync::Arc::new(signer));
-            }
-            // Set backend
-            {
-                let mut wb = lock_or_err(&state.wallet_backend, "wallet_backend")?;
-                *wb = Some(WalletBackend::Local);
-            }
-            // Update wallet state
-            {
-                let mut ws = lock_or_err(&state.wallet, "wallet")?;
-                ws.authorized = true;
-                ws.account = Some(account.clone());
-            }
-
-            // Resolve the pending eth_requestAccounts
-            resolve_pending_connect(state, vec![account]);
-
-            // Close the selector tab
-            let _ = state.proxy.send_event(UserEvent::CloseWalletSelector);
-
+            connect_local_signer(state, vec![signer], Some(LastWallet::Local));
+            Ok(Some(Value::Bool(true)))
+        }
+        Some(WalletSelectorMethod::ImportLocalKey) => {
+            tracing::info!("wallet-selector importing local key");
+            state.lock.ensure_unlocked()?;
+            let params = req
+                .params
+                .get(0)
+                .ok_or_else(|| anyhow!("missing key parameter"))?;
+            let secret = params
+                .get("secret")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("Enter a private key or recovery phrase"))?;
+            let password = params
+                .get("password")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let signer = crate::keystore::import(keystore_config_path(state)?, secret, password)?;
+            connect_local_signer(state, vec![signer], None);
+            Ok(Some(Value::Bool(true)))
+        }
+        Some(WalletSelectorMethod::UnlockLocalKey) => {
+            tracing::info!("wallet-selector unlocking local key");
+            state.lock.ensure_unlocked()?;
+            let password = req
+                .params
+                .get(0)
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let signers = crate::keystore::unlock(keystore_config_path(state)?, password)?;
+            connect_local_signer(state, signers, None);
+            Ok(Some(Value::Bool(true)))
+        }
+        Some(WalletSelectorMethod::ForgetLocalKey) => {
+            let password = req
+                .params
+                .get(0)
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            crate::keystore::forget(keystore_config_path(state)?, password)?;
             Ok(Some(Value::Bool(true)))
         }
         Some(WalletSelectorMethod::ConnectWalletConnect) => {
             tracing::info!("wallet-selector connecting walletconnect");
+            crate::fork::ensure_backend_allowed(fork_source(state), WalletBackend::WalletConnect)?;
             let resolved = state.resolved.as_ref();
             let project_id = resolved
                 .and_then(|r| r.walletconnect_project_id.clone())
                 .ok_or_else(|| {
                     anyhow!("WalletConnect requires walletConnect.projectId in config or VIBEFI_WC_PROJECT_ID env var")
                 })?;
-            let relay_url = resolved.and_then(|r| r.walletconnect_relay_url.clone());
+            let relay_urls = resolved
+                .map(|r| r.walletconnect_relay_urls.clone())
+                .unwrap_or_default();
 
             let bridge = WalletConnectBridge::spawn(WalletConnectConfig {
                 project_id,
-                relay_url,
+                relay_urls,
+                store_path: crate::profiles::walletconnect_store(state.resolved.as_deref()),
             })
             .context("failed to initialize WalletConnect bridge")?;
             let bridge = std::sync::Arc::new(std::sync::Mutex::new(bridge));
@@ -83,6 +131,7 @@ pub(super) fn handle_wallet_selector_ipc(
             }
 
             let chain_id = lock_or_err(&state.wallet, "wallet")?.chain.chain_id;
+            let state = state.clone();
             let proxy = state.proxy.clone();
             let ipc_id = req.id;
             let wv_id = webview_id.to_string();
@@ -99,8 +148,19 @@ pub(super) fn handle_wallet_selector_ipc(
                                     .send_event(UserEvent::WalletConnectPairing { uri, qr_svg });
                             }
                         }
+                        if let Some(status) = event.relay.clone() {
+                            let _ = proxy_for_events
+                                .send_event(UserEvent::WalletConnectRelayStatus { status });
+                        }
                     })
                 };
+                if result.is_ok() {
+                    super::reconnect::remember(&state, Some(LastWallet::WalletConnect));
+                    crate::walletconnect::spawn_relay_monitor(
+                        std::sync::Arc::downgrade(&bridge),
+                        proxy.clone(),
+                    );
+                }
                 let mapped = result.map_err(|e| e.to_string());
                 let _ = proxy.send_event(UserEvent::WalletConnectResult {
                     webview_id: wv_id,
@@ -115,14 +175,9 @@ pub(super) fn handle_wallet_selector_ipc(
         Some(WalletSelectorMethod::ConnectHardware) => {
             tracing::info!("wallet-selector connecting hardware wallet");
             let chain_id = lock_or_err(&state.wallet, "wallet")?.chain.chain_id;
-            let proxy = state.proxy.clone();
-            let hardware_signer = state.hardware_signer.clone();
-            let wallet_backend = state.wallet_backend.clone();
-            let wallet = state.wallet.clone();
-            let pending_connect = state.pending_connect.clone();
+            let state = state.clone();
             let ipc_id = req.id;
             let wv_id = webview_id.to_string();
-            let chain_id_hex = state.chain_id_hex();
 
             std::thread::spawn(move || {
                 let rt = match tokio::runtime::Builder::new_current_thread()
@@ -132,7 +187,7 @@ pub(super) fn handle_wallet_selector_ipc(
                     Ok(rt) => rt,
                     Err(e) => {
                         tracing::error!(error = %e, "hardware failed to create tokio runtime");
-                        let _ = proxy.send_event(UserEvent::HardwareSignResult {
+                        let _ = state.proxy.send_event(UserEvent::SignerResult {
                             webview_id: wv_id,
                             ipc_id,
                             result: Err(format!("runtime error: {e}")),
@@ -142,57 +197,10 @@ pub(super) fn handle_wallet_selector_ipc(
                 };
 
                 match rt.block_on(crate::hardware::detect_and_connect(chain_id)) {
-                    Ok(device) => {
-                        let account = crate::hardware::get_address(&device);
-                        tracing::info!(account, "hardware connected");
-
-                        // Store hardware signer
-                        {
-                            let mut hs = hardware_signer.lock().expect("hardware_signer");
-                            *hs = Some(device);
-                        }
-                        // Set backend
-                        {
-                            let mut wb = wallet_backend.lock().expect("wallet_backend");
-                            *wb = Some(WalletBackend::Hardware);
-                        }
-                        // Update wallet state
-                        {
-                            let mut ws = wallet.lock().expect("wallet");
-                            ws.authorized = true;
-                            ws.account = Some(account.clone());
-                        }
-
-                        // Resolve pending connect if any
-                        let pending: Vec<_> = pending_connect
-                            .lock()
-                            .expect("pending_connect")
-                            .drain(..)
-                            .collect();
-                        for pc in pending {
-                            let _ = proxy.send_event(UserEvent::WalletConnectResult {
-                                webview_id: pc.webview_id,
-                                ipc_id: pc.ipc_id,
-                                result: Ok(WalletConnectSession {
-                                    accounts: vec![account.clone()],
-                                    chain_id_hex: chain_id_hex.clone(),
-                                }),
-                            });
-                        }
-
-                        // Respond OK to the selector tab
-                        let _ = proxy.send_event(UserEvent::HardwareSignResult {
-                            webview_id: wv_id,
-                            ipc_id,
-                            result: Ok("true".to_string()),
-                        });
-
-                        // Close selector
-                        let _ = proxy.send_event(UserEvent::CloseWalletSelector);
-                    }
+                    Ok(device) => complete_hardware_connect(&state, device, wv_id, ipc_id, "true"),
                     Err(e) => {
                         tracing::warn!(error = %e, "hardware connection failed");
-                        let _ = proxy.send_event(UserEvent::HardwareSignResult {
+                        let _ = state.proxy.send_event(UserEvent::SignerResult {
                             webview_id: wv_id,
                             ipc_id,
                             result: Err(e.to_string()),
@@ -201,63 +209,287 @@ pub(super) fn handle_wallet_selector_ipc(
                 }
             });
 
-            // Response comes later via HardwareSignResult event
+            // Response comes later via SignerResult event
+            Ok(None)
+        }
+        Some(WalletSelectorMethod::ConnectLattice) => {
+            let device_id = req
+                .params
+                .get(0)
+                .and_then(|v| v.get("deviceId"))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| anyhow!("Lattice device ID is required"))?
+                .to_string();
+            tracing::info!("wallet-selector connecting lattice");
+            spawn_lattice_connect(state, webview_id, req.id, move || {
+                crate::lattice::connect(&device_id)
+            });
+            Ok(None)
+        }
+        Some(WalletSelectorMethod::PairLattice) => {
+            let code = req
+                .params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("pairing code is required"))?
+                .to_string();
+            tracing::info!("wallet-selector pairing lattice");
+            spawn_lattice_connect(state, webview_id, req.id, move || {
+                crate::lattice::pair(&code).map(Some)
+            });
+            Ok(None)
+        }
+        Some(WalletSelectorMethod::ScanLedgerBle) => {
+            tracing::info!("wallet-selector scanning for bluetooth ledgers");
+            let proxy = state.proxy.clone();
+            let ipc_id = req.id;
+            let wv_id = webview_id.to_string();
+            std::thread::spawn(move || {
+                let result = crate::ledger_ble::scan(LEDGER_BLE_SCAN_TIMEOUT)
+                    .map(|devices| serde_json::json!(devices))
+                    .map_err(|e| format!("{e:#}"));
+                let _ = proxy.send_event(UserEvent::RpcResult {
+                    webview_id: wv_id,
+                    ipc_id,
+                    result,
+                });
+            });
+            Ok(None)
+        }
+        Some(WalletSelectorMethod::ConnectLedgerBle) => {
+            let device: crate::ledger_ble::BleDevice = req
+                .params
+                .get(0)
+                .cloned()
+                .ok_or_else(|| anyhow!("Ledger device is required"))
+                .and_then(|v| serde_json::from_value(v).context("invalid Ledger device"))?;
+            tracing::info!(
+                device = device.name,
+                "wallet-selector connecting ledger over bluetooth"
+            );
+            let state = state.clone();
+            let ipc_id = req.id;
+            let wv_id = webview_id.to_string();
+            std::thread::spawn(move || match crate::ledger_ble::connect(&device) {
+                Ok(signer) => {
+                    remember_ledger_ble(&state, device);
+                    complete_hardware_connect(
+                        &state,
+                        HardwareDevice::LedgerBle(signer),
+                        wv_id,
+                        ipc_id,
+                        "true",
+                    );
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "ledger bluetooth connection failed");
+                    let _ = state.proxy.send_event(UserEvent::SignerResult {
+                        webview_id: wv_id,
+                        ipc_id,
+                        result: Err(format!("{e:#}")),
+                    });
+                }
+            });
+            Ok(None)
+        }
+        Some(WalletSelectorMethod::ConnectSmartAccount) => {
+            tracing::info!("wallet-selector connecting smart account");
+            crate::fork::ensure_backend_allowed(fork_source(state), WalletBackend::SmartAccount)?;
+            let state = state.clone();
+            let ipc_id = req.id;
+            let wv_id = webview_id.to_string();
+            // Derivation asks the factory over RPC; keep it off the event loop.
+            std::thread::spawn(move || {
+                let result = crate::smart_account::connect(&state).map(|account| {
+                    let address = format!("{:#x}", account.address);
+                    super::reconnect::remember(
+                        &state,
+                        super::reconnect::smart_account_wallet(&state),
+                    );
+                    *state.smart_account.lock().expect("smart_account") = Some(account);
+                    resolve_pending_connect(&state, WalletBackend::SmartAccount, vec![address]);
+                    let _ = state.proxy.send_event(UserEvent::CloseWalletSelector);
+                    Value::Bool(true)
+                });
+                if let Err(e) = &result {
+                    tracing::warn!(error = %format!("{e:#}"), "smart account connection failed");
+                }
+                let _ = state.proxy.send_event(UserEvent::RpcResult {
+                    webview_id: wv_id,
+                    ipc_id,
+                    result: result.map_err(|e| format!("{e:#}")),
+                });
+            });
+            Ok(None)
+        }
+        Some(WalletSelectorMethod::ConnectRemoteSigner) => {
+            tracing::info!("wallet-selector connecting remote signer");
+            crate::fork::ensure_backend_allowed(fork_source(state), WalletBackend::Remote)?;
+            let state = state.clone();
+            let ipc_id = req.id;
+            let wv_id = webview_id.to_string();
+            std::thread::spawn(move || {
+                let result = super::remote_signer::connect(&state).map(|accounts| {
+                    super::reconnect::remember(&state, Some(LastWallet::Remote));
+                    resolve_pending_connect(&state, WalletBackend::Remote, accounts);
+                    let _ = state.proxy.send_event(UserEvent::CloseWalletSelector);
+                    Value::Bool(true)
+                });
+                if let Err(e) = &result {
+                    tracing::warn!(error = %format!("{e:#}"), "remote signer connection failed");
+                }
+                let _ = state.proxy.send_event(UserEvent::RpcResult {
+                    webview_id: wv_id,
+                    ipc_id,
+                    result: result.map_err(|e| format!("{e:#}")),
+                });
+            });
             Ok(None)
         }
         None => bail!("Unknown wallet selector method: {}", req.method),
     }
 }
 
-fn local_signer_available(state: &AppState) -> bool {
-    is_test_network(state)
+fn remembered_ledger_ble(state: &AppState) -> Option<crate::ledger_ble::BleDevice> {
+    let config_path = state.resolved.as_ref()?.config_path.as_ref()?;
+    crate::settings::load_settings(config_path).ledger_ble_device
 }
 
-fn local_signer_requires_private_key(state: &AppState) -> bool {
-    local_signer_available(state) && !has_configured_local_signer(state)
+/// Saves the Ledger so the selector offers it again without a scan.
+fn remember_ledger_ble(state: &AppState, device: crate::ledger_ble::BleDevice) {
+    let Some(config_path) = state.resolved.as_ref().and_then(|r| r.config_path.as_ref()) else {
+        return;
+    };
+    let mut settings = crate::settings::load_settings(config_path);
+    if settings.ledger_ble_device.as_ref() == Some(&device) {
+        return;
+    }
+    settings.ledger_ble_device = Some(device);
+    if let Err(e) = crate::settings::save_settings(config_path, &settings) {
+        tracing::warn!(error = %format!("{e:#}"), "failed to remember ledger bluetooth device");
+    }
 }
 
-fn has_configured_local_signer(state: &AppState) -> bool {
-    state
-        .resolved
-        .as_ref()
-        .and_then(|r| r.developer_private_key.as_ref())
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .is_some()
+/// Runs a Lattice connect or pair step off the UI thread. The selector gets
+/// `"needsPairing"` back when the device still has to be paired.
+fn spawn_lattice_connect<F>(state: &AppState, webview_id: &str, ipc_id: u64, task: F)
+where
+    F: FnOnce() -> Result<Option<crate::lattice::LatticeSigner>> + Send + 'static,
+{
+    let state = state.clone();
+    let wv_id = webview_id.to_string();
+    std::thread::spawn(move || match task() {
+        Ok(Some(signer)) => complete_hardware_connect(
+            &state,
+            HardwareDevice::Lattice(signer),
+            wv_id,
+            ipc_id,
+            "paired",
+        ),
+        Ok(None) => {
+            let _ = state.proxy.send_event(UserEvent::SignerResult {
+                webview_id: wv_id,
+                ipc_id,
+                result: Ok("needsPairing".to_string()),
+            });
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "lattice connection failed");
+            let _ = state.proxy.send_event(UserEvent::SignerResult {
+                webview_id: wv_id,
+                ipc_id,
+                result: Err(format!("{e:#}")),
+            });
+        }
+    });
 }
 
-fn resolve_local_signer_hex(state: &AppState, req: &IpcRequest) -> Result<String> {
-    if !is_test_network(state) {
-        return Err(anyhow!("Local signer is only available on test networks"));
+/// Installs a connected hardware device as the active wallet, resolves any
+/// pending dapp connect, answers the selector and closes it.
+fn complete_hardware_connect(
+    state: &AppState,
+    device: HardwareDevice,
+    webview_id: String,
+    ipc_id: u64,
+    response: &str,
+) {
+    let account = crate::hardware::get_address(&device);
+    tracing::info!(account, "hardware connected");
+    super::reconnect::remember(state, super::reconnect::hardware_wallet(&device));
+
+    // Store hardware signer
+    {
+        let mut hs = state.hardware_signer.lock().expect("hardware_signer");
+        *hs = Some(device);
     }
 
-    if let Some(private_key) = requested_local_private_key(req) {
-        return Ok(private_key);
+    // Bind the waiting tabs and resolve their eth_requestAccounts
+    resolve_pending_connect(state, WalletBackend::Hardware, vec![account]);
+
+    // Respond OK to the selector tab
+    let _ = state.proxy.send_event(UserEvent::SignerResult {
+        webview_id,
+        ipc_id,
+        result: Ok(response.to_string()),
+    });
+
+    // Close selector
+    let _ = state.proxy.send_event(UserEvent::CloseWalletSelector);
+}
+
+/// Stores `signers` as the local keys, the first one active, binds the
+/// waiting tabs to all of them and closes the selector. Keystore keys need
+/// their password, so they are not reconnected at startup.
+fn connect_local_signer(
+    state: &AppState,
+    signers: Vec<alloy_signer_local::PrivateKeySigner>,
+    remembered: Option<LastWallet>,
+) {
+    *state.signer.lock().expect("signer") = signers.into_iter().map(std::sync::Arc::new).collect();
+    super::reconnect::remember(state, remembered);
+    resolve_pending_connect(state, WalletBackend::Local, state.local_accounts());
+    let _ = state.proxy.send_event(UserEvent::CloseWalletSelector);
+}
+
+fn local_signer_available(state: &AppState) -> bool {
+    has_developer_key(state) || keystore_config_path(state).is_ok()
+}
+
+/// The configured `developerPrivateKey`, which only signs on test networks.
+fn has_developer_key(state: &AppState) -> bool {
+    is_test_network(state) && developer_private_key(state).is_ok()
+}
+
+fn developer_private_key(state: &AppState) -> Result<String> {
+    if !is_test_network(state) {
+        bail!("The configured developer key only signs on test networks");
     }
+    state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.developer_private_key.as_deref())
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| anyhow!("No developer key is configured"))
+}
 
-    let explicit_key = state
+fn keystore_config_path(state: &AppState) -> Result<&std::path::Path> {
+    state
         .resolved
         .as_ref()
-        .and_then(|r| r.developer_private_key.clone())
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty());
-    if let Some(key) = explicit_key {
-        Ok(key)
-    } else {
-        Err(anyhow!(
-            "No local signer configured for this test network. Enter a private key in the wallet selector."
-        ))
-    }
+        .and_then(|r| r.config_path.as_deref())
+        .ok_or_else(|| anyhow!("storing a key needs a config file"))
 }
 
-fn requested_local_private_key(req: &IpcRequest) -> Option<String> {
-    req.params
-        .as_array()
-        .and_then(|params| params.first())
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-        .map(ToOwned::to_owned)
+/// The fork a `--fork` session runs against.
+fn fork_source(state: &AppState) -> Option<&crate::fork::ForkSource> {
+    state
+        .resolved
+        .as_ref()
+        .and_then(|resolved| resolved.fork.as_ref())
 }
 
 fn is_test_network(state: &AppState) -> bool {
@@ -268,23 +500,10 @@ fn is_test_network(state: &AppState) -> bool {
         .unwrap_or(false)
 }
 
-/// Resolve a pending `eth_requestAccounts` from a dapp tab by sending the
-/// account list back to the original webview.
-fn resolve_pending_connect(state: &AppState, accounts: Vec<String>) {
-    let pending: Vec<_> = state
-        .pending_connect
-        .lock()
-        .expect("pending_connect")
-        .drain(..)
-        .collect();
-    for pc in pending {
-        let _ = state.proxy.send_event(UserEvent::WalletConnectResult {
-            webview_id: pc.webview_id,
-            ipc_id: pc.ipc_id,
-            result: Ok(WalletConnectSession {
-                accounts: accounts.clone(),
-                chain_id_hex: state.chain_id_hex(),
-            }),
-        });
-    }
+/// Hands the newly connected backend to the event loop, which binds every
+/// dapp tab waiting in `pending_connect` to it.
+fn resolve_pending_connect(state: &AppState, backend: WalletBackend, accounts: Vec<String>) {
+    let _ = state
+        .proxy
+        .send_event(UserEvent::WalletConnected { backend, accounts });
 }
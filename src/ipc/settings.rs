@@ -1,3 +1,4 @@
+use alloy_primitives::Address;
 use anyhow::{Context, Result, anyhow, bail};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -6,8 +7,26 @@ use std::path::Path;
 
 use crate::config::IpfsFetchBackend;
 use crate::ipc_contract::IpcRequest;
+use crate::policy::ApprovalRule;
+use crate::registry::{DEFAULT_DOWNLOAD_CONCURRENCY, MAX_DOWNLOAD_CONCURRENCY};
 use crate::rpc_manager::{DEFAULT_MAX_CONCURRENT_RPC, RpcEndpoint};
-use crate::state::AppState;
+use crate::settings::{
+    BackgroundRefreshSettings, CommunityRegistry, FiatPriceSettings, HeavyTabSettings,
+};
+use crate::state::{AppState, UserEvent};
+use crate::storage::{DEFAULT_STORAGE_QUOTA_MB, PartitionUsage};
+use crate::tab_usage::{ProcessUsage, TabUsage};
+use crate::webview_manager::WebViewManager;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionSettings {
+    access_lists: bool,
+    #[serde(default)]
+    offline_signing: bool,
+    #[serde(default)]
+    fiat_prices: FiatPriceSettings,
+}
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -15,6 +34,34 @@ struct IpfsSettingsResponse {
     fetch_backend: IpfsFetchBackend,
     gateway_endpoint: String,
     default_gateway_endpoint: String,
+    download_concurrency: usize,
+    default_download_concurrency: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TabUsageResponse {
+    tabs: Vec<TabUsageRow>,
+    /// Unset where the process tree cannot be sampled.
+    process: Option<ProcessUsage>,
+    heavy_tab: HeavyTabSettings,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TabUsageRow {
+    id: String,
+    label: String,
+    suspended: bool,
+    usage: Option<TabUsage>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SiteDataResponse {
+    quota_mb: u64,
+    default_quota_mb: u64,
+    partitions: Vec<PartitionUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,6 +70,9 @@ struct SetIpfsSettingsRequest {
     fetch_backend: IpfsFetchBackend,
     #[serde(default)]
     gateway_endpoint: Option<String>,
+    /// Left as saved when unset.
+    #[serde(default)]
+    download_concurrency: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,9 +82,181 @@ struct SetRpcAndIpfsSettingsRequest {
     fetch_backend: IpfsFetchBackend,
     #[serde(default)]
     gateway_endpoint: Option<String>,
+    /// Left as saved when unset.
+    #[serde(default)]
+    download_concurrency: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrefetchSettings {
+    metered_connection: bool,
+    #[serde(default)]
+    bundle_cache_max_mb: Option<u64>,
+    #[serde(default)]
+    icon_prefetch_kb: Option<u64>,
+    #[serde(default)]
+    background_refresh: BackgroundRefreshSettings,
+    #[serde(default, skip_deserializing)]
+    pinned_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NftSettingsResponse {
+    indexer: Option<String>,
+    /// Accounts connected in any tab, offered in the gallery's picker.
+    accounts: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApprovalRulesResponse {
+    rules: Vec<ApprovalRule>,
+    /// Chains rules can be added for; every other chain always prompts.
+    chains: Vec<u64>,
+    /// Open dapps a rule can be scoped to.
+    dapps: Vec<RuleDapp>,
+}
+
+#[derive(Debug, Serialize)]
+struct RuleDapp {
+    subject: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetNftSettingsRequest {
+    #[serde(default)]
+    indexer: Option<String>,
 }
 
-fn open_directory_in_file_manager(path: &Path) -> Result<()> {
+/// Sets the lock passphrase, or removes it when `passphrase` is absent.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetLockPassphraseRequest {
+    #[serde(default)]
+    current_passphrase: Option<String>,
+    #[serde(default)]
+    passphrase: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RegistriesResponse {
+    default_registry: String,
+    default_label: &'static str,
+    chain_id: u64,
+    community: Vec<CommunityRegistryEntry>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommunityRegistryEntry {
+    #[serde(flatten)]
+    registry: CommunityRegistry,
+    /// Whether the registry is on the configured chain and so gets listed.
+    active: bool,
+}
+
+const MAX_REGISTRY_LABEL_CHARS: usize = 40;
+
+fn parse_address(value: &str, what: &str) -> Result<String> {
+    let address: Address = value
+        .trim()
+        .parse()
+        .with_context(|| format!("{what} is not a valid address: {value:?}"))?;
+    Ok(format!("{address:#x}"))
+}
+
+/// Validates a registry submitted from settings, normalizing its addresses to
+/// lowercase so lookups and duplicate checks compare equal.
+fn normalize_registry(mut registry: CommunityRegistry) -> Result<CommunityRegistry> {
+    registry.address = parse_address(&registry.address, "registry")?;
+    if registry.chain_id == 0 {
+        bail!("chainId must not be 0");
+    }
+    registry.label = registry.label.trim().to_string();
+    if registry.label.is_empty() {
+        bail!("registry label is required");
+    }
+    if registry.label.chars().count() > MAX_REGISTRY_LABEL_CHARS {
+        bail!("registry label exceeds {MAX_REGISTRY_LABEL_CHARS} characters");
+    }
+    let mut publishers = Vec::new();
+    for publisher in registry.trusted_publishers.iter().map(|p| p.trim()) {
+        if publisher.is_empty() {
+            continue;
+        }
+        let publisher = parse_address(publisher, "trusted publisher")?;
+        if !publishers.contains(&publisher) {
+            publishers.push(publisher);
+        }
+    }
+    registry.trusted_publishers = publishers;
+    Ok(registry)
+}
+
+/// Validates price settings from the settings page. Empty fields fall back to
+/// the defaults.
+fn normalize_fiat_prices(mut prices: FiatPriceSettings) -> Result<FiatPriceSettings> {
+    prices.currency = prices
+        .currency
+        .map(|currency| currency.trim().to_ascii_lowercase())
+        .filter(|currency| !currency.is_empty());
+    if let Some(currency) = prices
+        .currency
+        .as_ref()
+        .filter(|currency| currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_lowercase()))
+    {
+        bail!("currency must be a three-letter code such as USD, got {currency:?}");
+    }
+    prices.endpoint = prices
+        .endpoint
+        .map(|endpoint| endpoint.trim().trim_end_matches('/').to_string())
+        .filter(|endpoint| !endpoint.is_empty());
+    if let Some(endpoint) = &prices.endpoint {
+        let url = reqwest::Url::parse(endpoint)
+            .with_context(|| format!("price endpoint is not a URL: {endpoint:?}"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("price endpoint must use http or https");
+        }
+    }
+    Ok(prices)
+}
+
+/// Validates the NFT indexer template. An empty value clears it, which falls
+/// back to scanning Transfer logs.
+fn validate_download_concurrency(concurrency: Option<usize>) -> Result<()> {
+    if concurrency.is_some_and(|n| !(1..=MAX_DOWNLOAD_CONCURRENCY).contains(&n)) {
+        bail!("downloadConcurrency must be between 1 and {MAX_DOWNLOAD_CONCURRENCY}");
+    }
+    Ok(())
+}
+
+fn normalize_nft_indexer(indexer: Option<String>) -> Result<Option<String>> {
+    let Some(indexer) = indexer
+        .map(|indexer| indexer.trim().to_string())
+        .filter(|indexer| !indexer.is_empty())
+    else {
+        return Ok(None);
+    };
+    if !indexer.contains("{account}") {
+        bail!("NFT indexer URL must contain an {{account}} placeholder");
+    }
+    let sample = indexer
+        .replace("{account}", "0x0")
+        .replace("{chainId}", "1");
+    let url = reqwest::Url::parse(&sample)
+        .with_context(|| format!("NFT indexer is not a URL: {indexer:?}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("NFT indexer must use http or https");
+    }
+    Ok(Some(indexer))
+}
+
+pub(crate) fn open_directory_in_file_manager(path: &Path) -> Result<()> {
     #[cfg(target_os = "macos")]
     {
         let status = std::process::Command::new("open")
@@ -75,7 +297,182 @@ fn open_directory_in_file_manager(path: &Path) -> Result<()> {
     Ok(())
 }
 
-pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<Value> {
+/// Chain the loaded profile is configured for; saved endpoints belong to it.
+fn profile_chain_id(state: &AppState) -> Result<u64> {
+    state
+        .resolved
+        .as_ref()
+        .map(|r| r.chain_id)
+        .ok_or_else(|| anyhow!("No config loaded"))
+}
+
+/// Time range of a spend report request; the whole history when omitted.
+fn report_range(req: &IpcRequest) -> Result<String> {
+    match req.params.get(0) {
+        None | Some(Value::Null) => Ok("all".to_string()),
+        Some(range) => range
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("report range must be a string")),
+    }
+}
+
+/// Settings requests that wait on the network, on passphrase key derivation,
+/// on a file dialog or on walking the bundle cache; the router runs them on a
+/// worker thread and answers through `RpcResult`.
+pub(super) fn is_blocking_method(method: &str) -> bool {
+    matches!(
+        method,
+        "vibefi_testEndpoint"
+            | "vibefi_testIpfs"
+            | "vibefi_listNfts"
+            | "vibefi_nftImage"
+            | "vibefi_setLockPassphrase"
+            | "vibefi_exportSpendReport"
+            | "vibefi_exportSignedTransactions"
+            | "vibefi_importSignedTransactions"
+            | "vibefi_addLocalAccount"
+            | "vibefi_getCacheUsage"
+            | "vibefi_clearCache"
+    )
+}
+
+pub(super) fn handle_blocking_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<Value> {
+    match req.method.as_str() {
+        "vibefi_testEndpoint" => test_endpoint(state, req),
+        "vibefi_getCacheUsage" => Ok(serde_json::to_value(crate::cache_manager::usage(state)?)?),
+        "vibefi_clearCache" => {
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("Network not configured"))?;
+            let root_cid = req.params.get(0).and_then(Value::as_str);
+            let open = state.bundles_in_use().into_iter().collect();
+            let result = crate::cache_manager::clear(&resolved.cache_dir, root_cid, &open)?;
+            tracing::info!(
+                target: "vibefi::audit",
+                root_cid = root_cid.unwrap_or("all"),
+                removed = result.removed,
+                freed_bytes = result.freed_bytes,
+                skipped = result.skipped,
+                "bundle cache cleared"
+            );
+            crate::prefetch::request_prefetch();
+            Ok(serde_json::to_value(result)?)
+        }
+        "vibefi_testIpfs" => {
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("No config loaded"))?;
+            let checks = crate::ipfs_check::run(&resolved.http_client, resolved);
+            Ok(serde_json::to_value(checks)?)
+        }
+        "vibefi_listNfts" => super::nft::list_nfts(state, req),
+        "vibefi_nftImage" => super::nft::nft_image(state, req),
+        "vibefi_setLockPassphrase" => {
+            let params: SetLockPassphraseRequest = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing lock passphrase parameter"))?,
+            )?;
+            crate::lock::set_passphrase(
+                state,
+                params.current_passphrase.as_deref(),
+                params.passphrase.as_deref(),
+            )?;
+            Ok(crate::lock::status(state))
+        }
+        "vibefi_exportSpendReport" => {
+            let range = report_range(req)?;
+            let config_path = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_deref())
+                .ok_or_else(|| anyhow!("No config loaded"))?;
+            let Some(dest) = crate::file_dialog::pick_save(
+                "Export spend report",
+                &format!("vibefi-spend-{range}.csv"),
+                &["csv".to_string()],
+            )?
+            else {
+                return Ok(Value::Null);
+            };
+            let records = crate::tx_history::load(config_path);
+            let csv = crate::tx_history::to_csv(&records, crate::tx_history::range_start(&range)?);
+            std::fs::write(&dest, csv).with_context(|| format!("write {}", dest.display()))?;
+            tracing::info!(path = %dest.display(), range, "exported spend report");
+            Ok(Value::String(dest.to_string_lossy().into_owned()))
+        }
+        "vibefi_exportSignedTransactions" => {
+            let format = crate::signed_export::ExportFormat::parse(
+                req.params.get(0).and_then(Value::as_str).unwrap_or("json"),
+            )?;
+            let txs = crate::signed_export::held(state)?;
+            if txs.is_empty() {
+                return Err(anyhow!("No signed transactions to export"));
+            }
+            let extension = format.extension();
+            let Some(dest) = crate::file_dialog::pick_save(
+                "Export signed transactions",
+                &format!("vibefi-signed-transactions.{extension}"),
+                &[extension.to_string()],
+            )?
+            else {
+                return Ok(Value::Null);
+            };
+            let contents = crate::signed_export::export(&txs, format)?;
+            std::fs::write(&dest, contents).with_context(|| format!("write {}", dest.display()))?;
+            let hashes: Vec<String> = txs.into_iter().map(|tx| tx.hash).collect();
+            crate::signed_export::discard(state, &hashes)?;
+            tracing::info!(
+                target: "vibefi::audit",
+                path = %dest.display(),
+                count = hashes.len(),
+                "exported signed transactions"
+            );
+            Ok(Value::String(dest.to_string_lossy().into_owned()))
+        }
+        "vibefi_importSignedTransactions" => {
+            let Some(path) = crate::file_dialog::pick_open(
+                "Import signed transactions",
+                &["json".to_string(), "txt".to_string()],
+            )?
+            else {
+                return Ok(Value::Null);
+            };
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("read {}", path.display()))?;
+            let txs = crate::signed_export::parse_import(&text)?;
+            crate::signed_export::broadcast(state, txs)
+        }
+        "vibefi_addLocalAccount" => super::accounts::add_local_account(state, req),
+        _ => Err(anyhow!("Unsupported method: {}", req.method)),
+    }
+}
+
+fn test_endpoint(state: &AppState, req: &IpcRequest) -> Result<Value> {
+    let url = req
+        .params
+        .get(0)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .ok_or_else(|| anyhow!("missing endpoint URL"))?;
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("No config loaded"))?;
+    let result = crate::rpc_manager::test_endpoint(&resolved.http_client, url, resolved.chain_id)?;
+    Ok(serde_json::to_value(result)?)
+}
+
+pub(super) fn handle_settings_ipc(
+    state: &AppState,
+    manager: &WebViewManager,
+    req: &IpcRequest,
+) -> Result<Value> {
     match req.method.as_str() {
         "vibefi_getEndpoints" => {
             let mgr = state
@@ -102,7 +499,24 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
             if endpoints.iter().any(|ep| ep.url.trim().is_empty()) {
                 return Err(anyhow!("RPC endpoint URL cannot be empty"));
             }
-            tracing::info!(count = endpoints.len(), "settings set rpc endpoints");
+            if state.resolved.as_ref().is_some_and(|r| r.fork.is_some()) {
+                return Err(anyhow!(
+                    "RPC endpoints can't be changed in a fork session; every request goes to the fork"
+                ));
+            }
+            let chain_id = profile_chain_id(state)?;
+            let endpoints: Vec<RpcEndpoint> = endpoints
+                .into_iter()
+                .map(|ep| RpcEndpoint {
+                    chain_id: Some(chain_id),
+                    ..ep
+                })
+                .collect();
+            tracing::info!(
+                count = endpoints.len(),
+                chain_id,
+                "settings set rpc endpoints"
+            );
 
             // Update the live manager
             {
@@ -120,7 +534,7 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
                 state.resolved.as_ref().and_then(|r| r.config_path.clone())
             {
                 let mut settings = crate::settings::load_settings(config_path);
-                settings.rpc_endpoints = endpoints;
+                settings.set_rpc_endpoints_for(chain_id, endpoints);
                 crate::settings::save_settings(config_path, &settings)?;
             }
 
@@ -149,6 +563,11 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
                 .ipfs
                 .gateway_endpoint
                 .unwrap_or_else(|| default_gateway_endpoint.clone());
+            let download_concurrency = user_settings
+                .ipfs
+                .download_concurrency
+                .unwrap_or(DEFAULT_DOWNLOAD_CONCURRENCY)
+                .clamp(1, MAX_DOWNLOAD_CONCURRENCY);
             tracing::debug!(
                 backend = fetch_backend.as_str(),
                 "settings get ipfs settings"
@@ -158,6 +577,8 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
                 fetch_backend,
                 gateway_endpoint,
                 default_gateway_endpoint,
+                download_concurrency,
+                default_download_concurrency: DEFAULT_DOWNLOAD_CONCURRENCY,
             })?)
         }
         "vibefi_setIpfsSettings" => {
@@ -167,6 +588,7 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
                     .cloned()
                     .ok_or_else(|| anyhow!("missing ipfs settings parameter"))?,
             )?;
+            validate_download_concurrency(params.download_concurrency)?;
             tracing::info!(
                 backend = params.fetch_backend.as_str(),
                 "settings set ipfs settings"
@@ -183,6 +605,9 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
                     .map(str::trim)
                     .filter(|s| !s.is_empty())
                     .map(ToOwned::to_owned);
+                if params.download_concurrency.is_some() {
+                    settings.ipfs.download_concurrency = params.download_concurrency;
+                }
                 crate::settings::save_settings(config_path, &settings)?;
             }
 
@@ -198,6 +623,7 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
             if params.max_concurrent_rpc < 1 {
                 return Err(anyhow!("maxConcurrentRpc must be a whole number >= 1"));
             }
+            validate_download_concurrency(params.download_concurrency)?;
             tracing::info!(
                 max_concurrent_rpc = params.max_concurrent_rpc,
                 backend = params.fetch_backend.as_str(),
@@ -226,6 +652,9 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
                     .map(str::trim)
                     .filter(|s| !s.is_empty())
                     .map(ToOwned::to_owned);
+                if params.download_concurrency.is_some() {
+                    settings.ipfs.download_concurrency = params.download_concurrency;
+                }
                 crate::settings::save_settings(config_path, &settings)?;
             }
 
@@ -267,6 +696,540 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
             }
             Ok(Value::Bool(true))
         }
+        "vibefi_getPrefetchSettings" => {
+            let settings = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_ref())
+                .map(|p| crate::settings::load_settings(p))
+                .unwrap_or_default();
+            Ok(serde_json::to_value(PrefetchSettings {
+                metered_connection: settings.metered_connection,
+                bundle_cache_max_mb: settings.bundle_cache_max_mb,
+                icon_prefetch_kb: settings.icon_prefetch_kb,
+                background_refresh: settings.background_refresh,
+                pinned_count: settings.pinned_dapps.len(),
+            })?)
+        }
+        "vibefi_setPrefetchSettings" => {
+            let params: PrefetchSettings = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing prefetch settings parameter"))?,
+            )?;
+            if params.bundle_cache_max_mb == Some(0) {
+                return Err(anyhow!("bundle cache limit must be at least 1 MB"));
+            }
+            tracing::info!(
+                metered = params.metered_connection,
+                cache_max_mb = ?params.bundle_cache_max_mb,
+                icon_prefetch_kb = ?params.icon_prefetch_kb,
+                background_refresh = ?params.background_refresh,
+                "settings set prefetch settings"
+            );
+            if let Some(ref config_path) =
+                state.resolved.as_ref().and_then(|r| r.config_path.clone())
+            {
+                let mut settings = crate::settings::load_settings(config_path);
+                settings.metered_connection = params.metered_connection;
+                settings.bundle_cache_max_mb = params.bundle_cache_max_mb;
+                settings.icon_prefetch_kb = params.icon_prefetch_kb;
+                settings.background_refresh = params.background_refresh;
+                crate::settings::save_settings(config_path, &settings)?;
+            }
+            // A lowered limit takes effect now rather than at the next launch.
+            let state = state.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = crate::cache_manager::enforce_budget(&state) {
+                    tracing::warn!(error = %format!("{err:#}"), "bundle cache eviction failed");
+                }
+            });
+            crate::prefetch::request_prefetch();
+            crate::scheduler::reschedule();
+            Ok(Value::Bool(true))
+        }
+        "vibefi_listProfiles" => {
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("No config loaded"))?;
+            Ok(serde_json::to_value(crate::profiles::list(resolved))?)
+        }
+        "vibefi_createProfile" => {
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("No config loaded"))?;
+            let name = req
+                .params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("missing profile name"))?;
+            Ok(Value::String(crate::profiles::create(resolved, name)?))
+        }
+        "vibefi_switchProfile" => {
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("No config loaded"))?;
+            let name = crate::profiles::normalize_name(
+                req.params
+                    .get(0)
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| anyhow!("missing profile name"))?,
+            )?;
+            let profile = crate::profiles::list(resolved)
+                .into_iter()
+                .find(|profile| profile.name == name)
+                .ok_or_else(|| anyhow!("no profile named {name}"))?;
+            if profile.active {
+                return Ok(Value::Bool(false));
+            }
+            tracing::info!(target: "vibefi::audit", profile = %name, "switching profile");
+            state
+                .proxy
+                .send_event(UserEvent::RestartWithProfile { name })
+                .map_err(|_| anyhow!("event loop closed"))?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_getSignedTransactions" => {
+            Ok(serde_json::to_value(crate::signed_export::held(state)?)?)
+        }
+        "vibefi_discardSignedTransactions" => {
+            let hashes: Vec<String> = match req.params.get(0) {
+                Some(hashes) => serde_json::from_value(hashes.clone())?,
+                None => crate::signed_export::held(state)?
+                    .into_iter()
+                    .map(|tx| tx.hash)
+                    .collect(),
+            };
+            tracing::info!(
+                target: "vibefi::audit",
+                count = hashes.len(),
+                "discarded signed transactions"
+            );
+            crate::signed_export::discard(state, &hashes)?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_getTransactionSettings" => {
+            let settings = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_ref())
+                .map(|p| crate::settings::load_settings(p))
+                .unwrap_or_default();
+            Ok(serde_json::to_value(TransactionSettings {
+                access_lists: settings.access_lists,
+                offline_signing: settings.offline_signing,
+                fiat_prices: settings.fiat_prices,
+            })?)
+        }
+        "vibefi_setTransactionSettings" => {
+            let mut params: TransactionSettings = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing transaction settings parameter"))?,
+            )?;
+            params.fiat_prices = normalize_fiat_prices(params.fiat_prices)?;
+            tracing::info!(
+                access_lists = params.access_lists,
+                offline_signing = params.offline_signing,
+                fiat_prices = params.fiat_prices.enabled,
+                "settings set transaction settings"
+            );
+            if let Some(ref config_path) =
+                state.resolved.as_ref().and_then(|r| r.config_path.clone())
+            {
+                let mut settings = crate::settings::load_settings(config_path);
+                settings.access_lists = params.access_lists;
+                settings.offline_signing = params.offline_signing;
+                settings.fiat_prices = params.fiat_prices;
+                crate::settings::save_settings(config_path, &settings)?;
+            }
+            Ok(Value::Bool(true))
+        }
+        "vibefi_getNftSettings" => {
+            let settings = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_ref())
+                .map(|p| crate::settings::load_settings(p))
+                .unwrap_or_default();
+            Ok(serde_json::to_value(NftSettingsResponse {
+                indexer: settings.nft_indexer,
+                accounts: super::nft::connected_accounts(state),
+            })?)
+        }
+        "vibefi_setNftSettings" => {
+            let params: SetNftSettingsRequest = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing NFT settings parameter"))?,
+            )?;
+            let indexer = normalize_nft_indexer(params.indexer)?;
+            tracing::info!(indexer = indexer.is_some(), "settings set nft settings");
+            if let Some(ref config_path) =
+                state.resolved.as_ref().and_then(|r| r.config_path.clone())
+            {
+                let mut settings = crate::settings::load_settings(config_path);
+                settings.nft_indexer = indexer;
+                crate::settings::save_settings(config_path, &settings)?;
+            }
+            Ok(Value::Bool(true))
+        }
+        "vibefi_getApprovalRules" => {
+            let settings = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_ref())
+                .map(|p| crate::settings::load_settings(p))
+                .unwrap_or_default();
+            let mut dapps: Vec<RuleDapp> = Vec::new();
+            for identity in manager.apps.iter().filter_map(|e| e.identity.as_ref()) {
+                let Some(subject) = crate::permissions::subject_for(identity) else {
+                    continue;
+                };
+                if !dapps.iter().any(|d| d.subject == subject) {
+                    dapps.push(RuleDapp {
+                        subject,
+                        name: identity.name.clone(),
+                    });
+                }
+            }
+            Ok(serde_json::to_value(ApprovalRulesResponse {
+                rules: settings.approval_rules,
+                chains: crate::policy::dev_chains(state),
+                dapps,
+            })?)
+        }
+        "vibefi_setApprovalRules" => {
+            let rules: Vec<ApprovalRule> = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing approval rules parameter"))?,
+            )?;
+            let rules = crate::policy::normalize_rules(state, rules)?;
+            let config_path = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_deref())
+                .ok_or_else(|| anyhow!("approval rules require a config file"))?;
+            tracing::info!(count = rules.len(), "settings set approval rules");
+            let mut settings = crate::settings::load_settings(config_path);
+            settings.approval_rules = rules;
+            crate::settings::save_settings(config_path, &settings)?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_getLocalAccounts" => super::accounts::local_accounts(state),
+        "vibefi_selectAccount" => super::accounts::select_account(state, req),
+        "vibefi_getLockSettings" => Ok(crate::lock::status(state)),
+        "vibefi_getLaunchHistory" => {
+            // Optional `[rootCid]` narrows the history to one bundle.
+            let root_cid = req.params.get(0).and_then(Value::as_str);
+            let reports: Vec<_> = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_deref())
+                .map(crate::launch_history::load)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|report| root_cid.is_none_or(|cid| report.root_cid == cid))
+                .collect();
+            Ok(serde_json::to_value(reports)?)
+        }
+        "vibefi_getSpendReport" => {
+            let range = report_range(req)?;
+            let records = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_deref())
+                .map(crate::tx_history::load)
+                .unwrap_or_default();
+            let since = crate::tx_history::range_start(&range)?;
+            Ok(serde_json::to_value(crate::tx_history::spend_report(
+                &records, since,
+            ))?)
+        }
+        "vibefi_setAutoLock" => {
+            let minutes = req
+                .params
+                .get(0)
+                .filter(|value| !value.is_null())
+                .map(|value| {
+                    value
+                        .as_u64()
+                        .filter(|minutes| (1..=24 * 60).contains(minutes))
+                        .ok_or_else(|| anyhow!("auto-lock must be 1 to 1440 minutes"))
+                })
+                .transpose()?;
+            tracing::info!(?minutes, "settings set auto-lock");
+            let config_path = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_deref())
+                .ok_or_else(|| anyhow!("No config loaded"))?;
+            let mut settings = crate::settings::load_settings(config_path);
+            settings.lock.auto_lock_minutes = minutes;
+            crate::settings::save_settings(config_path, &settings)?;
+            Ok(crate::lock::status(state))
+        }
+        "vibefi_lockNow" => {
+            if !crate::lock::passphrase_set(state) {
+                bail!("Set a passphrase before locking VibeFi");
+            }
+            crate::lock::lock(state);
+            Ok(crate::lock::status(state))
+        }
+        "vibefi_getCacheIntegrity" => {
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("Network not configured"))?;
+            Ok(serde_json::to_value(crate::cache_integrity::load_report(
+                &resolved.cache_dir,
+            ))?)
+        }
+        "vibefi_getRegistries" => {
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("Network not configured"))?;
+            let settings = resolved
+                .config_path
+                .as_deref()
+                .map(crate::settings::load_settings)
+                .unwrap_or_default();
+            let community = settings
+                .community_registries
+                .into_iter()
+                .map(|registry| CommunityRegistryEntry {
+                    active: registry.chain_id == resolved.chain_id,
+                    registry,
+                })
+                .collect();
+            Ok(serde_json::to_value(RegistriesResponse {
+                default_registry: resolved.dapp_registry.clone(),
+                default_label: crate::registry::DEFAULT_REGISTRY_LABEL,
+                chain_id: resolved.chain_id,
+                community,
+            })?)
+        }
+        "vibefi_saveRegistry" => {
+            let registry: CommunityRegistry = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing registry parameter"))?,
+            )?;
+            let registry = normalize_registry(registry)?;
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("Network not configured"))?;
+            if registry.chain_id == resolved.chain_id
+                && registry
+                    .address
+                    .eq_ignore_ascii_case(&resolved.dapp_registry)
+            {
+                bail!("this is already the configured dapp registry");
+            }
+            let config_path = resolved
+                .config_path
+                .as_deref()
+                .ok_or_else(|| anyhow!("adding registries requires a config file"))?;
+            tracing::info!(
+                address = %registry.address,
+                chain_id = registry.chain_id,
+                label = %registry.label,
+                trusted_publishers = registry.trusted_publishers.len(),
+                "settings save community registry"
+            );
+            let mut settings = crate::settings::load_settings(config_path);
+            match settings.community_registries.iter_mut().find(|existing| {
+                existing.address == registry.address && existing.chain_id == registry.chain_id
+            }) {
+                Some(existing) => *existing = registry,
+                None => settings.community_registries.push(registry),
+            }
+            crate::settings::save_settings(config_path, &settings)?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_removeRegistry" => {
+            let address = req
+                .params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("missing registry address"))?;
+            let address = parse_address(address, "registry")?;
+            let chain_id = req
+                .params
+                .get(1)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| anyhow!("missing registry chainId"))?;
+            let config_path = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_deref())
+                .ok_or_else(|| anyhow!("removing registries requires a config file"))?;
+            tracing::info!(%address, chain_id, "settings remove community registry");
+            let mut settings = crate::settings::load_settings(config_path);
+            settings
+                .community_registries
+                .retain(|registry| registry.address != address || registry.chain_id != chain_id);
+            // Pins of the removed registry could never resolve again.
+            settings
+                .pinned_dapps
+                .retain(|pin| pin.registry.as_deref() != Some(address.as_str()));
+            crate::settings::save_settings(config_path, &settings)?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_getRememberedPrompts" => {
+            let settings = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_ref())
+                .map(|p| crate::settings::load_settings(p))
+                .unwrap_or_default();
+            Ok(serde_json::to_value(settings.remembered_prompts)?)
+        }
+        "vibefi_forgetPrompt" => {
+            // Without a key every remembered choice is forgotten.
+            let key = req.params.get(0).and_then(Value::as_str);
+            let config_path = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_deref())
+                .ok_or_else(|| anyhow!("remembered choices require a config file"))?;
+            tracing::info!(key = ?key, "settings forget remembered prompt");
+            let mut settings = crate::settings::load_settings(config_path);
+            match key {
+                Some(key) => {
+                    settings.remembered_prompts.remove(key);
+                }
+                None => settings.remembered_prompts.clear(),
+            }
+            crate::settings::save_settings(config_path, &settings)?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_getDappPermissions" => Ok(serde_json::to_value(crate::permissions::list(
+            state, manager,
+        ))?),
+        "vibefi_revokeDappPermission" => {
+            let (subject, kind, value) = crate::permissions::parse_revoke_params(&req.params)?;
+            crate::permissions::revoke(state, manager, &subject, kind, &value)?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_resetDappPermissions" => {
+            let subject = req
+                .params
+                .get(0)
+                .and_then(Value::as_str)
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| anyhow!("missing dapp"))?;
+            crate::permissions::reset(state, manager, subject)?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_getTabUsage" => {
+            let settings = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_ref())
+                .map(|p| crate::settings::load_settings(p))
+                .unwrap_or_default();
+            let tabs = manager
+                .apps
+                .iter()
+                .map(|entry| TabUsageRow {
+                    id: entry.id.clone(),
+                    label: entry.label.clone(),
+                    suspended: entry.is_suspended(),
+                    usage: entry.usage,
+                })
+                .collect();
+            Ok(serde_json::to_value(TabUsageResponse {
+                tabs,
+                process: crate::tab_usage::process_usage(),
+                heavy_tab: settings.heavy_tab,
+            })?)
+        }
+        "vibefi_setHeavyTabSettings" => {
+            let params: HeavyTabSettings = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing heavy tab settings parameter"))?,
+            )?;
+            if params.heap_mb == 0 {
+                return Err(anyhow!("heavy tab heap limit must be at least 1 MB"));
+            }
+            if !(1..=100).contains(&params.cpu_percent) {
+                return Err(anyhow!("heavy tab CPU limit must be between 1 and 100%"));
+            }
+            let config_path = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_deref())
+                .ok_or_else(|| anyhow!("heavy tab settings require a config file"))?;
+            tracing::info!(
+                enabled = params.enabled,
+                heap_mb = params.heap_mb,
+                cpu_percent = params.cpu_percent,
+                "settings set heavy tab warning"
+            );
+            let mut settings = crate::settings::load_settings(config_path);
+            settings.heavy_tab = params;
+            crate::settings::save_settings(config_path, &settings)?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_getSiteData" => {
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("Network not configured"))?;
+            Ok(serde_json::to_value(SiteDataResponse {
+                quota_mb: crate::storage::quota_bytes(state) / (1024 * 1024),
+                default_quota_mb: DEFAULT_STORAGE_QUOTA_MB,
+                partitions: crate::storage::list_partitions(resolved),
+            })?)
+        }
+        "vibefi_setStorageQuota" => {
+            let quota_mb = req
+                .params
+                .get(0)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| anyhow!("missing storage quota"))?;
+            if quota_mb == 0 {
+                return Err(anyhow!("storage quota must be at least 1 MB"));
+            }
+            let config_path = state
+                .resolved
+                .as_ref()
+                .and_then(|r| r.config_path.as_deref())
+                .ok_or_else(|| anyhow!("storage quota requires a config file"))?;
+            tracing::info!(quota_mb, "settings set storage quota");
+            let mut settings = crate::settings::load_settings(config_path);
+            settings.storage_quota_mb = Some(quota_mb);
+            crate::settings::save_settings(config_path, &settings)?;
+            Ok(Value::Bool(true))
+        }
+        "vibefi_clearSiteData" => {
+            let key = req
+                .params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("missing storage partition"))?;
+            let resolved = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("Network not configured"))?;
+            tracing::info!(key, "settings clear site data");
+            crate::storage::clear_partition(resolved, key, &manager.open_storage_partitions())?;
+            Ok(Value::Bool(true))
+        }
         "vibefi_openLogDirectory" => {
             let log_dir = crate::runtime_paths::resolve_log_dir();
             fs::create_dir_all(&log_dir)
@@ -277,3 +1240,104 @@ pub(super) fn handle_settings_ipc(state: &AppState, req: &IpcRequest) -> Result<
         _ => Err(anyhow!("Unsupported settings method: {}", req.method)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_fiat_prices, normalize_registry};
+    use crate::settings::{CommunityRegistry, FiatPriceSettings};
+
+    fn registry(address: &str, label: &str) -> CommunityRegistry {
+        CommunityRegistry {
+            address: address.to_string(),
+            chain_id: 1,
+            label: label.to_string(),
+            ..CommunityRegistry::default()
+        }
+    }
+
+    #[test]
+    fn normalizes_registry_addresses_and_publishers() {
+        let mut input = registry(" 0x00000000000000000000000000000000000000AB ", " Picks ");
+        input.trusted_publishers = vec![
+            "0x00000000000000000000000000000000000000CD".to_string(),
+            "0x00000000000000000000000000000000000000cd".to_string(),
+            " ".to_string(),
+        ];
+        let normalized = normalize_registry(input).unwrap();
+        assert_eq!(
+            normalized.address,
+            "0x00000000000000000000000000000000000000ab"
+        );
+        assert_eq!(normalized.label, "Picks");
+        assert_eq!(
+            normalized.trusted_publishers,
+            vec!["0x00000000000000000000000000000000000000cd".to_string()]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_registries() {
+        assert!(normalize_registry(registry("0x1234", "Picks")).is_err());
+        assert!(
+            normalize_registry(registry("0x00000000000000000000000000000000000000ab", "  "))
+                .is_err()
+        );
+        let mut wrong_chain = registry("0x00000000000000000000000000000000000000ab", "Picks");
+        wrong_chain.chain_id = 0;
+        assert!(normalize_registry(wrong_chain).is_err());
+    }
+
+    #[test]
+    fn normalizes_fiat_price_settings() {
+        let normalized = normalize_fiat_prices(FiatPriceSettings {
+            enabled: true,
+            endpoint: Some(" https://prices.example/api/v3/ ".to_string()),
+            currency: Some(" EUR".to_string()),
+        })
+        .unwrap();
+        assert_eq!(
+            normalized.endpoint.as_deref(),
+            Some("https://prices.example/api/v3")
+        );
+        assert_eq!(normalized.currency.as_deref(), Some("eur"));
+    }
+
+    #[test]
+    fn blank_fiat_price_fields_use_the_defaults() {
+        let blank = normalize_fiat_prices(FiatPriceSettings {
+            enabled: true,
+            endpoint: Some(" ".to_string()),
+            currency: Some(String::new()),
+        })
+        .unwrap();
+        assert_eq!(
+            blank,
+            FiatPriceSettings {
+                enabled: true,
+                ..FiatPriceSettings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn refuses_currencies_that_are_not_iso_codes() {
+        assert!(
+            normalize_fiat_prices(FiatPriceSettings {
+                currency: Some("euro".to_string()),
+                ..FiatPriceSettings::default()
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn refuses_price_endpoints_that_are_not_http() {
+        assert!(
+            normalize_fiat_prices(FiatPriceSettings {
+                endpoint: Some("ftp://prices.example".to_string()),
+                ..FiatPriceSettings::default()
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,531 @@
+use alloy_dyn_abi::TypedData;
+use alloy_primitives::{B256, TxKind, utils::format_units};
+use alloy_rpc_types_eth::{AccessList, TransactionRequest};
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Value, json};
+use wry::WebView;
+
+use crate::ipc_contract::IpcRequest;
+use crate::prompt::PromptRequest;
+use crate::state::{AppState, SignerCapabilities, TabBadge, UserEvent, WalletBackend};
+
+use super::hardware::HardwareWallet;
+use super::local::LocalWallet;
+use super::remote_signer::RemoteWallet;
+use super::rpc::{
+    FilledTransaction, authorized_sender, decode_0x_hex, fill_tx_request, parse_hex_u64,
+    send_raw_transaction,
+};
+use super::smart_account::SmartAccountWallet;
+use super::{emit_chain_changed, try_spawn_rpc_passthrough};
+
+/// Who is asking for a signature and with which account.
+pub(super) struct SignContext<'a> {
+    pub state: &'a AppState,
+    /// Tab that sent the request; `None` for transactions from the host UI.
+    pub webview_id: Option<&'a str>,
+    /// Account expected to sign, as the dapp or host named it.
+    pub account: String,
+}
+
+/// A backend that holds keys (or reaches a service that does) and signs with
+/// them. Methods block and are only called from worker threads.
+pub(super) trait WalletSigner: Sync {
+    fn backend(&self) -> WalletBackend;
+
+    /// Provider name reported by `wallet_getProviderInfo`.
+    fn provider_name(&self) -> &'static str;
+
+    fn capabilities(&self) -> SignerCapabilities;
+
+    /// Accounts the backend currently signs for, primary first.
+    fn accounts(&self, state: &AppState) -> Result<Vec<String>>;
+
+    /// EIP-191 signature over `message`, as hex.
+    fn sign_message(&self, ctx: &SignContext, message: &[u8]) -> Result<String>;
+
+    /// EIP-712 signature over the dapp's typed data JSON, as hex; see
+    /// [`typed_data_hash`].
+    fn sign_typed_data(&self, ctx: &SignContext, typed_data_json: &str) -> Result<String>;
+
+    /// Signs a filled transaction and returns the raw signed transaction.
+    fn sign_transaction(&self, ctx: &SignContext, tx: FilledTransaction) -> Result<String>;
+
+    /// Fills, signs and broadcasts `tx`, whose `from` is already the sender,
+    /// and returns the transaction hash.
+    fn send_transaction(&self, ctx: &SignContext, tx: TransactionRequest) -> Result<String> {
+        let sender = tx
+            .from
+            .ok_or_else(|| anyhow!("transaction has no sender"))?;
+        let tx = fill_tx_request(ctx.state, tx, sender)?;
+        // Backends that confirm every request do so in `sign_transaction`.
+        if !self.capabilities().client_confirmation {
+            confirm_transaction(
+                ctx,
+                &tx.tx,
+                transaction_details(ctx.state, ctx.webview_id, &tx.tx, tx.touched.as_ref()),
+            )?;
+        }
+        let raw_tx = self.sign_transaction(ctx, tx)?;
+        if crate::signed_export::offline_signing(ctx.state) {
+            let held = crate::signed_export::hold(ctx.state, &raw_tx)?;
+            bail!(
+                "transaction {} was signed and held for export; it was not broadcast",
+                held.hash
+            );
+        }
+        send_raw_transaction(ctx.state, raw_tx)
+    }
+}
+
+/// The signer for `backend`. WalletConnect has none: the paired wallet
+/// answers every request itself.
+pub(super) fn wallet_signer(backend: WalletBackend) -> Option<&'static dyn WalletSigner> {
+    match backend {
+        WalletBackend::Local => Some(&LocalWallet),
+        WalletBackend::Hardware => Some(&HardwareWallet),
+        WalletBackend::SmartAccount => Some(&SmartAccountWallet),
+        WalletBackend::Remote => Some(&RemoteWallet),
+        WalletBackend::WalletConnect => None,
+    }
+}
+
+/// Handles a dapp request routed to a key-holding backend. Signing runs on a
+/// worker thread and answers through `UserEvent::SignerResult`; reads go to
+/// the RPC passthrough.
+pub(super) fn handle_signer_ipc(
+    webview: &WebView,
+    state: &AppState,
+    webview_id: &str,
+    req: &IpcRequest,
+    backend: WalletBackend,
+) -> Result<Option<Value>> {
+    let signer = wallet_signer(backend)
+        .ok_or_else(|| anyhow!("{} wallets do not sign in the client", backend.as_str()))?;
+    if let Some(value) = super::network_identity_response(state, req.method.as_str()) {
+        return Ok(Some(value));
+    }
+
+    // The router only sends requests naming an account this backend holds.
+    let account = super::request_signer(req)
+        .or_else(|| state.account_for(webview_id))
+        .map(|account| account.to_ascii_lowercase());
+
+    match req.method.as_str() {
+        "wallet_getProviderInfo" => {
+            let info = super::provider_info(
+                state,
+                webview_id,
+                signer.provider_name(),
+                Some(backend),
+                account,
+                signer.capabilities().into(),
+            );
+            Ok(Some(serde_json::to_value(info)?))
+        }
+        "wallet_switchEthereumChain" if signer.capabilities().switch_chain => {
+            switch_chain(webview, state, webview_id, req)
+        }
+        "personal_sign" => {
+            let account = account.ok_or_else(|| anyhow!("no account to sign with"))?;
+            let msg = req
+                .params
+                .get(0)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("invalid params for personal_sign"))?;
+            let bytes = decode_0x_hex(msg).unwrap_or_else(|| msg.as_bytes().to_vec());
+            spawn_signer_task(state, webview_id, req, signer, account, move |ctx| {
+                signer.sign_message(ctx, &bytes)
+            });
+            Ok(None)
+        }
+        "eth_signTypedData_v4" => {
+            let account = account.ok_or_else(|| anyhow!("no account to sign with"))?;
+            // Most dapps send the typed data as a JSON string, some as an object.
+            let typed_data_json = match req.params.get(1) {
+                Some(Value::String(json)) => json.clone(),
+                Some(object @ Value::Object(_)) => object.to_string(),
+                _ => return Err(anyhow!("invalid params for eth_signTypedData_v4")),
+            };
+            // Malformed typed data fails here rather than after a prompt.
+            typed_data_hash(&typed_data_json)?;
+            spawn_signer_task(state, webview_id, req, signer, account, move |ctx| {
+                signer.sign_typed_data(ctx, &typed_data_json)
+            });
+            Ok(None)
+        }
+        "eth_sendTransaction" => {
+            let account =
+                account.ok_or_else(|| anyhow!("Unauthorized: call eth_requestAccounts first"))?;
+            let mut tx: TransactionRequest = req
+                .params
+                .get(0)
+                .cloned()
+                .ok_or_else(|| anyhow!("invalid params for eth_sendTransaction"))
+                .and_then(|tx| {
+                    serde_json::from_value(tx).context("invalid eth_sendTransaction object")
+                })?;
+            tx.from = Some(authorized_sender(state, webview_id, &tx)?);
+            tracing::info!(
+                webview_id,
+                ipc_id = req.id,
+                backend = backend.as_str(),
+                "spawning eth_sendTransaction worker"
+            );
+            spawn_signer_task(state, webview_id, req, signer, account, move |ctx| {
+                signer.send_transaction(ctx, tx)
+            });
+            Ok(None)
+        }
+        _ => {
+            if try_spawn_rpc_passthrough(state, webview_id, req) {
+                Ok(None)
+            } else {
+                Err(anyhow!("Unsupported method: {}", req.method))
+            }
+        }
+    }
+}
+
+fn switch_chain(
+    webview: &WebView,
+    state: &AppState,
+    webview_id: &str,
+    req: &IpcRequest,
+) -> Result<Option<Value>> {
+    let chain_id_hex = req
+        .params
+        .get(0)
+        .and_then(|v| v.get("chainId"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("invalid params for wallet_switchEthereumChain"))?;
+    let chain_id = parse_hex_u64(chain_id_hex).ok_or_else(|| anyhow!("invalid chainId"))?;
+    if !super::chains::is_known_chain(state, chain_id) {
+        return Err(super::chains::unrecognized_chain(chain_id));
+    }
+
+    {
+        let mut ws = state
+            .wallet
+            .lock()
+            .expect("poisoned wallet lock while switching chain");
+        ws.chain.chain_id = chain_id;
+    }
+    emit_chain_changed(webview, format!("0x{:x}", chain_id));
+    tracing::info!(
+        webview_id,
+        chain_id = format!("0x{:x}", chain_id),
+        "wallet switched chain"
+    );
+    Ok(Some(Value::Null))
+}
+
+/// What a transaction prompt shows: recipient, value, gas and fees, the
+/// calldata with ERC-20 calls or the requesting tab's ABIs spelled out,
+/// touched contracts and fiat values.
+pub(super) fn transaction_details(
+    state: &AppState,
+    webview_id: Option<&str>,
+    tx: &TransactionRequest,
+    touched: Option<&AccessList>,
+) -> Value {
+    let (to, to_ens) = match tx.to {
+        Some(TxKind::Call(to)) => (format!("{to:#x}"), crate::ens::label(state, to)),
+        _ => ("New contract".to_string(), None),
+    };
+    let symbol = super::chains::active_custom_network(state)
+        .map(|network| network.currency_symbol)
+        .unwrap_or_else(|| "ETH".to_string());
+    let gwei = |wei: u128| format!("{} gwei", format_units(wei, "gwei").unwrap_or_default());
+    let input = tx
+        .input
+        .input()
+        .map(|input| input.as_ref())
+        .unwrap_or_default();
+    let touches = touched.map(|list| {
+        list.iter()
+            .map(|item| {
+                json!({
+                    "address": format!("{:#x}", item.address),
+                    "storageSlots": item.storage_keys.len(),
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+    let call = crate::prices::describe_token_call(input);
+    let decoded = call
+        .is_none()
+        .then(|| crate::decoder::decode_call(state, webview_id, input))
+        .flatten();
+    let mut details = json!({
+        "to": to,
+        "value": format!(
+            "{} {symbol}",
+            format_units(tx.value.unwrap_or_default(), 18).unwrap_or_default()
+        ),
+        "gasLimit": tx.gas,
+        "maxFeePerGas": tx.max_fee_per_gas.or(tx.gas_price).map(gwei),
+        "maxPriorityFeePerGas": tx.max_priority_fee_per_gas.map(gwei),
+        "nonce": tx.nonce,
+        "call": call,
+        "decoded": decoded,
+        "data": (!input.is_empty()).then(|| format!("0x{}", hex::encode(input))),
+        "touches": touches,
+        "fiat": crate::prices::transaction_fiat(state, tx),
+    });
+    if let Some(name) = to_ens {
+        details["toEns"] = json!(name);
+    }
+    details
+}
+
+/// Shows `details` in the transaction prompt and blocks until the user
+/// answers, or an approval rule does. Nothing is signed unless approved.
+pub(super) fn confirm_transaction(
+    ctx: &SignContext,
+    tx: &TransactionRequest,
+    details: Value,
+) -> Result<()> {
+    let active_chain_id = ctx
+        .state
+        .wallet
+        .lock()
+        .expect("poisoned wallet lock while reading the active chain")
+        .chain
+        .chain_id;
+    let facts = crate::policy::TransactionFacts::for_request(active_chain_id, tx)?;
+    let to = details["to"].as_str().unwrap_or_default().to_string();
+    let to_ens = details["toEns"].as_str().map(str::to_string);
+    let prompt = PromptRequest::new(
+        "transaction",
+        "Send transaction?",
+        "Check the recipient, value and fees before approving.",
+    )
+    .with_details(details)
+    .for_transaction(facts);
+    let prompt = match ctx.webview_id {
+        Some(webview_id) => prompt.from_webview(webview_id),
+        None => prompt,
+    };
+    let approved = crate::prompt::ask(ctx.state, prompt);
+    tracing::info!(
+        target: "vibefi::audit",
+        webview_id = ctx.webview_id.unwrap_or("host"),
+        account = %ctx.account,
+        %to,
+        to_ens = ?to_ens,
+        approved,
+        "transaction approval answered"
+    );
+    if !approved {
+        bail!("User rejected the request");
+    }
+    Ok(())
+}
+
+/// EIP-712 signing hash of the dapp's typed data JSON:
+/// `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))`.
+pub(super) fn typed_data_hash(typed_data_json: &str) -> Result<B256> {
+    let typed_data: TypedData =
+        serde_json::from_str(typed_data_json).context("invalid EIP-712 typed data")?;
+    typed_data
+        .eip712_signing_hash()
+        .map_err(|err| anyhow!("invalid EIP-712 typed data: {err}"))
+}
+
+/// Runs `task` off the event loop: devices wait for a button press, remote
+/// services for the network, and transactions for several RPC fill calls.
+fn spawn_signer_task<F>(
+    state: &AppState,
+    webview_id: &str,
+    req: &IpcRequest,
+    signer: &'static dyn WalletSigner,
+    account: String,
+    task: F,
+) where
+    F: FnOnce(&SignContext) -> Result<String> + Send + 'static,
+{
+    let state = state.clone();
+    let wv_id = webview_id.to_string();
+    let ipc_id = req.id;
+    let method = req.method.clone();
+    let backend = signer.backend().as_str();
+    tracing::debug!(webview_id, ipc_id, method = %method, backend, "spawning signer worker");
+    let count = state.increment_signature_pending(webview_id);
+    send_signature_badge(&state, webview_id, count);
+
+    std::thread::spawn(move || {
+        let ctx = SignContext {
+            state: &state,
+            webview_id: Some(&wv_id),
+            account,
+        };
+        let result = task(&ctx).map_err(|e| format!("{e:#}"));
+        if let Err(err) = &result {
+            tracing::warn!(
+                webview_id = %wv_id,
+                ipc_id,
+                method = %method,
+                backend,
+                error = %err,
+                "signer worker failed"
+            );
+        } else {
+            tracing::debug!(
+                webview_id = %wv_id,
+                ipc_id,
+                method = %method,
+                backend,
+                "signer worker succeeded"
+            );
+        }
+        let count = state.decrement_signature_pending(&wv_id);
+        send_signature_badge(&state, &wv_id, count);
+        if let Err(err) = state.proxy.send_event(UserEvent::SignerResult {
+            webview_id: wv_id,
+            ipc_id,
+            result,
+        }) {
+            tracing::warn!(error = %err, "failed to send SignerResult from worker");
+        }
+    });
+}
+
+fn send_signature_badge(state: &AppState, webview_id: &str, count: u32) {
+    if let Err(err) = state.proxy.send_event(UserEvent::TabBadgeChanged {
+        webview_id: webview_id.to_string(),
+        badge: TabBadge::AwaitingSignature(count),
+    }) {
+        tracing::warn!(error = %err, "failed to send awaiting-signature badge");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{typed_data_hash, wallet_signer};
+    use crate::state::{ProviderCapabilities, WalletBackend};
+    use serde_json::json;
+
+    #[test]
+    fn every_key_holding_backend_has_a_signer() {
+        for backend in [
+            WalletBackend::Local,
+            WalletBackend::Hardware,
+            WalletBackend::SmartAccount,
+            WalletBackend::Remote,
+        ] {
+            let signer = wallet_signer(backend).expect("signer");
+            assert_eq!(signer.backend(), backend);
+        }
+    }
+
+    #[test]
+    fn walletconnect_signs_in_the_wallet() {
+        assert!(wallet_signer(WalletBackend::WalletConnect).is_none());
+    }
+
+    #[test]
+    fn backend_names_match_their_serde_form() {
+        for backend in [
+            WalletBackend::Local,
+            WalletBackend::WalletConnect,
+            WalletBackend::Hardware,
+            WalletBackend::SmartAccount,
+            WalletBackend::Remote,
+        ] {
+            assert_eq!(
+                serde_json::to_value(backend).unwrap(),
+                serde_json::Value::from(backend.as_str())
+            );
+        }
+    }
+
+    fn caps(backend: WalletBackend) -> ProviderCapabilities {
+        let signer = wallet_signer(backend).expect("signer");
+        ProviderCapabilities::from(signer.capabilities())
+    }
+
+    #[test]
+    fn local_signer_reports_every_capability() {
+        assert_eq!(
+            serde_json::to_value(caps(WalletBackend::Local)).unwrap(),
+            json!({
+                "canSignTypedData": true,
+                "canSendRaw": true,
+                "canSwitchChain": true,
+                "clientConfirmation": false,
+            })
+        );
+    }
+
+    #[test]
+    fn smart_accounts_cannot_send_raw_transactions() {
+        assert!(!caps(WalletBackend::SmartAccount).can_send_raw);
+    }
+
+    #[test]
+    fn remote_signers_confirm_in_the_client() {
+        assert!(caps(WalletBackend::Remote).client_confirmation);
+    }
+
+    #[test]
+    fn no_backend_means_no_capabilities() {
+        assert!(!ProviderCapabilities::default().can_sign_typed_data);
+    }
+
+    /// The `Mail` example from the EIP-712 specification.
+    fn mail() -> serde_json::Value {
+        json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallet", "type": "address" }
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person" },
+                    { "name": "contents", "type": "string" }
+                ]
+            },
+            "primaryType": "Mail",
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+            },
+            "message": {
+                "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+                "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+                "contents": "Hello, Bob!"
+            }
+        })
+    }
+
+    #[test]
+    fn hashes_typed_data_per_eip712() {
+        let hash = typed_data_hash(&mail().to_string()).unwrap();
+        assert_eq!(
+            format!("{hash:#x}"),
+            "0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn typed_data_with_an_unknown_primary_type_is_refused() {
+        let mut unknown_type = mail();
+        unknown_type["primaryType"] = json!("Letter");
+        assert!(typed_data_hash(&unknown_type.to_string()).is_err());
+    }
+
+    #[test]
+    fn typed_data_must_be_json() {
+        assert!(typed_data_hash("not json").is_err());
+    }
+}
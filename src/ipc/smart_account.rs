@@ -0,0 +1,71 @@
+use alloy_rpc_types_eth::TransactionRequest;
+use anyhow::{Result, anyhow, bail};
+
+use crate::smart_account::SmartAccount;
+use crate::state::{AppState, SignerCapabilities, WalletBackend};
+
+use super::rpc::FilledTransaction;
+use super::signer::{
+    SignContext, WalletSigner, confirm_transaction, transaction_details, typed_data_hash,
+};
+
+/// The connected ERC-4337 account; its owner key signs and a bundler submits.
+pub(super) struct SmartAccountWallet;
+
+impl WalletSigner for SmartAccountWallet {
+    fn backend(&self) -> WalletBackend {
+        WalletBackend::SmartAccount
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "vibefi-smart-account"
+    }
+
+    fn capabilities(&self) -> SignerCapabilities {
+        SignerCapabilities {
+            switch_chain: false,
+            raw_transactions: false,
+            client_confirmation: false,
+        }
+    }
+
+    fn accounts(&self, state: &AppState) -> Result<Vec<String>> {
+        Ok(connected_account(state)
+            .map(|account| vec![format!("{:#x}", account.address)])
+            .unwrap_or_default())
+    }
+
+    fn sign_message(&self, ctx: &SignContext, message: &[u8]) -> Result<String> {
+        let account = require_account(ctx.state)?;
+        crate::smart_account::sign_message(ctx.state, &account, message)
+    }
+
+    fn sign_typed_data(&self, ctx: &SignContext, typed_data_json: &str) -> Result<String> {
+        let account = require_account(ctx.state)?;
+        let hash = typed_data_hash(typed_data_json)?;
+        crate::smart_account::sign_hash(ctx.state, &account, hash)
+    }
+
+    fn sign_transaction(&self, _ctx: &SignContext, _tx: FilledTransaction) -> Result<String> {
+        bail!("Smart accounts send transactions as UserOperations and cannot sign them raw")
+    }
+
+    /// Waits for the bundler to include the UserOperation; may take minutes.
+    fn send_transaction(&self, ctx: &SignContext, tx: TransactionRequest) -> Result<String> {
+        let account = require_account(ctx.state)?;
+        confirm_transaction(
+            ctx,
+            &tx,
+            transaction_details(ctx.state, ctx.webview_id, &tx, None),
+        )?;
+        crate::smart_account::send_transaction(ctx.state, &account, tx)
+    }
+}
+
+fn connected_account(state: &AppState) -> Option<SmartAccount> {
+    state.smart_account.lock().expect("smart_account").clone()
+}
+
+fn require_account(state: &AppState) -> Result<SmartAccount> {
+    connected_account(state).ok_or_else(|| anyhow!("Smart account not connected"))
+}
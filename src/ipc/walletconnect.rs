@@ -3,7 +3,7 @@ use serde_json::Value;
 use wry::WebView;
 
 use crate::ipc_contract::IpcRequest;
-use crate::state::{AppState, ProviderInfo, UserEvent, WalletBackend};
+use crate::state::{AppState, ProviderCapabilities, UserEvent, WalletBackend};
 use crate::walletconnect::{HelperEvent, WalletConnectSession};
 
 use super::rpc::parse_hex_u64;
@@ -16,72 +16,6 @@ pub(super) fn handle_walletconnect_ipc(
     req: &IpcRequest,
 ) -> Result<Option<Value>> {
     match req.method.as_str() {
-        "eth_requestAccounts" => {
-            let chain_id = state
-                .wallet
-                .lock()
-                .expect("poisoned wallet lock while handling walletconnect eth_requestAccounts")
-                .chain
-                .chain_id;
-            tracing::info!(
-                chain_id = format!("0x{:x}", chain_id),
-                "walletconnect eth_requestAccounts received"
-            );
-            let bridge = state
-                .walletconnect
-                .lock()
-                .expect("poisoned walletconnect lock while retrieving bridge")
-                .as_ref()
-                .ok_or_else(|| anyhow!("walletconnect bridge unavailable"))?
-                .clone();
-            let proxy = state.proxy.clone();
-            let ipc_id = req.id;
-            let wv_id = webview_id.to_string();
-
-            std::thread::spawn(move || {
-                let result = {
-                    let mut bridge = bridge
-                        .lock()
-                        .expect("poisoned walletconnect bridge lock during connect");
-                    let proxy_for_events = proxy.clone();
-                    bridge.connect_with_event_handler(chain_id, move |event| {
-                        if event.event == "display_uri" {
-                            if let Some(uri) = event.uri.clone() {
-                                let qr_svg = event.qr_svg.clone().unwrap_or_default();
-                                let _ = proxy_for_events
-                                    .send_event(UserEvent::WalletConnectPairing { uri, qr_svg });
-                            }
-                        }
-                    })
-                };
-                let mapped = result.map_err(|e| e.to_string());
-                let _ = proxy.send_event(UserEvent::WalletConnectResult {
-                    webview_id: wv_id,
-                    ipc_id,
-                    result: mapped,
-                });
-            });
-
-            Ok(None)
-        }
-        "eth_accounts" => {
-            let value =
-                walletconnect_request(webview, state, req.method.as_str(), req.params.clone())?;
-            let accounts = if let Some(arr) = value.as_array() {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(str::to_string))
-                    .collect::<Vec<_>>()
-            } else {
-                vec![]
-            };
-            let mut ws = state
-                .wallet
-                .lock()
-                .expect("poisoned wallet lock while applying walletconnect eth_accounts");
-            ws.authorized = !accounts.is_empty();
-            ws.account = accounts.first().cloned();
-            Ok(Some(value))
-        }
         "eth_chainId" => {
             let value =
                 walletconnect_request(webview, state, req.method.as_str(), req.params.clone())?;
@@ -111,17 +45,14 @@ pub(super) fn handle_walletconnect_ipc(
             Ok(Some(Value::String(chain_id.to_string())))
         }
         "wallet_getProviderInfo" => {
-            let ws = state
-                .wallet
-                .lock()
-                .expect("poisoned wallet lock while building walletconnect provider info");
-            let info = ProviderInfo {
-                name: "vibefi-walletconnect".to_string(),
-                chain_id: format!("0x{:x}", ws.chain.chain_id),
-                backend: "walletconnect",
-                account: ws.account.clone(),
-                walletconnect_uri: ws.walletconnect_uri.clone(),
-            };
+            let info = super::provider_info(
+                state,
+                webview_id,
+                "vibefi-walletconnect",
+                Some(WalletBackend::WalletConnect),
+                state.account_for(webview_id),
+                ProviderCapabilities::WALLETCONNECT,
+            );
             Ok(Some(serde_json::to_value(info)?))
         }
         "wallet_switchEthereumChain" => {
@@ -198,15 +129,8 @@ fn apply_walletconnect_event(webview: &WebView, state: &AppState, event: &Helper
         }
         "accountsChanged" => {
             let accounts = event.accounts.clone().unwrap_or_default();
-            {
-                let mut ws = state
-                    .wallet
-                    .lock()
-                    .expect("poisoned wallet lock while applying walletconnect accountsChanged");
-                ws.authorized = !accounts.is_empty();
-                ws.account = accounts.first().cloned();
-            }
-            emit_accounts_changed(webview, accounts);
+            let changed = state.apply_walletconnect_accounts(&accounts);
+            notify_accounts_changed(state, changed);
         }
         "chainChanged" => {
             if let Some(chain_hex) = event.chain_id.clone() {
@@ -221,15 +145,8 @@ fn apply_walletconnect_event(webview: &WebView, state: &AppState, event: &Helper
             }
         }
         "disconnect" => {
-            {
-                let mut ws = state
-                    .wallet
-                    .lock()
-                    .expect("poisoned wallet lock while applying walletconnect disconnect");
-                ws.authorized = false;
-                ws.account = None;
-            }
-            emit_accounts_changed(webview, Vec::new());
+            let changed = state.apply_walletconnect_accounts(&[]);
+            notify_accounts_changed(state, changed);
         }
         _ => {}
     }
@@ -261,21 +178,11 @@ pub fn handle_walletconnect_connect_result(
                     .wallet
                     .lock()
                     .expect("poisoned wallet lock while storing walletconnect session state");
-                ws.authorized = !session.accounts.is_empty();
-                ws.account = session.accounts.first().cloned();
                 ws.chain.chain_id = chain_id;
                 ws.walletconnect_uri = None;
             }
-            // Set backend to WalletConnect if not already set
-            {
-                let mut wb = state
-                    .wallet_backend
-                    .lock()
-                    .expect("poisoned wallet_backend lock while setting walletconnect backend");
-                if wb.is_none() {
-                    *wb = Some(WalletBackend::WalletConnect);
-                }
-            }
+            let changed = state.apply_walletconnect_accounts(&session.accounts);
+            notify_accounts_changed(state, changed);
             if !session.accounts.is_empty() {
                 emit_accounts_changed(webview, session.accounts.clone());
             }
@@ -297,3 +204,17 @@ pub fn handle_walletconnect_connect_result(
         }
     }
 }
+
+/// Pushes `accountsChanged` with the full account list to tabs whose
+/// WalletConnect accounts moved; they may not be the tab whose request
+/// surfaced the change.
+pub(super) fn notify_accounts_changed(state: &AppState, webview_ids: Vec<String>) {
+    for webview_id in webview_ids {
+        let accounts = state.accounts_for(&webview_id);
+        let _ = state.proxy.send_event(UserEvent::ProviderEvent {
+            webview_id,
+            event: "accountsChanged".to_string(),
+            value: Value::Array(accounts.into_iter().map(Value::String).collect()),
+        });
+    }
+}
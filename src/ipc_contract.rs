@@ -7,7 +7,11 @@ pub const PROVIDER_ID_TABBAR: &str = "vibefi-tabbar";
 pub const PROVIDER_ID_PROVIDER: &str = "vibefi-provider";
 pub const PROVIDER_ID_SETTINGS: &str = "vibefi-settings";
 pub const PROVIDER_ID_IPFS: &str = "vibefi-ipfs";
+pub const PROVIDER_ID_NETWORK: &str = "vibefi-network";
+pub const PROVIDER_ID_APP: &str = "vibefi-app";
 pub const PROVIDER_ID_AUTOMATION: &str = "vibefi-automation";
+pub const PROVIDER_ID_PROMPT: &str = "vibefi-prompt";
+pub const PROVIDER_ID_PALETTE: &str = "vibefi-palette";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KnownProviderId {
@@ -17,7 +21,11 @@ pub enum KnownProviderId {
     Tabbar,
     Settings,
     Ipfs,
+    Network,
+    App,
     Automation,
+    Prompt,
+    Palette,
 }
 
 impl KnownProviderId {
@@ -29,12 +37,19 @@ impl KnownProviderId {
             PROVIDER_ID_TABBAR => Some(Self::Tabbar),
             PROVIDER_ID_SETTINGS => Some(Self::Settings),
             PROVIDER_ID_IPFS => Some(Self::Ipfs),
+            PROVIDER_ID_NETWORK => Some(Self::Network),
+            PROVIDER_ID_APP => Some(Self::App),
             PROVIDER_ID_AUTOMATION => Some(Self::Automation),
+            PROVIDER_ID_PROMPT => Some(Self::Prompt),
+            PROVIDER_ID_PALETTE => Some(Self::Palette),
             _ => None,
         }
     }
 }
 
+/// Method of an IPC message carrying a JSON-RPC batch in `batch`.
+pub const BATCH_METHOD: &str = "vibefi_batch";
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IpcRequest {
@@ -45,6 +60,20 @@ pub struct IpcRequest {
     pub method: String,
     #[serde(default)]
     pub params: Value,
+    /// Calls of a JSON-RPC batch, answered together with one ordered array.
+    #[serde(default)]
+    pub batch: Option<Vec<BatchCall>>,
+}
+
+/// One call of a JSON-RPC batch. `id` is the dapp's own and is echoed back
+/// with the call's result.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchCall {
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
 }
 
 impl IpcRequest {
@@ -67,8 +96,17 @@ impl IpcRequest {
 pub enum WalletSelectorMethod {
     GetCapabilities,
     ConnectLocal,
+    ImportLocalKey,
+    UnlockLocalKey,
+    ForgetLocalKey,
     ConnectWalletConnect,
     ConnectHardware,
+    ConnectLattice,
+    PairLattice,
+    ScanLedgerBle,
+    ConnectLedgerBle,
+    ConnectSmartAccount,
+    ConnectRemoteSigner,
 }
 
 impl WalletSelectorMethod {
@@ -76,8 +114,17 @@ impl WalletSelectorMethod {
         match value {
             "vibefi_getSelectorCapabilities" => Some(Self::GetCapabilities),
             "vibefi_connectLocal" => Some(Self::ConnectLocal),
+            "vibefi_importLocalKey" => Some(Self::ImportLocalKey),
+            "vibefi_unlockLocalKey" => Some(Self::UnlockLocalKey),
+            "vibefi_forgetLocalKey" => Some(Self::ForgetLocalKey),
             "vibefi_connectWalletConnect" => Some(Self::ConnectWalletConnect),
             "vibefi_connectHardware" => Some(Self::ConnectHardware),
+            "vibefi_connectLattice" => Some(Self::ConnectLattice),
+            "vibefi_pairLattice" => Some(Self::PairLattice),
+            "vibefi_scanLedgerBle" => Some(Self::ScanLedgerBle),
+            "vibefi_connectLedgerBle" => Some(Self::ConnectLedgerBle),
+            "vibefi_connectSmartAccount" => Some(Self::ConnectSmartAccount),
+            "vibefi_connectRemoteSigner" => Some(Self::ConnectRemoteSigner),
             _ => None,
         }
     }
@@ -87,6 +134,8 @@ impl WalletSelectorMethod {
 pub enum TabbarMethod {
     SwitchTab,
     CloseTab,
+    SwitchWallet,
+    AddWallet,
 }
 
 impl TabbarMethod {
@@ -94,6 +143,8 @@ impl TabbarMethod {
         match value {
             "switchTab" => Some(Self::SwitchTab),
             "closeTab" => Some(Self::CloseTab),
+            "switchWallet" => Some(Self::SwitchWallet),
+            "addWallet" => Some(Self::AddWallet),
             _ => None,
         }
     }
@@ -105,8 +156,8 @@ pub enum HostDispatchKind {
     RpcResponse,
     ProviderEvent,
     WalletconnectPairing,
+    WalletconnectRelay,
     TabbarUpdate,
-    RpcStatus,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -119,6 +170,9 @@ pub struct HostDispatchEnvelope<T: Serialize> {
 pub struct RpcResponseError {
     pub code: i64,
     pub message: String,
+    /// Stable code and hint from [`crate::error_hints`], when one applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -146,11 +200,6 @@ pub struct WalletconnectPairingPayload {
 pub struct TabbarUpdatePayload {
     pub tabs: Vec<Value>,
     pub active_index: usize,
-}
-
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct RpcStatusPayload {
-    pub webview_id: String,
-    pub pending_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fork_label: Option<String>,
 }
@@ -0,0 +1,192 @@
+use reqwest::blocking::Client as HttpClient;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+use crate::config::{IpfsFetchBackend, ResolvedConfig};
+
+/// The empty UnixFS directory. Every gateway and router can resolve it without
+/// depending on any particular pin.
+const PROBE_CID: &str = "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IpfsService {
+    /// The local node's RPC API.
+    Api,
+    /// The local node's gateway, used by the `localnode` backend.
+    Gateway,
+    HeliaGateway,
+    HeliaRouter,
+}
+
+/// Outcome of probing one IPFS service.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpfsCheck {
+    pub service: IpfsService,
+    pub url: String,
+    /// Whether the service answers for the fetch backend in use.
+    pub in_use: bool,
+    /// The service answered at all, even with an error status.
+    pub reachable: bool,
+    /// The service answered the probe successfully.
+    pub ok: bool,
+    pub latency_ms: Option<u64>,
+    /// Node version for the API; otherwise the HTTP status or error.
+    pub detail: String,
+}
+
+/// Probes the IPFS API, gateway, Helia gateways and Helia routers in parallel.
+pub fn run(http: &HttpClient, devnet: &ResolvedConfig) -> Vec<IpfsCheck> {
+    let ipfs = crate::registry::resolve_effective_ipfs_config(devnet);
+    let local = ipfs.fetch_backend == IpfsFetchBackend::LocalNode;
+    let helia = ipfs.fetch_backend == IpfsFetchBackend::Helia;
+
+    let mut targets = vec![
+        (IpfsService::Api, devnet.ipfs_api.clone(), local),
+        (IpfsService::Gateway, ipfs.gateway_endpoint.clone(), local),
+    ];
+    targets.extend(
+        ipfs.helia_gateways
+            .iter()
+            .map(|url| (IpfsService::HeliaGateway, url.clone(), helia)),
+    );
+    targets.extend(
+        ipfs.helia_routers
+            .iter()
+            .map(|url| (IpfsService::HeliaRouter, url.clone(), helia)),
+    );
+
+    let checks: Vec<IpfsCheck> = std::thread::scope(|scope| {
+        let handles: Vec<_> = targets
+            .into_iter()
+            .map(|(service, url, in_use)| {
+                scope.spawn(move || probe(http, service, &url, PROBE_CID, in_use))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("ipfs probe thread panicked"))
+            .collect()
+    });
+    for check in &checks {
+        tracing::info!(
+            service = ?check.service,
+            url = %check.url,
+            in_use = check.in_use,
+            ok = check.ok,
+            latency_ms = check.latency_ms,
+            detail = %check.detail,
+            "ipfs connectivity check"
+        );
+    }
+    checks
+}
+
+fn probe(
+    http: &HttpClient,
+    service: IpfsService,
+    base: &str,
+    cid: &str,
+    in_use: bool,
+) -> IpfsCheck {
+    let base = base.trim_end_matches('/');
+    let request = match service {
+        // Kubo only accepts POST on its RPC API.
+        IpfsService::Api => http.post(format!("{base}/api/v0/version")),
+        IpfsService::Gateway | IpfsService::HeliaGateway => http.get(format!("{base}/ipfs/{cid}")),
+        IpfsService::HeliaRouter => http
+            .get(format!("{base}/routing/v1/providers/{cid}"))
+            .header("Accept", "application/json"),
+    };
+
+    let started = Instant::now();
+    let response = request.timeout(PROBE_TIMEOUT).send();
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let mut check = IpfsCheck {
+        service,
+        url: base.to_string(),
+        in_use,
+        reachable: false,
+        ok: false,
+        latency_ms: None,
+        detail: String::new(),
+    };
+    let response = match response {
+        Ok(response) => response,
+        Err(err) => {
+            check.detail = if err.is_timeout() {
+                format!("no answer within {}s", PROBE_TIMEOUT.as_secs())
+            } else {
+                format!("connection failed: {err}")
+            };
+            return check;
+        }
+    };
+
+    let status = response.status();
+    check.reachable = true;
+    check.latency_ms = Some(latency_ms);
+    // A router without providers for the CID answers 404, which still shows
+    // it is serving the routing API.
+    check.ok = status.is_success() || (service == IpfsService::HeliaRouter && status == 404);
+    check.detail = match service {
+        IpfsService::Api if status.is_success() => response
+            .json::<Value>()
+            .ok()
+            .and_then(|body| {
+                body.get("Version")?
+                    .as_str()
+                    .map(|v| format!("version {v}"))
+            })
+            .unwrap_or_else(|| format!("HTTP {status}")),
+        _ => format!("HTTP {status}"),
+    };
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IpfsService, probe};
+    use crate::test_harness::MockGateway;
+
+    #[test]
+    fn gateway_probe_reports_served_content() {
+        let gateway = MockGateway::start();
+        let cid = gateway.add_bundle(&[("index.html", b"<html></html>")]);
+        let http = reqwest::blocking::Client::new();
+        let path = format!("{cid}/manifest.json");
+
+        let found = probe(&http, IpfsService::Gateway, &gateway.url, &path, true);
+        assert!(found.ok && found.reachable);
+        assert!(found.latency_ms.is_some());
+    }
+
+    #[test]
+    fn gateway_probe_reports_missing_content_as_reachable() {
+        let gateway = MockGateway::start();
+        let http = reqwest::blocking::Client::new();
+
+        let missing = probe(
+            &http,
+            IpfsService::Gateway,
+            &gateway.url,
+            "bafymissing/x",
+            true,
+        );
+        assert!(missing.reachable && !missing.ok);
+        assert_eq!(missing.detail, "HTTP 404 Not Found");
+    }
+
+    #[test]
+    fn gateway_probe_reports_a_closed_port_as_unreachable() {
+        let http = reqwest::blocking::Client::new();
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        let down = probe(&http, IpfsService::Gateway, &url, "bafymissing/x", true);
+        assert!(!down.reachable && down.latency_ms.is_none());
+    }
+}
@@ -0,0 +1,381 @@
+//! Encrypted store for the local signer's keys, kept as `keystore.json` next
+//! to `settings.json`. A private key or BIP-39 mnemonic is imported once from
+//! the wallet selector. A private key is stored as is; a mnemonic is checked
+//! and stored as its phrase, from which more accounts can be derived later.
+//! Either is sealed as an age file encrypted to the user's password (scrypt),
+//! so it can also be opened with `age -d`. Connecting the local signer
+//! afterwards asks for the password in the selector.
+
+use age::secrecy::SecretString;
+use alloy_primitives::{Address, B256};
+use alloy_signer_local::{MnemonicBuilder, PrivateKeySigner};
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+const KEYSTORE_FILE: &str = "keystore.json";
+const KEYSTORE_VERSION: u32 = 2;
+const MIN_PASSWORD_LEN: usize = 8;
+const MNEMONIC_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+/// Most derived accounts a keystore keeps.
+const MAX_ACCOUNTS: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeystoreFile {
+    version: u32,
+    /// First account of the sealed secret, readable without the password.
+    /// Opening the secret checks it, so editing it makes unlocking fail.
+    address: Address,
+    #[serde(default)]
+    secret: SecretKind,
+    /// Accounts derived from a mnemonic so far, in derivation order; the
+    /// first is `address`. Checked against the mnemonic on unlock.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    accounts: Vec<Address>,
+    /// The secret as a base64 age file with a scrypt passphrase stanza.
+    sealed: String,
+}
+
+impl KeystoreFile {
+    fn accounts(&self) -> Vec<Address> {
+        if self.accounts.is_empty() {
+            vec![self.address]
+        } else {
+            self.accounts.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum SecretKind {
+    #[default]
+    PrivateKey,
+    /// English BIP-39 phrase; accounts are `m/44'/60'/0'/0/{index}`.
+    Mnemonic,
+}
+
+fn keystore_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(KEYSTORE_FILE)
+}
+
+fn load(config_path: &Path) -> Option<KeystoreFile> {
+    let path = keystore_path(config_path);
+    let raw = match std::fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            tracing::warn!(path = %path.display(), error = %err, "failed to read keystore.json");
+            return None;
+        }
+    };
+    serde_json::from_str(&raw)
+        .map_err(|err| {
+            tracing::warn!(path = %path.display(), error = %err, "failed to parse keystore.json");
+        })
+        .ok()
+}
+
+fn save(config_path: &Path, file: &KeystoreFile) -> Result<()> {
+    let json = serde_json::to_string_pretty(file).context("serialize keystore")?;
+    write_private(&keystore_path(config_path), &json)
+}
+
+/// Accounts of the stored keys, first account first; empty when nothing was
+/// imported.
+pub fn stored_accounts(config_path: &Path) -> Vec<Address> {
+    load(config_path)
+        .map(|file| file.accounts())
+        .unwrap_or_default()
+}
+
+/// Whether more accounts can be derived, i.e. a mnemonic was imported.
+pub fn can_derive(config_path: &Path) -> bool {
+    load(config_path).is_some_and(|file| file.secret == SecretKind::Mnemonic)
+}
+
+/// Imports `secret`, a hex private key or a BIP-39 mnemonic, and stores it
+/// sealed under `password`. Refuses to replace a stored key. A mnemonic
+/// starts with its first account.
+pub fn import(config_path: &Path, secret: &str, password: &str) -> Result<PrivateKeySigner> {
+    if load(config_path).is_some() {
+        bail!("A key is already stored; forget it before importing another");
+    }
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        bail!("Password must be at least {MIN_PASSWORD_LEN} characters");
+    }
+    let secret = secret.trim();
+    let (kind, bytes) = if secret.split_whitespace().count() > 1 {
+        let phrase = normalize_mnemonic(secret)?;
+        (
+            SecretKind::Mnemonic,
+            Zeroizing::new(phrase.as_bytes().to_vec()),
+        )
+    } else {
+        let hex = secret.strip_prefix("0x").unwrap_or(secret);
+        let bytes = Zeroizing::new(hex::decode(hex).context("private key is not hex")?);
+        if bytes.len() != 32 {
+            bail!("private key must be 32 bytes");
+        }
+        (SecretKind::PrivateKey, bytes)
+    };
+    let signer = signer_at(kind, &bytes, 0)?;
+    let file = encrypt(kind, &bytes, signer.address(), password, None)?;
+    save(config_path, &file)?;
+    tracing::info!(target: "vibefi::audit", address = %signer.address(), "imported key into keystore");
+    Ok(signer)
+}
+
+/// Opens the stored keys with `password`, first account first.
+pub fn unlock(config_path: &Path, password: &str) -> Result<Vec<PrivateKeySigner>> {
+    let file = load(config_path).ok_or_else(|| anyhow!("no key is stored"))?;
+    let secret = decrypt(&file, password)?;
+    file.accounts()
+        .into_iter()
+        .enumerate()
+        .map(|(index, address)| {
+            let signer = signer_at(file.secret, &secret, index as u32)?;
+            if signer.address() != address {
+                bail!("stored key does not match account {address}");
+            }
+            Ok(signer)
+        })
+        .collect()
+}
+
+/// Derives the next account from a stored mnemonic and keeps it.
+pub fn add_account(config_path: &Path, password: &str) -> Result<PrivateKeySigner> {
+    let mut file = load(config_path).ok_or_else(|| anyhow!("no key is stored"))?;
+    if file.secret != SecretKind::Mnemonic {
+        bail!("Only keys imported from a recovery phrase can derive more accounts");
+    }
+    let mut accounts = file.accounts();
+    if accounts.len() >= MAX_ACCOUNTS {
+        bail!("A keystore keeps at most {MAX_ACCOUNTS} accounts");
+    }
+    let secret = decrypt(&file, password)?;
+    let signer = signer_at(file.secret, &secret, accounts.len() as u32)?;
+    accounts.push(signer.address());
+    file.accounts = accounts;
+    save(config_path, &file)?;
+    tracing::info!(target: "vibefi::audit", address = %signer.address(), "derived keystore account");
+    Ok(signer)
+}
+
+/// Deletes the stored key once `password` opens it.
+pub fn forget(config_path: &Path, password: &str) -> Result<()> {
+    let file = load(config_path).ok_or_else(|| anyhow!("no key is stored"))?;
+    decrypt(&file, password)?;
+    std::fs::remove_file(keystore_path(config_path)).context("remove keystore.json")?;
+    tracing::info!(target: "vibefi::audit", address = %file.address, "removed key from keystore");
+    Ok(())
+}
+
+fn signer_at(kind: SecretKind, secret: &[u8], index: u32) -> Result<PrivateKeySigner> {
+    let key = match kind {
+        SecretKind::PrivateKey if index == 0 => {
+            let mut key = Zeroizing::new([0u8; 32]);
+            if secret.len() != key.len() {
+                bail!("stored key has the wrong length");
+            }
+            key.copy_from_slice(secret);
+            key
+        }
+        SecretKind::PrivateKey => bail!("a private key has a single account"),
+        SecretKind::Mnemonic => return mnemonic_account(secret, index),
+    };
+    PrivateKeySigner::from_bytes(&B256::from(*key)).context("invalid private key")
+}
+
+/// Seals `secret` under `password`. `work_factor` overrides the scrypt cost
+/// (`log2 N`), which otherwise targets about a second on this machine.
+fn encrypt(
+    kind: SecretKind,
+    secret: &[u8],
+    address: Address,
+    password: &str,
+    work_factor: Option<u8>,
+) -> Result<KeystoreFile> {
+    let mut recipient = age::scrypt::Recipient::new(SecretString::from(password.to_string()));
+    if let Some(log_n) = work_factor {
+        recipient.set_work_factor(log_n);
+    }
+    let sealed = age::encrypt(&recipient, secret).context("failed to encrypt the key")?;
+    Ok(KeystoreFile {
+        version: KEYSTORE_VERSION,
+        address,
+        secret: kind,
+        accounts: match kind {
+            SecretKind::PrivateKey => Vec::new(),
+            SecretKind::Mnemonic => vec![address],
+        },
+        sealed: BASE64.encode(sealed),
+    })
+}
+
+fn decrypt(file: &KeystoreFile, password: &str) -> Result<Zeroizing<Vec<u8>>> {
+    if file.version != KEYSTORE_VERSION {
+        bail!("unsupported keystore format");
+    }
+    let sealed = BASE64
+        .decode(&file.sealed)
+        .context("keystore secret is not base64")?;
+    let identity = age::scrypt::Identity::new(SecretString::from(password.to_string()));
+    let secret = Zeroizing::new(age::decrypt(&identity, &sealed).map_err(|err| match err {
+        age::DecryptError::DecryptionFailed | age::DecryptError::NoMatchingKeys => {
+            anyhow!("Wrong password")
+        }
+        err => anyhow!("failed to open the keystore: {err}"),
+    })?);
+    if signer_at(file.secret, &secret, 0)?.address() != file.address {
+        bail!("stored key does not match account {}", file.address);
+    }
+    Ok(secret)
+}
+
+/// Replaces `path` with `contents`, readable by the current user only where
+/// the platform supports it. The file is written next to `path` and renamed
+/// over it, so a crash leaves either the old keystore or the new one.
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    let partial = path.with_extension("json.part");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&partial)
+        .context("create keystore.json.part")?;
+    file.write_all(contents.as_bytes())
+        .context("write keystore.json.part")?;
+    file.sync_all().context("sync keystore.json.part")?;
+    drop(file);
+    std::fs::rename(&partial, path).context("replace keystore.json")
+}
+
+/// The phrase in the form it is stored: lowercase words separated by single
+/// spaces. Whether the words and checksum are valid is left to
+/// [`mnemonic_account`].
+fn normalize_mnemonic(phrase: &str) -> Result<Zeroizing<String>> {
+    let words: Vec<String> = phrase
+        .split_whitespace()
+        .map(str::to_ascii_lowercase)
+        .collect();
+    if !MNEMONIC_WORD_COUNTS.contains(&words.len()) {
+        bail!("a recovery phrase has 12, 15, 18, 21 or 24 words");
+    }
+    if words
+        .iter()
+        .any(|word| !word.chars().all(|c| c.is_ascii_alphabetic()))
+    {
+        bail!("only English recovery phrases are supported");
+    }
+    Ok(Zeroizing::new(words.join(" ")))
+}
+
+/// Account `index` (`m/44'/60'/0'/0/{index}`) of an English BIP-39 phrase
+/// without passphrase. Fails on unknown words or a bad checksum.
+fn mnemonic_account(phrase: &[u8], index: u32) -> Result<PrivateKeySigner> {
+    let phrase = std::str::from_utf8(phrase).context("stored recovery phrase is not UTF-8")?;
+    MnemonicBuilder::english()
+        .phrase(phrase)
+        .index(index)
+        .and_then(|builder| builder.build())
+        .map_err(|err| anyhow!("invalid recovery phrase: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeystoreFile, SecretKind, decrypt, encrypt, mnemonic_account, normalize_mnemonic};
+    use alloy_primitives::{Address, address};
+
+    /// Anvil's default mnemonic.
+    fn anvil_phrase() -> String {
+        normalize_mnemonic("Test test test test test test test test test  test test junk")
+            .unwrap()
+            .to_string()
+    }
+
+    const ANVIL_ACCOUNT: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+
+    fn sealed_anvil_phrase() -> KeystoreFile {
+        encrypt(
+            SecretKind::Mnemonic,
+            anvil_phrase().as_bytes(),
+            ANVIL_ACCOUNT,
+            "correct horse",
+            Some(10),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn derives_the_first_mnemonic_account() {
+        // The BIP-39 test phrase for all-zero entropy and its first account.
+        let abandon = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                       abandon abandon about";
+        assert_eq!(
+            mnemonic_account(normalize_mnemonic(abandon).unwrap().as_bytes(), 0)
+                .unwrap()
+                .address(),
+            address!("9858EfFD232B4033E47d90003D41EC34EcaEda94")
+        );
+    }
+
+    #[test]
+    fn refuses_mnemonics_with_a_bad_checksum() {
+        // The same words with a wrong checksum.
+        let bad_checksum = "abandon ".repeat(12);
+        assert!(
+            mnemonic_account(normalize_mnemonic(&bad_checksum).unwrap().as_bytes(), 0).is_err()
+        );
+    }
+
+    #[test]
+    fn refuses_phrases_of_the_wrong_length() {
+        assert!(normalize_mnemonic("test test test").is_err());
+    }
+
+    #[test]
+    fn derives_accounts_by_index_from_a_normalized_phrase() {
+        let phrase = anvil_phrase();
+        let phrase = phrase.as_bytes();
+        assert_eq!(
+            hex::encode(mnemonic_account(phrase, 0).unwrap().to_bytes()),
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+        );
+        assert_eq!(
+            hex::encode(mnemonic_account(phrase, 1).unwrap().to_bytes()),
+            "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"
+        );
+    }
+
+    #[test]
+    fn sealed_secrets_round_trip() {
+        let file = sealed_anvil_phrase();
+        assert_eq!(file.accounts(), [ANVIL_ACCOUNT]);
+        assert_eq!(
+            *decrypt(&file, "correct horse").unwrap(),
+            anvil_phrase().into_bytes()
+        );
+    }
+
+    #[test]
+    fn a_wrong_password_does_not_decrypt() {
+        assert!(decrypt(&sealed_anvil_phrase(), "wrong horse").is_err());
+    }
+
+    #[test]
+    fn the_address_is_checked_against_the_sealed_secret() {
+        let mut file = sealed_anvil_phrase();
+        file.address = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+        assert!(decrypt(&file, "correct horse").is_err());
+    }
+}
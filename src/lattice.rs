@@ -0,0 +1,323 @@
+use alloy_consensus::SignableTransaction;
+use alloy_primitives::{Address, Signature, U256};
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+
+use crate::{logging, runtime_paths};
+
+/// A connected helper waiting for the user to enter the pairing code shown on
+/// the device. Only one pairing can be in flight at a time.
+static PENDING_PAIRING: Mutex<Option<LatticeBridge>> = Mutex::new(None);
+
+#[derive(Debug, Deserialize)]
+struct HelperResponse {
+    pub id: u64,
+    #[serde(default)]
+    pub result: Option<Value>,
+    #[serde(default)]
+    pub error: Option<HelperError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HelperError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConnectResponse {
+    pub paired: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddressResponse {
+    pub address: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignatureResponse {
+    pub r: String,
+    pub s: String,
+    pub v: String,
+}
+
+pub struct LatticeBridge {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+    device_id: Option<String>,
+}
+
+impl LatticeBridge {
+    pub fn spawn() -> Result<Self> {
+        let helper_script = runtime_paths::resolve_lattice_helper_script()?;
+        let node_path = runtime_paths::resolve_node_binary()?;
+        let mut child = Command::new(&node_path)
+            .arg(&helper_script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to spawn lattice helper via {}", node_path))?;
+
+        if let Some(stderr) = child.stderr.take() {
+            logging::forward_child_stderr("lattice", stderr);
+        } else {
+            tracing::warn!("lattice helper stderr unavailable");
+        }
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("lattice helper stdin unavailable"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("lattice helper stdout unavailable"))?;
+        let mut bridge = Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            next_id: 1,
+            device_id: None,
+        };
+
+        bridge.ping().context(
+            "lattice helper failed ping; run `cd client/lattice-helper && bun install` first",
+        )?;
+        Ok(bridge)
+    }
+
+    /// Connects to the device and reports whether this client is already paired.
+    pub fn connect(&mut self, device_id: &str) -> Result<bool> {
+        tracing::info!(device_id, "lattice connect requested");
+        let result = self.send_command("connect", serde_json::json!({ "deviceId": device_id }))?;
+        let response: ConnectResponse =
+            serde_json::from_value(result).context("invalid connect response from helper")?;
+        self.device_id = Some(device_id.to_string());
+        Ok(response.paired)
+    }
+
+    pub fn pair(&mut self, code: &str) -> Result<()> {
+        let result = self.send_command("pair", serde_json::json!({ "code": code }))?;
+        let response: ConnectResponse =
+            serde_json::from_value(result).context("invalid pair response from helper")?;
+        if !response.paired {
+            bail!("Lattice rejected the pairing code");
+        }
+        Ok(())
+    }
+
+    pub fn address(&mut self) -> Result<Address> {
+        let result = self.send_command("getAddress", Value::Null)?;
+        let response: AddressResponse =
+            serde_json::from_value(result).context("invalid address response from helper")?;
+        response
+            .address
+            .parse()
+            .context("lattice returned an invalid address")
+    }
+
+    pub fn sign_personal(&mut self, msg: &[u8]) -> Result<Signature> {
+        let result = self.send_command(
+            "signPersonal",
+            serde_json::json!({ "payload": format!("0x{}", hex::encode(msg)) }),
+        )?;
+        parse_signature(result)
+    }
+
+    /// Signs the unsigned RLP encoding of a transaction.
+    pub fn sign_transaction(&mut self, payload: &[u8]) -> Result<Signature> {
+        let result = self.send_command(
+            "signTransaction",
+            serde_json::json!({ "payload": format!("0x{}", hex::encode(payload)) }),
+        )?;
+        parse_signature(result)
+    }
+
+    fn ping(&mut self) -> Result<()> {
+        let _ = self.send_command("ping", Value::Null)?;
+        Ok(())
+    }
+
+    fn send_command(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let payload = serde_json::json!({
+            "id": id,
+            "method": method,
+            "params": params
+        });
+        let line = serde_json::to_string(&payload)?;
+        self.stdin
+            .write_all(line.as_bytes())
+            .context("failed writing helper request")?;
+        self.stdin
+            .write_all(b"\n")
+            .context("failed writing helper newline")?;
+        self.stdin
+            .flush()
+            .context("failed flushing helper request")?;
+
+        loop {
+            let mut raw = String::new();
+            let n = self
+                .stdout
+                .read_line(&mut raw)
+                .context("failed reading helper response")?;
+            if n == 0 {
+                bail!("lattice helper closed pipe unexpectedly");
+            }
+            let raw = raw.trim();
+            if raw.is_empty() {
+                continue;
+            }
+            let resp: HelperResponse =
+                serde_json::from_str(raw).context("invalid lattice helper response payload")?;
+            if resp.id != id {
+                bail!(
+                    "lattice helper returned mismatched id (expected {}, got {})",
+                    id,
+                    resp.id
+                );
+            }
+            if let Some(error) = resp.error {
+                bail!("lattice helper error {}: {}", error.code, error.message);
+            }
+            return Ok(resp.result.unwrap_or(Value::Null));
+        }
+    }
+}
+
+impl Drop for LatticeBridge {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A paired Lattice1 and the account it signs for.
+pub struct LatticeSigner {
+    bridge: Mutex<LatticeBridge>,
+    address: Address,
+    device_id: Option<String>,
+}
+
+impl LatticeSigner {
+    pub fn new(mut bridge: LatticeBridge) -> Result<Self> {
+        let address = bridge.address()?;
+        tracing::info!(address = format!("0x{:x}", address), "lattice detected");
+        Ok(Self {
+            device_id: bridge.device_id.clone(),
+            bridge: Mutex::new(bridge),
+            address,
+        })
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn device_id(&self) -> Option<&str> {
+        self.device_id.as_deref()
+    }
+
+    pub fn sign_message(&self, msg: &[u8]) -> Result<Signature> {
+        self.bridge
+            .lock()
+            .expect("lattice_bridge")
+            .sign_personal(msg)
+    }
+
+    pub fn sign_transaction(&self, tx: &dyn SignableTransaction<Signature>) -> Result<Signature> {
+        let payload = tx.encoded_for_signing();
+        self.bridge
+            .lock()
+            .expect("lattice_bridge")
+            .sign_transaction(&payload)
+    }
+}
+
+/// Connects to a Lattice1. Returns the signer when the device is already
+/// paired, or parks the bridge until `pair` is called with the device code.
+pub fn connect(device_id: &str) -> Result<Option<LatticeSigner>> {
+    let device_id = device_id.trim();
+    if device_id.is_empty() {
+        bail!("Lattice device ID is required");
+    }
+    let mut bridge = LatticeBridge::spawn().context("failed to initialize Lattice bridge")?;
+    if bridge.connect(device_id)? {
+        return LatticeSigner::new(bridge).map(Some);
+    }
+    tracing::info!(device_id, "lattice requires pairing");
+    *PENDING_PAIRING.lock().expect("lattice_pending_pairing") = Some(bridge);
+    Ok(None)
+}
+
+/// Completes a pairing started by `connect`.
+pub fn pair(code: &str) -> Result<LatticeSigner> {
+    let code = code.trim();
+    if code.is_empty() {
+        bail!("pairing code is required");
+    }
+    let mut bridge = PENDING_PAIRING
+        .lock()
+        .expect("lattice_pending_pairing")
+        .take()
+        .ok_or_else(|| anyhow!("no Lattice pairing in progress"))?;
+    if let Err(err) = bridge.pair(code) {
+        // Keep the session so the user can retry with the right code.
+        *PENDING_PAIRING.lock().expect("lattice_pending_pairing") = Some(bridge);
+        return Err(err);
+    }
+    LatticeSigner::new(bridge)
+}
+
+fn parse_signature(result: Value) -> Result<Signature> {
+    let response: SignatureResponse =
+        serde_json::from_value(result).context("invalid signature response from helper")?;
+    let r = parse_word(&response.r).context("invalid signature r")?;
+    let s = parse_word(&response.s).context("invalid signature s")?;
+    let v = parse_word(&response.v).context("invalid signature v")?;
+    let v: u64 = v
+        .try_into()
+        .map_err(|_| anyhow!("signature v out of range"))?;
+    Ok(Signature::new(r, s, y_parity_from_v(v)?))
+}
+
+fn parse_word(value: &str) -> Result<U256> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    U256::from_str_radix(digits, 16).map_err(|e| anyhow!("{e}"))
+}
+
+/// Maps a raw, legacy (27/28) or EIP-155 `v` to the y-parity bit.
+fn y_parity_from_v(v: u64) -> Result<bool> {
+    match v {
+        0 | 1 => Ok(v == 1),
+        27 | 28 => Ok(v == 28),
+        v if v >= 35 => Ok((v - 35) % 2 == 1),
+        _ => bail!("unexpected signature v value {v}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::y_parity_from_v;
+
+    #[test]
+    fn maps_v_to_y_parity() {
+        assert!(!y_parity_from_v(0).unwrap());
+        assert!(y_parity_from_v(1).unwrap());
+        assert!(!y_parity_from_v(27).unwrap());
+        assert!(y_parity_from_v(28).unwrap());
+        // EIP-155 on Sepolia (chain id 11155111).
+        assert!(!y_parity_from_v(11155111 * 2 + 35).unwrap());
+        assert!(y_parity_from_v(11155111 * 2 + 36).unwrap());
+        assert!(y_parity_from_v(29).is_err());
+    }
+}
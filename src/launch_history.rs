@@ -0,0 +1,238 @@
+//! A report for every dapp launch attempt: which backend fetched the bundle,
+//! how long each stage took, how much was downloaded and whether it opened.
+//! Kept next to `settings.json` so slow or failing launches can be looked
+//! into afterwards.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::state::AppState;
+
+const HISTORY_FILE: &str = "launch-history.json";
+/// Oldest reports are dropped past this many.
+const MAX_REPORTS: usize = 200;
+
+/// Serializes read-modify-write cycles of the history file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchResult {
+    Opened,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchStage {
+    /// Same names as the `launchProgress` stages, plus `open`.
+    pub stage: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchReport {
+    pub root_cid: String,
+    pub dapp: String,
+    /// Unix seconds.
+    pub started_at: u64,
+    /// IPFS fetch backend, when the launch got as far as fetching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    pub stages: Vec<LaunchStage>,
+    /// Zero when the bundle came from the cache.
+    pub bytes_downloaded: u64,
+    /// `None` when a cached build was used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_ms: Option<u64>,
+    pub duration_ms: u64,
+    pub result: LaunchResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Times a launch as it runs. Stages are consecutive: starting one ends the
+/// previous one.
+pub struct LaunchRecorder {
+    report: LaunchReport,
+    started: Instant,
+    stage: Option<(String, Instant)>,
+}
+
+impl LaunchRecorder {
+    pub fn start(root_cid: &str, dapp: &str) -> Self {
+        Self {
+            report: LaunchReport {
+                root_cid: root_cid.to_string(),
+                dapp: dapp.to_string(),
+                started_at: now(),
+                backend: None,
+                stages: Vec::new(),
+                bytes_downloaded: 0,
+                build_ms: None,
+                duration_ms: 0,
+                result: LaunchResult::Failed,
+                error: None,
+            },
+            started: Instant::now(),
+            stage: None,
+        }
+    }
+
+    pub fn stage(&mut self, stage: &str) {
+        self.end_stage();
+        self.stage = Some((stage.to_string(), Instant::now()));
+    }
+
+    pub fn set_dapp(&mut self, dapp: &str) {
+        self.report.dapp = dapp.to_string();
+    }
+
+    pub fn set_backend(&mut self, backend: &str) {
+        self.report.backend = Some(backend.to_string());
+    }
+
+    pub fn add_downloaded(&mut self, bytes: u64) {
+        self.report.bytes_downloaded += bytes;
+    }
+
+    pub fn set_build_ms(&mut self, build_ms: u64) {
+        self.report.build_ms = Some(build_ms);
+    }
+
+    fn end_stage(&mut self) {
+        if let Some((stage, started)) = self.stage.take() {
+            self.report.stages.push(LaunchStage {
+                stage,
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+    }
+
+    fn into_report<T>(mut self, result: &Result<T>) -> LaunchReport {
+        self.end_stage();
+        self.report.duration_ms = self.started.elapsed().as_millis() as u64;
+        match result {
+            Ok(_) => self.report.result = LaunchResult::Opened,
+            Err(err) => {
+                self.report.result = LaunchResult::Failed;
+                self.report.error = Some(format!("{err:#}"));
+            }
+        }
+        self.report
+    }
+
+    /// Ends the launch with `result` and stores its report.
+    pub fn finish<T>(self, state: &AppState, result: &Result<T>) {
+        let report = self.into_report(result);
+        tracing::info!(
+            root_cid = %report.root_cid,
+            result = ?report.result,
+            duration_ms = report.duration_ms,
+            bytes_downloaded = report.bytes_downloaded,
+            "launch report"
+        );
+        let Some(config_path) = state
+            .resolved
+            .as_ref()
+            .and_then(|r| r.config_path.as_deref())
+        else {
+            return;
+        };
+        if let Err(err) = record(config_path, report) {
+            tracing::warn!(error = %format!("{err:#}"), "failed to record launch report");
+        }
+    }
+}
+
+/// Stored reports, newest first.
+pub fn load(config_path: &Path) -> Vec<LaunchReport> {
+    let mut reports = read(config_path);
+    reports.reverse();
+    reports
+}
+
+fn read(config_path: &Path) -> Vec<LaunchReport> {
+    std::fs::read(config_path.with_file_name(HISTORY_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_slice(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn record(config_path: &Path, report: LaunchReport) -> Result<()> {
+    let _guard = FILE_LOCK.lock().expect("launch history file");
+    let mut reports = read(config_path);
+    reports.push(report);
+    if reports.len() > MAX_REPORTS {
+        reports.drain(..reports.len() - MAX_REPORTS);
+    }
+    let path = config_path.with_file_name(HISTORY_FILE);
+    std::fs::write(&path, serde_json::to_vec(&reports)?)
+        .with_context(|| format!("write {}", path.display()))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LaunchRecorder, LaunchReport, LaunchResult, load, record};
+    use anyhow::anyhow;
+
+    fn failed_build() -> LaunchReport {
+        let mut launch = LaunchRecorder::start("bafyapp", "Swap");
+        launch.set_backend("helia");
+        launch.stage("download");
+        launch.add_downloaded(1024);
+        launch.stage("build");
+        launch.set_build_ms(5);
+        launch.into_report(&Err::<(), _>(anyhow!("build failed")))
+    }
+
+    #[test]
+    fn records_stages_in_order() {
+        let failed = failed_build();
+        assert_eq!(
+            failed
+                .stages
+                .iter()
+                .map(|stage| stage.stage.as_str())
+                .collect::<Vec<_>>(),
+            ["download", "build"]
+        );
+    }
+
+    #[test]
+    fn records_why_a_launch_failed() {
+        let failed = failed_build();
+        assert_eq!(failed.result, LaunchResult::Failed);
+        assert_eq!(failed.error.as_deref(), Some("build failed"));
+    }
+
+    #[test]
+    fn keeps_reports_newest_first() {
+        let dir =
+            std::env::temp_dir().join(format!("vibefi-launch-history-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.json");
+
+        record(&config_path, failed_build()).unwrap();
+        let opened = LaunchRecorder::start("bafyapp", "Swap").into_report(&Ok(()));
+        record(&config_path, opened).unwrap();
+
+        let reports = load(&config_path);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].result, LaunchResult::Opened);
+        assert_eq!(reports[1].bytes_downloaded, 1024);
+        assert_eq!(reports[1].backend.as_deref(), Some("helia"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
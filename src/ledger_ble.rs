@@ -0,0 +1,561 @@
+use alloy_consensus::SignableTransaction;
+use alloy_primitives::{Address, B256, Signature, U256, eip191_hash_message, keccak256};
+use anyhow::{Context, Result, anyhow, bail};
+use btleplug::api::{
+    Central, Characteristic, Manager as _, Peripheral as _, ScanFilter, ValueNotification,
+    WriteType,
+};
+use btleplug::platform::{Adapter, Manager, Peripheral};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use uuid::Uuid;
+
+/// GATT layout of a Ledger model with a Bluetooth radio.
+struct Model {
+    name: &'static str,
+    service: Uuid,
+    notify: Uuid,
+    write: Uuid,
+}
+
+const MODELS: [Model; 3] = [
+    Model {
+        name: "Nano X",
+        service: Uuid::from_u128(0x13d63400_2c97_0004_0000_4c6564676572),
+        notify: Uuid::from_u128(0x13d63400_2c97_0004_0001_4c6564676572),
+        write: Uuid::from_u128(0x13d63400_2c97_0004_0002_4c6564676572),
+    },
+    Model {
+        name: "Stax",
+        service: Uuid::from_u128(0x13d63400_2c97_6004_0000_4c6564676572),
+        notify: Uuid::from_u128(0x13d63400_2c97_6004_0001_4c6564676572),
+        write: Uuid::from_u128(0x13d63400_2c97_6004_0002_4c6564676572),
+    },
+    Model {
+        name: "Flex",
+        service: Uuid::from_u128(0x13d63400_2c97_3004_0000_4c6564676572),
+        notify: Uuid::from_u128(0x13d63400_2c97_3004_0001_4c6564676572),
+        write: Uuid::from_u128(0x13d63400_2c97_3004_0002_4c6564676572),
+    },
+];
+
+/// Frame tag for APDU data on the Ledger BLE channel.
+const TAG_APDU: u8 = 0x05;
+/// Frame tag the device answers an MTU query with.
+const TAG_MTU: u8 = 0x08;
+/// Frame size used until the device reports its MTU.
+const DEFAULT_MTU: usize = 20;
+const SCAN_POLL: Duration = Duration::from_millis(250);
+/// How long to look for a remembered device before giving up.
+const RECONNECT_SCAN_TIMEOUT: Duration = Duration::from_secs(10);
+const MTU_TIMEOUT: Duration = Duration::from_secs(5);
+/// Signing waits for the user to review and approve on the device.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(180);
+
+// Ethereum app APDUs.
+const CLA: u8 = 0xe0;
+const INS_GET_ADDRESS: u8 = 0x02;
+const INS_SIGN_TX: u8 = 0x04;
+const INS_SIGN_PERSONAL: u8 = 0x08;
+const P1_FIRST: u8 = 0x00;
+const P1_MORE: u8 = 0x80;
+const MAX_APDU_DATA: usize = 255;
+/// m/44'/60'/0'/0/0, the first Ledger Live account, as over USB.
+const DERIVATION_PATH: [u32; 5] = [44 | HARDENED, 60 | HARDENED, HARDENED, 0, 0];
+const HARDENED: u32 = 0x8000_0000;
+
+/// A Ledger seen during a scan. `id` is the platform peripheral id, stable
+/// across sessions on the same machine, so it is what gets remembered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BleDevice {
+    pub id: String,
+    pub name: String,
+}
+
+/// Looks for Ledgers advertising over Bluetooth for `timeout`.
+pub fn scan(timeout: Duration) -> Result<Vec<BleDevice>> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("failed to create bluetooth runtime")?;
+    rt.block_on(async {
+        let adapter = adapter().await?;
+        let found = discover(&adapter, timeout, None).await?;
+        Ok(found.into_iter().map(|(_, device)| device).collect())
+    })
+}
+
+/// Connects to `device`, found by an earlier scan, and reads the
+/// account from its Ethereum app. The first connection to a device triggers
+/// the operating system's Bluetooth pairing prompt.
+pub fn connect(device: &BleDevice) -> Result<LedgerBleSigner> {
+    let (worker, mut requests) = unbounded_channel::<Exchange>();
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let device_id = device.id.clone();
+    // The transport lives on its own runtime: on Linux the BlueZ connection is
+    // driven by tasks that must outlive the caller's short-lived runtime.
+    std::thread::Builder::new()
+        .name("ledger-ble".to_string())
+        .spawn(move || {
+            let rt = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(anyhow!("failed to create bluetooth runtime: {e}")));
+                    return;
+                }
+            };
+            rt.block_on(async move {
+                let mut transport = match Transport::open(&device_id).await {
+                    Ok(transport) => transport,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(()));
+                while let Some((apdu, reply)) = requests.recv().await {
+                    let _ = reply.send(transport.exchange(&apdu).await);
+                }
+                let _ = transport.peripheral.disconnect().await;
+                tracing::info!(device_id, "ledger bluetooth disconnected");
+            });
+        })
+        .context("failed to start ledger bluetooth worker")?;
+    ready_rx
+        .recv()
+        .map_err(|_| anyhow!("ledger bluetooth worker exited"))??;
+
+    let mut signer = LedgerBleSigner {
+        worker,
+        address: Address::ZERO,
+    };
+    signer.address = signer.get_address()?;
+    tracing::info!(
+        address = format!("0x{:x}", signer.address),
+        device = device.name,
+        "ledger detected over bluetooth"
+    );
+    Ok(signer)
+}
+
+type Exchange = (Vec<u8>, mpsc::Sender<Result<Vec<u8>>>);
+
+/// A Ledger connected over Bluetooth and the account it signs for. APDUs go
+/// to the transport thread, which disconnects when the signer is dropped.
+pub struct LedgerBleSigner {
+    worker: UnboundedSender<Exchange>,
+    address: Address,
+}
+
+impl LedgerBleSigner {
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn sign_message(&self, msg: &[u8]) -> Result<Signature> {
+        let mut payload = derivation_path();
+        payload.extend_from_slice(&(msg.len() as u32).to_be_bytes());
+        payload.extend_from_slice(msg);
+        let response = self.send_chunked(INS_SIGN_PERSONAL, &payload)?;
+        self.signature(&response, eip191_hash_message(msg))
+    }
+
+    pub fn sign_transaction(&self, tx: &dyn SignableTransaction<Signature>) -> Result<Signature> {
+        let encoded = tx.encoded_for_signing();
+        let mut payload = derivation_path();
+        payload.extend_from_slice(&encoded);
+        let response = self.send_chunked(INS_SIGN_TX, &payload)?;
+        self.signature(&response, keccak256(&encoded))
+    }
+
+    fn get_address(&self) -> Result<Address> {
+        let response = self.exchange(INS_GET_ADDRESS, P1_FIRST, &derivation_path())?;
+        parse_address(&response)
+    }
+
+    /// Sends `payload` in as many APDUs as it takes and returns the last answer.
+    fn send_chunked(&self, ins: u8, payload: &[u8]) -> Result<Vec<u8>> {
+        let mut response = Vec::new();
+        for (index, chunk) in payload.chunks(MAX_APDU_DATA).enumerate() {
+            let p1 = if index == 0 { P1_FIRST } else { P1_MORE };
+            response = self.exchange(ins, p1, chunk)?;
+        }
+        Ok(response)
+    }
+
+    fn exchange(&self, ins: u8, p1: u8, data: &[u8]) -> Result<Vec<u8>> {
+        let mut apdu = vec![CLA, ins, p1, 0x00, data.len() as u8];
+        apdu.extend_from_slice(data);
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.worker
+            .send((apdu, reply_tx))
+            .map_err(|_| anyhow!("Ledger disconnected"))?;
+        reply_rx
+            .recv()
+            .map_err(|_| anyhow!("Ledger disconnected"))?
+    }
+
+    /// The app answers `v || r || s`, with `v` possibly truncated for large
+    /// EIP-155 chain ids, so the parity is taken from whichever recovers the
+    /// account.
+    fn signature(&self, response: &[u8], hash: B256) -> Result<Signature> {
+        if response.len() < 65 {
+            bail!("Ledger returned a short signature");
+        }
+        let r = U256::from_be_slice(&response[1..33]);
+        let s = U256::from_be_slice(&response[33..65]);
+        [false, true]
+            .into_iter()
+            .map(|parity| Signature::new(r, s, parity))
+            .find(|sig| sig.recover_address_from_prehash(&hash).ok() == Some(self.address))
+            .ok_or_else(|| anyhow!("Ledger signature does not match account"))
+    }
+}
+
+struct Transport {
+    peripheral: Peripheral,
+    write: Characteristic,
+    notify: Uuid,
+    notifications: Pin<Box<dyn Stream<Item = ValueNotification> + Send>>,
+    mtu: usize,
+}
+
+impl Transport {
+    async fn open(device_id: &str) -> Result<Self> {
+        let adapter = adapter().await?;
+        let peripheral = discover(&adapter, RECONNECT_SCAN_TIMEOUT, Some(device_id))
+            .await?
+            .into_iter()
+            .find(|(_, device)| device.id == device_id)
+            .map(|(peripheral, _)| peripheral)
+            .ok_or_else(|| anyhow!("Ledger not found. Unlock it and make sure Bluetooth is on."))?;
+        if !peripheral.is_connected().await.unwrap_or(false) {
+            peripheral
+                .connect()
+                .await
+                .context("failed to connect to the Ledger over Bluetooth")?;
+        }
+        peripheral
+            .discover_services()
+            .await
+            .context("failed to read Ledger services")?;
+        let characteristics = peripheral.characteristics();
+        let model = MODELS
+            .iter()
+            .find(|model| {
+                characteristics
+                    .iter()
+                    .any(|c| c.service_uuid == model.service)
+            })
+            .ok_or_else(|| anyhow!("device does not expose the Ledger service"))?;
+        let find = |uuid: Uuid| {
+            characteristics
+                .iter()
+                .find(|c| c.uuid == uuid)
+                .cloned()
+                .ok_or_else(|| anyhow!("Ledger {} is missing characteristic {uuid}", model.name))
+        };
+        let notify = find(model.notify)?;
+        let write = find(model.write)?;
+        peripheral
+            .subscribe(&notify)
+            .await
+            .context("failed to subscribe to Ledger notifications")?;
+        let notifications = peripheral.notifications().await?;
+        let mut transport = Self {
+            peripheral,
+            write,
+            notify: notify.uuid,
+            notifications,
+            mtu: DEFAULT_MTU,
+        };
+        match transport.query_mtu().await {
+            Ok(mtu) => transport.mtu = mtu,
+            Err(e) => tracing::warn!(error = %e, "ledger mtu query failed; using default"),
+        }
+        tracing::info!(
+            model = model.name,
+            mtu = transport.mtu,
+            "ledger bluetooth connected"
+        );
+        Ok(transport)
+    }
+
+    async fn query_mtu(&mut self) -> Result<usize> {
+        self.write_frame(&[TAG_MTU, 0, 0, 0, 0]).await?;
+        let frame = self.next_frame(MTU_TIMEOUT).await?;
+        match frame.as_slice() {
+            [TAG_MTU, _, _, _, _, mtu, ..] if usize::from(*mtu) > HEADER_LEN => {
+                Ok(usize::from(*mtu))
+            }
+            _ => bail!("unexpected mtu response"),
+        }
+    }
+
+    async fn exchange(&mut self, apdu: &[u8]) -> Result<Vec<u8>> {
+        for frame in frame_apdu(apdu, self.mtu) {
+            self.write_frame(&frame).await?;
+        }
+        let mut reader = ResponseReader::default();
+        loop {
+            let frame = self.next_frame(RESPONSE_TIMEOUT).await?;
+            if let Some(response) = reader.push(&frame)? {
+                return check_status(response);
+            }
+        }
+    }
+
+    async fn write_frame(&self, frame: &[u8]) -> Result<()> {
+        self.peripheral
+            .write(&self.write, frame, WriteType::WithResponse)
+            .await
+            .context("failed to write to the Ledger")
+    }
+
+    async fn next_frame(&mut self, timeout: Duration) -> Result<Vec<u8>> {
+        loop {
+            let notification = tokio::time::timeout(timeout, self.notifications.next())
+                .await
+                .map_err(|_| anyhow!("timed out waiting for the Ledger"))?
+                .ok_or_else(|| anyhow!("Ledger disconnected"))?;
+            if notification.uuid == self.notify {
+                return Ok(notification.value);
+            }
+        }
+    }
+}
+
+async fn adapter() -> Result<Adapter> {
+    let manager = Manager::new()
+        .await
+        .context("Bluetooth is unavailable on this machine")?;
+    manager
+        .adapters()
+        .await
+        .context("failed to list Bluetooth adapters")?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No Bluetooth adapter found"))
+}
+
+/// Scans until `timeout` passes, or until `wanted` shows up.
+async fn discover(
+    adapter: &Adapter,
+    timeout: Duration,
+    wanted: Option<&str>,
+) -> Result<Vec<(Peripheral, BleDevice)>> {
+    let filter = ScanFilter {
+        services: MODELS.iter().map(|model| model.service).collect(),
+    };
+    adapter
+        .start_scan(filter)
+        .await
+        .context("failed to start Bluetooth scan")?;
+    let deadline = Instant::now() + timeout;
+    let found = loop {
+        let found = ledger_peripherals(adapter).await?;
+        let done = wanted.is_some_and(|id| found.iter().any(|(_, device)| device.id == id));
+        if done || Instant::now() >= deadline {
+            break found;
+        }
+        tokio::time::sleep(SCAN_POLL).await;
+    };
+    if let Err(e) = adapter.stop_scan().await {
+        tracing::debug!(error = %e, "failed to stop bluetooth scan");
+    }
+    Ok(found)
+}
+
+async fn ledger_peripherals(adapter: &Adapter) -> Result<Vec<(Peripheral, BleDevice)>> {
+    let mut found = Vec::new();
+    for peripheral in adapter.peripherals().await? {
+        let Some(properties) = peripheral.properties().await? else {
+            continue;
+        };
+        let model = MODELS
+            .iter()
+            .find(|model| properties.services.contains(&model.service));
+        let Some(model) = model else {
+            continue;
+        };
+        let device = BleDevice {
+            id: peripheral.id().to_string(),
+            name: properties
+                .local_name
+                .unwrap_or_else(|| format!("Ledger {}", model.name)),
+        };
+        found.push((peripheral, device));
+    }
+    Ok(found)
+}
+
+/// Tag, sequence number and, on the first frame, the APDU length.
+const HEADER_LEN: usize = 5;
+
+/// Splits an APDU into frames of at most `mtu` bytes.
+fn frame_apdu(apdu: &[u8], mtu: usize) -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    let mut rest = apdu;
+    let mut seq: u16 = 0;
+    while seq == 0 || !rest.is_empty() {
+        let mut frame = vec![TAG_APDU];
+        frame.extend_from_slice(&seq.to_be_bytes());
+        if seq == 0 {
+            frame.extend_from_slice(&(apdu.len() as u16).to_be_bytes());
+        }
+        let take = rest.len().min(mtu - frame.len());
+        frame.extend_from_slice(&rest[..take]);
+        rest = &rest[take..];
+        frames.push(frame);
+        seq += 1;
+    }
+    frames
+}
+
+/// Reassembles a response from notification frames.
+#[derive(Default)]
+struct ResponseReader {
+    expected: usize,
+    data: Vec<u8>,
+    seq: u16,
+}
+
+impl ResponseReader {
+    fn push(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>> {
+        let [TAG_APDU, hi, lo, body @ ..] = frame else {
+            bail!("unexpected frame from the Ledger");
+        };
+        let seq = u16::from_be_bytes([*hi, *lo]);
+        if seq != self.seq {
+            bail!(
+                "Ledger frame out of order (expected {}, got {seq})",
+                self.seq
+            );
+        }
+        let body = if seq == 0 {
+            let [len_hi, len_lo, body @ ..] = body else {
+                bail!("truncated first frame from the Ledger");
+            };
+            self.expected = usize::from(u16::from_be_bytes([*len_hi, *len_lo]));
+            body
+        } else {
+            body
+        };
+        self.data.extend_from_slice(body);
+        self.seq += 1;
+        if self.data.len() < self.expected {
+            return Ok(None);
+        }
+        self.data.truncate(self.expected);
+        Ok(Some(std::mem::take(&mut self.data)))
+    }
+}
+
+/// Strips the status word, turning the common failures into something the
+/// user can act on.
+fn check_status(mut response: Vec<u8>) -> Result<Vec<u8>> {
+    if response.len() < 2 {
+        bail!("Ledger returned an empty response");
+    }
+    let sw = u16::from_be_bytes([response[response.len() - 2], response[response.len() - 1]]);
+    response.truncate(response.len() - 2);
+    match sw {
+        0x9000 => Ok(response),
+        0x6985 => bail!("Request rejected on the Ledger"),
+        0x5515 => bail!("Unlock the Ledger and try again"),
+        0x6d00 | 0x6e00 | 0x6511 => bail!("Open the Ethereum app on the Ledger"),
+        0x6a80 => bail!("Enable blind signing in the Ethereum app settings"),
+        _ => bail!("Ledger error 0x{sw:04x}"),
+    }
+}
+
+fn derivation_path() -> Vec<u8> {
+    let mut out = vec![DERIVATION_PATH.len() as u8];
+    for index in DERIVATION_PATH {
+        out.extend_from_slice(&index.to_be_bytes());
+    }
+    out
+}
+
+/// GET ADDRESS answers `len || pubkey || len || ascii hex address`.
+fn parse_address(response: &[u8]) -> Result<Address> {
+    let pubkey_len = usize::from(*response.first().context("empty address response")?);
+    let address_len = usize::from(
+        *response
+            .get(1 + pubkey_len)
+            .context("truncated address response")?,
+    );
+    let start = 2 + pubkey_len;
+    let ascii = response
+        .get(start..start + address_len)
+        .context("truncated address response")?;
+    let hex = std::str::from_utf8(ascii).context("invalid address response")?;
+    format!("0x{hex}")
+        .parse()
+        .context("Ledger returned an invalid address")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResponseReader, check_status, frame_apdu, parse_address};
+
+    fn sample_apdu() -> Vec<u8> {
+        (0..300u16).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn apdus_are_split_into_sequenced_frames() {
+        let frames = frame_apdu(&sample_apdu(), 20);
+        assert_eq!(&frames[0][..5], &[0x05, 0, 0, 0x01, 0x2c]);
+        assert_eq!(&frames[1][..3], &[0x05, 0, 1]);
+        assert!(frames.iter().all(|frame| frame.len() <= 20));
+    }
+
+    #[test]
+    fn frames_are_reassembled_into_the_response() {
+        let apdu = sample_apdu();
+        let frames = frame_apdu(&apdu, 20);
+        let mut reader = ResponseReader::default();
+        let (last, rest) = frames.split_last().unwrap();
+        for frame in rest {
+            assert_eq!(reader.push(frame).unwrap(), None);
+        }
+        assert_eq!(reader.push(last).unwrap(), Some(apdu));
+    }
+
+    #[test]
+    fn dropped_frames_are_not_stitched_together() {
+        // A dropped frame must not be stitched into a bogus response.
+        let frames = frame_apdu(&sample_apdu(), 20);
+        let mut reader = ResponseReader::default();
+        reader.push(&frames[0]).unwrap();
+        assert!(reader.push(&frames[2]).is_err());
+    }
+
+    #[test]
+    fn parses_addresses_from_successful_responses() {
+        let mut response = vec![65];
+        response.extend_from_slice(&[4; 65]);
+        response.push(40);
+        response.extend_from_slice(b"5fbdb2315678afecb367f032d93f642f64180aa3");
+        response.extend_from_slice(&[0x90, 0x00]);
+        let address = parse_address(&check_status(response).unwrap()).unwrap();
+        assert_eq!(
+            format!("{address:#x}"),
+            "0x5fbdb2315678afecb367f032d93f642f64180aa3"
+        );
+    }
+
+    #[test]
+    fn error_status_words_are_reported() {
+        assert!(check_status(vec![0x69, 0x85]).is_err());
+    }
+}
@@ -0,0 +1,585 @@
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use flate2::read::GzDecoder;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zip::ZipArchive;
+
+use crate::bundle::{normalize_manifest_path, verify_manifest, walk_files};
+use crate::bundle_export::EXPORT_RECORD;
+use crate::config::ResolvedConfig;
+use crate::state::{AppState, DappIdentity, TabAction, UserEvent};
+
+/// Provider event telling the Studio tab a dropped project is waiting for
+/// `vibefi_takeDroppedProject`.
+pub const STUDIO_PROJECT_EVENT: &str = "vibefiProjectDropped";
+/// Largest project exchanged with Studio; its files cross IPC as base64.
+pub(crate) const MAX_STUDIO_PROJECT_BYTES: u64 = 25 * 1024 * 1024;
+/// Refuse archives that unpack to more than this.
+const MAX_UNPACKED_BYTES: u64 = 512 * 1024 * 1024;
+const MAX_DOWNLOAD_BYTES: u64 = 256 * 1024 * 1024;
+
+/// A bundle opened from disk instead of the registry: a bundle source
+/// directory, an exported bundle, or a zip of either.
+#[derive(Debug, Clone)]
+pub struct LocalBundle {
+    pub name: String,
+    /// Directory holding `manifest.json` and the verified bundle files.
+    pub source_dir: PathBuf,
+    /// Build output, when the bundle came with one.
+    pub dist_dir: Option<PathBuf>,
+}
+
+/// A bundle without build output, handed to Studio to edit and build.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StudioProject {
+    pub name: String,
+    pub files: Vec<ProjectFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectFile {
+    pub path: String,
+    /// Base64-encoded file contents.
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveKind {
+    fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else {
+            None
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::TarGz => "tar.gz",
+        }
+    }
+}
+
+/// Whether `path` could be a bundle: a directory or a zip or tar.gz archive.
+pub fn is_bundle_path(path: &Path) -> bool {
+    path.is_dir() || ArchiveKind::of(path).is_some()
+}
+
+/// Resolves a `--bundle` / `vibefi_importBundle` source to a local path.
+/// Local directories and archives are used in place; `ipfs://<cid>` is
+/// downloaded into the bundle cache like a registry launch; `https://` URLs
+/// must serve a zip or tar.gz archive.
+pub fn fetch(source: &str, resolved: Option<&ResolvedConfig>) -> Result<PathBuf> {
+    if let Some(rest) = source.strip_prefix("ipfs://") {
+        let root_cid = rest.trim_end_matches('/');
+        if root_cid.is_empty() || root_cid.contains(['/', '\\', '.', '?', '#']) {
+            bail!("expected ipfs://<root CID>, got {source}");
+        }
+        let resolved = resolved.ok_or_else(|| anyhow!("ipfs:// bundles need a network config"))?;
+        return crate::registry::fetch_bundle(resolved, root_cid);
+    }
+    if source.starts_with("https://") || source.starts_with("http://") {
+        let url = Url::parse(source).with_context(|| format!("invalid bundle URL {source}"))?;
+        let loopback = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+        if url.scheme() != "https" && !loopback {
+            bail!("bundle URLs must use https (plain http only for localhost)");
+        }
+        let client = resolved
+            .map(|resolved| resolved.http_client.clone())
+            .unwrap_or_default();
+        return download_archive(&client, url);
+    }
+    Ok(PathBuf::from(source))
+}
+
+/// Unpacks `path` if it is an archive, then verifies the bundle inside
+/// against its manifest and scans it for secrets. An exported bundle's
+/// `dist/` is moved into place so it is served like a cached build.
+pub fn open(path: &Path) -> Result<LocalBundle> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("{} does not exist", path.display()))?;
+    let unpacked = match ArchiveKind::of(&path) {
+        _ if path.is_dir() => path.clone(),
+        Some(ArchiveKind::Zip) => unpack_zip(&path)?,
+        Some(ArchiveKind::TarGz) => unpack_tar_gz(&path)?,
+        None => bail!(
+            "{} is not a bundle directory or zip/tar.gz archive",
+            path.display()
+        ),
+    };
+    let root = locate_root(&unpacked)?;
+    let exported = root.join("source").join("manifest.json").exists();
+    let source_dir = if exported {
+        root.join("source")
+    } else {
+        root.clone()
+    };
+    verify_manifest(&source_dir)?;
+    crate::bundle::ensure_client_supported(&source_dir)?;
+    crate::secret_scan::ensure_no_secrets(&source_dir)?;
+
+    let built = source_dir.join(".vibefi").join("dist");
+    let export_dist = root.join("dist");
+    if exported && export_dist.join("index.html").exists() {
+        copy_dir(&export_dist, &built)?;
+        crate::bundle::record_dist_hashes(&built)?;
+    }
+    let dist_dir = Some(built).filter(|dir| crate::bundle::has_verified_build(dir));
+    let name = bundle_name(&root, &source_dir, &path);
+    tracing::info!(
+        source = %path.display(),
+        source_dir = %source_dir.display(),
+        built = dist_dir.is_some(),
+        "opened local bundle"
+    );
+    Ok(LocalBundle {
+        name,
+        source_dir,
+        dist_dir,
+    })
+}
+
+/// Builds `bundle` the way `--bundle` does and returns its dist dir. Source
+/// maps are emitted under the dev profile.
+pub fn build(state: &AppState, bundle: &LocalBundle) -> Result<PathBuf> {
+    let dist_dir = bundle.source_dir.join(".vibefi").join("dist");
+    let source_maps = state.resolved.as_deref().is_some_and(|r| r.dev_profile);
+    crate::bundle::build_bundle(&bundle.source_dir, &dist_dir, source_maps)?;
+    Ok(dist_dir)
+}
+
+/// Fetches, verifies and if needed builds `source`, then opens it in a new
+/// dapp tab. Blocks; run it off the event loop.
+pub fn import(state: &AppState, source: &str) -> Result<()> {
+    let path = fetch(source, state.resolved.as_deref())?;
+    let bundle = open(&path)?;
+    let dist_dir = match bundle.dist_dir.clone() {
+        Some(dist_dir) => dist_dir,
+        None => build(state, &bundle)?,
+    };
+    open_tab(state, bundle, dist_dir);
+    Ok(())
+}
+
+pub fn open_tab(state: &AppState, bundle: LocalBundle, dist_dir: PathBuf) {
+    let identity = DappIdentity {
+        name: bundle.name,
+        local_project: bundle.dist_dir.is_none(),
+        ..DappIdentity::default()
+    };
+    let _ = state
+        .proxy
+        .send_event(UserEvent::TabAction(TabAction::OpenApp {
+            identity,
+            dist_dir,
+        }));
+}
+
+/// Reads the files listed in the manifest, plus the manifest itself, for
+/// Studio.
+pub fn studio_project(bundle: &LocalBundle) -> Result<StudioProject> {
+    let mut paths = vec!["manifest.json".to_string()];
+    for entry in crate::manifest::load(&bundle.source_dir)?.files {
+        paths.push(normalize_manifest_path(&entry.path));
+    }
+    let root = bundle
+        .source_dir
+        .canonicalize()
+        .with_context(|| format!("{} does not exist", bundle.source_dir.display()))?;
+    let mut total = 0u64;
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        let bytes = fs::read(resolve_in_project(&root, &path)?)
+            .with_context(|| format!("failed to read {path}"))?;
+        total += bytes.len() as u64;
+        if total > MAX_STUDIO_PROJECT_BYTES {
+            bail!(
+                "project is larger than {} MiB",
+                MAX_STUDIO_PROJECT_BYTES / 1024 / 1024
+            );
+        }
+        files.push(ProjectFile {
+            path,
+            content: base64::engine::general_purpose::STANDARD.encode(bytes),
+        });
+    }
+    Ok(StudioProject {
+        name: bundle.name.clone(),
+        files,
+    })
+}
+
+/// Resolves a manifest path under the canonical project `root`. Parent
+/// components, absolute paths and symlinks leading out of the project are
+/// refused, so a crafted manifest cannot hand Studio files from elsewhere.
+fn resolve_in_project(root: &Path, rel: &str) -> Result<PathBuf> {
+    let relative = Path::new(rel);
+    if !relative
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)))
+    {
+        bail!("{rel} is not a path inside the project");
+    }
+    let resolved = root
+        .join(relative)
+        .canonicalize()
+        .with_context(|| format!("failed to read {rel}"))?;
+    if !resolved.starts_with(root) {
+        tracing::warn!(
+            target: "vibefi::audit",
+            path = rel,
+            resolved = %resolved.display(),
+            "project file resolves outside the project"
+        );
+        bail!("{rel} leads outside the project");
+    }
+    Ok(resolved)
+}
+
+/// The bundle is either at the top of `dir` or, as when a folder is zipped,
+/// in its only subdirectory.
+fn locate_root(dir: &Path) -> Result<PathBuf> {
+    let is_root = |dir: &Path| {
+        dir.join("manifest.json").exists() || dir.join("source").join("manifest.json").exists()
+    };
+    if is_root(dir) {
+        return Ok(dir.to_path_buf());
+    }
+    let subdirs: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && name != "__MACOSX"
+        })
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    match subdirs.as_slice() {
+        [only] if is_root(only) => Ok(only.clone()),
+        _ => Err(anyhow!("manifest.json missing in bundle")),
+    }
+}
+
+/// Name from the export record or manifest, falling back to the file name.
+fn bundle_name(root: &Path, source_dir: &Path, original: &Path) -> String {
+    let name_in = |path: PathBuf| {
+        let raw = fs::read(path).ok()?;
+        let value: serde_json::Value = serde_json::from_slice(&raw).ok()?;
+        value
+            .get("name")
+            .and_then(|name| name.as_str())
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+    };
+    name_in(root.join(EXPORT_RECORD))
+        .or_else(|| name_in(source_dir.join("manifest.json")))
+        .or_else(|| archive_stem(original))
+        .unwrap_or_else(|| "Local bundle".to_string())
+}
+
+/// File name without `.zip` / `.tar.gz` / `.tgz`.
+fn archive_stem(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let lower = name.to_ascii_lowercase();
+    let cut = [".tar.gz", ".tgz", ".zip"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map_or(name.len(), |ext| name.len() - ext.len());
+    Some(name[..cut].to_string()).filter(|stem| !stem.is_empty())
+}
+
+/// A fresh directory under the temp dir for unpacking or downloading.
+pub(crate) fn workspace(label: &str) -> Result<PathBuf> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let dest = std::env::temp_dir()
+        .join("vibefi-imports")
+        .join(format!("{label}-{stamp}"));
+    fs::create_dir_all(&dest).with_context(|| format!("failed to create {}", dest.display()))?;
+    Ok(dest)
+}
+
+/// Downloads an archive, naming it after the URL and, when the URL does not
+/// say, after what the file turns out to be.
+fn download_archive(client: &reqwest::blocking::Client, url: Url) -> Result<PathBuf> {
+    let file_name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("bundle")
+        .to_string();
+    let label = archive_stem(Path::new(&file_name)).unwrap_or_else(|| "bundle".to_string());
+    tracing::info!(%url, "downloading bundle archive");
+    let response = client
+        .get(url.clone())
+        .send()
+        .with_context(|| format!("failed to download {url}"))?;
+    if !response.status().is_success() {
+        bail!("{url} answered {}", response.status());
+    }
+    let dir = workspace(&label)?;
+    let download = dir.join("bundle.download");
+    crate::download::stream_to_file(response, &download, MAX_DOWNLOAD_BYTES, None)?;
+
+    let mut magic = [0u8; 4];
+    let read = fs::File::open(&download)?.read(&mut magic)?;
+    let kind = match &magic[..read] {
+        [b'P', b'K', 3, 4] => ArchiveKind::Zip,
+        [0x1f, 0x8b, ..] => ArchiveKind::TarGz,
+        _ => bail!("{url} did not serve a zip or tar.gz archive"),
+    };
+    let archive = dir.join(format!("{label}.{}", kind.extension()));
+    fs::rename(&download, &archive)?;
+    Ok(archive)
+}
+
+fn check_unpacked_size(unpacked: u64) -> Result<()> {
+    if unpacked > MAX_UNPACKED_BYTES {
+        bail!(
+            "archive unpacks to more than {} MiB",
+            MAX_UNPACKED_BYTES / 1024 / 1024
+        );
+    }
+    Ok(())
+}
+
+/// Unpacks a zip archive into a fresh workspace.
+fn unpack_zip(archive: &Path) -> Result<PathBuf> {
+    let file =
+        fs::File::open(archive).with_context(|| format!("failed to open {}", archive.display()))?;
+    let mut zip = ZipArchive::new(file).context("not a readable zip archive")?;
+    let dest = workspace(&archive_stem(archive).unwrap_or_else(|| "bundle".to_string()))?;
+
+    let mut unpacked = 0u64;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+        let Some(relative) = entry.enclosed_name() else {
+            bail!("archive entry escapes the bundle: {}", entry.name());
+        };
+        let target = dest.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+        unpacked += entry.size();
+        check_unpacked_size(unpacked)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = fs::File::create(&target)
+            .with_context(|| format!("failed to create {}", target.display()))?;
+        io::copy(&mut entry, &mut out)
+            .with_context(|| format!("failed to unpack {}", entry.name()))?;
+    }
+    tracing::debug!(archive = %archive.display(), dest = %dest.display(), "unpacked bundle archive");
+    Ok(dest)
+}
+
+/// Unpacks a gzipped tarball into a fresh workspace. Only regular files and
+/// directories are taken; links could point outside the workspace.
+fn unpack_tar_gz(archive: &Path) -> Result<PathBuf> {
+    let file =
+        fs::File::open(archive).with_context(|| format!("failed to open {}", archive.display()))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let dest = workspace(&archive_stem(archive).unwrap_or_else(|| "bundle".to_string()))?;
+
+    let mut unpacked = 0u64;
+    for entry in tar.entries().context("not a readable tar.gz archive")? {
+        let mut entry = entry.context("not a readable tar.gz archive")?;
+        let name = entry.path()?.display().to_string();
+        let kind = entry.header().entry_type();
+        if !kind.is_file() && !kind.is_dir() {
+            bail!("archive entry is not a regular file: {name}");
+        }
+        unpacked += entry.size();
+        check_unpacked_size(unpacked)?;
+        if !entry
+            .unpack_in(&dest)
+            .with_context(|| format!("failed to unpack {name}"))?
+        {
+            bail!("archive entry escapes the bundle: {name}");
+        }
+    }
+    tracing::debug!(archive = %archive.display(), dest = %dest.display(), "unpacked bundle archive");
+    Ok(dest)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        fs::remove_dir_all(to).with_context(|| format!("failed to replace {}", to.display()))?;
+    }
+    for path in walk_files(from)? {
+        let relative = path.strip_prefix(from).context("file outside dist dir")?;
+        let target = to.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&path, &target).with_context(|| format!("failed to copy {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fetch, open};
+    use crate::bundle::sha256_hex;
+    use crate::bundle_export::{ExportParts, export_bundle};
+    use crate::test_harness::{MockGateway, MockRpc, test_config};
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A cached bundle `bafylocal` under a fresh temp dir, with one source
+    /// file and build output. Returns the temp dir and the bundle dir.
+    fn cached_bundle(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("vibefi-local-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let bundle = dir.join("cache").join("bafylocal");
+        fs::create_dir_all(bundle.join(".vibefi/dist")).unwrap();
+        fs::write(bundle.join("app.js"), b"app").unwrap();
+        let manifest = serde_json::json!({
+            "name": "Swap",
+            "files": [{ "path": "app.js", "bytes": 3, "sha256": sha256_hex(b"app") }]
+        });
+        fs::write(bundle.join("manifest.json"), manifest.to_string()).unwrap();
+        fs::write(bundle.join(".vibefi/dist/index.html"), b"<html>").unwrap();
+        (dir, bundle)
+    }
+
+    /// A project directory packed as `counter.tar.gz` under a fresh temp dir.
+    fn project_tarball(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("vibefi-local-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let project = dir.join("project");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("app.js"), b"app").unwrap();
+        let manifest = serde_json::json!({
+            "files": [{ "path": "app.js", "bytes": 3, "sha256": sha256_hex(b"app") }]
+        });
+        fs::write(project.join("manifest.json"), manifest.to_string()).unwrap();
+        let archive = dir.join("counter.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            fs::File::create(&archive).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut tar = tar::Builder::new(encoder);
+        tar.append_dir_all("counter", &project).unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+        (dir, archive)
+    }
+
+    #[test]
+    fn opens_exported_zips_with_their_dist() {
+        let (dir, _) = cached_bundle("zip");
+        let archive = dir.join("swap.zip");
+        export_bundle(
+            &dir.join("cache"),
+            "bafylocal",
+            None,
+            ExportParts::All,
+            &archive,
+        )
+        .unwrap();
+        let opened = open(&archive).unwrap();
+        assert_eq!(opened.name, "Swap");
+        let dist = opened.dist_dir.expect("exported dist");
+        assert_eq!(fs::read(dist.join("index.html")).unwrap(), b"<html>");
+        let _ = fs::remove_dir_all(&dir);
+        let _ = fs::remove_dir_all(opened.source_dir.parent().unwrap());
+    }
+
+    #[test]
+    fn unbuilt_bundles_go_to_studio() {
+        let (dir, bundle) = cached_bundle("unbuilt");
+        // A bundle directory that was never built goes to Studio.
+        fs::remove_dir_all(bundle.join(".vibefi")).unwrap();
+        let source = open(&bundle).unwrap();
+        assert!(source.dist_dir.is_none());
+        let project = super::studio_project(&source).unwrap();
+        assert_eq!(project.files.len(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn project_paths_stay_inside_the_project() {
+        let (dir, bundle) = cached_bundle("paths");
+        let root = bundle.canonicalize().unwrap();
+        assert!(super::resolve_in_project(&root, "app.js").is_ok());
+        assert!(super::resolve_in_project(&root, "../swap.zip").is_err());
+        assert!(super::resolve_in_project(&root, "/etc/hostname").is_err());
+        #[cfg(unix)]
+        {
+            let outside = dir.join("outside.txt");
+            fs::write(&outside, b"outside").unwrap();
+            std::os::unix::fs::symlink(&outside, bundle.join("escape.txt")).unwrap();
+            assert!(super::resolve_in_project(&root, "escape.txt").is_err());
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn tampered_sources_are_refused() {
+        let (dir, bundle) = cached_bundle("tampered");
+        fs::write(bundle.join("app.js"), b"APP!").unwrap();
+        assert!(open(&bundle).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fetches_tarballs() {
+        let (dir, archive) = project_tarball("tarball");
+        let opened = open(&fetch(archive.to_str().unwrap(), None).unwrap()).unwrap();
+        assert_eq!(opened.name, "counter");
+        assert!(opened.source_dir.ends_with("counter"));
+        assert!(opened.dist_dir.is_none());
+        let _ = fs::remove_dir_all(&dir);
+        let _ = fs::remove_dir_all(opened.source_dir.parent().unwrap());
+    }
+
+    #[test]
+    fn fetches_ipfs_sources_into_the_bundle_cache() {
+        let dir = std::env::temp_dir().join(format!("vibefi-local-ipfs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let rpc = MockRpc::start();
+        let gateway = MockGateway::start();
+        let config = test_config(&rpc, &gateway, &dir.join("cache"));
+        let root_cid = gateway.add_bundle(&[("index.html", b"<html></html>".as_slice())]);
+        let fetched = fetch(&format!("ipfs://{root_cid}"), Some(&config)).unwrap();
+        assert_eq!(fetched, dir.join("cache").join(&root_cid));
+        assert_eq!(open(&fetched).unwrap().name, "Fixture");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn refuses_ipfs_paths_and_plain_http() {
+        let dir = std::env::temp_dir().join(format!("vibefi-local-refused-{}", std::process::id()));
+        let rpc = MockRpc::start();
+        let gateway = MockGateway::start();
+        let config = test_config(&rpc, &gateway, &dir.join("cache"));
+        assert!(fetch("ipfs://bafy/../etc", Some(&config)).is_err());
+        assert!(fetch("http://example.com/counter.zip", Some(&config)).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
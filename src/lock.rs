@@ -0,0 +1,375 @@
+//! Client lock screen. With a passphrase set the client starts locked and
+//! locks again after the configured idle time or on request. While locked,
+//! signing requests fail with the EIP-1193 "Unauthorized" code and the local
+//! key is not held in usable form: it is sealed with ChaCha20-Poly1305 under a
+//! key derived from the passphrase with PBKDF2-HMAC-SHA256, and the derived
+//! key itself is dropped until the next unlock.
+
+use alloy_primitives::B256;
+use alloy_signer_local::PrivateKeySigner;
+use anyhow::{Context, Result, anyhow, bail};
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::{hmac, pbkdf2};
+use serde_json::json;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+use crate::prompt::PromptRequest;
+use crate::settings::{LockSettings, load_settings, save_settings};
+use crate::state::AppState;
+
+/// Error message for signing requests refused while locked. Responses
+/// carrying it use the EIP-1193 "Unauthorized" code.
+pub const LOCKED_ERROR: &str = "Unauthorized: VibeFi is locked";
+/// EIP-1193 provider error code for an unauthorized method or account.
+pub const UNAUTHORIZED_CODE: i64 = 4100;
+/// Prompt kind of the unlock screen. Its answer carries the passphrase.
+pub const UNLOCK_PROMPT: &str = "unlock";
+
+const MIN_PASSPHRASE_LEN: usize = 8;
+/// PBKDF2 rounds for the passphrase, as OWASP recommends for HMAC-SHA256.
+const KDF_ROUNDS: u32 = 600_000;
+const AUTO_LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const VERIFIER_LABEL: &[u8] = b"vibefi-lock-verifier";
+
+/// Whether the client is locked, and the key material that comes and goes
+/// with it.
+#[derive(Clone, Default)]
+pub struct ClientLock {
+    inner: Arc<Mutex<LockInner>>,
+}
+
+#[derive(Default)]
+struct LockInner {
+    locked: bool,
+    /// Passphrase-derived key, held only while unlocked.
+    kek: Option<Zeroizing<[u8; 32]>>,
+    /// Local signing keys sealed under `kek` while locked, in order. Each is
+    /// its nonce followed by the ciphertext and tag.
+    sealed: Vec<Vec<u8>>,
+    last_activity: Option<Instant>,
+}
+
+impl ClientLock {
+    pub fn is_locked(&self) -> bool {
+        self.inner().locked
+    }
+
+    /// Fails with [`LOCKED_ERROR`] while locked.
+    pub fn ensure_unlocked(&self) -> Result<()> {
+        if self.is_locked() {
+            bail!(LOCKED_ERROR);
+        }
+        Ok(())
+    }
+
+    /// Records user activity, restarting the auto-lock countdown.
+    pub fn touch(&self) {
+        self.inner().last_activity = Some(Instant::now());
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.inner()
+            .last_activity
+            .map(|at| at.elapsed())
+            .unwrap_or_default()
+    }
+
+    fn inner(&self) -> std::sync::MutexGuard<'_, LockInner> {
+        self.inner.lock().expect("client lock")
+    }
+}
+
+/// Locks the client at startup when a passphrase is set, and starts the
+/// auto-lock timer.
+pub fn start(state: &AppState) {
+    state.lock.touch();
+    if passphrase_set(state) {
+        lock(state);
+    }
+    let state = state.clone();
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(AUTO_LOCK_CHECK_INTERVAL);
+            if state.lock.is_locked() {
+                continue;
+            }
+            let Some(settings) = lock_settings(&state) else {
+                continue;
+            };
+            let Some(minutes) = settings.auto_lock_minutes.filter(|_| is_enabled(&settings)) else {
+                continue;
+            };
+            if state.lock.idle_for() >= Duration::from_secs(minutes * 60) {
+                tracing::info!(minutes, "auto-locking after inactivity");
+                lock(&state);
+            }
+        }
+    });
+}
+
+/// Locks the client and shows the unlock screen. Does nothing without a
+/// passphrase or when already locked.
+pub fn lock(state: &AppState) {
+    if !passphrase_set(state) {
+        return;
+    }
+    {
+        let mut inner = state.lock.inner();
+        if inner.locked {
+            return;
+        }
+        let signers = std::mem::take(&mut *state.signer.lock().expect("signer"));
+        // The signing keys zeroize themselves once the last handle is dropped.
+        let sealed = match inner.kek.as_ref() {
+            Some(kek) => signers
+                .iter()
+                .enumerate()
+                .map(|(index, signer)| seal(kek, index, &Zeroizing::new(signer.to_bytes().0)))
+                .collect(),
+            None => Ok(Vec::new()),
+        };
+        inner.sealed = sealed.unwrap_or_else(|err| {
+            tracing::error!(
+                error = %err,
+                "failed to seal the local signer; reconnect it after unlocking"
+            );
+            Vec::new()
+        });
+        inner.kek = None;
+        inner.locked = true;
+    }
+    tracing::info!(target: "vibefi::audit", "client locked");
+    let state = state.clone();
+    std::thread::spawn(move || {
+        while state.lock.is_locked() {
+            let request = PromptRequest::new(
+                UNLOCK_PROMPT,
+                "VibeFi is locked",
+                "Enter your passphrase to unlock.",
+            );
+            // Answering only returns once the passphrase was accepted; a
+            // prompt that could not be shown is retried.
+            if !crate::prompt::ask(&state, request) {
+                std::thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+}
+
+/// Checks `passphrase`, restores the local signer and unlocks the client.
+pub fn unlock(state: &AppState, passphrase: &str) -> Result<()> {
+    let settings = lock_settings(state).ok_or_else(|| anyhow!("no lock passphrase is set"))?;
+    let kek = check_passphrase(&settings, passphrase)?;
+    let mut inner = state.lock.inner();
+    if !inner.sealed.is_empty() {
+        let signers = inner
+            .sealed
+            .iter()
+            .enumerate()
+            .map(|(index, sealed)| {
+                let key = unseal(&kek, index, sealed)?;
+                PrivateKeySigner::from_bytes(&B256::from(*key))
+                    .map(Arc::new)
+                    .context("failed to restore the local signer")
+            })
+            .collect::<Result<Vec<_>>>()?;
+        inner.sealed.clear();
+        *state.signer.lock().expect("signer") = signers;
+    }
+    inner.kek = Some(kek);
+    inner.locked = false;
+    inner.last_activity = Some(Instant::now());
+    drop(inner);
+    tracing::info!(target: "vibefi::audit", "client unlocked");
+    Ok(())
+}
+
+/// Sets, changes (`new` given) or removes (`new` is `None`) the passphrase.
+/// Changing or removing one requires the current passphrase.
+pub fn set_passphrase(state: &AppState, current: Option<&str>, new: Option<&str>) -> Result<()> {
+    let config_path = config_path(state)?;
+    let mut settings = load_settings(config_path);
+    if is_enabled(&settings.lock) {
+        state.lock.ensure_unlocked()?;
+        let current = current.ok_or_else(|| anyhow!("Enter the current passphrase"))?;
+        check_passphrase(&settings.lock, current)?;
+    }
+    let kek = match new {
+        Some(passphrase) => {
+            if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+                bail!("Passphrase must be at least {MIN_PASSPHRASE_LEN} characters");
+            }
+            let mut salt = [0u8; 16];
+            getrandom::fill(&mut salt).map_err(|err| anyhow!("no system randomness: {err}"))?;
+            let kek = derive_key(passphrase.as_bytes(), &salt, KDF_ROUNDS);
+            settings.lock.salt = Some(hex::encode(salt));
+            settings.lock.verifier =
+                Some(hex::encode(hmac::sign(&verifier_key(&kek), VERIFIER_LABEL)));
+            Some(kek)
+        }
+        None => {
+            settings.lock.salt = None;
+            settings.lock.verifier = None;
+            None
+        }
+    };
+    save_settings(config_path, &settings)?;
+    state.lock.inner().kek = kek;
+    tracing::info!(target: "vibefi::audit", enabled = new.is_some(), "lock passphrase updated");
+    Ok(())
+}
+
+/// Lock state for the settings page.
+pub fn status(state: &AppState) -> serde_json::Value {
+    let settings = lock_settings(state).unwrap_or_default();
+    json!({
+        "enabled": is_enabled(&settings),
+        "locked": state.lock.is_locked(),
+        "autoLockMinutes": settings.auto_lock_minutes,
+    })
+}
+
+pub fn passphrase_set(state: &AppState) -> bool {
+    lock_settings(state).is_some_and(|settings| is_enabled(&settings))
+}
+
+fn is_enabled(settings: &LockSettings) -> bool {
+    settings.salt.is_some() && settings.verifier.is_some()
+}
+
+fn lock_settings(state: &AppState) -> Option<LockSettings> {
+    config_path(state).ok().map(|path| load_settings(path).lock)
+}
+
+fn config_path(state: &AppState) -> Result<&std::path::Path> {
+    state
+        .resolved
+        .as_ref()
+        .and_then(|resolved| resolved.config_path.as_deref())
+        .ok_or_else(|| anyhow!("no config file to store the lock settings in"))
+}
+
+/// Derives the key for `passphrase` and checks it against the stored verifier.
+fn check_passphrase(settings: &LockSettings, passphrase: &str) -> Result<Zeroizing<[u8; 32]>> {
+    let (Some(salt), Some(verifier)) = (&settings.salt, &settings.verifier) else {
+        bail!("no lock passphrase is set");
+    };
+    let salt = hex::decode(salt).context("stored lock salt is not hex")?;
+    let verifier = hex::decode(verifier).context("stored lock verifier is not hex")?;
+    let kek = derive_key(passphrase.as_bytes(), &salt, KDF_ROUNDS);
+    hmac::verify(&verifier_key(&kek), VERIFIER_LABEL, &verifier)
+        .map_err(|_| anyhow!("Wrong passphrase"))?;
+    Ok(kek)
+}
+
+fn derive_key(passphrase: &[u8], salt: &[u8], rounds: u32) -> Zeroizing<[u8; 32]> {
+    let rounds = NonZeroU32::new(rounds).expect("lock KDF rounds are nonzero");
+    let mut kek = Zeroizing::new([0u8; 32]);
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        rounds,
+        salt,
+        passphrase,
+        &mut *kek,
+    );
+    kek
+}
+
+fn verifier_key(kek: &[u8; 32]) -> hmac::Key {
+    hmac::Key::new(hmac::HMAC_SHA256, kek)
+}
+
+fn cipher(kek: &[u8; 32]) -> LessSafeKey {
+    LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, kek).expect("32-byte key"))
+}
+
+/// Seals a signing key under a fresh nonce. Its position is authenticated,
+/// so sealed keys cannot be swapped around.
+fn seal(kek: &[u8; 32], index: usize, key: &[u8; 32]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::fill(&mut nonce).map_err(|err| anyhow!("no system randomness: {err}"))?;
+    let mut sealed = key.to_vec();
+    cipher(kek)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from((index as u64).to_be_bytes()),
+            &mut sealed,
+        )
+        .map_err(|_| anyhow!("failed to seal the local signer"))?;
+    Ok([&nonce[..], &sealed].concat())
+}
+
+fn unseal(kek: &[u8; 32], index: usize, sealed: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+    if sealed.len() < NONCE_LEN {
+        bail!("sealed local signer is truncated");
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| anyhow!("sealed local signer has a bad nonce"))?;
+    let mut opened = Zeroizing::new(ciphertext.to_vec());
+    let plain = cipher(kek)
+        .open_in_place(
+            nonce,
+            Aad::from((index as u64).to_be_bytes()),
+            opened.as_mut_slice(),
+        )
+        .map_err(|_| anyhow!("failed to unseal the local signer"))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    if plain.len() != key.len() {
+        bail!("sealed local signer has the wrong length");
+    }
+    key.copy_from_slice(plain);
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{derive_key, seal, unseal};
+
+    #[test]
+    fn derives_pbkdf2_keys() {
+        // Published PBKDF2-HMAC-SHA256 vectors.
+        assert_eq!(
+            hex::encode(*derive_key(b"password", b"salt", 1)),
+            "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"
+        );
+        assert_eq!(
+            hex::encode(*derive_key(b"password", b"salt", 2)),
+            "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43"
+        );
+    }
+
+    const KEY: [u8; 32] = [0x42; 32];
+
+    #[test]
+    fn sealed_keys_round_trip() {
+        let kek = derive_key(b"correct horse", b"0123456789abcdef", 10);
+        let sealed = seal(&kek, 0, &KEY).unwrap();
+        assert!(!sealed.windows(KEY.len()).any(|window| window == KEY));
+        assert_eq!(*unseal(&kek, 0, &sealed).unwrap(), KEY);
+    }
+
+    #[test]
+    fn sealing_twice_uses_a_fresh_nonce() {
+        let kek = derive_key(b"correct horse", b"0123456789abcdef", 10);
+        assert_ne!(seal(&kek, 0, &KEY).unwrap(), seal(&kek, 0, &KEY).unwrap());
+    }
+
+    #[test]
+    fn the_sealed_position_is_authenticated() {
+        let kek = derive_key(b"correct horse", b"0123456789abcdef", 10);
+        let sealed = seal(&kek, 0, &KEY).unwrap();
+        assert!(unseal(&kek, 1, &sealed).is_err());
+    }
+
+    #[test]
+    fn a_wrong_password_does_not_unseal() {
+        let kek = derive_key(b"correct horse", b"0123456789abcdef", 10);
+        let sealed = seal(&kek, 0, &KEY).unwrap();
+        let other = derive_key(b"wrong horse", b"0123456789abcdef", 10);
+        assert!(unseal(&other, 0, &sealed).is_err());
+    }
+}
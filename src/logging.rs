@@ -1,14 +1,25 @@
 use anyhow::{Context, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{Resource, trace::SdkTracerProvider};
 use std::io::{BufRead, BufReader};
 use std::process::ChildStderr;
 use std::sync::OnceLock;
 use tracing_appender::non_blocking::WorkerGuard;
-use tracing_subscriber::{EnvFilter, fmt, prelude::*};
+use tracing_subscriber::{EnvFilter, Registry, fmt, prelude::*, reload};
 
 use crate::runtime_paths;
 
 static FILE_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
 
+type OtlpLayer =
+    tracing_opentelemetry::OpenTelemetryLayer<Registry, opentelemetry_sdk::trace::Tracer>;
+
+/// Empty until the config enables trace export; logging starts before the
+/// config is loaded.
+static OTLP_LAYER: OnceLock<reload::Handle<Option<OtlpLayer>, Registry>> = OnceLock::new();
+static OTLP_PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
 #[derive(Clone, Copy, Debug)]
 enum LogProfile {
     Dev,
@@ -36,7 +47,11 @@ pub fn init_logging() -> Result<()> {
     let env_filter = EnvFilter::try_new(filter_spec.clone())
         .with_context(|| format!("invalid log filter: {filter_spec}"))?;
 
+    let (otlp_layer, otlp_handle) = reload::Layer::new(None);
+    let _ = OTLP_LAYER.set(otlp_handle);
+
     let subscriber = tracing_subscriber::registry()
+        .with(otlp_layer)
         .with(env_filter)
         .with(
             fmt::layer()
@@ -67,6 +82,52 @@ pub fn init_logging() -> Result<()> {
     Ok(())
 }
 
+/// Starts exporting spans (IPC handling, RPC calls, IPFS fetches, bundle
+/// builds) to an OTLP/HTTP collector at `endpoint`. Spans go through the same
+/// filter as log lines.
+pub fn enable_otlp_export(endpoint: &str, chain_id: u64) -> Result<()> {
+    let handle = OTLP_LAYER.get().context("logging is not initialized")?;
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()
+        .context("failed to build OTLP span exporter")?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name("vibefi-client")
+                .with_attribute(opentelemetry::KeyValue::new(
+                    "service.version",
+                    env!("CARGO_PKG_VERSION"),
+                ))
+                .with_attribute(opentelemetry::KeyValue::new(
+                    "vibefi.chain_id",
+                    chain_id as i64,
+                ))
+                .build(),
+        )
+        .build();
+    let tracer = provider.tracer("vibefi");
+    OTLP_PROVIDER
+        .set(provider)
+        .map_err(|_| anyhow::anyhow!("OTLP export was already enabled"))?;
+    handle
+        .reload(Some(tracing_opentelemetry::layer().with_tracer(tracer)))
+        .context("failed to install OTLP layer")?;
+    tracing::info!(endpoint, "OTLP trace export enabled");
+    Ok(())
+}
+
+/// Flushes spans still queued for the collector; called on exit.
+pub fn shutdown_otlp_export() {
+    if let Some(provider) = OTLP_PROVIDER.get() {
+        if let Err(err) = provider.shutdown() {
+            tracing::warn!(error = %err, "failed to flush OTLP spans");
+        }
+    }
+}
+
 pub fn forward_child_stderr(helper: &'static str, stderr: ChildStderr) {
     let thread_name = format!("{helper}-stderr-log");
     let _ = std::thread::Builder::new()
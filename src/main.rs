@@ -3,39 +3,98 @@ compile_error!(
     "The 'automation' feature is only allowed in debug builds. Do not ship release binaries with automation enabled."
 );
 
+mod asset_pool;
 #[cfg(feature = "automation")]
 mod automation;
 #[cfg(not(feature = "automation"))]
 #[path = "automation_stub.rs"]
 mod automation;
+mod block_watcher;
 mod bundle;
+mod bundle_export;
+mod cache_integrity;
+mod cache_manager;
+mod client_info;
+mod clipboard;
+mod commands;
 mod config;
+mod connectivity;
+mod contract_logs;
+mod decoder;
+mod dist_cache;
+mod download;
+mod ens;
+mod error_hints;
 mod events;
+mod file_dialog;
+mod fork;
 mod hardware;
 mod ipc;
 mod ipc_contract;
-mod ipfs_helper;
+mod ipfs_check;
+mod keystore;
+mod lattice;
+mod launch_history;
+mod ledger_ble;
+mod local_bundle;
+mod lock;
 mod logging;
+mod manifest;
 mod menu;
+mod messaging;
+mod multicall;
+mod notifications;
+mod permissions;
+mod policy;
+mod prefetch;
+mod preview_eval;
+mod prices;
+mod profiles;
+mod prompt;
+mod publish;
+mod publish_preflight;
+mod qr;
 mod registry;
+mod registry_cache;
+mod registry_index;
+mod remote_signer;
+mod reports;
+mod rpc_client;
 mod rpc_manager;
 mod runtime_paths;
+mod scheduler;
+mod secret_scan;
 mod settings;
+mod shutdown;
+mod signed_export;
+mod smart_account;
+mod startup_profile;
 mod state;
+mod storage;
+mod studio_context;
+mod subscriptions;
+mod tab_usage;
+#[cfg(test)]
+mod test_harness;
+mod tx_history;
 mod ui_bridge;
+mod verified_fetch;
 mod walletconnect;
 mod webview;
 mod webview_manager;
+mod window_state;
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use std::{
     collections::HashMap,
+    collections::HashSet,
     collections::VecDeque,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
+    time::Instant,
 };
 use tao::{
-    dpi::LogicalSize,
     event::{Event, StartCause, WindowEvent},
     event_loop::ControlFlow,
     window::WindowBuilder,
@@ -43,9 +102,16 @@ use tao::{
 
 use bundle::{BundleConfig, build_bundle, verify_manifest};
 use config::{CliArgs, ConfigBuilder, load_config};
+use fork::ForkSource;
+use rpc_client::RpcClient;
 use rpc_manager::{DEFAULT_MAX_CONCURRENT_RPC, RpcEndpoint, RpcEndpointManager};
-use state::{AppState, Chain, UserEvent, WalletState};
-use webview::{EmbeddedContent, WebViewHost, build_app_webview, build_tab_bar_webview};
+use state::{
+    AppState, Chain, DappIdentity, TabAction, TabBadge, TabBadges, UserEvent, WalletState,
+};
+use webview::{
+    EmbeddedContent, WebViewHost, WebViewSource, build_app_webview, build_tab_bar_webview,
+    restore_app_webview,
+};
 use webview_manager::{AppWebViewEntry, AppWebViewKind, WebViewManager};
 
 static INDEX_HTML: &str = include_str!("../internal-ui/static/home.html");
@@ -63,12 +129,21 @@ static PRELOAD_TAB_BAR_JS: &str = include_str!("../internal-ui/dist/preload-tabb
 static SETTINGS_HTML: &str = include_str!("../internal-ui/static/settings.html");
 static SETTINGS_JS: &str = include_str!("../internal-ui/dist/settings.js");
 static PRELOAD_SETTINGS_JS: &str = include_str!("../internal-ui/dist/preload-settings.js");
+static PROMPT_HTML: &str = include_str!("../internal-ui/static/prompt.html");
+static PROMPT_JS: &str = include_str!("../internal-ui/dist/prompt.js");
+static PRELOAD_PROMPT_JS: &str = include_str!("../internal-ui/dist/preload-prompt.js");
+static PALETTE_HTML: &str = include_str!("../internal-ui/static/palette.html");
+static PALETTE_JS: &str = include_str!("../internal-ui/dist/palette.js");
+static PRELOAD_PALETTE_JS: &str = include_str!("../internal-ui/dist/preload-palette.js");
 
 fn main() -> Result<()> {
+    startup_profile::begin();
     apply_linux_env_defaults();
     logging::init_logging()?;
 
     let cli = CliArgs::parse();
+    startup_profile::set_enabled(cli.profile_startup);
+    startup_profile::mark("logging_ready");
     #[cfg(not(feature = "automation"))]
     if cli.automation {
         anyhow::bail!(
@@ -79,19 +154,43 @@ fn main() -> Result<()> {
     if cli.automation {
         anyhow::bail!("--automation is not supported on Windows");
     }
-    let bundle = resolve_bundle(&cli)?;
-    let studio_bundle = resolve_studio_bundle(&cli)?;
-    if bundle.is_some() && studio_bundle.is_some() {
-        tracing::warn!("--studio-bundle is ignored when --bundle is provided");
-    }
     let config_path = cli
         .config
         .or_else(|| runtime_paths::resolve_default_config());
+    let profile = cli.profile.clone().or_else(|| {
+        std::env::var("VIBEFI_PROFILE")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    });
 
+    let config_phase = startup_profile::phase("load_config");
     let resolved = match config_path.as_ref().map(|p| (p, load_config(p))) {
         Some((_, Ok(cfg))) => {
-            let resolved = ConfigBuilder::new(cfg, config_path.clone()).build();
+            let mut resolved = ConfigBuilder::new(cfg, config_path.clone()).build();
+            resolved.dev_profile |= cli.dev_profile;
+            if let Some(profile) = profile.as_deref() {
+                profiles::apply(&mut resolved, profile).context("failed to select profile")?;
+            }
+            if let Some(upstream) = cli.fork.as_deref() {
+                let _phase = startup_profile::phase("start_fork");
+                let source = ForkSource {
+                    upstream: if upstream.is_empty() {
+                        resolved.rpc_url.clone()
+                    } else {
+                        upstream.to_string()
+                    },
+                    block: cli.fork_block,
+                };
+                resolved.rpc_url = fork::start(&source, resolved.chain_id, &resolved.http_client)
+                    .context("failed to start --fork node")?;
+                resolved.fork = Some(source);
+            }
             resolved.log_startup_summary();
+            if let Some(endpoint) = resolved.otlp_endpoint.as_deref() {
+                if let Err(err) = logging::enable_otlp_export(endpoint, resolved.chain_id) {
+                    tracing::warn!(endpoint, error = %format!("{err:#}"), "failed to enable OTLP trace export");
+                }
+            }
             Some(Arc::new(resolved))
         }
         Some((path, Err(e))) => {
@@ -101,29 +200,69 @@ fn main() -> Result<()> {
         None => None,
     };
 
+    drop(config_phase);
+    if cli.fork.is_some() && resolved.is_none() {
+        anyhow::bail!("--fork needs a network config to fork");
+    }
+    if profile.is_some() && resolved.is_none() {
+        anyhow::bail!("--profile needs a network config");
+    }
+
+    let bundle = {
+        let _phase = startup_profile::phase("resolve_bundle");
+        // ipfs:// and https:// sources download through the network config.
+        resolve_bundle(&cli, resolved.as_deref())?
+    };
+    // Only verified here; building Studio happens off the critical path.
+    let studio_source_dir = resolve_studio_bundle(&cli)?;
+    let no_build = cli.no_build;
+    if bundle.is_some() && studio_source_dir.is_some() {
+        tracing::warn!("--studio-bundle is ignored when --bundle is provided");
+    }
+
+    if let Some(root_cid) = cli.export_bundle.as_deref() {
+        let resolved = resolved
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("--export-bundle needs a network config"))?;
+        let parts = bundle_export::ExportParts::parse(&cli.export_parts)
+            .ok_or_else(|| anyhow::anyhow!("--export-parts must be one of all, source, dist"))?;
+        let dest = cli.export_to.as_deref().expect("clap requires --export-to");
+        let exported =
+            bundle_export::export_bundle(&resolved.cache_dir, root_cid, None, parts, dest)?;
+        println!("{}", exported.display());
+        return Ok(());
+    }
     let initial_chain_id = resolved.as_ref().map(|r| r.chain_id).unwrap_or(1);
 
     // --- Load user settings + build RPC manager ---
+    let rpc_client = RpcClient::new()?;
     let rpc_manager = if let Some(ref res) = resolved {
         let user_settings = res
             .config_path
             .as_ref()
             .map(|p| settings::load_settings(p))
             .unwrap_or_default();
-        let endpoints = if user_settings.rpc_endpoints.is_empty() {
+        // A fork session sends everything to the fork node.
+        let (endpoints, default_label) = if res.fork.is_some() {
+            (Vec::new(), "Fork")
+        } else {
+            (user_settings.rpc_endpoints_for(res.chain_id), "Default")
+        };
+        let endpoints = if endpoints.is_empty() {
             vec![RpcEndpoint {
                 url: res.rpc_url.clone(),
-                label: Some("Default".to_string()),
+                label: Some(default_label.to_string()),
+                chain_id: Some(res.chain_id),
             }]
         } else {
-            user_settings.rpc_endpoints
+            endpoints
         };
         let max_concurrent = user_settings
             .max_concurrent_rpc
             .unwrap_or(DEFAULT_MAX_CONCURRENT_RPC);
         Some(RpcEndpointManager::new(
             endpoints,
-            res.http_client.clone(),
+            rpc_client.clone(),
             max_concurrent,
         ))
     } else {
@@ -131,6 +270,7 @@ fn main() -> Result<()> {
     };
 
     // --- Window + event loop ---
+    let event_loop_phase = startup_profile::phase("event_loop");
     let mut event_loop = tao::event_loop::EventLoopBuilder::<UserEvent>::with_user_event().build();
     #[cfg(target_os = "macos")]
     {
@@ -139,37 +279,81 @@ fn main() -> Result<()> {
         event_loop.set_activation_policy(ActivationPolicy::Regular);
         event_loop.set_dock_visibility(true);
         event_loop.set_activate_ignoring_other_apps(true);
-        menu::setup_macos_app_menu("VibeFi");
     }
     let proxy = event_loop.create_proxy();
+    drop(event_loop_phase);
 
     let state = AppState {
         wallet: Arc::new(Mutex::new(WalletState {
-            authorized: false,
             chain: Chain {
                 chain_id: initial_chain_id,
             },
-            account: None,
+            walletconnect_accounts: Vec::new(),
             walletconnect_uri: None,
         })),
-        wallet_backend: Arc::new(Mutex::new(None)),
-        signer: Arc::new(Mutex::new(None)),
+        tab_wallets: Arc::new(Mutex::new(HashMap::new())),
+        signer: Arc::new(Mutex::new(Vec::new())),
         walletconnect: Arc::new(Mutex::new(None)),
         hardware_signer: Arc::new(Mutex::new(None)),
+        smart_account: Arc::new(Mutex::new(None)),
+        remote_signer: Arc::new(Mutex::new(None)),
         resolved,
         proxy: proxy.clone(),
         pending_connect: Arc::new(Mutex::new(VecDeque::new())),
         app_capabilities: Arc::new(Mutex::new(HashMap::new())),
         selector_webview_id: Arc::new(Mutex::new(None)),
         rpc_manager: Arc::new(Mutex::new(rpc_manager)),
+        rpc_client,
         settings_webview_id: Arc::new(Mutex::new(None)),
         pending_rpc_counts: Arc::new(Mutex::new(HashMap::new())),
+        pending_signature_counts: Arc::new(Mutex::new(HashMap::new())),
+        known_dapps: Arc::new(Mutex::new(HashMap::new())),
+        bundles_in_use: Arc::new(Mutex::new(HashSet::new())),
+        subscriptions: Arc::new(Mutex::new(Default::default())),
+        blocks: Default::default(),
+        lock: Default::default(),
+        dropped_project: Arc::new(Mutex::new(None)),
+        preview_evals: Default::default(),
         automation: cli.automation,
     };
     if cli.automation {
         automation::spawn_stdin_reader(proxy.clone());
     }
-    let mut manager = WebViewManager::new(1.0);
+    let recent_dapps = state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.config_path.as_deref())
+        .map(shutdown::last_session_tabs)
+        .unwrap_or_default();
+    let mut app_menu = match menu::AppMenu::new("VibeFi", proxy.clone(), recent_dapps) {
+        Ok(app_menu) => Some(app_menu),
+        Err(err) => {
+            tracing::warn!(error = %format!("{err:#}"), "failed to build menu bar");
+            None
+        }
+    };
+    lock::start(&state);
+    ipc::spawn_reconnect(&state);
+    webview_manager::spawn_idle_tab_monitor(
+        proxy.clone(),
+        state.resolved.as_ref().and_then(|r| r.config_path.clone()),
+    );
+    tab_usage::spawn_monitor(
+        proxy.clone(),
+        state.resolved.as_ref().and_then(|r| r.config_path.clone()),
+    );
+    let mut manager = WebViewManager::new(1.0, proxy.clone(), state.tab_wallets.clone());
+    let fork_label = state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.fork.as_ref())
+        .map(ForkSource::label);
+    let window_title = std::iter::once("VibeFi")
+        .chain(state.resolved.as_ref().and_then(|r| r.profile.as_deref()))
+        .chain(fork_label.as_deref())
+        .collect::<Vec<_>>()
+        .join(" — ");
+    manager.fork_label = fork_label;
     let mut window: Option<tao::window::Window> = None;
     #[cfg(target_os = "linux")]
     let mut gtk_tab_bar_container: Option<gtk::Box> = None;
@@ -220,9 +404,31 @@ fn main() -> Result<()> {
                     &proxy,
                 );
             }
+            Event::UserEvent(UserEvent::OpenPalette) => {
+                let host = window.as_ref().map(|w| WebViewHost {
+                    window: w,
+                    #[cfg(target_os = "linux")]
+                    tab_bar_container: gtk_tab_bar_container
+                        .as_ref()
+                        .expect("linux tab bar container not initialized"),
+                    #[cfg(target_os = "linux")]
+                    app_container: gtk_app_container
+                        .as_ref()
+                        .expect("linux app container not initialized"),
+                });
+                events::user_event::handle_open_palette(
+                    host.as_ref(),
+                    &state,
+                    &mut manager,
+                    &proxy,
+                );
+            }
             Event::UserEvent(UserEvent::WalletConnectPairing { uri, qr_svg }) => {
                 events::user_event::handle_walletconnect_pairing(&state, &manager, uri, qr_svg);
             }
+            Event::UserEvent(UserEvent::WalletConnectRelayStatus { status }) => {
+                events::user_event::handle_walletconnect_relay_status(&state, &mut manager, status);
+            }
             Event::UserEvent(UserEvent::WalletConnectResult {
                 webview_id,
                 ipc_id,
@@ -236,26 +442,48 @@ fn main() -> Result<()> {
                     result,
                 );
             }
-            Event::UserEvent(UserEvent::HardwareSignResult {
+            Event::UserEvent(UserEvent::SignerResult {
                 webview_id,
                 ipc_id,
                 result,
             }) => {
-                events::user_event::handle_hardware_sign_result(
+                subscriptions::on_transaction_result(
+                    &state,
+                    &webview_id,
+                    ipc_id,
+                    result.as_deref().ok(),
+                );
+                tx_history::on_result(&state, &webview_id, ipc_id, result.as_deref().ok());
+                events::user_event::handle_signer_result(
                     &manager, webview_id, ipc_id, result,
                 );
             }
-            Event::UserEvent(UserEvent::RpcPendingChanged { webview_id, count }) => {
-                events::user_event::handle_rpc_pending_changed(&manager, &webview_id, count);
+            Event::UserEvent(UserEvent::TabBadgeChanged { webview_id, badge }) => {
+                manager.set_badge(&webview_id, badge);
+            }
+            Event::UserEvent(UserEvent::DappsListed { latest }) => {
+                manager.mark_dapp_updates(&latest);
             }
             Event::UserEvent(UserEvent::RpcResult {
                 webview_id,
                 ipc_id,
                 result,
             }) => {
+                subscriptions::on_transaction_result(
+                    &state,
+                    &webview_id,
+                    ipc_id,
+                    result.as_ref().ok().and_then(|value| value.as_str()),
+                );
+                tx_history::on_result(
+                    &state,
+                    &webview_id,
+                    ipc_id,
+                    result.as_ref().ok().and_then(|value| value.as_str()),
+                );
                 events::user_event::handle_rpc_result(&manager, webview_id.clone(), ipc_id, result);
                 let count = state.decrement_rpc_pending(&webview_id);
-                events::user_event::handle_rpc_pending_changed(&manager, &webview_id, count);
+                manager.set_badge(&webview_id, TabBadge::PendingRpc(count));
             }
             Event::UserEvent(UserEvent::ProviderEvent {
                 webview_id,
@@ -264,10 +492,44 @@ fn main() -> Result<()> {
             }) => {
                 events::user_event::handle_provider_event(&manager, webview_id, event, value);
             }
+            Event::UserEvent(UserEvent::ConnectivityChanged { online }) => {
+                events::user_event::handle_connectivity_changed(&state, &manager, online);
+            }
+            Event::UserEvent(UserEvent::WalletConnected { backend, accounts }) => {
+                events::user_event::handle_wallet_connected(
+                    &state,
+                    &mut manager,
+                    backend,
+                    accounts,
+                );
+            }
             Event::UserEvent(UserEvent::StudioBundleResolved {
                 placeholder_id,
                 result,
             }) => {
+                events::user_event::handle_studio_bundle_resolved(
+                    &state,
+                    &mut manager,
+                    placeholder_id,
+                    result,
+                );
+            }
+            Event::UserEvent(UserEvent::SuspendIdleTabs { idle_for }) => {
+                events::user_event::handle_suspend_idle_tabs(&state, &manager, idle_for);
+            }
+            Event::UserEvent(UserEvent::TabSnapshot {
+                webview_id,
+                snapshot,
+            }) => {
+                events::user_event::handle_tab_snapshot(&mut manager, &webview_id, &snapshot);
+            }
+            Event::UserEvent(UserEvent::SampleTabUsage { heavy_tab }) => {
+                manager.request_usage_samples(heavy_tab);
+            }
+            Event::UserEvent(UserEvent::TabUsage { webview_id, sample }) => {
+                manager.record_usage(&webview_id, &sample);
+            }
+            Event::UserEvent(UserEvent::ShowPrompt { request, reply }) => {
                 let host = window.as_ref().map(|w| WebViewHost {
                     window: w,
                     #[cfg(target_os = "linux")]
@@ -279,13 +541,13 @@ fn main() -> Result<()> {
                         .as_ref()
                         .expect("linux app container not initialized"),
                 });
-                events::user_event::handle_studio_bundle_resolved(
+                events::user_event::handle_show_prompt(
                     host.as_ref(),
                     &state,
                     &mut manager,
                     &proxy,
-                    placeholder_id,
-                    result,
+                    request,
+                    reply,
                 );
             }
             Event::UserEvent(UserEvent::CloseWalletSelector) => {
@@ -301,7 +563,24 @@ fn main() -> Result<()> {
                     automation::handle_command(id, cmd_type, target, js, &manager);
                 }
             }
+            Event::UserEvent(UserEvent::Menu(command)) => {
+                if let Some(app_menu) = app_menu.as_ref() {
+                    events::user_event::handle_menu_command(&state, &mut manager, app_menu, command);
+                }
+            }
+            Event::UserEvent(UserEvent::RestartWithProfile { name }) => {
+                shutdown::run(&state, &manager, window.as_ref());
+                if let Err(err) = profiles::relaunch(&name) {
+                    tracing::error!(profile = %name, error = %format!("{err:#}"), "failed to restart with profile");
+                }
+                *control_flow = ControlFlow::Exit;
+            }
             Event::UserEvent(UserEvent::TabAction(action)) => {
+                if let (TabAction::OpenApp { identity, .. }, Some(app_menu)) =
+                    (&action, app_menu.as_mut())
+                {
+                    app_menu.remember(identity);
+                }
                 let host = window.as_ref().map(|w| WebViewHost {
                     window: w,
                     #[cfg(target_os = "linux")]
@@ -322,12 +601,42 @@ fn main() -> Result<()> {
                 );
             }
 
+            Event::UserEvent(UserEvent::BundleDropped { path }) => {
+                events::user_event::handle_bundle_dropped(&state, &manager, path);
+            }
+            Event::UserEvent(UserEvent::StudioProjectDropped) => {
+                let host = window.as_ref().map(|w| WebViewHost {
+                    window: w,
+                    #[cfg(target_os = "linux")]
+                    tab_bar_container: gtk_tab_bar_container
+                        .as_ref()
+                        .expect("linux tab bar container not initialized"),
+                    #[cfg(target_os = "linux")]
+                    app_container: gtk_app_container
+                        .as_ref()
+                        .expect("linux app container not initialized"),
+                });
+                events::user_event::handle_studio_project_dropped(
+                    host.as_ref(),
+                    &state,
+                    &mut manager,
+                    &proxy,
+                );
+            }
+
             Event::NewEvents(StartCause::Init) => {
                 if window.is_none() {
-                    let built = WindowBuilder::new()
-                        .with_title("VibeFi")
-                        .with_inner_size(LogicalSize::new(1280.0, 720.0))
-                        .build(event_loop_window_target)
+                    let saved_window = state
+                        .resolved
+                        .as_ref()
+                        .and_then(|r| r.config_path.as_deref())
+                        .and_then(shutdown::last_window_state);
+                    let built = window_state::restore(
+                        WindowBuilder::new().with_title(&window_title),
+                        saved_window.as_ref(),
+                        event_loop_window_target.available_monitors(),
+                    )
+                    .build(event_loop_window_target)
                         .context("failed to build window");
                     let window_handle = match built {
                         Ok(window) => window,
@@ -337,6 +646,7 @@ fn main() -> Result<()> {
                             return;
                         }
                     };
+                    startup_profile::mark("window_built");
                     #[cfg(target_os = "macos")]
                     menu::setup_macos_dock_icon();
 
@@ -348,6 +658,11 @@ fn main() -> Result<()> {
                         gtk_tab_bar_container = Some(tb);
                         gtk_app_container = Some(app);
                     }
+                    if let Some(app_menu) = app_menu.as_ref() {
+                        if let Err(err) = app_menu.attach(&window_handle) {
+                            tracing::warn!(error = %format!("{err:#}"), "failed to attach menu bar");
+                        }
+                    }
 
                     let host = WebViewHost {
                         window: &window_handle,
@@ -380,6 +695,7 @@ fn main() -> Result<()> {
                         Ok(tb) => manager.tab_bar = Some(tb),
                         Err(e) => tracing::error!(error = ?e, "tab bar error"),
                     }
+                    startup_profile::mark("tab_bar_built");
 
                     // 2. Build initial app webview(s)
                     let has_registry = state
@@ -387,28 +703,45 @@ fn main() -> Result<()> {
                         .as_ref()
                         .map(|r| !r.dapp_registry.is_empty())
                         .unwrap_or(false);
-                    let dist_dir = bundle.as_ref().map(|cfg| cfg.dist_dir.clone());
-                    let studio_dist_dir = studio_bundle.as_ref().map(|cfg| cfg.dist_dir.clone());
+                    let studio_source_dir = studio_source_dir.clone();
                     let bounds = manager.app_rect(w, h);
-                    if let Some(dist_dir) = dist_dir.clone() {
+                    if let Some(bundle) = bundle.clone() {
                         let app_id = manager.next_app_id();
-                        match build_app_webview(
+                        let source = WebViewSource {
+                            dist_dir: Some(bundle.dist_dir),
+                            embedded: EmbeddedContent::Default,
+                            storage: None,
+                            ephemeral: false,
+                            local_project: bundle.local_project,
+                        };
+                        match restore_app_webview(
                             &host,
                             &app_id,
-                            Some(dist_dir),
-                            EmbeddedContent::Default,
+                            source.clone(),
+                            None,
                             &state,
                             proxy.clone(),
                             bounds,
                         ) {
                             Ok(wv) => {
                                 manager.apps.push(AppWebViewEntry {
-                                    webview: wv,
+                                    webview: Some(wv),
+                                    source: Some(source),
+                                    snapshot: None,
+                                    last_active: Instant::now(),
                                     id: app_id,
                                     label: "App".to_string(),
                                     kind: AppWebViewKind::Standard,
                                     selectable: true,
                                     loading: false,
+                                    usage: None,
+                                    unanswered_samples: 0,
+                                    badges: TabBadges::default(),
+                                    identity: Some(DappIdentity {
+                                        name: "App".to_string(),
+                                        local_project: bundle.local_project,
+                                        ..DappIdentity::default()
+                                    }),
                                 });
                                 manager.active_app_index = Some(0);
                                 manager.update_tab_bar();
@@ -439,45 +772,45 @@ fn main() -> Result<()> {
                         };
 
                         manager.apps.push(AppWebViewEntry {
-                            webview: launcher_webview,
+                            webview: Some(launcher_webview),
+                            source: Some(WebViewSource {
+                                dist_dir: None,
+                                embedded: EmbeddedContent::Launcher,
+                                storage: None,
+                                ephemeral: false,
+                                local_project: false,
+                            }),
+                            snapshot: None,
+                            last_active: Instant::now(),
                             id: launcher_id,
                             label: "Launcher".to_string(),
                             kind: AppWebViewKind::Launcher,
                             selectable: true,
                             loading: false,
+                            usage: None,
+                            unanswered_samples: 0,
+                            badges: TabBadges::default(),
+                            identity: None,
                         });
                         manager.active_app_index = Some(0);
 
+                        // The Studio webview is built when the tab is first activated;
+                        // until its bundle resolves there is nothing to build it from.
                         let studio_placeholder_id = manager.next_app_id();
-                        let studio_placeholder = match build_app_webview(
-                            &host,
-                            &studio_placeholder_id,
-                            None,
-                            EmbeddedContent::Default,
-                            &state,
-                            proxy.clone(),
-                            bounds,
-                        ) {
-                            Ok(wv) => wv,
-                            Err(e) => {
-                                tracing::error!(error = ?e, "studio placeholder webview error");
-                                *control_flow = ControlFlow::Exit;
-                                return;
-                            }
-                        };
-                        if let Err(err) = studio_placeholder.set_visible(false) {
-                            tracing::warn!(
-                                error = %err,
-                                "failed to hide inactive studio placeholder tab"
-                            );
-                        }
                         manager.apps.push(AppWebViewEntry {
-                            webview: studio_placeholder,
+                            webview: None,
+                            source: None,
+                            snapshot: None,
+                            last_active: Instant::now(),
                             id: studio_placeholder_id.clone(),
                             label: "Studio".to_string(),
                             kind: AppWebViewKind::Studio,
                             selectable: false,
                             loading: true,
+                            usage: None,
+                            unanswered_samples: 0,
+                            badges: TabBadges::default(),
+                            identity: None,
                         });
 
                         manager.update_tab_bar();
@@ -486,13 +819,10 @@ fn main() -> Result<()> {
                         let proxy_clone = proxy.clone();
                         let studio_placeholder_id_clone = studio_placeholder_id.clone();
                         std::thread::spawn(move || {
+                            let _phase = startup_profile::phase("resolve_studio");
                             let result = (|| -> Result<std::path::PathBuf> {
-                                if let Some(studio_dist_dir) = studio_dist_dir {
-                                    tracing::info!(
-                                        studio_dist_dir = %studio_dist_dir.display(),
-                                        "loading Studio from local --studio-bundle"
-                                    );
-                                    return Ok(studio_dist_dir);
+                                if let Some(source_dir) = studio_source_dir {
+                                    return build_studio_bundle(&source_dir, no_build);
                                 }
                                 let studio_dapp_id = state_clone
                                     .resolved
@@ -510,7 +840,16 @@ fn main() -> Result<()> {
                                     cid = %studio_cid,
                                     "loading Studio from DappRegistry"
                                 );
-                                registry::prepare_dapp_dist(&state_clone, &studio_cid, None)
+                                let mut launch =
+                                    launch_history::LaunchRecorder::start(&studio_cid, "Studio");
+                                let result = registry::prepare_dapp_dist(
+                                    &state_clone,
+                                    &studio_cid,
+                                    None,
+                                    &mut launch,
+                                );
+                                launch.finish(&state_clone, &result);
+                                result
                             })()
                             .map_err(|err| err.to_string());
                             let _ = proxy_clone.send_event(UserEvent::StudioBundleResolved {
@@ -531,12 +870,25 @@ fn main() -> Result<()> {
                         ) {
                             Ok(wv) => {
                                 manager.apps.push(AppWebViewEntry {
-                                    webview: wv,
+                                    webview: Some(wv),
+                                    source: Some(WebViewSource {
+                                        dist_dir: None,
+                                        embedded: EmbeddedContent::Default,
+                                        storage: None,
+                                        ephemeral: false,
+                                        local_project: false,
+                                    }),
+                                    snapshot: None,
+                                    last_active: Instant::now(),
                                     id: app_id,
                                     label: "Home".to_string(),
                                     kind: AppWebViewKind::Standard,
                                     selectable: true,
                                     loading: false,
+                                    usage: None,
+                                    unanswered_samples: 0,
+                                    badges: TabBadges::default(),
+                                    identity: None,
                                 });
                                 manager.active_app_index = Some(0);
                                 manager.update_tab_bar();
@@ -553,6 +905,15 @@ fn main() -> Result<()> {
                     install_or_update_macos_automation_banner(&window_handle, state.automation);
 
                     window = Some(window_handle);
+                    startup_profile::mark("window_ready");
+
+                    // Warm-up work that would otherwise delay the first frame.
+                    if state.resolved.is_some() {
+                        prefetch::spawn_prefetch_worker(state.clone());
+                        cache_integrity::spawn_integrity_worker(state.clone());
+                        connectivity::spawn_monitor(state.clone());
+                        scheduler::spawn_scheduler(state.clone());
+                    }
 
                     if state.automation {
                         for entry in &manager.apps {
@@ -567,10 +928,15 @@ fn main() -> Result<()> {
                 }
             }
 
+            Event::LoopDestroyed => shutdown::run(&state, &manager, window.as_ref()),
+            Event::MainEventsCleared => {
+                ui_bridge::flush_pending(|id| manager.webview_for_id(id));
+            }
             Event::WindowEvent {
                 event: WindowEvent::CloseRequested,
                 ..
             } => {
+                shutdown::run(&state, &manager, window.as_ref());
                 *control_flow = ControlFlow::Exit;
             }
             Event::WindowEvent {
@@ -770,32 +1136,50 @@ fn add_linux_automation_banner(vbox: &gtk::Box) {
     vbox.pack_start(&banner, false, true, 0);
 }
 
-fn resolve_bundle(cli: &CliArgs) -> Result<Option<BundleConfig>> {
+fn resolve_bundle(
+    cli: &CliArgs,
+    resolved: Option<&config::ResolvedConfig>,
+) -> Result<Option<BundleConfig>> {
     let Some(ref source) = cli.bundle else {
         return Ok(None);
     };
-    let source_dir = source
-        .canonicalize()
-        .context("bundle path does not exist")?;
-    let dist_dir = source_dir.join(".vibefi").join("dist");
-    verify_manifest(&source_dir)?;
-    if !cli.no_build {
-        build_bundle(&source_dir, &dist_dir)?;
+    let path = local_bundle::fetch(source, resolved)?;
+    let bundle = local_bundle::open(&path)?;
+    let dist_dir = bundle.source_dir.join(".vibefi").join("dist");
+    // A project directory is rebuilt on every run; archives and remote
+    // bundles that ship their build output are served as they are.
+    let rebuild = path.is_dir() || bundle.dist_dir.is_none();
+    if rebuild && !cli.no_build {
+        let source_maps = resolved.is_some_and(|r| r.dev_profile);
+        build_bundle(&bundle.source_dir, &dist_dir, source_maps)?;
     }
-    Ok(Some(BundleConfig { dist_dir }))
+    Ok(Some(BundleConfig {
+        dist_dir,
+        local_project: rebuild,
+    }))
 }
 
-fn resolve_studio_bundle(cli: &CliArgs) -> Result<Option<BundleConfig>> {
+fn resolve_studio_bundle(cli: &CliArgs) -> Result<Option<PathBuf>> {
     let Some(ref source) = cli.studio_bundle else {
         return Ok(None);
     };
     let source_dir = source
         .canonicalize()
         .context("studio bundle path does not exist")?;
-    let dist_dir = source_dir.join(".vibefi").join("dist");
     verify_manifest(&source_dir)?;
-    if !cli.no_build {
-        build_bundle(&source_dir, &dist_dir)?;
+    Ok(Some(source_dir))
+}
+
+/// Builds a local `--studio-bundle`. Runs on the Studio loader thread so a slow
+/// `bun build` does not hold up the window.
+fn build_studio_bundle(source_dir: &Path, no_build: bool) -> Result<PathBuf> {
+    let dist_dir = source_dir.join(".vibefi").join("dist");
+    tracing::info!(
+        studio_dist_dir = %dist_dir.display(),
+        "loading Studio from local --studio-bundle"
+    );
+    if !no_build {
+        build_bundle(source_dir, &dist_dir, false)?;
     }
-    Ok(Some(BundleConfig { dist_dir }))
+    Ok(dist_dir)
 }
@@ -0,0 +1,291 @@
+//! The bundle `manifest.json` format, read by the validator, the build and the
+//! capability loader.
+//!
+//! Schema 1 is the original flat layout and has no `schemaVersion`. Schema 2
+//! sets `"schemaVersion": 2`, requires a `sha256` for every file and groups
+//! the rest into sections:
+//!
+//! ```jsonc
+//! {
+//!   "schemaVersion": 2,
+//!   "name": "Example",
+//!   "layout": "constrained",              // or "static-html"
+//!   "files": [{ "path": "src/App.tsx", "bytes": 812, "sha256": "…" }],
+//!   "capabilities": { … },                // same as schema 1
+//!   "icons": [{ "src": "assets/icon.png" }],
+//!   "entrypoints": { "main": "index.html" },
+//!   "requirements": {
+//!     "minClientVersion": "0.2.0",
+//!     "chains": { "required": 1, "supported": [10, 8453] }
+//!   }
+//! }
+//! ```
+//!
+//! Schema 1 declares chains in a top-level `chains` object of the same shape.
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Newest schema this client understands.
+pub const LATEST_SCHEMA_VERSION: u64 = 2;
+const DEFAULT_ENTRYPOINT: &str = "index.html";
+
+/// A parsed manifest, whichever schema it was written in.
+#[derive(Debug, Clone)]
+pub struct BundleManifest {
+    pub schema_version: u64,
+    pub name: Option<String>,
+    pub files: Vec<BundleManifestFile>,
+    pub layout: Option<String>,
+    /// The `capabilities` section as written; the capability loader and the
+    /// response policy read the parts they enforce.
+    pub capabilities: Option<Value>,
+    pub icons: Vec<BundleIcon>,
+    /// Page the dapp opens on, relative to the build output.
+    pub entrypoint: String,
+    /// Oldest client release able to run the bundle, as `major.minor.patch`.
+    pub min_client_version: Option<String>,
+    pub chains: ChainRequirements,
+}
+
+/// Chains a dapp declares it runs on. Declaring none allows any chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ChainRequirements {
+    /// Chain switched to at launch when the current one is not allowed.
+    #[serde(default)]
+    pub required: Option<u64>,
+    /// Further chains the dapp works on.
+    #[serde(default)]
+    pub supported: Vec<u64>,
+}
+
+impl ChainRequirements {
+    pub fn is_empty(&self) -> bool {
+        self.required.is_none() && self.supported.is_empty()
+    }
+
+    pub fn allows(&self, chain_id: u64) -> bool {
+        self.is_empty() || self.required == Some(chain_id) || self.supported.contains(&chain_id)
+    }
+
+    /// Chain to switch to when the current one is not allowed.
+    pub fn preferred(&self) -> Option<u64> {
+        self.required.or_else(|| self.supported.first().copied())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BundleManifestFile {
+    pub path: String,
+    pub bytes: u64,
+    /// Hex-encoded SHA-256 of the file contents. Optional in schema 1.
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BundleIcon {
+    /// Bundle path of the image; must be listed in `files`.
+    pub src: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestV1 {
+    #[serde(default)]
+    name: Option<String>,
+    files: Vec<BundleManifestFile>,
+    #[serde(default)]
+    layout: Option<String>,
+    #[serde(default)]
+    constraints: Option<ConstraintsV1>,
+    #[serde(default)]
+    capabilities: Option<Value>,
+    #[serde(default)]
+    min_client_version: Option<String>,
+    #[serde(default)]
+    chains: ChainRequirements,
+}
+
+#[derive(Deserialize)]
+struct ConstraintsV1 {
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestV2 {
+    #[serde(default)]
+    name: Option<String>,
+    files: Vec<BundleManifestFile>,
+    #[serde(default)]
+    layout: Option<String>,
+    #[serde(default)]
+    capabilities: Option<Value>,
+    #[serde(default)]
+    icons: Vec<BundleIcon>,
+    #[serde(default)]
+    entrypoints: EntrypointsV2,
+    #[serde(default)]
+    requirements: RequirementsV2,
+}
+
+#[derive(Default, Deserialize)]
+struct EntrypointsV2 {
+    #[serde(default)]
+    main: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RequirementsV2 {
+    #[serde(default)]
+    min_client_version: Option<String>,
+    #[serde(default)]
+    chains: ChainRequirements,
+}
+
+impl BundleManifest {
+    pub fn is_static_html(&self) -> bool {
+        self.layout.as_deref() == Some("static-html")
+    }
+
+    fn from_v1(raw: ManifestV1) -> Self {
+        // Schema 1 bundles could pick the layout through `constraints.type`.
+        let constraint = raw.constraints.and_then(|c| c.kind);
+        let layout = if constraint.as_deref() == Some("static-html") {
+            constraint
+        } else {
+            raw.layout.or(constraint)
+        };
+        Self {
+            schema_version: 1,
+            name: raw.name,
+            files: raw.files,
+            layout,
+            capabilities: raw.capabilities,
+            icons: Vec::new(),
+            entrypoint: DEFAULT_ENTRYPOINT.to_string(),
+            min_client_version: raw.min_client_version,
+            chains: raw.chains,
+        }
+    }
+
+    fn from_v2(raw: ManifestV2) -> Result<Self> {
+        if let Some(file) = raw.files.iter().find(|file| file.sha256.is_none()) {
+            bail!("manifest.json schema 2 requires a sha256 for {}", file.path);
+        }
+        Ok(Self {
+            schema_version: 2,
+            name: raw.name,
+            files: raw.files,
+            layout: raw.layout,
+            capabilities: raw.capabilities,
+            icons: raw.icons,
+            entrypoint: raw
+                .entrypoints
+                .main
+                .unwrap_or_else(|| DEFAULT_ENTRYPOINT.to_string()),
+            min_client_version: raw.requirements.min_client_version,
+            chains: raw.requirements.chains,
+        })
+    }
+}
+
+/// Parses `manifest.json` bytes of any supported schema.
+pub fn parse(raw: &[u8]) -> Result<BundleManifest> {
+    let value: Value = serde_json::from_slice(raw).context("manifest.json is not valid JSON")?;
+    let schema_version = match value.get("schemaVersion") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| anyhow!("manifest.json schemaVersion must be a positive integer"))?,
+    };
+    match schema_version {
+        1 => serde_json::from_value(value)
+            .map(BundleManifest::from_v1)
+            .context("invalid manifest.json (schema 1)"),
+        2 => BundleManifest::from_v2(
+            serde_json::from_value(value).context("invalid manifest.json (schema 2)")?,
+        ),
+        newer => bail!(
+            "manifest.json uses schemaVersion {newer}, but this client reads up to {LATEST_SCHEMA_VERSION}. Update VibeFi to open this bundle."
+        ),
+    }
+}
+
+/// Reads and parses `manifest.json` in `bundle_dir`.
+pub fn load(bundle_dir: &Path) -> Result<BundleManifest> {
+    let raw = fs::read(bundle_dir.join("manifest.json")).context("read manifest.json")?;
+    parse(&raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LATEST_SCHEMA_VERSION, parse};
+    use serde_json::json;
+
+    #[test]
+    fn reads_schema_v1_manifests() {
+        let v1 = json!({
+            "files": [{ "path": "index.html", "bytes": 3 }],
+            "constraints": { "type": "static-html" },
+            "minClientVersion": "0.1.0"
+        });
+        let manifest = parse(v1.to_string().as_bytes()).unwrap();
+        assert_eq!(manifest.schema_version, 1);
+        assert!(manifest.is_static_html());
+        assert_eq!(manifest.min_client_version.as_deref(), Some("0.1.0"));
+        assert_eq!(manifest.entrypoint, "index.html");
+        assert!(manifest.chains.allows(8453));
+    }
+
+    #[test]
+    fn reads_schema_v2_manifests() {
+        let v2 = json!({
+            "schemaVersion": 2,
+            "layout": "constrained",
+            "files": [{ "path": "src/App.tsx", "bytes": 3, "sha256": "ab" }],
+            "icons": [{ "src": "icon.png", "sizes": "64x64" }],
+            "requirements": {
+                "minClientVersion": "0.2.0",
+                "chains": { "required": 1, "supported": [10] }
+            }
+        });
+        let manifest = parse(v2.to_string().as_bytes()).unwrap();
+        assert_eq!(manifest.schema_version, 2);
+        assert!(!manifest.is_static_html());
+        assert_eq!(manifest.icons[0].src, "icon.png");
+        assert_eq!(manifest.min_client_version.as_deref(), Some("0.2.0"));
+        assert!(manifest.chains.allows(10));
+        assert!(!manifest.chains.allows(8453));
+        assert_eq!(manifest.chains.preferred(), Some(1));
+    }
+
+    #[test]
+    fn schema_v2_files_need_a_hash() {
+        let unhashed = json!({
+            "schemaVersion": 2,
+            "files": [{ "path": "src/App.tsx", "bytes": 3 }]
+        });
+        let err = parse(unhashed.to_string().as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("sha256 for src/App.tsx"));
+    }
+
+    #[test]
+    fn newer_schemas_ask_for_an_update() {
+        let future = json!({ "schemaVersion": LATEST_SCHEMA_VERSION + 1, "files": [] });
+        let err = parse(future.to_string().as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("Update VibeFi"));
+    }
+
+    #[test]
+    fn schema_version_must_be_a_number() {
+        assert!(parse(br#"{ "schemaVersion": "2", "files": [] }"#).is_err());
+    }
+}
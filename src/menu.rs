@@ -1,123 +1,309 @@
-#[cfg(target_os = "macos")]
-pub fn setup_macos_app_menu(app_name: &str) {
-    use objc2::{MainThreadOnly, sel};
-    use objc2_app_kit::{NSApplication, NSEventModifierFlags, NSMenu, NSMenuItem};
-    use objc2_foundation::{MainThreadMarker, NSString};
+use anyhow::Result;
+use muda::accelerator::{Accelerator, CMD_OR_CTRL, Code, Modifiers};
+use muda::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use std::sync::Mutex;
+use tao::event_loop::EventLoopProxy;
 
-    let mtm = MainThreadMarker::new().expect("failed to acquire MainThreadMarker on macOS");
-    let app = NSApplication::sharedApplication(mtm);
-    if app.mainMenu().is_some() {
-        return;
+use crate::state::{DappIdentity, UserEvent};
+
+/// Dapps kept under Dapps → Recent.
+const MAX_RECENT: usize = 8;
+const RECENT_PREFIX: &str = "dapps.recent.";
+
+/// A menu entry picked by the user; handled on the event loop like any other
+/// [`UserEvent`]. The command palette lists these too; see
+/// [`crate::commands`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuCommand {
+    NewTab,
+    CloseTab,
+    CommandPalette,
+    OpenSettings,
+    ShowLauncher,
+    /// Index into [`AppMenu::recent`].
+    OpenRecent(usize),
+    ConnectWallet,
+    SwitchAccount,
+    ToggleDevtools,
+    OpenLogs,
+}
+
+impl MenuCommand {
+    /// Every command with a fixed menu entry, in menu order.
+    pub const FIXED: [Self; 9] = [
+        Self::NewTab,
+        Self::CloseTab,
+        Self::CommandPalette,
+        Self::OpenSettings,
+        Self::ShowLauncher,
+        Self::ConnectWallet,
+        Self::SwitchAccount,
+        Self::ToggleDevtools,
+        Self::OpenLogs,
+    ];
+
+    pub(crate) fn id(self) -> String {
+        match self {
+            Self::NewTab => "file.new_tab".to_string(),
+            Self::CloseTab => "file.close_tab".to_string(),
+            Self::CommandPalette => "file.command_palette".to_string(),
+            Self::OpenSettings => "file.settings".to_string(),
+            Self::ShowLauncher => "dapps.launcher".to_string(),
+            Self::OpenRecent(index) => format!("{RECENT_PREFIX}{index}"),
+            Self::ConnectWallet => "wallet.connect".to_string(),
+            Self::SwitchAccount => "wallet.switch".to_string(),
+            Self::ToggleDevtools => "developer.devtools".to_string(),
+            Self::OpenLogs => "developer.logs".to_string(),
+        }
     }
 
-    let menubar = NSMenu::initWithTitle(NSMenu::alloc(mtm), &NSString::from_str(""));
+    pub(crate) fn from_id(id: &str) -> Option<Self> {
+        if let Some(index) = id.strip_prefix(RECENT_PREFIX) {
+            return index.parse().ok().map(Self::OpenRecent);
+        }
+        Self::FIXED.into_iter().find(|command| command.id() == id)
+    }
 
-    let app_menu = NSMenu::initWithTitle(NSMenu::alloc(mtm), &NSString::from_str(app_name));
-    let app_menu_item = unsafe {
-        NSMenuItem::initWithTitle_action_keyEquivalent(
-            NSMenuItem::alloc(mtm),
-            &NSString::from_str(""),
-            None,
-            &NSString::from_str(""),
-        )
-    };
-    menubar.addItem(&app_menu_item);
-    app_menu_item.setSubmenu(Some(&app_menu));
-
-    let quit_title = format!("Quit {app_name}");
-    let quit_item = unsafe {
-        NSMenuItem::initWithTitle_action_keyEquivalent(
-            NSMenuItem::alloc(mtm),
-            &NSString::from_str(&quit_title),
-            Some(sel!(terminate:)),
-            &NSString::from_str("q"),
-        )
-    };
-    quit_item.setKeyEquivalentModifierMask(NSEventModifierFlags::Command);
-    app_menu.addItem(&quit_item);
-
-    let edit_menu = NSMenu::initWithTitle(NSMenu::alloc(mtm), &NSString::from_str("Edit"));
-    let edit_menu_item = unsafe {
-        NSMenuItem::initWithTitle_action_keyEquivalent(
-            NSMenuItem::alloc(mtm),
-            &NSString::from_str("Edit"),
-            None,
-            &NSString::from_str(""),
-        )
-    };
-    menubar.addItem(&edit_menu_item);
-    edit_menu_item.setSubmenu(Some(&edit_menu));
-
-    let undo_item = unsafe {
-        NSMenuItem::initWithTitle_action_keyEquivalent(
-            NSMenuItem::alloc(mtm),
-            &NSString::from_str("Undo"),
-            Some(sel!(undo:)),
-            &NSString::from_str("z"),
-        )
-    };
-    undo_item.setKeyEquivalentModifierMask(NSEventModifierFlags::Command);
-    edit_menu.addItem(&undo_item);
-
-    let redo_item = unsafe {
-        NSMenuItem::initWithTitle_action_keyEquivalent(
-            NSMenuItem::alloc(mtm),
-            &NSString::from_str("Redo"),
-            Some(sel!(redo:)),
-            &NSString::from_str("Z"),
-        )
-    };
-    redo_item
-        .setKeyEquivalentModifierMask(NSEventModifierFlags::Command | NSEventModifierFlags::Shift);
-    edit_menu.addItem(&redo_item);
-
-    edit_menu.addItem(&NSMenuItem::separatorItem(mtm));
-
-    let cut_item = unsafe {
-        NSMenuItem::initWithTitle_action_keyEquivalent(
-            NSMenuItem::alloc(mtm),
-            &NSString::from_str("Cut"),
-            Some(sel!(cut:)),
-            &NSString::from_str("x"),
-        )
-    };
-    cut_item.setKeyEquivalentModifierMask(NSEventModifierFlags::Command);
-    edit_menu.addItem(&cut_item);
-
-    let copy_item = unsafe {
-        NSMenuItem::initWithTitle_action_keyEquivalent(
-            NSMenuItem::alloc(mtm),
-            &NSString::from_str("Copy"),
-            Some(sel!(copy:)),
-            &NSString::from_str("c"),
-        )
-    };
-    copy_item.setKeyEquivalentModifierMask(NSEventModifierFlags::Command);
-    edit_menu.addItem(&copy_item);
-
-    let paste_item = unsafe {
-        NSMenuItem::initWithTitle_action_keyEquivalent(
-            NSMenuItem::alloc(mtm),
-            &NSString::from_str("Paste"),
-            Some(sel!(paste:)),
-            &NSString::from_str("v"),
-        )
-    };
-    paste_item.setKeyEquivalentModifierMask(NSEventModifierFlags::Command);
-    edit_menu.addItem(&paste_item);
-
-    let select_all_item = unsafe {
-        NSMenuItem::initWithTitle_action_keyEquivalent(
-            NSMenuItem::alloc(mtm),
-            &NSString::from_str("Select All"),
-            Some(sel!(selectAll:)),
-            &NSString::from_str("a"),
-        )
-    };
-    select_all_item.setKeyEquivalentModifierMask(NSEventModifierFlags::Command);
-    edit_menu.addItem(&select_all_item);
+    /// Title of the menu entry; recent dapps are titled by name instead.
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::NewTab => "New Tab",
+            Self::CloseTab => "Close Tab",
+            Self::CommandPalette => "Command Palette…",
+            Self::OpenSettings => "Settings…",
+            Self::ShowLauncher => "Launcher",
+            Self::OpenRecent(_) => "Recent Dapp",
+            Self::ConnectWallet => "Connect Wallet…",
+            Self::SwitchAccount => "Switch Account…",
+            Self::ToggleDevtools => "Toggle Developer Tools",
+            Self::OpenLogs => "Open Logs Folder",
+        }
+    }
+
+    /// Keyboard shortcut, with Cmd on macOS and Ctrl elsewhere.
+    pub fn shortcut(self) -> Option<(Modifiers, Code)> {
+        match self {
+            Self::NewTab => Some((CMD_OR_CTRL, Code::KeyT)),
+            Self::CloseTab => Some((CMD_OR_CTRL, Code::KeyW)),
+            Self::CommandPalette => Some((CMD_OR_CTRL, Code::KeyK)),
+            Self::OpenSettings => Some((CMD_OR_CTRL, Code::Comma)),
+            Self::ShowLauncher => Some((CMD_OR_CTRL | Modifiers::SHIFT, Code::KeyL)),
+            Self::SwitchAccount => Some((CMD_OR_CTRL | Modifiers::SHIFT, Code::KeyA)),
+            Self::ToggleDevtools => Some((CMD_OR_CTRL | Modifiers::ALT, Code::KeyI)),
+            Self::OpenRecent(_) | Self::ConnectWallet | Self::OpenLogs => None,
+        }
+    }
+
+    /// [`Self::shortcut`] as shown next to the title, e.g. `Ctrl+Shift+L`.
+    pub fn shortcut_label(self) -> Option<String> {
+        let (modifiers, code) = self.shortcut()?;
+        let mut keys = Vec::new();
+        if modifiers.contains(Modifiers::SUPER) {
+            keys.push("Cmd");
+        }
+        if modifiers.contains(Modifiers::CONTROL) {
+            keys.push("Ctrl");
+        }
+        if modifiers.contains(Modifiers::ALT) {
+            keys.push(if cfg!(target_os = "macos") {
+                "Option"
+            } else {
+                "Alt"
+            });
+        }
+        if modifiers.contains(Modifiers::SHIFT) {
+            keys.push("Shift");
+        }
+        let key = match code {
+            Code::Comma => ",".to_string(),
+            other => other.to_string().trim_start_matches("Key").to_string(),
+        };
+        Some(format!("{}+{key}", keys.join("+")))
+    }
+
+    fn item(self, title: &str, accelerator: Option<Accelerator>) -> MenuItem {
+        MenuItem::with_id(self.id(), title, true, accelerator)
+    }
+
+    /// The menu entry of a fixed command.
+    fn entry(self) -> MenuItem {
+        let accelerator = self
+            .shortcut()
+            .map(|(modifiers, code)| Accelerator::new(Some(modifiers), code));
+        self.item(self.title(), accelerator)
+    }
+}
+
+/// The window menu bar: File, Edit, Dapps, Wallet and Developer menus, plus
+/// the application menu on macOS.
+pub struct AppMenu {
+    menu: Menu,
+    recent_menu: Submenu,
+    recent_items: Vec<MenuItem>,
+    recent: Vec<DappIdentity>,
+}
+
+impl AppMenu {
+    /// Builds the menus and forwards picks to the event loop. `recent` seeds
+    /// Dapps → Recent, most recent first.
+    pub fn new(
+        app_name: &str,
+        proxy: EventLoopProxy<UserEvent>,
+        recent: Vec<DappIdentity>,
+    ) -> Result<Self> {
+        let menu = Menu::new();
+
+        #[cfg(target_os = "macos")]
+        menu.append(&Submenu::with_items(
+            app_name,
+            true,
+            &[
+                &PredefinedMenuItem::about(None, None),
+                &PredefinedMenuItem::separator(),
+                &PredefinedMenuItem::hide(None),
+                &PredefinedMenuItem::hide_others(None),
+                &PredefinedMenuItem::separator(),
+                &PredefinedMenuItem::quit(Some(&format!("Quit {app_name}"))),
+            ],
+        )?)?;
+
+        let file_menu = Submenu::with_items(
+            "File",
+            true,
+            &[
+                &MenuCommand::NewTab.entry(),
+                &MenuCommand::CloseTab.entry(),
+                &PredefinedMenuItem::separator(),
+                &MenuCommand::CommandPalette.entry(),
+                &MenuCommand::OpenSettings.entry(),
+            ],
+        )?;
+        #[cfg(not(target_os = "macos"))]
+        file_menu.append_items(&[
+            &PredefinedMenuItem::separator(),
+            &PredefinedMenuItem::quit(Some(&format!("Quit {app_name}"))),
+        ])?;
+        menu.append(&file_menu)?;
+
+        menu.append(&Submenu::with_items(
+            "Edit",
+            true,
+            &[
+                &PredefinedMenuItem::undo(None),
+                &PredefinedMenuItem::redo(None),
+                &PredefinedMenuItem::separator(),
+                &PredefinedMenuItem::cut(None),
+                &PredefinedMenuItem::copy(None),
+                &PredefinedMenuItem::paste(None),
+                &PredefinedMenuItem::select_all(None),
+            ],
+        )?)?;
+
+        let recent_menu = Submenu::new("Recent", true);
+        menu.append(&Submenu::with_items(
+            "Dapps",
+            true,
+            &[&MenuCommand::ShowLauncher.entry(), &recent_menu],
+        )?)?;
+
+        menu.append(&Submenu::with_items(
+            "Wallet",
+            true,
+            &[
+                &MenuCommand::ConnectWallet.entry(),
+                &MenuCommand::SwitchAccount.entry(),
+            ],
+        )?)?;
+
+        menu.append(&Submenu::with_items(
+            "Developer",
+            true,
+            &[
+                &MenuCommand::ToggleDevtools.entry(),
+                &MenuCommand::OpenLogs.entry(),
+            ],
+        )?)?;
+
+        let proxy = Mutex::new(proxy);
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            let Some(command) = MenuCommand::from_id(event.id.as_ref()) else {
+                return;
+            };
+            if let Ok(proxy) = proxy.lock() {
+                let _ = proxy.send_event(UserEvent::Menu(command));
+            }
+        }));
+
+        let mut app_menu = Self {
+            menu,
+            recent_menu,
+            recent_items: Vec::new(),
+            recent: Vec::new(),
+        };
+        app_menu.recent = recent.into_iter().take(MAX_RECENT).collect();
+        app_menu.rebuild_recent();
+        Ok(app_menu)
+    }
+
+    /// Shows the menu bar; on Windows and Linux it belongs to `window`.
+    pub fn attach(&self, window: &tao::window::Window) -> Result<()> {
+        #[cfg(target_os = "macos")]
+        {
+            let _ = window;
+            self.menu.init_for_nsapp();
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use anyhow::Context;
+            use tao::platform::windows::WindowExtWindows;
+            // SAFETY: the window outlives the menu bar attached to it.
+            unsafe { self.menu.init_for_hwnd(window.hwnd() as _) }.context("attach menu bar")?;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use anyhow::Context;
+            use tao::platform::unix::WindowExtUnix;
+            self.menu
+                .init_for_gtk_window(window.gtk_window(), window.default_vbox())
+                .context("attach menu bar")?;
+        }
+        Ok(())
+    }
+
+    pub fn recent(&self, index: usize) -> Option<&DappIdentity> {
+        self.recent.get(index)
+    }
 
-    app.setMainMenu(Some(&menubar));
+    /// Moves a launched dapp to the top of Dapps → Recent.
+    pub fn remember(&mut self, identity: &DappIdentity) {
+        let Some(root_cid) = identity.root_cid.as_deref() else {
+            return;
+        };
+        self.recent
+            .retain(|known| known.root_cid.as_deref() != Some(root_cid));
+        self.recent.insert(0, identity.clone());
+        self.recent.truncate(MAX_RECENT);
+        self.rebuild_recent();
+    }
+
+    fn rebuild_recent(&mut self) {
+        for item in self.recent_items.drain(..) {
+            let _ = self.recent_menu.remove(&item);
+        }
+        if self.recent.is_empty() {
+            self.recent_items
+                .push(MenuItem::new("No Recent Dapps", false, None));
+        }
+        for (index, identity) in self.recent.iter().enumerate() {
+            self.recent_items
+                .push(MenuCommand::OpenRecent(index).item(&identity.name, None));
+        }
+        for item in &self.recent_items {
+            if let Err(err) = self.recent_menu.append(item) {
+                tracing::warn!(error = %err, "failed to update recent dapps menu");
+            }
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
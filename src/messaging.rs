@@ -0,0 +1,164 @@
+use anyhow::{Result, anyhow, bail};
+use serde_json::{Value, json};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::ipc_contract::IpcRequest;
+use crate::state::{AppState, DappIdentity};
+use crate::webview_manager::WebViewManager;
+
+/// Provider event delivering a dapp-to-dapp message.
+pub const MESSAGE_EVENT: &str = "vibefiMessage";
+/// Largest serialized `data` payload a message may carry.
+const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+
+static NEXT_MESSAGE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Whether two dapps may exchange messages: both must name each other under
+/// `capabilities.messaging.peers`, and dapp ids only match within a registry.
+fn may_exchange(
+    sender: &DappIdentity,
+    sender_peers: &[String],
+    recipient: &DappIdentity,
+    recipient_peers: &[String],
+) -> bool {
+    let (Some(sender_id), Some(recipient_id)) = (&sender.dapp_id, &recipient.dapp_id) else {
+        return false;
+    };
+    sender.registry_address == recipient.registry_address
+        && sender_peers.contains(recipient_id)
+        && recipient_peers.contains(sender_id)
+}
+
+/// Handles `vibefi_postMessage` (`{to, data, replyTo?}`): delivers `data` to
+/// every open tab of dapp `to` that accepts messages from the sender and
+/// returns the message id replies refer to.
+pub fn post(
+    state: &AppState,
+    manager: &WebViewManager,
+    webview_id: &str,
+    req: &IpcRequest,
+) -> Result<Value> {
+    let sender = manager
+        .apps
+        .iter()
+        .find(|entry| entry.id == webview_id)
+        .and_then(|entry| entry.identity.clone())
+        .filter(|identity| identity.dapp_id.is_some())
+        .ok_or_else(|| anyhow!("only registry dapps can send messages"))?;
+    let sender_peers = state
+        .app_capabilities_for(webview_id)
+        .map(|caps| caps.message_peers)
+        .unwrap_or_default();
+    if sender_peers.is_empty() {
+        bail!("messaging capability denied");
+    }
+
+    let message = req
+        .params
+        .get(0)
+        .ok_or_else(|| anyhow!("message is required"))?;
+    let to = match message.get("to") {
+        Some(Value::String(to)) => to.clone(),
+        Some(Value::Number(to)) => to.to_string(),
+        _ => bail!("message recipient is required"),
+    };
+    let data = message.get("data").cloned().unwrap_or(Value::Null);
+    if serde_json::to_vec(&data)?.len() > MAX_MESSAGE_BYTES {
+        bail!("message exceeds {MAX_MESSAGE_BYTES} bytes");
+    }
+    let reply_to = message.get("replyTo").and_then(Value::as_str);
+    if !sender_peers.contains(&to) {
+        bail!("messaging capability denied for dapp {to}");
+    }
+
+    let id = format!("0x{:x}", NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed));
+    let payload = json!({
+        "id": id,
+        "from": { "dappId": sender.dapp_id, "name": sender.name },
+        "replyTo": reply_to,
+        "data": data,
+    });
+    let mut delivered = 0;
+    for entry in &manager.apps {
+        let (Some(webview), Some(recipient)) = (&entry.webview, &entry.identity) else {
+            continue;
+        };
+        if entry.id == webview_id || recipient.dapp_id.as_deref() != Some(to.as_str()) {
+            continue;
+        }
+        let recipient_peers = state
+            .app_capabilities_for(&entry.id)
+            .map(|caps| caps.message_peers)
+            .unwrap_or_default();
+        if !may_exchange(&sender, &sender_peers, recipient, &recipient_peers) {
+            continue;
+        }
+        crate::ui_bridge::emit_provider_event(webview, MESSAGE_EVENT, payload.clone());
+        delivered += 1;
+    }
+    if delivered == 0 {
+        bail!("dapp {to} is not open or does not accept messages from this dapp");
+    }
+    tracing::debug!(webview_id, from = %sender, to, message = %id, delivered, "dapp message");
+    Ok(Value::String(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::may_exchange;
+    use crate::state::DappIdentity;
+
+    fn dapp(id: &str, registry: Option<&str>) -> DappIdentity {
+        DappIdentity {
+            dapp_id: Some(id.to_string()),
+            registry_address: registry.map(str::to_string),
+            ..DappIdentity::default()
+        }
+    }
+
+    fn peers(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn dapps_that_name_each_other_may_exchange() {
+        assert!(may_exchange(
+            &dapp("1", None),
+            &peers(&["2"]),
+            &dapp("2", None),
+            &peers(&["1"])
+        ));
+    }
+
+    #[test]
+    fn both_dapps_must_opt_in() {
+        // The swap dapp did not opt in to the portfolio dapp.
+        assert!(!may_exchange(
+            &dapp("1", None),
+            &peers(&["2"]),
+            &dapp("2", None),
+            &peers(&["3"])
+        ));
+    }
+
+    #[test]
+    fn peers_are_matched_per_registry() {
+        // Same id from a community registry is a different dapp.
+        assert!(!may_exchange(
+            &dapp("1", None),
+            &peers(&["2"]),
+            &dapp("2", Some("0xabc")),
+            &peers(&["1"])
+        ));
+    }
+
+    #[test]
+    fn unidentified_tabs_may_not_exchange() {
+        assert!(!may_exchange(
+            &DappIdentity::default(),
+            &peers(&["2"]),
+            &dapp("2", None),
+            &peers(&["1"])
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+use alloy_primitives::{Address, Bytes, address};
+use alloy_sol_types::{SolCall, sol};
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::ipc::rpc_request;
+use crate::state::AppState;
+
+sol! {
+    struct Call3 {
+        address target;
+        bool allowFailure;
+        bytes callData;
+    }
+
+    struct Call3Result {
+        bool success;
+        bytes returnData;
+    }
+
+    function aggregate3(Call3[] calls) external payable returns (Call3Result[] returnData);
+}
+
+/// Multicall3, deployed at the same address on most chains.
+pub const MULTICALL3: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+/// Reads per `aggregate3` call. Public RPCs cap `eth_call` gas and response
+/// size, so large scans are split.
+const MAX_BATCH: usize = 100;
+
+/// Whether Multicall3 has code on each chain checked this session.
+static DEPLOYED: Mutex<Option<HashMap<u64, bool>>> = Mutex::new(None);
+
+/// One `eth_call` the client makes for itself.
+#[derive(Debug, Clone)]
+pub struct ReadCall {
+    pub to: Address,
+    pub data: Bytes,
+}
+
+/// Return data of one read, or why it failed. One failing read never fails
+/// the others.
+pub type ReadResult = std::result::Result<Bytes, String>;
+
+/// Runs `calls` against the latest block, batched through Multicall3 where it
+/// is deployed and one by one elsewhere. Results are in call order.
+pub fn read_many(state: &AppState, calls: &[ReadCall]) -> Vec<ReadResult> {
+    if calls.len() > 1 && multicall_deployed(state) {
+        calls
+            .chunks(MAX_BATCH)
+            .flat_map(|chunk| read_batch(state, chunk))
+            .collect()
+    } else {
+        calls.iter().map(|call| read_one(state, call)).collect()
+    }
+}
+
+/// Reads `chunk` in one `aggregate3` call. When the node rejects the whole
+/// batch (gas cap, response limit), it is halved until the culprit is alone.
+fn read_batch(state: &AppState, chunk: &[ReadCall]) -> Vec<ReadResult> {
+    if chunk.len() == 1 {
+        return vec![read_one(state, &chunk[0])];
+    }
+    let batch = eth_call(state, MULTICALL3, encode_batch(chunk))
+        .and_then(|raw| decode_batch(chunk.len(), &raw));
+    match batch {
+        Ok(results) => results,
+        Err(err) => {
+            tracing::debug!(
+                calls = chunk.len(),
+                error = %format!("{err:#}"),
+                "multicall batch failed; splitting"
+            );
+            let (left, right) = chunk.split_at(chunk.len() / 2);
+            let mut results = read_batch(state, left);
+            results.extend(read_batch(state, right));
+            results
+        }
+    }
+}
+
+fn read_one(state: &AppState, call: &ReadCall) -> ReadResult {
+    eth_call(state, call.to, call.data.clone()).map_err(|err| format!("{err:#}"))
+}
+
+fn encode_batch(calls: &[ReadCall]) -> Bytes {
+    aggregate3Call {
+        calls: calls
+            .iter()
+            .map(|call| Call3 {
+                target: call.to,
+                allowFailure: true,
+                callData: call.data.clone(),
+            })
+            .collect(),
+    }
+    .abi_encode()
+    .into()
+}
+
+fn decode_batch(expected: usize, raw: &[u8]) -> Result<Vec<ReadResult>> {
+    let results =
+        aggregate3Call::abi_decode_returns(raw).context("multicall returned invalid data")?;
+    if results.len() != expected {
+        bail!(
+            "multicall returned {} results for {expected} calls",
+            results.len()
+        );
+    }
+    Ok(results
+        .into_iter()
+        .map(|result| {
+            if result.success {
+                Ok(result.returnData)
+            } else {
+                Err(format!(
+                    "execution reverted: 0x{}",
+                    hex::encode(&result.returnData)
+                ))
+            }
+        })
+        .collect())
+}
+
+fn multicall_deployed(state: &AppState) -> bool {
+    let chain_id = state.wallet.lock().expect("wallet").chain.chain_id;
+    if let Some(known) = DEPLOYED
+        .lock()
+        .expect("multicall deployments")
+        .as_ref()
+        .and_then(|known| known.get(&chain_id))
+    {
+        return *known;
+    }
+    let code = match rpc_request(
+        state,
+        "eth_getCode",
+        json!([format!("{MULTICALL3:#x}"), "latest"]),
+    ) {
+        Ok(code) => code,
+        Err(err) => {
+            // Not cached, so the next read checks again.
+            tracing::debug!(chain_id, error = %format!("{err:#}"), "multicall3 lookup failed");
+            return false;
+        }
+    };
+    let deployed = code.as_str().is_some_and(|code| code.len() > 2);
+    tracing::info!(chain_id, deployed, "multicall3 availability checked");
+    DEPLOYED
+        .lock()
+        .expect("multicall deployments")
+        .get_or_insert_with(HashMap::new)
+        .insert(chain_id, deployed);
+    deployed
+}
+
+fn eth_call(state: &AppState, to: Address, data: Bytes) -> Result<Bytes> {
+    let result = rpc_request(
+        state,
+        "eth_call",
+        json!([{ "to": format!("{to:#x}"), "data": data }, "latest"]),
+    )?;
+    let hex_str = result
+        .as_str()
+        .ok_or_else(|| anyhow!("eth_call returned non-string data"))?;
+    hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str))
+        .map(Bytes::from)
+        .context("eth_call returned bad hex")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Call3Result, ReadCall, aggregate3Call, decode_batch, encode_batch};
+    use alloy_primitives::{Address, Bytes};
+    use alloy_sol_types::SolCall;
+
+    #[test]
+    fn encoded_batches_let_each_call_fail() {
+        let calls = vec![
+            ReadCall {
+                to: Address::repeat_byte(0x11),
+                data: Bytes::from_static(&[0x70, 0xa0, 0x82, 0x31]),
+            },
+            ReadCall {
+                to: Address::repeat_byte(0x22),
+                data: Bytes::from_static(&[0xdd, 0x62, 0xed, 0x3e]),
+            },
+        ];
+        let encoded = aggregate3Call::abi_decode(&encode_batch(&calls)).unwrap();
+        assert_eq!(encoded.calls.len(), 2);
+        assert!(encoded.calls.iter().all(|call| call.allowFailure));
+        assert_eq!(encoded.calls[1].target, Address::repeat_byte(0x22));
+    }
+
+    /// One call that returned `0x01` and one that reverted with `0x08c3`.
+    fn returned() -> Vec<u8> {
+        aggregate3Call::abi_encode_returns(&vec![
+            Call3Result {
+                success: true,
+                returnData: Bytes::from_static(&[0x01]),
+            },
+            Call3Result {
+                success: false,
+                returnData: Bytes::from_static(&[0x08, 0xc3]),
+            },
+        ])
+    }
+
+    #[test]
+    fn a_reverted_call_does_not_fail_the_batch() {
+        let results = decode_batch(2, &returned()).unwrap();
+        assert_eq!(results[0], Ok(Bytes::from_static(&[0x01])));
+        assert_eq!(results[1], Err("execution reverted: 0x08c3".to_string()));
+    }
+
+    #[test]
+    fn results_must_match_the_call_count() {
+        assert!(decode_batch(3, &returned()).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::{Context, Result, bail};
+use std::collections::{HashMap, VecDeque};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// At most `RATE_LIMIT` notifications per tab within `RATE_WINDOW`.
+const RATE_LIMIT: usize = 5;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+static SENT: Mutex<Option<HashMap<String, VecDeque<Instant>>>> = Mutex::new(None);
+/// Answers to the notification prompt for this session, per tab.
+static SESSION_GRANTS: Mutex<Option<HashMap<String, bool>>> = Mutex::new(None);
+
+fn allow(sent: &mut VecDeque<Instant>, now: Instant) -> bool {
+    while sent
+        .front()
+        .is_some_and(|at| now.duration_since(*at) >= RATE_WINDOW)
+    {
+        sent.pop_front();
+    }
+    if sent.len() >= RATE_LIMIT {
+        return false;
+    }
+    sent.push_back(now);
+    true
+}
+
+/// Counts a notification from `webview_id` against its rate limit.
+pub fn reserve_slot(webview_id: &str) -> Result<()> {
+    let mut sent = SENT.lock().expect("notifications_sent");
+    let history = sent
+        .get_or_insert_with(HashMap::new)
+        .entry(webview_id.to_string())
+        .or_default();
+    if !allow(history, Instant::now()) {
+        bail!(
+            "notification rate limit exceeded ({RATE_LIMIT} per {}s)",
+            RATE_WINDOW.as_secs()
+        );
+    }
+    Ok(())
+}
+
+pub fn session_permission(webview_id: &str) -> Option<bool> {
+    SESSION_GRANTS
+        .lock()
+        .expect("notification_grants")
+        .as_ref()
+        .and_then(|grants| grants.get(webview_id).copied())
+}
+
+pub fn set_session_permission(webview_id: &str, granted: bool) {
+    SESSION_GRANTS
+        .lock()
+        .expect("notification_grants")
+        .get_or_insert_with(HashMap::new)
+        .insert(webview_id.to_string(), granted);
+}
+
+/// Forgets the tab's answer so its next notification asks again.
+pub fn clear_session_permission(webview_id: &str) {
+    if let Some(grants) = SESSION_GRANTS.lock().expect("notification_grants").as_mut() {
+        grants.remove(webview_id);
+    }
+}
+
+/// Drops the rate limit history and session answer of a closed tab.
+pub fn forget_webview(webview_id: &str) {
+    if let Some(sent) = SENT.lock().expect("notifications_sent").as_mut() {
+        sent.remove(webview_id);
+    }
+    if let Some(grants) = SESSION_GRANTS.lock().expect("notification_grants").as_mut() {
+        grants.remove(webview_id);
+    }
+}
+
+fn run(program: &str, args: &[&str], envs: &[(&str, &str)]) -> Result<()> {
+    let status = Command::new(program)
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .with_context(|| format!("failed to run '{program}'"))?;
+    if !status.success() {
+        bail!("'{program}' exited with status {}", status);
+    }
+    Ok(())
+}
+
+/// Shows a desktop notification using the platform's notification tool.
+/// Title and body are passed as arguments, never spliced into a script.
+pub fn show(title: &str, body: &str) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        return run(
+            "osascript",
+            &[
+                "-e",
+                "on run argv",
+                "-e",
+                "display notification (item 2 of argv) with title (item 1 of argv)",
+                "-e",
+                "end run",
+                title,
+                body,
+            ],
+            &[],
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        const SCRIPT: &str = "Add-Type -AssemblyName System.Windows.Forms; \
+            $n = New-Object System.Windows.Forms.NotifyIcon; \
+            $n.Icon = [System.Drawing.SystemIcons]::Information; \
+            $n.Visible = $true; \
+            $n.ShowBalloonTip(10000, $env:VIBEFI_NOTIFY_TITLE, $env:VIBEFI_NOTIFY_BODY, 'Info'); \
+            Start-Sleep -Seconds 10; \
+            $n.Dispose()";
+        return run(
+            "powershell",
+            &["-NoProfile", "-NonInteractive", "-Command", SCRIPT],
+            &[("VIBEFI_NOTIFY_TITLE", title), ("VIBEFI_NOTIFY_BODY", body)],
+        );
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        return run(
+            "notify-send",
+            &["--app-name=VibeFi", "--", title, body],
+            &[],
+        )
+        .context("notify-send unavailable (install libnotify)");
+    }
+
+    #[allow(unreachable_code)]
+    {
+        bail!("notifications are not supported on this platform")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RATE_LIMIT, RATE_WINDOW, allow};
+    use std::collections::VecDeque;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn rate_limit_slides_with_the_window() {
+        let start = Instant::now();
+        let mut sent = VecDeque::new();
+        for _ in 0..RATE_LIMIT {
+            assert!(allow(&mut sent, start));
+        }
+        assert!(!allow(&mut sent, start + Duration::from_secs(1)));
+        assert!(allow(&mut sent, start + RATE_WINDOW));
+        assert_eq!(sent.len(), 1);
+    }
+}
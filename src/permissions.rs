@@ -0,0 +1,500 @@
+use anyhow::{Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::state::{AppRuntimeCapabilities, AppState, DappIdentity, IpfsCapabilityRule, UserEvent};
+use crate::webview_manager::WebViewManager;
+
+/// Prompt kind under which revoked manifest capabilities are remembered as
+/// denied, next to the user's other remembered decisions.
+const CAPABILITY_KIND: &str = "capability";
+const NOTIFICATIONS_KIND: &str = "notifications";
+
+/// Something the client granted a dapp that the user can take back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GrantKind {
+    Accounts,
+    Ipfs,
+    Network,
+    Notifications,
+    Camera,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Grant {
+    pub kind: GrantKind,
+    /// Identifies the item when revoking it (account, origin, rule).
+    pub value: String,
+    pub label: String,
+}
+
+/// Everything granted to one dapp, across its open tabs and remembered choices.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DappGrants {
+    pub subject: String,
+    pub name: String,
+    pub open_tabs: usize,
+    pub grants: Vec<Grant>,
+}
+
+/// Who permissions are kept for: the registry dapp, else the bundle. Tabs
+/// with neither (local bundles without a CID) and unverified launches cannot
+/// keep permissions.
+pub fn subject_for(identity: &DappIdentity) -> Option<String> {
+    if identity.unverified {
+        return None;
+    }
+    match (&identity.dapp_id, &identity.root_cid) {
+        (Some(dapp_id), _) => Some(match &identity.registry {
+            Some(registry) => format!("dapp:{registry}/{dapp_id}"),
+            None => format!("dapp:{dapp_id}"),
+        }),
+        (None, Some(root_cid)) => Some(format!("bundle:{root_cid}")),
+        (None, None) => None,
+    }
+}
+
+pub fn ipfs_rule_label(rule: &IpfsCapabilityRule) -> String {
+    format!(
+        "{}/{{{}}} as {}",
+        rule.cid.as_deref().unwrap_or("*"),
+        rule.paths.join(","),
+        rule.as_kinds.join(",")
+    )
+}
+
+fn revocation_key(subject: &str, kind: GrantKind, value: &str) -> String {
+    let scope = match kind {
+        GrantKind::Camera => "camera".to_string(),
+        GrantKind::Network => format!("network:{value}"),
+        GrantKind::Ipfs => format!("ipfs:{value}"),
+        GrantKind::Accounts | GrantKind::Notifications => value.to_string(),
+    };
+    format!("{subject}|{CAPABILITY_KIND}|{scope}")
+}
+
+/// Drops the manifest capabilities the user revoked for `subject`.
+pub fn apply_revocations(
+    remembered: &BTreeMap<String, bool>,
+    subject: &str,
+    caps: &mut AppRuntimeCapabilities,
+) {
+    let revoked =
+        |kind, value: &str| remembered.get(&revocation_key(subject, kind, value)) == Some(&false);
+    caps.network_allow
+        .retain(|origin| !revoked(GrantKind::Network, origin));
+    caps.ipfs_allow
+        .retain(|rule| !revoked(GrantKind::Ipfs, &ipfs_rule_label(rule)));
+    if revoked(GrantKind::Camera, "") {
+        caps.qr_scan = false;
+    }
+}
+
+/// Capabilities a newly opened tab of `identity` gets: its manifest's, minus
+/// what the user revoked. Unverified launches get none.
+pub fn restrict_capabilities(
+    state: &AppState,
+    identity: Option<&DappIdentity>,
+    mut caps: AppRuntimeCapabilities,
+) -> AppRuntimeCapabilities {
+    if identity.is_some_and(|identity| identity.unverified) {
+        tracing::info!("unverified dapp; manifest capabilities not granted");
+        // Chain declarations only restrict the dapp, so they still apply.
+        return AppRuntimeCapabilities {
+            chains: caps.chains,
+            ..AppRuntimeCapabilities::default()
+        };
+    }
+    drop_loopback_origins(identity, &mut caps);
+    let subject = identity.and_then(subject_for);
+    let config_path = state.resolved.as_ref().and_then(|r| r.config_path.as_ref());
+    if let (Some(subject), Some(config_path)) = (subject, config_path) {
+        let settings = crate::settings::load_settings(config_path);
+        apply_revocations(&settings.remembered_prompts, &subject, &mut caps);
+    }
+    caps
+}
+
+/// Removes `network.allow` origins on this machine unless the tab was built
+/// from a local project; a published dapp must not reach local services.
+fn drop_loopback_origins(identity: Option<&DappIdentity>, caps: &mut AppRuntimeCapabilities) {
+    if identity.is_some_and(|identity| identity.local_project) {
+        return;
+    }
+    caps.network_allow.retain(|origin| {
+        let local = crate::ipc::is_loopback_origin(origin);
+        if local {
+            tracing::warn!(
+                origin,
+                "loopback network origin is only granted to local projects"
+            );
+        }
+        !local
+    });
+}
+
+fn open_tabs<'a>(manager: &'a WebViewManager, subject: &str) -> Vec<&'a str> {
+    manager
+        .apps
+        .iter()
+        .filter(|entry| entry.identity.as_ref().and_then(subject_for).as_deref() == Some(subject))
+        .map(|entry| entry.id.as_str())
+        .collect()
+}
+
+fn push_unique(grants: &mut Vec<Grant>, grant: Grant) {
+    if !grants
+        .iter()
+        .any(|g| g.kind == grant.kind && g.value == grant.value)
+    {
+        grants.push(grant);
+    }
+}
+
+/// Grants of every dapp with an open tab or a remembered "allow".
+pub fn list(state: &AppState, manager: &WebViewManager) -> Vec<DappGrants> {
+    let remembered = state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.config_path.as_ref())
+        .map(|path| crate::settings::load_settings(path).remembered_prompts)
+        .unwrap_or_default();
+    let chain_id = state.chain_id_hex();
+    let mut dapps: Vec<DappGrants> = Vec::new();
+
+    for entry in &manager.apps {
+        let Some(identity) = entry.identity.as_ref() else {
+            continue;
+        };
+        let Some(subject) = subject_for(identity) else {
+            continue;
+        };
+        let index = match dapps.iter().position(|d| d.subject == subject) {
+            Some(index) => index,
+            None => {
+                dapps.push(DappGrants {
+                    subject,
+                    name: identity.name.clone(),
+                    open_tabs: 0,
+                    grants: Vec::new(),
+                });
+                dapps.len() - 1
+            }
+        };
+        let dapp = &mut dapps[index];
+        dapp.open_tabs += 1;
+        for account in state.accounts_for(&entry.id) {
+            let label = format!("{account} on chain {chain_id}");
+            push_unique(
+                &mut dapp.grants,
+                Grant {
+                    kind: GrantKind::Accounts,
+                    value: account,
+                    label,
+                },
+            );
+        }
+        if crate::notifications::session_permission(&entry.id) == Some(true) {
+            push_unique(&mut dapp.grants, notifications_grant());
+        }
+        let Some(caps) = state.app_capabilities_for(&entry.id) else {
+            continue;
+        };
+        for origin in caps.network_allow {
+            push_unique(
+                &mut dapp.grants,
+                Grant {
+                    kind: GrantKind::Network,
+                    label: format!("Fetch from {origin}"),
+                    value: origin,
+                },
+            );
+        }
+        for rule in &caps.ipfs_allow {
+            let value = ipfs_rule_label(rule);
+            push_unique(
+                &mut dapp.grants,
+                Grant {
+                    kind: GrantKind::Ipfs,
+                    label: format!("Read IPFS {value}"),
+                    value,
+                },
+            );
+        }
+        if caps.qr_scan {
+            push_unique(
+                &mut dapp.grants,
+                Grant {
+                    kind: GrantKind::Camera,
+                    value: String::new(),
+                    label: "Camera (QR scanner)".to_string(),
+                },
+            );
+        }
+    }
+
+    // Remembered notification grants outlive the tabs that asked.
+    for (key, approved) in &remembered {
+        let mut parts = key.splitn(3, '|');
+        let (Some(subject), Some(kind)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        if !approved || kind != NOTIFICATIONS_KIND || subject == "host" {
+            continue;
+        }
+        match dapps.iter_mut().find(|d| d.subject == subject) {
+            Some(dapp) => push_unique(&mut dapp.grants, notifications_grant()),
+            None => dapps.push(DappGrants {
+                subject: subject.to_string(),
+                name: subject.to_string(),
+                open_tabs: 0,
+                grants: vec![notifications_grant()],
+            }),
+        }
+    }
+
+    dapps.retain(|dapp| !dapp.grants.is_empty());
+    dapps
+}
+
+fn notifications_grant() -> Grant {
+    Grant {
+        kind: GrantKind::Notifications,
+        value: String::new(),
+        label: "Desktop notifications".to_string(),
+    }
+}
+
+/// Takes one grant back from `subject`: disconnects the account from its tabs
+/// (announcing it with `accountsChanged`), forgets the notification
+/// permission, or removes the capability from open tabs and future launches.
+pub fn revoke(
+    state: &AppState,
+    manager: &WebViewManager,
+    subject: &str,
+    kind: GrantKind,
+    value: &str,
+) -> Result<()> {
+    let tabs = open_tabs(manager, subject);
+    tracing::info!(
+        target: "vibefi::audit",
+        subject,
+        ?kind,
+        value,
+        tabs = tabs.len(),
+        "dapp permission revoked"
+    );
+    match kind {
+        GrantKind::Accounts => {
+            for webview_id in tabs {
+                if state.remove_tab_account(webview_id, value) {
+                    notify_accounts_changed(state, webview_id);
+                }
+            }
+            Ok(())
+        }
+        GrantKind::Notifications => {
+            for webview_id in tabs {
+                crate::notifications::clear_session_permission(webview_id);
+            }
+            update_remembered(state, |remembered| {
+                remembered
+                    .retain(|key, _| !key.starts_with(&format!("{subject}|{NOTIFICATIONS_KIND}|")));
+            })
+        }
+        GrantKind::Network | GrantKind::Ipfs | GrantKind::Camera => {
+            let key = revocation_key(subject, kind, value);
+            update_remembered(state, |remembered| {
+                remembered.insert(key.clone(), false);
+            })?;
+            let remembered = BTreeMap::from([(key, false)]);
+            let mut caps = state.app_capabilities.lock().expect("app_capabilities");
+            for webview_id in tabs {
+                if let Some(caps) = caps.get_mut(webview_id) {
+                    apply_revocations(&remembered, subject, caps);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Returns `subject` to a fresh install: disconnects its accounts, forgets
+/// every remembered decision about it and restores its manifest capabilities.
+pub fn reset(state: &AppState, manager: &WebViewManager, subject: &str) -> Result<()> {
+    tracing::info!(target: "vibefi::audit", subject, "dapp permissions reset");
+    update_remembered(state, |remembered| {
+        remembered.retain(|key, _| !key.starts_with(&format!("{subject}|")));
+    })?;
+    for entry in manager
+        .apps
+        .iter()
+        .filter(|entry| entry.identity.as_ref().and_then(subject_for).as_deref() == Some(subject))
+    {
+        if state.unbind_tab_wallet(&entry.id).is_some() {
+            notify_accounts_changed(state, &entry.id);
+        }
+        crate::notifications::clear_session_permission(&entry.id);
+        if let Some(dist_dir) = entry.source.as_ref().and_then(|s| s.dist_dir.as_deref()) {
+            let caps = restrict_capabilities(
+                state,
+                entry.identity.as_ref(),
+                crate::events::user_event::load_app_capabilities_from_dist(dist_dir),
+            );
+            state
+                .app_capabilities
+                .lock()
+                .expect("app_capabilities")
+                .insert(entry.id.clone(), caps);
+        }
+    }
+    Ok(())
+}
+
+fn update_remembered(
+    state: &AppState,
+    update: impl FnOnce(&mut BTreeMap<String, bool>),
+) -> Result<()> {
+    let Some(config_path) = state.resolved.as_ref().and_then(|r| r.config_path.as_ref()) else {
+        return Ok(());
+    };
+    let mut settings = crate::settings::load_settings(config_path);
+    update(&mut settings.remembered_prompts);
+    crate::settings::save_settings(config_path, &settings)
+}
+
+fn notify_accounts_changed(state: &AppState, webview_id: &str) {
+    let accounts = state.accounts_for(webview_id);
+    let _ = state.proxy.send_event(UserEvent::ProviderEvent {
+        webview_id: webview_id.to_string(),
+        event: "accountsChanged".to_string(),
+        value: Value::Array(accounts.into_iter().map(Value::String).collect()),
+    });
+}
+
+/// Parses the `[subject, kind, value]` params of a revoke request.
+pub fn parse_revoke_params(params: &Value) -> Result<(String, GrantKind, String)> {
+    let subject = params
+        .get(0)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| anyhow!("missing dapp"))?;
+    let kind: GrantKind = params
+        .get(1)
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()?
+        .ok_or_else(|| anyhow!("missing permission kind"))?;
+    let value = params.get(2).and_then(Value::as_str).unwrap_or_default();
+    if kind == GrantKind::Accounts && value.is_empty() {
+        bail!("missing account to revoke");
+    }
+    Ok((subject.to_string(), kind, value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        GrantKind, apply_revocations, drop_loopback_origins, ipfs_rule_label, revocation_key,
+    };
+    use crate::state::{AppRuntimeCapabilities, DappIdentity, IpfsCapabilityRule};
+    use std::collections::BTreeMap;
+
+    fn meta_rule() -> IpfsCapabilityRule {
+        IpfsCapabilityRule {
+            cid: None,
+            paths: vec!["meta.json".to_string()],
+            as_kinds: vec!["json".to_string()],
+            max_bytes: None,
+        }
+    }
+
+    fn granted_caps() -> AppRuntimeCapabilities {
+        AppRuntimeCapabilities {
+            network_allow: vec![
+                "https://api.example".to_string(),
+                "https://cdn.example".to_string(),
+            ],
+            ipfs_allow: vec![meta_rule()],
+            qr_scan: true,
+            ..AppRuntimeCapabilities::default()
+        }
+    }
+
+    /// Revocations of one network origin, the IPFS rule and the camera for
+    /// `dapp:7`.
+    fn revoked_for_dapp_7() -> BTreeMap<String, bool> {
+        let subject = "dapp:7";
+        BTreeMap::from([
+            (
+                revocation_key(subject, GrantKind::Network, "https://api.example"),
+                false,
+            ),
+            (
+                revocation_key(subject, GrantKind::Ipfs, &ipfs_rule_label(&meta_rule())),
+                false,
+            ),
+            (revocation_key(subject, GrantKind::Camera, ""), false),
+        ])
+    }
+
+    #[test]
+    fn revoked_capabilities_are_dropped() {
+        let mut restricted = granted_caps();
+        apply_revocations(&revoked_for_dapp_7(), "dapp:7", &mut restricted);
+        assert_eq!(restricted.network_allow, ["https://cdn.example"]);
+        assert!(restricted.ipfs_allow.is_empty());
+        assert!(!restricted.qr_scan);
+    }
+
+    #[test]
+    fn revocations_do_not_apply_to_other_dapps() {
+        let mut other = granted_caps();
+        apply_revocations(&revoked_for_dapp_7(), "dapp:8", &mut other);
+        assert_eq!(other.network_allow.len(), 2);
+        assert!(other.qr_scan);
+    }
+
+    fn caps_with_local_origin() -> AppRuntimeCapabilities {
+        AppRuntimeCapabilities {
+            network_allow: vec![
+                "https://api.example".to_string(),
+                "http://localhost:8080".to_string(),
+                "http://127.0.0.1:8545".to_string(),
+            ],
+            ..AppRuntimeCapabilities::default()
+        }
+    }
+
+    #[test]
+    fn published_dapps_are_not_granted_loopback_origins() {
+        let registry_dapp = DappIdentity {
+            name: "Swap".to_string(),
+            dapp_id: Some("7".to_string()),
+            ..DappIdentity::default()
+        };
+        let mut caps = caps_with_local_origin();
+        drop_loopback_origins(Some(&registry_dapp), &mut caps);
+        assert_eq!(caps.network_allow, ["https://api.example"]);
+
+        let mut caps = caps_with_local_origin();
+        drop_loopback_origins(None, &mut caps);
+        assert_eq!(caps.network_allow, ["https://api.example"]);
+    }
+
+    #[test]
+    fn local_projects_keep_loopback_origins() {
+        let project = DappIdentity {
+            name: "My dapp".to_string(),
+            local_project: true,
+            ..DappIdentity::default()
+        };
+        let mut caps = caps_with_local_origin();
+        drop_loopback_origins(Some(&project), &mut caps);
+        assert_eq!(caps.network_allow.len(), 3);
+    }
+}
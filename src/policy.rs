@@ -0,0 +1,273 @@
+//! Auto-approval rules for transaction prompts on development chains, e.g.
+//! "approve anything under 0.01 ETH on 31337 from this dapp". Rules are
+//! checked on the event loop before a prompt is queued. Chains that are not
+//! a local devnet or the configured test network always prompt, whatever
+//! the rules say. A transfer's amount is in its calldata rather than its
+//! value, so contract calls prompt unless the rule allows them.
+
+use alloy_primitives::U256;
+use alloy_primitives::utils::{format_units, parse_units};
+use alloy_rpc_types_eth::TransactionRequest;
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::prompt::PromptRequest;
+use crate::state::AppState;
+
+/// Chain ids of local devnets (Anvil/Hardhat and Ganache/Geth dev mode).
+pub(crate) const DEVNET_CHAIN_IDS: &[u64] = &[31337, 1337];
+
+/// Approves transaction prompts on `chain_id` whose native value is at most
+/// `max_value`, from one dapp or from any.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalRule {
+    pub chain_id: u64,
+    /// Permission subject of the dapp, see [`crate::permissions::subject_for`];
+    /// any dapp tab when unset. Transactions the client sends itself always
+    /// prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dapp: Option<String>,
+    /// Dapp name shown in Settings; not used for matching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dapp_name: Option<String>,
+    /// Largest value approved, in ether, e.g. `"0.01"`.
+    pub max_value: String,
+    /// Also approve transactions with calldata. Off by default, since value
+    /// says nothing about what a token `transfer` or `approve` moves.
+    #[serde(default)]
+    pub allow_calldata: bool,
+}
+
+impl ApprovalRule {
+    fn max_value_wei(&self) -> Option<U256> {
+        let value = self.max_value.trim();
+        if value.starts_with('-') {
+            return None;
+        }
+        parse_units(value, 18).ok().map(Into::into)
+    }
+
+    fn matches(&self, subject: Option<&str>, tx: &TransactionFacts) -> bool {
+        self.chain_id == tx.chain_id
+            && self
+                .dapp
+                .as_deref()
+                .is_none_or(|dapp| subject == Some(dapp))
+            && self.max_value_wei().is_some_and(|max| tx.value <= max)
+            && (self.allow_calldata || !tx.has_calldata)
+    }
+}
+
+/// What a transaction prompt is checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionFacts {
+    /// The active chain, which the transaction is sent on.
+    pub chain_id: u64,
+    pub value: U256,
+    pub has_calldata: bool,
+}
+
+impl TransactionFacts {
+    /// Facts of `tx` as it will be sent on `active_chain_id`. A `chainId` the
+    /// dapp set for another chain is refused rather than trusted, so rules for
+    /// a devnet can't approve what lands elsewhere.
+    pub fn for_request(active_chain_id: u64, tx: &TransactionRequest) -> Result<Self> {
+        if let Some(chain_id) = tx.chain_id.filter(|chain_id| *chain_id != active_chain_id) {
+            bail!(
+                "transaction is for chain {chain_id} but the wallet is on chain {active_chain_id}"
+            );
+        }
+        Ok(Self {
+            chain_id: active_chain_id,
+            value: tx.value.unwrap_or_default(),
+            has_calldata: tx.input.input().is_some_and(|input| !input.is_empty()),
+        })
+    }
+}
+
+/// Whether rules may apply on `chain_id`.
+pub fn is_dev_chain(state: &AppState, chain_id: u64) -> bool {
+    DEVNET_CHAIN_IDS.contains(&chain_id)
+        || state
+            .resolved
+            .as_deref()
+            .is_some_and(|r| r.test_network && r.chain_id == chain_id)
+}
+
+/// Chains rules can be written for with this config.
+pub fn dev_chains(state: &AppState) -> Vec<u64> {
+    let mut chains = DEVNET_CHAIN_IDS.to_vec();
+    if let Some(resolved) = state
+        .resolved
+        .as_deref()
+        .filter(|r| r.test_network && !chains.contains(&r.chain_id))
+    {
+        chains.insert(0, resolved.chain_id);
+    }
+    chains
+}
+
+/// Checks rules from Settings and writes their values in canonical form.
+/// Rules for chains that always prompt are refused.
+pub fn normalize_rules(state: &AppState, rules: Vec<ApprovalRule>) -> Result<Vec<ApprovalRule>> {
+    rules
+        .into_iter()
+        .map(|mut rule| {
+            if !is_dev_chain(state, rule.chain_id) {
+                bail!(
+                    "chain {} is not a devnet or this profile's test network; it always prompts",
+                    rule.chain_id
+                );
+            }
+            let max = rule
+                .max_value_wei()
+                .with_context(|| format!("invalid amount: {}", rule.max_value))?;
+            rule.max_value = format_units(max, 18)
+                .map_err(|err| anyhow!("invalid amount: {err}"))?
+                .trim_end_matches('0')
+                .trim_end_matches('.')
+                .to_string();
+            rule.dapp = rule
+                .dapp
+                .map(|dapp| dapp.trim().to_string())
+                .filter(|dapp| !dapp.is_empty());
+            Ok(rule)
+        })
+        .collect()
+}
+
+/// The rule approving `request`, if any. Only dapp transaction prompts on
+/// dev chains qualify.
+pub fn approving_rule(state: &AppState, request: &PromptRequest) -> Option<ApprovalRule> {
+    let tx = request.transaction?;
+    request.webview_id.as_ref()?;
+    if !is_dev_chain(state, tx.chain_id) {
+        return None;
+    }
+    let config_path = state.resolved.as_ref()?.config_path.as_deref()?;
+    let subject = request
+        .requester
+        .as_ref()
+        .and_then(crate::permissions::subject_for);
+    crate::settings::load_settings(config_path)
+        .approval_rules
+        .into_iter()
+        .find(|rule| rule.matches(subject.as_deref(), &tx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApprovalRule, TransactionFacts};
+    use alloy_primitives::{Bytes, U256};
+    use alloy_rpc_types_eth::TransactionRequest;
+
+    /// Up to 0.01 ETH from `dapp:7` on the devnet.
+    fn devnet_rule() -> ApprovalRule {
+        ApprovalRule {
+            chain_id: 31337,
+            dapp: Some("dapp:7".to_string()),
+            dapp_name: None,
+            max_value: "0.01".to_string(),
+            allow_calldata: false,
+        }
+    }
+
+    fn tx(chain_id: u64, value: u64) -> TransactionFacts {
+        TransactionFacts {
+            chain_id,
+            value: U256::from(value),
+            has_calldata: false,
+        }
+    }
+
+    const LIMIT: u64 = 10_000_000_000_000_000;
+
+    #[test]
+    fn rules_match_values_up_to_their_cap() {
+        let rule = devnet_rule();
+        assert!(rule.matches(Some("dapp:7"), &tx(31337, LIMIT)));
+        assert!(rule.matches(Some("dapp:7"), &tx(31337, 0)));
+        assert!(!rule.matches(Some("dapp:7"), &tx(31337, LIMIT + 1)));
+    }
+
+    #[test]
+    fn rules_match_their_dapp_only() {
+        let rule = devnet_rule();
+        assert!(!rule.matches(Some("dapp:8"), &tx(31337, 1)));
+        assert!(!rule.matches(None, &tx(31337, 1)));
+    }
+
+    #[test]
+    fn rules_match_their_chain_only() {
+        assert!(!devnet_rule().matches(Some("dapp:7"), &tx(1, 1)));
+    }
+
+    #[test]
+    fn rules_without_a_dapp_match_any_dapp() {
+        let any_dapp = ApprovalRule {
+            dapp: None,
+            ..devnet_rule()
+        };
+        assert!(any_dapp.matches(None, &tx(31337, 1)));
+    }
+
+    #[test]
+    fn rules_with_an_invalid_cap_match_nothing() {
+        let invalid = ApprovalRule {
+            dapp: None,
+            max_value: "lots".to_string(),
+            ..devnet_rule()
+        };
+        assert!(!invalid.matches(None, &tx(31337, 0)));
+    }
+
+    #[test]
+    fn facts_use_the_active_chain() {
+        // A mainnet tab claiming to be on the devnet is refused outright.
+        let spoofed = TransactionRequest {
+            chain_id: Some(31337),
+            ..Default::default()
+        };
+        assert!(TransactionFacts::for_request(1, &spoofed).is_err());
+        let facts = TransactionFacts::for_request(31337, &spoofed).unwrap();
+        assert_eq!(facts.chain_id, 31337);
+        assert!(!facts.has_calldata);
+    }
+
+    /// An ERC-20 transfer, which carries no value.
+    fn token_transfer() -> TransactionFacts {
+        let transfer = TransactionRequest::default().input(
+            Bytes::from(
+                hex::decode(
+                    "a9059cbb0000000000000000000000000000000000000000000000000000000000000001",
+                )
+                .unwrap(),
+            )
+            .into(),
+        );
+        let facts = TransactionFacts::for_request(31337, &transfer).unwrap();
+        assert!(facts.has_calldata);
+        facts
+    }
+
+    fn value_rule(allow_calldata: bool) -> ApprovalRule {
+        ApprovalRule {
+            chain_id: 31337,
+            dapp: None,
+            dapp_name: None,
+            max_value: "1".to_string(),
+            allow_calldata,
+        }
+    }
+
+    #[test]
+    fn calls_do_not_pass_a_value_cap() {
+        assert!(!value_rule(false).matches(Some("dapp:7"), &token_transfer()));
+    }
+
+    #[test]
+    fn calls_pass_rules_that_allow_them() {
+        assert!(value_rule(true).matches(Some("dapp:7"), &token_transfer()));
+    }
+}
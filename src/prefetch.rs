@@ -0,0 +1,147 @@
+use anyhow::{Result, anyhow};
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::{Condvar, Mutex},
+    time::Duration,
+};
+
+use crate::cache_manager::{budget_bytes, evict_to_budget};
+use crate::registry::dapp_key;
+use crate::settings::{load_settings, save_settings};
+use crate::state::AppState;
+
+/// How often pinned dapps are checked for a newer bundle.
+const PREFETCH_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// Leave startup bandwidth to whatever the user opens first.
+const STARTUP_DELAY: Duration = Duration::from_secs(20);
+
+static WAKE: Mutex<bool> = Mutex::new(false);
+static WAKE_SIGNAL: Condvar = Condvar::new();
+
+/// Runs a prefetch pass now instead of waiting for the next interval, e.g.
+/// after the user pins a dapp or changes the prefetch settings.
+pub fn request_prefetch() {
+    *WAKE.lock().expect("prefetch_wake") = true;
+    WAKE_SIGNAL.notify_one();
+}
+
+pub fn spawn_prefetch_worker(state: AppState) {
+    if state.resolved.is_none() {
+        return;
+    }
+    std::thread::spawn(move || {
+        wait_for_wake(STARTUP_DELAY);
+        loop {
+            if let Err(err) = prefetch_pinned(&state) {
+                tracing::warn!(error = %format!("{err:#}"), "pinned dapp prefetch failed");
+            }
+            wait_for_wake(PREFETCH_INTERVAL);
+        }
+    });
+}
+
+fn wait_for_wake(timeout: Duration) {
+    let guard = WAKE.lock().expect("prefetch_wake");
+    let (mut woken, _) = WAKE_SIGNAL
+        .wait_timeout_while(guard, timeout, |woken| !*woken)
+        .expect("prefetch_wake");
+    *woken = false;
+}
+
+/// Resolves the latest bundle of every pinned dapp, then fetches, verifies and
+/// builds the ones not cached yet while keeping the cache within budget.
+fn prefetch_pinned(state: &AppState) -> Result<()> {
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("Network not configured"))?;
+    let Some(config_path) = resolved.config_path.as_deref() else {
+        return Ok(());
+    };
+    let settings = load_settings(config_path);
+    if settings.pinned_dapps.is_empty() {
+        return Ok(());
+    }
+    if settings.metered_connection {
+        tracing::debug!("skipping pinned dapp prefetch on metered connection");
+        return Ok(());
+    }
+
+    let dapps = crate::connectivity::retry_when_online("registry refresh", || {
+        crate::registry::list_dapps(state)
+    })?;
+    let mut latest = Vec::new();
+    for pin in &settings.pinned_dapps {
+        let Some(dapp) = dapps
+            .iter()
+            .find(|dapp| dapp.dapp_id == pin.dapp_id && dapp.registry == pin.registry)
+        else {
+            continue;
+        };
+        if dapp.status == "Published" && !dapp.root_cid.is_empty() {
+            latest.push((
+                dapp_key(pin.registry.as_deref(), &pin.dapp_id),
+                dapp.name.clone(),
+                dapp.root_cid.clone(),
+            ));
+        }
+    }
+    record_latest_bundles(config_path, &latest)?;
+
+    let keep: HashSet<String> = latest.iter().map(|(_, _, cid)| cid.clone()).collect();
+    let budget = budget_bytes(&settings);
+    for (dapp, _, root_cid) in &latest {
+        // The user may mark the connection metered while a pass is running.
+        if load_settings(config_path).metered_connection {
+            tracing::info!("pinned dapp prefetch stopped: connection marked metered");
+            break;
+        }
+        match crate::registry::prefetch_dapp_bundle(state, root_cid) {
+            Ok(()) => tracing::info!(dapp, root_cid, "prefetched pinned dapp bundle"),
+            Err(err) => tracing::warn!(
+                dapp,
+                root_cid,
+                error = %format!("{err:#}"),
+                "pinned dapp prefetch failed"
+            ),
+        }
+        if let Some(budget) = budget {
+            let mut protected = keep.clone();
+            protected.extend(state.bundles_in_use());
+            let used = evict_to_budget(&resolved.cache_dir, budget, &protected)?;
+            if used > budget {
+                tracing::warn!(
+                    used,
+                    budget,
+                    "pinned bundles exceed the cache budget; skipping remaining prefetches"
+                );
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Stores the latest root CID and name of each pinned dapp, keyed by
+/// [`dapp_key`], so it can be listed and launched without reaching the registry.
+fn record_latest_bundles(config_path: &Path, latest: &[(String, String, String)]) -> Result<()> {
+    // Re-read: the launcher may have changed the pins while the registry was queried.
+    let mut settings = load_settings(config_path);
+    let mut changed = false;
+    for pin in settings.pinned_dapps.iter_mut() {
+        let key = dapp_key(pin.registry.as_deref(), &pin.dapp_id);
+        let Some((_, name, root_cid)) = latest.iter().find(|(dapp, _, _)| *dapp == key) else {
+            continue;
+        };
+        if pin.root_cid != *root_cid || pin.name != *name {
+            pin.root_cid = root_cid.clone();
+            pin.name = name.clone();
+            changed = true;
+        }
+    }
+    if changed {
+        save_settings(config_path, &settings)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,326 @@
+//! Console for Studio's preview: evaluates an expression in a tab built from a
+//! local project and answers with a printable form of its value, so a
+//! developer can look at the running dapp's state without rebuilding it. Only
+//! with `--dev-profile`, which already opens devtools on those tabs.
+
+use anyhow::{Result, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wry::WebView;
+
+use crate::state::{AppState, UserEvent};
+use crate::webview_manager::{AppWebViewEntry, WebViewManager};
+
+/// Method the evaluated script answers with, on the tab's `vibefi-app` channel.
+pub const RESULT_METHOD: &str = "vibefi_previewEvalResult";
+/// How long Studio waits for an answer; covers awaited contract reads.
+const EVAL_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_EXPRESSION_BYTES: usize = 16 * 1024;
+/// Longest printed value passed back to Studio; the rest is cut.
+const MAX_RESULT_CHARS: usize = 64 * 1024;
+/// What the wrapper script evaluates to once it parsed and started.
+const STARTED: &str = "vibefi-eval-started";
+
+/// Studio's `vibefi_evalInPreview` request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalRequest {
+    /// A JavaScript expression; `await` is allowed.
+    pub expression: String,
+    /// The tab to evaluate in; the most recently shown local project tab if
+    /// unset.
+    #[serde(default)]
+    pub tab_id: Option<String>,
+}
+
+struct PendingEval {
+    studio_webview_id: String,
+    ipc_id: u64,
+    tab_id: String,
+}
+
+/// Evaluations waiting for their tab to answer, by eval id.
+#[derive(Clone, Default)]
+pub struct PreviewEvals {
+    next_id: Arc<AtomicU64>,
+    pending: Arc<Mutex<HashMap<u64, PendingEval>>>,
+}
+
+impl PreviewEvals {
+    fn insert(&self, eval: PendingEval) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.pending
+            .lock()
+            .expect("poisoned preview eval lock while adding an eval")
+            .insert(id, eval);
+        id
+    }
+
+    /// Takes the eval `id`, if it is still waiting and `from` is the tab it
+    /// was sent to; any webview with a live page could post a result.
+    fn take(&self, id: u64, from: Option<&str>) -> Option<PendingEval> {
+        let mut pending = self
+            .pending
+            .lock()
+            .expect("poisoned preview eval lock while taking an eval");
+        if from.is_some_and(|from| pending.get(&id).is_none_or(|eval| eval.tab_id != from)) {
+            return None;
+        }
+        pending.remove(&id)
+    }
+}
+
+fn answer(state: &AppState, eval: PendingEval, result: Result<Value, String>) {
+    let _ = state.proxy.send_event(UserEvent::RpcResult {
+        webview_id: eval.studio_webview_id,
+        ipc_id: eval.ipc_id,
+        result,
+    });
+}
+
+/// The local project tab to evaluate in, `tab_id` or the one shown last, and
+/// its webview.
+fn target_tab<'a>(
+    manager: &'a WebViewManager,
+    tab_id: Option<&str>,
+) -> Result<(&'a str, &'a WebView)> {
+    let is_local = |entry: &&AppWebViewEntry| {
+        entry
+            .identity
+            .as_ref()
+            .is_some_and(|identity| identity.local_project)
+    };
+    let entry = match tab_id {
+        Some(tab_id) => manager
+            .apps
+            .iter()
+            .find(|entry| entry.id == tab_id)
+            .filter(is_local),
+        None => manager
+            .apps
+            .iter()
+            .filter(is_local)
+            .filter(|entry| entry.webview.is_some())
+            .max_by_key(|entry| entry.last_active),
+    };
+    match entry {
+        Some(AppWebViewEntry {
+            id,
+            webview: Some(webview),
+            ..
+        }) => Ok((id.as_str(), webview)),
+        Some(_) => bail!("the preview tab is suspended; show it and try again"),
+        None => bail!("no open tab was built from a local project"),
+    }
+}
+
+/// Wraps `expression` so the tab posts its printed value back as
+/// [`RESULT_METHOD`]. The expression is embedded as source rather than passed
+/// to `eval`, which the dapp's CSP forbids; one that does not parse leaves
+/// the script without its [`STARTED`] value.
+fn wrap_expression(eval_id: u64, expression: &str) -> String {
+    format!(
+        r#"(() => {{
+  const post = (result) => window.ipc.postMessage(JSON.stringify({{
+    id: 0,
+    providerId: "vibefi-app",
+    method: "{RESULT_METHOD}",
+    params: [Object.assign({{ evalId: {eval_id} }}, result)],
+  }}));
+  const print = (value) => {{
+    if (value === undefined) return "undefined";
+    if (typeof value === "bigint") return `${{value}}n`;
+    if (typeof value === "function") return `[Function ${{value.name || "anonymous"}}]`;
+    if (typeof value === "symbol" || value instanceof Error) return String(value);
+    const seen = new WeakSet();
+    try {{
+      return JSON.stringify(value, (_key, inner) => {{
+        if (typeof inner === "bigint") return `${{inner}}n`;
+        if (typeof inner === "function") return `[Function ${{inner.name || "anonymous"}}]`;
+        if (inner && typeof inner === "object") {{
+          if (seen.has(inner)) return "[Circular]";
+          seen.add(inner);
+        }}
+        return inner;
+      }}, 2) ?? String(value);
+    }} catch (_err) {{
+      return String(value);
+    }}
+  }};
+  (async () => (
+{expression}
+  ))().then(
+    (value) => post({{ ok: true, type: typeof value, value: print(value).slice(0, {MAX_RESULT_CHARS}) }}),
+    (err) => post({{ ok: false, error: String((err && err.stack) || err).slice(0, {MAX_RESULT_CHARS}) }}),
+  );
+  return "{STARTED}";
+}})()"#
+    )
+}
+
+/// Starts `vibefi_evalInPreview` for Studio's `studio_webview_id`; the answer
+/// comes later as an `RpcResult`.
+pub fn start(
+    state: &AppState,
+    manager: &WebViewManager,
+    studio_webview_id: &str,
+    ipc_id: u64,
+    request: &EvalRequest,
+) -> Result<()> {
+    if !state.resolved.as_ref().is_some_and(|r| r.dev_profile) {
+        bail!("the preview console needs --dev-profile");
+    }
+    if request.expression.trim().is_empty() {
+        bail!("nothing to evaluate");
+    }
+    if request.expression.len() > MAX_EXPRESSION_BYTES {
+        bail!("expression is longer than {MAX_EXPRESSION_BYTES} bytes");
+    }
+    let (tab_id, webview) = target_tab(manager, request.tab_id.as_deref())?;
+    let evals = state.preview_evals.clone();
+    let eval_id = evals.insert(PendingEval {
+        studio_webview_id: studio_webview_id.to_string(),
+        ipc_id,
+        tab_id: tab_id.to_string(),
+    });
+    tracing::info!(tab = tab_id, eval_id, "evaluating in preview");
+
+    let script = wrap_expression(eval_id, &request.expression);
+    let callback_state = state.clone();
+    let started = webview.evaluate_script_with_callback(&script, move |value| {
+        if serde_json::from_str::<String>(&value).is_ok_and(|value| value == STARTED) {
+            return;
+        }
+        if let Some(eval) = callback_state.preview_evals.take(eval_id, None) {
+            answer(
+                &callback_state,
+                eval,
+                Err("the expression could not be evaluated; check its syntax".to_string()),
+            );
+        }
+    });
+    if let Err(err) = started {
+        evals.take(eval_id, None);
+        bail!("failed to evaluate in the preview tab: {err}");
+    }
+
+    let timeout_state = state.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(EVAL_TIMEOUT);
+        if let Some(eval) = timeout_state.preview_evals.take(eval_id, None) {
+            answer(
+                &timeout_state,
+                eval,
+                Err("the preview tab did not answer in time".to_string()),
+            );
+        }
+    });
+    Ok(())
+}
+
+/// The printed value or error in a tab's [`RESULT_METHOD`] message.
+fn eval_result(params: &Value) -> Result<Value, String> {
+    let cut = |text: &str| text.chars().take(MAX_RESULT_CHARS).collect::<String>();
+    if params.get("ok").and_then(Value::as_bool) == Some(true) {
+        Ok(json!({
+            "type": params.get("type").and_then(Value::as_str).unwrap_or("undefined"),
+            "value": cut(params.get("value").and_then(Value::as_str).unwrap_or_default()),
+        }))
+    } else {
+        Err(cut(params
+            .get("error")
+            .and_then(Value::as_str)
+            .unwrap_or("evaluation failed")))
+    }
+}
+
+/// Passes a tab's answer on to Studio. Answers for evals that timed out or
+/// were sent to another tab are dropped.
+pub fn finish(state: &AppState, tab_id: &str, params: &Value) {
+    let params = params.get(0).unwrap_or(params);
+    let Some(eval_id) = params.get("evalId").and_then(Value::as_u64) else {
+        return;
+    };
+    let Some(eval) = state.preview_evals.take(eval_id, Some(tab_id)) else {
+        tracing::debug!(
+            tab = tab_id,
+            eval_id,
+            "dropping unexpected preview eval result"
+        );
+        return;
+    };
+    answer(state, eval, eval_result(params));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PendingEval, PreviewEvals, eval_result, wrap_expression};
+    use serde_json::json;
+
+    fn pending(tab_id: &str) -> PendingEval {
+        PendingEval {
+            studio_webview_id: "studio".to_string(),
+            ipc_id: 7,
+            tab_id: tab_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn results_are_only_taken_from_the_evaluated_tab() {
+        let evals = PreviewEvals::default();
+        let id = evals.insert(pending("app-3"));
+        assert!(evals.take(id, Some("app-4")).is_none());
+        assert_eq!(evals.take(id, Some("app-3")).unwrap().ipc_id, 7);
+        assert!(evals.take(id, Some("app-3")).is_none());
+    }
+
+    #[test]
+    fn timed_out_evals_are_taken_once() {
+        let evals = PreviewEvals::default();
+        let first = evals.insert(pending("app-3"));
+        let second = evals.insert(pending("app-3"));
+        assert_ne!(first, second);
+        assert!(evals.take(first, None).is_some());
+        assert!(evals.take(first, Some("app-3")).is_none());
+    }
+
+    #[test]
+    fn wrapped_expression_is_embedded_as_source() {
+        let script = wrap_expression(12, "await window.wagmi.getAccount()");
+        assert!(script.contains("\nawait window.wagmi.getAccount()\n"));
+        assert!(script.contains("evalId: 12"));
+        assert!(!script.contains("eval("));
+    }
+
+    #[test]
+    fn passes_printed_values_on() {
+        assert_eq!(
+            eval_result(&json!({ "ok": true, "type": "bigint", "value": "42n" })),
+            Ok(json!({ "type": "bigint", "value": "42n" }))
+        );
+    }
+
+    #[test]
+    fn passes_thrown_errors_on() {
+        assert_eq!(
+            eval_result(&json!({ "ok": false, "error": "ReferenceError: x is not defined" })),
+            Err("ReferenceError: x is not defined".to_string())
+        );
+    }
+
+    #[test]
+    fn cuts_long_values() {
+        assert_eq!(
+            eval_result(&json!({ "ok": true, "type": "string", "value": "x".repeat(70_000) }))
+                .unwrap()["value"]
+                .as_str()
+                .unwrap()
+                .len(),
+            super::MAX_RESULT_CHARS
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use alloy_primitives::{Address, U256, utils::format_units};
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_sol_types::{SolCall, sol};
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::multicall::{ReadCall, read_many};
+use crate::settings::FiatPriceSettings;
+use crate::state::AppState;
+
+sol! {
+    function transfer(address to, uint256 amount) external returns (bool);
+    function transferFrom(address from, address to, uint256 amount) external returns (bool);
+    function approve(address spender, uint256 amount) external returns (bool);
+    function decimals() external view returns (uint8);
+    function symbol() external view returns (string);
+}
+
+/// CoinGecko-compatible API used when the settings name none.
+pub const DEFAULT_PRICE_ENDPOINT: &str = "https://api.coingecko.com/api/v3";
+pub const DEFAULT_CURRENCY: &str = "usd";
+/// Prices are reused for this long, so a burst of prompts makes one request.
+const PRICE_TTL: Duration = Duration::from_secs(60);
+/// A slow price service must not hold up the confirmation prompt for long.
+const PRICE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Quotes not shown for this long are dropped instead of refreshed.
+const QUOTE_IDLE_LIMIT: Duration = Duration::from_secs(60 * 60);
+
+/// Quotes keyed by request URL.
+static QUOTES: Mutex<Option<HashMap<String, CachedQuote>>> = Mutex::new(None);
+
+struct CachedQuote {
+    price: f64,
+    fetched: Instant,
+    /// Last time a prompt asked for it.
+    used: Instant,
+    asset: String,
+    currency: String,
+}
+
+/// A token amount a transaction moves or approves.
+#[derive(Debug, Clone, PartialEq)]
+struct TokenAmount {
+    token: Address,
+    raw: U256,
+    /// `approve` with the maximum amount; it has no meaningful fiat value.
+    unlimited: bool,
+}
+
+/// Fiat details for a transaction prompt: the native value and any
+/// recognized ERC-20 amount, priced in the user's currency. `None` when price
+/// display is off, the chain has no known assets, or nothing could be priced.
+pub fn transaction_fiat(state: &AppState, tx: &TransactionRequest) -> Option<Value> {
+    let settings = price_settings(state).filter(|settings| settings.enabled)?;
+    let chain_id = state.wallet.lock().expect("wallet").chain.chain_id;
+    let (native_id, platform) = chain_assets(chain_id)?;
+    let http = &state.resolved.as_ref()?.http_client;
+    let endpoint = settings
+        .endpoint
+        .as_deref()
+        .unwrap_or(DEFAULT_PRICE_ENDPOINT)
+        .trim_end_matches('/');
+    let currency = settings.currency.as_deref().unwrap_or(DEFAULT_CURRENCY);
+
+    let mut fiat = serde_json::Map::new();
+    if let Some(value) = tx.value.filter(|value| !value.is_zero()) {
+        let url = format!("{endpoint}/simple/price?ids={native_id}&vs_currencies={currency}");
+        match quote(http, &url, native_id, currency) {
+            Ok(price) => {
+                if let Some(amount) = to_decimal(value, 18) {
+                    fiat.insert("value".into(), json!(amount * price));
+                }
+            }
+            Err(err) => {
+                tracing::debug!(error = %format!("{err:#}"), chain_id, "native price unavailable")
+            }
+        }
+    }
+
+    let token = tx
+        .input
+        .input()
+        .zip(tx.to.and_then(|to| to.to().copied()))
+        .and_then(|(input, to)| token_amount(to, input));
+    if let Some(entry) = token.and_then(|token| {
+        let (decimals, symbol) = token_metadata(state, token.token)?;
+        let address = format!("{:#x}", token.token);
+        let mut entry = json!({ "address": address, "symbol": symbol });
+        if token.unlimited {
+            entry["amount"] = json!("unlimited");
+            return Some(entry);
+        }
+        entry["amount"] = json!(format_units(token.raw, decimals).ok()?);
+        let url = format!(
+            "{endpoint}/simple/token_price/{platform}?contract_addresses={address}&vs_currencies={currency}"
+        );
+        match quote(http, &url, &address, currency) {
+            Ok(price) => entry["value"] = json!(to_decimal(token.raw, decimals)? * price),
+            Err(err) => {
+                tracing::debug!(error = %format!("{err:#}"), token = %address, "token price unavailable")
+            }
+        }
+        Some(entry)
+    }) {
+        fiat.insert("token".into(), entry);
+    }
+
+    if fiat.is_empty() {
+        return None;
+    }
+    fiat.insert("currency".into(), json!(currency));
+    Some(Value::Object(fiat))
+}
+
+fn price_settings(state: &AppState) -> Option<FiatPriceSettings> {
+    let config_path = state.resolved.as_ref()?.config_path.as_deref()?;
+    Some(crate::settings::load_settings(config_path).fiat_prices)
+}
+
+/// Price-service ids for the native coin and the token platform of chains
+/// whose assets have listed prices.
+fn chain_assets(chain_id: u64) -> Option<(&'static str, &'static str)> {
+    match chain_id {
+        1 => Some(("ethereum", "ethereum")),
+        10 => Some(("ethereum", "optimistic-ethereum")),
+        100 => Some(("xdai", "xdai")),
+        137 => Some(("polygon-ecosystem-token", "polygon-pos")),
+        8453 => Some(("ethereum", "base")),
+        42161 => Some(("ethereum", "arbitrum-one")),
+        _ => None,
+    }
+}
+
+/// Recognizes ERC-20 `transfer`, `transferFrom` and `approve` calls to `to`.
+fn token_amount(to: Address, input: &[u8]) -> Option<TokenAmount> {
+    let (raw, approval) = if let Ok(call) = transferCall::abi_decode(input) {
+        (call.amount, false)
+    } else if let Ok(call) = transferFromCall::abi_decode(input) {
+        (call.amount, false)
+    } else {
+        (approveCall::abi_decode(input).ok()?.amount, true)
+    };
+    Some(TokenAmount {
+        token: to,
+        raw,
+        unlimited: approval && raw == U256::MAX,
+    })
+}
+
+/// An ERC-20 `transfer`, `transferFrom` or `approve` call spelled out for a
+/// confirmation prompt, with raw amounts.
+pub fn describe_token_call(input: &[u8]) -> Option<Value> {
+    if let Ok(call) = transferCall::abi_decode(input) {
+        Some(json!({
+            "function": "transfer",
+            "to": format!("{:#x}", call.to),
+            "amount": call.amount.to_string(),
+        }))
+    } else if let Ok(call) = transferFromCall::abi_decode(input) {
+        Some(json!({
+            "function": "transferFrom",
+            "from": format!("{:#x}", call.from),
+            "to": format!("{:#x}", call.to),
+            "amount": call.amount.to_string(),
+        }))
+    } else {
+        let call = approveCall::abi_decode(input).ok()?;
+        Some(json!({
+            "function": "approve",
+            "spender": format!("{:#x}", call.spender),
+            "amount": if call.amount == U256::MAX {
+                "unlimited".to_string()
+            } else {
+                call.amount.to_string()
+            },
+        }))
+    }
+}
+
+/// Decimals and symbol of `token`. Contracts answering neither are not
+/// treated as tokens.
+fn token_metadata(state: &AppState, token: Address) -> Option<(u8, String)> {
+    let results = read_many(
+        state,
+        &[
+            ReadCall {
+                to: token,
+                data: decimalsCall {}.abi_encode().into(),
+            },
+            ReadCall {
+                to: token,
+                data: symbolCall {}.abi_encode().into(),
+            },
+        ],
+    );
+    let decimals = decimalsCall::abi_decode_returns(results[0].as_ref().ok()?).ok()?;
+    let symbol = results[1]
+        .as_ref()
+        .ok()
+        .and_then(|raw| symbolCall::abi_decode_returns(raw).ok())
+        .unwrap_or_default();
+    Some((decimals, symbol))
+}
+
+fn to_decimal(amount: U256, decimals: u8) -> Option<f64> {
+    format_units(amount, decimals).ok()?.parse().ok()
+}
+
+/// Price of `asset` in `currency` from a `simple/price`-shaped response.
+fn quote(http: &reqwest::blocking::Client, url: &str, asset: &str, currency: &str) -> Result<f64> {
+    let cached = QUOTES
+        .lock()
+        .expect("price quotes")
+        .as_mut()
+        .and_then(|quotes| quotes.get_mut(url))
+        .filter(|quote| quote.fetched.elapsed() < PRICE_TTL)
+        .map(|quote| {
+            quote.used = Instant::now();
+            quote.price
+        });
+    if let Some(price) = cached {
+        return Ok(price);
+    }
+    let price = fetch_quote(http, url, asset, currency)?;
+    let now = Instant::now();
+    QUOTES
+        .lock()
+        .expect("price quotes")
+        .get_or_insert_with(HashMap::new)
+        .insert(
+            url.to_string(),
+            CachedQuote {
+                price,
+                fetched: now,
+                used: now,
+                asset: asset.to_string(),
+                currency: currency.to_string(),
+            },
+        );
+    Ok(price)
+}
+
+/// Fetches the quotes prompts asked for within the last hour again, so the
+/// next prompt need not wait on the price service; run by the background
+/// scheduler. Older quotes are dropped.
+pub fn refresh(http: &reqwest::blocking::Client) {
+    let due: Vec<(String, String, String)> = {
+        let mut quotes = QUOTES.lock().expect("price quotes");
+        let Some(quotes) = quotes.as_mut() else {
+            return;
+        };
+        quotes.retain(|_, quote| quote.used.elapsed() < QUOTE_IDLE_LIMIT);
+        quotes
+            .iter()
+            .filter(|(_, quote)| quote.fetched.elapsed() >= PRICE_TTL / 2)
+            .map(|(url, quote)| (url.clone(), quote.asset.clone(), quote.currency.clone()))
+            .collect()
+    };
+    for (url, asset, currency) in &due {
+        match fetch_quote(http, url, asset, currency) {
+            Ok(price) => {
+                if let Some(quote) = QUOTES
+                    .lock()
+                    .expect("price quotes")
+                    .as_mut()
+                    .and_then(|quotes| quotes.get_mut(url))
+                {
+                    quote.price = price;
+                    quote.fetched = Instant::now();
+                }
+            }
+            Err(err) => {
+                tracing::debug!(asset, error = %format!("{err:#}"), "price refresh failed")
+            }
+        }
+    }
+    tracing::debug!(refreshed = due.len(), "price quotes refreshed");
+}
+
+fn fetch_quote(
+    http: &reqwest::blocking::Client,
+    url: &str,
+    asset: &str,
+    currency: &str,
+) -> Result<f64> {
+    let response = http
+        .get(url)
+        .timeout(PRICE_TIMEOUT)
+        .send()
+        .context("price request failed")?
+        .error_for_status()
+        .context("price service error")?;
+    let body: Value = response.json().context("price response is not JSON")?;
+    parse_quote(&body, asset, currency)
+}
+
+fn parse_quote(body: &Value, asset: &str, currency: &str) -> Result<f64> {
+    body.get(asset)
+        .and_then(|prices| prices.get(currency))
+        .and_then(Value::as_f64)
+        .ok_or_else(|| anyhow!("no {currency} price for {asset}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TokenAmount, approveCall, parse_quote, to_decimal, token_amount, transferCall};
+    use alloy_primitives::{Address, U256};
+    use alloy_sol_types::SolCall;
+    use serde_json::json;
+
+    #[test]
+    fn reads_transfer_amounts() {
+        let token = Address::repeat_byte(0x55);
+        let transfer = transferCall {
+            to: Address::repeat_byte(0x66),
+            amount: U256::from(2_500_000u64),
+        }
+        .abi_encode();
+        assert_eq!(
+            token_amount(token, &transfer),
+            Some(TokenAmount {
+                token,
+                raw: U256::from(2_500_000u64),
+                unlimited: false,
+            })
+        );
+    }
+
+    #[test]
+    fn flags_unlimited_approvals() {
+        let token = Address::repeat_byte(0x55);
+        let approve = approveCall {
+            spender: Address::repeat_byte(0x66),
+            amount: U256::MAX,
+        }
+        .abi_encode();
+        assert!(token_amount(token, &approve).unwrap().unlimited);
+    }
+
+    #[test]
+    fn other_calls_carry_no_token_amount() {
+        let token = Address::repeat_byte(0x55);
+        assert_eq!(token_amount(token, &[0xde, 0xad, 0xbe, 0xef]), None);
+    }
+
+    #[test]
+    fn scales_raw_amounts_by_decimals() {
+        assert_eq!(to_decimal(U256::from(2_500_000u64), 6), Some(2.5));
+    }
+
+    #[test]
+    fn reads_quotes_in_the_requested_currency() {
+        let body = json!({ "ethereum": { "eur": 2000.5 } });
+        assert_eq!(parse_quote(&body, "ethereum", "eur").unwrap(), 2000.5);
+        assert!(parse_quote(&body, "ethereum", "usd").is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! User profiles. Each named profile keeps its own settings, keystore,
+//! permissions, tab session, storage partitions and bundle cache, so a shared
+//! machine or a dev and a personal setup don't mix wallet state. The default
+//! profile is the data next to the network config, as before profiles
+//! existed; a named one lives under `profiles/<name>/` beside it, with its
+//! cache under `profiles/<name>/` in the cache dir.
+//!
+//! A profile is picked at startup with `--profile` (or `VIBEFI_PROFILE`).
+//! Switching from Settings restarts the client with the other profile.
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ResolvedConfig;
+
+/// Name of the profile that uses the data next to the network config.
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIR: &str = "profiles";
+const MAX_NAME_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+}
+
+/// Lowercased `name`, or an error unless it is 1 to 32 letters, digits,
+/// `-` or `_`. These names become directory names.
+pub fn normalize_name(name: &str) -> Result<String> {
+    let name = name.trim().to_ascii_lowercase();
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        bail!("profile names are 1 to {MAX_NAME_LEN} characters");
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!("profile names may only contain letters, digits, '-' and '_'");
+    }
+    Ok(name)
+}
+
+/// Points `resolved` at profile `name`'s data, creating its directories.
+pub fn apply(resolved: &mut ResolvedConfig, name: &str) -> Result<()> {
+    let name = normalize_name(name)?;
+    if name == DEFAULT_PROFILE {
+        return Ok(());
+    }
+    let config_path = resolved
+        .config_path
+        .as_deref()
+        .ok_or_else(|| anyhow!("--profile needs a network config"))?;
+    let file_name = config_path
+        .file_name()
+        .ok_or_else(|| anyhow!("invalid config path {}", config_path.display()))?;
+    let dir = config_path.with_file_name(PROFILES_DIR).join(&name);
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    resolved.config_path = Some(dir.join(file_name));
+    resolved.cache_dir = resolved.cache_dir.join(PROFILES_DIR).join(&name);
+    resolved.profile = Some(name);
+    Ok(())
+}
+
+/// Directory holding the named profiles. With a profile active, the config
+/// path is already inside it.
+fn profiles_root(resolved: &ResolvedConfig) -> Option<PathBuf> {
+    let config_path = resolved.config_path.as_deref()?;
+    match resolved.profile {
+        Some(_) => config_path.parent()?.parent().map(Path::to_path_buf),
+        None => Some(config_path.with_file_name(PROFILES_DIR)),
+    }
+}
+
+/// The default profile and every named one, the active one marked.
+pub fn list(resolved: &ResolvedConfig) -> Vec<ProfileInfo> {
+    let active = resolved.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+    let mut names: Vec<String> = profiles_root(resolved)
+        .and_then(|root| fs::read_dir(root).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| normalize_name(name).is_ok_and(|normalized| normalized == *name))
+        .filter(|name| name != DEFAULT_PROFILE)
+        .collect();
+    names.sort();
+    std::iter::once(DEFAULT_PROFILE.to_string())
+        .chain(names)
+        .map(|name| ProfileInfo {
+            active: name == active,
+            name,
+        })
+        .collect()
+}
+
+/// Creates an empty profile and returns its normalized name.
+pub fn create(resolved: &ResolvedConfig, name: &str) -> Result<String> {
+    let name = normalize_name(name)?;
+    let root = profiles_root(resolved).ok_or_else(|| anyhow!("No config loaded"))?;
+    let dir = root.join(&name);
+    if name == DEFAULT_PROFILE || dir.exists() {
+        bail!("profile {name} already exists");
+    }
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    tracing::info!(target: "vibefi::audit", profile = %name, "created profile");
+    Ok(name)
+}
+
+/// Where the WalletConnect helper keeps its session for the active profile;
+/// `None` leaves the helper's default, which belongs to the default profile.
+pub fn walletconnect_store(resolved: Option<&ResolvedConfig>) -> Option<PathBuf> {
+    let resolved = resolved.filter(|resolved| resolved.profile.is_some())?;
+    Some(
+        resolved
+            .config_path
+            .as_deref()?
+            .with_file_name("walletconnect-store.json"),
+    )
+}
+
+/// Starts the client again with profile `name` and the same arguments.
+/// The caller shuts this instance down.
+pub fn relaunch(name: &str) -> Result<()> {
+    let exe = std::env::current_exe().context("locate the client executable")?;
+    let args = relaunch_args(std::env::args_os().skip(1), name);
+    std::process::Command::new(&exe)
+        .args(&args)
+        // An inherited override would win over the new --profile.
+        .env_remove("VIBEFI_PROFILE")
+        .spawn()
+        .with_context(|| format!("restart {}", exe.display()))?;
+    Ok(())
+}
+
+/// `args` without any `--profile`, followed by the one for `name`.
+fn relaunch_args(args: impl IntoIterator<Item = OsString>, name: &str) -> Vec<OsString> {
+    let mut kept = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            args.next();
+            continue;
+        }
+        if arg
+            .to_str()
+            .is_some_and(|arg| arg.starts_with("--profile="))
+        {
+            continue;
+        }
+        kept.push(arg);
+    }
+    kept.push("--profile".into());
+    kept.push(name.into());
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProfileInfo, apply, create, list, normalize_name, relaunch_args};
+    use crate::config::{AppConfig, ConfigBuilder, ResolvedConfig};
+    use std::ffi::OsString;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn profiles_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("vibefi-profiles-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn base_config(dir: &Path) -> ResolvedConfig {
+        let config: AppConfig = serde_json::from_value(serde_json::json!({
+            "chainId": 11155111,
+            "rpcUrl": "http://127.0.0.1:8545",
+            "dappRegistry": "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+            "cacheDir": dir.join("cache"),
+        }))
+        .unwrap();
+        ConfigBuilder::new(config, Some(dir.join("sepolia.json"))).build()
+    }
+
+    fn with_profile(base: &ResolvedConfig, name: &str) -> ResolvedConfig {
+        let mut resolved = base.clone();
+        apply(&mut resolved, name).unwrap();
+        resolved
+    }
+
+    fn profile(name: &str, active: bool) -> ProfileInfo {
+        ProfileInfo {
+            name: name.to_string(),
+            active,
+        }
+    }
+
+    #[test]
+    fn the_default_profile_keeps_the_base_dirs() {
+        let base = base_config(&profiles_dir("default"));
+        let default = with_profile(&base, "Default");
+        assert_eq!(default.config_path, base.config_path);
+        assert_eq!(default.profile, None);
+    }
+
+    #[test]
+    fn profiles_get_their_own_data_and_cache_dirs() {
+        let dir = profiles_dir("dirs");
+        let work = with_profile(&base_config(&dir), " Work ");
+        assert_eq!(work.profile.as_deref(), Some("work"));
+        assert_eq!(
+            work.config_path,
+            Some(dir.join("profiles").join("work").join("sepolia.json"))
+        );
+        assert_eq!(
+            work.cache_dir,
+            dir.join("cache").join("profiles").join("work")
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn creates_profiles_once() {
+        let dir = profiles_dir("create");
+        let base = base_config(&dir);
+        let work = with_profile(&base, "work");
+        assert_eq!(create(&work, "dev").unwrap(), "dev");
+        assert!(create(&base, "dev").is_err());
+        assert!(create(&base, "default").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn lists_profiles_with_the_active_one_marked() {
+        let dir = profiles_dir("list");
+        let base = base_config(&dir);
+        let work = with_profile(&base, "work");
+        create(&work, "dev").unwrap();
+        assert_eq!(
+            list(&work),
+            vec![
+                profile("default", false),
+                profile("dev", false),
+                profile("work", true)
+            ]
+        );
+        assert_eq!(list(&base)[0], profile("default", true));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn names_are_safe_directory_names() {
+        assert_eq!(normalize_name("Dev_2").unwrap(), "dev_2");
+        for bad in ["", "../keys", "a b", "x".repeat(33).as_str()] {
+            assert!(normalize_name(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn relaunch_replaces_the_profile() {
+        let args = [
+            "--config",
+            "c.json",
+            "--profile",
+            "old",
+            "--profile=older",
+            "--dev-profile",
+        ]
+        .map(OsString::from);
+        assert_eq!(
+            relaunch_args(args, "work"),
+            ["--config", "c.json", "--dev-profile", "--profile", "work"].map(OsString::from)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+
+use crate::state::{AppState, DappIdentity, UserEvent};
+
+/// Id of the prompt overlay webview. It covers the app area but is not a tab.
+pub const PROMPT_WEBVIEW_ID: &str = "prompt";
+/// Provider event that hands the overlay the next prompt to show.
+pub const PROMPT_EVENT: &str = "vibefiPrompt";
+
+static NEXT_PROMPT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Something a handler needs the user to approve. `kind` selects the payload
+/// renderer in the prompt UI, which falls back to listing `details`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptRequest {
+    pub id: u64,
+    pub kind: String,
+    pub title: String,
+    pub message: String,
+    /// Filled in on the event loop from the requesting tab.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requester: Option<DappIdentity>,
+    pub details: Value,
+    /// Whether the prompt offers to remember the choice.
+    pub rememberable: bool,
+    /// Prompts queued behind this one.
+    pub queued: usize,
+    #[serde(skip)]
+    pub webview_id: Option<String>,
+    #[serde(skip)]
+    remember_scope: Option<String>,
+    /// Set on transaction prompts so approval rules can answer them.
+    #[serde(skip)]
+    pub transaction: Option<crate::policy::TransactionFacts>,
+}
+
+impl PromptRequest {
+    pub fn new(kind: &str, title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            id: 0,
+            kind: kind.to_string(),
+            title: title.into(),
+            message: message.into(),
+            requester: None,
+            details: Value::Null,
+            rememberable: false,
+            queued: 0,
+            webview_id: None,
+            remember_scope: None,
+            transaction: None,
+        }
+    }
+
+    /// Attributes the prompt to the dapp tab `webview_id`.
+    pub fn from_webview(mut self, webview_id: &str) -> Self {
+        self.webview_id = Some(webview_id.to_string());
+        self
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// Lets an approval rule answer the prompt; see [`crate::policy`].
+    pub fn for_transaction(mut self, facts: crate::policy::TransactionFacts) -> Self {
+        self.transaction = Some(facts);
+        self
+    }
+
+    /// Offers "remember my choice". The decision is remembered per requester,
+    /// kind and `scope` (e.g. the capability or chain asked for).
+    pub fn rememberable(mut self, scope: impl Into<String>) -> Self {
+        self.remember_scope = Some(scope.into());
+        self
+    }
+
+    /// Key a remembered decision is stored under. Prompts from tabs without a
+    /// registry or bundle identity cannot be remembered.
+    pub fn remember_key(&self) -> Option<String> {
+        let scope = self.remember_scope.as_deref()?;
+        let subject = match (&self.webview_id, &self.requester) {
+            (None, _) => "host".to_string(),
+            (Some(_), Some(requester)) => crate::permissions::subject_for(requester)?,
+            (Some(_), None) => return None,
+        };
+        Some(format!("{subject}|{}|{scope}", self.kind))
+    }
+}
+
+/// The overlay's answer to a prompt.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptAnswer {
+    pub id: u64,
+    pub approved: bool,
+    #[serde(default)]
+    pub remember: bool,
+}
+
+/// A prompt on screen or waiting for the overlay, with the channel its
+/// handler is blocked on.
+pub struct PendingPrompt {
+    pub request: PromptRequest,
+    pub reply: mpsc::Sender<bool>,
+}
+
+/// Shows `request` in the prompt overlay and blocks until the user answers.
+/// Call it from a worker thread, never the event loop. A prompt that cannot
+/// be shown counts as denied.
+pub fn ask(state: &AppState, mut request: PromptRequest) -> bool {
+    request.id = NEXT_PROMPT_ID.fetch_add(1, Ordering::Relaxed);
+    let (id, kind) = (request.id, request.kind.clone());
+    let (reply, answer) = mpsc::channel();
+    if let Err(err) = state
+        .proxy
+        .send_event(UserEvent::ShowPrompt { request, reply })
+    {
+        tracing::warn!(id, kind, error = %err, "failed to show prompt");
+        return false;
+    }
+    answer.recv().unwrap_or_else(|_| {
+        tracing::warn!(id, kind, "prompt dropped without an answer");
+        false
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PromptRequest;
+    use crate::state::DappIdentity;
+
+    #[test]
+    fn dapp_prompts_are_remembered_per_dapp_kind_and_scope() {
+        let mut request = PromptRequest::new("capability", "Camera", "")
+            .from_webview("app-3")
+            .rememberable("camera");
+        request.requester = Some(DappIdentity {
+            name: "Swap".to_string(),
+            dapp_id: Some("7".to_string()),
+            registry: Some("Picks".to_string()),
+            ..DappIdentity::default()
+        });
+        assert_eq!(
+            request.remember_key().as_deref(),
+            Some("dapp:Picks/7|capability|camera")
+        );
+    }
+
+    #[test]
+    fn prompts_from_unknown_requesters_are_not_remembered() {
+        let request = PromptRequest::new("capability", "Camera", "")
+            .from_webview("app-3")
+            .rememberable("camera");
+        assert_eq!(request.remember_key(), None);
+    }
+
+    #[test]
+    fn host_prompts_are_remembered_per_kind_and_scope() {
+        let host = PromptRequest::new("download", "Save file", "").rememberable("all");
+        assert_eq!(host.remember_key().as_deref(), Some("host|download|all"));
+        assert_eq!(PromptRequest::new("download", "", "").remember_key(), None);
+    }
+}
@@ -0,0 +1,302 @@
+//! Publishing from Studio. Checks a project the way the preflight does, adds
+//! it to the configured IPFS node (`ipfsApi`) with pinning, then registers
+//! the root CID with `dappRegistry` from the connected wallet and sets the
+//! name, version and description of the new version.
+
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_sol_types::{SolCall, SolEvent, sol};
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs;
+use std::time::{Duration, Instant};
+
+use crate::config::RegistrySchema;
+use crate::local_bundle::ProjectFile;
+use crate::publish_preflight::StepStatus;
+use crate::registry::DappPublished;
+use crate::state::AppState;
+
+/// How long to wait for each registry transaction to be mined.
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(300);
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+sol! {
+    function publish(bytes rootCid) returns (uint256 dappId, uint256 versionId);
+    function setMetadata(uint256 dappId, uint256 versionId, string name, string version, string description);
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishRequest {
+    /// Project files as Studio holds them, including `manifest.json`.
+    pub files: Vec<ProjectFile>,
+    /// Defaults to the manifest's `name`. No metadata is set without one.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// CID version to add with; the IPFS node's default when unset.
+    #[serde(default)]
+    pub cid_version: Option<u8>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishResult {
+    pub root_cid: String,
+    pub dapp_id: String,
+    pub version_id: String,
+    pub publish_tx: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_tx: Option<String>,
+}
+
+/// Validates, builds, pins and registers the project, waiting for each
+/// transaction to be mined. Blocks; run it off the event loop.
+pub fn publish_dapp(state: &AppState, request: &PublishRequest) -> Result<PublishResult> {
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("Network not configured"))?;
+    let registry: Address = resolved
+        .dapp_registry
+        .parse()
+        .context("invalid dappRegistry address")?;
+    if resolved.registry_schema(&resolved.dapp_registry) == RegistrySchema::V2 {
+        bail!(
+            "publishing to a schema 2 registry needs a publisher signature, which is not supported yet"
+        );
+    }
+    if resolved.fork.is_some() {
+        bail!(
+            "publishing is disabled in fork sessions; the transactions would only land on the fork"
+        );
+    }
+    // The metadata call needs the id the mined publish assigns.
+    if crate::signed_export::offline_signing(state) {
+        bail!("turn off signing without broadcasting to publish");
+    }
+
+    let dir = crate::local_bundle::workspace("publish")?;
+    let result = (|| -> Result<(String, Option<String>)> {
+        crate::publish_preflight::write_project(&dir, &request.files)?;
+        let mut steps = Vec::new();
+        let manifest = crate::publish_preflight::validate(&dir, &mut steps);
+        if manifest.is_some() {
+            steps.push(crate::publish_preflight::build(&dir));
+        }
+        let failures: Vec<String> = steps
+            .iter()
+            .filter(|step| step.status == StepStatus::Failed)
+            .map(|step| format!("{}: {}", step.name, step.message))
+            .collect();
+        let manifest = match manifest {
+            Some(manifest) if failures.is_empty() => manifest,
+            _ => bail!(
+                "the project did not pass its checks ({})",
+                failures.join("; ")
+            ),
+        };
+        let root_cid = crate::publish_preflight::add_bundle(
+            &resolved.http_client,
+            &resolved.ipfs_api,
+            &dir,
+            &manifest,
+            request.cid_version,
+            true,
+        )
+        .with_context(|| format!("failed to add the bundle to {}", resolved.ipfs_api))?;
+        tracing::info!(target: "vibefi::audit", root_cid = %root_cid, "bundle pinned for publishing");
+        Ok((root_cid, manifest.name))
+    })();
+    if let Err(err) = fs::remove_dir_all(&dir) {
+        tracing::debug!(dir = %dir.display(), error = %err, "failed to remove publish workspace");
+    }
+    let (root_cid, manifest_name) = result?;
+
+    let publish_tx =
+        crate::ipc::send_host_transaction(state, registry, publish_calldata(&root_cid))?;
+    tracing::info!(target: "vibefi::audit", root_cid = %root_cid, tx_hash = %publish_tx, "dapp publish submitted");
+    let receipt = wait_for_receipt(state, &publish_tx)?;
+    let (dapp_id, version_id) = published_ids(&receipt, &registry).ok_or_else(|| {
+        anyhow!("publish transaction {publish_tx} emitted no DappPublished event")
+    })?;
+    tracing::info!(target: "vibefi::audit", %dapp_id, %version_id, root_cid = %root_cid, "dapp published");
+
+    let name = request
+        .name
+        .clone()
+        .or(manifest_name)
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    let metadata_tx = match name {
+        Some(name) => {
+            let data = metadata_calldata(
+                dapp_id,
+                version_id,
+                &name,
+                request.version.as_deref().unwrap_or_default(),
+                request.description.as_deref().unwrap_or_default(),
+            );
+            let hash = crate::ipc::send_host_transaction(state, registry, data)?;
+            wait_for_receipt(state, &hash)?;
+            tracing::info!(target: "vibefi::audit", %dapp_id, %version_id, name = %name, tx_hash = %hash, "dapp metadata set");
+            Some(hash)
+        }
+        None => None,
+    };
+
+    Ok(PublishResult {
+        root_cid,
+        dapp_id: dapp_id.to_string(),
+        version_id: version_id.to_string(),
+        publish_tx,
+        metadata_tx,
+    })
+}
+
+/// The registry stores the CID as its UTF-8 text.
+fn publish_calldata(root_cid: &str) -> Bytes {
+    publishCall {
+        rootCid: Bytes::copy_from_slice(root_cid.as_bytes()),
+    }
+    .abi_encode()
+    .into()
+}
+
+fn metadata_calldata(
+    dapp_id: U256,
+    version_id: U256,
+    name: &str,
+    version: &str,
+    description: &str,
+) -> Bytes {
+    setMetadataCall {
+        dappId: dapp_id,
+        versionId: version_id,
+        name: name.to_string(),
+        version: version.to_string(),
+        description: description.to_string(),
+    }
+    .abi_encode()
+    .into()
+}
+
+/// Dapp and version id from the `DappPublished` event `registry` emitted.
+fn published_ids(receipt: &Value, registry: &Address) -> Option<(U256, U256)> {
+    let topic0 = format!("{:#x}", DappPublished::SIGNATURE_HASH);
+    receipt
+        .get("logs")?
+        .as_array()?
+        .iter()
+        .filter(|log| {
+            log.get("address")
+                .and_then(Value::as_str)
+                .and_then(|address| address.parse::<Address>().ok())
+                .is_some_and(|address| address == *registry)
+        })
+        .find_map(|log| {
+            let topics: Vec<&str> = log
+                .get("topics")?
+                .as_array()?
+                .iter()
+                .filter_map(Value::as_str)
+                .collect();
+            if topics.len() < 3 || !topics[0].eq_ignore_ascii_case(&topic0) {
+                return None;
+            }
+            Some((topics[1].parse().ok()?, topics[2].parse().ok()?))
+        })
+}
+
+/// Polls for the receipt of `hash` and fails when it reverted.
+fn wait_for_receipt(state: &AppState, hash: &str) -> Result<Value> {
+    let deadline = Instant::now() + RECEIPT_TIMEOUT;
+    loop {
+        let receipt = crate::ipc::rpc_request(state, "eth_getTransactionReceipt", json!([hash]))
+            .context("eth_getTransactionReceipt failed")?;
+        if !receipt.is_null() {
+            if receipt.get("status").and_then(Value::as_str) == Some("0x0") {
+                bail!("transaction {hash} reverted");
+            }
+            return Ok(receipt);
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "transaction {hash} was not mined within {} minutes",
+                RECEIPT_TIMEOUT.as_secs() / 60
+            );
+        }
+        std::thread::sleep(RECEIPT_POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DappPublished, metadata_calldata, publish_calldata, publishCall, published_ids,
+        setMetadataCall,
+    };
+    use alloy_primitives::{Address, U256};
+    use alloy_sol_types::{SolCall, SolEvent};
+    use serde_json::json;
+
+    #[test]
+    fn encodes_the_publish_call() {
+        let cid = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+        let publish = publishCall::abi_decode(&publish_calldata(cid)).unwrap();
+        assert_eq!(publish.rootCid.as_ref(), cid.as_bytes());
+    }
+
+    #[test]
+    fn encodes_the_metadata_call() {
+        let metadata = setMetadataCall::abi_decode(&metadata_calldata(
+            U256::from(7),
+            U256::from(2),
+            "Swap",
+            "1.0.0",
+            "",
+        ))
+        .unwrap();
+        assert_eq!(
+            (metadata.dappId, metadata.versionId),
+            (U256::from(7), U256::from(2))
+        );
+        assert_eq!(metadata.name, "Swap");
+    }
+
+    fn published_topic(value: u64) -> String {
+        format!("{:#066x}", value)
+    }
+
+    #[test]
+    fn reads_the_published_ids_from_the_registry_log() {
+        let registry = Address::repeat_byte(0x11);
+        let receipt = json!({
+            "status": "0x1",
+            "logs": [
+                {
+                    "address": format!("{:#x}", Address::repeat_byte(0x22)),
+                    "topics": [format!("{:#x}", DappPublished::SIGNATURE_HASH), published_topic(1), published_topic(1)],
+                },
+                {
+                    "address": "0x1111111111111111111111111111111111111111",
+                    "topics": [format!("{:#x}", DappPublished::SIGNATURE_HASH), published_topic(7), published_topic(2)],
+                },
+            ],
+        });
+        assert_eq!(
+            published_ids(&receipt, &registry),
+            Some((U256::from(7), U256::from(2)))
+        );
+    }
+
+    #[test]
+    fn receipts_without_a_registry_log_have_no_ids() {
+        let registry = Address::repeat_byte(0x11);
+        assert_eq!(published_ids(&json!({ "logs": [] }), &registry), None);
+    }
+}
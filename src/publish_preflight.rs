@@ -0,0 +1,669 @@
+//! Publish dry run for Studio. Runs everything a publish would check on a
+//! throwaway copy of the project and reports what it found, without pinning
+//! content or sending a transaction.
+
+use alloy_primitives::{U256, utils::format_units};
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use reqwest::blocking::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path};
+use std::time::Duration;
+
+use crate::bundle::normalize_manifest_path;
+use crate::local_bundle::{MAX_STUDIO_PROJECT_BYTES, ProjectFile};
+use crate::manifest::BundleManifest;
+use crate::state::AppState;
+
+/// Hashing only reads the files, but a large bundle still takes a while.
+const HASH_TIMEOUT: Duration = Duration::from_secs(120);
+/// Adding also uploads and stores every file.
+const ADD_TIMEOUT: Duration = Duration::from_secs(600);
+/// Directory name the bundle is added under; only its CID is reported.
+const HASH_ROOT: &str = "bundle";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightRequest {
+    /// Project files as Studio holds them, including `manifest.json`.
+    pub files: Vec<ProjectFile>,
+    /// Encoded registry call the real publish would send, for gas estimation.
+    #[serde(default)]
+    pub publish_call: Option<PublishCall>,
+    /// CID version the publish will use; the IPFS node's default when unset.
+    #[serde(default)]
+    pub cid_version: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublishCall {
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Defaults to the configured `dappRegistry`.
+    #[serde(default)]
+    pub to: Option<String>,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Passed,
+    Warning,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreflightStep {
+    pub name: &'static str,
+    pub status: StepStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasEstimate {
+    pub chain_id: u64,
+    pub gas: String,
+    /// Wei per gas.
+    pub gas_price: String,
+    /// `gas * gasPrice` in the native coin, formatted with 18 decimals.
+    pub fee: String,
+}
+
+/// What Studio shows before the real publish. `ok` is false when any step
+/// failed; warnings and skipped steps do not block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightReport {
+    pub ok: bool,
+    pub steps: Vec<PreflightStep>,
+    pub root_cid: Option<String>,
+    pub gas: Option<GasEstimate>,
+    /// Capabilities the manifest grants, in plain words.
+    pub capabilities: Vec<String>,
+}
+
+/// Capabilities a manifest grants and what looks off about it.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Audit {
+    granted: Vec<String>,
+    warnings: Vec<String>,
+}
+
+/// Writes the project to a fresh temp directory, then validates, builds,
+/// hashes and audits it there. Blocks; run it off the event loop.
+pub fn run(state: &AppState, request: &PreflightRequest) -> Result<PreflightReport> {
+    let dir = crate::local_bundle::workspace("preflight")?;
+    let report = write_project(&dir, &request.files).map(|()| preflight(state, &dir, request));
+    if let Err(err) = fs::remove_dir_all(&dir) {
+        tracing::debug!(dir = %dir.display(), error = %err, "failed to remove preflight workspace");
+    }
+    report
+}
+
+fn preflight(state: &AppState, dir: &Path, request: &PreflightRequest) -> PreflightReport {
+    let mut steps = Vec::new();
+    let mut root_cid = None;
+    let mut gas = None;
+    let mut capabilities = Vec::new();
+
+    let manifest = validate(dir, &mut steps);
+    match &manifest {
+        Some(manifest) => {
+            steps.push(build(dir));
+            let (step, cid) = hash(state, dir, manifest, request.cid_version);
+            steps.push(step);
+            root_cid = cid;
+            let audit = audit(manifest);
+            steps.push(audit_step(&audit));
+            capabilities = audit.granted;
+        }
+        None => {
+            for name in ["build", "cid", "audit"] {
+                steps.push(skipped(name, "Fix the manifest first."));
+            }
+        }
+    }
+    match &request.publish_call {
+        Some(call) => match estimate_gas(state, call) {
+            Ok(estimate) => {
+                steps.push(passed(
+                    "gas",
+                    format!(
+                        "about {} gas, {} at the current gas price",
+                        estimate.gas, estimate.fee
+                    ),
+                ));
+                gas = Some(estimate);
+            }
+            Err(err) => steps.push(failed("gas", format!("{err:#}"), Vec::new())),
+        },
+        None => steps.push(skipped("gas", "No publish call to estimate.")),
+    }
+
+    let ok = steps.iter().all(|step| step.status != StepStatus::Failed);
+    tracing::info!(ok, root_cid = ?root_cid, "publish preflight finished");
+    PreflightReport {
+        ok,
+        steps,
+        root_cid,
+        gas,
+        capabilities,
+    }
+}
+
+pub(crate) fn write_project(dir: &Path, files: &[ProjectFile]) -> Result<()> {
+    let mut total = 0u64;
+    for file in files {
+        let relative = Path::new(&file.path);
+        if relative.as_os_str().is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!("invalid project file path {}", file.path);
+        }
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&file.content)
+            .with_context(|| format!("{} is not valid base64", file.path))?;
+        total += bytes.len() as u64;
+        if total > MAX_STUDIO_PROJECT_BYTES {
+            bail!(
+                "project is larger than {} MiB",
+                MAX_STUDIO_PROJECT_BYTES / 1024 / 1024
+            );
+        }
+        let target = dir.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, bytes).with_context(|| format!("failed to write {}", file.path))?;
+    }
+    Ok(())
+}
+
+/// Manifest, file hashes, client version and secret scan, as a launch checks
+/// them. Returns the manifest when it could be read.
+pub(crate) fn validate(dir: &Path, steps: &mut Vec<PreflightStep>) -> Option<BundleManifest> {
+    let manifest = match crate::manifest::load(dir) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            steps.push(failed("validate", format!("{err:#}"), Vec::new()));
+            return None;
+        }
+    };
+    let checks = crate::bundle::reverify_manifest(dir)
+        .and_then(|()| crate::bundle::ensure_client_supported(dir));
+    let findings = crate::secret_scan::scan_bundle(dir).unwrap_or_default();
+    steps.push(match checks {
+        Err(err) => failed("validate", format!("{err:#}"), Vec::new()),
+        Ok(()) if !findings.is_empty() => failed(
+            "validate",
+            format!("{} possible secret(s) in the bundle", findings.len()),
+            findings.iter().map(ToString::to_string).collect(),
+        ),
+        Ok(()) => passed(
+            "validate",
+            format!(
+                "manifest schema {} lists {} file(s)",
+                manifest.schema_version,
+                manifest.files.len()
+            ),
+        ),
+    });
+    Some(manifest)
+}
+
+/// Builds into the workspace, which starts without `node_modules` or earlier
+/// output, so the result matches what a fresh client builds after fetching.
+pub(crate) fn build(dir: &Path) -> PreflightStep {
+    let dist_dir = dir.join(".vibefi").join("dist");
+    if let Err(err) = crate::bundle::build_bundle(dir, &dist_dir, false) {
+        return failed("build", format!("{err:#}"), Vec::new());
+    }
+    if !dist_dir.join("index.html").exists() {
+        return failed(
+            "build",
+            "build produced no index.html".to_string(),
+            Vec::new(),
+        );
+    }
+    let files = crate::bundle::walk_files(&dist_dir).unwrap_or_default();
+    let bytes: u64 = files
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum();
+    passed(
+        "build",
+        format!("{} file(s), {} KB", files.len(), bytes.div_ceil(1024)),
+    )
+}
+
+/// Asks the configured IPFS node to hash the published files without
+/// storing them. The step is skipped when no node is reachable.
+fn hash(
+    state: &AppState,
+    dir: &Path,
+    manifest: &BundleManifest,
+    cid_version: Option<u8>,
+) -> (PreflightStep, Option<String>) {
+    let Some(resolved) = state.resolved.as_ref() else {
+        return (skipped("cid", "Network not configured."), None);
+    };
+    match add_bundle(
+        &resolved.http_client,
+        &resolved.ipfs_api,
+        dir,
+        manifest,
+        cid_version,
+        false,
+    ) {
+        Ok(cid) => (passed("cid", cid.clone()), Some(cid)),
+        Err(err)
+            if err
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|err| err.is_connect() || err.is_timeout()) =>
+        {
+            let message = format!(
+                "No IPFS node answered at {}; start one to compute the root CID.",
+                resolved.ipfs_api
+            );
+            (skipped("cid", message), None)
+        }
+        Err(err) => (failed("cid", format!("{err:#}"), Vec::new()), None),
+    }
+}
+
+/// Root CID of `manifest.json` plus the files it lists, which is what gets
+/// pinned and what launches fetch. Only hashes unless `pin` is set, in which
+/// case the node stores and pins the files.
+pub(crate) fn add_bundle(
+    http: &reqwest::blocking::Client,
+    ipfs_api: &str,
+    dir: &Path,
+    manifest: &BundleManifest,
+    cid_version: Option<u8>,
+    pin: bool,
+) -> Result<String> {
+    let mut paths: Vec<String> = manifest
+        .files
+        .iter()
+        .map(|entry| normalize_manifest_path(&entry.path))
+        .collect();
+    paths.push("manifest.json".to_string());
+    paths.sort();
+    paths.dedup();
+
+    // Kubo wants every directory announced before the files in it.
+    let mut dirs = BTreeSet::from([HASH_ROOT.to_string()]);
+    for path in &paths {
+        let mut segments: Vec<&str> = path.split('/').collect();
+        segments.pop();
+        let mut prefix = HASH_ROOT.to_string();
+        for segment in segments {
+            prefix = format!("{prefix}/{segment}");
+            dirs.insert(prefix.clone());
+        }
+    }
+    let mut form = Form::new();
+    for dir_name in &dirs {
+        form = form.part(
+            "file",
+            Part::bytes(Vec::new())
+                .file_name(dir_name.clone())
+                .mime_str("application/x-directory")?,
+        );
+    }
+    for path in &paths {
+        let bytes = fs::read(dir.join(path)).with_context(|| format!("failed to read {path}"))?;
+        form = form.part(
+            "file",
+            Part::bytes(bytes)
+                .file_name(format!("{HASH_ROOT}/{path}"))
+                .mime_str("application/octet-stream")?,
+        );
+    }
+
+    let mut url = format!(
+        "{}/api/v0/add?only-hash={}&pin={pin}&quieter=true",
+        ipfs_api.trim_end_matches('/'),
+        !pin
+    );
+    if let Some(version) = cid_version {
+        url.push_str(&format!("&cid-version={version}"));
+    }
+    let response = http
+        .post(url)
+        .multipart(form)
+        .timeout(if pin { ADD_TIMEOUT } else { HASH_TIMEOUT })
+        .send()?;
+    let status = response.status();
+    let body = response
+        .text()
+        .context("failed to read IPFS add response")?;
+    if !status.is_success() {
+        bail!(
+            "IPFS node refused to {} the bundle ({status}): {}",
+            if pin { "add" } else { "hash" },
+            body.trim()
+        );
+    }
+    // One JSON object per line; the wrapping directory comes last.
+    body.lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|entry| entry.get("Name").and_then(Value::as_str) == Some(HASH_ROOT))
+        .filter_map(|entry| {
+            entry
+                .get("Hash")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .next_back()
+        .ok_or_else(|| anyhow!("IPFS add response has no root CID"))
+}
+
+fn estimate_gas(state: &AppState, call: &PublishCall) -> Result<GasEstimate> {
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("Network not configured"))?;
+    let mut tx = json!({
+        "to": call.to.as_deref().unwrap_or(&resolved.dapp_registry),
+        "data": call.data,
+    });
+    if let Some(from) = &call.from {
+        tx["from"] = json!(from);
+    }
+    let gas = quantity(&crate::ipc::rpc_request(
+        state,
+        "eth_estimateGas",
+        json!([tx]),
+    )?)
+    .ok_or_else(|| anyhow!("eth_estimateGas returned an invalid quantity"))?;
+    let gas_price = quantity(&crate::ipc::rpc_request(state, "eth_gasPrice", json!([]))?)
+        .ok_or_else(|| anyhow!("eth_gasPrice returned an invalid quantity"))?;
+    Ok(GasEstimate {
+        chain_id: resolved.chain_id,
+        gas: gas.to_string(),
+        gas_price: gas_price.to_string(),
+        fee: format_units(gas * gas_price, 18).unwrap_or_default(),
+    })
+}
+
+fn quantity(value: &Value) -> Option<U256> {
+    let raw = value.as_str()?;
+    U256::from_str_radix(raw.strip_prefix("0x")?, 16).ok()
+}
+
+fn audit_step(audit: &Audit) -> PreflightStep {
+    if audit.warnings.is_empty() {
+        passed(
+            "audit",
+            format!("{} capability grant(s)", audit.granted.len()),
+        )
+    } else {
+        PreflightStep {
+            name: "audit",
+            status: StepStatus::Warning,
+            message: format!("{} thing(s) to review", audit.warnings.len()),
+            details: audit.warnings.clone(),
+        }
+    }
+}
+
+/// Lists what the manifest grants and flags what a reviewer should look at.
+/// Reads `capabilities` the way the capability loader does.
+fn audit(manifest: &BundleManifest) -> Audit {
+    let mut audit = Audit::default();
+    if manifest
+        .name
+        .as_deref()
+        .is_none_or(|name| name.trim().is_empty())
+    {
+        audit.warnings.push("manifest.json has no name".to_string());
+    }
+    if manifest.schema_version < crate::manifest::LATEST_SCHEMA_VERSION {
+        audit.warnings.push(format!(
+            "manifest uses schema {}; schema {} requires a sha256 for every file",
+            manifest.schema_version,
+            crate::manifest::LATEST_SCHEMA_VERSION
+        ));
+    }
+    let unhashed = manifest.files.iter().filter(|f| f.sha256.is_none()).count();
+    if unhashed > 0 {
+        audit
+            .warnings
+            .push(format!("{unhashed} file(s) have no sha256"));
+    }
+    if manifest.chains.is_empty() {
+        audit
+            .warnings
+            .push("no chains declared; the dapp may switch to any chain".to_string());
+    }
+
+    let Some(capabilities) = &manifest.capabilities else {
+        return audit;
+    };
+    let Some(capabilities) = capabilities.as_object() else {
+        audit
+            .warnings
+            .push("capabilities is not an object; the dapp would get none".to_string());
+        return audit;
+    };
+    let strings = |section: &str, key: &str| -> Vec<String> {
+        capabilities
+            .get(section)
+            .and_then(|section| section.get(key))
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|item| item.trim().to_string())
+                    .filter(|item| !item.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let flag = |section: &str, key: &str| {
+        capabilities
+            .get(section)
+            .and_then(|section| section.get(key))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    };
+
+    for origin in strings("network", "allow") {
+        match crate::ipc::normalize_network_origin(&origin) {
+            Some(origin) if crate::ipc::is_loopback_origin(&origin) => audit.warnings.push(format!(
+                "network origin {origin} is on the user's machine; published dapps are not granted it"
+            )),
+            Some(origin) => audit.granted.push(format!("network access to {origin}")),
+            None => audit.warnings.push(format!(
+                "network origin {origin} is ignored; only https, or http on localhost, is allowed"
+            )),
+        }
+    }
+    let ipfs_rules = capabilities
+        .get("ipfs")
+        .and_then(|ipfs| ipfs.get("allow"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    for rule in ipfs_rules {
+        match rule.get("cid").and_then(Value::as_str) {
+            Some(cid) => audit.granted.push(format!("IPFS reads from {cid}")),
+            None => {
+                audit.granted.push("IPFS reads from any CID".to_string());
+                audit
+                    .warnings
+                    .push("an ipfs.allow rule has no cid and matches any content".to_string());
+            }
+        }
+    }
+    if flag("clipboard", "write") {
+        audit.granted.push("clipboard write".to_string());
+    }
+    if flag("qr", "scan") {
+        audit.granted.push("camera QR scanning".to_string());
+    }
+    if flag("notifications", "send") {
+        audit.granted.push("desktop notifications".to_string());
+    }
+    for topic in strings("events", "subscribe") {
+        match crate::subscriptions::Topic::parse(&topic) {
+            Some(_) => audit.granted.push(format!("{topic} events")),
+            None => audit
+                .warnings
+                .push(format!("unknown event topic {topic} is ignored")),
+        }
+    }
+    let (open, save) = (flag("files", "open"), flag("files", "save"));
+    if open || save {
+        let modes = [(open, "open"), (save, "save")]
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, mode)| *mode)
+            .collect::<Vec<_>>()
+            .join(" and ");
+        let extensions = strings("files", "extensions");
+        if extensions.is_empty() {
+            audit.granted.push(format!("file {modes} (any type)"));
+            audit
+                .warnings
+                .push("files capability allows any file type".to_string());
+        } else {
+            audit
+                .granted
+                .push(format!("file {modes} ({})", extensions.join(", ")));
+        }
+        let max_bytes = capabilities
+            .get("files")
+            .and_then(|files| files.get("maxBytes"))
+            .and_then(Value::as_u64);
+        if max_bytes.is_some_and(|max| max > crate::state::MAX_FILE_BYTES as u64) {
+            audit.warnings.push(format!(
+                "files.maxBytes is capped at {} MiB",
+                crate::state::MAX_FILE_BYTES / 1024 / 1024
+            ));
+        }
+    }
+    for peer in strings("messaging", "peers") {
+        audit.granted.push(format!("messages with dapp {peer}"));
+    }
+    for key in capabilities.keys() {
+        if !KNOWN_CAPABILITIES.contains(&key.as_str()) {
+            audit
+                .warnings
+                .push(format!("unknown capability {key} is ignored"));
+        }
+    }
+    audit
+}
+
+const KNOWN_CAPABILITIES: &[&str] = &[
+    "ipfs",
+    "network",
+    "clipboard",
+    "qr",
+    "events",
+    "notifications",
+    "files",
+    "messaging",
+];
+
+fn passed(name: &'static str, message: String) -> PreflightStep {
+    PreflightStep {
+        name,
+        status: StepStatus::Passed,
+        message,
+        details: Vec::new(),
+    }
+}
+
+fn failed(name: &'static str, message: String, details: Vec<String>) -> PreflightStep {
+    PreflightStep {
+        name,
+        status: StepStatus::Failed,
+        message,
+        details,
+    }
+}
+
+fn skipped(name: &'static str, message: &str) -> PreflightStep {
+    PreflightStep {
+        name,
+        status: StepStatus::Skipped,
+        message: message.to_string(),
+        details: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Audit, audit};
+    use crate::manifest::parse;
+    use serde_json::json;
+
+    fn loose_manifest() -> serde_json::Value {
+        json!({
+            "schemaVersion": 2,
+            "name": "Swap",
+            "files": [{ "path": "index.html", "bytes": 3, "sha256": "ab" }],
+            "requirements": { "chains": { "required": 1 } },
+            "capabilities": {
+                "network": { "allow": ["https://api.example.com/v1", "http://example.com"] },
+                "ipfs": { "allow": [{ "paths": ["*"] }] },
+                "clipboard": { "write": true },
+                "events": { "subscribe": ["txStatus", "priceTicks"] },
+                "files": { "save": true, "extensions": ["csv"] },
+                "camera": true
+            }
+        })
+    }
+
+    #[test]
+    fn lists_granted_capabilities() {
+        let audit = audit_of(&loose_manifest());
+        assert_eq!(
+            audit.granted,
+            [
+                "network access to https://api.example.com",
+                "IPFS reads from any CID",
+                "clipboard write",
+                "txStatus events",
+                "file save (csv)",
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_loose_capabilities() {
+        let audit = audit_of(&loose_manifest());
+        assert_eq!(audit.warnings.len(), 4);
+        assert!(audit.warnings[0].contains("http://example.com"));
+        assert!(audit.warnings.iter().any(|w| w.contains("priceTicks")));
+        assert!(audit.warnings.iter().any(|w| w.contains("camera")));
+    }
+
+    #[test]
+    fn flags_a_bare_manifest() {
+        let bare = json!({ "files": [{ "path": "index.html", "bytes": 3 }] });
+        let audit = audit_of(&bare);
+        assert!(audit.granted.is_empty());
+        // No name, schema 1, an unhashed file and no chains.
+        assert_eq!(audit.warnings.len(), 4);
+    }
+
+    fn audit_of(manifest: &serde_json::Value) -> Audit {
+        audit(&parse(manifest.to_string().as_bytes()).unwrap())
+    }
+}
@@ -0,0 +1,187 @@
+use alloy_primitives::Address;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+
+/// Largest camera frame accepted for decoding (a 1080p frame).
+pub const MAX_FRAME_PIXELS: usize = 1920 * 1080;
+/// Largest imported image file accepted for decoding.
+pub const MAX_IMAGE_BYTES: usize = 8 * 1024 * 1024;
+
+/// What a scanned QR code contains, as far as the wallet cares.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum QrPayload {
+    /// A WalletConnect v2 pairing URI (`wc:<topic>@2?...`).
+    #[serde(rename_all = "camelCase")]
+    Walletconnect {
+        uri: String,
+    },
+    /// A BC-UR payload from an air-gapped signer (`ur:<type>/...`).
+    #[serde(rename_all = "camelCase")]
+    AirGapped {
+        ur_type: String,
+        value: String,
+    },
+    /// A bare address or EIP-681 `ethereum:` URI, checksummed.
+    #[serde(rename_all = "camelCase")]
+    Address {
+        address: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        chain_id: Option<u64>,
+    },
+    Text {
+        value: String,
+    },
+}
+
+/// Decodes every QR code in an 8-bit greyscale frame.
+pub fn decode_luma(width: usize, height: usize, luma: &[u8]) -> Result<Vec<String>> {
+    if width == 0 || height == 0 {
+        bail!("frame is empty");
+    }
+    if width.saturating_mul(height) > MAX_FRAME_PIXELS {
+        bail!("frame exceeds {MAX_FRAME_PIXELS} pixels");
+    }
+    if luma.len() != width * height {
+        bail!(
+            "frame has {} bytes, expected {}x{}",
+            luma.len(),
+            width,
+            height
+        );
+    }
+    let mut prepared =
+        rqrr::PreparedImage::prepare_from_greyscale(width, height, |x, y| luma[y * width + x]);
+    Ok(prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| match grid.decode() {
+            Ok((_, content)) => Some(content),
+            Err(err) => {
+                tracing::debug!(error = %err, "qr grid failed to decode");
+                None
+            }
+        })
+        .collect())
+}
+
+/// Decodes every QR code in an encoded PNG or JPEG image.
+pub fn decode_image(bytes: &[u8]) -> Result<Vec<String>> {
+    if bytes.len() > MAX_IMAGE_BYTES {
+        bail!("image exceeds {MAX_IMAGE_BYTES} bytes");
+    }
+    let image = image::load_from_memory(bytes)
+        .context("unsupported image")?
+        .to_luma8();
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    decode_luma(width, height, image.as_raw())
+}
+
+pub fn classify(raw: &str) -> QrPayload {
+    let text = raw.trim();
+    let lower = text.to_ascii_lowercase();
+    if lower.starts_with("wc:") && text.contains('@') {
+        return QrPayload::Walletconnect {
+            uri: text.to_string(),
+        };
+    }
+    if let Some(rest) = lower.strip_prefix("ur:") {
+        if let Some((ur_type, _)) = rest.split_once('/') {
+            return QrPayload::AirGapped {
+                ur_type: ur_type.to_string(),
+                value: lower.clone(),
+            };
+        }
+    }
+    if let Some(payload) = classify_address(text) {
+        return payload;
+    }
+    QrPayload::Text {
+        value: text.to_string(),
+    }
+}
+
+fn classify_address(text: &str) -> Option<QrPayload> {
+    let target = match text.get(..9) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("ethereum:") => {
+            let rest = &text[9..];
+            rest.strip_prefix("pay-").unwrap_or(rest)
+        }
+        _ => text,
+    };
+    // EIP-681: `0xADDR[@chainId][/function][?params]`
+    let end = target.find(['/', '?']).unwrap_or(target.len());
+    let (address, chain_id) = match target[..end].split_once('@') {
+        Some((address, chain)) => (address, Some(chain.parse::<u64>().ok()?)),
+        None => (&target[..end], None),
+    };
+    if address.len() != 42 || !address.starts_with("0x") {
+        return None;
+    }
+    let digits = &address[2..];
+    let mixed_case = digits.chars().any(|c| c.is_ascii_lowercase())
+        && digits.chars().any(|c| c.is_ascii_uppercase());
+    let parsed = if mixed_case {
+        Address::parse_checksummed(address, None).ok()?
+    } else {
+        address.parse::<Address>().ok()?
+    };
+    Some(QrPayload::Address {
+        address: parsed.to_checksum(None),
+        chain_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QrPayload, classify, decode_luma};
+
+    const ADDRESS: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+    #[test]
+    fn classifies_addresses_and_eip681_uris() {
+        assert_eq!(
+            classify(&ADDRESS.to_ascii_lowercase()),
+            QrPayload::Address {
+                address: ADDRESS.to_string(),
+                chain_id: None,
+            }
+        );
+        assert_eq!(
+            classify(&format!("ethereum:pay-{ADDRESS}@11155111?value=1e18")),
+            QrPayload::Address {
+                address: ADDRESS.to_string(),
+                chain_id: Some(11155111),
+            }
+        );
+        // A bad checksum is most likely a misread, not a different account.
+        let bad = ADDRESS.replace('C', "c").replacen('d', "D", 1);
+        assert!(matches!(classify(&bad), QrPayload::Text { .. }));
+    }
+
+    #[test]
+    fn classifies_pairing_and_air_gapped_payloads() {
+        let wc = "wc:7f6e504bfad60b485450578e05678ed3e8e8c4751d3c6160be17160d63ec90f9@2?relay-protocol=irn&symKey=587d5484ce2a2a6ee3ba1962fdd7e8588e06200c46823bd18fbd67def96ad303";
+        assert_eq!(
+            classify(wc),
+            QrPayload::Walletconnect {
+                uri: wc.to_string()
+            }
+        );
+        assert_eq!(
+            classify("UR:ETH-SIGNATURE/OEADTPDAGD"),
+            QrPayload::AirGapped {
+                ur_type: "eth-signature".to_string(),
+                value: "ur:eth-signature/oeadtpdagd".to_string(),
+            }
+        );
+        assert!(matches!(classify("hello"), QrPayload::Text { .. }));
+    }
+
+    #[test]
+    fn rejects_malformed_frames() {
+        assert!(decode_luma(0, 10, &[]).is_err());
+        assert!(decode_luma(4, 4, &[0; 15]).is_err());
+        assert!(decode_luma(4, 4, &[255; 16]).unwrap().is_empty());
+    }
+}
@@ -3,17 +3,26 @@ use alloy_sol_types::{SolEvent, sol};
 use anyhow::{Context, Result, anyhow, bail};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     io::ErrorKind,
     path::{Component, Path, PathBuf},
     str::FromStr,
+    sync::{
+        Condvar, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
 };
 
-use crate::bundle::{BundleManifest, build_bundle, verify_manifest};
-use crate::config::{IpfsFetchBackend, ResolvedConfig};
-use crate::ipfs_helper::{IpfsHelperBridge, IpfsHelperConfig};
-use crate::state::{AppState, TabAction, UserEvent};
+use crate::bundle::{build_bundle, verify_manifest, verify_root_cid};
+use crate::config::{IpfsFetchBackend, RegistrySchema, ResolvedConfig};
+use crate::download::{read_limited, stream_to_file};
+use crate::launch_history::LaunchRecorder;
+use crate::manifest::{self, BundleManifest, BundleManifestFile};
+use crate::state::{AppState, DappIdentity, TabAction, UserEvent};
+use crate::subscriptions::Topic;
+use crate::verified_fetch::VerifiedFetcher;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,6 +34,44 @@ pub struct DappInfo {
     pub description: String,
     pub status: String,
     pub root_cid: String,
+    /// Proposer of the latest version.
+    pub publisher: String,
+    /// Whether the user has reported this dapp; the launcher hides these by default.
+    pub reported: bool,
+    /// Whether the user pinned this dapp for background prefetch.
+    pub pinned: bool,
+    /// Listed from the pinned-dapp cache because the registry was unreachable.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    /// Manifest hash the publisher committed to on chain; schema v2 registries only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest_hash: Option<String>,
+    /// Publisher signature over the release; schema v2 registries only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher_signature: Option<String>,
+    /// Community registry address; unset for the configured registry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// Label of the registry that listed the dapp.
+    pub registry_label: String,
+    /// Trust warnings from the listing registry's own policy.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// `data:` URL of the manifest's icon once cached; see [`crate::registry_cache`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+/// Shown as the provenance of dapps from the configured `dappRegistry`.
+pub const DEFAULT_REGISTRY_LABEL: &str = "VibeFi";
+
+/// Identifies a dapp across registries; dapp ids are only unique per registry.
+/// Dapps of the configured registry keep their bare id.
+pub fn dapp_key(registry: Option<&str>, dapp_id: &str) -> String {
+    match registry {
+        Some(registry) => format!("{registry}:{dapp_id}"),
+        None => dapp_id.to_string(),
+    }
 }
 
 sol! {
@@ -40,11 +87,51 @@ sol! {
     event DappPaused(uint256 indexed dappId, uint256 indexed versionId, address pausedBy, string reason);
     event DappUnpaused(uint256 indexed dappId, uint256 indexed versionId, address unpausedBy, string reason);
     event DappDeprecated(uint256 indexed dappId, uint256 indexed versionId, address deprecatedBy, string reason);
+
+    // Schema v2.
+    event DappPublishedV2(
+        uint256 indexed dappId,
+        uint256 indexed versionId,
+        bytes rootCid,
+        address proposer,
+        bytes32 manifestHash,
+        bytes publisherSignature
+    );
+    event DappUpgradedV2(
+        uint256 indexed dappId,
+        uint256 indexed fromVersionId,
+        uint256 indexed toVersionId,
+        bytes rootCid,
+        address proposer,
+        bytes32 manifestHash,
+        bytes publisherSignature
+    );
 }
 
-#[derive(Debug, Deserialize)]
+/// Events scanned for a registry with `schema`.
+fn registry_topics(schema: RegistrySchema) -> Vec<B256> {
+    let mut topics = vec![
+        DappPublished::SIGNATURE_HASH,
+        DappUpgraded::SIGNATURE_HASH,
+        DappMetadata::SIGNATURE_HASH,
+        DappPaused::SIGNATURE_HASH,
+        DappUnpaused::SIGNATURE_HASH,
+        DappDeprecated::SIGNATURE_HASH,
+    ];
+    if schema == RegistrySchema::V2 {
+        topics.extend([
+            DappPublishedV2::SIGNATURE_HASH,
+            DappUpgradedV2::SIGNATURE_HASH,
+        ]);
+    }
+    topics
+}
+
+/// A log as `eth_getLogs` returns it; also the form kept in
+/// [`crate::registry_index`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct RpcLog {
+pub(crate) struct RpcLog {
     address: String,
     data: String,
     topics: Vec<String>,
@@ -54,6 +141,13 @@ struct RpcLog {
     log_index: Option<String>,
 }
 
+impl RpcLog {
+    /// Block the log was mined in; `None` for pending logs.
+    pub(crate) fn block(&self) -> Option<u64> {
+        parse_hex_u64_opt(self.block_number.as_deref())
+    }
+}
+
 struct LogEntry {
     block_number: u64,
     log_index: u64,
@@ -61,17 +155,59 @@ struct LogEntry {
     log: Log,
 }
 
+/// IPFS settings after `settings.json` overrides are applied.
 #[derive(Debug, Clone)]
-struct EffectiveIpfsConfig {
-    fetch_backend: IpfsFetchBackend,
-    gateway_endpoint: String,
-    helia_gateways: Vec<String>,
-    helia_routers: Vec<String>,
-    helia_timeout_ms: u64,
+pub(crate) struct EffectiveIpfsConfig {
+    pub fetch_backend: IpfsFetchBackend,
+    pub gateway_endpoint: String,
+    pub helia_gateways: Vec<String>,
+    pub helia_routers: Vec<String>,
+    /// Bundle files downloaded at once.
+    pub download_concurrency: usize,
+}
+
+/// Root CIDs whose cache dir is being written. A launch and a background
+/// prefetch of the same bundle take turns instead of racing on its files.
+static PREPARING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static PREPARE_DONE: Condvar = Condvar::new();
+
+/// Serializes work on one cached bundle: preparing it for launch, prefetching
+/// it, or re-verifying it.
+pub(crate) struct PrepareGuard {
+    root_cid: String,
+}
+
+impl PrepareGuard {
+    pub(crate) fn acquire(root_cid: &str) -> Self {
+        let mut preparing = PREPARE_DONE
+            .wait_while(PREPARING.lock().expect("preparing"), |cids| {
+                cids.iter().any(|cid| cid == root_cid)
+            })
+            .expect("preparing");
+        preparing.push(root_cid.to_string());
+        Self {
+            root_cid: root_cid.to_string(),
+        }
+    }
+}
+
+impl Drop for PrepareGuard {
+    fn drop(&mut self) {
+        PREPARING
+            .lock()
+            .expect("preparing")
+            .retain(|cid| *cid != self.root_cid);
+        PREPARE_DONE.notify_all();
+    }
 }
 
 const LAUNCH_PROGRESS_EVENT: &str = "vibefiLaunchProgress";
-const RPC_LOGS_BLOCK_CHUNK: u64 = 50_000;
+pub(crate) const MAX_MANIFEST_BYTES: u64 = 4 * 1024 * 1024;
+/// Bundle files fetched at once unless `ipfs.downloadConcurrency` says otherwise.
+pub(crate) const DEFAULT_DOWNLOAD_CONCURRENCY: usize = 6;
+pub(crate) const MAX_DOWNLOAD_CONCURRENCY: usize = 16;
+/// Tries per bundle file before a download gives up.
+const FILE_DOWNLOAD_ATTEMPTS: u32 = 3;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -113,6 +249,9 @@ impl LaunchProgress {
     }
 }
 
+/// Lists the dapps of the configured registry followed by those of every
+/// community registry on the current chain. An unreachable community registry
+/// is skipped rather than failing the whole list.
 pub fn list_dapps(state: &AppState) -> Result<Vec<DappInfo>> {
     let devnet = state
         .resolved
@@ -121,21 +260,323 @@ pub fn list_dapps(state: &AppState) -> Result<Vec<DappInfo>> {
     if devnet.dapp_registry.is_empty() {
         return Err(anyhow!("config missing dappRegistry"));
     }
-    let address = devnet.dapp_registry.clone();
-    let published = rpc_get_logs(state, &address, DappPublished::SIGNATURE_HASH)?;
-    let upgraded = rpc_get_logs(state, &address, DappUpgraded::SIGNATURE_HASH)?;
-    let metadata = rpc_get_logs(state, &address, DappMetadata::SIGNATURE_HASH)?;
-    let paused = rpc_get_logs(state, &address, DappPaused::SIGNATURE_HASH)?;
-    let unpaused = rpc_get_logs(state, &address, DappUnpaused::SIGNATURE_HASH)?;
-    let deprecated = rpc_get_logs(state, &address, DappDeprecated::SIGNATURE_HASH)?;
+    let settings = devnet
+        .config_path
+        .as_deref()
+        .map(crate::settings::load_settings)
+        .unwrap_or_default();
 
-    let mut all = Vec::new();
-    all.extend(published);
-    all.extend(upgraded);
-    all.extend(metadata);
-    all.extend(paused);
-    all.extend(unpaused);
-    all.extend(deprecated);
+    let mut result = list_registry_dapps(
+        state,
+        &devnet.dapp_registry,
+        devnet.deploy_block.unwrap_or(0),
+    )?;
+    for dapp in &mut result {
+        dapp.registry_label = DEFAULT_REGISTRY_LABEL.to_string();
+    }
+    for registry in &settings.community_registries {
+        if registry.chain_id != devnet.chain_id {
+            tracing::debug!(
+                registry = %registry.address,
+                chain_id = registry.chain_id,
+                "skipping community registry on another chain"
+            );
+            continue;
+        }
+        let from_block = registry.deploy_block.unwrap_or(0);
+        match list_registry_dapps(state, &registry.address, from_block) {
+            Ok(dapps) => result.extend(dapps.into_iter().map(|mut dapp| {
+                dapp.warnings = community_warnings(registry, &dapp.publisher);
+                dapp.registry = Some(registry.address.clone());
+                dapp.registry_label = registry.label.clone();
+                dapp
+            })),
+            Err(err) => tracing::warn!(
+                registry = %registry.address,
+                label = %registry.label,
+                error = %format!("{err:#}"),
+                "community registry unreachable; its dapps are not listed"
+            ),
+        }
+    }
+
+    let reported = crate::reports::reported_dapp_ids(devnet);
+    let pinned: Vec<String> = settings
+        .pinned_dapps
+        .iter()
+        .map(|pin| dapp_key(pin.registry.as_deref(), &pin.dapp_id))
+        .collect();
+    for dapp in &mut result {
+        let key = dapp_key(dapp.registry.as_deref(), &dapp.dapp_id);
+        dapp.reported = reported.contains(&key);
+        dapp.pinned = pinned.contains(&key);
+    }
+    publish_registry_updates(state, &result);
+    Ok(result)
+}
+
+/// Root CID of every dapp in the previous listing, keyed by [`dapp_key`].
+static LAST_LISTING: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+/// Tells `registryUpdates` subscribers about dapps that are new or have a new
+/// latest version since the previous listing, and the tab bar about open
+/// tabs running an older version. The first listing only records for
+/// subscribers.
+fn publish_registry_updates(state: &AppState, dapps: &[DappInfo]) {
+    let listing: HashMap<String, String> = dapps
+        .iter()
+        .map(|dapp| {
+            (
+                dapp_key(dapp.registry.as_deref(), &dapp.dapp_id),
+                dapp.root_cid.clone(),
+            )
+        })
+        .collect();
+    let _ = state.proxy.send_event(UserEvent::DappsListed {
+        latest: listing.clone(),
+    });
+    let previous = LAST_LISTING.lock().expect("last_listing").replace(listing);
+    let Some(previous) = previous else {
+        return;
+    };
+    for dapp in dapps {
+        let change = match previous.get(&dapp_key(dapp.registry.as_deref(), &dapp.dapp_id)) {
+            None => "added",
+            Some(root_cid) if *root_cid != dapp.root_cid => "updated",
+            Some(_) => continue,
+        };
+        crate::subscriptions::publish(
+            state,
+            Topic::RegistryUpdates,
+            None,
+            serde_json::json!({
+                "change": change,
+                "dappId": dapp.dapp_id,
+                "registry": dapp.registry,
+                "registryLabel": dapp.registry_label,
+                "name": dapp.name,
+                "version": dapp.version,
+                "versionId": dapp.version_id,
+                "rootCid": dapp.root_cid,
+            }),
+        );
+    }
+}
+
+/// Warnings for a dapp listed by `registry`, judged only by that registry's
+/// allowlist so one registry's trust never vouches for another's dapps.
+fn community_warnings(
+    registry: &crate::settings::CommunityRegistry,
+    publisher: &str,
+) -> Vec<String> {
+    if registry.trusted_publishers.is_empty() {
+        return vec![format!(
+            "Listed by community registry \"{}\", which vouches for no publishers",
+            registry.label
+        )];
+    }
+    let trusted = registry
+        .trusted_publishers
+        .iter()
+        .any(|trusted| trusted.eq_ignore_ascii_case(publisher));
+    if trusted {
+        Vec::new()
+    } else {
+        vec![format!(
+            "Publisher {} is not on the allowlist of \"{}\"",
+            if publisher.is_empty() {
+                "(unknown)"
+            } else {
+                publisher
+            },
+            registry.label
+        )]
+    }
+}
+
+fn list_registry_dapps(state: &AppState, address: &str, from_block: u64) -> Result<Vec<DappInfo>> {
+    Ok(registry_history(state, address, from_block)?
+        .values()
+        .map(|dapp| {
+            version_info(
+                dapp.dapp_id,
+                dapp.latest_version_id,
+                dapp.versions.get(&dapp.latest_version_id),
+            )
+        })
+        .collect())
+}
+
+/// Every version of `dapp_id`, newest first, as listed by the configured
+/// registry or, with `registry` set, by that community registry.
+pub fn list_dapp_versions(
+    state: &AppState,
+    registry: Option<&str>,
+    dapp_id: &str,
+) -> Result<Vec<DappInfo>> {
+    let devnet = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("Network not configured"))?;
+    let id: u64 = dapp_id
+        .parse()
+        .map_err(|_| anyhow!("invalid dappId: {dapp_id}"))?;
+    let community = match registry {
+        Some(address) => {
+            let settings = devnet
+                .config_path
+                .as_deref()
+                .map(crate::settings::load_settings)
+                .unwrap_or_default();
+            let community = settings
+                .community_registries
+                .into_iter()
+                .find(|known| {
+                    known.chain_id == devnet.chain_id && known.address.eq_ignore_ascii_case(address)
+                })
+                .ok_or_else(|| anyhow!("unknown community registry {address}"))?;
+            Some(community)
+        }
+        None => None,
+    };
+    let (address, from_block) = match &community {
+        Some(community) => (community.address.as_str(), community.deploy_block),
+        None => (devnet.dapp_registry.as_str(), devnet.deploy_block),
+    };
+    if address.is_empty() {
+        bail!("config missing dappRegistry");
+    }
+    let history = registry_history(state, address, from_block.unwrap_or(0))?;
+    let dapp = history
+        .get(&id)
+        .ok_or_else(|| anyhow!("dappId {dapp_id} not found in the registry"))?;
+    let reported = crate::reports::reported_dapp_ids(devnet).contains(&dapp_key(
+        community
+            .as_ref()
+            .map(|community| community.address.as_str()),
+        dapp_id,
+    ));
+    Ok(dapp
+        .versions
+        .iter()
+        .rev()
+        .map(|(version_id, version)| {
+            let mut info = version_info(dapp.dapp_id, *version_id, Some(version));
+            info.reported = reported;
+            match &community {
+                Some(community) => {
+                    info.warnings = community_warnings(community, &info.publisher);
+                    info.registry = Some(community.address.clone());
+                    info.registry_label = community.label.clone();
+                }
+                None => info.registry_label = DEFAULT_REGISTRY_LABEL.to_string(),
+            }
+            info
+        })
+        .collect())
+}
+
+/// `(dappId, versionId, registry)` from `vibefi_launchDapp`'s options. Ids
+/// may be strings or numbers.
+fn parse_version_pick(options: &serde_json::Value) -> Result<(String, String, Option<String>)> {
+    let id = |key: &str| match options.get(key) {
+        Some(serde_json::Value::String(id)) => Ok(id.clone()),
+        Some(serde_json::Value::Number(id)) => Ok(id.to_string()),
+        _ => Err(anyhow!("missing or invalid {key}")),
+    };
+    let registry = options
+        .get("registry")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    Ok((id("dappId")?, id("versionId")?, registry))
+}
+
+/// Launches `version_id` of a dapp rather than its latest version, for
+/// rolling back to a release that works.
+fn launch_dapp_version(
+    state: &AppState,
+    webview_id: &str,
+    registry: Option<&str>,
+    dapp_id: &str,
+    version_id: &str,
+) -> Result<()> {
+    let version = list_dapp_versions(state, registry, dapp_id)?
+        .into_iter()
+        .find(|version| version.version_id == version_id)
+        .ok_or_else(|| anyhow!("dappId {dapp_id} has no version {version_id}"))?;
+    if version.root_cid.is_empty() {
+        bail!("version {version_id} of dappId {dapp_id} has no rootCid");
+    }
+    if version.status == "Paused" {
+        bail!("version {version_id} of dappId {dapp_id} is paused by the registry");
+    }
+    tracing::info!(
+        target: "vibefi::audit",
+        dapp_id,
+        version_id,
+        root_cid = %version.root_cid,
+        status = %version.status,
+        "launching a specific dapp version"
+    );
+    let name = if version.name.is_empty() {
+        version.root_cid.clone()
+    } else {
+        version.name.clone()
+    };
+    remember_dapp_identities(state, std::slice::from_ref(&version));
+    launch_dapp(state, webview_id, &version.root_cid, &name)
+}
+
+/// Listing entry for one version; `version` is `None` when the registry
+/// emitted nothing for it.
+fn version_info(dapp_id: u64, version_id: u64, version: Option<&RegistryVersion>) -> DappInfo {
+    DappInfo {
+        dapp_id: dapp_id.to_string(),
+        version_id: version_id.to_string(),
+        name: version.and_then(|v| v.name.clone()).unwrap_or_default(),
+        version: version.and_then(|v| v.version.clone()).unwrap_or_default(),
+        description: version
+            .and_then(|v| v.description.clone())
+            .unwrap_or_default(),
+        status: version
+            .and_then(|v| v.status.clone())
+            .unwrap_or_else(|| "Unknown".to_string()),
+        root_cid: version.and_then(|v| v.root_cid.clone()).unwrap_or_default(),
+        publisher: version
+            .and_then(|v| v.publisher.clone())
+            .unwrap_or_default(),
+        manifest_hash: version.and_then(|v| v.manifest_hash.clone()),
+        publisher_signature: version.and_then(|v| v.publisher_signature.clone()),
+        reported: false,
+        pinned: false,
+        offline: false,
+        registry: None,
+        registry_label: String::new(),
+        warnings: Vec::new(),
+        icon: None,
+    }
+}
+
+/// Every dapp `address` has published, with all of its versions.
+fn registry_history(
+    state: &AppState,
+    address: &str,
+    from_block: u64,
+) -> Result<BTreeMap<u64, RegistryDapp>> {
+    let schema = state
+        .resolved
+        .as_ref()
+        .map(|resolved| resolved.registry_schema(address))
+        .unwrap_or_default();
+    tracing::debug!(
+        registry = address,
+        schema = schema.as_str(),
+        "scanning registry"
+    );
+    let mut all = registry_logs(state, address, from_block, schema)?
+        .into_iter()
+        .map(rpc_log_to_entry)
+        .collect::<Result<Vec<_>>>()?;
     all.sort_by(|a, b| {
         let block_diff = a.block_number.cmp(&b.block_number);
         if block_diff != std::cmp::Ordering::Equal {
@@ -143,38 +584,52 @@ pub fn list_dapps(state: &AppState) -> Result<Vec<DappInfo>> {
         }
         a.log_index.cmp(&b.log_index)
     });
+    assemble_registry(all)
+}
 
-    #[derive(Debug)]
-    struct Version {
-        root_cid: Option<String>,
-        name: Option<String>,
-        version: Option<String>,
-        description: Option<String>,
-        status: Option<String>,
-    }
-    #[derive(Debug)]
-    struct Dapp {
-        dapp_id: u64,
-        latest_version_id: u64,
-        versions: HashMap<u64, Version>,
-    }
+/// What the registry's events say about one version of a dapp.
+#[derive(Debug)]
+struct RegistryVersion {
+    root_cid: Option<String>,
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    status: Option<String>,
+    publisher: Option<String>,
+    manifest_hash: Option<String>,
+    publisher_signature: Option<String>,
+}
+
+#[derive(Debug)]
+struct RegistryDapp {
+    dapp_id: u64,
+    latest_version_id: u64,
+    versions: BTreeMap<u64, RegistryVersion>,
+}
 
-    let mut dapps: HashMap<u64, Dapp> = HashMap::new();
+/// Folds registry events, oldest first, into each dapp's versions.
+fn assemble_registry(all: Vec<LogEntry>) -> Result<BTreeMap<u64, RegistryDapp>> {
+    let mut dapps: BTreeMap<u64, RegistryDapp> = BTreeMap::new();
 
     macro_rules! get_or_create_version {
         ($dapps:expr, $dapp_id:expr, $version_id:expr) => {{
-            let dapp = $dapps.entry($dapp_id).or_insert_with(|| Dapp {
+            let dapp = $dapps.entry($dapp_id).or_insert_with(|| RegistryDapp {
                 dapp_id: $dapp_id,
                 latest_version_id: 0,
-                versions: HashMap::new(),
+                versions: BTreeMap::new(),
             });
-            dapp.versions.entry($version_id).or_insert_with(|| Version {
-                root_cid: None,
-                name: None,
-                version: None,
-                description: None,
-                status: None,
-            })
+            dapp.versions
+                .entry($version_id)
+                .or_insert_with(|| RegistryVersion {
+                    root_cid: None,
+                    name: None,
+                    version: None,
+                    description: None,
+                    status: None,
+                    publisher: None,
+                    manifest_hash: None,
+                    publisher_signature: None,
+                })
         }};
     }
 
@@ -188,6 +643,7 @@ pub fn list_dapps(state: &AppState) -> Result<Vec<DappInfo>> {
                 let v = get_or_create_version!(dapps, dapp_id, version_id);
                 v.root_cid = Some(root);
                 v.status = Some("Published".to_string());
+                v.publisher = Some(decoded.data.proposer.to_string());
                 dapps
                     .get_mut(&dapp_id)
                     .expect("dapp entry missing after version creation")
@@ -201,6 +657,39 @@ pub fn list_dapps(state: &AppState) -> Result<Vec<DappInfo>> {
                 let v = get_or_create_version!(dapps, dapp_id, version_id);
                 v.root_cid = Some(root);
                 v.status = Some("Published".to_string());
+                v.publisher = Some(decoded.data.proposer.to_string());
+                dapps
+                    .get_mut(&dapp_id)
+                    .expect("dapp entry missing after version creation")
+                    .latest_version_id = version_id;
+            }
+            "DappPublishedV2" => {
+                let decoded = DappPublishedV2::decode_log(&log.log)?;
+                let dapp_id = u256_to_u64(decoded.data.dappId)?;
+                let version_id = u256_to_u64(decoded.data.versionId)?;
+                let root = bytes_to_string(&decoded.data.rootCid);
+                let v = get_or_create_version!(dapps, dapp_id, version_id);
+                v.root_cid = Some(root);
+                v.status = Some("Published".to_string());
+                v.publisher = Some(decoded.data.proposer.to_string());
+                v.manifest_hash = Some(format!("{:#x}", decoded.data.manifestHash));
+                v.publisher_signature = Some(decoded.data.publisherSignature.to_string());
+                dapps
+                    .get_mut(&dapp_id)
+                    .expect("dapp entry missing after version creation")
+                    .latest_version_id = version_id;
+            }
+            "DappUpgradedV2" => {
+                let decoded = DappUpgradedV2::decode_log(&log.log)?;
+                let dapp_id = u256_to_u64(decoded.data.dappId)?;
+                let version_id = u256_to_u64(decoded.data.toVersionId)?;
+                let root = bytes_to_string(&decoded.data.rootCid);
+                let v = get_or_create_version!(dapps, dapp_id, version_id);
+                v.root_cid = Some(root);
+                v.status = Some("Published".to_string());
+                v.publisher = Some(decoded.data.proposer.to_string());
+                v.manifest_hash = Some(format!("{:#x}", decoded.data.manifestHash));
+                v.publisher_signature = Some(decoded.data.publisherSignature.to_string());
                 dapps
                     .get_mut(&dapp_id)
                     .expect("dapp entry missing after version creation")
@@ -239,29 +728,222 @@ pub fn list_dapps(state: &AppState) -> Result<Vec<DappInfo>> {
             _ => {}
         }
     }
+    Ok(dapps)
+}
 
-    let mut result = Vec::new();
-    let mut keys: Vec<u64> = dapps.keys().cloned().collect();
-    keys.sort_unstable();
-    for key in keys {
-        if let Some(dapp) = dapps.get(&key) {
-            let latest = dapp.versions.get(&dapp.latest_version_id);
-            result.push(DappInfo {
-                dapp_id: dapp.dapp_id.to_string(),
-                version_id: dapp.latest_version_id.to_string(),
-                name: latest.and_then(|v| v.name.clone()).unwrap_or_default(),
-                version: latest.and_then(|v| v.version.clone()).unwrap_or_default(),
-                description: latest
-                    .and_then(|v| v.description.clone())
-                    .unwrap_or_default(),
-                status: latest
-                    .and_then(|v| v.status.clone())
-                    .unwrap_or_else(|| "Unknown".to_string()),
-                root_cid: latest.and_then(|v| v.root_cid.clone()).unwrap_or_default(),
-            });
+/// Registry events from `from_block` on: those in the on-disk index plus a
+/// scan of the blocks after it. When the node can't be reached, the index
+/// alone is used so the launcher still lists what it knew.
+fn registry_logs(
+    state: &AppState,
+    address: &str,
+    from_block: u64,
+    schema: RegistrySchema,
+) -> Result<Vec<RpcLog>> {
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("Network not configured"))?;
+    // A fork's history goes away with it; keep it out of the chain's index.
+    let persist = resolved.fork.is_none();
+    let mut index = if persist {
+        crate::registry_index::load(
+            &resolved.cache_dir,
+            resolved.chain_id,
+            address,
+            from_block,
+            schema,
+        )
+    } else {
+        crate::registry_index::RegistryIndex::new(from_block, schema)
+    };
+
+    let latest_block = match rpc_latest_block_number(state) {
+        Ok(block) => block,
+        Err(err) if index.synced_to().is_some() => {
+            tracing::warn!(
+                registry = address,
+                error = %format!("{err:#}"),
+                "registry unreachable; listing from the local index"
+            );
+            return Ok(index.logs().to_vec());
+        }
+        Err(err) => return Err(err),
+    };
+    if let Some(point) = index.synced_to().cloned() {
+        let current = if point.block > latest_block {
+            None
+        } else {
+            rpc_block_hash(state, point.block)?
+        };
+        if current.as_deref() != Some(point.hash.as_str()) {
+            tracing::info!(
+                registry = address,
+                block = point.block,
+                "registry index no longer matches the chain; rebuilding"
+            );
+            index = crate::registry_index::RegistryIndex::new(from_block, schema);
         }
     }
-    Ok(result)
+
+    let scan_from = index.resume_block();
+    let scanned = scan_registry_logs(state, address, schema, scan_from, latest_block)?;
+    tracing::debug!(
+        registry = address,
+        indexed = index.logs().len(),
+        scanned = scanned.len(),
+        from_block = scan_from,
+        to_block = latest_block,
+        "registry logs synced"
+    );
+    let mut logs = index.logs().to_vec();
+    logs.extend(scanned.iter().cloned());
+
+    let settled = latest_block.saturating_sub(crate::registry_index::REORG_DEPTH);
+    if persist && settled >= scan_from {
+        match rpc_block_hash(state, settled) {
+            Ok(Some(hash)) => {
+                index.advance(
+                    &scanned,
+                    crate::registry_index::SyncPoint {
+                        block: settled,
+                        hash,
+                    },
+                );
+                crate::registry_index::save(
+                    &resolved.cache_dir,
+                    resolved.chain_id,
+                    address,
+                    &index,
+                );
+            }
+            Ok(None) => {}
+            Err(err) => tracing::debug!(
+                registry = address,
+                error = %format!("{err:#}"),
+                "registry index not updated"
+            ),
+        }
+    }
+    Ok(logs)
+}
+
+/// Scans `from_block..=to_block` for the events of `schema`.
+fn scan_registry_logs(
+    state: &AppState,
+    address: &str,
+    schema: RegistrySchema,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<RpcLog>> {
+    if from_block > to_block {
+        return Ok(Vec::new());
+    }
+    let topics = registry_topics(schema);
+    // Each event kind is an independent getLogs scan; run them side by side so
+    // launcher load time is bounded by the slowest scan rather than their sum.
+    let results: Vec<Result<Vec<RpcLog>>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = topics
+            .iter()
+            .map(|topic| {
+                let state = state.clone();
+                let topic = *topic;
+                scope.spawn(move || rpc_get_logs(&state, address, from_block, to_block, topic))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow!("registry log fetch thread panicked")))
+            })
+            .collect()
+    });
+
+    let mut all = Vec::new();
+    for logs in results {
+        all.extend(logs?);
+    }
+    Ok(all)
+}
+
+/// Pinned dapps whose latest known bundle is already cached, for listing
+/// while the registry is unreachable.
+fn offline_pinned_dapps(state: &AppState) -> Vec<DappInfo> {
+    let Some(devnet) = state.resolved.as_ref() else {
+        return Vec::new();
+    };
+    let Some(config_path) = devnet.config_path.as_deref() else {
+        return Vec::new();
+    };
+    let settings = crate::settings::load_settings(config_path);
+    settings
+        .pinned_dapps
+        .into_iter()
+        .filter(|pin| {
+            !pin.root_cid.is_empty()
+                && devnet
+                    .cache_dir
+                    .join(&pin.root_cid)
+                    .join("manifest.json")
+                    .exists()
+        })
+        .map(|pin| {
+            let registry_label = match &pin.registry {
+                Some(address) => settings
+                    .community_registries
+                    .iter()
+                    .find(|registry| registry.address == *address)
+                    .map(|registry| registry.label.clone())
+                    .unwrap_or_else(|| address.clone()),
+                None => DEFAULT_REGISTRY_LABEL.to_string(),
+            };
+            DappInfo {
+                dapp_id: pin.dapp_id,
+                version_id: String::new(),
+                name: pin.name,
+                version: String::new(),
+                description: String::new(),
+                status: "Published".to_string(),
+                root_cid: pin.root_cid,
+                publisher: String::new(),
+                manifest_hash: None,
+                publisher_signature: None,
+                reported: false,
+                pinned: true,
+                offline: true,
+                registry: pin.registry,
+                registry_label,
+                warnings: Vec::new(),
+                icon: None,
+            }
+        })
+        .collect()
+}
+
+/// Pins or unpins a dapp for background prefetch.
+fn set_dapp_pinned(state: &AppState, pin: crate::settings::PinnedDapp, pinned: bool) -> Result<()> {
+    let config_path = state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.config_path.as_deref())
+        .ok_or_else(|| anyhow!("pinning dapps requires a config file"))?;
+    let mut settings = crate::settings::load_settings(config_path);
+    settings
+        .pinned_dapps
+        .retain(|existing| existing.dapp_id != pin.dapp_id || existing.registry != pin.registry);
+    if pinned {
+        tracing::info!(dapp_id = %pin.dapp_id, "dapp pinned");
+        settings.pinned_dapps.push(pin);
+    } else {
+        tracing::info!(dapp_id = %pin.dapp_id, "dapp unpinned");
+    }
+    crate::settings::save_settings(config_path, &settings)?;
+    if pinned {
+        crate::prefetch::request_prefetch();
+    }
+    Ok(())
 }
 
 pub fn resolve_published_root_cid_by_dapp_id(
@@ -271,7 +953,7 @@ pub fn resolve_published_root_cid_by_dapp_id(
     let dapps = list_dapps(state)?;
     let studio = dapps
         .into_iter()
-        .find(|dapp| dapp.dapp_id == studio_dapp_id.to_string())
+        .find(|dapp| dapp.registry.is_none() && dapp.dapp_id == studio_dapp_id.to_string())
         .ok_or_else(|| anyhow!("studio dappId {} not found in DappRegistry", studio_dapp_id))?;
     if studio.status != "Published" {
         bail!(
@@ -289,22 +971,22 @@ pub fn resolve_published_root_cid_by_dapp_id(
     Ok(studio.root_cid)
 }
 
-fn rpc_get_logs(state: &AppState, address: &str, topic0: B256) -> Result<Vec<LogEntry>> {
-    let devnet = state
+/// Logs of `address` with `topic0` over `from_block..=latest_block`, fetched
+/// in ranges of at most `logsBlockRange` blocks; see [`get_logs_chunked`].
+fn rpc_get_logs(
+    state: &AppState,
+    address: &str,
+    from_block: u64,
+    latest_block: u64,
+    topic0: B256,
+) -> Result<Vec<RpcLog>> {
+    let chunk = state
         .resolved
         .as_ref()
-        .ok_or_else(|| anyhow!("Network not configured"))?;
+        .ok_or_else(|| anyhow!("Network not configured"))?
+        .logs_block_range;
     let topics = vec![format!("0x{}", hex::encode(topic0))];
-    let mut out = Vec::new();
-    let from_block = devnet.deploy_block.unwrap_or(0);
-    let latest_block = rpc_latest_block_number(state)?;
-    if from_block > latest_block {
-        return Ok(out);
-    }
-
-    let mut to_block = latest_block;
-    loop {
-        let start_block = from_block.max(to_block.saturating_sub(RPC_LOGS_BLOCK_CHUNK - 1));
+    get_logs_chunked(from_block, latest_block, chunk, |start_block, to_block| {
         let payload = serde_json::json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -316,8 +998,38 @@ fn rpc_get_logs(state: &AppState, address: &str, topic0: B256) -> Result<Vec<Log
                 "toBlock": format!("0x{:x}", to_block)
             }]
         });
-        let v = rpc_send_with_manager_fallback(state, &payload, "rpc getLogs failed")?;
+        rpc_send_with_manager_fallback(state, &payload, "rpc getLogs failed")
+    })
+}
+
+/// Runs `get_logs(from, to)` over `from_block..=to_block` in ranges of at
+/// most `chunk` blocks, newest first. A range the node refuses as too wide or
+/// as returning too many results is halved and retried, and the smaller size
+/// is kept for the rest of the scan. The merged logs are in chain order.
+fn get_logs_chunked(
+    from_block: u64,
+    to_block: u64,
+    chunk: u64,
+    mut get_logs: impl FnMut(u64, u64) -> Result<serde_json::Value>,
+) -> Result<Vec<RpcLog>> {
+    let mut chunk = chunk.max(1);
+    let mut out = Vec::new();
+    let mut to_block = to_block;
+    loop {
+        let start_block = from_block.max(to_block.saturating_sub(chunk - 1));
+        let v = get_logs(start_block, to_block)?;
         if let Some(err) = v.get("error") {
+            if start_block < to_block && is_log_range_error(err) {
+                chunk = (to_block - start_block + 1) / 2;
+                tracing::debug!(
+                    from_block = start_block,
+                    to_block,
+                    chunk,
+                    error = %err,
+                    "getLogs range refused; splitting"
+                );
+                continue;
+            }
             return Err(anyhow!("rpc getLogs error: {}", err));
         }
         let logs_val = v
@@ -325,20 +1037,54 @@ fn rpc_get_logs(state: &AppState, address: &str, topic0: B256) -> Result<Vec<Log
             .cloned()
             .unwrap_or(serde_json::Value::Array(Vec::new()));
         let logs: Vec<RpcLog> = serde_json::from_value(logs_val)?;
-        for log in logs {
-            let log_entry = rpc_log_to_entry(log)?;
-            out.push(log_entry);
-        }
+        out.extend(logs);
 
         if start_block == from_block {
             break;
         }
-        to_block = start_block.saturating_sub(1);
+        to_block = start_block - 1;
     }
+    out.sort_by_key(|log| (log.block(), parse_hex_u64_opt(log.log_index.as_deref())));
     Ok(out)
 }
 
+/// Whether a getLogs error is a provider's range or result-count limit, as
+/// opposed to a failure a smaller range would not fix. Providers word these
+/// differently, e.g. "query returned more than 10000 results" (Infura) or
+/// "Log response size exceeded" (Alchemy).
+fn is_log_range_error(err: &serde_json::Value) -> bool {
+    // EIP-1474 "limit exceeded".
+    if err.get("code").and_then(serde_json::Value::as_i64) == Some(-32005) {
+        return true;
+    }
+    let message = err
+        .get("message")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    [
+        "more than",
+        "too many",
+        "too large",
+        "too wide",
+        "block range",
+        "range limit",
+        "response size",
+        "limit exceeded",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle))
+}
+
+/// Reuses the block watcher's head when it is current, and shares what it
+/// fetches otherwise.
 fn rpc_latest_block_number(state: &AppState) -> Result<u64> {
+    if let Some(block) = state
+        .blocks
+        .recent(crate::block_watcher::BLOCK_POLL_INTERVAL)
+    {
+        return Ok(block);
+    }
     let payload = serde_json::json!({
         "jsonrpc": "2.0",
         "id": 1,
@@ -353,7 +1099,28 @@ fn rpc_latest_block_number(state: &AppState) -> Result<u64> {
         .get("result")
         .and_then(|value| value.as_str())
         .ok_or_else(|| anyhow!("rpc blockNumber returned non-string result"))?;
-    parse_hex_u64(block).ok_or_else(|| anyhow!("rpc blockNumber returned invalid quantity"))
+    let block =
+        parse_hex_u64(block).ok_or_else(|| anyhow!("rpc blockNumber returned invalid quantity"))?;
+    state.blocks.record(block);
+    Ok(block)
+}
+
+/// Hash of block `number`, or `None` if the node doesn't have it.
+fn rpc_block_hash(state: &AppState, number: u64) -> Result<Option<String>> {
+    let payload = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getBlockByNumber",
+        "params": [format!("0x{number:x}"), false]
+    });
+    let v = rpc_send_with_manager_fallback(state, &payload, "rpc getBlockByNumber failed")?;
+    if let Some(err) = v.get("error") {
+        return Err(anyhow!("rpc getBlockByNumber error: {}", err));
+    }
+    Ok(v.get("result")
+        .and_then(|block| block.get("hash"))
+        .and_then(|hash| hash.as_str())
+        .map(str::to_string))
 }
 
 fn rpc_send_with_manager_fallback(
@@ -420,6 +1187,10 @@ fn event_kind(log: &Log) -> Result<String> {
         Ok("DappUnpaused".to_string())
     } else if topic0 == DappDeprecated::SIGNATURE_HASH {
         Ok("DappDeprecated".to_string())
+    } else if topic0 == DappPublishedV2::SIGNATURE_HASH {
+        Ok("DappPublishedV2".to_string())
+    } else if topic0 == DappUpgradedV2::SIGNATURE_HASH {
+        Ok("DappUpgradedV2".to_string())
     } else {
         Err(anyhow!("unknown event signature"))
     }
@@ -436,20 +1207,351 @@ pub fn handle_launcher_ipc(
             let webview_id = webview_id.to_string();
             let ipc_id = req.id;
             std::thread::spawn(move || {
-                let result = (|| -> Result<serde_json::Value> {
-                    tracing::info!("launcher: fetching dapp list from logs");
-                    let mut dapps = list_dapps(&state_clone)?;
-                    if let Some(studio_dapp_id) = state_clone
-                        .resolved
-                        .as_ref()
-                        .and_then(|resolved| resolved.studio_dapp_id)
-                    {
-                        let studio_id = studio_dapp_id.to_string();
-                        dapps.retain(|dapp| dapp.dapp_id != studio_id);
-                    }
-                    Ok(serde_json::to_value(dapps)?)
-                })()
-                .map_err(|e| e.to_string());
+                let result = (|| -> Result<serde_json::Value> {
+                    tracing::info!("launcher: fetching dapp list from logs");
+                    let mut dapps = match list_dapps(&state_clone) {
+                        Ok(dapps) => dapps,
+                        Err(err) => {
+                            let offline = offline_pinned_dapps(&state_clone);
+                            if offline.is_empty() {
+                                return Err(err);
+                            }
+                            tracing::warn!(
+                                error = %err,
+                                count = offline.len(),
+                                "launcher: registry unreachable, listing cached pinned dapps"
+                            );
+                            offline
+                        }
+                    };
+                    remember_dapp_identities(&state_clone, &dapps);
+                    crate::registry_cache::annotate(&state_clone, &mut dapps, &webview_id);
+                    if let Some(studio_dapp_id) = state_clone
+                        .resolved
+                        .as_ref()
+                        .and_then(|resolved| resolved.studio_dapp_id)
+                    {
+                        let studio_id = studio_dapp_id.to_string();
+                        dapps.retain(|dapp| dapp.registry.is_some() || dapp.dapp_id != studio_id);
+                    }
+                    Ok(serde_json::to_value(dapps)?)
+                })()
+                .map_err(|e| e.to_string());
+                let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
+                    webview_id,
+                    ipc_id,
+                    result,
+                });
+            });
+            Ok(None)
+        }
+        "vibefi_listDappVersions" => {
+            let dapp_id = req
+                .params
+                .get(0)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("missing dappId"))?
+                .to_string();
+            let registry = req
+                .params
+                .get(1)
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            let state_clone = state.clone();
+            let webview_id = webview_id.to_string();
+            let ipc_id = req.id;
+            std::thread::spawn(move || {
+                let result = list_dapp_versions(&state_clone, registry.as_deref(), &dapp_id)
+                    .and_then(|versions| Ok(serde_json::to_value(versions)?))
+                    .map_err(|e| e.to_string());
+                let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
+                    webview_id,
+                    ipc_id,
+                    result,
+                });
+            });
+            Ok(None)
+        }
+        "vibefi_launchDapp" => {
+            // `{ dappId, versionId, registry? }` picks a version other than the
+            // latest; its rootCid then comes from the registry.
+            let pinned_version = req
+                .params
+                .get(2)
+                .filter(|options| options.get("versionId").is_some())
+                .map(parse_version_pick)
+                .transpose()?;
+            let root_cid = req
+                .params
+                .get(0)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            if root_cid.is_empty() && pinned_version.is_none() {
+                bail!("missing rootCid");
+            }
+            let name = req
+                .params
+                .get(1)
+                .and_then(|v| v.as_str())
+                .unwrap_or(&root_cid)
+                .to_string();
+            let state_clone = state.clone();
+            let webview_id = webview_id.to_string();
+            let ipc_id = req.id;
+            std::thread::spawn(move || {
+                let launched = match pinned_version {
+                    Some((dapp_id, version_id, registry)) => launch_dapp_version(
+                        &state_clone,
+                        &webview_id,
+                        registry.as_deref(),
+                        &dapp_id,
+                        &version_id,
+                    ),
+                    None => launch_dapp(&state_clone, &webview_id, &root_cid, &name),
+                };
+                let result = launched
+                    .map(|_| serde_json::Value::Bool(true))
+                    .map_err(|e| e.to_string());
+                let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
+                    webview_id,
+                    ipc_id,
+                    result,
+                });
+            });
+            Ok(None)
+        }
+        "vibefi_launchCid" => {
+            let root_cid = parse_root_cid(
+                req.params
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("missing rootCid"))?,
+            )?;
+            let state_clone = state.clone();
+            let webview_id = webview_id.to_string();
+            let ipc_id = req.id;
+            std::thread::spawn(move || {
+                let result = launch_cid(&state_clone, &webview_id, &root_cid)
+                    .map(|identity| {
+                        serde_json::json!({
+                            "name": identity.name,
+                            "unverified": identity.unverified,
+                        })
+                    })
+                    .map_err(|e| e.to_string());
+                let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
+                    webview_id,
+                    ipc_id,
+                    result,
+                });
+            });
+            Ok(None)
+        }
+        "vibefi_reportDapp" => {
+            let params = req
+                .params
+                .get(0)
+                .ok_or_else(|| anyhow!("missing report params"))?;
+            let field = |name: &str| {
+                params
+                    .get(name)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let dapp_id = field("dappId");
+            if dapp_id.is_empty() {
+                bail!("missing dappId");
+            }
+            let (version_id, root_cid, reason) =
+                (field("versionId"), field("rootCid"), field("reason"));
+            let registry = Some(field("registry")).filter(|registry| !registry.is_empty());
+            let state_clone = state.clone();
+            let webview_id = webview_id.to_string();
+            let ipc_id = req.id;
+            // On-chain submission waits on the wallet; keep it off the UI thread.
+            std::thread::spawn(move || {
+                let result = crate::reports::report_dapp(
+                    &state_clone,
+                    registry.as_deref(),
+                    &dapp_id,
+                    &version_id,
+                    &root_cid,
+                    &reason,
+                )
+                .and_then(|report| Ok(serde_json::to_value(report)?))
+                .map_err(|e| format!("{e:#}"));
+                let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
+                    webview_id,
+                    ipc_id,
+                    result,
+                });
+            });
+            Ok(None)
+        }
+        "vibefi_pinDapp" => {
+            let params = req
+                .params
+                .get(0)
+                .ok_or_else(|| anyhow!("missing pin params"))?;
+            let pinned = params
+                .get("pinned")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true);
+            let pin: crate::settings::PinnedDapp =
+                serde_json::from_value(params.clone()).context("invalid pin params")?;
+            if pin.dapp_id.is_empty() {
+                bail!("missing dappId");
+            }
+            set_dapp_pinned(state, pin, pinned)?;
+            Ok(Some(serde_json::Value::Bool(pinned)))
+        }
+        "vibefi_exportBundle" => {
+            let params = req
+                .params
+                .get(0)
+                .ok_or_else(|| anyhow!("missing export params"))?;
+            let field = |name: &str| {
+                params
+                    .get(name)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            };
+            let root_cid = field("rootCid").ok_or_else(|| anyhow!("missing rootCid"))?;
+            let name = field("name").filter(|name| !name.trim().is_empty());
+            let cache_dir = state
+                .resolved
+                .as_ref()
+                .ok_or_else(|| anyhow!("Network not configured"))?
+                .cache_dir
+                .clone();
+            let state_clone = state.clone();
+            let webview_id = webview_id.to_string();
+            let ipc_id = req.id;
+            // Blocks on the save dialog and on copying the bundle.
+            std::thread::spawn(move || {
+                let result = (|| -> Result<serde_json::Value> {
+                    let label = name.as_deref().unwrap_or("bundle");
+                    let short_cid: String = root_cid.chars().take(12).collect();
+                    let Some(dest) = crate::file_dialog::pick_save(
+                        &format!("Export {label}"),
+                        &format!("{label}-{short_cid}.zip"),
+                        &["zip".to_string()],
+                    )?
+                    else {
+                        return Ok(serde_json::Value::Null);
+                    };
+                    let exported = crate::bundle_export::export_bundle(
+                        &cache_dir,
+                        &root_cid,
+                        name.as_deref(),
+                        crate::bundle_export::ExportParts::All,
+                        &dest,
+                    )?;
+                    Ok(serde_json::Value::String(
+                        exported.to_string_lossy().into_owned(),
+                    ))
+                })()
+                .map_err(|e| format!("{e:#}"));
+                let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
+                    webview_id,
+                    ipc_id,
+                    result,
+                });
+            });
+            Ok(None)
+        }
+        "vibefi_importBundle" => {
+            let source = req
+                .params
+                .get(0)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|source| !source.is_empty())
+                .ok_or_else(|| anyhow!("missing bundle source"))?
+                .to_string();
+            tracing::info!(
+                target: "vibefi::audit",
+                webview_id,
+                source = %source,
+                "bundle import requested over ipc"
+            );
+            let state_clone = state.clone();
+            let webview_id = webview_id.to_string();
+            let ipc_id = req.id;
+            // Downloads, unpacks and builds; keep it off the UI thread.
+            std::thread::spawn(move || {
+                let result = crate::local_bundle::import(&state_clone, &source)
+                    .map(|()| serde_json::Value::Bool(true))
+                    .map_err(|e| format!("{e:#}"));
+                let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
+                    webview_id,
+                    ipc_id,
+                    result,
+                });
+            });
+            Ok(None)
+        }
+        "vibefi_takeDroppedProject" => {
+            let project = state
+                .dropped_project
+                .lock()
+                .expect("poisoned dropped_project lock")
+                .take();
+            Ok(Some(serde_json::to_value(project)?))
+        }
+        "vibefi_packContext" => {
+            let request: crate::studio_context::PackRequest = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing context request"))?,
+            )
+            .context("invalid context request")?;
+            let packed = crate::studio_context::pack(&request)?;
+            Ok(Some(serde_json::to_value(packed)?))
+        }
+        "vibefi_publishPreflight" => {
+            let request: crate::publish_preflight::PreflightRequest = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing preflight request"))?,
+            )
+            .context("invalid preflight request")?;
+            let state_clone = state.clone();
+            let webview_id = webview_id.to_string();
+            let ipc_id = req.id;
+            // Builds and hashes the bundle; keep it off the UI thread.
+            std::thread::spawn(move || {
+                let result = crate::publish_preflight::run(&state_clone, &request)
+                    .and_then(|report| Ok(serde_json::to_value(report)?))
+                    .map_err(|e| format!("{e:#}"));
+                let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
+                    webview_id,
+                    ipc_id,
+                    result,
+                });
+            });
+            Ok(None)
+        }
+        "vibefi_publishDapp" => {
+            let request: crate::publish::PublishRequest = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing publish request"))?,
+            )
+            .context("invalid publish request")?;
+            let state_clone = state.clone();
+            let webview_id = webview_id.to_string();
+            let ipc_id = req.id;
+            // Builds, uploads and waits for two transactions; keep it off the UI thread.
+            std::thread::spawn(move || {
+                let result = crate::publish::publish_dapp(&state_clone, &request)
+                    .and_then(|published| Ok(serde_json::to_value(published)?))
+                    .map_err(|e| format!("{e:#}"));
                 let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
                     webview_id,
                     ipc_id,
@@ -458,26 +1560,21 @@ pub fn handle_launcher_ipc(
             });
             Ok(None)
         }
-        "vibefi_launchDapp" => {
-            let root_cid = req
-                .params
-                .get(0)
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow!("missing rootCid"))?
-                .to_string();
-            let name = req
-                .params
-                .get(1)
-                .and_then(|v| v.as_str())
-                .unwrap_or(&root_cid)
-                .to_string();
+        "vibefi_contractLogs" => {
+            let request: crate::contract_logs::LogsRequest = serde_json::from_value(
+                req.params
+                    .get(0)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing logs request"))?,
+            )
+            .context("invalid logs request")?;
             let state_clone = state.clone();
             let webview_id = webview_id.to_string();
             let ipc_id = req.id;
             std::thread::spawn(move || {
-                let result = launch_dapp(&state_clone, &webview_id, &root_cid, &name)
-                    .map(|_| serde_json::Value::Bool(true))
-                    .map_err(|e| e.to_string());
+                let result = crate::contract_logs::tail(&state_clone, &request)
+                    .and_then(|page| Ok(serde_json::to_value(page)?))
+                    .map_err(|e| format!("{e:#}"));
                 let _ = state_clone.proxy.send_event(UserEvent::RpcResult {
                     webview_id,
                     ipc_id,
@@ -494,57 +1591,329 @@ pub fn handle_launcher_ipc(
     }
 }
 
-fn launch_dapp(state: &AppState, webview_id: &str, root_cid: &str, name: &str) -> Result<()> {
-    let dist_dir = prepare_dapp_dist(state, root_cid, Some(webview_id))?;
+/// Lists the registries again so launch identities are current without the
+/// launcher open; used by the background scheduler. Returns the dapp count.
+pub(crate) fn refresh_listing(state: &AppState) -> Result<usize> {
+    let dapps = list_dapps(state)?;
+    remember_dapp_identities(state, &dapps);
+    Ok(dapps.len())
+}
+
+fn remember_dapp_identities(state: &AppState, dapps: &[DappInfo]) {
+    let mut known = state
+        .known_dapps
+        .lock()
+        .expect("poisoned known_dapps lock while caching registry identities");
+    for dapp in dapps {
+        if dapp.root_cid.is_empty() {
+            continue;
+        }
+        // A bundle also listed by the configured registry keeps that provenance.
+        let listed_by_default = known
+            .get(&dapp.root_cid)
+            .is_some_and(|identity| identity.dapp_id.is_some() && identity.registry.is_none());
+        if dapp.registry.is_some() && listed_by_default {
+            continue;
+        }
+        known.insert(
+            dapp.root_cid.clone(),
+            DappIdentity {
+                name: dapp.name.clone(),
+                dapp_id: Some(dapp.dapp_id.clone()),
+                root_cid: Some(dapp.root_cid.clone()),
+                publisher: Some(dapp.publisher.clone()).filter(|p| !p.is_empty()),
+                registry: dapp.registry.as_ref().map(|_| dapp.registry_label.clone()),
+                registry_address: dapp.registry.clone(),
+                unverified: false,
+                local_project: false,
+            },
+        );
+    }
+}
+
+/// Registry identity for `root_cid` when it was listed, otherwise just the CID.
+fn dapp_identity_for_launch(state: &AppState, root_cid: &str, name: &str) -> DappIdentity {
+    let known = state
+        .known_dapps
+        .lock()
+        .expect("poisoned known_dapps lock while resolving launch identity")
+        .get(root_cid)
+        .cloned();
+    let mut identity = known.unwrap_or_else(|| DappIdentity {
+        root_cid: Some(root_cid.to_string()),
+        ..DappIdentity::default()
+    });
+    identity.name = name.to_string();
+    identity
+}
+
+pub(crate) fn launch_dapp(
+    state: &AppState,
+    webview_id: &str,
+    root_cid: &str,
+    name: &str,
+) -> Result<()> {
+    let mut launch = LaunchRecorder::start(root_cid, name);
+    let result = open_dapp(state, webview_id, root_cid, name, &mut launch);
+    launch.finish(state, &result);
+    result
+}
+
+fn open_dapp(
+    state: &AppState,
+    webview_id: &str,
+    root_cid: &str,
+    name: &str,
+    launch: &mut LaunchRecorder,
+) -> Result<()> {
+    let dist_dir = prepare_dapp_dist(state, root_cid, Some(webview_id), launch)?;
+    let identity = dapp_identity_for_launch(state, root_cid, name);
+    launch.stage("open");
+    ensure_launch_chain(state, &dist_dir, &identity)?;
+    crate::storage::enforce_quota(state, &identity)?;
+    let _ = state
+        .proxy
+        .send_event(UserEvent::TabAction(TabAction::OpenApp {
+            identity,
+            dist_dir,
+        }));
+    Ok(())
+}
+
+/// Launches a root CID pasted into the launcher. A CID the registries list
+/// opens like any listed launch; anything else opens as unverified, with no
+/// manifest capabilities and ephemeral storage.
+fn launch_cid(state: &AppState, webview_id: &str, root_cid: &str) -> Result<DappIdentity> {
+    let mut launch = LaunchRecorder::start(root_cid, root_cid);
+    let result = open_cid(state, webview_id, root_cid, &mut launch);
+    launch.finish(state, &result);
+    result
+}
+
+fn open_cid(
+    state: &AppState,
+    webview_id: &str,
+    root_cid: &str,
+    launch: &mut LaunchRecorder,
+) -> Result<DappIdentity> {
+    let known = state
+        .known_dapps
+        .lock()
+        .expect("poisoned known_dapps lock while resolving pasted cid")
+        .get(root_cid)
+        .cloned();
+    if let Some(identity) = &known {
+        launch.set_dapp(&identity.name);
+    }
+    let dist_dir = prepare_dapp_dist(state, root_cid, Some(webview_id), launch)?;
+    let identity = known.unwrap_or_else(|| {
+        let short_cid: String = root_cid.chars().take(12).collect();
+        DappIdentity {
+            name: format!("Unverified {short_cid}…"),
+            root_cid: Some(root_cid.to_string()),
+            unverified: true,
+            ..DappIdentity::default()
+        }
+    });
+    tracing::info!(dapp = %identity, "launching pasted root cid");
+    launch.set_dapp(&identity.name);
+    launch.stage("open");
+    ensure_launch_chain(state, &dist_dir, &identity)?;
+    crate::storage::enforce_quota(state, &identity)?;
     let _ = state
         .proxy
         .send_event(UserEvent::TabAction(TabAction::OpenApp {
-            name: name.to_string(),
+            identity: identity.clone(),
             dist_dir,
         }));
+    Ok(identity)
+}
+
+/// Moves the client to a chain the dapp's manifest declares before the dapp
+/// opens. With no tab connected to a wallet the switch is made directly;
+/// otherwise the user is asked, and declining cancels the launch. Blocks on a
+/// prompt, so call it off the event loop.
+fn ensure_launch_chain(state: &AppState, dist_dir: &Path, identity: &DappIdentity) -> Result<()> {
+    let Some(bundle_dir) = dist_dir.parent().and_then(Path::parent) else {
+        return Ok(());
+    };
+    let chains = manifest::load(bundle_dir)?.chains;
+    let current = state.wallet.lock().expect("wallet").chain.chain_id;
+    if chains.allows(current) {
+        return Ok(());
+    }
+    let Some(target) = chains.preferred() else {
+        return Ok(());
+    };
+    let wallet_in_use = state
+        .tab_wallets
+        .lock()
+        .expect("tab_wallets")
+        .values()
+        .any(|wallet| !wallet.addresses().is_empty());
+    if wallet_in_use {
+        let request = crate::prompt::PromptRequest::new(
+            "switchChain",
+            "Switch network",
+            format!(
+                "{} runs on chain {target}, but the client is on chain {current}. Switch to chain {target}?",
+                identity.name
+            ),
+        )
+        .with_details(serde_json::json!({
+            "dapp": identity.to_string(),
+            "from": current,
+            "to": target,
+        }));
+        if !crate::prompt::ask(state, request) {
+            bail!(
+                "{} does not support chain {current}; switch to chain {target} to launch it",
+                identity.name
+            );
+        }
+    }
+    set_chain(state, target);
+    tracing::info!(dapp = %identity, from = current, to = target, "switched chain for launch");
     Ok(())
 }
 
+/// Moves the client to `chain_id` and tells every open dapp.
+pub(crate) fn set_chain(state: &AppState, chain_id: u64) {
+    state.wallet.lock().expect("wallet").chain.chain_id = chain_id;
+    let tabs: Vec<String> = state
+        .app_capabilities
+        .lock()
+        .expect("app_capabilities")
+        .keys()
+        .cloned()
+        .collect();
+    for webview_id in tabs {
+        let _ = state.proxy.send_event(UserEvent::ProviderEvent {
+            webview_id,
+            event: "chainChanged".to_string(),
+            value: serde_json::Value::String(format!("0x{chain_id:x}")),
+        });
+    }
+}
+
+/// Normalizes a pasted root CID, accepting `ipfs://` and `/ipfs/` prefixes.
+/// The CID names a cache directory, so only CIDv0 (base58) and base32 CIDv1
+/// strings are accepted.
+fn parse_root_cid(input: &str) -> Result<String> {
+    let trimmed = input.trim();
+    let cid = trimmed
+        .strip_prefix("ipfs://")
+        .or_else(|| trimmed.strip_prefix("/ipfs/"))
+        .unwrap_or(trimmed)
+        .trim_end_matches('/');
+    let v0 =
+        cid.len() == 46 && cid.starts_with("Qm") && cid.bytes().all(|b| b.is_ascii_alphanumeric());
+    let v1 = (50..=120).contains(&cid.len())
+        && cid.starts_with('b')
+        && cid
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || (b'2'..=b'7').contains(&b));
+    if !v0 && !v1 {
+        bail!("not a bundle root CID: {trimmed:?}");
+    }
+    Ok(cid.to_string())
+}
+
+/// Fetches, verifies and builds `root_cid` for launch, marking it in use.
 pub fn prepare_dapp_dist(
     state: &AppState,
     root_cid: &str,
     progress_webview_id: Option<&str>,
+    launch: &mut LaunchRecorder,
+) -> Result<PathBuf> {
+    let dist_dir = prepare_bundle_dist(state, root_cid, progress_webview_id, launch)?;
+    state
+        .bundles_in_use
+        .lock()
+        .expect("poisoned bundles_in_use lock while preparing dapp")
+        .insert(root_cid.to_string());
+    if let Some(bundle_dir) = dist_dir.parent().and_then(Path::parent) {
+        if let Err(err) = fs::write(bundle_dir.join(crate::cache_manager::LAST_USED_MARKER), b"") {
+            tracing::debug!(error = %err, "failed to touch bundle last-used marker");
+        }
+    }
+    if let Err(err) = crate::cache_manager::enforce_budget(state) {
+        tracing::warn!(error = %format!("{err:#}"), "bundle cache eviction failed");
+    }
+    Ok(dist_dir)
+}
+
+/// Downloads and verifies `root_cid` into the bundle cache without building
+/// it, for `--bundle ipfs://<cid>`. Returns the cached bundle dir.
+pub(crate) fn fetch_bundle(devnet: &ResolvedConfig, root_cid: &str) -> Result<PathBuf> {
+    let _guard = PrepareGuard::acquire(root_cid);
+    let bundle_dir = devnet.cache_dir.join(root_cid);
+    let ipfs = resolve_effective_ipfs_config(devnet);
+    ensure_bundle_cached(devnet, &ipfs, root_cid, &bundle_dir, &mut |_| {})?;
+    Ok(bundle_dir)
+}
+
+/// Readies `root_cid` in the cache without launching it, so a later launch
+/// skips the download and build.
+pub fn prefetch_dapp_bundle(state: &AppState, root_cid: &str) -> Result<()> {
+    // A prefetch is not a launch, so its report is not kept.
+    let mut launch = LaunchRecorder::start(root_cid, root_cid);
+    prepare_bundle_dist(state, root_cid, None, &mut launch).map(|_| ())
+}
+
+fn prepare_bundle_dist(
+    state: &AppState,
+    root_cid: &str,
+    progress_webview_id: Option<&str>,
+    launch: &mut LaunchRecorder,
 ) -> Result<PathBuf> {
     let devnet = state
         .resolved
         .as_ref()
         .ok_or_else(|| anyhow!("Network not configured"))?;
+    let _guard = PrepareGuard::acquire(root_cid);
     tracing::info!(root_cid, "prepare dapp: fetch bundle");
     let bundle_dir = devnet.cache_dir.join(root_cid);
-    let ipfs = resolve_effective_ipfs_config(state, devnet);
+    let ipfs = resolve_effective_ipfs_config(devnet);
     tracing::info!(backend = ipfs.fetch_backend.as_str(), "ipfs backend");
+    launch.set_backend(ipfs.fetch_backend.as_str());
+    launch.stage("download");
 
     emit_launch_progress_if(
         state,
+        root_cid,
         progress_webview_id,
         LaunchProgress::simple("prepare", "Preparing bundle retrieval...", 2),
     );
 
     {
         let mut emit = |progress: LaunchProgress| {
-            emit_launch_progress_if(state, progress_webview_id, progress)
+            emit_launch_progress_if(state, root_cid, progress_webview_id, progress)
         };
-        ensure_bundle_cached(devnet, &ipfs, root_cid, &bundle_dir, &mut emit)?;
+        if ensure_bundle_cached(devnet, &ipfs, root_cid, &bundle_dir, &mut emit)? {
+            launch.add_downloaded(crate::cache_manager::dir_size(&bundle_dir));
+        }
     }
 
     tracing::info!("prepare dapp: verify bundle manifest");
+    launch.stage("verify");
     emit_launch_progress_if(
         state,
+        root_cid,
         progress_webview_id,
         LaunchProgress::simple("verify", "Verifying downloaded bundle...", 88),
     );
     verify_manifest(&bundle_dir)?;
+    crate::bundle::ensure_client_supported(&bundle_dir)?;
+    crate::secret_scan::warn_on_secrets(&bundle_dir)?;
 
     let dist_dir = bundle_dir.join(".vibefi").join("dist");
-    if dist_dir.join("index.html").exists() {
+    launch.stage("build");
+    if crate::bundle::has_verified_build(&dist_dir) {
         tracing::info!("prepare dapp: using cached build");
         emit_launch_progress_if(
             state,
+            root_cid,
             progress_webview_id,
             LaunchProgress::simple("build", "Using cached build artifacts.", 96),
         );
@@ -552,13 +1921,17 @@ pub fn prepare_dapp_dist(
         tracing::info!("prepare dapp: build bundle");
         emit_launch_progress_if(
             state,
+            root_cid,
             progress_webview_id,
             LaunchProgress::simple("build", "Building bundle...", 94),
         );
-        build_bundle(&bundle_dir, &dist_dir)?;
+        let started = std::time::Instant::now();
+        build_bundle(&bundle_dir, &dist_dir, false)?;
+        launch.set_build_ms(started.elapsed().as_millis() as u64);
     }
     emit_launch_progress_if(
         state,
+        root_cid,
         progress_webview_id,
         LaunchProgress::simple("done", "Launch complete.", 100),
     );
@@ -574,7 +1947,17 @@ fn emit_launch_progress(state: &AppState, webview_id: &str, progress: LaunchProg
     });
 }
 
-fn emit_launch_progress_if(state: &AppState, webview_id: Option<&str>, progress: LaunchProgress) {
+/// Reports progress to the launching webview, if any, and to every dapp
+/// subscribed to `launchProgress`.
+fn emit_launch_progress_if(
+    state: &AppState,
+    root_cid: &str,
+    webview_id: Option<&str>,
+    progress: LaunchProgress,
+) {
+    let mut data = serde_json::to_value(&progress).unwrap_or(serde_json::Value::Null);
+    data["rootCid"] = serde_json::Value::String(root_cid.to_string());
+    crate::subscriptions::publish(state, Topic::LaunchProgress, None, data);
     if let Some(webview_id) = webview_id {
         emit_launch_progress(state, webview_id, progress);
     }
@@ -586,7 +1969,8 @@ fn ensure_bundle_cached(
     root_cid: &str,
     bundle_dir: &Path,
     on_progress: &mut dyn FnMut(LaunchProgress),
-) -> Result<()> {
+) -> Result<bool> {
+    let _span = tracing::info_span!("ipfs_bundle_fetch", root_cid).entered();
     if bundle_dir.join("manifest.json").exists() {
         match verify_manifest(bundle_dir) {
             Ok(()) => {
@@ -595,7 +1979,7 @@ fn ensure_bundle_cached(
                     "Using cached IPFS bundle files.",
                     82,
                 ));
-                return Ok(());
+                return Ok(false);
             }
             Err(err) => {
                 tracing::warn!(
@@ -622,15 +2006,18 @@ fn ensure_bundle_cached(
             ensure_bundle_cached_local_node(devnet, ipfs, root_cid, bundle_dir, on_progress)
         }
         IpfsFetchBackend::Helia => {
-            ensure_bundle_cached_helia(ipfs, root_cid, bundle_dir, on_progress)
+            ensure_bundle_cached_helia(devnet, ipfs, root_cid, bundle_dir, on_progress)
         }
-    };
+    }
+    // Per-file digests only tie the files to the manifest; the root CID ties
+    // the manifest to the registry.
+    .and_then(|()| verify_root_cid(bundle_dir, root_cid));
     if let Err(err) = result {
         // Prevent interrupted downloads from becoming sticky cache failures.
         let _ = fs::remove_dir_all(bundle_dir);
         return Err(err);
     }
-    Ok(())
+    Ok(true)
 }
 
 fn ensure_bundle_cached_local_node(
@@ -661,32 +2048,24 @@ fn ensure_bundle_cached_local_node(
 }
 
 fn ensure_bundle_cached_helia(
+    devnet: &ResolvedConfig,
     ipfs: &EffectiveIpfsConfig,
     root_cid: &str,
     bundle_dir: &Path,
     on_progress: &mut dyn FnMut(LaunchProgress),
 ) -> Result<()> {
-    tracing::info!("launcher: download bundle via Helia verified fetch");
+    tracing::info!("launcher: download bundle via verified gateway fetch");
     on_progress(LaunchProgress::simple(
         "download",
         "Fetching manifest from IPFS...",
         6,
     ));
     fs::create_dir_all(bundle_dir).context("create cache dir")?;
-    let mut helper = IpfsHelperBridge::spawn(IpfsHelperConfig {
-        gateways: ipfs.helia_gateways.clone(),
-        routers: ipfs.helia_routers.clone(),
-    })?;
-    let manifest_url = format!("ipfs://{root_cid}/manifest.json");
-    let manifest_resp = helper.fetch(&manifest_url, Some(ipfs.helia_timeout_ms))?;
-    if !(200..300).contains(&manifest_resp.status) {
-        return Err(anyhow!(
-            "fetch manifest failed with status {}",
-            manifest_resp.status
-        ));
-    }
-    let raw_bytes = manifest_resp.body;
-    let manifest: BundleManifest = serde_json::from_slice(&raw_bytes).context("parse manifest")?;
+    let fetcher = VerifiedFetcher::new(devnet);
+    let raw_bytes = fetcher
+        .fetch(root_cid, "manifest.json", MAX_MANIFEST_BYTES)
+        .context("fetch manifest")?;
+    let manifest = manifest::parse(&raw_bytes)?;
     if manifest.files.is_empty() {
         return Err(anyhow!("manifest.json missing files list"));
     }
@@ -699,34 +2078,46 @@ fn ensure_bundle_cached_helia(
         0,
         total_files,
     ));
-    for (idx, entry) in manifest.files.iter().enumerate() {
-        let file_url = format!("ipfs://{root_cid}/{}", entry.path);
-        let response = helper.fetch(&file_url, Some(ipfs.helia_timeout_ms))?;
-        if !(200..300).contains(&response.status) {
-            return Err(anyhow!(
-                "bundle fetch failed for {} with status {}",
-                entry.path,
-                response.status
-            ));
-        }
-        let dest = sanitize_bundle_destination(bundle_dir, &entry.path)?;
-        if let Some(parent) = dest.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        fs::write(dest, &response.body)?;
-        let completed = idx + 1;
-        on_progress(LaunchProgress::files(
-            "download",
-            format!("Downloaded {completed}/{total_files}: {}", entry.path),
-            download_percent(completed, total_files),
-            completed,
-            total_files,
-        ));
-    }
+    download_files_parallel(
+        &manifest.files,
+        ipfs.download_concurrency,
+        &|entry| download_bundle_file_verified(&fetcher, root_cid, bundle_dir, entry),
+        on_progress,
+    )?;
     fs::write(bundle_dir.join("manifest.json"), &raw_bytes).context("write manifest.json")?;
     Ok(())
 }
 
+/// Downloads one bundle file block by block. A file no gateway could serve
+/// in full is tried again.
+fn download_bundle_file_verified(
+    fetcher: &VerifiedFetcher,
+    root_cid: &str,
+    out_dir: &Path,
+    entry: &BundleManifestFile,
+) -> Result<()> {
+    let dest = sanitize_bundle_destination(out_dir, &entry.path)?;
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut attempt = 0;
+    let body = loop {
+        attempt += 1;
+        match fetcher.fetch(root_cid, &entry.path, entry.bytes) {
+            Ok(body) => break body,
+            Err(err) if attempt < FILE_DOWNLOAD_ATTEMPTS => {
+                pause_before_retry(&entry.path, attempt, &format!("{err:#}"))
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("download bundle file {}", entry.path));
+            }
+        }
+    };
+    stream_to_file(&body[..], &dest, entry.bytes, entry.sha256.as_deref())
+        .with_context(|| format!("store bundle file {}", entry.path))?;
+    Ok(())
+}
+
 fn fetch_dapp_manifest_local_node(
     devnet: &ResolvedConfig,
     ipfs: &EffectiveIpfsConfig,
@@ -743,14 +2134,52 @@ fn fetch_dapp_manifest_local_node(
         let text = res.text().unwrap_or_default();
         return Err(anyhow!("fetch manifest failed: {}", text));
     }
-    let raw_bytes = res.bytes().context("read manifest bytes")?.to_vec();
-    let manifest: BundleManifest = serde_json::from_slice(&raw_bytes).context("parse manifest")?;
+    let raw_bytes = read_limited(res, MAX_MANIFEST_BYTES).context("read manifest bytes")?;
+    let manifest = manifest::parse(&raw_bytes)?;
     if manifest.files.is_empty() {
         return Err(anyhow!("manifest.json missing files list"));
     }
     Ok((manifest, raw_bytes))
 }
 
+/// Fetches single bundle files with the effective IPFS backend, for callers
+/// that need a file or two rather than the whole bundle. Gateways that
+/// served a block are asked first for the fetcher's lifetime.
+pub(crate) struct BundleFileFetcher<'a> {
+    devnet: &'a ResolvedConfig,
+    ipfs: EffectiveIpfsConfig,
+    verified: VerifiedFetcher,
+}
+
+impl<'a> BundleFileFetcher<'a> {
+    pub(crate) fn new(devnet: &'a ResolvedConfig) -> Self {
+        Self {
+            devnet,
+            ipfs: resolve_effective_ipfs_config(devnet),
+            verified: VerifiedFetcher::new(devnet),
+        }
+    }
+
+    pub(crate) fn fetch(&mut self, root_cid: &str, path: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        match self.ipfs.fetch_backend {
+            IpfsFetchBackend::LocalNode => {
+                let gateway = normalize_gateway(&self.ipfs.gateway_endpoint);
+                let res = self
+                    .devnet
+                    .http_client
+                    .get(format!("{gateway}/ipfs/{root_cid}/{path}"))
+                    .send()
+                    .with_context(|| format!("fetch {path}"))?;
+                if !res.status().is_success() {
+                    bail!("fetch {path} failed with status {}", res.status());
+                }
+                read_limited(res, max_bytes).with_context(|| format!("read {path}"))
+            }
+            IpfsFetchBackend::Helia => self.verified.fetch(root_cid, path, max_bytes),
+        }
+    }
+}
+
 fn download_dapp_bundle_local_node(
     devnet: &ResolvedConfig,
     ipfs: &EffectiveIpfsConfig,
@@ -769,34 +2198,126 @@ fn download_dapp_bundle_local_node(
         0,
         total_files,
     ));
-    for (idx, entry) in manifest.files.iter().enumerate() {
-        let url = format!("{}/ipfs/{}/{}", gateway, root_cid, entry.path);
-        let res = devnet
-            .http_client
-            .get(url)
-            .send()
-            .context("fetch bundle file")?;
-        if !res.status().is_success() {
+    download_files_parallel(
+        &manifest.files,
+        ipfs.download_concurrency,
+        &|entry| download_bundle_file_local_node(devnet, &gateway, root_cid, out_dir, entry),
+        on_progress,
+    )?;
+    fs::write(out_dir.join("manifest.json"), manifest_bytes)?;
+    Ok(())
+}
+
+/// Runs `download` for every file on up to `concurrency` threads and
+/// reports progress from this thread as each one completes. No new files are
+/// started after a failure, and the first failure is returned.
+fn download_files_parallel(
+    files: &[BundleManifestFile],
+    concurrency: usize,
+    download: &(dyn Fn(&BundleManifestFile) -> Result<()> + Sync),
+    on_progress: &mut dyn FnMut(LaunchProgress),
+) -> Result<()> {
+    let total_files = files.len();
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let (tx, rx) = mpsc::channel::<(usize, Result<()>)>();
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, total_files.max(1)) {
+            let tx = tx.clone();
+            let (next, failed) = (&next, &failed);
+            scope.spawn(move || {
+                while !failed.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(entry) = files.get(index) else {
+                        break;
+                    };
+                    let result = download(entry);
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    if tx.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        let mut completed = 0;
+        let mut first_error = None;
+        for (index, result) in rx {
+            match result {
+                Ok(()) => {
+                    completed += 1;
+                    on_progress(LaunchProgress::files(
+                        "download",
+                        format!(
+                            "Downloaded {completed}/{total_files}: {}",
+                            files[index].path
+                        ),
+                        download_percent(completed, total_files),
+                        completed,
+                        total_files,
+                    ));
+                }
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    })
+}
+
+/// Downloads one bundle file from the local node. Connection errors, 5xx
+/// answers and bodies cut short or failing their digest are tried again.
+fn download_bundle_file_local_node(
+    devnet: &ResolvedConfig,
+    gateway: &str,
+    root_cid: &str,
+    out_dir: &Path,
+    entry: &BundleManifestFile,
+) -> Result<()> {
+    let url = format!("{}/ipfs/{}/{}", gateway, root_cid, entry.path);
+    let dest = sanitize_bundle_destination(out_dir, &entry.path)?;
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let last = attempt >= FILE_DOWNLOAD_ATTEMPTS;
+        let res = match devnet.http_client.get(&url).send() {
+            Ok(res) => res,
+            Err(err) if !last => {
+                pause_before_retry(&entry.path, attempt, &err);
+                continue;
+            }
+            Err(err) => return Err(err).context("fetch bundle file"),
+        };
+        let status = res.status();
+        if !status.is_success() {
+            if status.is_server_error() && !last {
+                pause_before_retry(&entry.path, attempt, &status);
+                continue;
+            }
             let text = res.text().unwrap_or_default();
             return Err(anyhow!("bundle fetch failed: {}", text));
         }
-        let bytes = res.bytes().context("read bundle file")?;
-        let dest = sanitize_bundle_destination(out_dir, &entry.path)?;
-        if let Some(parent) = dest.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        fs::write(dest, &bytes)?;
-        let completed = idx + 1;
-        on_progress(LaunchProgress::files(
-            "download",
-            format!("Downloaded {completed}/{total_files}: {}", entry.path),
-            download_percent(completed, total_files),
-            completed,
-            total_files,
-        ));
+        // Streamed straight to disk; the manifest size caps the body.
+        match stream_to_file(res, &dest, entry.bytes, entry.sha256.as_deref()) {
+            Ok(_) => return Ok(()),
+            Err(err) if !last => pause_before_retry(&entry.path, attempt, &err),
+            Err(err) => {
+                return Err(err).with_context(|| format!("download bundle file {}", entry.path));
+            }
+        }
     }
-    fs::write(out_dir.join("manifest.json"), manifest_bytes)?;
-    Ok(())
+}
+
+fn pause_before_retry(path: &str, attempt: u32, reason: &dyn std::fmt::Display) {
+    tracing::debug!(path, attempt, reason = %reason, "bundle file download failed; retrying");
+    std::thread::sleep(std::time::Duration::from_millis(250 * u64::from(attempt)));
 }
 
 fn download_percent(completed: usize, total: usize) -> u8 {
@@ -807,11 +2328,15 @@ fn download_percent(completed: usize, total: usize) -> u8 {
     pct.min(82) as u8
 }
 
-fn resolve_effective_ipfs_config(state: &AppState, devnet: &ResolvedConfig) -> EffectiveIpfsConfig {
+pub(crate) fn resolve_effective_ipfs_config(devnet: &ResolvedConfig) -> EffectiveIpfsConfig {
     let mut fetch_backend = devnet.ipfs_fetch_backend;
     let mut gateway_endpoint = devnet.ipfs_gateway.clone();
-    if let Some(config_path) = state.resolved.as_ref().and_then(|r| r.config_path.as_ref()) {
+    let mut download_concurrency = DEFAULT_DOWNLOAD_CONCURRENCY;
+    if let Some(config_path) = devnet.config_path.as_ref() {
         let settings = crate::settings::load_settings(config_path);
+        if let Some(concurrency) = settings.ipfs.download_concurrency {
+            download_concurrency = concurrency.clamp(1, MAX_DOWNLOAD_CONCURRENCY);
+        }
         if let Some(backend) = settings.ipfs.fetch_backend {
             fetch_backend = backend;
         }
@@ -827,7 +2352,7 @@ fn resolve_effective_ipfs_config(state: &AppState, devnet: &ResolvedConfig) -> E
         gateway_endpoint,
         helia_gateways: devnet.ipfs_helia_gateways.clone(),
         helia_routers: devnet.ipfs_helia_routers.clone(),
-        helia_timeout_ms: devnet.ipfs_helia_timeout_ms,
+        download_concurrency,
     }
 }
 
@@ -894,8 +2419,22 @@ fn u256_to_u64(value: U256) -> Result<u64> {
 
 #[cfg(test)]
 mod tests {
-    use super::{DappInfo, RpcLog};
+    use super::{
+        DappInfo, DappMetadata, DappPaused, DappPublished, DappPublishedV2, EffectiveIpfsConfig,
+        RegistryDapp, RpcLog, assemble_registry, bytes_to_string, community_warnings, dapp_key,
+        ensure_bundle_cached, get_logs_chunked, parse_hex_u64_opt, parse_root_cid,
+        parse_version_pick, registry_topics, rpc_log_to_entry, version_info,
+    };
+    use crate::bundle::verify_manifest;
+    use crate::config::{IpfsFetchBackend, RegistrySchema};
+    use crate::rpc_client::RpcClient;
+    use crate::rpc_manager::{RpcEndpoint, RpcEndpointManager};
+    use crate::settings::CommunityRegistry;
+    use crate::test_harness::{MockGateway, MockRpc, test_config};
+    use alloy_primitives::{Address, Bytes, U256};
+    use alloy_sol_types::SolEvent;
     use serde_json::json;
+    use std::fs;
 
     #[test]
     fn dapp_info_serializes_with_camel_case_keys() {
@@ -907,6 +2446,16 @@ mod tests {
             description: "Desc".to_string(),
             status: "Published".to_string(),
             root_cid: "bafy...".to_string(),
+            publisher: "0x0000000000000000000000000000000000000001".to_string(),
+            manifest_hash: None,
+            publisher_signature: None,
+            reported: false,
+            pinned: false,
+            offline: false,
+            registry: None,
+            registry_label: "VibeFi".to_string(),
+            warnings: Vec::new(),
+            icon: None,
         };
         let value = serde_json::to_value(dapp).expect("serialize DappInfo");
         assert_eq!(value.get("dappId"), Some(&json!("1")));
@@ -915,6 +2464,35 @@ mod tests {
         assert!(value.get("dapp_id").is_none());
         assert!(value.get("version_id").is_none());
         assert!(value.get("root_cid").is_none());
+        assert!(value.get("offline").is_none());
+        assert_eq!(value.get("registryLabel"), Some(&json!("VibeFi")));
+        assert!(value.get("registry").is_none());
+        assert!(value.get("warnings").is_none());
+        assert!(value.get("manifestHash").is_none());
+        assert!(value.get("publisherSignature").is_none());
+    }
+
+    #[test]
+    fn community_warnings_use_only_the_listing_registry_allowlist() {
+        let publisher = "0x00000000000000000000000000000000000000AA";
+        let mut registry = CommunityRegistry {
+            address: "0x0000000000000000000000000000000000000b0b".to_string(),
+            chain_id: 1,
+            label: "Bob's picks".to_string(),
+            ..CommunityRegistry::default()
+        };
+        assert_eq!(community_warnings(&registry, publisher).len(), 1);
+
+        registry.trusted_publishers = vec![publisher.to_ascii_lowercase()];
+        assert!(community_warnings(&registry, publisher).is_empty());
+        let warnings = community_warnings(&registry, "0x00000000000000000000000000000000000000cc");
+        assert!(warnings[0].contains("Bob's picks"));
+    }
+
+    #[test]
+    fn dapp_keys_are_scoped_to_community_registries() {
+        assert_eq!(dapp_key(None, "7"), "7");
+        assert_eq!(dapp_key(Some("0xb0b"), "7"), "0xb0b:7");
     }
 
     #[test]
@@ -940,4 +2518,323 @@ mod tests {
         assert!(parsed_missing.block_number.is_none());
         assert!(parsed_missing.log_index.is_none());
     }
+
+    #[test]
+    fn downloads_fixture_bundles_and_rejects_tampered_files() {
+        let rpc = MockRpc::start();
+        let gateway = MockGateway::start();
+        let dir = std::env::temp_dir().join(format!("vibefi-registry-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = test_config(&rpc, &gateway, &dir);
+        let ipfs = EffectiveIpfsConfig {
+            fetch_backend: IpfsFetchBackend::LocalNode,
+            gateway_endpoint: config.ipfs_gateway.clone(),
+            helia_gateways: Vec::new(),
+            helia_routers: Vec::new(),
+            download_concurrency: 2,
+        };
+
+        let root_cid = gateway.add_bundle(&[
+            ("index.html", b"<html></html>".as_slice()),
+            ("src/main.js", b"console.log(1)".as_slice()),
+        ]);
+        let bundle_dir = dir.join(&root_cid);
+        ensure_bundle_cached(&config, &ipfs, &root_cid, &bundle_dir, &mut |_| {}).unwrap();
+        assert!(bundle_dir.join("src/main.js").exists());
+        verify_manifest(&bundle_dir).unwrap();
+
+        let tampered = gateway.add_bundle(&[("index.html", b"hello".as_slice())]);
+        gateway.tamper(&tampered, "index.html", b"HELLO");
+        let tampered_dir = dir.join(&tampered);
+        assert!(
+            ensure_bundle_cached(&config, &ipfs, &tampered, &tampered_dir, &mut |_| {}).is_err()
+        );
+        assert!(!tampered_dir.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn published_events_from_the_node_decode() {
+        let rpc = MockRpc::start();
+        let registry = Address::repeat_byte(0x11);
+        rpc.set_block_number(20);
+        let published = DappPublished {
+            dappId: U256::from(7),
+            versionId: U256::from(1),
+            rootCid: Bytes::from_static(b"bafyapp"),
+            proposer: Address::repeat_byte(0xaa),
+        };
+        rpc.push_log(registry, published.encode_log_data(), 12);
+
+        let manager = RpcEndpointManager::new(
+            vec![RpcEndpoint {
+                url: rpc.url.clone(),
+                label: None,
+                chain_id: None,
+            }],
+            RpcClient::new().unwrap(),
+            1,
+        );
+        let response = manager
+            .send_rpc(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_getLogs",
+                "params": [{
+                    "address": format!("{registry:#x}"),
+                    "topics": [format!("{:#x}", DappPublished::SIGNATURE_HASH)],
+                    "fromBlock": "0x0",
+                    "toBlock": "0x14"
+                }]
+            }))
+            .unwrap();
+        let logs: Vec<RpcLog> = serde_json::from_value(response["result"].clone()).unwrap();
+        assert_eq!(logs.len(), 1);
+        let entry = rpc_log_to_entry(logs.into_iter().next().unwrap()).unwrap();
+        assert_eq!(
+            (entry.kind.as_str(), entry.block_number),
+            ("DappPublished", 12)
+        );
+        let decoded = DappPublished::decode_log(&entry.log).unwrap();
+        assert_eq!(bytes_to_string(&decoded.data.rootCid), "bafyapp");
+        assert_eq!(rpc.calls(), ["eth_getLogs"]);
+    }
+
+    /// Dapp 7 with version 1 published and version 2 published, then paused.
+    fn swap_with_two_versions() -> RegistryDapp {
+        let registry = Address::repeat_byte(0x11);
+        let entry = |data: alloy_primitives::LogData, block: u64| {
+            rpc_log_to_entry(RpcLog {
+                address: format!("{registry:#x}"),
+                data: format!("0x{}", hex::encode(&data.data)),
+                topics: data.topics().iter().map(|t| format!("{t:#x}")).collect(),
+                block_number: Some(format!("0x{block:x}")),
+                log_index: Some("0x0".to_string()),
+            })
+            .unwrap()
+        };
+        let published = |version: u64, cid: &'static [u8]| DappPublished {
+            dappId: U256::from(7),
+            versionId: U256::from(version),
+            rootCid: Bytes::from_static(cid),
+            proposer: Address::repeat_byte(0xaa),
+        };
+        let metadata = |version: u64, label: &str| DappMetadata {
+            dappId: U256::from(7),
+            versionId: U256::from(version),
+            name: "Swap".to_string(),
+            version: label.to_string(),
+            description: String::new(),
+        };
+        let paused = DappPaused {
+            dappId: U256::from(7),
+            versionId: U256::from(2),
+            pausedBy: Address::repeat_byte(0xbb),
+            reason: "broken".to_string(),
+        };
+        let mut dapps = assemble_registry(vec![
+            entry(published(1, b"bafyone").encode_log_data(), 10),
+            entry(metadata(1, "1.0.0").encode_log_data(), 11),
+            entry(published(2, b"bafytwo").encode_log_data(), 12),
+            entry(metadata(2, "1.1.0").encode_log_data(), 13),
+            entry(paused.encode_log_data(), 14),
+        ])
+        .unwrap();
+        dapps.remove(&7).unwrap()
+    }
+
+    #[test]
+    fn the_latest_version_is_the_newest_published() {
+        assert_eq!(swap_with_two_versions().latest_version_id, 2);
+    }
+
+    #[test]
+    fn every_version_is_kept_with_its_status() {
+        let dapp = swap_with_two_versions();
+        let versions: Vec<DappInfo> = dapp
+            .versions
+            .iter()
+            .rev()
+            .map(|(id, version)| version_info(dapp.dapp_id, *id, Some(version)))
+            .collect();
+        assert_eq!(
+            versions
+                .iter()
+                .map(|v| (v.version_id.as_str(), v.version.as_str(), v.status.as_str()))
+                .collect::<Vec<_>>(),
+            [("2", "1.1.0", "Paused"), ("1", "1.0.0", "Published")]
+        );
+        assert_eq!(versions[1].root_cid, "bafyone");
+    }
+
+    #[test]
+    fn version_picks_name_a_dapp_and_a_version() {
+        assert_eq!(
+            parse_version_pick(&json!({ "dappId": "7", "versionId": 1 })).unwrap(),
+            ("7".to_string(), "1".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn version_picks_need_a_dapp_id() {
+        assert!(parse_version_pick(&json!({ "versionId": 1 })).is_err());
+    }
+
+    /// Scans blocks 0 to 5000 of a node that refuses ranges over 1000
+    /// blocks, returning the logs and every range requested.
+    fn scan_refusing_node() -> (Vec<RpcLog>, Vec<(u64, u64)>) {
+        let log_at = |block: u64, index: u64| {
+            json!({
+                "address": "0x0000000000000000000000000000000000000011",
+                "data": "0x",
+                "topics": [],
+                "blockNumber": format!("0x{block:x}"),
+                "logIndex": format!("0x{index:x}"),
+            })
+        };
+        let node_logs = [
+            log_at(4000, 0),
+            log_at(2500, 1),
+            log_at(2500, 0),
+            log_at(10, 0),
+        ];
+        let mut requests = Vec::new();
+        let logs = get_logs_chunked(0, 5000, 50_000, |from, to| {
+            requests.push((from, to));
+            if to - from + 1 > 1000 {
+                return Ok(json!({
+                    "error": { "code": -32005, "message": "query returned more than 10000 results" }
+                }));
+            }
+            let result: Vec<_> = node_logs
+                .iter()
+                .filter(|log| {
+                    let block = parse_hex_u64_opt(log["blockNumber"].as_str()).unwrap();
+                    (from..=to).contains(&block)
+                })
+                .cloned()
+                .collect();
+            Ok(json!({ "result": result }))
+        })
+        .unwrap();
+        (logs, requests)
+    }
+
+    #[test]
+    fn get_logs_merges_chunks_in_order() {
+        let (logs, _) = scan_refusing_node();
+        let order: Vec<_> = logs
+            .iter()
+            .map(|log| (log.block(), log.log_index.as_deref()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (Some(10), Some("0x0")),
+                (Some(2500), Some("0x0")),
+                (Some(2500), Some("0x1")),
+                (Some(4000), Some("0x0")),
+            ]
+        );
+    }
+
+    #[test]
+    fn get_logs_splits_ranges_the_node_refuses() {
+        let (_, requests) = scan_refusing_node();
+        assert_eq!(
+            requests[..4],
+            [(0, 5000), (2501, 5000), (3751, 5000), (4376, 5000)]
+        );
+        assert_eq!(requests.last(), Some(&(0, 0)));
+    }
+
+    #[test]
+    fn get_logs_fails_on_other_node_errors() {
+        let failed = get_logs_chunked(0, 5000, 1000, |_, _| {
+            Ok(json!({ "error": { "code": -32601, "message": "method not found" } }))
+        });
+        assert!(failed.is_err());
+    }
+
+    #[test]
+    fn v2_registries_watch_the_v1_events_too() {
+        let v1 = registry_topics(RegistrySchema::V1);
+        let v2 = registry_topics(RegistrySchema::V2);
+        assert!(!v1.contains(&DappPublishedV2::SIGNATURE_HASH));
+        assert!(v2.contains(&DappPublishedV2::SIGNATURE_HASH));
+        assert!(v1.iter().all(|topic| v2.contains(topic)));
+    }
+
+    #[test]
+    fn v2_registries_decode_extended_events_alongside_v1() {
+        let rpc = MockRpc::start();
+        let registry = Address::repeat_byte(0x22);
+        rpc.set_block_number(30);
+        let legacy = DappPublished {
+            dappId: U256::from(1),
+            versionId: U256::from(1),
+            rootCid: Bytes::from_static(b"bafyold"),
+            proposer: Address::repeat_byte(0xaa),
+        };
+        let extended = DappPublishedV2 {
+            dappId: U256::from(2),
+            versionId: U256::from(1),
+            rootCid: Bytes::from_static(b"bafynew"),
+            proposer: Address::repeat_byte(0xbb),
+            manifestHash: [0x33; 32].into(),
+            publisherSignature: Bytes::from_static(&[0x44; 65]),
+        };
+        rpc.push_log(registry, legacy.encode_log_data(), 12);
+        rpc.push_log(registry, extended.encode_log_data(), 13);
+
+        let manager = RpcEndpointManager::new(
+            vec![RpcEndpoint {
+                url: rpc.url.clone(),
+                label: None,
+                chain_id: None,
+            }],
+            RpcClient::new().unwrap(),
+            1,
+        );
+        let topics: Vec<String> = registry_topics(RegistrySchema::V2)
+            .iter()
+            .map(|topic| format!("{topic:#x}"))
+            .collect();
+        let response = manager
+            .send_rpc(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_getLogs",
+                "params": [{
+                    "address": format!("{registry:#x}"),
+                    "topics": [topics],
+                    "fromBlock": "0x0",
+                    "toBlock": "0x1e"
+                }]
+            }))
+            .unwrap();
+        let logs: Vec<RpcLog> = serde_json::from_value(response["result"].clone()).unwrap();
+        let entries: Vec<_> = logs
+            .into_iter()
+            .map(|log| rpc_log_to_entry(log).unwrap())
+            .collect();
+        let kinds: Vec<&str> = entries.iter().map(|entry| entry.kind.as_str()).collect();
+        assert_eq!(kinds, ["DappPublished", "DappPublishedV2"]);
+        let decoded = DappPublishedV2::decode_log(&entries[1].log).unwrap();
+        assert_eq!(bytes_to_string(&decoded.data.rootCid), "bafynew");
+        assert_eq!(decoded.data.manifestHash, extended.manifestHash);
+        assert_eq!(decoded.data.publisherSignature.len(), 65);
+    }
+
+    #[test]
+    fn pasted_root_cids_are_normalized_and_checked() {
+        let v1 = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+        assert_eq!(parse_root_cid(&format!(" ipfs://{v1}/ ")).unwrap(), v1);
+        assert_eq!(parse_root_cid(&format!("/ipfs/{v1}")).unwrap(), v1);
+        let v0 = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+        assert_eq!(parse_root_cid(v0).unwrap(), v0);
+        assert!(parse_root_cid("../../settings").is_err());
+        assert!(parse_root_cid(&format!("{v1}/../x")).is_err());
+        assert!(parse_root_cid("").is_err());
+    }
 }
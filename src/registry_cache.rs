@@ -0,0 +1,399 @@
+//! Launcher metadata that lives in dapp bundles rather than in registry
+//! events: the icon a manifest declares. It is cached on disk per release,
+//! keyed by registry, dapp id and version id, so reopening the launcher does
+//! not go back to IPFS for it. Each entry is tagged with the root CID and
+//! manifest hash the registry events give the release; when new events
+//! change either, or a newer version supersedes it, the entry is dropped and
+//! fetched again. Icons are fetched in the background within a byte budget
+//! per listing and sent to the launcher as they arrive.
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::bundle::{normalize_manifest_path, normalize_sha256, sha256_hex};
+use crate::manifest::{BundleManifest, BundleManifestFile};
+use crate::registry::{BundleFileFetcher, DappInfo, MAX_MANIFEST_BYTES, dapp_key};
+use crate::state::{AppState, UserEvent};
+
+/// Icon bytes fetched per listing when the settings name no budget.
+pub const DEFAULT_ICON_PREFETCH_KB: u64 = 1024;
+/// Provider event carrying freshly cached icons, keyed like the launcher's
+/// dapp keys.
+const ICONS_EVENT: &str = "vibefiDappIcons";
+const CACHE_DIR: &str = "registry-metadata";
+const INDEX_FILE: &str = "index.json";
+const ICONS_DIR: &str = "icons";
+/// Larger icons are skipped; the launcher shows them at 24px.
+const MAX_ICON_BYTES: u64 = 256 * 1024;
+const ICON_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("webp", "image/webp"),
+    ("gif", "image/gif"),
+    ("svg", "image/svg+xml"),
+];
+
+/// Serializes read-modify-write cycles of the index.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+/// One background prefetch at a time; a listing during it waits for the next.
+static PREFETCHING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedRelease {
+    /// Root CID and manifest hash the release had when it was cached.
+    tag: String,
+    /// File under `icons/`; unset when the manifest declares no usable icon.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    icon: Option<String>,
+}
+
+type Index = BTreeMap<String, CachedRelease>;
+
+/// A listed release without a current cache entry.
+struct StaleRelease {
+    key: String,
+    tag: String,
+    dapp_key: String,
+    root_cid: String,
+}
+
+/// Fills in cached icons for `dapps`, drops entries the listing shows are
+/// outdated, and starts fetching the missing ones for `webview_id`.
+pub fn annotate(state: &AppState, dapps: &mut [DappInfo], webview_id: &str) {
+    let Some(resolved) = state.resolved.as_ref() else {
+        return;
+    };
+    let dir = resolved.cache_dir.join(CACHE_DIR);
+    let mut stale = Vec::new();
+    {
+        let _guard = INDEX_LOCK.lock().expect("registry cache index");
+        let mut index = load_index(&dir);
+        let mut changed = false;
+        for dapp in dapps.iter_mut() {
+            if dapp.offline || dapp.root_cid.is_empty() {
+                continue;
+            }
+            let registry = dapp.registry.as_deref().unwrap_or(&resolved.dapp_registry);
+            let key = release_key(registry, &dapp.dapp_id, &dapp.version_id);
+            changed |= drop_superseded(&mut index, &key);
+            let tag = release_tag(dapp);
+            match index.get(&key) {
+                Some(entry) if entry.tag == tag => {
+                    dapp.icon = entry.icon.as_deref().and_then(|icon| data_url(&dir, icon));
+                }
+                _ => stale.push(StaleRelease {
+                    key,
+                    tag,
+                    dapp_key: dapp_key(dapp.registry.as_deref(), &dapp.dapp_id),
+                    root_cid: dapp.root_cid.clone(),
+                }),
+            }
+        }
+        if changed {
+            remove_orphaned_icons(&dir, &index);
+            save_index(&dir, &index);
+        }
+    }
+
+    let settings = resolved
+        .config_path
+        .as_deref()
+        .map(crate::settings::load_settings)
+        .unwrap_or_default();
+    let budget = settings
+        .icon_prefetch_kb
+        .unwrap_or(DEFAULT_ICON_PREFETCH_KB)
+        .saturating_mul(1024);
+    if stale.is_empty() || budget == 0 || settings.metered_connection {
+        return;
+    }
+    if PREFETCHING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let state = state.clone();
+    let webview_id = webview_id.to_string();
+    std::thread::spawn(move || {
+        prefetch(&state, &dir, stale, budget, webview_id);
+        PREFETCHING.store(false, Ordering::SeqCst);
+    });
+}
+
+fn prefetch(
+    state: &AppState,
+    dir: &Path,
+    stale: Vec<StaleRelease>,
+    budget: u64,
+    webview_id: String,
+) {
+    let Some(resolved) = state.resolved.as_ref() else {
+        return;
+    };
+    let mut fetcher = BundleFileFetcher::new(resolved);
+    let mut remaining = budget;
+    let mut icons = serde_json::Map::new();
+    for release in stale {
+        let entry = match fetch_release(
+            &mut fetcher,
+            &resolved.cache_dir,
+            dir,
+            &release,
+            &mut remaining,
+        ) {
+            Ok(Some(entry)) => entry,
+            Ok(None) => {
+                tracing::debug!(
+                    budget,
+                    "icon prefetch budget spent; the rest waits for the next listing"
+                );
+                break;
+            }
+            Err(err) => {
+                // Not recorded, so the next listing tries again.
+                tracing::debug!(root_cid = %release.root_cid, error = %format!("{err:#}"), "failed to cache dapp icon");
+                continue;
+            }
+        };
+        if let Some(url) = entry.icon.as_deref().and_then(|icon| data_url(dir, icon)) {
+            icons.insert(release.dapp_key.clone(), serde_json::Value::String(url));
+        }
+        let _guard = INDEX_LOCK.lock().expect("registry cache index");
+        let mut index = load_index(dir);
+        index.insert(release.key, entry);
+        save_index(dir, &index);
+    }
+    tracing::info!(
+        icons = icons.len(),
+        fetched_bytes = budget - remaining,
+        "registry metadata prefetch finished"
+    );
+    if !icons.is_empty() {
+        let _ = state.proxy.send_event(UserEvent::ProviderEvent {
+            webview_id,
+            event: ICONS_EVENT.to_string(),
+            value: serde_json::Value::Object(icons),
+        });
+    }
+}
+
+/// Reads the release's manifest and stores its icon. Uses the bundle cache
+/// when the release was launched or prefetched before. Returns `None` when
+/// the icon does not fit in `remaining`.
+fn fetch_release(
+    fetcher: &mut BundleFileFetcher,
+    bundle_cache: &Path,
+    dir: &Path,
+    release: &StaleRelease,
+    remaining: &mut u64,
+) -> Result<Option<CachedRelease>> {
+    let cached_bundle = bundle_cache.join(&release.root_cid);
+    let cached = cached_bundle.join("manifest.json").exists();
+    let raw = if cached {
+        fs::read(cached_bundle.join("manifest.json")).context("read cached manifest.json")?
+    } else {
+        fetcher.fetch(&release.root_cid, "manifest.json", MAX_MANIFEST_BYTES)?
+    };
+    let manifest = crate::manifest::parse(&raw)?;
+    let Some((file, extension)) = pick_icon(&manifest) else {
+        return Ok(Some(CachedRelease {
+            tag: release.tag.clone(),
+            icon: None,
+        }));
+    };
+    let path = normalize_manifest_path(&file.path);
+    let bytes = if cached {
+        fs::read(cached_bundle.join(&path)).with_context(|| format!("read cached {path}"))?
+    } else {
+        if file.bytes > *remaining {
+            return Ok(None);
+        }
+        let bytes = fetcher.fetch(&release.root_cid, &path, file.bytes)?;
+        *remaining -= bytes.len() as u64;
+        bytes
+    };
+    if bytes.len() as u64 != file.bytes {
+        bail!("icon {path} size does not match the manifest");
+    }
+    let digest = sha256_hex(&bytes);
+    if file
+        .sha256
+        .as_deref()
+        .is_some_and(|expected| normalize_sha256(expected) != digest)
+    {
+        bail!("icon {path} hash does not match the manifest");
+    }
+    let name = format!("{digest}.{extension}");
+    let icons = dir.join(ICONS_DIR);
+    fs::create_dir_all(&icons).context("create icon cache dir")?;
+    fs::write(icons.join(&name), &bytes).context("write cached icon")?;
+    Ok(Some(CachedRelease {
+        tag: release.tag.clone(),
+        icon: Some(name),
+    }))
+}
+
+/// First declared icon of a supported type that the manifest lists and that
+/// is small enough to show in the launcher.
+fn pick_icon(manifest: &BundleManifest) -> Option<(&BundleManifestFile, &'static str)> {
+    manifest.icons.iter().find_map(|icon| {
+        let path = normalize_manifest_path(&icon.src);
+        let extension = icon_type(&path)?.0;
+        manifest
+            .files
+            .iter()
+            .find(|file| {
+                normalize_manifest_path(&file.path) == path && file.bytes <= MAX_ICON_BYTES
+            })
+            .map(|file| (file, extension))
+    })
+}
+
+fn icon_type(path: &str) -> Option<(&'static str, &'static str)> {
+    let extension = path.rsplit_once('.')?.1.to_ascii_lowercase();
+    ICON_TYPES
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .copied()
+}
+
+fn release_key(registry: &str, dapp_id: &str, version_id: &str) -> String {
+    format!("{}:{dapp_id}:{version_id}", registry.to_ascii_lowercase())
+}
+
+fn release_tag(dapp: &DappInfo) -> String {
+    format!(
+        "{}:{}",
+        dapp.root_cid,
+        dapp.manifest_hash.as_deref().unwrap_or_default()
+    )
+}
+
+/// Removes entries for other versions of the dapp `key` belongs to; only
+/// the latest version is listed, so older ones would never be read again.
+fn drop_superseded(index: &mut Index, key: &str) -> bool {
+    let Some((dapp, _)) = key.rsplit_once(':') else {
+        return false;
+    };
+    let prefix = format!("{dapp}:");
+    let before = index.len();
+    index.retain(|other, _| other == key || !other.starts_with(&prefix));
+    index.len() != before
+}
+
+fn remove_orphaned_icons(dir: &Path, index: &Index) {
+    let Ok(entries) = fs::read_dir(dir.join(ICONS_DIR)) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !index
+            .values()
+            .any(|release| release.icon.as_deref() == Some(name.as_str()))
+        {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn data_url(dir: &Path, icon: &str) -> Option<String> {
+    let (_, mime) = icon_type(icon)?;
+    let bytes = fs::read(dir.join(ICONS_DIR).join(icon)).ok()?;
+    Some(format!(
+        "data:{mime};base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+fn load_index(dir: &Path) -> Index {
+    fs::read(dir.join(INDEX_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_slice(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(dir: &Path, index: &Index) {
+    let result = (|| -> Result<()> {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(INDEX_FILE), serde_json::to_vec(index)?)?;
+        Ok(())
+    })();
+    if let Err(err) = result {
+        // Only costs a refetch on the next listing.
+        tracing::debug!(dir = %dir.display(), error = %err, "failed to save registry metadata cache");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CachedRelease, Index, drop_superseded, pick_icon, release_key};
+    use crate::manifest::parse;
+    use serde_json::json;
+
+    #[test]
+    fn picks_the_first_listed_icon_that_can_be_shown() {
+        let manifest = json!({
+            "schemaVersion": 2,
+            "files": [
+                { "path": "assets/logo.tiff", "bytes": 10, "sha256": "aa" },
+                { "path": "assets/huge.png", "bytes": 10_000_000, "sha256": "bb" },
+                { "path": "assets/icon.png", "bytes": 512, "sha256": "cc" }
+            ],
+            "icons": [
+                { "src": "assets/logo.tiff" },
+                { "src": "assets/huge.png" },
+                { "src": "./assets/icon.png" }
+            ]
+        });
+        let manifest = parse(manifest.to_string().as_bytes()).unwrap();
+        let (file, extension) = pick_icon(&manifest).unwrap();
+        assert_eq!(file.path, "assets/icon.png");
+        assert_eq!(extension, "png");
+    }
+
+    fn index_of(keys: &[String]) -> Index {
+        let mut index = Index::new();
+        for (tag, key) in keys.iter().enumerate() {
+            index.insert(
+                key.clone(),
+                CachedRelease {
+                    tag: tag.to_string(),
+                    icon: None,
+                },
+            );
+        }
+        index
+    }
+
+    #[test]
+    fn a_new_version_drops_older_ones_of_that_dapp_only() {
+        let mut index = index_of(&[
+            release_key("0xABC", "1", "1"),
+            release_key("0xabc", "12", "1"),
+            release_key("0xdef", "1", "1"),
+        ]);
+        assert!(drop_superseded(&mut index, &release_key("0xabc", "1", "2")));
+        assert_eq!(
+            index.keys().collect::<Vec<_>>(),
+            ["0xabc:12:1", "0xdef:1:1"]
+        );
+    }
+
+    #[test]
+    fn the_latest_version_supersedes_nothing() {
+        let mut index = index_of(&[
+            release_key("0xabc", "12", "1"),
+            release_key("0xdef", "1", "1"),
+        ]);
+        assert!(!drop_superseded(
+            &mut index,
+            &release_key("0xabc", "12", "1")
+        ));
+        assert_eq!(index.len(), 2);
+    }
+}
@@ -0,0 +1,224 @@
+//! Registry events already scanned, kept under `registry-index/` in the cache
+//! directory with one file per chain and registry, so a listing only asks the
+//! node for the blocks since the last one. Only events at least
+//! [`REORG_DEPTH`] blocks deep are stored, along with the hash of the last
+//! block covered; when that hash no longer matches the chain (a deep reorg or
+//! a devnet that was reset), the index is dropped and rebuilt.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::RegistrySchema;
+use crate::registry::RpcLog;
+
+/// Blocks behind the head before their events go into the index. Newer
+/// events are scanned on every listing.
+pub(crate) const REORG_DEPTH: u64 = 64;
+const INDEX_DIR: &str = "registry-index";
+
+/// Serializes reads and writes of the index files.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Last block an index covers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SyncPoint {
+    pub block: u64,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RegistryIndex {
+    /// Block the scan starts from and the events it looks for; the index is
+    /// rebuilt when the config changes either.
+    from_block: u64,
+    schema: RegistrySchema,
+    #[serde(default)]
+    synced_to: Option<SyncPoint>,
+    #[serde(default)]
+    logs: Vec<RpcLog>,
+}
+
+impl RegistryIndex {
+    pub(crate) fn new(from_block: u64, schema: RegistrySchema) -> Self {
+        Self {
+            from_block,
+            schema,
+            synced_to: None,
+            logs: Vec::new(),
+        }
+    }
+
+    pub(crate) fn synced_to(&self) -> Option<&SyncPoint> {
+        self.synced_to.as_ref()
+    }
+
+    /// First block the next scan has to cover.
+    pub(crate) fn resume_block(&self) -> u64 {
+        self.synced_to
+            .as_ref()
+            .map_or(self.from_block, |point| point.block + 1)
+    }
+
+    pub(crate) fn logs(&self) -> &[RpcLog] {
+        &self.logs
+    }
+
+    /// Records a scan from [`Self::resume_block`] on. Only logs up to
+    /// `point` are kept; later ones are scanned again next time.
+    pub(crate) fn advance(&mut self, scanned: &[RpcLog], point: SyncPoint) {
+        self.logs.extend(
+            scanned
+                .iter()
+                .filter(|log| log.block().is_some_and(|block| block <= point.block))
+                .cloned(),
+        );
+        self.synced_to = Some(point);
+    }
+}
+
+fn index_path(cache_dir: &Path, chain_id: u64, registry: &str) -> PathBuf {
+    let registry: String = registry
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    cache_dir
+        .join(INDEX_DIR)
+        .join(format!("{chain_id}-{registry}.json"))
+}
+
+/// The stored index of `registry`, or an empty one when there is none or it
+/// was built for another start block or schema.
+pub(crate) fn load(
+    cache_dir: &Path,
+    chain_id: u64,
+    registry: &str,
+    from_block: u64,
+    schema: RegistrySchema,
+) -> RegistryIndex {
+    let _guard = INDEX_LOCK.lock().expect("registry index");
+    fs::read(index_path(cache_dir, chain_id, registry))
+        .ok()
+        .and_then(|raw| serde_json::from_slice::<RegistryIndex>(&raw).ok())
+        .filter(|index| index.from_block == from_block && index.schema == schema)
+        .unwrap_or_else(|| RegistryIndex::new(from_block, schema))
+}
+
+pub(crate) fn save(cache_dir: &Path, chain_id: u64, registry: &str, index: &RegistryIndex) {
+    let path = index_path(cache_dir, chain_id, registry);
+    let _guard = INDEX_LOCK.lock().expect("registry index");
+    let result = (|| -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_vec(index)?)?;
+        Ok(())
+    })();
+    if let Err(err) = result {
+        // Only costs a longer scan on the next listing.
+        tracing::debug!(path = %path.display(), error = %err, "failed to save registry index");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RegistryIndex, SyncPoint, load, save};
+    use crate::config::RegistrySchema;
+    use crate::registry::RpcLog;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn log_at(block: Option<u64>) -> RpcLog {
+        serde_json::from_value(serde_json::json!({
+            "address": "0x0000000000000000000000000000000000000001",
+            "data": "0x",
+            "topics": [],
+            "blockNumber": block.map(|block| format!("0x{block:x}")),
+            "logIndex": "0x0",
+        }))
+        .unwrap()
+    }
+
+    const REGISTRY: &str = "0xAbCd000000000000000000000000000000000001";
+
+    fn index_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "vibefi-registry-index-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Indexes logs up to block 200 of chain 1 from block 100 and saves them.
+    fn synced_index(dir: &Path) -> RegistryIndex {
+        let mut index = load(dir, 1, REGISTRY, 100, RegistrySchema::V1);
+        index.advance(
+            &[
+                log_at(Some(150)),
+                log_at(Some(200)),
+                log_at(Some(260)),
+                log_at(None),
+            ],
+            SyncPoint {
+                block: 200,
+                hash: "0xaa".to_string(),
+            },
+        );
+        save(dir, 1, REGISTRY, &index);
+        index
+    }
+
+    #[test]
+    fn starts_from_the_deployment_block() {
+        let dir = index_dir("fresh");
+        assert_eq!(
+            load(&dir, 1, REGISTRY, 100, RegistrySchema::V1).resume_block(),
+            100
+        );
+    }
+
+    #[test]
+    fn keeps_only_settled_logs() {
+        let dir = index_dir("settled");
+        let index = synced_index(&dir);
+        assert_eq!(index.logs().len(), 2);
+        assert_eq!(index.resume_block(), 201);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reloads_the_saved_index() {
+        let dir = index_dir("reload");
+        synced_index(&dir);
+        let reloaded = load(&dir, 1, &REGISTRY.to_lowercase(), 100, RegistrySchema::V1);
+        assert_eq!(reloaded.synced_to().map(|point| point.block), Some(200));
+        assert_eq!(reloaded.logs().len(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rebuilds_for_another_config() {
+        let dir = index_dir("rebuild");
+        synced_index(&dir);
+        assert!(
+            load(&dir, 1, REGISTRY, 0, RegistrySchema::V1)
+                .synced_to()
+                .is_none()
+        );
+        assert!(
+            load(&dir, 1, REGISTRY, 100, RegistrySchema::V2)
+                .synced_to()
+                .is_none()
+        );
+        assert!(
+            load(&dir, 5, REGISTRY, 100, RegistrySchema::V1)
+                .synced_to()
+                .is_none()
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
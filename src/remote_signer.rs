@@ -0,0 +1,169 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Value, json};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::config::RemoteSignerSettings;
+
+/// Generous enough for signers that wait on an HSM or a human co-signer.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Client for an external signing service speaking the Ethereum signing
+/// JSON-RPC methods (web3signer's eth1 API, Clef, custodial signer gateways).
+/// Keys never leave the service; the client only forwards requests the user
+/// approved.
+pub struct RemoteSigner {
+    settings: RemoteSignerSettings,
+    client: reqwest::blocking::Client,
+    next_id: AtomicU64,
+}
+
+impl RemoteSigner {
+    pub fn new(settings: RemoteSignerSettings, client: reqwest::blocking::Client) -> Self {
+        Self {
+            settings,
+            client,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.settings.url
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut request = self
+            .client
+            .post(&self.settings.url)
+            .timeout(REQUEST_TIMEOUT)
+            .json(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        if let Some(token) = &self.settings.auth_token {
+            request = request.bearer_auth(token);
+        }
+        for (name, value) in &self.settings.headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .with_context(|| format!("remote signer unreachable at {}", self.settings.url))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            bail!("remote signer rejected the credentials ({status})");
+        }
+        let body: Value = response
+            .error_for_status()
+            .and_then(|response| response.json())
+            .with_context(|| format!("remote signer {method} failed"))?;
+        if let Some(error) = body.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string());
+            bail!("remote signer refused {method}: {message}");
+        }
+        Ok(body.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    fn call_hex(&self, method: &str, params: Value) -> Result<String> {
+        let result = self.call(method, params)?;
+        result
+            .as_str()
+            .filter(|value| {
+                value
+                    .strip_prefix("0x")
+                    .is_some_and(|digits| hex::decode(digits).is_ok())
+            })
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("remote signer returned invalid {method} result: {result}"))
+    }
+
+    /// Accounts the service signs for, lowercased.
+    pub fn accounts(&self) -> Result<Vec<String>> {
+        let result = self.call("eth_accounts", json!([]))?;
+        let accounts: Vec<String> = serde_json::from_value(result)
+            .context("remote signer returned invalid eth_accounts result")?;
+        Ok(accounts
+            .into_iter()
+            .map(|account| account.to_ascii_lowercase())
+            .collect())
+    }
+
+    /// EIP-191 signature of `message` (`eth_sign`, prefixed by the service).
+    pub fn sign_message(&self, account: &str, message: &[u8]) -> Result<String> {
+        self.call_hex(
+            "eth_sign",
+            json!([account, format!("0x{}", hex::encode(message))]),
+        )
+    }
+
+    /// EIP-712 signature of `typed_data`, the dapp's JSON object.
+    pub fn sign_typed_data(&self, account: &str, typed_data: Value) -> Result<String> {
+        self.call_hex("eth_signTypedData", json!([account, typed_data]))
+    }
+
+    /// Signs a filled transaction and returns the raw signed transaction.
+    pub fn sign_transaction(&self, tx: Value) -> Result<String> {
+        self.call_hex("eth_signTransaction", json!([tx]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RemoteSigner;
+    use crate::config::RemoteSignerSettings;
+    use crate::test_harness::MockRpc;
+    use serde_json::json;
+
+    fn signer(rpc: &MockRpc) -> RemoteSigner {
+        RemoteSigner::new(
+            RemoteSignerSettings {
+                url: rpc.url.clone(),
+                auth_token: Some("secret".to_string()),
+                headers: Default::default(),
+            },
+            reqwest::blocking::Client::new(),
+        )
+    }
+
+    #[test]
+    fn forwards_signing_methods_to_the_service() {
+        let rpc = MockRpc::start();
+        rpc.set_result(
+            "eth_accounts",
+            json!(["0x70997970C51812dc3A010C7d01b50e0d17dc79C8"]),
+        );
+        rpc.set_result("eth_sign", json!(format!("0x{}", "ab".repeat(65))));
+        rpc.set_result("eth_signTransaction", json!("0x02f8"));
+        let signer = signer(&rpc);
+
+        let accounts = signer.accounts().unwrap();
+        assert_eq!(accounts, ["0x70997970c51812dc3a010c7d01b50e0d17dc79c8"]);
+        let signature = signer.sign_message(&accounts[0], b"hello").unwrap();
+        assert_eq!(signature.len(), 2 + 130);
+        assert_eq!(signer.sign_transaction(json!({})).unwrap(), "0x02f8");
+        assert_eq!(
+            rpc.calls(),
+            ["eth_accounts", "eth_sign", "eth_signTransaction"]
+        );
+    }
+
+    #[test]
+    fn malformed_results_are_reported() {
+        let rpc = MockRpc::start();
+        rpc.set_result("eth_sign", json!("not hex"));
+        let err = signer(&rpc).sign_message("0x01", b"hi").unwrap_err();
+        assert!(err.to_string().contains("invalid eth_sign result"));
+    }
+
+    #[test]
+    fn service_errors_are_reported() {
+        let rpc = MockRpc::start();
+        let err = signer(&rpc)
+            .sign_typed_data("0x01", json!({}))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("eth_signTypedData not mocked"), "{err}");
+    }
+}
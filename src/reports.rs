@@ -0,0 +1,184 @@
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::{SolCall, sol};
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::config::ResolvedConfig;
+use crate::state::AppState;
+
+/// Longest reason accepted; the contract stores it as calldata.
+pub const MAX_REASON_CHARS: usize = 500;
+
+sol! {
+    function reportDapp(uint256 dappId, uint256 versionId, string reason);
+}
+
+/// A dapp the user reported. Kept locally either way so the launcher can hide
+/// it; `tx_hash` is set when the report was also submitted on-chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DappReport {
+    pub dapp_id: String,
+    #[serde(default)]
+    pub version_id: String,
+    #[serde(default)]
+    pub root_cid: String,
+    pub reason: String,
+    pub reported_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    /// Community registry the dapp was listed from; unset for the configured registry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+}
+
+/// Reports live next to `settings.json`, or in the cache dir without a config file.
+fn reports_path(resolved: &ResolvedConfig) -> PathBuf {
+    match &resolved.config_path {
+        Some(config_path) => config_path.with_file_name("reported-dapps.json"),
+        None => resolved.cache_dir.join("reported-dapps.json"),
+    }
+}
+
+pub fn load_reports(resolved: &ResolvedConfig) -> Vec<DappReport> {
+    let path = reports_path(resolved);
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            tracing::warn!(path = %path.display(), error = %err, "failed to read dapp reports");
+            return Vec::new();
+        }
+    };
+    serde_json::from_str(&raw).unwrap_or_else(|err| {
+        tracing::warn!(path = %path.display(), error = %err, "failed to parse dapp reports");
+        Vec::new()
+    })
+}
+
+fn save_reports(resolved: &ResolvedConfig, reports: &[DappReport]) -> Result<()> {
+    let path = reports_path(resolved);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("create reports directory")?;
+    }
+    let json = serde_json::to_string_pretty(reports).context("serialize dapp reports")?;
+    fs::write(&path, json).context("write reported-dapps.json")?;
+    Ok(())
+}
+
+/// Registry-scoped keys (see [`crate::registry::dapp_key`]) of the dapps the
+/// user has reported.
+pub fn reported_dapp_ids(resolved: &ResolvedConfig) -> HashSet<String> {
+    load_reports(resolved)
+        .iter()
+        .map(|report| crate::registry::dapp_key(report.registry.as_deref(), &report.dapp_id))
+        .collect()
+}
+
+fn report_calldata(dapp_id: &str, version_id: &str, reason: &str) -> Result<Vec<u8>> {
+    let dapp_id: U256 = dapp_id.parse().context("invalid dappId")?;
+    let version_id: U256 = if version_id.is_empty() {
+        U256::ZERO
+    } else {
+        version_id.parse().context("invalid versionId")?
+    };
+    Ok(reportDappCall {
+        dappId: dapp_id,
+        versionId: version_id,
+        reason: reason.to_string(),
+    }
+    .abi_encode())
+}
+
+/// Records a report for `dapp_id` and, when `reportRegistry` is configured,
+/// submits it on-chain from the connected wallet. A failed submission leaves
+/// nothing recorded so the user can retry. `reportRegistry` only moderates the
+/// configured registry, so reports on community registry dapps stay local.
+pub fn report_dapp(
+    state: &AppState,
+    registry: Option<&str>,
+    dapp_id: &str,
+    version_id: &str,
+    root_cid: &str,
+    reason: &str,
+) -> Result<DappReport> {
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("Network not configured"))?;
+    let reason = reason.trim();
+    if reason.is_empty() {
+        bail!("a reason is required to report a dapp");
+    }
+    if reason.chars().count() > MAX_REASON_CHARS {
+        bail!("report reason exceeds {MAX_REASON_CHARS} characters");
+    }
+
+    let calldata = report_calldata(dapp_id, version_id, reason)?;
+    let report_registry = resolved
+        .report_registry
+        .as_ref()
+        .filter(|_| registry.is_none());
+    let tx_hash = match report_registry {
+        Some(report_registry) => {
+            let to: Address = report_registry
+                .parse()
+                .context("invalid reportRegistry address")?;
+            let hash = crate::ipc::send_host_transaction(state, to, calldata.into())?;
+            tracing::info!(dapp_id, tx_hash = %hash, "dapp report submitted");
+            Some(hash)
+        }
+        None => {
+            tracing::info!(dapp_id, "dapp report stored locally");
+            None
+        }
+    };
+
+    let report = DappReport {
+        dapp_id: dapp_id.to_string(),
+        version_id: version_id.to_string(),
+        root_cid: root_cid.to_string(),
+        reason: reason.to_string(),
+        reported_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        tx_hash,
+        registry: registry.map(str::to_string),
+    };
+    let mut reports = load_reports(resolved);
+    reports.retain(|existing| {
+        existing.dapp_id != report.dapp_id || existing.registry != report.registry
+    });
+    reports.push(report.clone());
+    save_reports(resolved, &reports)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{report_calldata, reportDappCall};
+    use alloy_primitives::U256;
+    use alloy_sol_types::SolCall;
+
+    #[test]
+    fn encodes_report_calldata() {
+        let calldata = report_calldata("7", "", "phishing").unwrap();
+        assert_eq!(&calldata[..4], reportDappCall::SELECTOR.as_slice());
+        let decoded = reportDappCall::abi_decode(&calldata).unwrap();
+        assert_eq!(decoded.dappId, U256::from(7));
+        assert_eq!(decoded.versionId, U256::ZERO);
+        assert_eq!(decoded.reason, "phishing");
+    }
+
+    #[test]
+    fn rejects_non_numeric_dapp_ids() {
+        assert!(report_calldata("not-a-number", "1", "x").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Runtime and HTTP client shared by every JSON-RPC call. Dapp passthrough
+//! requests run as tasks on a small fixed pool of worker threads instead of
+//! a thread each, so a slow node queues requests rather than piling up
+//! threads, and the event loop never waits on the network.
+
+use anyhow::{Context, Result, bail};
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Worker threads driving RPC tasks; the requests themselves are I/O bound.
+const WORKER_THREADS: usize = 2;
+/// Passthrough requests accepted at once, queued or in flight. Beyond this a
+/// request fails right away instead of waiting behind a stalled node.
+const MAX_QUEUED_REQUESTS: usize = 512;
+
+/// Lives for the whole process: a runtime may not be dropped from one of
+/// its own tasks, which is where the last `AppState` clone can end up.
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+fn runtime() -> Result<&'static Runtime> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(WORKER_THREADS)
+        .thread_name("vibefi-rpc")
+        .enable_all()
+        .build()
+        .context("failed to create rpc runtime")?;
+    // A runtime built by a racing caller is dropped here, outside any task.
+    let _ = RUNTIME.set(runtime);
+    Ok(RUNTIME.get().expect("rpc runtime set"))
+}
+
+#[derive(Clone)]
+pub struct RpcClient {
+    runtime: Handle,
+    http: reqwest::Client,
+    /// For dapp `vibefi_httpFetch` requests; never follows redirects.
+    fetch: reqwest::Client,
+    queue: Arc<Semaphore>,
+}
+
+impl RpcClient {
+    pub fn new() -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .build()
+            .context("failed to build rpc http client")?;
+        let fetch = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .context("failed to build dapp fetch client")?;
+        Ok(Self {
+            runtime: runtime()?.handle().clone(),
+            http,
+            fetch,
+            queue: Arc::new(Semaphore::new(MAX_QUEUED_REQUESTS)),
+        })
+    }
+
+    pub fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    pub fn fetch_http(&self) -> &reqwest::Client {
+        &self.fetch
+    }
+
+    /// Runs `future` to completion from a thread outside the runtime, for
+    /// callers that are blocking anyway (signer workers, background tasks).
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Runs `task` on the runtime with a queue slot, which it holds until it
+    /// finishes. Fails when the queue is full.
+    pub fn spawn<F, Fut>(&self, task: F) -> Result<()>
+    where
+        F: FnOnce(OwnedSemaphorePermit) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let Ok(permit) = Arc::clone(&self.queue).try_acquire_owned() else {
+            bail!("too many RPC requests are waiting; try again shortly");
+        };
+        self.runtime.spawn(task(permit));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_QUEUED_REQUESTS, RpcClient};
+    use std::sync::mpsc;
+
+    /// Fills the queue with requests that wait until the returned sender
+    /// releases them.
+    fn fill_queue(client: &RpcClient) -> tokio::sync::watch::Sender<bool> {
+        let (release_tx, release_rx) = tokio::sync::watch::channel(false);
+        for _ in 0..MAX_QUEUED_REQUESTS {
+            let mut release = release_rx.clone();
+            client
+                .spawn(move |permit| async move {
+                    let _ = release.wait_for(|released| *released).await;
+                    drop(permit);
+                })
+                .unwrap();
+        }
+        release_tx
+    }
+
+    #[test]
+    fn queue_is_bounded() {
+        let client = RpcClient::new().unwrap();
+        let _release = fill_queue(&client);
+        assert!(client.spawn(|_| async {}).is_err());
+    }
+
+    #[test]
+    fn finished_requests_free_their_slots() {
+        let client = RpcClient::new().unwrap();
+        let release_tx = fill_queue(&client);
+        release_tx.send(true).unwrap();
+        let (done_tx, done_rx) = mpsc::channel();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            let done_tx = done_tx.clone();
+            if client
+                .spawn(move |_| async move {
+                    let _ = done_tx.send(());
+                })
+                .is_ok()
+            {
+                break;
+            }
+            assert!(std::time::Instant::now() < deadline, "queue never drained");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        done_rx.recv().unwrap();
+    }
+}
@@ -2,16 +2,111 @@ use anyhow::{Result, anyhow, bail};
 use reqwest::blocking::Client as HttpClient;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::sync::{Arc, Condvar, Mutex};
-use std::time::Instant;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::rpc_client::RpcClient;
 
 pub const DEFAULT_MAX_CONCURRENT_RPC: usize = 10;
 
+/// How long an endpoint test waits for each reply before giving up.
+const ENDPOINT_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+const ENDPOINT_TEST_SAMPLES: usize = 3;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RpcEndpoint {
     pub url: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    /// Chain the endpoint was saved for. Endpoints saved before chains were
+    /// recorded have none and are used for every profile.
+    #[serde(default, rename = "chainId", skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+}
+
+/// What an endpoint reported when tested from the settings page.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointTestResult {
+    pub url: String,
+    /// Median round trip of `eth_blockNumber`, in milliseconds.
+    pub latency_ms: u64,
+    pub chain_id: u64,
+    pub expected_chain_id: u64,
+    pub chain_mismatch: bool,
+    pub block_number: u64,
+}
+
+/// Checks that `url` answers JSON-RPC, measures its latency and compares the
+/// chain it reports with the one the profile expects.
+pub fn test_endpoint(
+    http: &HttpClient,
+    url: &str,
+    expected_chain_id: u64,
+) -> Result<EndpointTestResult> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| anyhow!("Invalid RPC URL {url}: {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("RPC URL must use http or https");
+    }
+
+    let chain_id = parse_quantity(&test_call(http, url, "eth_chainId")?, "eth_chainId")?;
+    let mut samples = Vec::with_capacity(ENDPOINT_TEST_SAMPLES);
+    let mut block_number = 0;
+    for _ in 0..ENDPOINT_TEST_SAMPLES {
+        let started = Instant::now();
+        let result = test_call(http, url, "eth_blockNumber")?;
+        samples.push(started.elapsed());
+        block_number = parse_quantity(&result, "eth_blockNumber")?;
+    }
+    samples.sort();
+    let latency_ms = samples[samples.len() / 2].as_millis() as u64;
+
+    let result = EndpointTestResult {
+        url: url.to_string(),
+        latency_ms,
+        chain_id,
+        expected_chain_id,
+        chain_mismatch: chain_id != expected_chain_id,
+        block_number,
+    };
+    tracing::info!(
+        url,
+        latency_ms,
+        chain_id,
+        expected_chain_id,
+        block_number,
+        "tested rpc endpoint"
+    );
+    Ok(result)
+}
+
+fn test_call(http: &HttpClient, url: &str, method: &str) -> Result<Value> {
+    let payload = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": [] });
+    let response = http
+        .post(url)
+        .timeout(ENDPOINT_TEST_TIMEOUT)
+        .json(&payload)
+        .send()
+        .map_err(|e| anyhow!("Connection error to {url}: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("HTTP {status} from {url}");
+    }
+    let mut body: Value = response
+        .json()
+        .map_err(|e| anyhow!("{url} did not answer with JSON-RPC: {e}"))?;
+    if let Some(error) = body.get("error") {
+        bail!("{method} failed on {url}: {error}");
+    }
+    Ok(body["result"].take())
+}
+
+fn parse_quantity(value: &Value, method: &str) -> Result<u64> {
+    value
+        .as_str()
+        .and_then(|hex| u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok())
+        .ok_or_else(|| anyhow!("{method} returned an invalid quantity: {value}"))
 }
 
 struct EndpointHealth {
@@ -25,70 +120,96 @@ struct HealthState {
     active_index: usize,
 }
 
-struct SemaphoreState {
-    max: usize,
-    in_flight: usize,
-}
-
-struct Semaphore {
-    state: Mutex<SemaphoreState>,
-    condvar: Condvar,
+/// Caps the requests in flight across all callers. Lowering the cap takes
+/// effect as requests finish: their permits are retired instead of returned.
+struct Concurrency {
+    permits: tokio::sync::Semaphore,
+    max: Mutex<usize>,
+    /// Permits still to retire after the cap was lowered below `in_flight`.
+    owed: AtomicUsize,
 }
 
-impl Semaphore {
+impl Concurrency {
     fn new(max: usize) -> Self {
         Self {
-            state: Mutex::new(SemaphoreState { max, in_flight: 0 }),
-            condvar: Condvar::new(),
-        }
-    }
-
-    fn acquire(&self) {
-        let mut s = self.state.lock().expect("semaphore lock");
-        while s.in_flight >= s.max {
-            s = self.condvar.wait(s).expect("semaphore wait");
+            permits: tokio::sync::Semaphore::new(max),
+            max: Mutex::new(max),
+            owed: AtomicUsize::new(0),
         }
-        s.in_flight += 1;
     }
 
-    fn release(&self) {
-        let mut s = self.state.lock().expect("semaphore lock");
-        s.in_flight = s.in_flight.saturating_sub(1);
-        drop(s);
-        self.condvar.notify_one();
+    async fn acquire(&self) -> Result<ConcurrencyGuard<'_>> {
+        let permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| anyhow!("rpc manager closed"))?;
+        Ok(ConcurrencyGuard {
+            concurrency: self,
+            permit: Some(permit),
+        })
     }
 
     fn set_max(&self, max: usize) {
-        let mut s = self.state.lock().expect("semaphore lock");
-        s.max = max;
-        drop(s);
-        self.condvar.notify_all();
+        let mut current = self.max.lock().expect("rpc concurrency lock");
+        if max > *current {
+            let mut grow = max - *current;
+            let repaid = self
+                .owed
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |owed| {
+                    Some(owed.saturating_sub(grow))
+                })
+                .unwrap_or(0)
+                .min(grow);
+            grow -= repaid;
+            self.permits.add_permits(grow);
+        } else {
+            let shrink = *current - max;
+            let forgotten = self.permits.forget_permits(shrink);
+            self.owed.fetch_add(shrink - forgotten, Ordering::SeqCst);
+        }
+        *current = max;
     }
 
     fn get_max(&self) -> usize {
-        self.state.lock().expect("semaphore lock").max
+        *self.max.lock().expect("rpc concurrency lock")
     }
 }
 
-/// RAII guard that releases the semaphore on drop, ensuring release always fires
-/// even if `send_rpc` returns early via `?` or explicit `return`.
-struct SemaphoreGuard(Arc<Semaphore>);
+/// Holds a concurrency slot, released on drop even if `send` returns early
+/// via `?` or explicit `return`.
+struct ConcurrencyGuard<'a> {
+    concurrency: &'a Concurrency,
+    permit: Option<tokio::sync::SemaphorePermit<'a>>,
+}
 
-impl Drop for SemaphoreGuard {
+impl Drop for ConcurrencyGuard<'_> {
     fn drop(&mut self) {
-        self.0.release();
+        let Some(permit) = self.permit.take() else {
+            return;
+        };
+        let retire = self
+            .concurrency
+            .owed
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |owed| {
+                owed.checked_sub(1)
+            })
+            .is_ok();
+        if retire {
+            permit.forget();
+        }
     }
 }
 
 #[derive(Clone)]
 pub struct RpcEndpointManager {
     health: Arc<Mutex<HealthState>>,
-    semaphore: Arc<Semaphore>,
-    http: HttpClient,
+    concurrency: Arc<Concurrency>,
+    client: RpcClient,
 }
 
 impl RpcEndpointManager {
-    pub fn new(endpoints: Vec<RpcEndpoint>, http: HttpClient, max_concurrent: usize) -> Self {
+    pub fn new(endpoints: Vec<RpcEndpoint>, client: RpcClient, max_concurrent: usize) -> Self {
         let endpoints: Vec<EndpointHealth> = endpoints
             .into_iter()
             .map(|ep| EndpointHealth {
@@ -107,12 +228,17 @@ impl RpcEndpointManager {
                 endpoints,
                 active_index: 0,
             })),
-            semaphore: Arc::new(Semaphore::new(max_concurrent)),
-            http,
+            concurrency: Arc::new(Concurrency::new(max_concurrent)),
+            client,
         }
     }
 
+    /// Blocking form of [`Self::send`] for callers on their own threads.
     pub fn send_rpc(&self, payload: &Value) -> Result<Value> {
+        self.client.block_on(self.send(payload))
+    }
+
+    pub async fn send(&self, payload: &Value) -> Result<Value> {
         let method = payload
             .get("method")
             .and_then(Value::as_str)
@@ -137,8 +263,7 @@ impl RpcEndpointManager {
         );
 
         // Acquire a concurrency slot. Released automatically when _guard is dropped.
-        self.semaphore.acquire();
-        let _guard = SemaphoreGuard(Arc::clone(&self.semaphore));
+        let _guard = self.concurrency.acquire().await?;
 
         for attempt in 0..max_retries {
             // Lock briefly to pick an endpoint — released before the HTTP call.
@@ -163,8 +288,9 @@ impl RpcEndpointManager {
             );
 
             // HTTP call — no locks held.
-            match self.try_send(&url, payload) {
+            match self.try_send(&url, payload).await {
                 Ok(body) => {
+                    crate::connectivity::report(true);
                     if body.get("error").is_some() {
                         let rpc_error = body
                             .get("error")
@@ -232,6 +358,7 @@ impl RpcEndpointManager {
                 .unwrap_or_else(|| "unknown".to_string()),
             "all rpc endpoints failed"
         );
+        crate::connectivity::report(false);
         Err(last_error.unwrap_or_else(|| anyhow!("All RPC endpoints failed")))
     }
 
@@ -260,12 +387,12 @@ impl RpcEndpointManager {
     }
 
     pub fn get_max_concurrent(&self) -> usize {
-        self.semaphore.get_max()
+        self.concurrency.get_max()
     }
 
     pub fn set_max_concurrent(&self, max: usize) {
         let max = max.max(1);
-        self.semaphore.set_max(max);
+        self.concurrency.set_max(max);
         tracing::info!(max, "rpc max concurrent updated");
     }
 
@@ -298,8 +425,8 @@ impl RpcEndpointManager {
         }
     }
 
-    fn try_send(&self, url: &str, payload: &Value) -> Result<Value> {
-        let res = self.http.post(url).json(payload).send();
+    async fn try_send(&self, url: &str, payload: &Value) -> Result<Value> {
+        let res = self.client.http().post(url).json(payload).send().await;
         match res {
             Ok(response) => {
                 let status = response.status();
@@ -311,6 +438,7 @@ impl RpcEndpointManager {
                 }
                 let body: Value = response
                     .json()
+                    .await
                     .map_err(|e| anyhow!("Failed to decode RPC response from {}: {}", url, e))?;
                 Ok(body)
             }
@@ -320,3 +448,97 @@ impl RpcEndpointManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Concurrency, RpcEndpoint, RpcEndpointManager, test_endpoint};
+    use crate::rpc_client::RpcClient;
+    use crate::test_harness::{MockRpc, TEST_CHAIN_ID};
+    use serde_json::{Value, json};
+
+    fn call(manager: &RpcEndpointManager, method: &str, params: Value) -> Value {
+        manager
+            .send_rpc(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .unwrap()["result"]
+            .clone()
+    }
+
+    #[test]
+    fn fails_over_and_follows_a_transaction_to_its_receipt() {
+        let flaky = MockRpc::start();
+        let backup = MockRpc::start();
+        flaky.fail_next(1);
+        backup.set_pending_polls(1);
+        let endpoint = |rpc: &MockRpc| RpcEndpoint {
+            url: rpc.url.clone(),
+            label: None,
+            chain_id: None,
+        };
+        let manager = RpcEndpointManager::new(
+            vec![endpoint(&flaky), endpoint(&backup)],
+            RpcClient::new().unwrap(),
+            2,
+        );
+
+        let hash = call(&manager, "eth_sendRawTransaction", json!(["0x02f8"]));
+        assert!(flaky.calls().is_empty());
+        assert_eq!(
+            call(&manager, "eth_getTransactionReceipt", json!([hash])),
+            Value::Null
+        );
+        let receipt = call(&manager, "eth_getTransactionReceipt", json!([hash]));
+        assert_eq!(receipt["status"], "0x1");
+        assert_eq!(
+            backup.calls(),
+            [
+                "eth_sendRawTransaction",
+                "eth_getTransactionReceipt",
+                "eth_getTransactionReceipt"
+            ]
+        );
+    }
+
+    #[test]
+    fn lowering_the_cap_retires_permits_as_requests_finish() {
+        let concurrency = Concurrency::new(2);
+        RpcClient::new().unwrap().block_on(async {
+            let first = concurrency.acquire().await.unwrap();
+            let second = concurrency.acquire().await.unwrap();
+            concurrency.set_max(1);
+            drop(first);
+            assert_eq!(concurrency.permits.available_permits(), 0);
+            drop(second);
+            assert_eq!(concurrency.permits.available_permits(), 1);
+            concurrency.set_max(3);
+            assert_eq!(concurrency.permits.available_permits(), 3);
+        });
+    }
+
+    #[test]
+    fn endpoint_test_flags_a_chain_mismatch() {
+        let rpc = MockRpc::start();
+        rpc.set_block_number(0x20);
+        let http = reqwest::blocking::Client::new();
+
+        let result = test_endpoint(&http, &rpc.url, 1).unwrap();
+        assert_eq!(result.chain_id, TEST_CHAIN_ID);
+        assert_eq!(result.block_number, 0x20);
+        assert!(result.chain_mismatch);
+        assert_eq!(
+            rpc.calls(),
+            [
+                "eth_chainId",
+                "eth_blockNumber",
+                "eth_blockNumber",
+                "eth_blockNumber"
+            ]
+        );
+        assert!(
+            !test_endpoint(&http, &rpc.url, TEST_CHAIN_ID)
+                .unwrap()
+                .chain_mismatch
+        );
+
+        assert!(test_endpoint(&http, "ftp://example.org", 1).is_err());
+    }
+}
@@ -122,6 +122,47 @@ pub fn resolve_bun_binary() -> Result<String> {
     bail!("bun runtime not found. install bun or set VIBEFI_BUN_BIN to a working executable path")
 }
 
+/// Resolve the Foundry `anvil` binary that `--fork` runs.
+///
+/// Resolution order:
+/// 1. `VIBEFI_ANVIL_BIN` environment variable
+/// 2. PATH probe for a working `anvil` binary
+/// 3. Foundry's default install location (`~/.foundry/bin/anvil`)
+pub fn resolve_anvil_binary() -> Result<String> {
+    // 1. Explicit env override
+    if let Ok(bin) = env::var("VIBEFI_ANVIL_BIN") {
+        let trimmed = bin.trim();
+        if !trimmed.is_empty() {
+            if command_version_ok(Path::new(trimmed)) {
+                return Ok(trimmed.to_string());
+            }
+            bail!(
+                "VIBEFI_ANVIL_BIN is set to {:?} but `--version` failed",
+                trimmed
+            );
+        }
+    }
+
+    // 2. PATH
+    if let Some(anvil) = probe_working_path_binary("anvil") {
+        return Ok(anvil.to_string_lossy().into_owned());
+    }
+
+    // 3. foundryup's install directory, often missing from a GUI app's PATH
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"));
+    if let Some(home) = home {
+        let name = if cfg!(windows) { "anvil.exe" } else { "anvil" };
+        let installed = PathBuf::from(home).join(".foundry").join("bin").join(name);
+        if installed.is_file() && command_version_ok(&installed) {
+            return Ok(installed.to_string_lossy().into_owned());
+        }
+    }
+
+    bail!(
+        "anvil not found. install Foundry (https://getfoundry.sh) or set VIBEFI_ANVIL_BIN to a working executable path"
+    )
+}
+
 /// Resolve the Node/Bun runtime binary.
 ///
 /// Resolution order:
@@ -239,87 +280,68 @@ pub fn resolve_wc_helper_script() -> Result<PathBuf> {
     )
 }
 
-/// Resolve the IPFS helper script path.
+/// Resolve the Lattice helper script path.
 ///
 /// Resolution order:
-/// 1. `VIBEFI_IPFS_HELPER_SCRIPT` environment variable
-/// 2. Bundled script inside macOS app bundle (`Contents/Resources/ipfs-helper/index.mjs`)
-/// 3. Bundled script in Linux package layouts (`<prefix>/lib/<pkg>/ipfs-helper/index.mjs`)
+/// 1. `VIBEFI_LATTICE_HELPER_SCRIPT` environment variable
+/// 2. Bundled script inside macOS app bundle (`Contents/Resources/lattice-helper.mjs`)
+/// 3. Bundled script in Linux package layouts (`<prefix>/lib/<pkg>/lattice-helper.mjs`)
 /// 4. Source-tree fallback via `CARGO_MANIFEST_DIR` (dev mode)
-pub fn resolve_ipfs_helper_script() -> Result<PathBuf> {
+pub fn resolve_lattice_helper_script() -> Result<PathBuf> {
     // 1. Explicit env override
-    if let Ok(path) = env::var("VIBEFI_IPFS_HELPER_SCRIPT") {
+    if let Ok(path) = env::var("VIBEFI_LATTICE_HELPER_SCRIPT") {
         let trimmed = path.trim();
         if trimmed.is_empty() {
-            bail!("VIBEFI_IPFS_HELPER_SCRIPT is set but empty or whitespace");
+            bail!("VIBEFI_LATTICE_HELPER_SCRIPT is set but empty or whitespace");
         }
         let p = PathBuf::from(trimmed);
         if p.is_file() {
             return Ok(p);
         }
         bail!(
-            "VIBEFI_IPFS_HELPER_SCRIPT is set to {:?} but the file does not exist or is not a regular file",
+            "VIBEFI_LATTICE_HELPER_SCRIPT is set to {:?} but the file does not exist or is not a regular file",
             path
         );
     }
 
     // 2. Bundled script in app bundle (cargo-packager flattens file resources into Contents/Resources/)
     if let Some(contents) = macos_bundle_contents_dir() {
-        let bundled_file = contents.join("Resources").join("ipfs-helper.mjs");
-        if bundled_file.exists() {
-            return Ok(bundled_file);
-        }
-        let bundled_dir = contents
-            .join("Resources")
-            .join("ipfs-helper")
-            .join("index.mjs");
-        if bundled_dir.exists() {
-            return Ok(bundled_dir);
+        let bundled = contents.join("Resources").join("lattice-helper.mjs");
+        if bundled.exists() {
+            return Ok(bundled);
         }
     }
 
     // 3. Bundled script in Linux package layouts (deb/appimage)
     if let Some(prefix) = linux_install_prefix_dir() {
-        let bundled_file = prefix
-            .join("lib")
-            .join(env!("CARGO_PKG_NAME"))
-            .join("ipfs-helper.mjs");
-        if bundled_file.exists() {
-            return Ok(bundled_file);
-        }
-        let bundled_dir = prefix
+        let bundled = prefix
             .join("lib")
             .join(env!("CARGO_PKG_NAME"))
-            .join("ipfs-helper")
-            .join("index.mjs");
-        if bundled_dir.exists() {
-            return Ok(bundled_dir);
+            .join("lattice-helper.mjs");
+        if bundled.exists() {
+            return Ok(bundled);
         }
     }
 
     // 3b. Bundled resource next to exe on Windows (NSIS install)
     if let Some(dir) = windows_exe_dir() {
-        let bundled_file = dir.join("ipfs-helper.mjs");
-        if bundled_file.exists() {
-            return Ok(bundled_file);
-        }
-        let bundled_dir = dir.join("ipfs-helper").join("index.mjs");
-        if bundled_dir.exists() {
-            return Ok(bundled_dir);
+        let bundled = dir.join("lattice-helper.mjs");
+        if bundled.exists() {
+            return Ok(bundled);
         }
     }
 
     // 4. Dev fallback: source tree
     let dev_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("ipfs-helper")
+        .join("lattice-helper")
         .join("index.mjs");
     if dev_path.exists() {
         return Ok(dev_path);
     }
 
     bail!(
-        "ipfs helper script not found. \
-         set VIBEFI_IPFS_HELPER_SCRIPT or ensure the app bundle includes it"
+        "lattice helper script not found. \
+         set VIBEFI_LATTICE_HELPER_SCRIPT or ensure the app bundle includes it"
     )
 }
 
@@ -0,0 +1,199 @@
+//! Background refresh while the client sits idle: the registry listing,
+//! pinned dapps' latest versions, and the ENS and price caches. Off by
+//! default. A pass is skipped while offline, on a metered connection and,
+//! unless the user allows it, on battery, and is put off while dapps are
+//! busy with RPC or signing requests.
+
+use std::path::Path;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use crate::settings::{BackgroundRefreshSettings, load_settings};
+use crate::state::AppState;
+
+/// Shortest interval the settings may ask for.
+pub const MIN_INTERVAL_MINS: u64 = 5;
+/// How often a pass put off by activity or a missing connection is retried.
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+/// How often a disabled scheduler looks at the settings again, in case a
+/// change did not wake it.
+const DISABLED_POLL: Duration = Duration::from_secs(10 * 60);
+
+static WAKE: Mutex<bool> = Mutex::new(false);
+static WAKE_SIGNAL: Condvar = Condvar::new();
+
+/// Re-reads the schedule now, e.g. after the user changes it.
+pub fn reschedule() {
+    *WAKE.lock().expect("scheduler_wake") = true;
+    WAKE_SIGNAL.notify_one();
+}
+
+/// Returns whether the wait ended early because of [`reschedule`].
+fn wait_for_wake(timeout: Duration) -> bool {
+    let guard = WAKE.lock().expect("scheduler_wake");
+    let (mut woken, _) = WAKE_SIGNAL
+        .wait_timeout_while(guard, timeout, |woken| !*woken)
+        .expect("scheduler_wake");
+    std::mem::take(&mut *woken)
+}
+
+pub fn spawn_scheduler(state: AppState) {
+    let Some(config_path) = state.resolved.as_ref().and_then(|r| r.config_path.clone()) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        loop {
+            let settings = load_settings(&config_path).background_refresh;
+            if !settings.enabled {
+                wait_for_wake(DISABLED_POLL);
+                continue;
+            }
+            if wait_for_wake(interval(&settings)) {
+                continue;
+            }
+            // Put off until the client is idle and allowed to use the network.
+            loop {
+                match skip_reason(&state, &config_path) {
+                    None => {
+                        refresh(&state);
+                        break;
+                    }
+                    Some(reason) => {
+                        tracing::debug!(reason, "background refresh put off");
+                        if reason == "disabled" || wait_for_wake(RETRY_DELAY) {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn interval(settings: &BackgroundRefreshSettings) -> Duration {
+    Duration::from_secs(settings.interval_mins.max(MIN_INTERVAL_MINS) * 60)
+}
+
+/// Why a pass can't run right now, if anything. Settings are read again
+/// since they may have changed during the wait.
+fn skip_reason(state: &AppState, config_path: &Path) -> Option<&'static str> {
+    let settings = load_settings(config_path);
+    if !settings.background_refresh.enabled {
+        return Some("disabled");
+    }
+    if settings.metered_connection {
+        return Some("metered connection");
+    }
+    if settings.background_refresh.pause_on_battery && on_battery() {
+        return Some("on battery");
+    }
+    if !crate::connectivity::is_online() {
+        return Some("offline");
+    }
+    if !is_idle(state) {
+        return Some("dapps are busy");
+    }
+    None
+}
+
+/// No dapp is waiting on an RPC response or a signature.
+fn is_idle(state: &AppState) -> bool {
+    let no_rpc = state
+        .pending_rpc_counts
+        .lock()
+        .expect("pending_rpc_counts")
+        .values()
+        .all(|count| *count == 0);
+    no_rpc
+        && state
+            .pending_signature_counts
+            .lock()
+            .expect("pending_signature_counts")
+            .values()
+            .all(|count| *count == 0)
+}
+
+fn refresh(state: &AppState) {
+    tracing::info!("background refresh started");
+    match crate::registry::refresh_listing(state) {
+        Ok(count) => tracing::debug!(dapps = count, "registry listing refreshed"),
+        Err(err) => {
+            tracing::warn!(error = %format!("{err:#}"), "background registry refresh failed")
+        }
+    }
+    // The prefetch worker checks pinned dapps for new versions and fetches them.
+    crate::prefetch::request_prefetch();
+    crate::ens::refresh(state);
+    if let Some(resolved) = state.resolved.as_ref() {
+        crate::prices::refresh(&resolved.http_client);
+    }
+}
+
+/// Whether the machine runs on battery. Unknown counts as mains power.
+#[cfg(target_os = "linux")]
+fn on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let mut has_battery = false;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" | "USB" => {
+                let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return false;
+                }
+            }
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+    has_battery
+}
+
+#[cfg(target_os = "macos")]
+fn on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn on_battery() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MIN_INTERVAL_MINS, interval};
+    use crate::settings::BackgroundRefreshSettings;
+    use std::time::Duration;
+
+    fn defaults() -> BackgroundRefreshSettings {
+        serde_json::from_str("{}").unwrap()
+    }
+
+    #[test]
+    fn is_off_by_default() {
+        let settings = defaults();
+        assert!(!settings.enabled);
+        assert!(settings.pause_on_battery);
+        assert_eq!(interval(&settings), Duration::from_secs(30 * 60));
+    }
+
+    #[test]
+    fn clamps_short_intervals() {
+        let eager = BackgroundRefreshSettings {
+            interval_mins: 1,
+            ..defaults()
+        };
+        assert_eq!(
+            interval(&eager),
+            Duration::from_secs(MIN_INTERVAL_MINS * 60)
+        );
+    }
+}
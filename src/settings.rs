@@ -1,9 +1,12 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::config::IpfsFetchBackend;
+use crate::ledger_ble::BleDevice;
+use crate::policy::ApprovalRule;
 use crate::rpc_manager::RpcEndpoint;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -13,6 +16,129 @@ pub struct IpfsUserSettings {
     pub fetch_backend: Option<IpfsFetchBackend>,
     #[serde(default)]
     pub gateway_endpoint: Option<String>,
+    /// Bundle files downloaded at once; unset uses
+    /// [`crate::registry::DEFAULT_DOWNLOAD_CONCURRENCY`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_concurrency: Option<usize>,
+}
+
+/// Fiat values in transaction prompts. Off by default: pricing a transaction
+/// sends its chain and token address to the price service.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FiatPriceSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// CoinGecko-compatible API base; unset uses
+    /// [`crate::prices::DEFAULT_PRICE_ENDPOINT`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// Lowercase ISO 4217 code; unset uses [`crate::prices::DEFAULT_CURRENCY`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+/// Client lock. Set when a passphrase is; see [`crate::lock`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LockSettings {
+    /// Hex salt the passphrase key is derived with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
+    /// Hex check value proving a passphrase right without storing it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<String>,
+    /// Locks after this long without user activity. Unset never auto-locks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_lock_minutes: Option<u64>,
+}
+
+/// Tabs over these limits are flagged in the tab bar; see [`crate::tab_usage`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HeavyTabSettings {
+    pub enabled: bool,
+    /// JS heap size, where the webview reports it.
+    pub heap_mb: u64,
+    /// Share of a sample window the tab's main thread was busy.
+    pub cpu_percent: u8,
+}
+
+impl Default for HeavyTabSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            heap_mb: 512,
+            cpu_percent: 80,
+        }
+    }
+}
+
+/// Scheduled background refresh; see [`crate::scheduler`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackgroundRefreshSettings {
+    pub enabled: bool,
+    /// Clamped to [`crate::scheduler::MIN_INTERVAL_MINS`].
+    pub interval_mins: u64,
+    pub pause_on_battery: bool,
+}
+
+impl Default for BackgroundRefreshSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_mins: 30,
+            pause_on_battery: true,
+        }
+    }
+}
+
+/// A chain added by a dapp with `wallet_addEthereumChain`, kept in
+/// `networks.json` next to `settings.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomNetwork {
+    pub chain_id: u64,
+    pub name: String,
+    pub rpc_url: String,
+    pub currency_symbol: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+}
+
+/// A dapp pinned in the launcher. `root_cid` is the latest bundle seen for it,
+/// kept so the dapp can still be listed and launched offline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedDapp {
+    pub dapp_id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub root_cid: String,
+    /// Community registry the dapp was listed from; unset for the configured registry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+}
+
+/// A registry contract the user added next to the configured `dappRegistry`.
+/// Its dapps are listed with its label, and trust decisions about them use
+/// only its own publisher allowlist.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommunityRegistry {
+    /// Lowercase `0x`-prefixed contract address.
+    pub address: String,
+    pub chain_id: u64,
+    pub label: String,
+    /// Publishers this registry vouches for. Dapps from anyone else are listed
+    /// with a warning; an empty list warns about every dapp.
+    #[serde(default)]
+    pub trusted_publishers: Vec<String>,
+    /// First block to scan for registry events.
+    #[serde(default)]
+    pub deploy_block: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +150,96 @@ pub struct UserSettings {
     pub max_concurrent_rpc: Option<usize>,
     #[serde(default)]
     pub ipfs: IpfsUserSettings,
+    /// Background dapp tabs idle for this long drop their webview and are
+    /// rebuilt on activation. Unset disables suspension.
+    #[serde(default)]
+    pub suspend_inactive_tabs_after_secs: Option<u64>,
+    /// Pinned dapps have their latest bundle prefetched in the background.
+    #[serde(default)]
+    pub pinned_dapps: Vec<PinnedDapp>,
+    /// Bundle cache budget; background prefetch evicts least recently used
+    /// unpinned bundles to stay under it. Unset means unbounded.
+    #[serde(default)]
+    pub bundle_cache_max_mb: Option<u64>,
+    /// The user marked their connection as metered; background prefetch is skipped.
+    #[serde(default)]
+    pub metered_connection: bool,
+    /// Bytes of dapp icons the launcher downloads per listing, in KB. Unset
+    /// uses [`crate::registry_cache::DEFAULT_ICON_PREFETCH_KB`]; 0 turns icon
+    /// prefetch off.
+    #[serde(default)]
+    pub icon_prefetch_kb: Option<u64>,
+    /// Extra registries whose dapps are listed in the launcher.
+    #[serde(default)]
+    pub community_registries: Vec<CommunityRegistry>,
+    /// Prompt decisions the user asked to remember, keyed by
+    /// [`crate::prompt::PromptRequest::remember_key`].
+    #[serde(default)]
+    pub remembered_prompts: BTreeMap<String, bool>,
+    /// Site data limit per dapp storage partition. Unset uses
+    /// [`crate::storage::DEFAULT_STORAGE_QUOTA_MB`].
+    #[serde(default)]
+    pub storage_quota_mb: Option<u64>,
+    /// The Ledger last connected over Bluetooth, offered first in the wallet
+    /// selector. The pairing itself is kept by the operating system.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ledger_ble_device: Option<BleDevice>,
+    /// Wallet the selector connected last, reconnected at startup; see
+    /// `ipc::reconnect`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_wallet: Option<LastWallet>,
+    /// Calls `eth_createAccessList` while filling transactions; see
+    /// `ipc::rpc::fill_tx_request`.
+    #[serde(default)]
+    pub access_lists: bool,
+    /// Holds signed transactions for export instead of broadcasting them;
+    /// see [`crate::signed_export`].
+    #[serde(default)]
+    pub offline_signing: bool,
+    #[serde(default)]
+    pub fiat_prices: FiatPriceSettings,
+    /// Transaction prompts approved without asking on dev chains; see
+    /// [`crate::policy`].
+    #[serde(default)]
+    pub approval_rules: Vec<ApprovalRule>,
+    /// NFT indexer URL with `{account}` and `{chainId}` placeholders. Unset
+    /// scans recent Transfer logs instead; see `ipc::nft`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nft_indexer: Option<String>,
+    #[serde(default)]
+    pub lock: LockSettings,
+    #[serde(default)]
+    pub heavy_tab: HeavyTabSettings,
+    #[serde(default)]
+    pub background_refresh: BackgroundRefreshSettings,
+}
+
+/// How to reach the last connected wallet again without the selector.
+/// Private keys typed into the selector are never stored, so `Local` only
+/// covers the developer key from the network config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "backend",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum LastWallet {
+    Local,
+    /// A USB Ledger or Trezor, found again by probing.
+    Hardware,
+    /// The Ledger in [`UserSettings::ledger_ble_device`].
+    LedgerBle,
+    /// A Lattice1 this client is already paired with.
+    Lattice {
+        device_id: String,
+    },
+    /// The session the WalletConnect helper keeps in its storage.
+    WalletConnect,
+    /// Derived again once its owner, a local or hardware wallet, is back.
+    SmartAccount {
+        owner: Box<LastWallet>,
+    },
+    Remote,
 }
 
 impl Default for UserSettings {
@@ -32,10 +248,51 @@ impl Default for UserSettings {
             rpc_endpoints: Vec::new(),
             max_concurrent_rpc: None,
             ipfs: IpfsUserSettings::default(),
+            suspend_inactive_tabs_after_secs: None,
+            pinned_dapps: Vec::new(),
+            bundle_cache_max_mb: None,
+            metered_connection: false,
+            icon_prefetch_kb: None,
+            community_registries: Vec::new(),
+            remembered_prompts: BTreeMap::new(),
+            storage_quota_mb: None,
+            ledger_ble_device: None,
+            last_wallet: None,
+            access_lists: false,
+            offline_signing: false,
+            fiat_prices: FiatPriceSettings::default(),
+            approval_rules: Vec::new(),
+            nft_indexer: None,
+            lock: LockSettings::default(),
+            heavy_tab: HeavyTabSettings::default(),
+            background_refresh: BackgroundRefreshSettings::default(),
         }
     }
 }
 
+impl UserSettings {
+    /// Endpoints saved for `chain_id`, including ones saved before endpoints
+    /// recorded their chain.
+    pub fn rpc_endpoints_for(&self, chain_id: u64) -> Vec<RpcEndpoint> {
+        self.rpc_endpoints
+            .iter()
+            .filter(|ep| ep.chain_id.is_none_or(|id| id == chain_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Replaces the endpoints for `chain_id`, keeping other chains' lists.
+    pub fn set_rpc_endpoints_for(&mut self, chain_id: u64, endpoints: Vec<RpcEndpoint>) {
+        self.rpc_endpoints
+            .retain(|ep| ep.chain_id.is_some_and(|id| id != chain_id));
+        self.rpc_endpoints
+            .extend(endpoints.into_iter().map(|ep| RpcEndpoint {
+                chain_id: Some(chain_id),
+                ..ep
+            }));
+    }
+}
+
 pub fn settings_path_from_config(config_path: &Path) -> PathBuf {
     config_path.with_file_name("settings.json")
 }
@@ -74,3 +331,33 @@ pub fn save_settings(config_path: &Path, settings: &UserSettings) -> Result<()>
     fs::write(&path, json).context("write settings.json")?;
     Ok(())
 }
+
+pub fn networks_path_from_config(config_path: &Path) -> PathBuf {
+    config_path.with_file_name("networks.json")
+}
+
+pub fn load_networks(config_path: &Path) -> Vec<CustomNetwork> {
+    let path = networks_path_from_config(config_path);
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            tracing::warn!(path = %path.display(), error = %err, "failed to read networks.json");
+            return Vec::new();
+        }
+    };
+    serde_json::from_str(&raw).unwrap_or_else(|err| {
+        tracing::warn!(path = %path.display(), error = %err, "failed to parse networks.json");
+        Vec::new()
+    })
+}
+
+/// Stores `network`, replacing an earlier entry for the same chain.
+pub fn save_network(config_path: &Path, network: CustomNetwork) -> Result<()> {
+    let mut networks = load_networks(config_path);
+    networks.retain(|existing| existing.chain_id != network.chain_id);
+    networks.push(network);
+    let json = serde_json::to_string_pretty(&networks).context("serialize networks")?;
+    fs::write(networks_path_from_config(config_path), json).context("write networks.json")?;
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tao::window::Window;
+
+use crate::state::{AppState, DappIdentity};
+use crate::webview_manager::WebViewManager;
+use crate::window_state::WindowState;
+
+/// How long a clean WalletConnect disconnect may hold up the exit.
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const SESSION_FILE: &str = "session.json";
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+/// Build tools (`bun install`, `vite build`) currently running, by pid.
+static CHILDREN: Mutex<Vec<(u32, &'static str)>> = Mutex::new(Vec::new());
+
+/// Dapp tabs and window geometry when the client last exited, kept next to
+/// `settings.json`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionState {
+    saved_at: u64,
+    #[serde(default)]
+    tabs: Vec<DappIdentity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active_tab: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    window: Option<WindowState>,
+}
+
+fn load_session(config_path: &Path) -> Option<SessionState> {
+    let raw = std::fs::read(config_path.with_file_name(SESSION_FILE)).ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+/// Dapp tabs that were open when the client last exited.
+pub fn last_session_tabs(config_path: &Path) -> Vec<DappIdentity> {
+    load_session(config_path)
+        .map(|session| session.tabs)
+        .unwrap_or_default()
+}
+
+/// Main window geometry when the client last exited.
+pub fn last_window_state(config_path: &Path) -> Option<WindowState> {
+    load_session(config_path)?.window
+}
+
+/// Runs `command` to completion like [`Command::output`], registered so that
+/// shutdown stops it instead of leaving it orphaned.
+pub fn output_tracked(command: &mut Command, label: &'static str) -> io::Result<Output> {
+    if SHUTTING_DOWN.load(Ordering::SeqCst) {
+        return Err(io::Error::other("client is shutting down"));
+    }
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let pid = child.id();
+    CHILDREN.lock().expect("children").push((pid, label));
+    let output = child.wait_with_output();
+    CHILDREN
+        .lock()
+        .expect("children")
+        .retain(|(tracked, _)| *tracked != pid);
+    output
+}
+
+/// Orderly exit: saves the open dapp tabs and window geometry, stops build tools, disconnects
+/// WalletConnect and releases helper processes. Runs once; later calls (the
+/// event loop is torn down after a close request) return immediately.
+pub fn run(state: &AppState, manager: &WebViewManager, window: Option<&Window>) {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tracing::info!("shutting down");
+    if let Err(err) = save_session(state, manager, window) {
+        tracing::warn!(error = %format!("{err:#}"), "failed to save session");
+    }
+    stop_children();
+    disconnect_walletconnect(state);
+    // Dropping the bridge kills the Lattice helper process.
+    drop(
+        state
+            .hardware_signer
+            .lock()
+            .expect("hardware_signer")
+            .take(),
+    );
+    crate::fork::stop();
+    crate::logging::shutdown_otlp_export();
+}
+
+fn save_session(state: &AppState, manager: &WebViewManager, window: Option<&Window>) -> Result<()> {
+    let Some(config_path) = state.resolved.as_ref().and_then(|r| r.config_path.as_ref()) else {
+        return Ok(());
+    };
+    let mut tabs = Vec::new();
+    let mut active_tab = None;
+    for (index, entry) in manager.apps.iter().enumerate() {
+        let Some(identity) = entry.identity.as_ref().filter(|id| id.root_cid.is_some()) else {
+            continue;
+        };
+        if manager.active_app_index == Some(index) {
+            active_tab = Some(tabs.len());
+        }
+        tabs.push(identity.clone());
+    }
+    let previous_window = last_window_state(config_path);
+    let window = match window {
+        Some(window) => crate::window_state::capture(window, previous_window.as_ref()),
+        None => previous_window,
+    };
+    let session = SessionState {
+        saved_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        tabs,
+        active_tab,
+        window,
+    };
+    let path = config_path.with_file_name(SESSION_FILE);
+    std::fs::write(&path, serde_json::to_vec_pretty(&session)?)
+        .with_context(|| format!("write {}", path.display()))?;
+    tracing::debug!(path = %path.display(), tabs = session.tabs.len(), "session saved");
+    Ok(())
+}
+
+fn stop_children() {
+    let children = std::mem::take(&mut *CHILDREN.lock().expect("children"));
+    for (pid, label) in children {
+        tracing::info!(pid, label, "stopping child process");
+        if let Err(err) = kill(pid) {
+            tracing::warn!(pid, label, error = %format!("{err:#}"), "failed to stop child process");
+        }
+    }
+}
+
+fn kill(pid: u32) -> Result<()> {
+    let pid = pid.to_string();
+    #[cfg(target_os = "windows")]
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid, "/T", "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    #[cfg(not(target_os = "windows"))]
+    let status = Command::new("kill")
+        .args(["-TERM", &pid])
+        .stderr(Stdio::null())
+        .status();
+    let status = status.context("failed to run kill")?;
+    if !status.success() {
+        anyhow::bail!("kill exited with {status}");
+    }
+    Ok(())
+}
+
+/// Ends the WalletConnect session so the wallet does not keep showing the
+/// client as connected. A helper that does not answer in time is left to
+/// exit with the client.
+fn disconnect_walletconnect(state: &AppState) {
+    let Some(bridge) = state.walletconnect.lock().expect("walletconnect").take() else {
+        return;
+    };
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let result = bridge
+            .lock()
+            .map_err(|_| anyhow::anyhow!("poisoned walletconnect bridge"))
+            .and_then(|mut bridge| bridge.disconnect());
+        let _ = done_tx.send(result);
+        // Dropping the last handle kills the helper.
+    });
+    match done_rx.recv_timeout(DISCONNECT_TIMEOUT) {
+        Ok(Ok(())) => tracing::info!("walletconnect session disconnected"),
+        Ok(Err(err)) => {
+            tracing::warn!(error = %format!("{err:#}"), "walletconnect disconnect failed")
+        }
+        Err(_) => tracing::warn!("walletconnect disconnect timed out"),
+    }
+}
@@ -0,0 +1,474 @@
+//! Offline signing. With `offlineSigning` on, transactions are signed but
+//! not broadcast: they are held in `signed-transactions.json` next to
+//! `settings.json` until exported to a file, which an air-gapped machine
+//! hands to one that is online or which goes to a private relay. The dapp
+//! gets a `TRANSACTION_HELD` error rather than a hash, since nothing was
+//! sent. Importing such a file broadcasts it through the configured RPC.
+//! Only the raw transactions in a file are trusted; sender, hash and nonce
+//! are decoded from them again.
+
+use alloy_consensus::transaction::SignerRecoverable;
+use alloy_consensus::{Transaction, TxEnvelope};
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::U256;
+use alloy_primitives::utils::format_ether;
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::state::AppState;
+
+const HELD_FILE: &str = "signed-transactions.json";
+/// Version of the JSON export; files without one are read as version 1.
+const FORMAT_VERSION: u32 = 1;
+/// Held transactions kept before the oldest are dropped.
+const MAX_HELD: usize = 256;
+
+/// Serializes read-modify-write cycles of the held file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A signed transaction and what it decodes to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedTx {
+    pub hash: String,
+    /// `None` for legacy transactions signed without EIP-155 replay protection.
+    pub chain_id: Option<u64>,
+    pub from: String,
+    /// `None` for contract creations.
+    pub to: Option<String>,
+    pub nonce: u64,
+    /// In wei.
+    pub value: String,
+    pub raw: String,
+    /// Unix time it was signed; 0 when imported.
+    #[serde(default)]
+    pub signed_at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedBundle {
+    #[serde(default = "format_version")]
+    version: u32,
+    transactions: Vec<SignedTx>,
+}
+
+fn format_version() -> u32 {
+    FORMAT_VERSION
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// [`SignedBundle`] with the decoded fields.
+    Json,
+    /// One `0x` raw transaction per line, as `eth_sendRawTransaction` takes it.
+    Raw,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "json" => Ok(Self::Json),
+            "raw" => Ok(Self::Raw),
+            other => bail!("unknown export format: {other}"),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Raw => "txt",
+        }
+    }
+}
+
+/// Whether transactions are signed without being broadcast.
+pub fn offline_signing(state: &AppState) -> bool {
+    state
+        .resolved
+        .as_ref()
+        .and_then(|resolved| resolved.config_path.as_deref())
+        .is_some_and(|path| crate::settings::load_settings(path).offline_signing)
+}
+
+/// Decodes a raw signed transaction and recovers its sender.
+pub fn describe(raw: &str) -> Result<SignedTx> {
+    let raw = raw.trim();
+    let bytes = hex::decode(raw.strip_prefix("0x").unwrap_or(raw))
+        .context("signed transaction is not hex")?;
+    let mut buf = bytes.as_slice();
+    let envelope = TxEnvelope::decode_2718(&mut buf)
+        .map_err(|err| anyhow!("not a signed transaction: {err}"))?;
+    if !buf.is_empty() {
+        bail!("unexpected bytes after the signed transaction");
+    }
+    let from = envelope
+        .recover_signer()
+        .map_err(|err| anyhow!("invalid transaction signature: {err}"))?;
+    Ok(SignedTx {
+        hash: format!("{:#x}", envelope.tx_hash()),
+        chain_id: envelope.chain_id(),
+        from: format!("{from:#x}"),
+        to: envelope.to().map(|to| format!("{to:#x}")),
+        nonce: envelope.nonce(),
+        value: envelope.value().to_string(),
+        raw: format!("0x{}", hex::encode(&bytes)),
+        signed_at: 0,
+    })
+}
+
+/// Holds `raw` for export instead of broadcasting it.
+pub fn hold(state: &AppState, raw: &str) -> Result<SignedTx> {
+    hold_in(&held_path(state)?, raw)
+}
+
+fn hold_in(path: &Path, raw: &str) -> Result<SignedTx> {
+    let mut tx = describe(raw)?;
+    tx.signed_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let _guard = FILE_LOCK
+        .lock()
+        .expect("poisoned signed transactions lock while holding a transaction");
+    let mut held = read_held(path)?;
+    held.retain(|existing| existing.hash != tx.hash);
+    held.push(tx.clone());
+    if held.len() > MAX_HELD {
+        let excess = held.len() - MAX_HELD;
+        held.drain(..excess);
+    }
+    write_held(path, &held)?;
+    tracing::info!(
+        target: "vibefi::audit",
+        hash = %tx.hash,
+        from = %tx.from,
+        to = ?tx.to,
+        nonce = tx.nonce,
+        chain_id = ?tx.chain_id,
+        "transaction signed and held for export"
+    );
+    Ok(tx)
+}
+
+/// Transactions waiting for export, oldest first.
+pub fn held(state: &AppState) -> Result<Vec<SignedTx>> {
+    let path = held_path(state)?;
+    let _guard = FILE_LOCK
+        .lock()
+        .expect("poisoned signed transactions lock while listing held transactions");
+    read_held(&path)
+}
+
+/// Drops the held transactions with these hashes.
+pub fn discard(state: &AppState, hashes: &[String]) -> Result<()> {
+    discard_in(&held_path(state)?, hashes)
+}
+
+fn discard_in(path: &Path, hashes: &[String]) -> Result<()> {
+    let _guard = FILE_LOCK
+        .lock()
+        .expect("poisoned signed transactions lock while discarding held transactions");
+    let mut held = read_held(path)?;
+    let before = held.len();
+    held.retain(|tx| !hashes.contains(&tx.hash));
+    if held.len() != before {
+        write_held(path, &held)?;
+    }
+    Ok(())
+}
+
+fn held_path(state: &AppState) -> Result<PathBuf> {
+    state
+        .resolved
+        .as_ref()
+        .and_then(|resolved| resolved.config_path.as_deref())
+        .map(|config_path| config_path.with_file_name(HELD_FILE))
+        .ok_or_else(|| anyhow!("No config loaded"))
+}
+
+/// An unreadable file is an error rather than an empty list, so holding the
+/// next transaction can't overwrite signed ones.
+fn read_held(path: &Path) -> Result<Vec<SignedTx>> {
+    match fs::read(path) {
+        Ok(bytes) => {
+            serde_json::from_slice(&bytes).with_context(|| format!("parse {}", path.display()))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err).with_context(|| format!("read {}", path.display())),
+    }
+}
+
+/// Writes through a sibling file and a rename, so a crash never leaves a
+/// half-written list.
+fn write_held(path: &Path, txs: &[SignedTx]) -> Result<()> {
+    let partial = path.with_extension("json.part");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&partial)
+        .with_context(|| format!("create {}", partial.display()))?;
+    file.write_all(&serde_json::to_vec_pretty(txs)?)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&partial, path).with_context(|| format!("write {}", path.display()))
+}
+
+pub fn export(txs: &[SignedTx], format: ExportFormat) -> Result<String> {
+    Ok(match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&SignedBundle {
+            version: FORMAT_VERSION,
+            transactions: txs.to_vec(),
+        })?,
+        ExportFormat::Raw => txs.iter().map(|tx| format!("{}\n", tx.raw)).collect(),
+    })
+}
+
+/// Reads an export in either format, or a JSON array of raw transactions.
+pub fn parse_import(text: &str) -> Result<Vec<SignedTx>> {
+    let text = text.trim();
+    let raws: Vec<String> = if text.starts_with('{') {
+        let bundle: SignedBundle = serde_json::from_str(text).context("invalid export file")?;
+        if bundle.version > FORMAT_VERSION {
+            bail!("export file version {} is not supported", bundle.version);
+        }
+        bundle.transactions.into_iter().map(|tx| tx.raw).collect()
+    } else if text.starts_with('[') {
+        serde_json::from_str(text).context("invalid list of raw transactions")?
+    } else {
+        text.split_whitespace().map(str::to_string).collect()
+    };
+    if raws.is_empty() {
+        bail!("the file holds no transactions");
+    }
+    raws.iter()
+        .enumerate()
+        .map(|(index, raw)| describe(raw).with_context(|| format!("transaction {}", index + 1)))
+        .collect()
+}
+
+/// Broadcasts `txs` once the user confirms, each sender's in nonce order.
+/// Transactions for another chain than the active one are refused before
+/// anything is sent. Returns the outcome of each.
+pub fn broadcast(state: &AppState, mut txs: Vec<SignedTx>) -> Result<Value> {
+    let chain_id = state
+        .wallet
+        .lock()
+        .expect("poisoned wallet lock while reading the active chain")
+        .chain
+        .chain_id;
+    if let Some(other) = txs
+        .iter()
+        .find(|tx| tx.chain_id.is_some_and(|id| id != chain_id))
+    {
+        bail!(
+            "transaction {} is for chain {}, but the client is on chain {chain_id}",
+            other.hash,
+            other.chain_id.unwrap_or_default()
+        );
+    }
+    txs.sort_by(|a, b| (&a.from, a.nonce).cmp(&(&b.from, b.nonce)));
+    let total: U256 = txs
+        .iter()
+        .map(|tx| tx.value.parse::<U256>().unwrap_or_default())
+        .fold(U256::ZERO, |sum, value| sum.saturating_add(value));
+    let request = crate::prompt::PromptRequest::new(
+        "broadcastSigned",
+        "Broadcast signed transactions?",
+        format!(
+            "{} signed {} will be sent to chain {chain_id} as they are.",
+            txs.len(),
+            if txs.len() == 1 {
+                "transaction"
+            } else {
+                "transactions"
+            }
+        ),
+    )
+    .with_details(json!({
+        "transactions": txs.len(),
+        "senders": txs
+            .iter()
+            .map(|tx| tx.from.as_str())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+            .join(", "),
+        "totalValue": format!("{} ETH", format_ether(total)),
+    }));
+    if !crate::prompt::ask(state, request) {
+        bail!("User rejected the request");
+    }
+
+    let mut results = Vec::with_capacity(txs.len());
+    for tx in txs {
+        let sent = crate::ipc::rpc_request(state, "eth_sendRawTransaction", json!([tx.raw]));
+        tracing::info!(
+            target: "vibefi::audit",
+            hash = %tx.hash,
+            from = %tx.from,
+            nonce = tx.nonce,
+            ok = sent.is_ok(),
+            "imported signed transaction broadcast"
+        );
+        match sent {
+            Ok(_) => {
+                crate::tx_history::record(state, None, &tx.hash);
+                if let Err(err) = discard(state, std::slice::from_ref(&tx.hash)) {
+                    tracing::warn!(hash = %tx.hash, error = %format!("{err:#}"), "failed to drop broadcast transaction from the held list");
+                }
+                results.push(json!({ "hash": tx.hash, "sent": true }));
+            }
+            Err(err) => results.push(json!({
+                "hash": tx.hash,
+                "sent": false,
+                "error": format!("{err:#}"),
+            })),
+        }
+    }
+    Ok(Value::Array(results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ExportFormat, SignedTx, describe, discard_in, export, hold_in, parse_import, read_held,
+    };
+    use alloy_consensus::{SignableTransaction, TxEip1559, TxEnvelope};
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_network::TxSignerSync;
+    use alloy_primitives::{Address, TxKind, U256};
+    use alloy_signer_local::PrivateKeySigner;
+    use std::path::{Path, PathBuf};
+
+    fn signed(nonce: u64) -> String {
+        let signer: PrivateKeySigner =
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
+        let mut tx = TxEip1559 {
+            chain_id: 11155111,
+            nonce,
+            gas_limit: 21_000,
+            max_fee_per_gas: 2_000_000_000,
+            max_priority_fee_per_gas: 1_000_000_000,
+            to: TxKind::Call(Address::repeat_byte(0x11)),
+            value: U256::from(5),
+            ..Default::default()
+        };
+        let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+        let envelope = TxEnvelope::from(tx.into_signed(signature));
+        format!("0x{}", hex::encode(envelope.encoded_2718()))
+    }
+
+    #[test]
+    fn describes_transactions_from_the_raw_bytes() {
+        let first = describe(&signed(7)).unwrap();
+        assert_eq!(first.chain_id, Some(11155111));
+        assert_eq!(first.nonce, 7);
+        assert_eq!(first.value, "5");
+        assert_eq!(first.from, "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23");
+        assert_eq!(
+            first.to.as_deref(),
+            Some("0x1111111111111111111111111111111111111111")
+        );
+    }
+
+    fn two_txs() -> Vec<SignedTx> {
+        vec![describe(&signed(7)).unwrap(), describe(&signed(8)).unwrap()]
+    }
+
+    #[test]
+    fn json_exports_round_trip() {
+        let txs = two_txs();
+        let json = export(&txs, ExportFormat::Json).unwrap();
+        assert_eq!(parse_import(&json).unwrap(), txs);
+    }
+
+    #[test]
+    fn raw_exports_round_trip() {
+        let txs = two_txs();
+        let raw = export(&txs, ExportFormat::Raw).unwrap();
+        assert_eq!(parse_import(&raw).unwrap(), txs);
+    }
+
+    #[test]
+    fn imports_a_json_list_of_raw_transactions() {
+        let txs = two_txs();
+        let list = serde_json::to_string(&[&txs[0].raw]).unwrap();
+        assert_eq!(parse_import(&list).unwrap()[0].hash, txs[0].hash);
+    }
+
+    #[test]
+    fn imports_ignore_metadata_in_the_file() {
+        let json = export(&two_txs(), ExportFormat::Json).unwrap();
+        let forged = json.replace("\"nonce\": 7", "\"nonce\": 70");
+        assert_eq!(parse_import(&forged).unwrap()[0].nonce, 7);
+    }
+
+    #[test]
+    fn imports_refuse_empty_or_damaged_transactions() {
+        assert!(parse_import("").is_err());
+        assert!(parse_import("0x02f8").is_err());
+        assert!(parse_import(&format!("{}00", signed(7))).is_err());
+    }
+
+    fn held_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("vibefi-held-txs-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("signed-transactions.json")
+    }
+
+    fn held_hashes(path: &Path) -> Vec<String> {
+        read_held(path)
+            .unwrap()
+            .into_iter()
+            .map(|tx| tx.hash)
+            .collect()
+    }
+
+    fn remove(path: &Path) {
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn held_transactions_are_listed_newest_first() {
+        let path = held_path("list");
+        let first = hold_in(&path, &signed(1)).unwrap();
+        assert!(first.signed_at > 0);
+        let second = hold_in(&path, &signed(2)).unwrap();
+        // Holding the same transaction again doesn't list it twice.
+        hold_in(&path, &signed(1)).unwrap();
+        assert_eq!(held_hashes(&path), [second.hash, first.hash]);
+        remove(&path);
+    }
+
+    #[test]
+    fn discarded_transactions_are_no_longer_held() {
+        let path = held_path("discard");
+        let first = hold_in(&path, &signed(1)).unwrap();
+        let second = hold_in(&path, &signed(2)).unwrap();
+        discard_in(&path, std::slice::from_ref(&second.hash)).unwrap();
+        assert_eq!(held_hashes(&path), [first.hash]);
+        remove(&path);
+    }
+
+    #[test]
+    fn a_damaged_file_is_reported_instead_of_replaced() {
+        let path = held_path("damaged");
+        std::fs::write(&path, "{").unwrap();
+        assert!(hold_in(&path, &signed(3)).is_err());
+        remove(&path);
+    }
+}
@@ -0,0 +1,626 @@
+use alloy_primitives::{Address, B256, Bytes, TxKind, U256, keccak256};
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_signer::SignerSync;
+use alloy_sol_types::{SolCall, SolValue, sol};
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+use crate::config::SmartAccountSettings;
+use crate::ipc::rpc_request;
+use crate::state::AppState;
+
+sol! {
+    // SimpleAccountFactory
+    function getAddress(address owner, uint256 salt) external view returns (address);
+    function createAccount(address owner, uint256 salt) external returns (address);
+    // SimpleAccount
+    function execute(address dest, uint256 value, bytes func) external;
+    // EntryPoint
+    function getNonce(address sender, uint192 key) external view returns (uint256);
+}
+
+/// Well-formed ECDSA signature used while the bundler and paymaster estimate
+/// gas; the real signature covers the gas limits they return.
+const DUMMY_SIGNATURE: &str = "0xfffffffffffffffffffffffffffffff0000000000000000000000000000000007aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1c";
+const BUNDLER_TIMEOUT: Duration = Duration::from_secs(30);
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Key that owns the smart account and signs its UserOperations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OwnerKey {
+    Local,
+    Hardware,
+}
+
+/// A smart account derived from the owner key through the configured factory.
+/// The account may not be deployed yet; its first UserOperation deploys it.
+#[derive(Debug, Clone)]
+pub struct SmartAccount {
+    pub address: Address,
+    pub owner: Address,
+    pub owner_key: OwnerKey,
+}
+
+/// EntryPoint v0.7 UserOperation in its JSON-RPC form.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factory: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factory_data: Option<Bytes>,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster_data: Option<Bytes>,
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    fn init_code(&self) -> Vec<u8> {
+        match self.factory {
+            Some(factory) => [
+                factory.as_slice(),
+                self.factory_data.as_deref().unwrap_or_default(),
+            ]
+            .concat(),
+            None => Vec::new(),
+        }
+    }
+
+    fn paymaster_and_data(&self) -> Vec<u8> {
+        let Some(paymaster) = self.paymaster else {
+            return Vec::new();
+        };
+        let gas = |limit: Option<U256>| limit.unwrap_or_default().saturating_to::<u128>();
+        [
+            paymaster.as_slice(),
+            gas(self.paymaster_verification_gas_limit)
+                .to_be_bytes()
+                .as_slice(),
+            gas(self.paymaster_post_op_gas_limit)
+                .to_be_bytes()
+                .as_slice(),
+            self.paymaster_data.as_deref().unwrap_or_default(),
+        ]
+        .concat()
+    }
+
+    /// Hash the account's owner signs, as computed by `EntryPoint.getUserOpHash`.
+    pub fn hash(&self, entry_point: Address, chain_id: u64) -> B256 {
+        let packed = (
+            self.sender,
+            self.nonce,
+            keccak256(self.init_code()),
+            keccak256(&self.call_data),
+            pack_u128_pair(self.verification_gas_limit, self.call_gas_limit),
+            self.pre_verification_gas,
+            pack_u128_pair(self.max_priority_fee_per_gas, self.max_fee_per_gas),
+            keccak256(self.paymaster_and_data()),
+        )
+            .abi_encode();
+        keccak256((keccak256(packed), entry_point, U256::from(chain_id)).abi_encode())
+    }
+}
+
+/// Packs two 128-bit values into one word, `high` first, as v0.7 stores gas
+/// limits and fees.
+fn pack_u128_pair(high: U256, low: U256) -> B256 {
+    let high = U256::from(high.saturating_to::<u128>());
+    let low = U256::from(low.saturating_to::<u128>());
+    B256::from(((high << 128) | low).to_be_bytes::<32>())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GasEstimate {
+    pre_verification_gas: U256,
+    verification_gas_limit: U256,
+    call_gas_limit: U256,
+    #[serde(default)]
+    paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default)]
+    paymaster_post_op_gas_limit: Option<U256>,
+}
+
+/// Result of ERC-7677 `pm_getPaymasterStubData` / `pm_getPaymasterData`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PaymasterFields {
+    #[serde(default)]
+    paymaster: Option<Address>,
+    #[serde(default)]
+    paymaster_data: Option<Bytes>,
+    #[serde(default)]
+    paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default)]
+    paymaster_post_op_gas_limit: Option<U256>,
+    #[serde(default)]
+    is_final: bool,
+}
+
+impl PaymasterFields {
+    fn apply(self, op: &mut UserOperation) {
+        op.paymaster = self.paymaster;
+        op.paymaster_data = self.paymaster_data;
+        if self.paymaster_verification_gas_limit.is_some() {
+            op.paymaster_verification_gas_limit = self.paymaster_verification_gas_limit;
+        }
+        if self.paymaster_post_op_gas_limit.is_some() {
+            op.paymaster_post_op_gas_limit = self.paymaster_post_op_gas_limit;
+        }
+    }
+}
+
+/// JSON-RPC endpoint outside the node's RPC pool: a bundler or a paymaster.
+struct Endpoint<'a> {
+    client: &'a reqwest::blocking::Client,
+    url: &'a str,
+    label: &'static str,
+}
+
+impl Endpoint<'_> {
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = self
+            .client
+            .post(self.url)
+            .timeout(BUNDLER_TIMEOUT)
+            .json(&body)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .with_context(|| format!("{} request {method} failed", self.label))?;
+        if let Some(error) = response.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string());
+            bail!("{} rejected {method}: {message}", self.label);
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
+
+fn settings(state: &AppState) -> Result<&SmartAccountSettings> {
+    state
+        .resolved
+        .as_ref()
+        .and_then(|resolved| resolved.smart_account.as_ref())
+        .ok_or_else(|| anyhow!("Smart accounts require smartAccount.bundlerUrl in config"))
+}
+
+fn parse_contract(value: &str, field: &str) -> Result<Address> {
+    value
+        .parse()
+        .with_context(|| format!("invalid smartAccount.{field} address: {value}"))
+}
+
+/// The key a new smart account would be owned by: the connected hardware
+/// wallet, else the local signer.
+pub fn available_owner(state: &AppState) -> Option<(OwnerKey, Address)> {
+    let hardware = state
+        .hardware_signer
+        .lock()
+        .expect("hardware_signer")
+        .as_ref()
+        .map(crate::hardware::get_address);
+    if let Some(address) = hardware.and_then(|address| address.parse().ok()) {
+        return Some((OwnerKey::Hardware, address));
+    }
+    state
+        .local_signer()
+        .map(|signer| (OwnerKey::Local, signer.address()))
+}
+
+/// Derives the counterfactual address of the account owned by the available
+/// owner key. Nothing is deployed until the first UserOperation.
+pub fn connect(state: &AppState) -> Result<SmartAccount> {
+    let settings = settings(state)?;
+    let (owner_key, owner) = available_owner(state).ok_or_else(|| {
+        anyhow!(
+            "Connect a hardware wallet or local signer first; it becomes the smart account owner"
+        )
+    })?;
+    let factory = parse_contract(&settings.factory, "factory")?;
+    let call = getAddressCall {
+        owner,
+        salt: U256::from(settings.salt),
+    };
+    let returned = eth_call(state, factory, call.abi_encode())?;
+    let address = getAddressCall::abi_decode_returns(&returned)
+        .context("account factory returned an invalid address")?;
+    tracing::info!(
+        account = format!("{:#x}", address),
+        owner = format!("{:#x}", owner),
+        ?owner_key,
+        "smart account derived"
+    );
+    Ok(SmartAccount {
+        address,
+        owner,
+        owner_key,
+    })
+}
+
+fn eth_call(state: &AppState, to: Address, data: Vec<u8>) -> Result<Vec<u8>> {
+    let result = rpc_request(
+        state,
+        "eth_call",
+        json!([{ "to": format!("{:#x}", to), "data": format!("0x{}", hex::encode(data)) }, "latest"]),
+    )?;
+    let hex_str = result
+        .as_str()
+        .ok_or_else(|| anyhow!("eth_call returned non-string data"))?;
+    hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str)).context("eth_call returned bad hex")
+}
+
+fn rpc_u256(state: &AppState, method: &str, params: Value) -> Result<U256> {
+    serde_json::from_value(rpc_request(state, method, params)?)
+        .with_context(|| format!("{method} returned an invalid quantity"))
+}
+
+/// Signs `message` with the owner key (EIP-191), as dapps expect from
+/// `personal_sign`. Returns the 65-byte signature as hex.
+pub fn sign_message(state: &AppState, account: &SmartAccount, message: &[u8]) -> Result<String> {
+    match account.owner_key {
+        OwnerKey::Local => {
+            let signer = owner_local_signer(state, account)?;
+            let sig = signer
+                .sign_message_sync(message)
+                .map_err(|e| anyhow!("sign_message failed: {e}"))?;
+            Ok(format!("0x{}", hex::encode(sig.as_bytes())))
+        }
+        OwnerKey::Hardware => with_owner_device(state, account, |rt, device| {
+            rt.block_on(crate::hardware::sign_message(device, message))
+        }),
+    }
+}
+
+/// Signs a raw 32-byte hash with the owner key.
+pub fn sign_hash(state: &AppState, account: &SmartAccount, hash: B256) -> Result<String> {
+    match account.owner_key {
+        OwnerKey::Local => {
+            let signer = owner_local_signer(state, account)?;
+            let sig = signer
+                .sign_hash_sync(&hash)
+                .map_err(|e| anyhow!("sign_hash failed: {e}"))?;
+            Ok(format!("0x{}", hex::encode(sig.as_bytes())))
+        }
+        OwnerKey::Hardware => with_owner_device(state, account, |rt, device| {
+            rt.block_on(crate::hardware::sign_hash(device, hash))
+        }),
+    }
+}
+
+fn owner_local_signer(
+    state: &AppState,
+    account: &SmartAccount,
+) -> Result<std::sync::Arc<alloy_signer_local::PrivateKeySigner>> {
+    state
+        .local_signer()
+        .filter(|signer| signer.address() == account.owner)
+        .ok_or_else(|| anyhow!("The smart account's local owner key is no longer connected"))
+}
+
+fn with_owner_device<F>(state: &AppState, account: &SmartAccount, sign: F) -> Result<String>
+where
+    F: FnOnce(&tokio::runtime::Runtime, &crate::hardware::HardwareDevice) -> Result<String>,
+{
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("runtime error")?;
+    let hardware_signer = state.hardware_signer.lock().expect("hardware_signer");
+    let device = hardware_signer
+        .as_ref()
+        .filter(|device| {
+            crate::hardware::get_address(device)
+                .parse::<Address>()
+                .is_ok_and(|address| address == account.owner)
+        })
+        .ok_or_else(|| anyhow!("The smart account's hardware owner is no longer connected"))?;
+    sign(&rt, device)
+}
+
+/// Sends the call described by an `eth_sendTransaction` request as a
+/// UserOperation and waits for it to be included. Returns the hash of the
+/// bundle transaction that included it.
+pub fn send_transaction(
+    state: &AppState,
+    account: &SmartAccount,
+    tx: TransactionRequest,
+) -> Result<String> {
+    if let Some(from) = tx.from {
+        if from != account.address {
+            bail!(
+                "Transaction 'from' ({:#x}) is not the smart account {:#x}",
+                from,
+                account.address
+            );
+        }
+    }
+    let to = match tx.to {
+        Some(TxKind::Call(to)) => to,
+        _ => bail!("Smart accounts cannot deploy contracts through eth_sendTransaction"),
+    };
+    let value = tx.value.unwrap_or_default();
+    let data = tx.input.input().cloned().unwrap_or_default();
+    send_call(state, account, to, value, data)
+}
+
+/// Wraps `to.call{value}(data)` in the account's `execute` and submits it to
+/// the bundler, sponsored by the paymaster when one is configured.
+fn send_call(
+    state: &AppState,
+    account: &SmartAccount,
+    to: Address,
+    value: U256,
+    data: Bytes,
+) -> Result<String> {
+    let settings = settings(state)?;
+    let resolved = state
+        .resolved
+        .as_ref()
+        .ok_or_else(|| anyhow!("No config loaded"))?;
+    let entry_point = parse_contract(&settings.entry_point, "entryPoint")?;
+    let chain_id = state.wallet.lock().expect("wallet").chain.chain_id;
+    let bundler = Endpoint {
+        client: &resolved.http_client,
+        url: &settings.bundler_url,
+        label: "bundler",
+    };
+    let paymaster = settings.paymaster_url.as_deref().map(|url| Endpoint {
+        client: &resolved.http_client,
+        url,
+        label: "paymaster",
+    });
+
+    let mut op = build_user_operation(state, settings, account, entry_point, to, value, data)?;
+    let chain_hex = format!("0x{:x}", chain_id);
+    let context = settings.paymaster_context.clone().unwrap_or(Value::Null);
+    let mut sponsored_final = false;
+    if let Some(paymaster) = &paymaster {
+        let stub: PaymasterFields = serde_json::from_value(paymaster.call(
+            "pm_getPaymasterStubData",
+            json!([op, entry_point, chain_hex, context]),
+        )?)
+        .context("paymaster returned invalid stub data")?;
+        sponsored_final = stub.is_final;
+        stub.apply(&mut op);
+    }
+
+    let estimate: GasEstimate = serde_json::from_value(
+        bundler.call("eth_estimateUserOperationGas", json!([op, entry_point]))?,
+    )
+    .context("bundler returned an invalid gas estimate")?;
+    op.pre_verification_gas = estimate.pre_verification_gas;
+    op.verification_gas_limit = estimate.verification_gas_limit;
+    op.call_gas_limit = estimate.call_gas_limit;
+    if op.paymaster.is_some() {
+        if estimate.paymaster_verification_gas_limit.is_some() {
+            op.paymaster_verification_gas_limit = estimate.paymaster_verification_gas_limit;
+        }
+        if estimate.paymaster_post_op_gas_limit.is_some() {
+            op.paymaster_post_op_gas_limit = estimate.paymaster_post_op_gas_limit;
+        }
+    }
+
+    if let Some(paymaster) = paymaster.as_ref().filter(|_| !sponsored_final) {
+        let fields: PaymasterFields = serde_json::from_value(paymaster.call(
+            "pm_getPaymasterData",
+            json!([op, entry_point, chain_hex, context]),
+        )?)
+        .context("paymaster returned invalid data")?;
+        fields.apply(&mut op);
+    }
+
+    let hash = op.hash(entry_point, chain_id);
+    let signature = sign_message(state, account, hash.as_slice())?;
+    op.signature = signature
+        .parse()
+        .context("owner returned an invalid signature")?;
+
+    let op_hash = bundler
+        .call("eth_sendUserOperation", json!([op, entry_point]))?
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("bundler returned a non-string UserOperation hash"))?;
+    tracing::info!(
+        account = format!("{:#x}", account.address),
+        user_op_hash = op_hash,
+        sponsored = op.paymaster.is_some(),
+        deploys = op.factory.is_some(),
+        "user operation submitted"
+    );
+    wait_for_inclusion(&bundler, &op_hash)
+}
+
+fn build_user_operation(
+    state: &AppState,
+    settings: &SmartAccountSettings,
+    account: &SmartAccount,
+    entry_point: Address,
+    to: Address,
+    value: U256,
+    data: Bytes,
+) -> Result<UserOperation> {
+    let nonce_call = getNonceCall {
+        sender: account.address,
+        key: Default::default(),
+    };
+    let nonce =
+        getNonceCall::abi_decode_returns(&eth_call(state, entry_point, nonce_call.abi_encode())?)
+            .context("EntryPoint returned an invalid nonce")?;
+
+    let code = rpc_request(
+        state,
+        "eth_getCode",
+        json!([format!("{:#x}", account.address), "latest"]),
+    )?;
+    let deployed = code.as_str().is_some_and(|code| code.len() > 2);
+    let (factory, factory_data) = if deployed {
+        (None, None)
+    } else {
+        let create = createAccountCall {
+            owner: account.owner,
+            salt: U256::from(settings.salt),
+        };
+        (
+            Some(parse_contract(&settings.factory, "factory")?),
+            Some(Bytes::from(create.abi_encode())),
+        )
+    };
+
+    let gas_price = rpc_u256(state, "eth_gasPrice", json!([]))?;
+    let priority = rpc_u256(state, "eth_maxPriorityFeePerGas", json!([])).unwrap_or(gas_price);
+
+    Ok(UserOperation {
+        sender: account.address,
+        nonce,
+        factory,
+        factory_data,
+        call_data: executeCall {
+            dest: to,
+            value,
+            func: data,
+        }
+        .abi_encode()
+        .into(),
+        max_fee_per_gas: gas_price,
+        max_priority_fee_per_gas: priority.min(gas_price),
+        signature: DUMMY_SIGNATURE.parse().expect("valid dummy signature"),
+        ..Default::default()
+    })
+}
+
+fn wait_for_inclusion(bundler: &Endpoint, op_hash: &str) -> Result<String> {
+    let started = Instant::now();
+    loop {
+        let receipt = bundler.call("eth_getUserOperationReceipt", json!([op_hash]))?;
+        if let Some(tx_hash) = receipt
+            .get("receipt")
+            .and_then(|receipt| receipt.get("transactionHash"))
+            .and_then(Value::as_str)
+        {
+            if receipt.get("success") == Some(&Value::Bool(false)) {
+                tracing::warn!(user_op_hash = op_hash, tx_hash, "user operation reverted");
+            }
+            return Ok(tx_hash.to_string());
+        }
+        if started.elapsed() >= RECEIPT_TIMEOUT {
+            bail!(
+                "UserOperation {op_hash} was not included within {}s",
+                RECEIPT_TIMEOUT.as_secs()
+            );
+        }
+        std::thread::sleep(RECEIPT_POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UserOperation, executeCall, pack_u128_pair};
+    use alloy_primitives::{Address, B256, Bytes, U256};
+    use alloy_sol_types::SolCall;
+
+    fn sample_op() -> UserOperation {
+        UserOperation {
+            sender: Address::repeat_byte(0x11),
+            nonce: U256::from(3),
+            call_data: executeCall {
+                dest: Address::repeat_byte(0x22),
+                value: U256::from(1),
+                func: Bytes::new(),
+            }
+            .abi_encode()
+            .into(),
+            call_gas_limit: U256::from(50_000),
+            verification_gas_limit: U256::from(100_000),
+            pre_verification_gas: U256::from(21_000),
+            max_fee_per_gas: U256::from(30),
+            max_priority_fee_per_gas: U256::from(2),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gas_pairs_pack_high_then_low() {
+        let packed = pack_u128_pair(U256::from(1), U256::from(2));
+        let mut expected = [0u8; 32];
+        expected[15] = 1;
+        expected[31] = 2;
+        assert_eq!(packed, B256::from(expected));
+    }
+
+    #[test]
+    fn hash_leaves_out_the_signature() {
+        let entry_point = Address::repeat_byte(0xee);
+        let op = sample_op();
+        let mut signed = op.clone();
+        signed.signature = Bytes::from_static(&[1; 65]);
+        assert_eq!(signed.hash(entry_point, 1), op.hash(entry_point, 1));
+    }
+
+    #[test]
+    fn hash_covers_gas_and_chain() {
+        let entry_point = Address::repeat_byte(0xee);
+        let op = sample_op();
+        let hash = op.hash(entry_point, 1);
+        let mut more_gas = op.clone();
+        more_gas.call_gas_limit = U256::from(60_000);
+        assert_ne!(more_gas.hash(entry_point, 1), hash);
+        assert_ne!(op.hash(entry_point, 11155111), hash);
+    }
+
+    #[test]
+    fn accounts_without_factory_or_paymaster_pack_nothing() {
+        let op = sample_op();
+        assert!(op.init_code().is_empty());
+        assert!(op.paymaster_and_data().is_empty());
+    }
+
+    #[test]
+    fn init_code_packs_factory_and_data() {
+        let mut op = sample_op();
+        op.factory = Some(Address::repeat_byte(0xfa));
+        op.factory_data = Some(Bytes::from_static(&[0xab, 0xcd]));
+        assert_eq!(op.init_code().len(), 22);
+    }
+
+    #[test]
+    fn paymaster_fields_are_packed() {
+        let mut op = sample_op();
+        op.paymaster = Some(Address::repeat_byte(0x99));
+        op.paymaster_verification_gas_limit = Some(U256::from(7));
+        op.paymaster_data = Some(Bytes::from_static(&[0x01]));
+        let packed = op.paymaster_and_data();
+        assert_eq!(packed.len(), 20 + 16 + 16 + 1);
+        assert_eq!(packed[35], 7);
+        assert_eq!(packed[52], 0x01);
+    }
+
+    #[test]
+    fn serializes_quantities_as_hex_and_skips_unset_fields() {
+        let json = serde_json::to_value(sample_op()).unwrap();
+        assert_eq!(json["callGasLimit"], "0xc350");
+        assert!(json.get("paymasterPostOpGasLimit").is_none());
+    }
+}
@@ -0,0 +1,61 @@
+//! Timing of startup phases, logged under `vibefi::startup` with `--profile-startup`.
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+static START: OnceLock<Instant> = OnceLock::new();
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Records process start. Call first thing in `main` so every phase is measured
+/// from the same origin.
+pub fn begin() {
+    let _ = START.get_or_init(Instant::now);
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn since_start_ms() -> u128 {
+    START.get_or_init(Instant::now).elapsed().as_millis()
+}
+
+/// Logs that a milestone was reached.
+pub fn mark(milestone: &'static str) {
+    if ENABLED.load(Ordering::Relaxed) {
+        tracing::info!(
+            target: "vibefi::startup",
+            milestone,
+            since_start_ms = since_start_ms(),
+            "startup milestone"
+        );
+    }
+}
+
+/// Times a phase until the returned guard is dropped.
+pub fn phase(phase: &'static str) -> PhaseTimer {
+    PhaseTimer {
+        phase,
+        started: Instant::now(),
+    }
+}
+
+pub struct PhaseTimer {
+    phase: &'static str,
+    started: Instant,
+}
+
+impl Drop for PhaseTimer {
+    fn drop(&mut self) {
+        if ENABLED.load(Ordering::Relaxed) {
+            tracing::info!(
+                target: "vibefi::startup",
+                phase = self.phase,
+                elapsed_ms = self.started.elapsed().as_millis(),
+                since_start_ms = since_start_ms(),
+                "startup phase"
+            );
+        }
+    }
+}
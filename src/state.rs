@@ -1,8 +1,9 @@
 use alloy_signer_local::PrivateKeySigner;
 use anyhow::{Result, anyhow};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
+    collections::HashSet,
     collections::VecDeque,
     path::PathBuf,
     sync::{Arc, Mutex, MutexGuard},
@@ -12,8 +13,9 @@ use tao::event_loop::EventLoopProxy;
 
 use crate::config::ResolvedConfig;
 use crate::hardware::HardwareDevice;
+use crate::rpc_client::RpcClient;
 use crate::rpc_manager::RpcEndpointManager;
-use crate::walletconnect::{WalletConnectBridge, WalletConnectSession};
+use crate::walletconnect::{RelayStatus, WalletConnectBridge, WalletConnectSession};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Chain {
@@ -35,16 +37,22 @@ pub enum UserEvent {
     },
     OpenWalletSelector,
     OpenSettings,
+    /// Open the command palette, or focus it if it is open.
+    OpenPalette,
     WalletConnectPairing {
         uri: String,
         qr_svg: String,
     },
+    /// The WalletConnect helper's relay connection changed.
+    WalletConnectRelayStatus {
+        status: RelayStatus,
+    },
     WalletConnectResult {
         webview_id: String,
         ipc_id: u64,
         result: Result<WalletConnectSession, String>,
     },
-    HardwareSignResult {
+    SignerResult {
         webview_id: String,
         ipc_id: u64,
         result: Result<String, String>,
@@ -54,21 +62,73 @@ pub enum UserEvent {
         ipc_id: u64,
         result: Result<serde_json::Value, String>,
     },
-    RpcPendingChanged {
+    /// One of a tab's tab bar badges changed.
+    TabBadgeChanged {
         webview_id: String,
-        count: u32,
+        badge: TabBadge,
+    },
+    /// The registry listed these bundles as the latest of each dapp, root
+    /// CIDs keyed by [`crate::registry::dapp_key`]; tabs running an older
+    /// one get the update badge.
+    DappsListed {
+        latest: HashMap<String, String>,
     },
     ProviderEvent {
         webview_id: String,
         event: String,
         value: serde_json::Value,
     },
+    /// The wallet selector connected `backend`; binds the dapp tabs waiting on it.
+    WalletConnected {
+        backend: WalletBackend,
+        accounts: Vec<String>,
+    },
     StudioBundleResolved {
         placeholder_id: String,
         result: Result<PathBuf, String>,
     },
     CloseWalletSelector,
     TabAction(TabAction),
+    /// Periodic tick asking background dapp tabs idle for `idle_for` to suspend.
+    SuspendIdleTabs {
+        idle_for: std::time::Duration,
+    },
+    /// Scroll/URL snapshot of a tab about to be suspended.
+    TabSnapshot {
+        webview_id: String,
+        snapshot: String,
+    },
+    /// Periodic tick asking every live tab for its resource usage; `heavy_tab`
+    /// is unset while the heavy-tab warning is off.
+    SampleTabUsage {
+        heavy_tab: Option<crate::settings::HeavyTabSettings>,
+    },
+    /// A tab's answer to `tab_usage::TAB_USAGE_JS`.
+    TabUsage {
+        webview_id: String,
+        sample: String,
+    },
+    /// A bundle directory or zip was dropped onto the window.
+    BundleDropped {
+        path: PathBuf,
+    },
+    /// A dropped bundle without build output is waiting for Studio.
+    StudioProjectDropped,
+    /// A menu bar entry was picked.
+    Menu(crate::menu::MenuCommand),
+    /// Shut down and start again with another user profile.
+    RestartWithProfile {
+        name: String,
+    },
+    /// The RPC became unreachable, or reachable again.
+    ConnectivityChanged {
+        online: bool,
+    },
+    /// A handler is blocked on the user's answer to `request`.
+    ShowPrompt {
+        request: crate::prompt::PromptRequest,
+        reply: std::sync::mpsc::Sender<bool>,
+    },
     AutomationCommand {
         id: String,
         cmd_type: String,
@@ -79,41 +139,302 @@ pub enum UserEvent {
 
 #[derive(Debug, Clone)]
 pub enum TabAction {
-    OpenApp { name: String, dist_dir: PathBuf },
+    OpenApp {
+        identity: DappIdentity,
+        dist_dir: PathBuf,
+    },
+    /// Builds the webview of a lazily created or suspended tab, then switches to it.
+    Activate { id: String },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Who a dapp tab belongs to. Shown next to anything the tab asks the user to
+/// approve so requests are never attributed to an opaque webview id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DappIdentity {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dapp_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_cid: Option<String>,
+    /// Proposer of the launched version, taken from registry logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// Label of the community registry that listed the dapp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// Address of that community registry; labels are not unique.
+    #[serde(skip)]
+    pub registry_address: Option<String>,
+    /// Launched from a pasted root CID that no registry lists. Runs without
+    /// manifest capabilities, remembered permissions or persistent storage.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unverified: bool,
+    /// Built on this machine from a project rather than fetched; see
+    /// `ResolvedConfig::dev_profile`.
+    #[serde(skip)]
+    pub local_project: bool,
+}
+
+impl std::fmt::Display for DappIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(dapp_id) = &self.dapp_id {
+            write!(f, " #{dapp_id}")?;
+        }
+        if let Some(root_cid) = &self.root_cid {
+            write!(f, " ({root_cid})")?;
+        }
+        if let Some(publisher) = &self.publisher {
+            write!(f, " published by {publisher}")?;
+        }
+        if let Some(registry) = &self.registry {
+            write!(f, " via {registry}")?;
+        }
+        if self.unverified {
+            write!(f, " [unverified]")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum WalletBackend {
     Local,
     WalletConnect,
     Hardware,
+    /// ERC-4337 account owned by the local or hardware key.
+    SmartAccount,
+    /// External signing service (web3signer or another JSON-RPC signer).
+    Remote,
+}
+
+impl WalletBackend {
+    /// The backend name reported to dapps, matching its serde form.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::WalletConnect => "walletconnect",
+            Self::Hardware => "hardware",
+            Self::SmartAccount => "smartaccount",
+            Self::Remote => "remote",
+        }
+    }
+}
+
+/// An account and the backend that signs for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WalletAccount {
+    pub backend: WalletBackend,
+    pub account: String,
+}
+
+/// The accounts a dapp tab is authorized to use, possibly spanning several
+/// backends. Each tab picks its own, so connecting a hardware wallet in one tab
+/// leaves the others untouched. The first account is the tab's primary one: it
+/// leads `eth_accounts` and signs requests that name no `from` address.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabWallet {
+    pub accounts: Vec<WalletAccount>,
 }
 
+impl TabWallet {
+    pub fn primary(&self) -> Option<&WalletAccount> {
+        self.accounts.first()
+    }
+
+    pub fn addresses(&self) -> Vec<String> {
+        self.accounts.iter().map(|a| a.account.clone()).collect()
+    }
+
+    /// Backend that signs for `account`, if the tab is authorized to use it.
+    pub fn owner_of(&self, account: &str) -> Option<WalletBackend> {
+        self.accounts
+            .iter()
+            .find(|a| a.account.eq_ignore_ascii_case(account))
+            .map(|a| a.backend)
+    }
+
+    pub fn has_backend(&self, backend: WalletBackend) -> bool {
+        self.accounts.iter().any(|a| a.backend == backend)
+    }
+
+    /// Makes `account` the tab's primary account. Returns whether the order
+    /// changed; `false` too when the tab does not hold it.
+    pub fn select(&mut self, account: &str) -> bool {
+        match self
+            .accounts
+            .iter()
+            .position(|a| a.account.eq_ignore_ascii_case(account))
+        {
+            Some(0) | None => false,
+            Some(at) => {
+                let selected = self.accounts.remove(at);
+                self.accounts.insert(0, selected);
+                true
+            }
+        }
+    }
+
+    /// Replaces the accounts held through `backend` with `accounts`, keeping
+    /// their position in the list. Addresses already held through another
+    /// backend stay with it. Returns whether the account list changed.
+    pub fn set_backend_accounts(&mut self, backend: WalletBackend, accounts: &[String]) -> bool {
+        let before = self.accounts.clone();
+        let at = self
+            .accounts
+            .iter()
+            .position(|a| a.backend == backend)
+            .unwrap_or(self.accounts.len());
+        let kept_before = self.accounts[..at]
+            .iter()
+            .filter(|a| a.backend != backend)
+            .count();
+        self.accounts.retain(|a| a.backend != backend);
+        let mut insert_at = kept_before;
+        for account in accounts {
+            if self.owner_of(account).is_some() {
+                continue;
+            }
+            self.accounts.insert(
+                insert_at,
+                WalletAccount {
+                    backend,
+                    account: account.clone(),
+                },
+            );
+            insert_at += 1;
+        }
+        self.accounts != before
+    }
+}
+
+/// A status shown on a tab in the tab bar; sets one field of [`TabBadges`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabBadge {
+    /// RPC requests in flight.
+    PendingRpc(u32),
+    /// Signing requests waiting on a prompt, a device or a remote signer.
+    AwaitingSignature(u32),
+    /// The registry lists a newer bundle of the tab's dapp.
+    UpdateAvailable(bool),
+    /// The page stopped answering; its web process has likely crashed.
+    Crashed(bool),
+}
+
+/// Every badge of one tab, as the tab bar renders them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabBadges {
+    pub pending_rpc: u32,
+    pub awaiting_signature: u32,
+    pub update_available: bool,
+    pub crashed: bool,
+}
+
+impl TabBadges {
+    /// Applies `badge`; returns whether it changed anything.
+    pub fn set(&mut self, badge: TabBadge) -> bool {
+        let before = *self;
+        match badge {
+            TabBadge::PendingRpc(count) => self.pending_rpc = count,
+            TabBadge::AwaitingSignature(count) => self.awaiting_signature = count,
+            TabBadge::UpdateAvailable(available) => self.update_available = available,
+            TabBadge::Crashed(crashed) => self.crashed = crashed,
+        }
+        *self != before
+    }
+}
+
+/// Tab wallet bindings keyed by webview id; shared with the tab bar.
+pub type TabWallets = Arc<Mutex<HashMap<String, TabWallet>>>;
+
+/// Answer to `wallet_getProviderInfo`. Every backend fills in every field, so
+/// callers read `capabilities` instead of matching on `backend`.
 #[derive(Debug, Serialize)]
 pub struct ProviderInfo {
     pub name: String,
     pub chain_id: String,
     pub backend: &'static str,
+    /// The tab's primary account.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub account: Option<String>,
+    /// Every account the tab may use, primary first.
+    pub accounts: Vec<String>,
+    /// Chains the tab may switch to, active one first.
+    pub chains: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub walletconnect_uri: Option<String>,
+    pub capabilities: ProviderCapabilities,
+    /// See [`crate::client_info::PROTOCOL_VERSION`].
+    pub protocol_version: u32,
+}
+
+/// What the tab's wallet can do, as reported to dapps and embedded UIs.
+/// All false when no wallet is connected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCapabilities {
+    pub can_sign_typed_data: bool,
+    /// Produces raw signed transactions; false for backends that submit
+    /// them their own way.
+    pub can_send_raw: bool,
+    pub can_switch_chain: bool,
+    /// Requests are confirmed in a client prompt rather than on a device or
+    /// in a paired wallet.
+    pub client_confirmation: bool,
+}
+
+impl ProviderCapabilities {
+    /// The paired wallet signs, switches chains and confirms on its side.
+    pub const WALLETCONNECT: Self = Self {
+        can_sign_typed_data: true,
+        can_send_raw: false,
+        can_switch_chain: true,
+        client_confirmation: false,
+    };
+}
+
+impl From<SignerCapabilities> for ProviderCapabilities {
+    /// Every key-holding backend signs EIP-712 typed data.
+    fn from(caps: SignerCapabilities) -> Self {
+        Self {
+            can_sign_typed_data: true,
+            can_send_raw: caps.raw_transactions,
+            can_switch_chain: caps.switch_chain,
+            client_confirmation: caps.client_confirmation,
+        }
+    }
+}
+
+/// What a key-holding backend supports beyond the common signing methods.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SignerCapabilities {
+    /// Handles `wallet_switchEthereumChain` itself.
+    pub switch_chain: bool,
+    /// Produces raw signed transactions; when false the backend submits
+    /// transactions its own way (e.g. as UserOperations).
+    pub raw_transactions: bool,
+    /// Every request is confirmed in a client prompt before it is signed.
+    pub client_confirmation: bool,
 }
 
 #[derive(Debug, Default)]
 pub struct WalletState {
-    pub authorized: bool,
     pub chain: Chain,
-    pub account: Option<String>,
+    /// Accounts exposed by the current WalletConnect session.
+    pub walletconnect_accounts: Vec<String>,
     pub walletconnect_uri: Option<String>,
 }
 
-/// Tracks a pending `eth_requestAccounts` that is waiting for the user to
-/// pick a wallet backend in the selector tab.
+/// Tracks a dapp tab waiting for the user to pick a wallet backend in the
+/// selector tab. `ipc_id` is the parked `eth_requestAccounts`, or `None` when
+/// the user asked to switch the tab's wallet from the tab bar.
 #[derive(Debug, Clone)]
 pub struct PendingConnect {
     pub webview_id: String,
-    pub ipc_id: u64,
+    pub ipc_id: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -124,18 +445,65 @@ pub struct IpfsCapabilityRule {
     pub max_bytes: Option<usize>,
 }
 
+/// File size limit when `capabilities.files.maxBytes` is not set.
+pub const DEFAULT_FILE_MAX_BYTES: usize = 5 * 1024 * 1024;
+/// Upper bound on `capabilities.files.maxBytes`; contents cross IPC as base64.
+pub const MAX_FILE_BYTES: usize = 50 * 1024 * 1024;
+
+/// File dialog access granted by `capabilities.files`.
+#[derive(Debug, Clone, Default)]
+pub struct FileCapability {
+    /// Whether `vibefi_openFile` may ask the user for a file to read.
+    pub open: bool,
+    /// Whether `vibefi_saveFile` may ask the user where to write a file.
+    pub save: bool,
+    /// Lowercase extensions without the dot; empty allows any.
+    pub extensions: Vec<String>,
+    pub max_bytes: usize,
+}
+
+impl FileCapability {
+    pub fn allows_path(&self, path: &std::path::Path) -> bool {
+        if self.extensions.is_empty() {
+            return true;
+        }
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.extensions.contains(&ext.to_lowercase()))
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct AppRuntimeCapabilities {
     pub ipfs_allow: Vec<IpfsCapabilityRule>,
+    /// Origins (`scheme://host[:port]`) reachable through `vibefi_httpFetch`.
+    pub network_allow: Vec<String>,
+    /// Whether the dapp may write to the clipboard via `vibefi_copyToClipboard`.
+    pub clipboard_write: bool,
+    /// Whether the dapp may use the camera QR scanner and `vibefi_decodeQr`.
+    pub qr_scan: bool,
+    /// Whether the dapp may show desktop notifications via `vibefi_notify`.
+    pub notifications: bool,
+    /// Topics the dapp may pass to `vibefi_subscribe`.
+    pub event_topics: Vec<crate::subscriptions::Topic>,
+    pub files: FileCapability,
+    /// Dapp ids (same registry) the dapp may exchange `vibefi_postMessage` messages with.
+    pub message_peers: Vec<String>,
+    /// Chains the manifest declares; `wallet_switchEthereumChain` to any
+    /// other is refused.
+    pub chains: crate::manifest::ChainRequirements,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub wallet: Arc<Mutex<WalletState>>,
-    pub wallet_backend: Arc<Mutex<Option<WalletBackend>>>,
-    pub signer: Arc<Mutex<Option<Arc<PrivateKeySigner>>>>,
+    pub tab_wallets: TabWallets,
+    /// Local signing keys, the active account first.
+    pub signer: Arc<Mutex<Vec<Arc<PrivateKeySigner>>>>,
     pub walletconnect: Arc<Mutex<Option<Arc<Mutex<WalletConnectBridge>>>>>,
     pub hardware_signer: Arc<Mutex<Option<HardwareDevice>>>,
+    pub smart_account: Arc<Mutex<Option<crate::smart_account::SmartAccount>>>,
+    pub remote_signer: Arc<Mutex<Option<Arc<crate::remote_signer::RemoteSigner>>>>,
     pub resolved: Option<Arc<ResolvedConfig>>,
     pub proxy: EventLoopProxy<UserEvent>,
     pub pending_connect: Arc<Mutex<VecDeque<PendingConnect>>>,
@@ -143,33 +511,143 @@ pub struct AppState {
     /// Webview ID of the wallet selector tab, if open.
     pub selector_webview_id: Arc<Mutex<Option<String>>>,
     pub rpc_manager: Arc<Mutex<Option<RpcEndpointManager>>>,
+    /// Runtime and HTTP client every JSON-RPC call goes through.
+    pub rpc_client: RpcClient,
     pub settings_webview_id: Arc<Mutex<Option<String>>>,
     /// Tracks how many RPC passthrough requests are in-flight per webview.
     pub pending_rpc_counts: Arc<Mutex<HashMap<String, u32>>>,
+    /// Tracks how many signing requests are in-flight per webview.
+    pub pending_signature_counts: Arc<Mutex<HashMap<String, u32>>>,
+    /// Registry identities seen by the last `vibefi_listDapps`, keyed by root CID.
+    pub known_dapps: Arc<Mutex<HashMap<String, DappIdentity>>>,
+    /// Root CIDs prepared for launch this session. Their cached bundles back
+    /// live webviews, so cache eviction leaves them alone.
+    pub bundles_in_use: Arc<Mutex<HashSet<String>>>,
+    /// `vibefi_subscribe` subscriptions of the open dapp tabs.
+    pub subscriptions: Arc<Mutex<crate::subscriptions::Subscriptions>>,
+    /// Chain head shared by everything that reacts to new blocks.
+    pub blocks: crate::block_watcher::BlockWatcher,
+    /// Lock screen state; signing is refused while locked.
+    pub lock: crate::lock::ClientLock,
+    /// Project dropped onto the window, until Studio takes it.
+    pub dropped_project: Arc<Mutex<Option<crate::local_bundle::StudioProject>>>,
+    /// Studio console expressions waiting for the preview tab's answer.
+    pub preview_evals: crate::preview_eval::PreviewEvals,
     /// Whether automation mode is enabled (--automation flag).
     pub automation: bool,
 }
 
 impl AppState {
+    /// The active local account's key.
     pub fn local_signer(&self) -> Option<Arc<PrivateKeySigner>> {
-        self.signer.lock().expect("signer").as_ref().cloned()
+        self.signer.lock().expect("signer").first().cloned()
     }
 
-    pub fn local_signer_address(&self) -> Option<String> {
+    /// The local key for `account`, in any case.
+    pub fn local_signer_for(&self, account: &str) -> Option<Arc<PrivateKeySigner>> {
         self.signer
             .lock()
             .expect("signer")
-            .as_ref()
+            .iter()
+            .find(|signer| format!("0x{:x}", signer.address()).eq_ignore_ascii_case(account))
+            .cloned()
+    }
+
+    /// Every local account, the active one first.
+    pub fn local_accounts(&self) -> Vec<String> {
+        self.signer
+            .lock()
+            .expect("signer")
+            .iter()
             .map(|signer| format!("0x{:x}", signer.address()))
+            .collect()
     }
 
-    pub fn account(&self) -> Option<String> {
-        let ws = self.wallet.lock().expect("wallet");
-        if let Some(account) = ws.account.clone() {
-            return Some(account);
+    pub fn tab_wallet(&self, webview_id: &str) -> Option<TabWallet> {
+        self.tab_wallets
+            .lock()
+            .expect("tab_wallets")
+            .get(webview_id)
+            .cloned()
+    }
+
+    /// Primary account of the tab; `None` until it completes `eth_requestAccounts`.
+    pub fn account_for(&self, webview_id: &str) -> Option<String> {
+        self.tab_wallet(webview_id)
+            .and_then(|binding| binding.primary().map(|a| a.account.clone()))
+    }
+
+    /// Every account the tab is authorized to use, primary first.
+    pub fn accounts_for(&self, webview_id: &str) -> Vec<String> {
+        self.tab_wallet(webview_id)
+            .map(|binding| binding.addresses())
+            .unwrap_or_default()
+    }
+
+    /// Backend of the tab's primary account.
+    pub fn wallet_backend_for(&self, webview_id: &str) -> Option<WalletBackend> {
+        self.tab_wallet(webview_id)
+            .and_then(|binding| binding.primary().map(|a| a.backend))
+    }
+
+    /// Adds `backend`'s accounts to the tab, replacing any it already held
+    /// through that backend. Returns whether the tab's account list changed.
+    pub fn bind_tab_wallet(
+        &self,
+        webview_id: &str,
+        backend: WalletBackend,
+        accounts: &[String],
+    ) -> bool {
+        tracing::info!(webview_id, ?backend, ?accounts, "tab wallet bound");
+        let mut bindings = self.tab_wallets.lock().expect("tab_wallets");
+        let binding = bindings.entry(webview_id.to_string()).or_default();
+        let changed = binding.set_backend_accounts(backend, accounts);
+        if binding.accounts.is_empty() {
+            bindings.remove(webview_id);
         }
-        drop(ws);
-        self.local_signer_address()
+        changed
+    }
+
+    /// Disconnects one account from the tab. Returns whether the tab held it.
+    pub fn remove_tab_account(&self, webview_id: &str, account: &str) -> bool {
+        let mut bindings = self.tab_wallets.lock().expect("tab_wallets");
+        let Some(binding) = bindings.get_mut(webview_id) else {
+            return false;
+        };
+        let before = binding.accounts.len();
+        binding
+            .accounts
+            .retain(|a| !a.account.eq_ignore_ascii_case(account));
+        let removed = binding.accounts.len() != before;
+        if binding.accounts.is_empty() {
+            bindings.remove(webview_id);
+        }
+        removed
+    }
+
+    pub fn unbind_tab_wallet(&self, webview_id: &str) -> Option<TabWallet> {
+        self.tab_wallets
+            .lock()
+            .expect("tab_wallets")
+            .remove(webview_id)
+    }
+
+    /// Records the WalletConnect session accounts and gives them to every tab
+    /// using WalletConnect, dropping those accounts when the session has none.
+    /// Returns the ids of the tabs whose account list changed.
+    pub fn apply_walletconnect_accounts(&self, accounts: &[String]) -> Vec<String> {
+        self.wallet.lock().expect("wallet").walletconnect_accounts = accounts.to_vec();
+        let mut bindings = self.tab_wallets.lock().expect("tab_wallets");
+        let mut changed = Vec::new();
+        bindings.retain(|webview_id, binding| {
+            if binding.has_backend(WalletBackend::WalletConnect)
+                && binding.set_backend_accounts(WalletBackend::WalletConnect, accounts)
+            {
+                changed.push(webview_id.clone());
+            }
+            !binding.accounts.is_empty()
+        });
+        changed
     }
 
     pub fn chain_id_hex(&self) -> String {
@@ -177,10 +655,6 @@ impl AppState {
         format!("0x{:x}", chain_id)
     }
 
-    pub fn get_wallet_backend(&self) -> Option<WalletBackend> {
-        *self.wallet_backend.lock().expect("wallet_backend")
-    }
-
     /// Increment the pending RPC count for a webview; returns the new count.
     pub fn increment_rpc_pending(&self, webview_id: &str) -> u32 {
         let mut map = self.pending_rpc_counts.lock().expect("pending_rpc_counts");
@@ -197,6 +671,62 @@ impl AppState {
         *count
     }
 
+    /// Increment the in-flight signing count for a webview; returns the new count.
+    pub fn increment_signature_pending(&self, webview_id: &str) -> u32 {
+        let mut map = self
+            .pending_signature_counts
+            .lock()
+            .expect("pending_signature_counts");
+        let count = map.entry(webview_id.to_string()).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// Decrement the in-flight signing count for a webview; returns the new count.
+    pub fn decrement_signature_pending(&self, webview_id: &str) -> u32 {
+        let mut map = self
+            .pending_signature_counts
+            .lock()
+            .expect("pending_signature_counts");
+        let count = map.entry(webview_id.to_string()).or_insert(0);
+        *count = count.saturating_sub(1);
+        *count
+    }
+
+    /// Whether a webview still awaits RPC results, a signature or a wallet
+    /// connection.
+    pub fn has_pending_work(&self, webview_id: &str) -> bool {
+        let rpc_pending = self
+            .pending_rpc_counts
+            .lock()
+            .expect("pending_rpc_counts")
+            .get(webview_id)
+            .is_some_and(|count| *count > 0);
+        let signature_pending = self
+            .pending_signature_counts
+            .lock()
+            .expect("pending_signature_counts")
+            .get(webview_id)
+            .is_some_and(|count| *count > 0);
+        rpc_pending
+            || signature_pending
+            || self
+                .pending_connect
+                .lock()
+                .expect("pending_connect")
+                .iter()
+                .any(|pc| pc.webview_id == webview_id)
+    }
+
+    pub fn bundles_in_use(&self) -> Vec<String> {
+        self.bundles_in_use
+            .lock()
+            .expect("bundles_in_use")
+            .iter()
+            .cloned()
+            .collect()
+    }
+
     pub fn app_capabilities_for(&self, webview_id: &str) -> Option<AppRuntimeCapabilities> {
         self.app_capabilities
             .lock()
@@ -209,3 +739,88 @@ impl AppState {
 pub(crate) fn lock_or_err<'a, T>(mutex: &'a Mutex<T>, name: &str) -> Result<MutexGuard<'a, T>> {
     mutex.lock().map_err(|_| anyhow!("poisoned lock: {}", name))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{TabBadge, TabBadges, TabWallet, WalletBackend};
+
+    fn accounts(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    /// A Ledger account connected first, then two WalletConnect accounts.
+    fn two_backends() -> TabWallet {
+        let mut wallet = TabWallet::default();
+        assert!(wallet.set_backend_accounts(WalletBackend::Hardware, &accounts(&["0xaa"])));
+        assert!(
+            wallet.set_backend_accounts(WalletBackend::WalletConnect, &accounts(&["0xbb", "0xcc"]))
+        );
+        wallet
+    }
+
+    #[test]
+    fn tab_wallet_aggregates_backends_in_connect_order() {
+        let wallet = two_backends();
+        assert_eq!(wallet.addresses(), accounts(&["0xaa", "0xbb", "0xcc"]));
+        assert_eq!(wallet.owner_of("0xBB"), Some(WalletBackend::WalletConnect));
+        assert_eq!(wallet.owner_of("0xdd"), None);
+    }
+
+    #[test]
+    fn tab_wallet_backends_keep_their_place_when_switching_accounts() {
+        let mut wallet = two_backends();
+        assert!(wallet.set_backend_accounts(WalletBackend::WalletConnect, &accounts(&["0xdd"])));
+        assert_eq!(wallet.addresses(), accounts(&["0xaa", "0xdd"]));
+        assert!(!wallet.set_backend_accounts(WalletBackend::WalletConnect, &accounts(&["0xdd"])));
+    }
+
+    #[test]
+    fn tab_wallet_does_not_duplicate_addresses_across_backends() {
+        let mut wallet = two_backends();
+        wallet.set_backend_accounts(WalletBackend::Local, &accounts(&["0xAA", "0xee"]));
+        assert_eq!(
+            wallet.addresses(),
+            accounts(&["0xaa", "0xbb", "0xcc", "0xee"])
+        );
+        assert_eq!(wallet.owner_of("0xaa"), Some(WalletBackend::Hardware));
+    }
+
+    #[test]
+    fn tab_wallet_promotes_the_next_backend_when_the_primary_leaves() {
+        let mut wallet = two_backends();
+        wallet.set_backend_accounts(WalletBackend::Hardware, &[]);
+        assert_eq!(
+            wallet.primary().map(|a| a.backend),
+            Some(WalletBackend::WalletConnect)
+        );
+    }
+
+    #[test]
+    fn tab_wallet_selecting_an_account_makes_it_primary() {
+        let mut wallet = two_backends();
+        assert!(wallet.select("0xCC"));
+        assert_eq!(wallet.addresses(), accounts(&["0xcc", "0xaa", "0xbb"]));
+        assert!(!wallet.select("0xcc"));
+        assert!(!wallet.select("0xff"));
+    }
+
+    #[test]
+    fn tab_badges_report_changes() {
+        let mut badges = TabBadges::default();
+        assert!(badges.set(TabBadge::PendingRpc(2)));
+        assert!(!badges.set(TabBadge::PendingRpc(2)));
+        assert!(badges.set(TabBadge::AwaitingSignature(1)));
+        assert!(badges.set(TabBadge::UpdateAvailable(true)));
+        assert!(badges.set(TabBadge::Crashed(true)));
+        assert!(!badges.set(TabBadge::Crashed(true)));
+        assert_eq!(
+            serde_json::to_value(badges).unwrap(),
+            serde_json::json!({
+                "pendingRpc": 2,
+                "awaitingSignature": 1,
+                "updateAvailable": true,
+                "crashed": true,
+            })
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config::ResolvedConfig;
+use crate::prompt::PromptRequest;
+use crate::state::{AppState, DappIdentity};
+
+/// Per-dapp storage quota used when the settings do not set one.
+pub const DEFAULT_STORAGE_QUOTA_MB: u64 = 100;
+const METADATA_FILE: &str = "partition.json";
+
+/// Who a storage partition belongs to, stored inside it for the settings list.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartitionMeta {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dapp_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    registry: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionUsage {
+    pub key: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dapp_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    pub used_bytes: u64,
+}
+
+/// Partitions live next to `settings.json`, or in the cache dir without a config file.
+fn storage_root(resolved: &ResolvedConfig) -> PathBuf {
+    match &resolved.config_path {
+        Some(config_path) => config_path.with_file_name("storage"),
+        None => resolved.cache_dir.join("storage"),
+    }
+}
+
+/// Registry dapps are keyed by registry and id so their storage survives
+/// upgrades; unlisted bundles by root CID. Unverified launches get none and
+/// run in an ephemeral webview instead.
+fn partition_key(identity: &DappIdentity) -> Option<String> {
+    if identity.unverified {
+        return None;
+    }
+    let subject = match (&identity.dapp_id, &identity.root_cid) {
+        (Some(dapp_id), _) => format!(
+            "dapp:{}:{dapp_id}",
+            identity.registry_address.as_deref().unwrap_or_default()
+        ),
+        (None, Some(root_cid)) => format!("bundle:{root_cid}"),
+        (None, None) => return None,
+    };
+    Some(hex::encode(&Sha256::digest(subject.as_bytes())[..16]))
+}
+
+fn is_partition_key(key: &str) -> bool {
+    key.len() == 32 && key.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// The storage partition of `identity`, created on first use. Tabs without a
+/// registry or bundle identity share the default partition (`None`).
+pub fn partition_dir(state: &AppState, identity: &DappIdentity) -> Option<PathBuf> {
+    let resolved = state.resolved.as_ref()?;
+    let dir = storage_root(resolved).join(partition_key(identity)?);
+    let meta = PartitionMeta {
+        name: identity.name.clone(),
+        dapp_id: identity.dapp_id.clone(),
+        registry: identity.registry.clone(),
+    };
+    let written = fs::create_dir_all(&dir).and_then(|()| {
+        fs::write(
+            dir.join(METADATA_FILE),
+            serde_json::to_vec_pretty(&meta).unwrap_or_default(),
+        )
+    });
+    match written {
+        Ok(()) => Some(dir),
+        Err(err) => {
+            tracing::warn!(dir = %dir.display(), error = %err, "failed to create storage partition");
+            None
+        }
+    }
+}
+
+/// WebKit data store id for a partition on macOS, where data directories are
+/// not configurable.
+#[cfg(target_os = "macos")]
+pub fn data_store_identifier(dir: &Path) -> [u8; 16] {
+    let digest = Sha256::digest(dir.to_string_lossy().as_bytes());
+    let mut id = [0u8; 16];
+    id.copy_from_slice(&digest[..16]);
+    id
+}
+
+pub fn quota_bytes(state: &AppState) -> u64 {
+    let quota_mb = state
+        .resolved
+        .as_ref()
+        .and_then(|r| r.config_path.as_deref())
+        .map(crate::settings::load_settings)
+        .and_then(|settings| settings.storage_quota_mb)
+        .unwrap_or(DEFAULT_STORAGE_QUOTA_MB);
+    quota_mb.saturating_mul(1024 * 1024)
+}
+
+/// Usage of every storage partition, largest first.
+pub fn list_partitions(resolved: &ResolvedConfig) -> Vec<PartitionUsage> {
+    let Ok(entries) = fs::read_dir(storage_root(resolved)) else {
+        return Vec::new();
+    };
+    let mut partitions: Vec<PartitionUsage> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let key = entry.file_name().to_string_lossy().into_owned();
+            if !is_partition_key(&key) {
+                return None;
+            }
+            let path = entry.path();
+            let meta: PartitionMeta = fs::read(path.join(METADATA_FILE))
+                .ok()
+                .and_then(|raw| serde_json::from_slice(&raw).ok())
+                .unwrap_or_default();
+            Some(PartitionUsage {
+                key,
+                name: meta.name,
+                dapp_id: meta.dapp_id,
+                registry: meta.registry,
+                used_bytes: crate::cache_manager::dir_size(&path),
+            })
+        })
+        .collect();
+    partitions.sort_by(|a, b| b.used_bytes.cmp(&a.used_bytes));
+    partitions
+}
+
+/// Deletes the site data in partition `key`, keeping only its metadata.
+/// `open` lists the partitions of live webviews, which are left alone.
+pub fn clear_partition(resolved: &ResolvedConfig, key: &str, open: &[PathBuf]) -> Result<()> {
+    if !is_partition_key(key) {
+        bail!("invalid storage partition");
+    }
+    let dir = storage_root(resolved).join(key);
+    if open.iter().any(|path| path == &dir) {
+        bail!("close the dapp's tabs before clearing its site data");
+    }
+    clear_dir(&dir)
+}
+
+fn clear_dir(dir: &Path) -> Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err).context("read storage partition"),
+    };
+    for entry in entries {
+        let entry = entry.context("read storage partition entry")?;
+        if entry.file_name() == METADATA_FILE {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        }
+        .with_context(|| format!("failed to remove {}", path.display()))?;
+    }
+    tracing::info!(dir = %dir.display(), "cleared dapp site data");
+    Ok(())
+}
+
+/// Checks the partition of a dapp about to launch against the quota. Over
+/// quota, the user either clears its site data or the launch is refused.
+/// Blocks on a prompt, so call it off the event loop.
+pub fn enforce_quota(state: &AppState, identity: &DappIdentity) -> Result<()> {
+    let Some(dir) = partition_dir(state, identity) else {
+        return Ok(());
+    };
+    let used = crate::cache_manager::dir_size(&dir);
+    let quota = quota_bytes(state);
+    if used <= quota {
+        return Ok(());
+    }
+    let (used_mb, quota_mb) = (used / (1024 * 1024), quota / (1024 * 1024));
+    tracing::warn!(dapp = %identity, used_mb, quota_mb, "dapp storage over quota");
+    let request = PromptRequest::new(
+        "storageQuota",
+        "Storage quota exceeded",
+        format!(
+            "{} stores {used_mb} MB of site data, over the {quota_mb} MB limit. Clear its site data to continue?",
+            identity.name
+        ),
+    )
+    .with_details(serde_json::json!({
+        "dapp": identity.to_string(),
+        "usedMb": used_mb,
+        "quotaMb": quota_mb,
+    }));
+    if !crate::prompt::ask(state, request) {
+        return Err(anyhow!(
+            "{} exceeds its {quota_mb} MB storage quota; clear its site data in Settings or raise the quota",
+            identity.name
+        ));
+    }
+    clear_dir(&dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_partition_key, partition_key};
+    use crate::state::DappIdentity;
+
+    fn registry_dapp() -> DappIdentity {
+        DappIdentity {
+            dapp_id: Some("7".to_string()),
+            root_cid: Some("bafy-v1".to_string()),
+            ..DappIdentity::default()
+        }
+    }
+
+    #[test]
+    fn partitions_follow_the_dapp_across_versions() {
+        let v1 = registry_dapp();
+        let v2 = DappIdentity {
+            root_cid: Some("bafy-v2".to_string()),
+            ..v1.clone()
+        };
+        let key = partition_key(&v1).unwrap();
+        assert!(is_partition_key(&key));
+        assert_eq!(partition_key(&v2), Some(key));
+    }
+
+    #[test]
+    fn partitions_are_per_registry() {
+        // Dapp ids are only unique per registry.
+        let community = DappIdentity {
+            registry_address: Some("0xabc".to_string()),
+            ..registry_dapp()
+        };
+        assert_ne!(partition_key(&community), partition_key(&registry_dapp()));
+    }
+
+    #[test]
+    fn unidentified_and_pasted_dapps_get_no_partition() {
+        assert_eq!(partition_key(&DappIdentity::default()), None);
+        let pasted = DappIdentity {
+            root_cid: Some("bafy-v1".to_string()),
+            unverified: true,
+            ..DappIdentity::default()
+        };
+        assert_eq!(partition_key(&pasted), None);
+    }
+
+    #[test]
+    fn paths_are_not_partition_keys() {
+        assert!(!is_partition_key("../settings"));
+    }
+}
@@ -0,0 +1,340 @@
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Token budget when the request names none.
+const DEFAULT_TOKEN_BUDGET: usize = 24_000;
+const MAX_TOKEN_BUDGET: usize = 200_000;
+/// Files larger than this are never packed whole; the agent can still read them.
+const MAX_FILE_BYTES: usize = 256 * 1024;
+/// A relevant file that does not fit is packed truncated when at least this
+/// much budget is left.
+const MIN_TRUNCATED_TOKENS: usize = 256;
+
+const IGNORED_DIRS: &[&str] = &["node_modules/", "dist/", ".vibefi/", ".git/"];
+const IGNORED_FILES: &[&str] = &["package-lock.json", "bun.lock", "bun.lockb", "yarn.lock"];
+const ENTRYPOINTS: &[&str] = &["manifest.json", "index.html", "src/App.tsx", "src/main.tsx"];
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "this", "that", "from", "into", "when", "then", "make", "add",
+    "use", "should", "please", "can", "not", "but", "all", "file", "code", "app",
+];
+
+/// What Studio sends to have its project context packed for a prompt.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackRequest {
+    pub prompt: String,
+    pub files: Vec<ContextFile>,
+    #[serde(default)]
+    pub token_budget: Option<usize>,
+    /// Recently edited paths, most recent first.
+    #[serde(default)]
+    pub recent: Vec<String>,
+    /// File open in the editor.
+    #[serde(default)]
+    pub active: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContextFile {
+    pub path: String,
+    /// UTF-8 text; binary files are left out by Studio.
+    pub content: String,
+}
+
+/// The files picked for a prompt, most relevant first.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackedContext {
+    pub files: Vec<PackedFile>,
+    /// Project files left out, so the agent knows what it can still read.
+    pub omitted: Vec<String>,
+    pub tokens_used: usize,
+    pub token_budget: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PackedFile {
+    pub path: String,
+    pub content: String,
+    pub score: u32,
+    /// Only the start of the file fit in the budget.
+    pub truncated: bool,
+}
+
+/// Picks the files most relevant to the prompt that fit the token budget.
+/// Relevance comes from file paths and declared symbols named in the prompt,
+/// how often prompt terms occur in a file, recent edits, the active file and
+/// the bundle's entry points. Files with no relevance are listed as omitted
+/// rather than packed.
+pub fn pack(request: &PackRequest) -> Result<PackedContext> {
+    let token_budget = request.token_budget.unwrap_or(DEFAULT_TOKEN_BUDGET);
+    if token_budget == 0 || token_budget > MAX_TOKEN_BUDGET {
+        bail!("tokenBudget must be between 1 and {MAX_TOKEN_BUDGET}");
+    }
+    let terms = prompt_terms(&request.prompt);
+    let prompt = request.prompt.to_lowercase();
+
+    let mut scored: Vec<(u32, &ContextFile)> = Vec::new();
+    let mut omitted = Vec::new();
+    for file in &request.files {
+        if is_ignored(&file.path) || file.content.len() > MAX_FILE_BYTES {
+            omitted.push(file.path.clone());
+            continue;
+        }
+        let score = score(file, &terms, &prompt, request);
+        if score == 0 {
+            omitted.push(file.path.clone());
+        } else {
+            scored.push((score, file));
+        }
+    }
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
+
+    let mut files = Vec::new();
+    let mut tokens_used = 0;
+    for (score, file) in scored {
+        let remaining = token_budget - tokens_used;
+        let tokens = estimate_tokens(&file.content);
+        if tokens <= remaining {
+            tokens_used += tokens;
+            files.push(PackedFile {
+                path: file.path.clone(),
+                content: file.content.clone(),
+                score,
+                truncated: false,
+            });
+        } else if remaining >= MIN_TRUNCATED_TOKENS {
+            let content = truncate_to_tokens(&file.content, remaining);
+            tokens_used += estimate_tokens(&content);
+            files.push(PackedFile {
+                path: file.path.clone(),
+                content,
+                score,
+                truncated: true,
+            });
+        } else {
+            omitted.push(file.path.clone());
+        }
+    }
+    omitted.sort();
+    tracing::debug!(
+        packed = files.len(),
+        omitted = omitted.len(),
+        tokens_used,
+        token_budget,
+        "packed studio context"
+    );
+    Ok(PackedContext {
+        files,
+        omitted,
+        tokens_used,
+        token_budget,
+    })
+}
+
+fn score(file: &ContextFile, terms: &HashSet<String>, prompt: &str, request: &PackRequest) -> u32 {
+    let path = file.path.to_lowercase();
+    let file_name = path.rsplit('/').next().unwrap_or(&path);
+    let mut score = 0;
+    if prompt.contains(file_name) {
+        score += 20;
+    }
+    if request.active.as_deref() == Some(file.path.as_str()) {
+        score += 25;
+    }
+    if let Some(rank) = request
+        .recent
+        .iter()
+        .position(|recent| *recent == file.path)
+    {
+        score += 10u32.saturating_sub(2 * rank as u32).max(2);
+    }
+    if ENTRYPOINTS.contains(&file.path.as_str()) {
+        score += 3;
+    }
+    let content = file.content.to_lowercase();
+    for term in terms {
+        if path.contains(term.as_str()) {
+            score += 8;
+        }
+        score += content.matches(term.as_str()).take(5).count() as u32;
+    }
+    for symbol in declared_symbols(&file.content) {
+        if terms.contains(&symbol.to_lowercase()) {
+            score += 6;
+        }
+    }
+    score
+}
+
+/// Identifiers and words in the prompt worth matching, lowercased. camelCase
+/// and snake_case names also contribute their parts.
+fn prompt_terms(prompt: &str) -> HashSet<String> {
+    let mut terms = HashSet::new();
+    for word in prompt.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        let mut parts = vec![word.to_string()];
+        parts.extend(split_identifier(word));
+        for part in parts {
+            let part = part.to_lowercase();
+            if part.len() >= 3 && !STOPWORDS.contains(&part.as_str()) {
+                terms.insert(part);
+            }
+        }
+    }
+    terms
+}
+
+fn split_identifier(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for c in word.chars() {
+        if c == '_' || (c.is_uppercase() && !current.is_empty()) {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            if c == '_' {
+                continue;
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    if parts.len() > 1 { parts } else { Vec::new() }
+}
+
+/// Names declared at the start of a line: functions, classes, components,
+/// types and top-level bindings in TypeScript, JavaScript and Solidity.
+fn declared_symbols(content: &str) -> Vec<&str> {
+    const KEYWORDS: &[&str] = &[
+        "function",
+        "class",
+        "interface",
+        "type",
+        "enum",
+        "const",
+        "let",
+        "contract",
+        "struct",
+        "event",
+    ];
+    const MODIFIERS: &[&str] = &["export", "default", "async", "abstract", "declare"];
+    let mut symbols = Vec::new();
+    for line in content.lines() {
+        let mut words = line
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .filter(|word| !word.is_empty())
+            .skip_while(|word| MODIFIERS.contains(word));
+        if words
+            .next()
+            .is_some_and(|keyword| KEYWORDS.contains(&keyword))
+        {
+            symbols.extend(words.next());
+        }
+    }
+    symbols
+}
+
+fn is_ignored(path: &str) -> bool {
+    IGNORED_DIRS
+        .iter()
+        .any(|dir| path.starts_with(dir) || path.contains(&format!("/{dir}")))
+        || IGNORED_FILES
+            .iter()
+            .any(|name| path.rsplit('/').next() == Some(*name))
+}
+
+/// Roughly four characters per token, which is close enough for code.
+fn estimate_tokens(content: &str) -> usize {
+    content.chars().count().div_ceil(4)
+}
+
+/// Keeps whole lines from the start of `content` within `tokens`.
+fn truncate_to_tokens(content: &str, tokens: usize) -> String {
+    let mut kept = String::new();
+    let mut chars = 0;
+    for line in content.split_inclusive('\n') {
+        chars += line.chars().count();
+        if chars.div_ceil(4) > tokens {
+            break;
+        }
+        kept.push_str(line);
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContextFile, PackRequest, pack};
+
+    fn file(path: &str, content: &str) -> ContextFile {
+        ContextFile {
+            path: path.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn swap_request() -> PackRequest {
+        PackRequest {
+            prompt: "Show the SwapPanel slippage in the header".to_string(),
+            files: vec![
+                file(
+                    "src/components/SwapPanel.tsx",
+                    "export function SwapPanel() {\n  const slippage = 0.5;\n}\n",
+                ),
+                file("src/Header.tsx", "export default function Header() {}\n"),
+                file("src/utils/format.ts", "export const pad = (n) => n;\n"),
+                file(
+                    "node_modules/react/index.js",
+                    "module.exports = slippage;\n",
+                ),
+                file("src/big.ts", &"// swap slippage\n".repeat(400)),
+            ],
+            token_budget: Some(300),
+            recent: vec!["src/Header.tsx".to_string()],
+            active: Some("src/utils/format.ts".to_string()),
+        }
+    }
+
+    #[test]
+    fn packs_the_most_relevant_file_first() {
+        let packed = pack(&swap_request()).unwrap();
+        assert_eq!(packed.files[0].path, "src/components/SwapPanel.tsx");
+    }
+
+    #[test]
+    fn packs_recent_and_active_files() {
+        let packed = pack(&swap_request()).unwrap();
+        let paths: Vec<&str> = packed.files.iter().map(|f| f.path.as_str()).collect();
+        assert!(paths.contains(&"src/Header.tsx"));
+        assert!(paths.contains(&"src/utils/format.ts"));
+    }
+
+    #[test]
+    fn stays_within_the_budget() {
+        let packed = pack(&swap_request()).unwrap();
+        assert!(packed.tokens_used <= 300);
+        // The big file only fits in part.
+        let big = packed.files.iter().find(|f| f.path == "src/big.ts");
+        assert!(big.is_none_or(|f| f.truncated));
+    }
+
+    #[test]
+    fn never_packs_dependencies() {
+        let packed = pack(&swap_request()).unwrap();
+        assert!(
+            packed
+                .omitted
+                .contains(&"node_modules/react/index.js".to_string())
+        );
+    }
+
+    #[test]
+    fn refuses_an_empty_budget() {
+        let mut too_big = swap_request();
+        too_big.token_budget = Some(0);
+        assert!(pack(&too_big).is_err());
+    }
+}
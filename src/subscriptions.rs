@@ -0,0 +1,262 @@
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::state::{AppState, UserEvent};
+
+/// Provider event carrying one delivery of a `vibefi_subscribe` subscription.
+pub const SUBSCRIPTION_EVENT: &str = "vibefiSubscription";
+
+/// Stop watching a transaction that has not been mined after this long.
+const TX_WATCH_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Client-side events a dapp can subscribe to. Each topic is granted by the
+/// bundle manifest under `capabilities.events.subscribe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topic {
+    /// Download, verify and build progress of every bundle being prepared.
+    LaunchProgress,
+    /// Status of transactions the subscribing tab sent.
+    TxStatus,
+    /// New dapps and versions seen in registry listings.
+    RegistryUpdates,
+}
+
+impl Topic {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "launchProgress" => Some(Self::LaunchProgress),
+            "txStatus" => Some(Self::TxStatus),
+            "registryUpdates" => Some(Self::RegistryUpdates),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::LaunchProgress => "launchProgress",
+            Self::TxStatus => "txStatus",
+            Self::RegistryUpdates => "registryUpdates",
+        }
+    }
+}
+
+/// Live subscriptions per webview. Dropped with the tab.
+#[derive(Debug, Default)]
+pub struct Subscriptions {
+    next_id: u64,
+    by_webview: HashMap<String, Vec<(String, Topic)>>,
+    /// Transaction requests whose hash should be watched once the wallet answers.
+    pending_tx: HashSet<(String, u64)>,
+}
+
+impl Subscriptions {
+    pub fn subscribe(&mut self, webview_id: &str, topic: Topic) -> String {
+        self.next_id += 1;
+        let id = format!("0x{:x}", self.next_id);
+        self.by_webview
+            .entry(webview_id.to_string())
+            .or_default()
+            .push((id.clone(), topic));
+        id
+    }
+
+    pub fn unsubscribe(&mut self, webview_id: &str, id: &str) -> bool {
+        let Some(subs) = self.by_webview.get_mut(webview_id) else {
+            return false;
+        };
+        let before = subs.len();
+        subs.retain(|(sub_id, _)| sub_id != id);
+        let removed = subs.len() != before;
+        if subs.is_empty() {
+            self.by_webview.remove(webview_id);
+        }
+        removed
+    }
+
+    pub fn remove_webview(&mut self, webview_id: &str) -> usize {
+        self.pending_tx.retain(|(id, _)| id != webview_id);
+        self.by_webview
+            .remove(webview_id)
+            .map(|subs| subs.len())
+            .unwrap_or(0)
+    }
+
+    pub fn is_subscribed(&self, webview_id: &str, topic: Topic) -> bool {
+        self.by_webview
+            .get(webview_id)
+            .is_some_and(|subs| subs.iter().any(|(_, t)| *t == topic))
+    }
+
+    /// `(webview_id, subscription_id)` of every subscription to `topic`,
+    /// limited to `webview_id` when given.
+    fn subscribers(&self, topic: Topic, webview_id: Option<&str>) -> Vec<(String, String)> {
+        self.by_webview
+            .iter()
+            .filter(|(id, _)| webview_id.is_none_or(|only| only == id.as_str()))
+            .flat_map(|(id, subs)| {
+                subs.iter()
+                    .filter(move |(_, t)| *t == topic)
+                    .map(move |(sub_id, _)| (id.clone(), sub_id.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Delivers `data` to every subscription to `topic`, or only to those of
+/// `webview_id` when given. Safe to call from any thread.
+pub fn publish(state: &AppState, topic: Topic, webview_id: Option<&str>, data: Value) {
+    let targets = state
+        .subscriptions
+        .lock()
+        .expect("subscriptions")
+        .subscribers(topic, webview_id);
+    for (webview_id, subscription) in targets {
+        let _ = state.proxy.send_event(UserEvent::ProviderEvent {
+            webview_id,
+            event: SUBSCRIPTION_EVENT.to_string(),
+            value: json!({
+                "subscription": subscription,
+                "topic": topic.as_str(),
+                "data": data,
+            }),
+        });
+    }
+}
+
+/// Marks a transaction request whose resulting hash should be watched, when
+/// the tab subscribed to `txStatus`.
+pub fn expect_transaction(state: &AppState, webview_id: &str, ipc_id: u64) {
+    let mut subs = state.subscriptions.lock().expect("subscriptions");
+    if subs.is_subscribed(webview_id, Topic::TxStatus) {
+        subs.pending_tx.insert((webview_id.to_string(), ipc_id));
+    }
+}
+
+/// Starts watching the hash a marked transaction request resolved to; `hash`
+/// is `None` when the request failed.
+pub fn on_transaction_result(state: &AppState, webview_id: &str, ipc_id: u64, hash: Option<&str>) {
+    let expected = state
+        .subscriptions
+        .lock()
+        .expect("subscriptions")
+        .pending_tx
+        .remove(&(webview_id.to_string(), ipc_id));
+    if !expected {
+        return;
+    }
+    if let Some(hash) = hash {
+        watch_transaction(state, webview_id, hash.to_string());
+    }
+}
+
+/// Polls the receipt of `hash` and reports `pending`, then `confirmed` or
+/// `failed`, to the tab that sent it.
+fn watch_transaction(state: &AppState, webview_id: &str, hash: String) {
+    let state = state.clone();
+    let webview_id = webview_id.to_string();
+    std::thread::spawn(move || {
+        publish(
+            &state,
+            Topic::TxStatus,
+            Some(&webview_id),
+            json!({ "hash": hash, "status": "pending" }),
+        );
+        // Receipts only change with a new block, and the block watcher holds
+        // its polls while offline.
+        let mut blocks = state.blocks.subscribe(&state);
+        let started = Instant::now();
+        while started.elapsed() < TX_WATCH_TIMEOUT {
+            if blocks
+                .next_block(TX_WATCH_TIMEOUT.saturating_sub(started.elapsed()))
+                .is_none()
+            {
+                break;
+            }
+            if !state
+                .subscriptions
+                .lock()
+                .expect("subscriptions")
+                .is_subscribed(&webview_id, Topic::TxStatus)
+            {
+                return;
+            }
+            match transaction_receipt(&state, &hash) {
+                Ok(Some(receipt)) => {
+                    let status = match receipt.get("status").and_then(Value::as_str) {
+                        Some("0x1") => "confirmed",
+                        _ => "failed",
+                    };
+                    publish(
+                        &state,
+                        Topic::TxStatus,
+                        Some(&webview_id),
+                        json!({
+                            "hash": hash,
+                            "status": status,
+                            "blockNumber": receipt.get("blockNumber"),
+                        }),
+                    );
+                    return;
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    tracing::debug!(hash, error = %format!("{err:#}"), "tx receipt poll failed")
+                }
+            }
+        }
+        tracing::info!(hash, webview_id, "stopped watching unmined transaction");
+    });
+}
+
+fn transaction_receipt(state: &AppState, hash: &str) -> Result<Option<Value>> {
+    let receipt = crate::ipc::rpc_request(state, "eth_getTransactionReceipt", json!([hash]))
+        .context("eth_getTransactionReceipt failed")?;
+    Ok(Some(receipt).filter(|receipt| !receipt.is_null()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Subscriptions, Topic};
+
+    #[test]
+    fn subscribers_can_be_filtered_by_webview() {
+        let mut subs = Subscriptions::default();
+        let a = subs.subscribe("app-1", Topic::TxStatus);
+        let b = subs.subscribe("app-2", Topic::TxStatus);
+        assert_ne!(a, b);
+        assert_eq!(subs.subscribers(Topic::TxStatus, None).len(), 2);
+        assert_eq!(
+            subs.subscribers(Topic::TxStatus, Some("app-1")),
+            vec![("app-1".to_string(), a)]
+        );
+    }
+
+    #[test]
+    fn only_the_subscribing_webview_can_unsubscribe() {
+        let mut subs = Subscriptions::default();
+        let a = subs.subscribe("app-1", Topic::TxStatus);
+        // Another tab cannot cancel app-1's subscription.
+        assert!(!subs.unsubscribe("app-2", &a));
+        assert!(subs.unsubscribe("app-1", &a));
+        assert!(!subs.is_subscribed("app-1", Topic::TxStatus));
+    }
+
+    #[test]
+    fn closing_a_webview_drops_its_subscriptions() {
+        let mut subs = Subscriptions::default();
+        subs.subscribe("app-1", Topic::TxStatus);
+        subs.subscribe("app-2", Topic::TxStatus);
+        subs.subscribe("app-2", Topic::RegistryUpdates);
+        assert_eq!(subs.remove_webview("app-2"), 2);
+        assert!(subs.subscribers(Topic::RegistryUpdates, None).is_empty());
+        assert_eq!(subs.subscribers(Topic::TxStatus, None).len(), 1);
+    }
+
+    #[test]
+    fn parses_topic_names() {
+        assert_eq!(Topic::parse("txStatus"), Some(Topic::TxStatus));
+        assert_eq!(Topic::parse("accountsChanged"), None);
+    }
+}
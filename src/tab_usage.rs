@@ -0,0 +1,309 @@
+//! Resource usage per tab, so a misbehaving dapp can be found among many open
+//! tabs. Each live tab reports its JS heap and how busy its main thread was,
+//! where its webview exposes them: heap and long tasks on WebView2, main
+//! thread lag only while visible elsewhere. The client's own process tree is
+//! sampled as a whole on Linux, which covers WebKitGTK's web processes.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tao::event_loop::EventLoopProxy;
+
+use crate::settings::HeavyTabSettings;
+use crate::state::UserEvent;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// A busy-time window shorter than this gives no CPU figure.
+const MIN_WINDOW_MS: f64 = 1000.0;
+
+/// Evaluated in each live tab on every sample. Installs its busy-time
+/// monitor on first use and returns what was measured since the last call.
+pub const TAB_USAGE_JS: &str = r#"(function () {
+  var now = performance.now();
+  var u = window.__vibefiUsage;
+  if (!u) {
+    u = window.__vibefiUsage = { busy: 0, visible: 0, at: now, longtask: false };
+    var types = (window.PerformanceObserver && PerformanceObserver.supportedEntryTypes) || [];
+    if (types.indexOf('longtask') >= 0) {
+      u.longtask = true;
+      new PerformanceObserver(function (list) {
+        list.getEntries().forEach(function (entry) { u.busy += entry.duration; });
+      }).observe({ type: 'longtask' });
+    } else {
+      var last = now;
+      setInterval(function () {
+        var t = performance.now();
+        if (document.visibilityState === 'visible') {
+          u.visible += t - last;
+          u.busy += Math.max(0, t - last - 200);
+        }
+        last = t;
+      }, 200);
+    }
+  }
+  var memory = performance.memory;
+  var sample = {
+    heapUsed: memory ? memory.usedJSHeapSize : null,
+    heapLimit: memory ? memory.jsHeapSizeLimit : null,
+    busyMs: u.busy,
+    windowMs: u.longtask ? now - u.at : u.visible
+  };
+  u.busy = 0;
+  u.visible = 0;
+  u.at = now;
+  return sample;
+})()"#;
+
+/// Latest usage of one tab; fields its webview does not expose stay unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabUsage {
+    pub heap_used: Option<u64>,
+    pub heap_limit: Option<u64>,
+    /// Share of the sample window the tab's main thread was busy.
+    pub cpu_percent: Option<f64>,
+    /// Over the heavy-tab limits in the settings.
+    pub heavy: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Sample {
+    heap_used: Option<f64>,
+    heap_limit: Option<f64>,
+    busy_ms: f64,
+    window_ms: f64,
+}
+
+impl TabUsage {
+    /// Reads the result of [`TAB_USAGE_JS`]. A sample without a usable CPU
+    /// window keeps the previous CPU figure.
+    pub fn from_sample(
+        raw: &str,
+        previous: Option<&TabUsage>,
+        limits: Option<&HeavyTabSettings>,
+    ) -> serde_json::Result<Self> {
+        let sample: Sample = serde_json::from_str(raw)?;
+        let cpu_percent = if sample.window_ms >= MIN_WINDOW_MS {
+            Some((sample.busy_ms / sample.window_ms * 100.0).clamp(0.0, 100.0))
+        } else {
+            previous.and_then(|usage| usage.cpu_percent)
+        };
+        let mut usage = Self {
+            heap_used: sample.heap_used.map(|bytes| bytes as u64),
+            heap_limit: sample.heap_limit.map(|bytes| bytes as u64),
+            cpu_percent,
+            heavy: false,
+        };
+        usage.heavy = limits.is_some_and(|limits| usage.exceeds(limits));
+        Ok(usage)
+    }
+
+    fn exceeds(&self, limits: &HeavyTabSettings) -> bool {
+        self.heap_used
+            .is_some_and(|bytes| bytes >= limits.heap_mb.saturating_mul(1024 * 1024))
+            || self
+                .cpu_percent
+                .is_some_and(|cpu| cpu >= f64::from(limits.cpu_percent))
+    }
+}
+
+/// Memory and CPU of the client and every process it started.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessUsage {
+    pub processes: usize,
+    pub rss_bytes: u64,
+    /// Summed over processes, so it can pass 100 on several cores.
+    pub cpu_percent: Option<f64>,
+}
+
+/// Latest process tree sample; `None` where it cannot be taken.
+static PROCESS: Mutex<Option<ProcessUsage>> = Mutex::new(None);
+
+pub fn process_usage() -> Option<ProcessUsage> {
+    *PROCESS.lock().expect("process usage")
+}
+
+/// Samples the process tree and asks the event loop to sample every live tab,
+/// every few seconds. The heavy-tab limits are re-read on each tick so
+/// changes apply without a restart.
+pub fn spawn_monitor(proxy: EventLoopProxy<UserEvent>, config_path: Option<PathBuf>) {
+    std::thread::spawn(move || {
+        let mut previous: Option<(u64, Instant)> = None;
+        loop {
+            std::thread::sleep(SAMPLE_INTERVAL);
+            let usage = sample_process_tree(&mut previous);
+            *PROCESS.lock().expect("process usage") = usage;
+            let heavy_tab = config_path
+                .as_deref()
+                .map(|path| crate::settings::load_settings(path).heavy_tab)
+                .filter(|limits| limits.enabled);
+            if proxy
+                .send_event(UserEvent::SampleTabUsage { heavy_tab })
+                .is_err()
+            {
+                // Event loop has shut down.
+                return;
+            }
+        }
+    });
+}
+
+/// Clock ticks per second in `/proc/<pid>/stat`; fixed by the Linux ABI.
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS: f64 = 100.0;
+/// Resident set sizes are in pages; 4 KiB on the platforms the client ships for.
+#[cfg(target_os = "linux")]
+const PAGE_SIZE: u64 = 4096;
+
+#[cfg(target_os = "linux")]
+fn sample_process_tree(previous: &mut Option<(u64, Instant)>) -> Option<ProcessUsage> {
+    let mut table = Vec::new();
+    for entry in std::fs::read_dir("/proc")
+        .ok()?
+        .filter_map(|entry| entry.ok())
+    {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse().ok())
+        else {
+            continue;
+        };
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        if let Some((ppid, ticks, rss_pages)) = parse_stat(&stat) {
+            table.push((pid, ppid, ticks, rss_pages));
+        }
+    }
+    let tree = process_tree(&table, std::process::id());
+    let ticks: u64 = tree.iter().map(|process| process.2).sum();
+    let rss_pages: u64 = tree.iter().map(|process| process.3).sum();
+    let now = Instant::now();
+    let cpu_percent = previous.map(|(last_ticks, at)| {
+        let secs = now.duration_since(at).as_secs_f64().max(f64::EPSILON);
+        ticks.saturating_sub(last_ticks) as f64 / CLOCK_TICKS / secs * 100.0
+    });
+    *previous = Some((ticks, now));
+    Some(ProcessUsage {
+        processes: tree.len(),
+        rss_bytes: rss_pages * PAGE_SIZE,
+        cpu_percent,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn sample_process_tree(_previous: &mut Option<(u64, Instant)>) -> Option<ProcessUsage> {
+    None
+}
+
+/// Parent pid, user plus system CPU ticks and resident pages from the
+/// contents of `/proc/<pid>/stat`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat(stat: &str) -> Option<(u32, u64, u64)> {
+    // The command name may contain spaces; fields resume after its last ')'.
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    let ppid = fields.get(1)?.parse().ok()?;
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let rss_pages = fields.get(21)?.parse().ok()?;
+    Some((ppid, utime + stime, rss_pages))
+}
+
+/// `root` and its descendants in a `(pid, ppid, ..)` table.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn process_tree(table: &[(u32, u32, u64, u64)], root: u32) -> Vec<(u32, u32, u64, u64)> {
+    let mut tree: Vec<_> = table.iter().filter(|p| p.0 == root).copied().collect();
+    let mut next = 0;
+    while next < tree.len() {
+        let parent = tree[next].0;
+        tree.extend(
+            table
+                .iter()
+                .filter(|p| p.1 == parent && p.0 != root)
+                .copied(),
+        );
+        next += 1;
+    }
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TabUsage, parse_stat, process_tree};
+    use crate::settings::HeavyTabSettings;
+
+    fn limits() -> HeavyTabSettings {
+        HeavyTabSettings {
+            enabled: true,
+            heap_mb: 256,
+            cpu_percent: 80,
+        }
+    }
+
+    #[test]
+    fn flags_tabs_over_the_heap_limit() {
+        let usage = TabUsage::from_sample(
+            r#"{"heapUsed":314572800,"heapLimit":4294705152,"busyMs":250,"windowMs":5000}"#,
+            None,
+            Some(&limits()),
+        )
+        .unwrap();
+        assert_eq!(usage.heap_used, Some(300 * 1024 * 1024));
+        assert_eq!(usage.cpu_percent, Some(5.0));
+        assert!(usage.heavy);
+    }
+
+    #[test]
+    fn hidden_tabs_keep_their_last_cpu_reading() {
+        // A hidden WebKit tab has no heap figure and no fresh CPU window.
+        let hidden = TabUsage::from_sample(
+            r#"{"heapUsed":null,"heapLimit":null,"busyMs":0,"windowMs":0}"#,
+            Some(&TabUsage {
+                cpu_percent: Some(92.0),
+                ..TabUsage::default()
+            }),
+            Some(&limits()),
+        )
+        .unwrap();
+        assert_eq!(hidden.heap_used, None);
+        assert!(hidden.heavy);
+    }
+
+    #[test]
+    fn tabs_are_not_flagged_without_limits() {
+        let previous = TabUsage {
+            cpu_percent: Some(92.0),
+            ..TabUsage::default()
+        };
+        assert!(
+            !TabUsage::from_sample(r#"{"busyMs":0,"windowMs":0}"#, Some(&previous), None)
+                .unwrap()
+                .heavy
+        );
+    }
+
+    #[test]
+    fn parses_process_stats() {
+        let stat = "4242 (WebKit Web (1)) S 4000 4242 4000 0 -1 4194560 9000 0 0 0 120 30 0 0 20 0 25 0 512 2147483648 2048 18446744073709551615";
+        assert_eq!(parse_stat(stat), Some((4000, 150, 2048)));
+    }
+
+    #[test]
+    fn collects_a_process_and_its_descendants() {
+        let table = [
+            (4000, 1, 10, 100),
+            (4242, 4000, 150, 2048),
+            (4300, 4242, 5, 10),
+            (5000, 1, 99, 99),
+        ];
+        let pids: Vec<u32> = process_tree(&table, 4000).iter().map(|p| p.0).collect();
+        assert_eq!(pids, [4000, 4242, 4300]);
+    }
+}
@@ -0,0 +1,375 @@
+//! Mock JSON-RPC node and IPFS gateway for tests that exercise the network
+//! code end to end. Both listen on an ephemeral localhost port and speak just
+//! enough HTTP/1.1 for the blocking reqwest client.
+
+use alloy_primitives::{Address, LogData};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::bundle::{CidVersion, sha256_hex, unixfs_dag};
+use crate::config::{AppConfig, ConfigBuilder, ResolvedConfig};
+
+/// Chain id the mock node reports and `test_config` configures.
+pub const TEST_CHAIN_ID: u64 = 31337;
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+struct HttpResponse {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    fn json(value: Value) -> Self {
+        Self {
+            status: 200,
+            body: value.to_string().into_bytes(),
+        }
+    }
+}
+
+type Handler = Arc<dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync>;
+
+/// Serves `handler` until the test process exits and returns the base URL.
+fn serve(handler: Handler) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+    let url = format!(
+        "http://{}",
+        listener.local_addr().expect("mock server addr")
+    );
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let handler = handler.clone();
+            std::thread::spawn(move || {
+                let _ = handle_connection(stream, handler.as_ref());
+            });
+        }
+    });
+    url
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    handler: &(dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync),
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let response = handler(&HttpRequest { method, path, body });
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+#[derive(Default)]
+struct RpcNode {
+    block_number: u64,
+    results: HashMap<String, Value>,
+    logs: Vec<Value>,
+    /// Receipt polls answered with `null` before a sent transaction is mined.
+    pending_polls: u32,
+    /// Sent transactions and the polls left until they are mined.
+    transactions: HashMap<String, u32>,
+    calls: Vec<String>,
+    /// Requests still to be answered with HTTP 503.
+    failures: u32,
+}
+
+impl RpcNode {
+    fn answer(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "eth_chainId" => Ok(json!(format!("0x{TEST_CHAIN_ID:x}"))),
+            "eth_blockNumber" => Ok(json!(format!("0x{:x}", self.block_number))),
+            "eth_getLogs" => Ok(Value::Array(self.matching_logs(&params[0]))),
+            "eth_sendRawTransaction" => {
+                let raw = params[0].as_str().unwrap_or_default();
+                let hash = format!("0x{}", sha256_hex(raw.as_bytes()));
+                self.transactions.insert(hash.clone(), self.pending_polls);
+                Ok(json!(hash))
+            }
+            "eth_getTransactionReceipt" => {
+                let hash = params[0].as_str().unwrap_or_default();
+                let Some(remaining) = self.transactions.get_mut(hash) else {
+                    return Ok(Value::Null);
+                };
+                if *remaining > 0 {
+                    *remaining -= 1;
+                    return Ok(Value::Null);
+                }
+                Ok(json!({
+                    "transactionHash": hash,
+                    "blockNumber": format!("0x{:x}", self.block_number),
+                    "status": "0x1",
+                }))
+            }
+            _ => self
+                .results
+                .get(method)
+                .cloned()
+                .ok_or_else(|| (-32601, format!("method {method} not mocked"))),
+        }
+    }
+
+    fn matching_logs(&self, filter: &Value) -> Vec<Value> {
+        let quantity = |key: &str| {
+            filter[key]
+                .as_str()
+                .and_then(|raw| u64::from_str_radix(raw.trim_start_matches("0x"), 16).ok())
+        };
+        let from = quantity("fromBlock").unwrap_or(0);
+        let to = quantity("toBlock").unwrap_or(u64::MAX);
+        let address = filter["address"].as_str().map(str::to_ascii_lowercase);
+        let topic0 = filter["topics"][0].as_str().map(str::to_ascii_lowercase);
+        self.logs
+            .iter()
+            .filter(|log| {
+                let block = log["blockNumber"]
+                    .as_str()
+                    .and_then(|raw| u64::from_str_radix(raw.trim_start_matches("0x"), 16).ok())
+                    .unwrap_or(0);
+                (from..=to).contains(&block)
+                    && address
+                        .as_deref()
+                        .is_none_or(|address| log["address"] == address)
+                    && topic0
+                        .as_deref()
+                        .is_none_or(|topic| log["topics"][0] == topic)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Mock JSON-RPC node: answers `eth_chainId`, `eth_blockNumber`,
+/// `eth_getLogs` from canned logs, and walks sent transactions from pending to
+/// mined. Other methods answer from `set_result`.
+pub struct MockRpc {
+    pub url: String,
+    node: Arc<Mutex<RpcNode>>,
+}
+
+impl MockRpc {
+    pub fn start() -> Self {
+        let node = Arc::new(Mutex::new(RpcNode {
+            block_number: 1,
+            ..RpcNode::default()
+        }));
+        let handler_node = node.clone();
+        let url = serve(Arc::new(move |req: &HttpRequest| {
+            let mut node = handler_node.lock().expect("mock rpc");
+            if node.failures > 0 {
+                node.failures -= 1;
+                return HttpResponse {
+                    status: 503,
+                    body: Vec::new(),
+                };
+            }
+            let payload: Value = serde_json::from_slice(&req.body).unwrap_or(Value::Null);
+            let method = payload["method"].as_str().unwrap_or_default().to_string();
+            node.calls.push(method.clone());
+            let response = match node.answer(&method, &payload["params"]) {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": payload["id"], "result": result }),
+                Err((code, message)) => json!({
+                    "jsonrpc": "2.0",
+                    "id": payload["id"],
+                    "error": { "code": code, "message": message },
+                }),
+            };
+            HttpResponse::json(response)
+        }));
+        Self { url, node }
+    }
+
+    fn node(&self) -> std::sync::MutexGuard<'_, RpcNode> {
+        self.node.lock().expect("mock rpc")
+    }
+
+    pub fn set_block_number(&self, block_number: u64) {
+        self.node().block_number = block_number;
+    }
+
+    pub fn set_result(&self, method: &str, result: Value) {
+        self.node().results.insert(method.to_string(), result);
+    }
+
+    /// Adds a log, e.g. from `SolEvent::encode_log_data`, emitted by
+    /// `address` at `block_number`.
+    pub fn push_log(&self, address: Address, log: LogData, block_number: u64) {
+        let mut node = self.node();
+        let log_index = node.logs.len();
+        node.logs.push(json!({
+            "address": format!("{address:#x}"),
+            "topics": log.topics().iter().map(|topic| format!("{topic:#x}")).collect::<Vec<_>>(),
+            "data": format!("0x{}", hex::encode(&log.data)),
+            "blockNumber": format!("0x{block_number:x}"),
+            "logIndex": format!("0x{log_index:x}"),
+        }));
+    }
+
+    /// Sent transactions stay pending for `polls` receipt requests.
+    pub fn set_pending_polls(&self, polls: u32) {
+        self.node().pending_polls = polls;
+    }
+
+    /// Answers the next `count` requests with HTTP 503.
+    pub fn fail_next(&self, count: u32) {
+        self.node().failures = count;
+    }
+
+    /// JSON-RPC methods received so far, in order.
+    pub fn calls(&self) -> Vec<String> {
+        self.node().calls.clone()
+    }
+}
+
+/// Mock IPFS gateway serving fixture bundles at `/ipfs/<cid>/<path>`.
+pub struct MockGateway {
+    pub url: String,
+    bundles: Arc<Mutex<HashMap<String, HashMap<String, Vec<u8>>>>>,
+    /// Blocks of every added bundle by CIDv1, for trustless `?format=raw` requests.
+    blocks: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    forged_block: Arc<Mutex<Option<Vec<u8>>>>,
+}
+
+impl MockGateway {
+    pub fn start() -> Self {
+        let bundles: Arc<Mutex<HashMap<String, HashMap<String, Vec<u8>>>>> = Arc::default();
+        let blocks: Arc<Mutex<HashMap<String, Vec<u8>>>> = Arc::default();
+        let forged_block: Arc<Mutex<Option<Vec<u8>>>> = Arc::default();
+        let (handler_bundles, handler_blocks, handler_forged) =
+            (bundles.clone(), blocks.clone(), forged_block.clone());
+        let url = serve(Arc::new(move |req: &HttpRequest| {
+            let rest = req
+                .path
+                .strip_prefix("/ipfs/")
+                .filter(|_| req.method == "GET");
+            let file = match rest.and_then(|rest| rest.strip_suffix("?format=raw")) {
+                Some(cid) => handler_forged
+                    .lock()
+                    .expect("mock gateway")
+                    .clone()
+                    .or_else(|| {
+                        handler_blocks
+                            .lock()
+                            .expect("mock gateway")
+                            .get(cid)
+                            .cloned()
+                    }),
+                None => rest
+                    .and_then(|rest| rest.split_once('/'))
+                    .and_then(|(cid, path)| {
+                        let bundles = handler_bundles.lock().expect("mock gateway");
+                        bundles.get(cid)?.get(path).cloned()
+                    }),
+            };
+            match file {
+                Some(body) => HttpResponse { status: 200, body },
+                None => HttpResponse {
+                    status: 404,
+                    body: b"not found".to_vec(),
+                },
+            }
+        }));
+        Self {
+            url,
+            bundles,
+            blocks,
+            forged_block,
+        }
+    }
+
+    /// Publishes a bundle of `files` with a matching `manifest.json` and
+    /// returns its root CID.
+    pub fn add_bundle(&self, files: &[(&str, &[u8])]) -> String {
+        let entries: Vec<Value> = files
+            .iter()
+            .map(|(path, bytes)| json!({ "path": path, "bytes": bytes.len(), "sha256": sha256_hex(bytes) }))
+            .collect();
+        let manifest = json!({ "name": "Fixture", "files": entries }).to_string();
+        let mut contents: HashMap<String, Vec<u8>> = files
+            .iter()
+            .map(|(path, bytes)| (path.to_string(), bytes.to_vec()))
+            .collect();
+        contents.insert("manifest.json".to_string(), manifest.into_bytes());
+        let mut blocks = self.blocks.lock().expect("mock gateway");
+        let root_cid = unixfs_dag(
+            contents.keys().cloned(),
+            CidVersion::V1,
+            &|path| Ok(contents[path].clone()),
+            &mut |cid, block| {
+                let cid = crate::verified_fetch::Cid::from_bytes(cid).expect("fixture CID");
+                blocks.insert(cid.to_string(), block.to_vec());
+            },
+        )
+        .expect("fixture root CID");
+        drop(blocks);
+        self.bundles
+            .lock()
+            .expect("mock gateway")
+            .insert(root_cid.clone(), contents);
+        root_cid
+    }
+
+    /// Answers every block request with `bytes`, as a lying trustless gateway would.
+    pub fn forge_blocks(&self, bytes: &[u8]) {
+        *self.forged_block.lock().expect("mock gateway") = Some(bytes.to_vec());
+    }
+
+    /// Serves `bytes` for `path` of bundle `root_cid` without touching its
+    /// manifest, as a misbehaving gateway would.
+    pub fn tamper(&self, root_cid: &str, path: &str, bytes: &[u8]) {
+        if let Some(bundle) = self.bundles.lock().expect("mock gateway").get_mut(root_cid) {
+            bundle.insert(path.to_string(), bytes.to_vec());
+        }
+    }
+}
+
+/// Config pointing at the mocks: local-node IPFS fetches through `gateway`,
+/// bundles cached under `cache_dir`.
+pub fn test_config(rpc: &MockRpc, gateway: &MockGateway, cache_dir: &Path) -> ResolvedConfig {
+    let config: AppConfig = serde_json::from_value(json!({
+        "chainId": TEST_CHAIN_ID,
+        "rpcUrl": rpc.url,
+        "dappRegistry": "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+        "ipfsGateway": gateway.url,
+        "ipfsFetchBackend": "localnode",
+    }))
+    .expect("test config");
+    let mut resolved = ConfigBuilder::new(config, None).build();
+    // Environment overrides must not point tests at a real node.
+    resolved.rpc_url = rpc.url.clone();
+    resolved.cache_dir = cache_dir.to_path_buf();
+    resolved
+}
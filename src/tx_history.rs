@@ -0,0 +1,522 @@
+use alloy_primitives::{U256, utils::format_units};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::decoder::AbiIndex;
+use crate::state::{AppState, DappIdentity};
+
+/// Transactions sent through the client, kept next to `settings.json`.
+const HISTORY_FILE: &str = "tx-history.json";
+/// Oldest records are dropped past this many.
+const MAX_RECORDS: usize = 10_000;
+/// Stop waiting for a receipt after this long; the record stays pending.
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Serializes read-modify-write cycles of the history file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+/// Transaction requests in flight, with the dapp that sent them and its
+/// bundle ABIs.
+type Sender = (DappIdentity, Option<Arc<AbiIndex>>);
+static PENDING: Mutex<Option<HashMap<(String, u64), Sender>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TxStatus {
+    Pending,
+    Confirmed,
+    Failed,
+}
+
+/// One transaction the client sent, filled in from its receipt once mined.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxRecord {
+    pub hash: String,
+    pub chain_id: u64,
+    /// Dapp name, or "VibeFi" for transactions the client sent itself.
+    pub dapp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dapp_id: Option<String>,
+    /// Unix seconds.
+    pub sent_at: u64,
+    pub status: TxStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    /// Wei, as a decimal string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
+    /// Wei per gas actually paid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    /// Function called, decoded with the sending dapp's bundle ABIs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call: Option<Value>,
+    /// Receipt logs the bundle ABIs decode, as event names and arguments.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<Value>,
+}
+
+impl TxRecord {
+    /// Gas fee in wei; zero until the receipt is in.
+    fn fee(&self) -> U256 {
+        let price = self
+            .effective_gas_price
+            .as_deref()
+            .and_then(|price| price.parse::<U256>().ok())
+            .unwrap_or_default();
+        price * U256::from(self.gas_used.unwrap_or_default())
+    }
+
+    fn value_wei(&self) -> U256 {
+        self.value
+            .as_deref()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Totals for one group of transactions. Amounts are per chain, since each
+/// chain pays in its own native coin.
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendTotals {
+    pub transactions: u64,
+    pub confirmed: u64,
+    pub failed: u64,
+    pub gas_used: u64,
+    /// Native coin, formatted with 18 decimals.
+    pub fees: String,
+    pub value: String,
+    #[serde(skip)]
+    fee_wei: U256,
+    #[serde(skip)]
+    value_wei: U256,
+}
+
+impl SpendTotals {
+    fn add(&mut self, record: &TxRecord) {
+        self.transactions += 1;
+        match record.status {
+            TxStatus::Confirmed => self.confirmed += 1,
+            TxStatus::Failed => self.failed += 1,
+            TxStatus::Pending => {}
+        }
+        self.gas_used += record.gas_used.unwrap_or_default();
+        self.fee_wei += record.fee();
+        // Reverted transactions transfer nothing.
+        if record.status != TxStatus::Failed {
+            self.value_wei += record.value_wei();
+        }
+        self.fees = ether(self.fee_wei);
+        self.value = ether(self.value_wei);
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSpend {
+    pub chain_id: u64,
+    #[serde(flatten)]
+    pub totals: SpendTotals,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DappSpend {
+    pub dapp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dapp_id: Option<String>,
+    pub chain_id: u64,
+    pub last_sent_at: u64,
+    #[serde(flatten)]
+    pub totals: SpendTotals,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendReport {
+    /// Unix seconds; `None` covers the whole history.
+    pub since: Option<u64>,
+    pub by_chain: Vec<ChainSpend>,
+    pub by_dapp: Vec<DappSpend>,
+}
+
+/// Notes the dapp behind a transaction request until its hash comes back.
+pub fn expect(webview_id: &str, ipc_id: u64, dapp: DappIdentity) {
+    PENDING
+        .lock()
+        .expect("tx history")
+        .get_or_insert_with(HashMap::new)
+        .insert(
+            (webview_id.to_string(), ipc_id),
+            (dapp, crate::decoder::tab_abis(webview_id)),
+        );
+}
+
+/// Records the transaction a request resolved to; `hash` is `None` when the
+/// request failed.
+pub fn on_result(state: &AppState, webview_id: &str, ipc_id: u64, hash: Option<&str>) {
+    let sender = PENDING
+        .lock()
+        .expect("tx history")
+        .as_mut()
+        .and_then(|pending| pending.remove(&(webview_id.to_string(), ipc_id)));
+    if let (Some((dapp, abis)), Some(hash)) = (sender, hash) {
+        track(state, Some(&dapp), abis, hash);
+    }
+}
+
+/// Stores `hash` as pending and fills it in from its receipt once mined.
+/// `dapp` is `None` for transactions the client sent itself.
+pub fn record(state: &AppState, dapp: Option<&DappIdentity>, hash: &str) {
+    track(state, dapp, None, hash);
+}
+
+/// [`record`], decoding the call and its events with `abis` once mined.
+fn track(state: &AppState, dapp: Option<&DappIdentity>, abis: Option<Arc<AbiIndex>>, hash: &str) {
+    let Some(config_path) = config_path(state) else {
+        return;
+    };
+    let entry = TxRecord {
+        hash: hash.to_string(),
+        chain_id: state.wallet.lock().expect("wallet").chain.chain_id,
+        dapp: dapp.map_or_else(|| "VibeFi".to_string(), |dapp| dapp.name.clone()),
+        dapp_id: dapp.and_then(|dapp| dapp.dapp_id.clone()),
+        sent_at: now(),
+        status: TxStatus::Pending,
+        from: None,
+        to: None,
+        value: None,
+        gas_used: None,
+        effective_gas_price: None,
+        block_number: None,
+        call: None,
+        events: Vec::new(),
+    };
+    // A transaction signed offline is recorded again when it is broadcast.
+    let recorded = update(&config_path, |records| {
+        if !records.iter().any(|record| record.hash == entry.hash) {
+            records.push(entry);
+        }
+    });
+    if let Err(err) = recorded {
+        tracing::warn!(hash, error = %format!("{err:#}"), "failed to record transaction");
+        return;
+    }
+    let state = state.clone();
+    let hash = hash.to_string();
+    std::thread::spawn(move || {
+        let mut blocks = state.blocks.subscribe(&state);
+        let started = Instant::now();
+        while let Some(_block) =
+            blocks.next_block(RECEIPT_TIMEOUT.saturating_sub(started.elapsed()))
+        {
+            match settle(&state, &config_path, &hash, abis.as_deref()) {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(err) => {
+                    tracing::debug!(hash, error = %format!("{err:#}"), "tx history receipt poll failed")
+                }
+            }
+            if started.elapsed() >= RECEIPT_TIMEOUT {
+                break;
+            }
+        }
+        tracing::info!(hash, "transaction not mined; left pending in history");
+    });
+}
+
+/// Fills in the record of `hash` once it has a receipt. Returns whether it did.
+fn settle(
+    state: &AppState,
+    config_path: &Path,
+    hash: &str,
+    abis: Option<&AbiIndex>,
+) -> Result<bool> {
+    let receipt = crate::ipc::rpc_request(state, "eth_getTransactionReceipt", json!([hash]))
+        .context("eth_getTransactionReceipt failed")?;
+    if receipt.is_null() {
+        return Ok(false);
+    }
+    let tx = crate::ipc::rpc_request(state, "eth_getTransactionByHash", json!([hash]))
+        .context("eth_getTransactionByHash failed")?;
+    let call = abis.and_then(|abis| {
+        let input = tx.get("input").and_then(Value::as_str)?;
+        abis.decode_call(&hex::decode(input.trim_start_matches("0x")).ok()?)
+    });
+    let events: Vec<Value> = abis
+        .zip(receipt.get("logs").and_then(Value::as_array))
+        .map(|(abis, logs)| logs.iter().filter_map(|log| abis.decode_log(log)).collect())
+        .unwrap_or_default();
+    update(config_path, |records| {
+        let Some(record) = records.iter_mut().find(|record| record.hash == hash) else {
+            return;
+        };
+        record.status = match receipt.get("status").and_then(Value::as_str) {
+            Some("0x1") => TxStatus::Confirmed,
+            _ => TxStatus::Failed,
+        };
+        record.from = receipt
+            .get("from")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        record.to = receipt
+            .get("to")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        record.gas_used = quantity(&receipt["gasUsed"]).and_then(|gas| u64::try_from(gas).ok());
+        record.effective_gas_price = quantity(&receipt["effectiveGasPrice"])
+            .or_else(|| quantity(&tx["gasPrice"]))
+            .map(|price| price.to_string());
+        record.block_number =
+            quantity(&receipt["blockNumber"]).and_then(|block| u64::try_from(block).ok());
+        record.value = quantity(&tx["value"]).map(|value| value.to_string());
+        record.call = call;
+        record.events = events;
+    })?;
+    Ok(true)
+}
+
+/// Spend report over records sent at or after `since`.
+pub fn spend_report(records: &[TxRecord], since: Option<u64>) -> SpendReport {
+    let mut by_chain: BTreeMap<u64, SpendTotals> = BTreeMap::new();
+    let mut by_dapp: BTreeMap<(String, Option<String>, u64), (u64, SpendTotals)> = BTreeMap::new();
+    for record in records
+        .iter()
+        .filter(|record| since.is_none_or(|since| record.sent_at >= since))
+    {
+        by_chain.entry(record.chain_id).or_default().add(record);
+        let (last_sent_at, totals) = by_dapp
+            .entry((record.dapp.clone(), record.dapp_id.clone(), record.chain_id))
+            .or_default();
+        *last_sent_at = (*last_sent_at).max(record.sent_at);
+        totals.add(record);
+    }
+    let mut by_dapp: Vec<DappSpend> = by_dapp
+        .into_iter()
+        .map(
+            |((dapp, dapp_id, chain_id), (last_sent_at, totals))| DappSpend {
+                dapp,
+                dapp_id,
+                chain_id,
+                last_sent_at,
+                totals,
+            },
+        )
+        .collect();
+    by_dapp.sort_by(|a, b| b.totals.fee_wei.cmp(&a.totals.fee_wei));
+    SpendReport {
+        since,
+        by_chain: by_chain
+            .into_iter()
+            .map(|(chain_id, totals)| ChainSpend { chain_id, totals })
+            .collect(),
+        by_dapp,
+    }
+}
+
+/// Records sent at or after `since` as CSV, one row per transaction.
+pub fn to_csv(records: &[TxRecord], since: Option<u64>) -> String {
+    let mut csv = String::from(
+        "sent_at_unix,chain_id,dapp,dapp_id,hash,status,from,to,value_wei,gas_used,effective_gas_price_wei,fee_wei,block_number,function,events\n",
+    );
+    for record in records
+        .iter()
+        .filter(|record| since.is_none_or(|since| record.sent_at >= since))
+    {
+        let row = [
+            record.sent_at.to_string(),
+            record.chain_id.to_string(),
+            csv_field(&record.dapp),
+            csv_field(record.dapp_id.as_deref().unwrap_or_default()),
+            record.hash.clone(),
+            format!("{:?}", record.status).to_lowercase(),
+            record.from.clone().unwrap_or_default(),
+            record.to.clone().unwrap_or_default(),
+            record.value.clone().unwrap_or_default(),
+            record
+                .gas_used
+                .map(|gas| gas.to_string())
+                .unwrap_or_default(),
+            record.effective_gas_price.clone().unwrap_or_default(),
+            record.fee().to_string(),
+            record
+                .block_number
+                .map(|block| block.to_string())
+                .unwrap_or_default(),
+            csv_field(
+                record
+                    .call
+                    .as_ref()
+                    .and_then(|call| call["signature"].as_str())
+                    .unwrap_or_default(),
+            ),
+            csv_field(
+                &record
+                    .events
+                    .iter()
+                    .filter_map(|event| event["event"].as_str())
+                    .collect::<Vec<_>>()
+                    .join(";"),
+            ),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Start of a report range: `24h`, `7d`, `30d`, `365d`, or `all`.
+pub fn range_start(range: &str) -> Result<Option<u64>> {
+    let days = match range {
+        "all" => return Ok(None),
+        "24h" => 1,
+        "7d" => 7,
+        "30d" => 30,
+        "365d" => 365,
+        other => return Err(anyhow!("unknown report range: {other}")),
+    };
+    Ok(Some(now().saturating_sub(days * 24 * 60 * 60)))
+}
+
+pub fn load(config_path: &Path) -> Vec<TxRecord> {
+    std::fs::read(config_path.with_file_name(HISTORY_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_slice(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn update(config_path: &Path, change: impl FnOnce(&mut Vec<TxRecord>)) -> Result<()> {
+    let _guard = FILE_LOCK.lock().expect("tx history file");
+    let mut records = load(config_path);
+    change(&mut records);
+    if records.len() > MAX_RECORDS {
+        records.drain(..records.len() - MAX_RECORDS);
+    }
+    let path = config_path.with_file_name(HISTORY_FILE);
+    std::fs::write(&path, serde_json::to_vec(&records)?)
+        .with_context(|| format!("write {}", path.display()))
+}
+
+fn config_path(state: &AppState) -> Option<std::path::PathBuf> {
+    state.resolved.as_ref()?.config_path.clone()
+}
+
+fn quantity(value: &Value) -> Option<U256> {
+    let raw = value.as_str()?;
+    U256::from_str_radix(raw.strip_prefix("0x")?, 16).ok()
+}
+
+fn ether(wei: U256) -> String {
+    format_units(wei, 18).unwrap_or_default()
+}
+
+fn csv_field(raw: &str) -> String {
+    if raw.contains([',', '"', '\n']) {
+        format!("\"{}\"", raw.replace('"', "\"\""))
+    } else {
+        raw.to_string()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TxRecord, TxStatus, spend_report, to_csv};
+
+    fn record(dapp: &str, sent_at: u64, status: TxStatus, value: &str) -> TxRecord {
+        TxRecord {
+            hash: format!("0x{sent_at:064x}"),
+            chain_id: 1,
+            dapp: dapp.to_string(),
+            dapp_id: None,
+            sent_at,
+            status,
+            from: None,
+            to: None,
+            value: Some(value.to_string()),
+            gas_used: Some(21_000),
+            effective_gas_price: Some("1000000000".to_string()),
+            block_number: Some(sent_at),
+            call: None,
+            events: Vec::new(),
+        }
+    }
+
+    fn records() -> Vec<TxRecord> {
+        vec![
+            record("Old", 10, TxStatus::Confirmed, "5"),
+            record("Swap, Inc", 100, TxStatus::Confirmed, "1000000000000000000"),
+            record("Swap, Inc", 200, TxStatus::Failed, "1000000000000000000"),
+            record("Mint", 300, TxStatus::Confirmed, "0"),
+        ]
+    }
+
+    #[test]
+    fn counts_transactions_per_chain_within_range() {
+        let report = spend_report(&records(), Some(100));
+        assert_eq!(report.by_chain.len(), 1);
+        let chain = &report.by_chain[0].totals;
+        assert_eq!(
+            (chain.transactions, chain.confirmed, chain.failed),
+            (3, 2, 1)
+        );
+    }
+
+    #[test]
+    fn totals_fees_and_value_moved() {
+        let report = spend_report(&records(), Some(100));
+        let chain = &report.by_chain[0].totals;
+        assert_eq!(chain.gas_used, 63_000);
+        assert_eq!(chain.fees, "0.000063000000000000");
+        // The reverted transfer moved nothing.
+        assert_eq!(chain.value, "1.000000000000000000");
+    }
+
+    #[test]
+    fn reports_spend_per_dapp() {
+        let report = spend_report(&records(), Some(100));
+        assert_eq!(report.by_dapp[0].dapp, "Swap, Inc");
+        assert_eq!(report.by_dapp[0].last_sent_at, 200);
+        assert_eq!(report.by_dapp[0].totals.transactions, 2);
+    }
+
+    #[test]
+    fn exports_calls_and_events_to_csv_within_range() {
+        let mut records = records();
+        records[3].call =
+            Some(serde_json::json!({ "function": "mint", "signature": "mint(address,uint256)" }));
+        records[3].events = vec![
+            serde_json::json!({ "event": "Transfer" }),
+            serde_json::json!({ "event": "Minted" }),
+        ];
+        let csv = to_csv(&records, Some(300));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("300,1,Mint,,0x"));
+        assert!(lines[1].contains(",confirmed,"));
+        assert!(lines[1].ends_with(",mint(address,uint256),Transfer;Minted"));
+    }
+
+    #[test]
+    fn quotes_csv_fields_with_commas() {
+        assert!(to_csv(&records(), None).contains("\"Swap, Inc\""));
+    }
+}
@@ -1,23 +1,68 @@
 use anyhow::Result;
+use base64::Engine;
 use serde::Serialize;
 use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use wry::WebView;
 
 use crate::ipc_contract::{
     HostDispatchEnvelope, HostDispatchKind, ProviderEventPayload, RpcResponseError,
-    RpcResponsePayload, RpcStatusPayload, TabbarUpdatePayload, WalletconnectPairingPayload,
+    RpcResponsePayload, TabbarUpdatePayload, WalletconnectPairingPayload,
 };
 
+thread_local! {
+    /// Envelopes queued per webview id since the last flush, in dispatch order.
+    /// Webviews live on the event loop thread, so this never crosses threads.
+    static PENDING: RefCell<HashMap<String, Vec<Value>>> = RefCell::new(HashMap::new());
+}
+
 fn dispatch<T: Serialize>(webview: &WebView, kind: HostDispatchKind, payload: T) -> Result<()> {
-    let envelope = HostDispatchEnvelope { kind, payload };
-    let script = format!(
-        "window.__VibefiHostDispatch({});",
-        serde_json::to_string(&envelope)?
-    );
-    webview.evaluate_script(&script)?;
+    let envelope = serde_json::to_value(HostDispatchEnvelope { kind, payload })?;
+    let id = webview.id().to_string();
+    PENDING.with(|pending| pending.borrow_mut().entry(id).or_default().push(envelope));
     Ok(())
 }
 
+/// Builds one script delivering `envelopes` in order. The JSON travels as
+/// base64 so no payload string is ever spliced into script source.
+fn batch_script(envelopes: &[Value]) -> Result<String> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(serde_json::to_vec(envelopes)?);
+    Ok(format!(
+        "(function() {{ \
+         var raw = atob('{encoded}'); \
+         var bytes = new Uint8Array(raw.length); \
+         for (var i = 0; i < raw.length; i++) bytes[i] = raw.charCodeAt(i); \
+         var batch = JSON.parse(new TextDecoder().decode(bytes)); \
+         for (var j = 0; j < batch.length; j++) window.__VibefiHostDispatch(batch[j]); \
+         }})();"
+    ))
+}
+
+/// Delivers everything queued by [`dispatch`], one script per webview. Called
+/// once the event loop has drained its events so bursts collapse into a single
+/// evaluation.
+pub fn flush_pending<'a>(lookup: impl Fn(&str) -> Option<&'a WebView>) {
+    let pending = PENDING.with(|pending| std::mem::take(&mut *pending.borrow_mut()));
+    for (id, envelopes) in pending {
+        let Some(webview) = lookup(&id) else {
+            tracing::debug!(
+                id,
+                dropped = envelopes.len(),
+                "webview gone before dispatch flush"
+            );
+            continue;
+        };
+        let result = batch_script(&envelopes).and_then(|script| {
+            webview.evaluate_script(&script)?;
+            Ok(())
+        });
+        if let Err(err) = result {
+            tracing::warn!(id, error = %err, "failed to flush host dispatch batch");
+        }
+    }
+}
+
 pub fn respond_ok(webview: &WebView, id: u64, value: Value) -> Result<()> {
     dispatch(
         webview,
@@ -38,8 +83,9 @@ pub fn respond_err(webview: &WebView, id: u64, message: &str) -> Result<()> {
             id,
             result: Value::Null,
             error: Some(RpcResponseError {
-                code: -32601,
+                code: crate::connectivity::error_code(message),
                 message: message.to_string(),
+                data: crate::error_hints::error_data(message),
             }),
         },
     )
@@ -80,21 +126,50 @@ pub fn emit_walletconnect_pairing(webview: &WebView, uri: &str, qr_svg: &str) {
     }
 }
 
-pub fn update_tabs(webview: &WebView, tabs: Vec<Value>, active_index: usize) -> Result<()> {
-    dispatch(
-        webview,
-        HostDispatchKind::TabbarUpdate,
-        TabbarUpdatePayload { tabs, active_index },
-    )
+pub fn emit_walletconnect_relay(webview: &WebView, status: &crate::walletconnect::RelayStatus) {
+    if let Err(err) = dispatch(webview, HostDispatchKind::WalletconnectRelay, status) {
+        tracing::warn!(error = %err, "failed to dispatch walletconnect relay status");
+    }
 }
 
-pub fn update_rpc_status(webview: &WebView, webview_id: &str, pending_count: u32) -> Result<()> {
+pub fn update_tabs(
+    webview: &WebView,
+    tabs: Vec<Value>,
+    active_index: usize,
+    fork_label: Option<String>,
+) -> Result<()> {
     dispatch(
         webview,
-        HostDispatchKind::RpcStatus,
-        RpcStatusPayload {
-            webview_id: webview_id.to_string(),
-            pending_count,
+        HostDispatchKind::TabbarUpdate,
+        TabbarUpdatePayload {
+            tabs,
+            active_index,
+            fork_label,
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::batch_script;
+    use base64::Engine;
+    use serde_json::json;
+
+    #[test]
+    fn batch_script_never_embeds_payload_text() {
+        let envelopes = vec![json!({
+            "kind": "providerEvent",
+            "payload": { "event": "x", "value": "</script><script>alert(1)</script>');" },
+        })];
+        let script = batch_script(&envelopes).unwrap();
+        assert!(!script.contains("</script>"));
+        assert!(!script.contains("alert"));
+
+        let encoded = script.split('\'').nth(1).unwrap();
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        let parsed: Vec<serde_json::Value> = serde_json::from_slice(&decoded).unwrap();
+        assert_eq!(parsed, envelopes);
+    }
+}
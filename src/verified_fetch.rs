@@ -0,0 +1,757 @@
+//! Verified IPFS retrieval for the `helia` fetch backend, without a helper
+//! process. Blocks are requested from trustless HTTP gateways as
+//! `application/vnd.ipld.raw`, several gateways at once, and the first
+//! answer that hashes to the block's CID wins. Files are put back together
+//! by walking their UnixFS DAG from the root CID, so no gateway can swap
+//! the content. When none of the gateways has a block, the delegated routers
+//! are asked for HTTP providers of it.
+
+use anyhow::{Context, Result, anyhow, bail};
+use reqwest::blocking::Client as HttpClient;
+use reqwest::header::ACCEPT;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::{Mutex, mpsc};
+use std::time::Duration;
+
+use crate::bundle::base32_lower;
+use crate::config::ResolvedConfig;
+use crate::download::read_limited;
+
+const DEFAULT_TIMEOUT_MS: u64 = 15_000;
+/// Gateways asked for the same block at once; the next ones are asked only
+/// when all of these fail.
+const RACED_GATEWAYS: usize = 3;
+/// Child blocks of one file node fetched at once.
+const BLOCK_CONCURRENCY: usize = 8;
+/// Largest block gateways serve; the importer's own chunks are 256 KiB.
+const MAX_BLOCK_BYTES: u64 = 2 * 1024 * 1024;
+const MAX_ROUTER_RESPONSE_BYTES: u64 = 1024 * 1024;
+const CODEC_RAW: u64 = 0x55;
+const CODEC_DAG_PB: u64 = 0x70;
+const MULTIHASH_IDENTITY: u64 = 0x00;
+const MULTIHASH_SHA2_256: u64 = 0x12;
+const UNIXFS_RAW: u64 = 0;
+const UNIXFS_DIRECTORY: u64 = 1;
+const UNIXFS_FILE: u64 = 2;
+const UNIXFS_HAMT_SHARD: u64 = 5;
+
+/// A CID with a supported multihash: sha2-256, or identity for inlined blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Cid {
+    codec: u64,
+    hash: u64,
+    digest: Vec<u8>,
+}
+
+impl Cid {
+    /// Reads a base58 CIDv0 (`Qm…`) or a base32 CIDv1 (`b…`).
+    pub(crate) fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let bytes = if text.len() == 46 && text.starts_with("Qm") {
+            base58btc_decode(text)?
+        } else if let Some(rest) = text.strip_prefix('b') {
+            base32_decode(rest)?
+        } else {
+            bail!("unsupported CID {text:?}: only CIDv0 and base32 CIDv1 are read");
+        };
+        Self::from_bytes(&bytes).with_context(|| format!("invalid CID {text}"))
+    }
+
+    /// Reads a binary CID as dag-pb links hold it.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        // A CIDv0 is a bare sha2-256 multihash of a dag-pb block.
+        if bytes.len() == 34 && bytes[..2] == [0x12, 0x20] {
+            return Ok(Self {
+                codec: CODEC_DAG_PB,
+                hash: MULTIHASH_SHA2_256,
+                digest: bytes[2..].to_vec(),
+            });
+        }
+        let mut reader = ProtoReader::new(bytes);
+        if reader.varint()? != 1 {
+            bail!("unsupported CID version");
+        }
+        let codec = reader.varint()?;
+        let hash = reader.varint()?;
+        let len = reader.varint()?;
+        let digest = reader.take(len)?.to_vec();
+        if !reader.is_empty() {
+            bail!("trailing bytes after CID");
+        }
+        match hash {
+            MULTIHASH_SHA2_256 if digest.len() == 32 => {}
+            MULTIHASH_IDENTITY => {}
+            _ => bail!("unsupported multihash 0x{hash:x}"),
+        }
+        Ok(Self {
+            codec,
+            hash,
+            digest,
+        })
+    }
+
+    /// Whether `block` is the content this CID names.
+    fn matches(&self, block: &[u8]) -> bool {
+        match self.hash {
+            MULTIHASH_SHA2_256 => Sha256::digest(block).as_slice() == self.digest,
+            _ => block == self.digest,
+        }
+    }
+}
+
+/// The CIDv1 base32 form, which gateways accept for CIDv0 content as well.
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = Vec::with_capacity(self.digest.len() + 4);
+        for value in [1, self.codec, self.hash, self.digest.len() as u64] {
+            put_varint(&mut bytes, value);
+        }
+        bytes.extend_from_slice(&self.digest);
+        write!(f, "b{}", base32_lower(&bytes))
+    }
+}
+
+/// Fetches files by root CID and path from the configured gateways.
+pub struct VerifiedFetcher {
+    http: HttpClient,
+    /// In the order they are asked; a gateway that serves a block moves to
+    /// the front.
+    gateways: Mutex<Vec<String>>,
+    routers: Vec<String>,
+    timeout: Duration,
+}
+
+impl VerifiedFetcher {
+    /// A fetcher for the `ipfsHeliaGateways`, `ipfsHeliaRouters` and
+    /// `ipfsHeliaTimeoutMs` of `resolved`.
+    pub fn new(resolved: &ResolvedConfig) -> Self {
+        let trimmed = |urls: &[String]| -> Vec<String> {
+            urls.iter()
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty())
+                .collect()
+        };
+        let timeout_ms = match resolved.ipfs_helia_timeout_ms {
+            0 => DEFAULT_TIMEOUT_MS,
+            ms => ms,
+        };
+        Self {
+            http: resolved.http_client.clone(),
+            gateways: Mutex::new(trimmed(&resolved.ipfs_helia_gateways)),
+            routers: trimmed(&resolved.ipfs_helia_routers),
+            timeout: Duration::from_millis(timeout_ms),
+        }
+    }
+
+    /// The file at `path` below `root_cid`, or the root itself when `path`
+    /// is empty. Fails as soon as it grows past `max_bytes`.
+    pub fn fetch(&self, root_cid: &str, path: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        let mut cid = Cid::parse(root_cid)?;
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            cid = self
+                .child(&cid, name)
+                .with_context(|| format!("resolve {path} in {root_cid}"))?;
+        }
+        let mut out = Vec::new();
+        let block = self.block(&cid)?;
+        self.append_file(&cid, &block, max_bytes, &mut out)
+            .with_context(|| format!("fetch {root_cid}/{path}"))?;
+        Ok(out)
+    }
+
+    /// CID of the entry `name` in directory `dir`.
+    fn child(&self, dir: &Cid, name: &str) -> Result<Cid> {
+        if dir.codec != CODEC_DAG_PB {
+            bail!("{dir} is not a directory");
+        }
+        let block = self.block(dir)?;
+        let node = PbNode::decode(&block)?;
+        match UnixfsData::decode(node.data)?.kind {
+            UNIXFS_DIRECTORY => {}
+            UNIXFS_HAMT_SHARD => bail!("{dir} is a sharded directory, which is not supported"),
+            _ => bail!("{dir} is not a directory"),
+        }
+        let link = node
+            .links
+            .iter()
+            .find(|link| link.name == name.as_bytes())
+            .ok_or_else(|| anyhow!("{name} not found"))?;
+        Cid::from_bytes(link.hash)
+    }
+
+    /// Appends the file bytes below `block` in DAG order.
+    fn append_file(
+        &self,
+        cid: &Cid,
+        block: &[u8],
+        max_bytes: u64,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        match cid.codec {
+            CODEC_RAW => return append_limited(out, block, max_bytes),
+            CODEC_DAG_PB => {}
+            codec => bail!("unsupported codec 0x{codec:x} in {cid}"),
+        }
+        let node = PbNode::decode(block)?;
+        let data = UnixfsData::decode(node.data)?;
+        match data.kind {
+            UNIXFS_FILE | UNIXFS_RAW => {}
+            UNIXFS_DIRECTORY | UNIXFS_HAMT_SHARD => bail!("{cid} is a directory"),
+            kind => bail!("unsupported UnixFS node type {kind} in {cid}"),
+        }
+        if data
+            .file_size
+            .is_some_and(|size| out.len() as u64 + size > max_bytes)
+        {
+            bail!("file exceeds {max_bytes} bytes");
+        }
+        append_limited(out, data.data, max_bytes)?;
+        let children = node
+            .links
+            .iter()
+            .map(|link| Cid::from_bytes(link.hash))
+            .collect::<Result<Vec<_>>>()?;
+        for batch in children.chunks(BLOCK_CONCURRENCY) {
+            let blocks = self.blocks(batch)?;
+            for (child, block) in batch.iter().zip(&blocks) {
+                self.append_file(child, block, max_bytes, out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn blocks(&self, cids: &[Cid]) -> Result<Vec<Vec<u8>>> {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = cids
+                .iter()
+                .map(|cid| scope.spawn(move || self.block(cid)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow!("block fetch panicked")))
+                })
+                .collect()
+        })
+    }
+
+    /// One block, from whichever gateway first sends bytes that hash to `cid`.
+    fn block(&self, cid: &Cid) -> Result<Vec<u8>> {
+        if cid.hash == MULTIHASH_IDENTITY {
+            return Ok(cid.digest.clone());
+        }
+        let gateways = self.gateways.lock().expect("gateways").clone();
+        let err = match self.race(cid, &gateways) {
+            Ok(block) => return Ok(block),
+            Err(err) => err,
+        };
+        let found: Vec<String> = self
+            .provider_gateways(cid)
+            .into_iter()
+            .filter(|gateway| !gateways.contains(gateway))
+            .collect();
+        if found.is_empty() {
+            return Err(err);
+        }
+        tracing::debug!(%cid, gateways = ?found, "routers named more gateways");
+        {
+            let mut known = self.gateways.lock().expect("gateways");
+            for gateway in &found {
+                if !known.contains(gateway) {
+                    known.push(gateway.clone());
+                }
+            }
+        }
+        self.race(cid, &found).map_err(|_| err)
+    }
+
+    /// Asks [`RACED_GATEWAYS`] gateways at a time and returns the first
+    /// verified block.
+    fn race(&self, cid: &Cid, gateways: &[String]) -> Result<Vec<u8>> {
+        if gateways.is_empty() {
+            bail!("no IPFS gateways configured");
+        }
+        let mut errors = Vec::new();
+        for group in gateways.chunks(RACED_GATEWAYS) {
+            let (tx, rx) = mpsc::channel();
+            for gateway in group {
+                let (tx, http, gateway, cid) =
+                    (tx.clone(), self.http.clone(), gateway.clone(), cid.clone());
+                let timeout = self.timeout;
+                // Not joined: the losers finish in the background, bounded by the timeout.
+                std::thread::spawn(move || {
+                    let result = fetch_block(&http, &gateway, &cid, timeout);
+                    let _ = tx.send((gateway, result));
+                });
+            }
+            drop(tx);
+            for (gateway, result) in rx {
+                match result {
+                    Ok(block) => {
+                        self.prefer(&gateway);
+                        return Ok(block);
+                    }
+                    Err(err) => errors.push(format!("{gateway}: {err:#}")),
+                }
+            }
+        }
+        bail!("no gateway served block {cid} ({})", errors.join("; "))
+    }
+
+    fn prefer(&self, gateway: &str) {
+        let mut gateways = self.gateways.lock().expect("gateways");
+        if let Some(index) = gateways.iter().position(|known| known == gateway) {
+            let gateway = gateways.remove(index);
+            gateways.insert(0, gateway);
+        }
+    }
+
+    /// HTTP gateways the delegated routers list as providers of `cid`.
+    fn provider_gateways(&self, cid: &Cid) -> Vec<String> {
+        let mut found = Vec::new();
+        for router in &self.routers {
+            let providers = (|| -> Result<Value> {
+                let res = self
+                    .http
+                    .get(format!("{router}/routing/v1/providers/{cid}"))
+                    .header(ACCEPT, "application/json")
+                    .timeout(self.timeout)
+                    .send()?
+                    .error_for_status()?;
+                let body = read_limited(res, MAX_ROUTER_RESPONSE_BYTES)?;
+                Ok(serde_json::from_slice(&body)?)
+            })();
+            let providers = match providers {
+                Ok(providers) => providers,
+                Err(err) => {
+                    tracing::debug!(router, error = %format!("{err:#}"), "delegated routing lookup failed");
+                    continue;
+                }
+            };
+            for provider in providers["Providers"].as_array().into_iter().flatten() {
+                let serves_http = provider["Protocols"].as_array().is_some_and(|protocols| {
+                    protocols
+                        .iter()
+                        .any(|protocol| protocol == "transport-ipfs-gateway-http")
+                });
+                if !serves_http {
+                    continue;
+                }
+                let addrs = provider["Addrs"].as_array().into_iter().flatten();
+                for url in addrs
+                    .filter_map(Value::as_str)
+                    .filter_map(multiaddr_gateway)
+                {
+                    if !found.contains(&url) {
+                        found.push(url);
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+fn fetch_block(http: &HttpClient, gateway: &str, cid: &Cid, timeout: Duration) -> Result<Vec<u8>> {
+    let res = http
+        .get(format!("{gateway}/ipfs/{cid}?format=raw"))
+        .header(ACCEPT, "application/vnd.ipld.raw")
+        .timeout(timeout)
+        .send()
+        .map_err(|err| {
+            if err.is_timeout() {
+                anyhow!("gateway timed out after {}ms", timeout.as_millis())
+            } else {
+                err.into()
+            }
+        })?;
+    if !res.status().is_success() {
+        bail!("answered {}", res.status());
+    }
+    let block = read_limited(res, MAX_BLOCK_BYTES)?;
+    if !cid.matches(&block) {
+        bail!("sent a block that does not match its CID");
+    }
+    Ok(block)
+}
+
+fn append_limited(out: &mut Vec<u8>, bytes: &[u8], max_bytes: u64) -> Result<()> {
+    if (out.len() + bytes.len()) as u64 > max_bytes {
+        bail!("file exceeds {max_bytes} bytes");
+    }
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// `https://host[:port]` for a provider address such as
+/// `/dns4/example.org/tcp/443/https`. Other transports are skipped.
+fn multiaddr_gateway(addr: &str) -> Option<String> {
+    let parts: Vec<&str> = addr.trim_start_matches('/').split('/').collect();
+    let [kind, host, "tcp", port, rest @ ..] = parts.as_slice() else {
+        return None;
+    };
+    if rest != ["https"] && rest != ["tls", "http"] {
+        return None;
+    }
+    let host = match *kind {
+        "dns" | "dns4" | "dns6" | "ip4" => host.to_string(),
+        "ip6" => format!("[{host}]"),
+        _ => return None,
+    };
+    Some(match port.parse::<u16>().ok()? {
+        443 => format!("https://{host}"),
+        port => format!("https://{host}:{port}"),
+    })
+}
+
+/// A dag-pb node: named links to other blocks, then the UnixFS data.
+struct PbNode<'a> {
+    links: Vec<PbLink<'a>>,
+    data: &'a [u8],
+}
+
+struct PbLink<'a> {
+    hash: &'a [u8],
+    name: &'a [u8],
+}
+
+impl<'a> PbNode<'a> {
+    fn decode(block: &'a [u8]) -> Result<Self> {
+        let mut node = Self {
+            links: Vec::new(),
+            data: &[],
+        };
+        let mut reader = ProtoReader::new(block);
+        while let Some((field, value)) = reader.field()? {
+            match (field, value) {
+                (1, Field::Bytes(data)) => node.data = data,
+                (2, Field::Bytes(link)) => node.links.push(PbLink::decode(link)?),
+                _ => bail!("malformed dag-pb node"),
+            }
+        }
+        Ok(node)
+    }
+}
+
+impl<'a> PbLink<'a> {
+    fn decode(bytes: &'a [u8]) -> Result<Self> {
+        let mut link = Self {
+            hash: &[],
+            name: &[],
+        };
+        let mut reader = ProtoReader::new(bytes);
+        while let Some((field, value)) = reader.field()? {
+            match (field, value) {
+                (1, Field::Bytes(hash)) => link.hash = hash,
+                (2, Field::Bytes(name)) => link.name = name,
+                (3, Field::Varint(_)) => {}
+                _ => bail!("malformed dag-pb link"),
+            }
+        }
+        Ok(link)
+    }
+}
+
+/// The fields of the UnixFS `Data` message a reader needs.
+struct UnixfsData<'a> {
+    kind: u64,
+    data: &'a [u8],
+    file_size: Option<u64>,
+}
+
+impl<'a> UnixfsData<'a> {
+    fn decode(bytes: &'a [u8]) -> Result<Self> {
+        let mut kind = None;
+        let mut out = Self {
+            kind: 0,
+            data: &[],
+            file_size: None,
+        };
+        let mut reader = ProtoReader::new(bytes);
+        while let Some((field, value)) = reader.field()? {
+            match (field, value) {
+                (1, Field::Varint(value)) => kind = Some(value),
+                (2, Field::Bytes(data)) => out.data = data,
+                (3, Field::Varint(size)) => out.file_size = Some(size),
+                // Block sizes, hash type, fanout, mode and mtime.
+                _ => {}
+            }
+        }
+        out.kind = kind.ok_or_else(|| anyhow!("UnixFS node without a type"))?;
+        Ok(out)
+    }
+}
+
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Just enough protobuf to read dag-pb and UnixFS.
+struct ProtoReader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for (index, byte) in self.buf.iter().enumerate().take(10) {
+            value |= u64::from(byte & 0x7f) << (7 * index);
+            if byte & 0x80 == 0 {
+                self.buf = &self.buf[index + 1..];
+                return Ok(value);
+            }
+        }
+        bail!("truncated varint")
+    }
+
+    fn take(&mut self, len: u64) -> Result<&'a [u8]> {
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= self.buf.len());
+        let len = len.ok_or_else(|| anyhow!("field runs past the end of the block"))?;
+        let (head, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    fn field(&mut self) -> Result<Option<(u64, Field<'a>)>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let value = match key & 7 {
+            0 => Field::Varint(self.varint()?),
+            1 => {
+                self.take(8)?;
+                Field::Fixed
+            }
+            2 => {
+                let len = self.varint()?;
+                Field::Bytes(self.take(len)?)
+            }
+            5 => {
+                self.take(4)?;
+                Field::Fixed
+            }
+            wire => bail!("unsupported protobuf wire type {wire}"),
+        };
+        Ok(Some((key >> 3, value)))
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn base58btc_decode(text: &str) -> Result<Vec<u8>> {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    // Little-endian base-256 digits.
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in text.bytes() {
+        let digit = ALPHABET
+            .iter()
+            .position(|letter| *letter == c)
+            .ok_or_else(|| anyhow!("invalid base58 character {:?}", c as char))?;
+        let mut carry = digit as u32;
+        for byte in &mut bytes {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = text.bytes().take_while(|c| *c == b'1').count();
+    Ok(std::iter::repeat_n(0, zeros)
+        .chain(bytes.into_iter().rev())
+        .collect())
+}
+
+/// Unpadded RFC 4648 base32 in either case.
+fn base32_decode(text: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for c in text.bytes().map(|c| c.to_ascii_lowercase()) {
+        let value = match c {
+            b'a'..=b'z' => c - b'a',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => bail!("invalid base32 character {:?}", c as char),
+        };
+        buffer = (buffer << 5) | u64::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cid, VerifiedFetcher, multiaddr_gateway};
+    use crate::config::ResolvedConfig;
+    use crate::test_harness::{MockGateway, MockRpc, test_config};
+
+    /// An honest gateway serving a bundle, and a lying one asked first.
+    struct Gateways {
+        honest: MockGateway,
+        liar: MockGateway,
+        root: String,
+        big: Vec<u8>,
+    }
+
+    impl Gateways {
+        fn start() -> Self {
+            let big: Vec<u8> = (0..600_000u32).map(|i| (i % 251) as u8).collect();
+            let honest = MockGateway::start();
+            let root =
+                honest.add_bundle(&[("index.html", b"<h1>hi</h1>"), ("assets/app.wasm", &big)]);
+            // Answers every block request, with bytes that match no CID.
+            let liar = MockGateway::start();
+            liar.forge_blocks(b"<h1>pwned</h1>");
+            Self {
+                honest,
+                liar,
+                root,
+                big,
+            }
+        }
+
+        fn config(&self, name: &str, gateways: &[&MockGateway]) -> ResolvedConfig {
+            let dir = std::env::temp_dir().join(format!(
+                "vibefi-verified-fetch-{name}-{}",
+                std::process::id()
+            ));
+            let mut resolved = test_config(&MockRpc::start(), &self.honest, &dir);
+            resolved.ipfs_helia_gateways =
+                gateways.iter().map(|gateway| gateway.url.clone()).collect();
+            resolved.ipfs_helia_routers = Vec::new();
+            resolved
+        }
+
+        fn fetcher(&self, name: &str) -> VerifiedFetcher {
+            VerifiedFetcher::new(&self.config(name, &[&self.liar, &self.honest]))
+        }
+    }
+
+    #[test]
+    fn fetches_files_past_a_lying_gateway() {
+        let gateways = Gateways::start();
+        assert_eq!(
+            gateways
+                .fetcher("small")
+                .fetch(&gateways.root, "index.html", 1024)
+                .unwrap(),
+            b"<h1>hi</h1>"
+        );
+    }
+
+    #[test]
+    fn fetches_files_spanning_many_blocks() {
+        let gateways = Gateways::start();
+        assert_eq!(
+            gateways
+                .fetcher("big")
+                .fetch(&gateways.root, "/assets/app.wasm", 1 << 20)
+                .unwrap(),
+            gateways.big
+        );
+    }
+
+    #[test]
+    fn refuses_files_over_the_size_limit() {
+        let gateways = Gateways::start();
+        assert!(
+            gateways
+                .fetcher("limit")
+                .fetch(&gateways.root, "assets/app.wasm", 1000)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn missing_files_are_errors() {
+        let gateways = Gateways::start();
+        assert!(
+            gateways
+                .fetcher("missing")
+                .fetch(&gateways.root, "missing.js", 1024)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn nothing_from_a_lying_gateway_verifies() {
+        let gateways = Gateways::start();
+        let resolved = gateways.config("liar", &[&gateways.liar]);
+        assert!(
+            VerifiedFetcher::new(&resolved)
+                .fetch(&gateways.root, "index.html", 1024)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn cids_round_trip_from_v0_to_v1() {
+        let v0 = Cid::parse("QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn").unwrap();
+        assert_eq!(
+            v0.to_string(),
+            "bafybeiczsscdsbs7ffqz55asqdf3smv6klcw3gofszvwlyarci47bgf354"
+        );
+        assert_eq!(Cid::parse(&v0.to_string()).unwrap(), v0);
+    }
+
+    #[test]
+    fn raw_cids_match_only_their_bytes() {
+        let raw =
+            Cid::parse("bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e").unwrap();
+        assert!(raw.matches(b"hello world"));
+        assert!(!raw.matches(b"hello world!"));
+    }
+
+    #[test]
+    fn refuses_other_multibase_encodings() {
+        assert!(Cid::parse("zdj7Wn9FQAURCP6MbwcWuzi7u65kAsXCdjNTkhbJcoaXBusq9").is_err());
+    }
+
+    #[test]
+    fn provider_http_addresses_become_gateways() {
+        assert_eq!(
+            multiaddr_gateway("/dns4/gw.example.org/tcp/443/https").as_deref(),
+            Some("https://gw.example.org")
+        );
+        assert_eq!(
+            multiaddr_gateway("/ip6/::1/tcp/8443/tls/http").as_deref(),
+            Some("https://[::1]:8443")
+        );
+    }
+
+    #[test]
+    fn other_provider_addresses_are_skipped() {
+        assert_eq!(multiaddr_gateway("/ip4/1.2.3.4/tcp/4001"), None);
+        assert_eq!(multiaddr_gateway("/ip4/1.2.3.4/udp/4001/quic-v1"), None);
+    }
+}
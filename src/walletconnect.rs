@@ -1,15 +1,41 @@
 use anyhow::{Context, Result, anyhow, bail};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
 use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::{Mutex, Weak};
+use std::time::Duration;
+use tao::event_loop::EventLoopProxy;
 
+use crate::state::UserEvent;
 use crate::{logging, runtime_paths};
 
+/// How often a connected bridge is asked for its relay connection state.
+const RELAY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Clone)]
 pub struct WalletConnectConfig {
     pub project_id: String,
+    /// Relays in the order the helper tries them; empty uses the SDK default.
+    pub relay_urls: Vec<String>,
+    /// Session file of the active profile; `None` uses the helper's default.
+    pub store_path: Option<PathBuf>,
+}
+
+/// The helper's connection to its current relay. It moves to the next relay
+/// in the list after failed reconnects, backing off between attempts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayStatus {
+    /// Unset while on the SDK's default relay.
+    #[serde(default)]
     pub relay_url: Option<String>,
+    /// `connecting`, `connected`, `reconnecting` or `failed`.
+    pub state: String,
+    /// Reconnect attempts since the relay was last reachable.
+    #[serde(default)]
+    pub attempt: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +72,8 @@ pub struct HelperEvent {
     #[serde(default)]
     #[serde(rename = "chainId")]
     pub chain_id: Option<String>,
+    #[serde(default)]
+    pub relay: Option<RelayStatus>,
 }
 
 enum BridgeMessage {
@@ -58,6 +86,9 @@ pub struct WalletConnectBridge {
     stdin: ChildStdin,
     stdout: BufReader<ChildStdout>,
     next_id: u64,
+    relay_status: Option<RelayStatus>,
+    /// Events drained by relay status polls, handed out with the next request.
+    pending_events: Vec<HelperEvent>,
 }
 
 impl WalletConnectBridge {
@@ -68,10 +99,15 @@ impl WalletConnectBridge {
 
         let helper_script = runtime_paths::resolve_wc_helper_script()?;
         let node_path = runtime_paths::resolve_node_binary()?;
-        let mut child = Command::new(&node_path)
+        let mut command = Command::new(&node_path);
+        command
             .arg(&helper_script)
             .env("VIBEFI_WC_PROJECT_ID", config.project_id)
-            .env("VIBEFI_WC_RELAY_URL", config.relay_url.unwrap_or_default())
+            .env("VIBEFI_WC_RELAY_URLS", config.relay_urls.join(","));
+        if let Some(store_path) = config.store_path.as_deref() {
+            command.env("VIBEFI_WC_STORE_PATH", store_path);
+        }
+        let mut child = command
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -97,6 +133,8 @@ impl WalletConnectBridge {
             stdin,
             stdout: BufReader::new(stdout),
             next_id: 1,
+            relay_status: None,
+            pending_events: Vec::new(),
         };
 
         bridge.ping().context(
@@ -124,22 +162,39 @@ impl WalletConnectBridge {
             }),
             |event| on_event(event),
         )?;
-        let response: ConnectResponse =
-            serde_json::from_value(result).context("invalid connect response from helper")?;
-        Ok(WalletConnectSession {
-            accounts: response.accounts,
-            chain_id_hex: response.chain_id,
-        })
+        parse_connect_response(result)
+    }
+
+    /// Resumes the session kept in the helper's storage. Fails instead of
+    /// pairing when there is none, so it never shows a QR code.
+    pub fn restore_session(&mut self, chain_id: u64) -> Result<WalletConnectSession> {
+        tracing::info!(
+            chain_id = format!("0x{:x}", chain_id),
+            "walletconnect restoring stored session"
+        );
+        let (result, events) = self.send_command(
+            "connect",
+            serde_json::json!({
+                "chainId": format!("0x{:x}", chain_id),
+                "restoreOnly": true
+            }),
+        )?;
+        self.pending_events
+            .extend(events.into_iter().filter(|event| event.relay.is_none()));
+        parse_connect_response(result)
     }
 
     pub fn request(&mut self, method: &str, params: Value) -> Result<(Value, Vec<HelperEvent>)> {
-        self.send_command(
+        let (result, events) = self.send_command(
             "request",
             serde_json::json!({
                 "method": method,
                 "params": params
             }),
-        )
+        )?;
+        let mut all = std::mem::take(&mut self.pending_events);
+        all.extend(events);
+        Ok((result, all))
     }
 
     pub fn disconnect(&mut self) -> Result<()> {
@@ -147,6 +202,19 @@ impl WalletConnectBridge {
         Ok(())
     }
 
+    /// Asks the helper for its relay state, which also drains events it
+    /// emitted since the last command.
+    pub fn relay_status(&mut self) -> Result<Option<RelayStatus>> {
+        let (result, events) = self.send_command("relayStatus", Value::Null)?;
+        self.pending_events
+            .extend(events.into_iter().filter(|event| event.relay.is_none()));
+        if !result.is_null() {
+            self.relay_status =
+                Some(serde_json::from_value(result).context("invalid relay status from helper")?);
+        }
+        Ok(self.relay_status.clone())
+    }
+
     fn ping(&mut self) -> Result<()> {
         let _ = self.send_command("ping", Value::Null)?;
         Ok(())
@@ -203,6 +271,9 @@ impl WalletConnectBridge {
             match parse_bridge_line(raw)? {
                 BridgeMessage::Event(event) => {
                     log_helper_event(&event);
+                    if let Some(relay) = &event.relay {
+                        self.relay_status = Some(relay.clone());
+                    }
                     on_event(&event);
                     continue;
                 }
@@ -228,6 +299,41 @@ impl WalletConnectBridge {
     }
 }
 
+/// Reports relay state changes of a connected bridge to the event loop until
+/// the bridge is dropped.
+pub fn spawn_relay_monitor(
+    bridge: Weak<Mutex<WalletConnectBridge>>,
+    proxy: EventLoopProxy<UserEvent>,
+) {
+    std::thread::spawn(move || {
+        let mut last: Option<RelayStatus> = None;
+        loop {
+            std::thread::sleep(RELAY_POLL_INTERVAL);
+            let Some(bridge) = bridge.upgrade() else {
+                break;
+            };
+            let status = match bridge.lock() {
+                Ok(mut bridge) => bridge.relay_status(),
+                Err(_) => break,
+            };
+            drop(bridge);
+            match status {
+                Ok(status) if status != last => {
+                    if let Some(status) = status.clone() {
+                        let _ = proxy.send_event(UserEvent::WalletConnectRelayStatus { status });
+                    }
+                    last = status;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!(error = %format!("{e:#}"), "walletconnect relay status unavailable");
+                    break;
+                }
+            }
+        }
+    });
+}
+
 impl Drop for WalletConnectBridge {
     fn drop(&mut self) {
         let _ = self.disconnect();
@@ -243,6 +349,15 @@ struct ConnectResponse {
     pub chain_id: String,
 }
 
+fn parse_connect_response(result: Value) -> Result<WalletConnectSession> {
+    let response: ConnectResponse =
+        serde_json::from_value(result).context("invalid connect response from helper")?;
+    Ok(WalletConnectSession {
+        accounts: response.accounts,
+        chain_id_hex: response.chain_id,
+    })
+}
+
 fn parse_bridge_line(raw: &str) -> Result<BridgeMessage> {
     let value: Value = serde_json::from_str(raw).context("helper output is not valid json")?;
     if value.get("event").is_some() {
@@ -274,6 +389,16 @@ fn log_helper_event(event: &HelperEvent) {
         "disconnect" => {
             tracing::info!("walletconnect disconnect");
         }
+        "relay_status" => {
+            if let Some(relay) = &event.relay {
+                tracing::info!(
+                    relay = relay.relay_url.as_deref().unwrap_or("default"),
+                    state = relay.state,
+                    attempt = relay.attempt,
+                    "walletconnect relay status"
+                );
+            }
+        }
         _ => {
             tracing::debug!(event = %event.event, "walletconnect event");
         }
@@ -1,18 +1,30 @@
 use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(target_os = "macos")]
+use wry::WebViewBuilderExtDarwin;
 #[cfg(target_os = "linux")]
 use wry::WebViewBuilderExtUnix;
 use wry::{
-    Rect, WebView, WebViewBuilder,
-    http::{Response, header::CONTENT_TYPE},
+    Rect, RequestAsyncResponder, WebContext, WebView, WebViewBuilder,
+    http::{
+        Response,
+        header::{ACCEPT_RANGES, CONTENT_RANGE, CONTENT_TYPE, RANGE},
+    },
 };
 
+use crate::bundle::{served_file_hashes, sha256_hex};
+use crate::dist_cache::DistCache;
 use crate::ipc::{emit_accounts_changed, emit_chain_changed};
 use crate::state::{AppState, UserEvent};
 use crate::{
-    HOME_JS, INDEX_HTML, LAUNCHER_HTML, LAUNCHER_JS, PRELOAD_APP_JS, PRELOAD_SETTINGS_JS,
-    PRELOAD_TAB_BAR_JS, PRELOAD_WALLET_SELECTOR_JS, SETTINGS_HTML, SETTINGS_JS, TAB_BAR_HTML,
+    HOME_JS, INDEX_HTML, LAUNCHER_HTML, LAUNCHER_JS, PALETTE_HTML, PALETTE_JS, PRELOAD_APP_JS,
+    PRELOAD_PALETTE_JS, PRELOAD_PROMPT_JS, PRELOAD_SETTINGS_JS, PRELOAD_TAB_BAR_JS,
+    PRELOAD_WALLET_SELECTOR_JS, PROMPT_HTML, PROMPT_JS, SETTINGS_HTML, SETTINGS_JS, TAB_BAR_HTML,
     TAB_BAR_JS, WALLET_SELECTOR_HTML, WALLET_SELECTOR_JS,
 };
 
@@ -28,6 +40,23 @@ pub struct WebViewHost<'a> {
     pub app_container: &'a gtk::Box,
 }
 
+impl WebViewHost<'_> {
+    /// Builds an app-area webview in this host.
+    fn build_app_child(&self, builder: WebViewBuilder<'_>) -> wry::Result<WebView> {
+        #[cfg(target_os = "linux")]
+        return builder.build_gtk(self.app_container);
+        #[cfg(not(target_os = "linux"))]
+        return builder.build_as_child(self.window);
+    }
+}
+
+thread_local! {
+    /// One web context per storage partition, kept for the life of the process
+    /// because webviews built from a context need it to outlive them.
+    static WEB_CONTEXTS: RefCell<HashMap<Option<PathBuf>, WebContext>> =
+        RefCell::new(HashMap::new());
+}
+
 /// What embedded content to serve when `dist_dir` is `None`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EmbeddedContent {
@@ -39,6 +68,67 @@ pub enum EmbeddedContent {
     WalletSelector,
     /// The settings tab.
     Settings,
+    /// The modal permission prompt overlay.
+    Prompt,
+    /// The command palette overlay.
+    Palette,
+}
+
+/// Everything needed to (re)build an app webview without the original caller.
+#[derive(Debug, Clone)]
+pub struct WebViewSource {
+    pub dist_dir: Option<PathBuf>,
+    pub embedded: EmbeddedContent,
+    /// Storage partition directory; `None` shares the default one.
+    pub storage: Option<PathBuf>,
+    /// Keeps site data in memory only, discarded with the webview.
+    pub ephemeral: bool,
+    /// Locally built project; relaxed for development under the dev profile.
+    pub local_project: bool,
+}
+
+/// Navigation state captured before a tab is suspended and replayed when it is rebuilt.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabSnapshot {
+    pub url: String,
+    #[serde(default)]
+    pub scroll_x: f64,
+    #[serde(default)]
+    pub scroll_y: f64,
+}
+
+/// Evaluated in a tab about to be suspended; the result parses as a [`TabSnapshot`].
+pub const TAB_SNAPSHOT_JS: &str =
+    "({ url: location.href, scrollX: window.scrollX, scrollY: window.scrollY })";
+
+/// Scrolls back to the saved position once, on the first load after a restore.
+fn scroll_restore_script(snapshot: &TabSnapshot) -> String {
+    let x = if snapshot.scroll_x.is_finite() {
+        snapshot.scroll_x
+    } else {
+        0.0
+    };
+    let y = if snapshot.scroll_y.is_finite() {
+        snapshot.scroll_y
+    } else {
+        0.0
+    };
+    format!(
+        "(function() {{ if (sessionStorage.getItem('__vibefiRestored')) return; \
+         sessionStorage.setItem('__vibefiRestored', '1'); \
+         window.addEventListener('load', function() {{ window.scrollTo({x}, {y}); }}, {{ once: true }}); }})();"
+    )
+}
+
+/// URL a restored tab starts at; anything the navigation handler would refuse
+/// falls back to the bundle entry point.
+fn restore_url(snapshot: Option<&TabSnapshot>) -> String {
+    snapshot
+        .map(|s| s.url.as_str())
+        .filter(|url| *url != "about:blank" && allow_navigation(url))
+        .unwrap_or("app://index.html")
+        .to_string()
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -47,7 +137,198 @@ enum CspProfile {
     StaticHtml,
 }
 
-fn serve_file(dist_dir: &PathBuf, path: &str) -> (Vec<u8>, String) {
+/// Serves dist files only after the bytes on disk match their expected SHA-256
+/// (see [`served_file_hashes`]); files without one are refused. Verified
+/// contents are kept in the dist cache under that hash, so later requests
+/// cannot observe files swapped out after the check. For built bundles the
+/// expected hashes are local, so this guards against corruption rather than
+/// tampering.
+struct VerifiedAssets {
+    expected: HashMap<String, String>,
+    cache: Arc<DistCache>,
+}
+
+impl VerifiedAssets {
+    fn for_dist(dist_dir: &Path) -> Result<Self> {
+        let expected = served_file_hashes(dist_dir)?;
+        tracing::debug!(files = expected.len(), "app protocol enforcing file hashes");
+        Ok(Self {
+            expected,
+            cache: crate::dist_cache::shared(),
+        })
+    }
+
+    /// The dist-relative key and expected digest of `file_path`, or `None`
+    /// for a file that must not be served.
+    fn expected<'a>(&'a self, dist_dir: &Path, file_path: &Path) -> Option<(String, &'a String)> {
+        let key = dist_relative_key(dist_dir, file_path)?;
+        match self.expected.get(&key) {
+            Some(expected) => Some((key, expected)),
+            None => {
+                tracing::warn!(path = %key, "refusing to serve file without an expected hash");
+                None
+            }
+        }
+    }
+
+    /// Returns `Ok(None)` when the file on disk is unlisted or no longer
+    /// matches its expected hash.
+    fn read(&self, dist_dir: &Path, file_path: &Path) -> std::io::Result<Option<Vec<u8>>> {
+        let Some((key, expected)) = self.expected(dist_dir, file_path) else {
+            return Ok(None);
+        };
+        if let Some(cached) = self.cache.get(expected) {
+            return Ok(Some(cached));
+        }
+        let data = fs::read(file_path)?;
+        let actual = sha256_hex(&data);
+        if &actual != expected {
+            tracing::warn!(
+                path = %key,
+                %expected,
+                %actual,
+                "refusing to serve asset with mismatched manifest hash"
+            );
+            return Ok(None);
+        }
+        self.cache.insert(expected, &data);
+        Ok(Some(data))
+    }
+
+    /// Bytes `start..=end` of a file, checked like [`VerifiedAssets::read`].
+    /// Files small enough for the dist cache are cut from the verified copy in
+    /// memory. Larger ones are hashed once in chunks and then read from disk a
+    /// range at a time; the hash is redone when their size or modification
+    /// time changes.
+    fn read_range(
+        &self,
+        dist_dir: &Path,
+        file_path: &Path,
+        start: u64,
+        end: u64,
+    ) -> std::io::Result<Option<Vec<u8>>> {
+        if fs::metadata(file_path)?.len() <= self.cache.max_entry_bytes() as u64 {
+            return Ok(self.read(dist_dir, file_path)?.map(|data| {
+                let end = data.len().min(end as usize + 1);
+                data.get(start as usize..end)
+                    .map(<[u8]>::to_vec)
+                    .unwrap_or_default()
+            }));
+        }
+        let Some((key, expected)) = self.expected(dist_dir, file_path) else {
+            return Ok(None);
+        };
+        let digests = self.cache.file_digests(file_path)?;
+        if &digests.sha256 != expected {
+            tracing::warn!(
+                path = %key,
+                %expected,
+                actual = %digests.sha256,
+                "refusing to serve asset with mismatched manifest hash"
+            );
+            return Ok(None);
+        }
+        let data = digests.read_range(file_path, start, end)?;
+        if data.is_none() {
+            tracing::warn!(path = %key, start, end, "refusing to serve range that changed since it was hashed");
+        }
+        Ok(data)
+    }
+}
+
+/// Most of a file one open-ended `Range` request (`bytes=N-`) gets. wry hands
+/// the webview whole response bodies, so media elements play large files by
+/// asking for the rest piece by piece.
+const MAX_RANGE_CHUNK_BYTES: u64 = 4 * 1024 * 1024;
+
+/// The part of a file a `Range` header asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteRange {
+    /// No header, or one the handler ignores: other units or several ranges.
+    Whole,
+    /// Inclusive offsets, both inside the file.
+    Part {
+        start: u64,
+        end: u64,
+    },
+    Unsatisfiable,
+}
+
+fn byte_range(header: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = header.and_then(|header| header.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Whole;
+    };
+    let Some((first, last)) = spec.split_once('-').filter(|_| !spec.contains(',')) else {
+        return ByteRange::Whole;
+    };
+    let (first, last) = (first.trim(), last.trim());
+    if first.is_empty() {
+        // `bytes=-N`: the last N bytes.
+        return match last.parse::<u64>() {
+            Ok(suffix) if suffix > 0 && len > 0 => ByteRange::Part {
+                start: len.saturating_sub(suffix),
+                end: len - 1,
+            },
+            Ok(_) => ByteRange::Unsatisfiable,
+            Err(_) => ByteRange::Whole,
+        };
+    }
+    let Ok(start) = first.parse::<u64>() else {
+        return ByteRange::Whole;
+    };
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    if last.is_empty() {
+        return ByteRange::Part {
+            start,
+            end: (len - 1).min(start.saturating_add(MAX_RANGE_CHUNK_BYTES - 1)),
+        };
+    }
+    match last.parse::<u64>() {
+        Ok(end) if end >= start => ByteRange::Part {
+            start,
+            end: end.min(len - 1),
+        },
+        _ => ByteRange::Whole,
+    }
+}
+
+/// A dist file answer, whole or in part.
+struct ServedFile {
+    status: u16,
+    body: Vec<u8>,
+    mime: String,
+    /// `Content-Range` of a 206 or 416 answer.
+    content_range: Option<String>,
+}
+
+impl ServedFile {
+    fn text(status: u16, body: String) -> Self {
+        Self {
+            status,
+            body: body.into_bytes(),
+            mime: "text/plain; charset=utf-8".to_string(),
+            content_range: None,
+        }
+    }
+}
+
+fn dist_relative_key(dist_dir: &Path, file_path: &Path) -> Option<String> {
+    let rel = file_path.strip_prefix(dist_dir).ok()?;
+    let parts: Vec<_> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(parts.join("/"))
+}
+
+fn serve_file(
+    dist_dir: &PathBuf,
+    path: &str,
+    range: Option<&str>,
+    assets: &VerifiedAssets,
+) -> ServedFile {
     let rel = path.trim_start_matches('/');
     let mut file_path = if rel.is_empty() {
         dist_dir.join("index.html")
@@ -58,17 +339,59 @@ fn serve_file(dist_dir: &PathBuf, path: &str) -> (Vec<u8>, String) {
         file_path = file_path.join("index.html");
     }
     if !file_path.exists() {
-        (
-            format!("Not found: {path}").into_bytes(),
-            "text/plain; charset=utf-8".to_string(),
-        )
-    } else {
-        let data = fs::read(&file_path).unwrap_or_else(|_| Vec::new());
-        let guess = mime_guess::MimeGuess::from_path(&file_path)
-            .first_or_octet_stream()
-            .essence_str()
-            .to_string();
-        (data, guess)
+        return ServedFile::text(200, format!("Not found: {path}"));
+    }
+    let mime = mime_guess::MimeGuess::from_path(&file_path)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string();
+    let len = fs::metadata(&file_path).map(|meta| meta.len()).unwrap_or(0);
+    let (start, end) = match byte_range(range, len) {
+        ByteRange::Whole => {
+            let data = match assets.read(dist_dir, &file_path) {
+                Ok(Some(data)) => data,
+                Ok(None) => {
+                    return ServedFile::text(403, format!("Integrity check failed: {path}"));
+                }
+                Err(_) => Vec::new(),
+            };
+            return ServedFile {
+                status: 200,
+                body: data,
+                mime,
+                content_range: None,
+            };
+        }
+        ByteRange::Part { start, end } => (start, end),
+        ByteRange::Unsatisfiable => {
+            return ServedFile {
+                status: 416,
+                body: Vec::new(),
+                mime,
+                content_range: Some(format!("bytes */{len}")),
+            };
+        }
+    };
+    let data = match assets.read_range(dist_dir, &file_path, start, end) {
+        Ok(Some(data)) => data,
+        Ok(None) => return ServedFile::text(403, format!("Integrity check failed: {path}")),
+        Err(_) => Vec::new(),
+    };
+    // The file may have shrunk since its length was read.
+    if data.is_empty() {
+        return ServedFile {
+            status: 416,
+            body: data,
+            mime,
+            content_range: Some(format!("bytes */{len}")),
+        };
+    }
+    let end = start + data.len() as u64 - 1;
+    ServedFile {
+        status: 206,
+        body: data,
+        mime,
+        content_range: Some(format!("bytes {start}-{end}/{len}")),
     }
 }
 
@@ -96,53 +419,178 @@ fn normalized_app_path(uri: &wry::http::Uri) -> String {
     result
 }
 
-fn csp_profile_for_dist(dist_dir: &PathBuf) -> CspProfile {
+/// Powerful web APIs that dapp content can never enable; hardware and payments
+/// must go through the wallet instead.
+const ALWAYS_DENIED_FEATURES: &[&str] = &["usb", "serial", "hid", "bluetooth", "payment"];
+
+/// Web APIs denied unless the manifest lists them under `capabilities.browser.allow`.
+const OPT_IN_FEATURES: &[&str] = &[
+    "camera",
+    "microphone",
+    "geolocation",
+    "clipboard-read",
+    "clipboard-write",
+    "fullscreen",
+    "display-capture",
+    "midi",
+    "screen-wake-lock",
+    "idle-detection",
+    "publickey-credentials-get",
+];
+
+/// Security headers attached to every `app://` response for a webview.
+#[derive(Debug, Clone)]
+struct ResponsePolicy {
+    csp: CspProfile,
+    permissions_policy: String,
+    /// Lets the page connect to local backends; dev profile project tabs only.
+    local_connect: bool,
+}
+
+impl ResponsePolicy {
+    fn strict() -> Self {
+        Self {
+            csp: CspProfile::Strict,
+            permissions_policy: permissions_policy_header(&[]),
+            local_connect: false,
+        }
+    }
+
+    fn content_security_policy(&self) -> String {
+        let csp = match self.csp {
+            CspProfile::Strict => {
+                "default-src 'self' app:; img-src 'self' data: app:; style-src 'self' 'unsafe-inline' app:; script-src 'self' app:; connect-src 'none'; frame-src 'none'; frame-ancestors 'none'; object-src 'none'; worker-src 'none'; base-uri 'none'; form-action 'none'; require-trusted-types-for 'script'; trusted-types default"
+            }
+            CspProfile::StaticHtml => {
+                "default-src 'self' app:; img-src 'self' data: app:; style-src 'self' 'unsafe-inline' app:; script-src 'self' 'unsafe-inline' app:; connect-src 'none'; frame-src 'none'; frame-ancestors 'none'; object-src 'none'; worker-src 'none'; base-uri 'none'; form-action 'none'"
+            }
+        };
+        if self.local_connect {
+            csp.replace("connect-src 'none'", LOCAL_CONNECT_SRC)
+        } else {
+            csp.to_string()
+        }
+    }
+}
+
+/// `connect-src` of dev profile project tabs: any port on the loopback host,
+/// plus the bundle's own files.
+const LOCAL_CONNECT_SRC: &str = "connect-src 'self' app: http://localhost:* https://localhost:* ws://localhost:* wss://localhost:* http://127.0.0.1:* https://127.0.0.1:* ws://127.0.0.1:* wss://127.0.0.1:*";
+
+fn permissions_policy_header(allowed: &[String]) -> String {
+    ALWAYS_DENIED_FEATURES
+        .iter()
+        .map(|feature| format!("{feature}=()"))
+        .chain(OPT_IN_FEATURES.iter().map(|feature| {
+            if allowed.iter().any(|a| a == feature) {
+                format!("{feature}=(self)")
+            } else {
+                format!("{feature}=()")
+            }
+        }))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn response_policy_for_dist(dist_dir: &PathBuf) -> ResponsePolicy {
     let Some(bundle_root) = dist_dir.parent().and_then(|p| p.parent()) else {
-        return CspProfile::Strict;
+        return ResponsePolicy::strict();
     };
-    let manifest_path = bundle_root.join("manifest.json");
-    let Ok(raw) = fs::read_to_string(manifest_path) else {
-        return CspProfile::Strict;
+    let Ok(manifest) = crate::manifest::load(bundle_root) else {
+        return ResponsePolicy::strict();
     };
-    let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&raw) else {
-        return CspProfile::Strict;
-    };
-    if parsed.get("layout").and_then(serde_json::Value::as_str) == Some("static-html") {
-        return CspProfile::StaticHtml;
+
+    let static_html = manifest.is_static_html();
+    let capabilities = manifest.capabilities.unwrap_or_default();
+    let mut allowed: Vec<String> = capabilities
+        .pointer("/browser/allow")
+        .and_then(serde_json::Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(|s| s.trim().to_ascii_lowercase())
+                .filter(|s| {
+                    if ALWAYS_DENIED_FEATURES.contains(&s.as_str()) {
+                        tracing::warn!(feature = %s, "manifest requested a denied browser feature");
+                        return false;
+                    }
+                    OPT_IN_FEATURES.contains(&s.as_str())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // The QR scanner needs the camera; granting `qr.scan` implies it.
+    let qr_scan = capabilities
+        .pointer("/qr/scan")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false);
+    if qr_scan && !allowed.iter().any(|f| f == "camera") {
+        allowed.push("camera".to_string());
     }
-    if parsed
-        .get("constraints")
-        .and_then(|value| value.get("type"))
-        .and_then(serde_json::Value::as_str)
-        == Some("static-html")
-    {
-        return CspProfile::StaticHtml;
+
+    ResponsePolicy {
+        csp: if static_html {
+            CspProfile::StaticHtml
+        } else {
+            CspProfile::Strict
+        },
+        permissions_policy: permissions_policy_header(&allowed),
+        local_connect: false,
     }
-    CspProfile::Strict
 }
 
 fn csp_response(
     body: Vec<u8>,
     mime: String,
-    profile: CspProfile,
+    policy: &ResponsePolicy,
 ) -> wry::http::Response<std::borrow::Cow<'static, [u8]>> {
-    let csp = match profile {
-        CspProfile::Strict => {
-            "default-src 'self' app:; img-src 'self' data: app:; style-src 'self' 'unsafe-inline' app:; script-src 'self' app:; connect-src 'none'; frame-src 'none'; object-src 'none'; worker-src 'none'; base-uri 'none'; form-action 'none'; require-trusted-types-for 'script'; trusted-types default"
-        }
-        CspProfile::StaticHtml => {
-            "default-src 'self' app:; img-src 'self' data: app:; style-src 'self' 'unsafe-inline' app:; script-src 'self' 'unsafe-inline' app:; connect-src 'none'; frame-src 'none'; object-src 'none'; worker-src 'none'; base-uri 'none'; form-action 'none'"
-        }
-    };
-    Response::builder()
-        .status(200)
-        .header(CONTENT_TYPE, mime.as_str())
-        .header("X-Content-Type-Options", "nosniff")
-        .header("Content-Security-Policy", csp)
+    csp_response_with_status(200, body, mime, policy)
+}
+
+fn csp_response_with_status(
+    status: u16,
+    body: Vec<u8>,
+    mime: String,
+    policy: &ResponsePolicy,
+) -> wry::http::Response<std::borrow::Cow<'static, [u8]>> {
+    csp_response_builder(status, &mime, policy)
         .body(std::borrow::Cow::Owned(body))
         .expect("failed to build CSP response")
 }
 
+/// Dist files also advertise range support, which media elements need to
+/// seek and to load large files in pieces.
+fn file_response(
+    served: ServedFile,
+    policy: &ResponsePolicy,
+) -> wry::http::Response<std::borrow::Cow<'static, [u8]>> {
+    let mut builder =
+        csp_response_builder(served.status, &served.mime, policy).header(ACCEPT_RANGES, "bytes");
+    if let Some(range) = &served.content_range {
+        builder = builder.header(CONTENT_RANGE, range.as_str());
+    }
+    builder
+        .body(std::borrow::Cow::Owned(served.body))
+        .expect("failed to build CSP response")
+}
+
+fn csp_response_builder(
+    status: u16,
+    mime: &str,
+    policy: &ResponsePolicy,
+) -> wry::http::response::Builder {
+    let csp = policy.content_security_policy();
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, mime)
+        .header("X-Content-Type-Options", "nosniff")
+        .header("Content-Security-Policy", csp.as_str())
+        .header("Permissions-Policy", policy.permissions_policy.as_str())
+        .header("Cross-Origin-Opener-Policy", "same-origin")
+        .header("X-Frame-Options", "DENY")
+}
+
 fn should_enable_devtools(state: &AppState) -> bool {
     state
         .resolved
@@ -186,26 +634,92 @@ pub fn build_app_webview(
     proxy: tao::event_loop::EventLoopProxy<UserEvent>,
     bounds: Rect,
 ) -> Result<WebView> {
-    tracing::debug!(?id, ?embedded, ?dist_dir, ?bounds, "build_app_webview");
+    let source = WebViewSource {
+        dist_dir,
+        embedded,
+        storage: None,
+        ephemeral: false,
+        local_project: false,
+    };
+    restore_app_webview(host, id, source, None, state, proxy, bounds)
+}
+
+/// Builds an app webview from `source`, resuming at `snapshot` when the tab
+/// was previously suspended.
+pub fn restore_app_webview(
+    host: &WebViewHost,
+    id: &str,
+    source: WebViewSource,
+    snapshot: Option<&TabSnapshot>,
+    state: &AppState,
+    proxy: tao::event_loop::EventLoopProxy<UserEvent>,
+    bounds: Rect,
+) -> Result<WebView> {
+    let WebViewSource {
+        dist_dir,
+        embedded,
+        storage,
+        ephemeral,
+        local_project,
+    } = source;
+    let dev_profile = local_project
+        && dist_dir.is_some()
+        && state.resolved.as_ref().is_some_and(|r| r.dev_profile);
+    tracing::debug!(
+        ?id,
+        ?embedded,
+        ?dist_dir,
+        ?bounds,
+        restored = snapshot.is_some(),
+        "build_app_webview"
+    );
 
     let protocol_dist = dist_dir.clone();
-    let csp_profile = dist_dir
+    let verified_assets = dist_dir
+        .as_deref()
+        .map(VerifiedAssets::for_dist)
+        .transpose()?
+        .map(Arc::new);
+    let mut response_policy = dist_dir
         .as_ref()
-        .map(csp_profile_for_dist)
-        .unwrap_or(CspProfile::Strict);
+        .map(response_policy_for_dist)
+        .unwrap_or_else(ResponsePolicy::strict);
+    if dev_profile {
+        tracing::info!(?id, "dev profile: allowing localhost connections");
+        response_policy.local_connect = true;
+    }
     let app_id_for_log = id.to_string();
-    let protocol = move |_webview_id: wry::WebViewId, request: wry::http::Request<Vec<u8>>| {
+    let protocol = move |_webview_id: wry::WebViewId,
+                         request: wry::http::Request<Vec<u8>>,
+                         responder: RequestAsyncResponder| {
         tracing::trace!(
             "app protocol handler ({app_id_for_log}): method={} uri={}",
             request.method(),
             request.uri()
         );
         let path = normalized_app_path(request.uri());
-        if let Some(ref dist) = protocol_dist {
+        if let (Some(dist), Some(assets)) = (&protocol_dist, &verified_assets) {
             tracing::trace!("serving from dist_dir: path={path:?}");
-            let (body, mime) = serve_file(dist, &path);
-            tracing::trace!("dist response: mime={mime:?}, body_len={}", body.len());
-            csp_response(body, mime, csp_profile)
+            let range = request
+                .headers()
+                .get(RANGE)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let (dist, assets, policy) =
+                (dist.clone(), Arc::clone(assets), response_policy.clone());
+            // Reading and hashing large assets off the event loop keeps the
+            // window responsive while they load; the asset pool caps how
+            // many run at once.
+            crate::asset_pool::spawn(move || {
+                let served = serve_file(&dist, &path, range.as_deref(), &assets);
+                tracing::trace!(
+                    "dist response: status={}, mime={:?}, body_len={}",
+                    served.status,
+                    served.mime,
+                    served.body.len()
+                );
+                responder.respond(file_response(served, &policy));
+            });
         } else {
             let matched = match (embedded, path.as_str()) {
                 (_, "/" | "/index.html") => {
@@ -214,12 +728,14 @@ pub fn build_app_webview(
                         EmbeddedContent::Launcher => LAUNCHER_HTML,
                         EmbeddedContent::WalletSelector => WALLET_SELECTOR_HTML,
                         EmbeddedContent::Settings => SETTINGS_HTML,
+                        EmbeddedContent::Prompt => PROMPT_HTML,
+                        EmbeddedContent::Palette => PALETTE_HTML,
                     };
                     tracing::trace!("serving embedded html for {embedded:?}, len={}", html.len());
                     csp_response(
                         html.as_bytes().to_vec(),
                         "text/html; charset=utf-8".to_string(),
-                        csp_profile,
+                        &response_policy,
                     )
                 }
                 (EmbeddedContent::Launcher, "/launcher.js") => {
@@ -227,7 +743,7 @@ pub fn build_app_webview(
                     csp_response(
                         LAUNCHER_JS.as_bytes().to_vec(),
                         "application/javascript; charset=utf-8".to_string(),
-                        csp_profile,
+                        &response_policy,
                     )
                 }
                 (EmbeddedContent::Default, "/home.js") => {
@@ -235,29 +751,39 @@ pub fn build_app_webview(
                     csp_response(
                         HOME_JS.as_bytes().to_vec(),
                         "application/javascript; charset=utf-8".to_string(),
-                        csp_profile,
+                        &response_policy,
                     )
                 }
                 (EmbeddedContent::WalletSelector, "/wallet-selector.js") => csp_response(
                     WALLET_SELECTOR_JS.as_bytes().to_vec(),
                     "application/javascript; charset=utf-8".to_string(),
-                    csp_profile,
+                    &response_policy,
                 ),
                 (EmbeddedContent::Settings, "/settings.js") => csp_response(
                     SETTINGS_JS.as_bytes().to_vec(),
                     "application/javascript; charset=utf-8".to_string(),
-                    csp_profile,
+                    &response_policy,
+                ),
+                (EmbeddedContent::Prompt, "/prompt.js") => csp_response(
+                    PROMPT_JS.as_bytes().to_vec(),
+                    "application/javascript; charset=utf-8".to_string(),
+                    &response_policy,
+                ),
+                (EmbeddedContent::Palette, "/palette.js") => csp_response(
+                    PALETTE_JS.as_bytes().to_vec(),
+                    "application/javascript; charset=utf-8".to_string(),
+                    &response_policy,
                 ),
                 _ => {
                     tracing::debug!("app protocol miss: embedded={embedded:?}, path={path:?}");
                     csp_response(
                         format!("Not found: {}", path).into_bytes(),
                         "text/plain; charset=utf-8".to_string(),
-                        csp_profile,
+                        &response_policy,
                     )
                 }
             };
-            matched
+            responder.respond(matched);
         }
     };
 
@@ -267,60 +793,98 @@ pub fn build_app_webview(
         allowed
     };
 
-    let init_script = match embedded {
+    let mut init_script = match embedded {
         EmbeddedContent::WalletSelector => PRELOAD_WALLET_SELECTOR_JS.to_string(),
         EmbeddedContent::Settings => PRELOAD_SETTINGS_JS.to_string(),
+        EmbeddedContent::Prompt => PRELOAD_PROMPT_JS.to_string(),
+        EmbeddedContent::Palette => PRELOAD_PALETTE_JS.to_string(),
         _ => PRELOAD_APP_JS.to_string(),
     };
+    if let Some(snapshot) = snapshot {
+        init_script.push('\n');
+        init_script.push_str(&scroll_restore_script(snapshot));
+    }
+    let start_url = restore_url(snapshot);
 
     let webview_id = id.to_string();
-    let builder = WebViewBuilder::new()
-        .with_id(id)
-        .with_bounds(bounds)
-        .with_initialization_script(init_script)
-        .with_devtools(should_enable_devtools(state))
-        .with_custom_protocol("app".into(), protocol)
-        .with_url("app://index.html")
-        .with_navigation_handler(navigation_handler)
-        .with_ipc_handler(move |req: wry::http::Request<String>| {
-            let _ = proxy.send_event(UserEvent::Ipc {
-                webview_id: webview_id.clone(),
-                msg: req.body().clone(),
-            });
-        });
-
-    tracing::debug!(id, "building app webview");
-    #[cfg(target_os = "linux")]
-    let webview = builder
-        .build_gtk(host.app_container)
-        .context("failed to build app webview")?;
-    #[cfg(not(target_os = "linux"))]
-    let webview = builder
-        .build_as_child(host.window)
+    let drop_proxy = proxy.clone();
+    tracing::debug!(id, storage = ?storage, ephemeral, "building app webview");
+    let webview = WEB_CONTEXTS
+        .with_borrow_mut(|contexts| {
+            let context = contexts
+                .entry(storage.clone())
+                .or_insert_with(|| WebContext::new(storage.clone()));
+            let builder = WebViewBuilder::new_with_web_context(context)
+                .with_id(id)
+                .with_bounds(bounds)
+                .with_initialization_script(init_script)
+                .with_devtools(dev_profile || should_enable_devtools(state))
+                .with_incognito(ephemeral)
+                .with_asynchronous_custom_protocol("app".into(), protocol)
+                .with_url(start_url)
+                .with_navigation_handler(navigation_handler)
+                .with_ipc_handler(move |req: wry::http::Request<String>| {
+                    let _ = proxy.send_event(UserEvent::Ipc {
+                        webview_id: webview_id.clone(),
+                        msg: req.body().clone(),
+                    });
+                });
+            // Dapps may take file drops themselves; only the client's own
+            // pages open dropped bundles.
+            let builder = if dist_dir.is_none() {
+                builder.with_drag_drop_handler(bundle_drop_handler(drop_proxy))
+            } else {
+                builder
+            };
+            #[cfg(target_os = "macos")]
+            let builder = match &storage {
+                Some(dir) => {
+                    builder.with_data_store_identifier(crate::storage::data_store_identifier(dir))
+                }
+                None => builder,
+            };
+            host.build_app_child(builder)
+        })
         .context("failed to build app webview")?;
     tracing::debug!(id, "app webview built");
 
-    // Emit initial chain/accounts state after load (skip for selector and settings tabs).
-    if embedded != EmbeddedContent::WalletSelector && embedded != EmbeddedContent::Settings {
-        let addr = state.account();
-        let chain_hex = state.chain_id_hex();
-        {
-            let ws = state
-                .wallet
-                .lock()
-                .expect("poisoned wallet lock while emitting initial account state");
-            if ws.authorized {
-                if let Some(addr) = addr {
-                    emit_accounts_changed(&webview, vec![addr]);
-                }
-            }
+    // Emit initial chain/accounts state after load (skip for internal surfaces).
+    if !matches!(
+        embedded,
+        EmbeddedContent::WalletSelector
+            | EmbeddedContent::Settings
+            | EmbeddedContent::Prompt
+            | EmbeddedContent::Palette
+    ) {
+        if let Some(addr) = state.account_for(id) {
+            emit_accounts_changed(&webview, vec![addr]);
         }
-        emit_chain_changed(&webview, chain_hex);
+        emit_chain_changed(&webview, state.chain_id_hex());
     }
 
     Ok(webview)
 }
 
+/// Hands a bundle directory or zip dropped onto the window to the event loop.
+/// Other drops fall through to the page.
+fn bundle_drop_handler(
+    proxy: tao::event_loop::EventLoopProxy<UserEvent>,
+) -> impl Fn(wry::DragDropEvent) -> bool + 'static {
+    move |event| {
+        let wry::DragDropEvent::Drop { paths, .. } = event else {
+            return false;
+        };
+        let Some(path) = paths
+            .into_iter()
+            .find(|path| crate::local_bundle::is_bundle_path(path))
+        else {
+            return false;
+        };
+        let _ = proxy.send_event(UserEvent::BundleDropped { path });
+        true
+    }
+}
+
 pub fn build_tab_bar_webview(
     host: &WebViewHost,
     proxy: tao::event_loop::EventLoopProxy<UserEvent>,
@@ -329,6 +893,7 @@ pub fn build_tab_bar_webview(
 ) -> Result<WebView> {
     tracing::debug!(?bounds, "build_tab_bar_webview");
 
+    let response_policy = ResponsePolicy::strict();
     let protocol = move |_webview_id: wry::WebViewId, request: wry::http::Request<Vec<u8>>| {
         tracing::trace!(
             "tabbar protocol handler: method={} uri={}",
@@ -359,9 +924,10 @@ pub fn build_tab_bar_webview(
                 )
             }
         };
-        csp_response(body, mime, CspProfile::Strict)
+        csp_response(body, mime, &response_policy)
     };
 
+    let drop_proxy = proxy.clone();
     let builder = WebViewBuilder::new()
         .with_id("tab-bar")
         .with_bounds(bounds)
@@ -369,6 +935,7 @@ pub fn build_tab_bar_webview(
         .with_devtools(enable_devtools)
         .with_custom_protocol("app".into(), protocol)
         .with_url("app://tabbar.html")
+        .with_drag_drop_handler(bundle_drop_handler(drop_proxy))
         .with_ipc_handler(move |req: wry::http::Request<String>| {
             let _ = proxy.send_event(UserEvent::Ipc {
                 webview_id: "tab-bar".to_string(),
@@ -392,7 +959,15 @@ pub fn build_tab_bar_webview(
 
 #[cfg(test)]
 mod tests {
-    use super::allow_navigation;
+    use super::{
+        ByteRange, MAX_RANGE_CHUNK_BYTES, ResponsePolicy, TabSnapshot, VerifiedAssets,
+        allow_navigation, byte_range, permissions_policy_header, restore_url, serve_file,
+    };
+    use crate::bundle::sha256_hex;
+    use crate::dist_cache::DistCache;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::Arc;
 
     #[test]
     fn allows_internal_navigation_origins() {
@@ -418,4 +993,218 @@ mod tests {
         assert!(!allow_navigation("https://app.localhost:8443/index.html"));
         assert!(!allow_navigation("not-a-url"));
     }
+
+    /// A dist dir of its own under `name` holding `index.html`, and assets
+    /// expecting its hash.
+    fn verified_index(name: &str) -> (PathBuf, VerifiedAssets) {
+        let dist = std::env::temp_dir().join(format!(
+            "vibefi-verified-assets-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dist).unwrap();
+        std::fs::write(dist.join("index.html"), b"<html>ok</html>").unwrap();
+        let assets = VerifiedAssets {
+            expected: HashMap::from([("index.html".to_string(), sha256_hex(b"<html>ok</html>"))]),
+            cache: Arc::new(DistCache::new(1024)),
+        };
+        (dist, assets)
+    }
+
+    #[test]
+    fn verified_assets_serve_matching_files() {
+        let (dist, assets) = verified_index("match");
+        assert_eq!(
+            assets
+                .read(&dist, &dist.join("index.html"))
+                .unwrap()
+                .as_deref(),
+            Some(&b"<html>ok</html>"[..])
+        );
+        let _ = std::fs::remove_dir_all(&dist);
+    }
+
+    #[test]
+    fn verified_assets_keep_serving_the_verified_copy() {
+        let (dist, assets) = verified_index("cached");
+        let file = dist.join("index.html");
+        assets.read(&dist, &file).unwrap();
+        // Once verified, the cached copy is served even if the file changes on disk.
+        std::fs::write(&file, b"<html>evil</html>").unwrap();
+        assert_eq!(
+            assets.read(&dist, &file).unwrap().as_deref(),
+            Some(&b"<html>ok</html>"[..])
+        );
+        let _ = std::fs::remove_dir_all(&dist);
+    }
+
+    #[test]
+    fn verified_assets_refuse_tampered_files() {
+        let (dist, assets) = verified_index("tampered");
+        let file = dist.join("index.html");
+        std::fs::write(&file, b"<html>evil</html>").unwrap();
+        assert_eq!(assets.read(&dist, &file).unwrap(), None);
+        let _ = std::fs::remove_dir_all(&dist);
+    }
+
+    #[test]
+    fn verified_assets_refuse_files_without_an_expected_hash() {
+        let (dist, assets) = verified_index("extra");
+        let extra = dist.join("extra.js");
+        std::fs::write(&extra, b"alert(1)").unwrap();
+        assert_eq!(assets.read(&dist, &extra).unwrap(), None);
+        let _ = std::fs::remove_dir_all(&dist);
+    }
+
+    fn part(start: u64, end: u64) -> ByteRange {
+        ByteRange::Part { start, end }
+    }
+
+    #[test]
+    fn parses_single_byte_ranges() {
+        assert_eq!(byte_range(None, 10), ByteRange::Whole);
+        assert_eq!(byte_range(Some("bytes=2-5"), 10), part(2, 5));
+        assert_eq!(byte_range(Some("bytes=2-50"), 10), part(2, 9));
+        assert_eq!(byte_range(Some("bytes=-3"), 10), part(7, 9));
+    }
+
+    #[test]
+    fn serves_the_whole_file_for_ranges_it_does_not_handle() {
+        assert_eq!(byte_range(Some("bytes=0-1, 4-5"), 10), ByteRange::Whole);
+        assert_eq!(byte_range(Some("items=0-1"), 10), ByteRange::Whole);
+    }
+
+    #[test]
+    fn ranges_past_the_end_are_unsatisfiable() {
+        assert_eq!(byte_range(Some("bytes=10-"), 10), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn open_ended_ranges_of_large_files_come_back_a_chunk_at_a_time() {
+        assert_eq!(
+            byte_range(Some("bytes=0-"), 3 * MAX_RANGE_CHUNK_BYTES),
+            part(0, MAX_RANGE_CHUNK_BYTES - 1)
+        );
+    }
+
+    const VIDEO: &[u8] = b"0123456789abcdef";
+
+    /// A dist dir of its own under `name` holding a video too large for the
+    /// assets' cache, so ranges are read from disk.
+    fn uncached_video(name: &str) -> (PathBuf, VerifiedAssets) {
+        let dist =
+            std::env::temp_dir().join(format!("vibefi-ranges-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dist).unwrap();
+        std::fs::write(dist.join("video.mp4"), VIDEO).unwrap();
+        let assets = VerifiedAssets {
+            expected: HashMap::from([("video.mp4".to_string(), sha256_hex(VIDEO))]),
+            cache: Arc::new(DistCache::new(8)),
+        };
+        (dist, assets)
+    }
+
+    #[test]
+    fn serves_byte_ranges_of_verified_files() {
+        let (dist, assets) = uncached_video("partial");
+        let served = serve_file(&dist, "/video.mp4", Some("bytes=4-7"), &assets);
+        assert_eq!((served.status, &served.body[..]), (206, &b"4567"[..]));
+        assert_eq!(served.content_range.as_deref(), Some("bytes 4-7/16"));
+        assert_eq!(served.mime, "video/mp4");
+        let served = serve_file(&dist, "/video.mp4", Some("bytes=10-"), &assets);
+        assert_eq!(&served.body[..], b"abcdef");
+        let _ = std::fs::remove_dir_all(&dist);
+    }
+
+    #[test]
+    fn answers_unsatisfiable_ranges_with_416() {
+        let (dist, assets) = uncached_video("unsatisfiable");
+        let served = serve_file(&dist, "/video.mp4", Some("bytes=16-"), &assets);
+        assert_eq!(served.status, 416);
+        assert_eq!(served.content_range.as_deref(), Some("bytes */16"));
+        let _ = std::fs::remove_dir_all(&dist);
+    }
+
+    #[test]
+    fn serves_whole_verified_files_without_a_range() {
+        let (dist, assets) = uncached_video("whole");
+        let served = serve_file(&dist, "/video.mp4", None, &assets);
+        assert_eq!((served.status, &served.body[..]), (200, VIDEO));
+        let _ = std::fs::remove_dir_all(&dist);
+    }
+
+    #[test]
+    fn refuses_ranges_of_files_changed_in_place() {
+        let (dist, assets) = uncached_video("changed");
+        serve_file(&dist, "/video.mp4", None, &assets);
+        // Same size, so the remembered hash may still be current; the bytes
+        // in the range are checked anyway.
+        std::fs::write(dist.join("video.mp4"), b"0123456789abcdeX").unwrap();
+        let served = serve_file(&dist, "/video.mp4", Some("bytes=10-"), &assets);
+        assert_eq!(served.status, 403);
+        let _ = std::fs::remove_dir_all(&dist);
+    }
+
+    #[test]
+    fn refuses_ranges_of_truncated_files() {
+        let (dist, assets) = uncached_video("truncated");
+        serve_file(&dist, "/video.mp4", None, &assets);
+        std::fs::write(dist.join("video.mp4"), b"0123456789evil").unwrap();
+        let served = serve_file(&dist, "/video.mp4", Some("bytes=0-3"), &assets);
+        assert_eq!(served.status, 403);
+        let _ = std::fs::remove_dir_all(&dist);
+    }
+
+    #[test]
+    fn permissions_policy_denies_hardware_and_grants_opt_ins() {
+        let header = permissions_policy_header(&["clipboard-write".to_string()]);
+        assert!(header.contains("usb=()"));
+        assert!(header.contains("serial=()"));
+        assert!(header.contains("payment=()"));
+        assert!(header.contains("camera=()"));
+        assert!(header.contains("clipboard-write=(self)"));
+    }
+
+    #[test]
+    fn permissions_policy_never_grants_hardware() {
+        let header = permissions_policy_header(&["usb".to_string()]);
+        assert!(header.contains("usb=()"));
+        assert!(!header.contains("(self)"));
+    }
+
+    #[test]
+    fn restore_url_only_resumes_internal_pages() {
+        let snapshot = |url: &str| TabSnapshot {
+            url: url.to_string(),
+            ..TabSnapshot::default()
+        };
+        assert_eq!(restore_url(None), "app://index.html");
+        assert_eq!(
+            restore_url(Some(&snapshot("app://index.html/swap"))),
+            "app://index.html/swap"
+        );
+        assert_eq!(
+            restore_url(Some(&snapshot("https://evil.tld/"))),
+            "app://index.html"
+        );
+        assert_eq!(
+            restore_url(Some(&snapshot("about:blank"))),
+            "app://index.html"
+        );
+    }
+
+    #[test]
+    fn local_connect_only_relaxes_connect_src() {
+        let mut policy = ResponsePolicy::strict();
+        let strict = policy.content_security_policy();
+        assert!(strict.contains("connect-src 'none'"));
+
+        policy.local_connect = true;
+        let relaxed = policy.content_security_policy();
+        assert!(!relaxed.contains("connect-src 'none'"));
+        assert!(relaxed.contains("http://localhost:*"));
+        assert!(relaxed.contains("ws://127.0.0.1:*"));
+        assert_eq!(
+            relaxed.replace(super::LOCAL_CONNECT_SRC, "connect-src 'none'"),
+            strict
+        );
+    }
 }
@@ -1,5 +1,19 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tao::event_loop::EventLoopProxy;
 use wry::{Rect, WebView, dpi::PhysicalPosition, dpi::PhysicalSize};
 
+use crate::commands::PALETTE_WEBVIEW_ID;
+use crate::prompt::{PROMPT_WEBVIEW_ID, PendingPrompt};
+use crate::settings::HeavyTabSettings;
+use crate::state::{
+    DappIdentity, TabAction, TabBadge, TabBadges, TabWallets, UserEvent, WalletBackend,
+};
+use crate::tab_usage::{TAB_USAGE_JS, TabUsage};
+use crate::walletconnect::RelayStatus;
+use crate::webview::{TAB_SNAPSHOT_JS, TabSnapshot, WebViewSource};
+
 /// On macOS, bring a child webview to the front of the window's view hierarchy.
 /// Walk up from the WKWebView until we find a view whose superview is the
 /// window's contentView, then remove+re-add that view so it becomes the
@@ -39,6 +53,36 @@ fn bring_webview_to_front(webview: &WebView) {
 #[cfg(not(target_os = "macos"))]
 fn bring_webview_to_front(_webview: &WebView) {}
 
+/// How often the idle-tab monitor re-reads settings and looks for tabs to suspend.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Periodically asks the event loop to suspend idle background tabs while
+/// `suspendInactiveTabsAfterSecs` is set. Settings are re-read on every tick so
+/// changes apply without a restart.
+pub fn spawn_idle_tab_monitor(proxy: EventLoopProxy<UserEvent>, config_path: Option<PathBuf>) {
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(IDLE_CHECK_INTERVAL);
+            let Some(secs) = config_path.as_deref().and_then(|path| {
+                crate::settings::load_settings(path).suspend_inactive_tabs_after_secs
+            }) else {
+                continue;
+            };
+            let event = UserEvent::SuspendIdleTabs {
+                idle_for: Duration::from_secs(secs),
+            };
+            if proxy.send_event(event).is_err() {
+                // Event loop has shut down.
+                return;
+            }
+        }
+    });
+}
+
+/// Usage samples a live tab may leave unanswered in a row before it is
+/// badged as crashed.
+const CRASHED_AFTER_UNANSWERED_SAMPLES: u32 = 3;
+
 /// Logical tab bar height in points. Must be scaled by the window's scale factor
 /// to get the physical pixel height used in `Rect` bounds.
 pub const TAB_BAR_HEIGHT_LOGICAL: f64 = 40.0;
@@ -56,33 +100,92 @@ impl AppWebViewKind {
     pub fn is_closeable(self) -> bool {
         !matches!(self, Self::Launcher | Self::Studio)
     }
+
+    /// Dapp tabs can drop their webview while in the background; internal
+    /// surfaces hold in-flight UI state (pairing, forms) and always stay alive.
+    pub fn is_suspendable(self) -> bool {
+        matches!(self, Self::Standard | Self::Studio)
+    }
+
+    /// Tabs that talk to a wallet and so get their own backend binding.
+    pub fn binds_wallet(self) -> bool {
+        matches!(self, Self::Standard | Self::Studio)
+    }
 }
 
 pub struct AppWebViewEntry {
-    pub webview: WebView,
+    /// `None` until the tab is first activated, or while it is suspended.
+    pub webview: Option<WebView>,
+    /// How to rebuild the webview; `None` for placeholders that cannot be built yet.
+    pub source: Option<WebViewSource>,
+    /// Scroll/URL state captured when the tab was suspended.
+    pub snapshot: Option<TabSnapshot>,
+    /// When the tab was last shown or hidden.
+    pub last_active: Instant,
     pub id: String,
     pub label: String,
     pub kind: AppWebViewKind,
     pub selectable: bool,
     pub loading: bool,
+    /// Latest resource usage sample; unset until the first one, and while suspended.
+    pub usage: Option<TabUsage>,
+    /// Usage samples requested since the page last answered one.
+    pub unanswered_samples: u32,
+    /// Status the tab bar shows on the tab; see [`WebViewManager::set_badge`].
+    pub badges: TabBadges,
+    /// Set for dapp tabs; internal surfaces (launcher, settings, ...) have none.
+    pub identity: Option<DappIdentity>,
+}
+
+impl AppWebViewEntry {
+    /// Whether the tab has a source but no live webview, i.e. needs building on activation.
+    pub fn is_suspended(&self) -> bool {
+        self.webview.is_none() && self.source.is_some()
+    }
 }
 
 pub struct WebViewManager {
     pub tab_bar: Option<WebView>,
     pub apps: Vec<AppWebViewEntry>,
     pub active_app_index: Option<usize>,
+    /// Modal prompt overlay; while it is open the active tab stays hidden.
+    pub prompt: Option<WebView>,
+    /// The prompt on screen first, then the ones waiting behind it.
+    pub prompts: VecDeque<PendingPrompt>,
+    /// Command palette overlay; hides the active tab like the prompt does.
+    pub palette: Option<WebView>,
     next_id: u64,
     scale_factor: f64,
+    proxy: EventLoopProxy<UserEvent>,
+    tab_wallets: TabWallets,
+    /// Last reported WalletConnect relay state, shown on WalletConnect tabs.
+    pub walletconnect_relay: Option<RelayStatus>,
+    /// Limits tabs are flagged heavy over; unset while the warning is off.
+    pub heavy_tab: Option<HeavyTabSettings>,
+    /// Shown at the end of the tab bar in a `--fork` session.
+    pub fork_label: Option<String>,
 }
 
 impl WebViewManager {
-    pub fn new(scale_factor: f64) -> Self {
+    pub fn new(
+        scale_factor: f64,
+        proxy: EventLoopProxy<UserEvent>,
+        tab_wallets: TabWallets,
+    ) -> Self {
         Self {
             tab_bar: None,
             apps: Vec::new(),
             active_app_index: None,
+            prompt: None,
+            prompts: VecDeque::new(),
+            palette: None,
             next_id: 0,
             scale_factor,
+            proxy,
+            tab_wallets,
+            walletconnect_relay: None,
+            heavy_tab: None,
+            fork_label: None,
         }
     }
 
@@ -104,13 +207,22 @@ impl WebViewManager {
         if id == "tab-bar" {
             return self.tab_bar.as_ref();
         }
-        self.apps.iter().find(|e| e.id == id).map(|e| &e.webview)
+        if id == PROMPT_WEBVIEW_ID {
+            return self.prompt.as_ref();
+        }
+        if id == PALETTE_WEBVIEW_ID {
+            return self.palette.as_ref();
+        }
+        self.apps
+            .iter()
+            .find(|e| e.id == id)
+            .and_then(|e| e.webview.as_ref())
     }
 
     pub fn active_app_webview(&self) -> Option<&WebView> {
         self.active_app_index
             .and_then(|i| self.apps.get(i))
-            .map(|e| &e.webview)
+            .and_then(|e| e.webview.as_ref())
     }
 
     pub fn index_of_id(&self, id: &str) -> Option<usize> {
@@ -130,18 +242,35 @@ impl WebViewManager {
             tracing::debug!(index, "switch_to ignored for non-selectable tab");
             return;
         }
-        if let Some(old) = self.active_app_index {
-            if old < self.apps.len() {
-                if let Err(err) = self.apps[old].webview.set_visible(false) {
-                    tracing::warn!(index = old, error = %err, "failed to hide previous webview");
-                }
-            }
-        }
-        if let Err(err) = self.apps[index].webview.set_visible(true) {
+        // Picking a tab, from the tab bar or the palette, dismisses the palette.
+        self.palette = None;
+        let Some(target) = self.apps[index].webview.as_ref() else {
+            // Built on the event loop where a WebViewHost is available; the
+            // activation handler calls back into switch_to once it exists.
+            let _ = self
+                .proxy
+                .send_event(UserEvent::TabAction(TabAction::Activate {
+                    id: self.apps[index].id.clone(),
+                }));
+            return;
+        };
+        // Behind an open prompt the new tab is revealed once the prompt closes.
+        if let Err(err) = target.set_visible(self.prompt.is_none()) {
             tracing::warn!(index, error = %err, "failed to show target webview");
         }
         #[cfg(target_os = "macos")]
-        bring_webview_to_front(&self.apps[index].webview);
+        bring_webview_to_front(target);
+        if let Some(old) = self.active_app_index.filter(|old| *old != index) {
+            if let Some(entry) = self.apps.get_mut(old) {
+                if let Some(webview) = &entry.webview {
+                    if let Err(err) = webview.set_visible(false) {
+                        tracing::warn!(index = old, error = %err, "failed to hide previous webview");
+                    }
+                }
+                entry.last_active = Instant::now();
+            }
+        }
+        self.apps[index].last_active = Instant::now();
         self.active_app_index = Some(index);
         tracing::debug!(index, "switched active webview");
         self.update_tab_bar();
@@ -187,13 +316,184 @@ impl WebViewManager {
         };
         self.active_app_index = new_active;
         if let Some(i) = new_active {
-            if let Err(err) = self.apps[i].webview.set_visible(true) {
-                tracing::warn!(index = i, error = %err, "failed to show active webview after close");
+            match &self.apps[i].webview {
+                Some(webview) => {
+                    if let Err(err) = webview.set_visible(self.prompt.is_none()) {
+                        tracing::warn!(index = i, error = %err, "failed to show active webview after close");
+                    }
+                    self.apps[i].last_active = Instant::now();
+                }
+                None => {
+                    let _ = self
+                        .proxy
+                        .send_event(UserEvent::TabAction(TabAction::Activate {
+                            id: self.apps[i].id.clone(),
+                        }));
+                }
             }
         }
         self.update_tab_bar();
     }
 
+    /// Asks every background dapp tab idle for at least `idle_for` for a
+    /// snapshot; [`Self::suspend`] drops the webview once it arrives.
+    pub fn request_idle_snapshots(&self, idle_for: Duration, busy: impl Fn(&str) -> bool) {
+        for (index, entry) in self.apps.iter().enumerate() {
+            if Some(index) == self.active_app_index
+                || !entry.kind.is_suspendable()
+                || entry.source.is_none()
+                || entry.last_active.elapsed() < idle_for
+                || busy(&entry.id)
+            {
+                continue;
+            }
+            let Some(webview) = &entry.webview else {
+                continue;
+            };
+            let proxy = self.proxy.clone();
+            let webview_id = entry.id.clone();
+            let result = webview.evaluate_script_with_callback(TAB_SNAPSHOT_JS, move |snapshot| {
+                let _ = proxy.send_event(UserEvent::TabSnapshot {
+                    webview_id: webview_id.clone(),
+                    snapshot,
+                });
+            });
+            if let Err(err) = result {
+                tracing::warn!(id = %entry.id, error = %err, "failed to snapshot idle tab");
+            }
+        }
+    }
+
+    /// Drops the webview of a background tab, keeping `snapshot` to restore from.
+    /// Ignored if the tab became active while the snapshot was in flight.
+    pub fn suspend(&mut self, id: &str, snapshot: TabSnapshot) {
+        let Some(index) = self.index_of_id(id) else {
+            return;
+        };
+        if Some(index) == self.active_app_index {
+            return;
+        }
+        let entry = &mut self.apps[index];
+        if entry.webview.take().is_none() {
+            return;
+        }
+        tracing::info!(id, url = %snapshot.url, "suspended idle tab");
+        entry.snapshot = Some(snapshot);
+        entry.usage = None;
+        entry.unanswered_samples = 0;
+        entry.badges.set(TabBadge::Crashed(false));
+        self.update_tab_bar();
+    }
+
+    /// Asks every live tab for a [`TabUsage`] sample; [`Self::record_usage`]
+    /// stores it once it arrives. A tab that left the last few unanswered is
+    /// badged as crashed until it answers again.
+    pub fn request_usage_samples(&mut self, heavy_tab: Option<HeavyTabSettings>) {
+        self.heavy_tab = heavy_tab;
+        let mut crashed = Vec::new();
+        for entry in &mut self.apps {
+            let Some(webview) = &entry.webview else {
+                continue;
+            };
+            entry.unanswered_samples += 1;
+            if entry.unanswered_samples > CRASHED_AFTER_UNANSWERED_SAMPLES && !entry.badges.crashed
+            {
+                crashed.push(entry.id.clone());
+            }
+            let proxy = self.proxy.clone();
+            let webview_id = entry.id.clone();
+            let result = webview.evaluate_script_with_callback(TAB_USAGE_JS, move |sample| {
+                let _ = proxy.send_event(UserEvent::TabUsage {
+                    webview_id: webview_id.clone(),
+                    sample,
+                });
+            });
+            if let Err(err) = result {
+                tracing::debug!(id = %entry.id, error = %err, "failed to sample tab usage");
+            }
+        }
+        for id in crashed {
+            tracing::warn!(id, "tab stopped answering; marking it crashed");
+            self.set_badge(&id, TabBadge::Crashed(true));
+        }
+    }
+
+    /// Stores a tab's usage sample and clears its crashed badge. The tab bar
+    /// shows usage of heavy tabs only, so it is refreshed while the tab is
+    /// heavy and when that changes.
+    pub fn record_usage(&mut self, id: &str, sample: &str) {
+        let heavy_tab = self.heavy_tab;
+        let Some(entry) = self.apps.iter_mut().find(|e| e.id == id) else {
+            return;
+        };
+        if entry.webview.is_none() {
+            return;
+        }
+        entry.unanswered_samples = 0;
+        let recovered = entry.badges.set(TabBadge::Crashed(false));
+        if recovered {
+            tracing::info!(id, "tab is answering again");
+        }
+        let usage = match TabUsage::from_sample(sample, entry.usage.as_ref(), heavy_tab.as_ref()) {
+            Ok(usage) => usage,
+            Err(err) => {
+                tracing::debug!(id, error = %err, "discarding unreadable tab usage sample");
+                if recovered {
+                    self.update_tab_bar();
+                }
+                return;
+            }
+        };
+        let was_heavy = entry.usage.is_some_and(|usage| usage.heavy);
+        entry.usage = Some(usage);
+        if usage.heavy && !was_heavy {
+            tracing::info!(
+                id,
+                label = %entry.label,
+                heap_used = ?usage.heap_used,
+                cpu_percent = ?usage.cpu_percent,
+                "tab is over the heavy-tab limits"
+            );
+        }
+        if usage.heavy || was_heavy || recovered {
+            self.update_tab_bar();
+        }
+    }
+
+    /// Sets one of a tab's badges and refreshes the tab bar if it changed.
+    pub fn set_badge(&mut self, id: &str, badge: TabBadge) {
+        let Some(entry) = self.apps.iter_mut().find(|e| e.id == id) else {
+            return;
+        };
+        if entry.badges.set(badge) {
+            self.update_tab_bar();
+        }
+    }
+
+    /// Badges dapp tabs whose bundle is older than the registry's latest.
+    /// `latest` maps [`crate::registry::dapp_key`] to the latest root CID.
+    pub fn mark_dapp_updates(&mut self, latest: &HashMap<String, String>) {
+        let mut changed = false;
+        for entry in &mut self.apps {
+            let Some(identity) = entry.identity.as_ref() else {
+                continue;
+            };
+            let (Some(dapp_id), Some(root_cid)) = (&identity.dapp_id, &identity.root_cid) else {
+                continue;
+            };
+            let key = crate::registry::dapp_key(identity.registry_address.as_deref(), dapp_id);
+            let Some(latest_cid) = latest.get(&key) else {
+                continue;
+            };
+            changed |= entry
+                .badges
+                .set(TabBadge::UpdateAvailable(latest_cid != root_cid));
+        }
+        if changed {
+            self.update_tab_bar();
+        }
+    }
+
     pub fn index_of_kind(&self, kind: AppWebViewKind) -> Option<usize> {
         self.apps.iter().position(|e| e.kind == kind)
     }
@@ -202,6 +502,78 @@ impl WebViewManager {
         self.apps.iter().find(|e| e.id == id).map(|e| e.kind)
     }
 
+    /// Storage partitions of tabs whose webview is currently built.
+    pub fn open_storage_partitions(&self) -> Vec<PathBuf> {
+        self.apps
+            .iter()
+            .filter(|e| e.webview.is_some())
+            .filter_map(|e| e.source.as_ref()?.storage.clone())
+            .collect()
+    }
+
+    /// Identity to attribute requests from `id` to, falling back to the tab label.
+    pub fn dapp_identity_for(&self, id: &str) -> DappIdentity {
+        let Some(entry) = self.apps.iter().find(|e| e.id == id) else {
+            return DappIdentity {
+                name: id.to_string(),
+                ..DappIdentity::default()
+            };
+        };
+        entry.identity.clone().unwrap_or_else(|| DappIdentity {
+            name: entry.label.clone(),
+            ..DappIdentity::default()
+        })
+    }
+
+    /// Shows `webview` as the modal prompt overlay, hiding the active tab.
+    pub fn show_prompt(&mut self, webview: WebView) {
+        if let Some(active) = self.active_app_webview() {
+            if let Err(err) = active.set_visible(false) {
+                tracing::warn!(error = %err, "failed to hide active webview behind prompt");
+            }
+        }
+        #[cfg(target_os = "macos")]
+        bring_webview_to_front(&webview);
+        self.prompt = Some(webview);
+    }
+
+    /// Drops the prompt overlay and shows the active tab again.
+    pub fn close_prompt(&mut self) {
+        if self.prompt.take().is_none() {
+            return;
+        }
+        if let Some(active) = self.active_app_webview() {
+            if let Err(err) = active.set_visible(true) {
+                tracing::warn!(error = %err, "failed to show active webview after prompt");
+            }
+        }
+    }
+
+    /// Shows `webview` as the command palette, hiding the active tab.
+    pub fn show_palette(&mut self, webview: WebView) {
+        if let Some(active) = self.active_app_webview() {
+            if let Err(err) = active.set_visible(false) {
+                tracing::warn!(error = %err, "failed to hide active webview behind palette");
+            }
+        }
+        #[cfg(target_os = "macos")]
+        bring_webview_to_front(&webview);
+        self.palette = Some(webview);
+    }
+
+    /// Drops the palette and shows the active tab again, unless a prompt
+    /// still covers it.
+    pub fn close_palette(&mut self) {
+        if self.palette.take().is_none() || self.prompt.is_some() {
+            return;
+        }
+        if let Some(active) = self.active_app_webview() {
+            if let Err(err) = active.set_visible(true) {
+                tracing::warn!(error = %err, "failed to show active webview after palette");
+            }
+        }
+    }
+
     pub fn close_by_kind(&mut self, kind: AppWebViewKind) {
         if let Some(idx) = self.index_of_kind(kind) {
             self.close_app(idx);
@@ -226,10 +598,23 @@ impl WebViewManager {
             size: PhysicalSize::new(phys_width, app_height).into(),
         };
         for entry in &self.apps {
-            if let Err(err) = entry.webview.set_bounds(app_rect) {
+            let Some(webview) = &entry.webview else {
+                continue;
+            };
+            if let Err(err) = webview.set_bounds(app_rect) {
                 tracing::warn!(id = %entry.id, error = %err, "failed to set app webview bounds");
             }
         }
+        if let Some(prompt) = &self.prompt {
+            if let Err(err) = prompt.set_bounds(app_rect) {
+                tracing::warn!(error = %err, "failed to set prompt bounds");
+            }
+        }
+        if let Some(palette) = &self.palette {
+            if let Err(err) = palette.set_bounds(app_rect) {
+                tracing::warn!(error = %err, "failed to set palette bounds");
+            }
+        }
     }
 
     pub fn update_tab_bar(&self) {
@@ -237,21 +622,35 @@ impl WebViewManager {
             Some(tb) => tb,
             None => return,
         };
+        let wallets = self.tab_wallets.lock().expect("tab_wallets");
         let tabs: Vec<serde_json::Value> = self
             .apps
             .iter()
             .map(|e| {
+                let wallet = wallets.get(&e.id);
+                let relay = wallet
+                    .and_then(|w| w.primary())
+                    .filter(|w| w.backend == WalletBackend::WalletConnect)
+                    .and(self.walletconnect_relay.as_ref());
                 serde_json::json!({
                     "id": e.id,
                     "label": e.label,
                     "closable": e.kind.is_closeable(),
                     "clickable": e.selectable,
                     "loading": e.loading,
+                    "suspended": e.is_suspended(),
+                    "wallet": wallet.and_then(|w| w.primary()),
+                    "walletAccounts": wallet.map_or(0, |w| w.accounts.len()),
+                    "walletSwitchable": e.kind.binds_wallet(),
+                    "walletRelay": relay,
+                    "heavy": e.usage.filter(|usage| usage.heavy),
+                    "badges": e.badges,
                 })
             })
             .collect();
+        drop(wallets);
         let active = self.active_app_index.unwrap_or(0);
-        if let Err(err) = crate::ui_bridge::update_tabs(tb, tabs, active) {
+        if let Err(err) = crate::ui_bridge::update_tabs(tb, tabs, active, self.fork_label.clone()) {
             tracing::warn!(error = %err, "failed to update tab bar");
         }
     }
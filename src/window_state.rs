@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use tao::{
+    dpi::{LogicalSize, PhysicalPosition},
+    monitor::MonitorHandle,
+    window::{Window, WindowBuilder},
+};
+
+const DEFAULT_WIDTH: f64 = 1280.0;
+const DEFAULT_HEIGHT: f64 = 720.0;
+const MIN_WIDTH: f64 = 640.0;
+const MIN_HEIGHT: f64 = 400.0;
+/// How much of the title bar must land on a monitor for a saved position to
+/// be reused, so the window can always be grabbed and moved.
+const MIN_VISIBLE_WIDTH: i64 = 100;
+const TITLE_BAR_HEIGHT: i64 = 32;
+
+/// Main window geometry, saved with the session on exit. The size is in
+/// logical pixels; the position is the physical outer position in desktop
+/// coordinates, as the window system reports it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowState {
+    pub width: f64,
+    pub height: f64,
+    pub x: i32,
+    pub y: i32,
+    #[serde(default)]
+    pub maximized: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<String>,
+}
+
+/// The part of a monitor layout the sanity checks look at.
+#[derive(Debug, Clone)]
+struct MonitorArea {
+    name: Option<String>,
+    x: i64,
+    y: i64,
+    width: i64,
+    height: i64,
+    scale_factor: f64,
+}
+
+impl From<&MonitorHandle> for MonitorArea {
+    fn from(monitor: &MonitorHandle) -> Self {
+        let position = monitor.position();
+        let size = monitor.size();
+        Self {
+            name: monitor.name(),
+            x: position.x.into(),
+            y: position.y.into(),
+            width: size.width.into(),
+            height: size.height.into(),
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+/// Reads the current geometry of `window`. While maximized the window
+/// reports the maximized bounds, so the normal size and position from
+/// `previous` are kept for when it is restored.
+pub fn capture(window: &Window, previous: Option<&WindowState>) -> Option<WindowState> {
+    let maximized = window.is_maximized();
+    let monitor = window.current_monitor().and_then(|monitor| monitor.name());
+    if maximized {
+        if let Some(previous) = previous {
+            return Some(WindowState {
+                maximized,
+                monitor: monitor.or_else(|| previous.monitor.clone()),
+                ..previous.clone()
+            });
+        }
+    }
+    let position = window.outer_position().ok()?;
+    let size: LogicalSize<f64> = window.inner_size().to_logical(window.scale_factor());
+    Some(WindowState {
+        width: size.width,
+        height: size.height,
+        x: position.x,
+        y: position.y,
+        maximized,
+        monitor,
+    })
+}
+
+/// Applies `saved` to `builder` after checking it against the monitors
+/// attached now. Without a usable saved state the window opens at the default
+/// size in the default position.
+pub fn restore(
+    builder: WindowBuilder,
+    saved: Option<&WindowState>,
+    monitors: impl IntoIterator<Item = MonitorHandle>,
+) -> WindowBuilder {
+    let Some(saved) = saved else {
+        return builder.with_inner_size(LogicalSize::new(DEFAULT_WIDTH, DEFAULT_HEIGHT));
+    };
+    let monitors: Vec<MonitorArea> = monitors
+        .into_iter()
+        .map(|m| MonitorArea::from(&m))
+        .collect();
+    let (width, height, position) = sanitize(saved, &monitors);
+    if position.is_none() {
+        tracing::info!(
+            monitor = saved.monitor.as_deref().unwrap_or("unknown"),
+            "saved window position is off screen; using the default position"
+        );
+    }
+    let mut builder = builder
+        .with_inner_size(LogicalSize::new(width, height))
+        .with_maximized(saved.maximized);
+    if let Some(position) = position {
+        builder = builder.with_position(position);
+    }
+    builder
+}
+
+/// Clamps the saved size to the monitor it lands on and drops the position
+/// when the monitor it was on is gone or the title bar would be off screen.
+fn sanitize(
+    saved: &WindowState,
+    monitors: &[MonitorArea],
+) -> (f64, f64, Option<PhysicalPosition<i32>>) {
+    let monitor_gone = saved.monitor.as_ref().is_some_and(|name| {
+        monitors.iter().all(|m| m.name.as_ref() != Some(name)) && !monitors.is_empty()
+    });
+    let target = monitors
+        .iter()
+        .filter(|m| title_bar_overlap(saved, m) >= MIN_VISIBLE_WIDTH)
+        .max_by_key(|m| title_bar_overlap(saved, m));
+    let position =
+        (!monitor_gone && target.is_some()).then(|| PhysicalPosition::new(saved.x, saved.y));
+
+    let mut width = saved.width.max(MIN_WIDTH);
+    let mut height = saved.height.max(MIN_HEIGHT);
+    if let Some(bounds) = target.or(monitors.first()) {
+        let max_width = bounds.width as f64 / bounds.scale_factor;
+        let max_height = bounds.height as f64 / bounds.scale_factor;
+        width = width.min(max_width.max(MIN_WIDTH));
+        height = height.min(max_height.max(MIN_HEIGHT));
+    }
+    (width, height, position)
+}
+
+/// Horizontal pixels of the window's title bar strip that fall on `monitor`.
+fn title_bar_overlap(saved: &WindowState, monitor: &MonitorArea) -> i64 {
+    let x = i64::from(saved.x);
+    let y = i64::from(saved.y);
+    let width = (saved.width * monitor.scale_factor) as i64;
+    let vertical = y + TITLE_BAR_HEIGHT > monitor.y && y < monitor.y + monitor.height;
+    if !vertical {
+        return 0;
+    }
+    (x + width).min(monitor.x + monitor.width) - x.max(monitor.x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MonitorArea, WindowState, sanitize};
+
+    fn monitor(name: &str, x: i64, width: i64) -> MonitorArea {
+        MonitorArea {
+            name: Some(name.to_string()),
+            x,
+            y: 0,
+            width,
+            height: 1080,
+            scale_factor: 1.0,
+        }
+    }
+
+    fn saved_on_external_monitor() -> WindowState {
+        WindowState {
+            width: 1600.0,
+            height: 900.0,
+            x: 2100,
+            y: 40,
+            maximized: false,
+            monitor: Some("DELL U2720Q".to_string()),
+        }
+    }
+
+    #[test]
+    fn saved_position_is_kept_on_the_same_monitor_layout() {
+        let both = [
+            monitor("Built-in", 0, 1920),
+            monitor("DELL U2720Q", 1920, 2560),
+        ];
+        let (width, height, position) = sanitize(&saved_on_external_monitor(), &both);
+        assert_eq!((width, height), (1600.0, 900.0));
+        assert_eq!(position.map(|p| (p.x, p.y)), Some((2100, 40)));
+    }
+
+    #[test]
+    fn saved_position_is_dropped_when_the_monitor_is_gone() {
+        // The external monitor was unplugged: open on the laptop screen, at
+        // most as large as it is.
+        let laptop = [monitor("Built-in", 0, 1440)];
+        let (width, _, position) = sanitize(&saved_on_external_monitor(), &laptop);
+        assert_eq!(width, 1440.0);
+        assert!(position.is_none());
+    }
+
+    #[test]
+    fn saved_position_is_dropped_when_the_window_is_off_screen() {
+        // Same monitor, but the window was left almost entirely off screen.
+        let stray = WindowState {
+            x: -1550,
+            monitor: Some("Built-in".to_string()),
+            ..saved_on_external_monitor()
+        };
+        let laptop = [monitor("Built-in", 0, 1440)];
+        assert!(sanitize(&stray, &laptop).2.is_none());
+    }
+}
@@ -41,11 +41,19 @@ class FileKeyValueStorage {
   }
 }
 
-const wcStoragePath = path.join(os.homedir(), ".vibefi", "walletconnect-store.json");
+// The client sets VIBEFI_WC_STORE_PATH for named profiles so their sessions stay apart.
+const wcStoragePath =
+  process.env.VIBEFI_WC_STORE_PATH || path.join(os.homedir(), ".vibefi", "walletconnect-store.json");
 const wcStorage = new FileKeyValueStorage(wcStoragePath);
 
 const projectId = process.env.VIBEFI_WC_PROJECT_ID || process.env.WC_PROJECT_ID || "";
-const relayUrl = process.env.VIBEFI_WC_RELAY_URL || process.env.WC_RELAY_URL || undefined;
+// Relays in the order they are tried. Empty uses the SDK's default relay.
+const relayUrls = (
+  process.env.VIBEFI_WC_RELAY_URLS || process.env.VIBEFI_WC_RELAY_URL || process.env.WC_RELAY_URL || ""
+)
+  .split(",")
+  .map((url) => url.trim())
+  .filter(Boolean);
 const metadataName = process.env.VIBEFI_WC_METADATA_NAME || "VibeFi Desktop";
 const metadataUrl = process.env.VIBEFI_WC_METADATA_URL || "https://vibefi.dev";
 const metadataDesc = process.env.VIBEFI_WC_METADATA_DESC || "VibeFi desktop WalletConnect bridge";
@@ -53,6 +61,9 @@ const metadataIcon = process.env.VIBEFI_WC_METADATA_ICON || "";
 const connectTimeoutMs = Number.parseInt(process.env.VIBEFI_WC_CONNECT_TIMEOUT_MS || "180000", 10);
 const requestTimeoutMs = Number.parseInt(process.env.VIBEFI_WC_REQUEST_TIMEOUT_MS || "30000", 10);
 const disconnectTimeoutMs = Number.parseInt(process.env.VIBEFI_WC_DISCONNECT_TIMEOUT_MS || "5000", 10);
+const relayTimeoutMs = Number.parseInt(process.env.VIBEFI_WC_RELAY_TIMEOUT_MS || "15000", 10);
+const RELAY_BACKOFF_MIN_MS = 1000;
+const RELAY_BACKOFF_MAX_MS = 30000;
 
 // The WalletConnect SDK throws unhandled exceptions for several internal issues:
 // - chainChanged fires before rpcProviders are populated (TypeError: setDefaultChain)
@@ -114,6 +125,9 @@ if (!projectId) {
 let provider = null;
 let connectedAccounts = [];
 let connectedChainIdHex = "0x1";
+let relayIndex = 0;
+let relayStatus = { relayUrl: currentRelayUrl() ?? null, state: "connecting", attempt: 0 };
+let reconnectTimer = null;
 
 function writeMessage(payload) {
   process.stdout.write(`${JSON.stringify(payload)}\n`);
@@ -173,6 +187,7 @@ async function resetProviderAndStorage(reason) {
 
   const oldProvider = provider;
   provider = null;
+  clearReconnectTimer();
   connectedAccounts = [];
   connectedChainIdHex = "0x1";
 
@@ -203,6 +218,73 @@ async function resetProviderAndStorage(reason) {
   }
 }
 
+function currentRelayUrl() {
+  return relayUrls.length > 0 ? relayUrls[relayIndex] : undefined;
+}
+
+function advanceRelay() {
+  if (relayUrls.length > 1) {
+    relayIndex = (relayIndex + 1) % relayUrls.length;
+  }
+}
+
+function setRelayStatus(state, attempt = 0) {
+  relayStatus = { relayUrl: currentRelayUrl() ?? null, state, attempt };
+  emitEvent("relay_status", { relay: relayStatus });
+}
+
+function clearReconnectTimer() {
+  if (reconnectTimer) {
+    clearTimeout(reconnectTimer);
+    reconnectTimer = null;
+  }
+}
+
+// The SDK retries its own relay after a drop; this backs off between attempts
+// and moves on to the next configured relay when one stays unreachable. The
+// session lives in storage, so it survives switching relays.
+function scheduleRelayReconnect(wc, attempt) {
+  if (reconnectTimer || provider !== wc) return;
+  const delay = Math.min(RELAY_BACKOFF_MIN_MS * 2 ** attempt, RELAY_BACKOFF_MAX_MS);
+  setRelayStatus("reconnecting", attempt + 1);
+  reconnectTimer = setTimeout(async () => {
+    reconnectTimer = null;
+    const relayer = wc.signer?.client?.core?.relayer;
+    if (provider !== wc || !relayer) return;
+    if (relayer.connected) {
+      setRelayStatus("connected");
+      return;
+    }
+    if (attempt > 0) advanceRelay();
+    setRelayStatus("reconnecting", attempt + 1);
+    try {
+      await withTimeout(relayer.restartTransport(currentRelayUrl()), relayTimeoutMs, "relay reconnect");
+      setRelayStatus("connected");
+    } catch (err) {
+      log(`relay ${currentRelayUrl() ?? "default"} reconnect failed: ${err?.message || err}`);
+      scheduleRelayReconnect(wc, attempt + 1);
+    }
+  }, delay);
+}
+
+function watchRelay(wc) {
+  const relayer = wc.signer?.client?.core?.relayer;
+  if (typeof relayer?.on !== "function") {
+    log("relayer events unavailable; relay failover disabled");
+    return;
+  }
+  relayer.on("relayer_connect", () => {
+    if (provider !== wc) return;
+    clearReconnectTimer();
+    if (relayStatus.state !== "connected") setRelayStatus("connected");
+  });
+  relayer.on("relayer_disconnect", () => {
+    if (provider !== wc) return;
+    log(`relay ${currentRelayUrl() ?? "default"} disconnected`);
+    scheduleRelayReconnect(wc, relayStatus.state === "reconnecting" ? relayStatus.attempt : 0);
+  });
+}
+
 function normalizeChainIdHex(value) {
   if (typeof value === "number") return `0x${value.toString(16)}`;
   if (typeof value === "string") {
@@ -242,10 +324,8 @@ function parseAccounts(result) {
     .filter((entry) => typeof entry === "string");
 }
 
-async function ensureProvider(chains) {
-  if (provider) return provider;
-  log(`init provider chains=${chains.join(",")}`);
-  provider = await EthereumProvider.init({
+function initProvider(chains, relayUrl) {
+  return EthereumProvider.init({
     projectId,
     chains,
     showQrModal: false,
@@ -258,6 +338,30 @@ async function ensureProvider(chains) {
       icons: metadataIcon ? [metadataIcon] : []
     }
   });
+}
+
+async function ensureProvider(chains) {
+  if (provider) return provider;
+  let lastError = null;
+  for (let tried = 0; tried < Math.max(relayUrls.length, 1); tried += 1) {
+    const relayUrl = currentRelayUrl();
+    log(`init provider chains=${chains.join(",")} relay=${relayUrl ?? "default"}`);
+    setRelayStatus("connecting");
+    try {
+      provider = await withTimeout(initProvider(chains, relayUrl), relayTimeoutMs, "relay connect");
+      break;
+    } catch (err) {
+      lastError = err;
+      log(`relay ${relayUrl ?? "default"} unreachable: ${err?.message || err}`);
+      advanceRelay();
+    }
+  }
+  if (!provider) {
+    setRelayStatus("failed");
+    throw lastError ?? new Error("no WalletConnect relay reachable");
+  }
+  setRelayStatus("connected");
+  watchRelay(provider);
 
   provider.on("display_uri", async (uri) => {
     log("display_uri received");
@@ -290,7 +394,7 @@ async function ensureProvider(chains) {
   return provider;
 }
 
-async function connect(requiredChainId) {
+async function connect(requiredChainId, restoreOnly = false) {
   const chains = uniqueNumbers([requiredChainId, 1, 11155111]);
   let attempt = 0;
   while (attempt < 2) {
@@ -301,6 +405,8 @@ async function connect(requiredChainId) {
       restoredSession = Boolean(wc.session);
       if (restoredSession) {
         log(`using restored session from storage (storedKeys=${storedKeysBefore})`);
+      } else if (restoreOnly) {
+        throw new Error("no stored WalletConnect session");
       } else {
         log(`connecting session chains=${chains.join(",")}`);
         await withTimeout(wc.connect({ chains }), connectTimeoutMs, "connect");
@@ -368,6 +474,9 @@ async function handleCommand(msg) {
   if (method === "ping") {
     return { id, result: { ok: true } };
   }
+  if (method === "relayStatus") {
+    return { id, result: provider ? relayStatus : null };
+  }
   if (method === "connect") {
     const chainId = params?.chainId;
     const required = typeof chainId === "string"
@@ -375,8 +484,9 @@ async function handleCommand(msg) {
       : typeof chainId === "number"
       ? chainId
       : undefined;
-    log(`command connect chainId=${required ?? "none"}`);
-    const result = await connect(required);
+    const restoreOnly = params?.restoreOnly === true;
+    log(`command connect chainId=${required ?? "none"} restoreOnly=${restoreOnly}`);
+    const result = await connect(required, restoreOnly);
     return { id, result };
   }
   if (method === "request") {
@@ -389,6 +499,7 @@ async function handleCommand(msg) {
     return { id, result };
   }
   if (method === "disconnect") {
+    clearReconnectTimer();
     if (provider) {
       await withTimeout(provider.disconnect(), disconnectTimeoutMs, "disconnect");
       try {